@@ -0,0 +1,272 @@
+use crate::core::command::{self, CommandRunner};
+use crate::error::{DotfilesError, Result};
+use crate::install::repos;
+use crate::symlink::{self, manual::ManualSymlinker};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Where bare-repo guides put the git directory, relative to `$HOME`
+pub const CANDIDATE_DIRS: &[&str] = &[".cfg", ".dotfiles", ".dotfiles.git", ".myconf", ".dotcfg"];
+
+/// A bare git repo whose work tree is `$HOME` (the `config` alias style)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BareRepo {
+    pub git_dir: PathBuf,
+    pub work_tree: PathBuf,
+}
+
+/// What converting a bare repo did (or would do, in a dry run)
+#[derive(Debug, Default)]
+pub struct Conversion {
+    /// Number of files the bare repo tracks
+    pub tracked: usize,
+    /// Top-level files in `$HOME` replaced with links into the new checkout
+    pub linked: Vec<PathBuf>,
+    /// Top-level directories left as they are, since they usually hold untracked files too
+    pub left_in_place: Vec<PathBuf>,
+}
+
+/// Finds a bare dotfiles repo in one of the usual places
+pub fn detect(home_dir: &Path) -> Option<BareRepo> {
+    CANDIDATE_DIRS.iter().find_map(|dir| {
+        let git_dir = home_dir.join(dir);
+        let config = fs::read_to_string(git_dir.join("config")).ok()?;
+        let bare = git_dir.join("HEAD").is_file()
+            && git_dir.join("objects").is_dir()
+            && config_value(&config, "bare").as_deref() == Some("true");
+        bare.then(|| BareRepo {
+            work_tree: config_value(&config, "worktree")
+                .map(|tree| git_dir.join(tree))
+                .unwrap_or_else(|| home_dir.to_path_buf()),
+            git_dir,
+        })
+    })
+}
+
+/// Reads a `[core]` value from a git config file
+fn config_value(config: &str, key: &str) -> Option<String> {
+    let mut in_core = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_core = line.eq_ignore_ascii_case("[core]");
+        } else if in_core {
+            if let Some((name, value)) = line.split_once('=') {
+                if name.trim().eq_ignore_ascii_case(key) {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+impl BareRepo {
+    /// The `git` invocation the `config` alias stands for
+    fn git(&self) -> CommandRunner {
+        CommandRunner::new("git")
+            .arg("--git-dir")
+            .arg(&self.git_dir)
+            .arg("--work-tree")
+            .arg(&self.work_tree)
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+    }
+
+    /// Returns the tracked paths, relative to the work tree
+    pub fn tracked(&self) -> Result<Vec<PathBuf>> {
+        let output = self.git().arg("ls-files").run()?;
+        Ok(output.stdout.lines().map(PathBuf::from).collect())
+    }
+
+    /// Returns tracked paths with uncommitted changes
+    pub fn modified(&self) -> Result<Vec<String>> {
+        let output = self
+            .git()
+            .args(["status", "--porcelain", "--untracked-files=no"])
+            .run()?;
+        Ok(output
+            .stdout
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// The shell command equivalent to the `config` alias, for messages
+    pub fn alias(&self) -> String {
+        format!(
+            "git --git-dir={} --work-tree={}",
+            self.git_dir.display(),
+            self.work_tree.display()
+        )
+    }
+}
+
+/// Turns a bare-repo setup into a regular checkout at `dotfiles_dir`
+///
+/// Tracked paths are already relative to `$HOME`, which is the flat layout
+/// this tool links, so a clone keeps both the layout and the history. The
+/// clone takes over the bare repo's `origin`. Top-level tracked files in
+/// `$HOME` are then replaced with links into the checkout; directories such
+/// as `~/.config` usually hold untracked files as well and are left alone.
+/// The bare repo itself is not touched.
+pub fn convert(repo: &BareRepo, dotfiles_dir: &Path, dry_run: bool) -> Result<Conversion> {
+    let modified = repo.modified()?;
+    if !modified.is_empty() {
+        return Err(DotfilesError::Config(format!(
+            "{} tracked file(s) have uncommitted changes ({}); commit them first: {} commit -a",
+            modified.len(),
+            modified.join(", "),
+            repo.alias()
+        )));
+    }
+    let occupied = fs::read_dir(dotfiles_dir)
+        .map(|mut entries| entries.next().is_some())
+        .unwrap_or(false);
+    if occupied {
+        return Err(DotfilesError::Config(format!(
+            "{} already exists and is not empty",
+            dotfiles_dir.display()
+        )));
+    }
+
+    let tracked = repo.tracked()?;
+    let mut conversion = Conversion {
+        tracked: tracked.len(),
+        ..Conversion::default()
+    };
+
+    if !dry_run {
+        CommandRunner::new("git")
+            .args(["clone", "-q"])
+            .arg(&repo.git_dir)
+            .arg(dotfiles_dir)
+            .timeout(command::NETWORK_TIMEOUT)
+            .stream(false)
+            .run()?;
+        if let Some(url) = repos::remote_url(&repo.git_dir) {
+            CommandRunner::new("git")
+                .arg("-C")
+                .arg(dotfiles_dir)
+                .args(["remote", "set-url", "origin", &url])
+                .timeout(command::QUICK_TIMEOUT)
+                .stream(false)
+                .run()?;
+        }
+    }
+
+    let symlinker = ManualSymlinker {
+        dry_run,
+        force: false,
+        skip: Vec::new(),
+        config_home: None,
+    };
+    for name in top_level(&tracked) {
+        let path = repo.work_tree.join(&name);
+        if path.is_symlink() || !path.exists() {
+            continue;
+        }
+        if path.is_dir() {
+            conversion.left_in_place.push(path);
+            continue;
+        }
+        if !dry_run {
+            fs::remove_file(&path)?;
+        }
+        symlinker.create_symlink(&dotfiles_dir.join(&name), &path)?;
+        conversion.linked.push(path);
+    }
+
+    Ok(conversion)
+}
+
+/// Distinct top-level entries among tracked paths, minus ones never linked
+fn top_level(tracked: &[PathBuf]) -> Vec<PathBuf> {
+    let names: BTreeSet<PathBuf> = tracked
+        .iter()
+        .filter_map(|path| match path.components().next()? {
+            Component::Normal(name) => Some(PathBuf::from(name)),
+            _ => None,
+        })
+        .filter(|name| !symlink::EXCLUSIONS.contains(&name.to_string_lossy().as_ref()))
+        .collect();
+    names.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(args: &[&str]) {
+        CommandRunner::new("git")
+            .args(args)
+            .stream(false)
+            .run()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_detect_and_convert() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let git_dir = home.join(".cfg");
+        fs::create_dir_all(home.join(".config/nvim")).unwrap();
+        fs::write(home.join(".zshrc"), "export EDITOR=nvim\n").unwrap();
+        fs::write(home.join(".config/nvim/init.lua"), "-- nvim\n").unwrap();
+        fs::write(home.join(".config/nvim/lazy-lock.json"), "{}\n").unwrap();
+        fs::write(home.join("README.md"), "# dotfiles\n").unwrap();
+
+        git(&["init", "-q", "--bare", git_dir.to_str().unwrap()]);
+        let repo = detect(&home).unwrap();
+        assert_eq!(repo.git_dir, git_dir);
+        assert_eq!(repo.work_tree, home);
+
+        let alias = |args: &[&str]| {
+            let mut full = vec![
+                "--git-dir",
+                git_dir.to_str().unwrap(),
+                "--work-tree",
+                home.to_str().unwrap(),
+                "-c",
+                "user.name=Test",
+                "-c",
+                "user.email=test@example.com",
+            ];
+            full.extend_from_slice(args);
+            git(&full);
+        };
+        alias(&["add", ".zshrc", ".config/nvim/init.lua", "README.md"]);
+        alias(&["commit", "-q", "-m", "init"]);
+        assert_eq!(repo.tracked().unwrap().len(), 3);
+
+        fs::write(home.join(".zshrc"), "export EDITOR=vim\n").unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        assert!(convert(&repo, &dotfiles, false).is_err());
+        fs::write(home.join(".zshrc"), "export EDITOR=nvim\n").unwrap();
+
+        let conversion = convert(&repo, &dotfiles, false).unwrap();
+        assert_eq!(conversion.tracked, 3);
+        assert_eq!(conversion.linked, vec![home.join(".zshrc")]);
+        assert_eq!(conversion.left_in_place, vec![home.join(".config")]);
+        assert_eq!(
+            fs::read_link(home.join(".zshrc")).unwrap(),
+            dotfiles.join(".zshrc")
+        );
+        assert_eq!(
+            fs::read_to_string(dotfiles.join(".config/nvim/init.lua")).unwrap(),
+            "-- nvim\n"
+        );
+        assert!(home.join("README.md").is_file());
+        assert!(!dotfiles.join(".config/nvim/lazy-lock.json").exists());
+    }
+
+    #[test]
+    fn test_config_value() {
+        let config = "[core]\n\trepositoryformatversion = 0\n\tbare = true\n\
+                      [remote \"origin\"]\n\tbare = false\n";
+        assert_eq!(config_value(config, "bare").as_deref(), Some("true"));
+        assert_eq!(config_value(config, "worktree"), None);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::backup::{copy_dir_recursive, is_record};
+use crate::core::audit::{self, Action};
+use crate::error::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a backed-up entry compares with what is on disk now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// Same content as the current file (or every file below a directory)
+    Unchanged,
+    /// Current content differs
+    Modified,
+    /// Exists only in the backup
+    Deleted,
+}
+
+impl EntryStatus {
+    /// One-character marker shown in the browser
+    pub fn marker(&self) -> &str {
+        match self {
+            EntryStatus::Unchanged => " ",
+            EntryStatus::Modified => "M",
+            EntryStatus::Deleted => "D",
+        }
+    }
+}
+
+/// A file or directory inside a backup, paired with its current location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseEntry {
+    pub name: String,
+    pub backup_path: PathBuf,
+    pub current_path: PathBuf,
+    pub is_dir: bool,
+    pub status: EntryStatus,
+}
+
+/// Lists one directory of a backup, directories first, compared with `current_dir`
+pub fn list_entries(backup_dir: &Path, current_dir: &Path) -> Result<Vec<BrowseEntry>> {
+    let mut entries: Vec<BrowseEntry> = fs::read_dir(backup_dir)?
+        .flatten()
+        .filter(|entry| !is_record(&entry.file_name()))
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let backup_path = entry.path();
+            let current_path = current_dir.join(&name);
+            BrowseEntry {
+                is_dir: backup_path.is_dir(),
+                status: compare(&backup_path, &current_path),
+                name,
+                backup_path,
+                current_path,
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// Compares a backed-up path with its current counterpart
+pub fn compare(backup_path: &Path, current_path: &Path) -> EntryStatus {
+    if !current_path.exists() {
+        return EntryStatus::Deleted;
+    }
+
+    if backup_path.is_dir() {
+        let Ok(children) = fs::read_dir(backup_path) else {
+            return EntryStatus::Modified;
+        };
+        let changed = children
+            .flatten()
+            .filter(|child| !is_record(&child.file_name()))
+            .any(|child| {
+                compare(&child.path(), &current_path.join(child.file_name()))
+                    != EntryStatus::Unchanged
+            });
+        return if changed {
+            EntryStatus::Modified
+        } else {
+            EntryStatus::Unchanged
+        };
+    }
+
+    match (fs::read(backup_path), fs::read(current_path)) {
+        (Ok(backup), Ok(current)) if backup == current => EntryStatus::Unchanged,
+        _ => EntryStatus::Modified,
+    }
+}
+
+/// Copies one backed-up file or directory over its current location
+///
+/// Directories are merged: files in the backup overwrite current ones, and
+/// files that only exist now are kept. A symlink at the current location is
+/// replaced, never written through, so whatever it points at (often a file in
+/// the dotfiles repo) stays as it is.
+pub fn restore_entry(entry: &BrowseEntry) -> Result<()> {
+    if let Some(parent) = entry.current_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if entry.current_path.is_symlink() {
+        fs::remove_file(&entry.current_path)?;
+    }
+
+    if entry.is_dir {
+        copy_dir_recursive(&entry.backup_path, &entry.current_path)?;
+    } else {
+        fs::copy(&entry.backup_path, &entry.current_path)?;
+    }
+    audit::record(Action::FileCopied {
+        from: entry.backup_path.clone(),
+        to: entry.current_path.clone(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::SOURCE_FILE;
+    use tempfile::TempDir;
+
+    fn fixture() -> (TempDir, PathBuf, PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let backup = temp.path().join("backup");
+        let current = temp.path().join("current");
+        fs::create_dir_all(backup.join("nvim")).unwrap();
+        fs::create_dir_all(current.join("nvim")).unwrap();
+        fs::write(backup.join(SOURCE_FILE), "/x").unwrap();
+        fs::write(backup.join("nvim/init.lua"), "old").unwrap();
+        fs::write(current.join("nvim/init.lua"), "new").unwrap();
+        fs::write(backup.join(".zshrc"), "same").unwrap();
+        fs::write(current.join(".zshrc"), "same").unwrap();
+        fs::write(backup.join(".psqlrc"), "gone").unwrap();
+        (temp, backup, current)
+    }
+
+    #[test]
+    fn test_list_entries() {
+        let (_temp, backup, current) = fixture();
+        let entries = list_entries(&backup, &current).unwrap();
+
+        let summary: Vec<(&str, EntryStatus)> = entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("nvim", EntryStatus::Modified),
+                (".psqlrc", EntryStatus::Deleted),
+                (".zshrc", EntryStatus::Unchanged),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_entry() {
+        let (_temp, backup, current) = fixture();
+        fs::write(current.join("nvim/extra.lua"), "kept").unwrap();
+
+        for entry in list_entries(&backup, &current).unwrap() {
+            restore_entry(&entry).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(current.join(".psqlrc")).unwrap(), "gone");
+        assert_eq!(
+            fs::read_to_string(current.join("nvim/init.lua")).unwrap(),
+            "old"
+        );
+        assert!(current.join("nvim/extra.lua").exists());
+        assert!(list_entries(&backup, &current)
+            .unwrap()
+            .iter()
+            .all(|e| e.status == EntryStatus::Unchanged));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_restore_entry_replaces_symlinks() {
+        let (temp, backup, current) = fixture();
+        let repo = temp.path().join("dotfiles");
+        fs::create_dir_all(repo.join("nvim")).unwrap();
+        fs::write(repo.join(".psqlrc"), "repo").unwrap();
+        fs::write(repo.join("nvim/init.lua"), "repo").unwrap();
+        fs::remove_dir_all(current.join("nvim")).unwrap();
+        std::os::unix::fs::symlink(repo.join(".psqlrc"), current.join(".psqlrc")).unwrap();
+        std::os::unix::fs::symlink(repo.join("nvim"), current.join("nvim")).unwrap();
+
+        for entry in list_entries(&backup, &current).unwrap() {
+            restore_entry(&entry).unwrap();
+        }
+
+        assert!(!current.join(".psqlrc").is_symlink());
+        assert!(!current.join("nvim").is_symlink());
+        assert_eq!(fs::read_to_string(current.join(".psqlrc")).unwrap(), "gone");
+        assert_eq!(fs::read_to_string(repo.join(".psqlrc")).unwrap(), "repo");
+        assert_eq!(
+            fs::read_to_string(repo.join("nvim/init.lua")).unwrap(),
+            "repo"
+        );
+    }
+}
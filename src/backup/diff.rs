@@ -0,0 +1,139 @@
+use colored::Colorize;
+
+/// Above this many line pairs the diff falls back to "all removed, all added"
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One line of a line-based diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Diffs two texts line by line (longest common subsequence)
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    if old.len().saturating_mul(new.len()) > MAX_DIFF_CELLS {
+        return old
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string()))
+            .chain(new.iter().map(|line| DiffLine::Added(line.to_string())))
+            .collect();
+    }
+
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(
+        old[i..]
+            .iter()
+            .map(|line| DiffLine::Removed(line.to_string())),
+    );
+    lines.extend(
+        new[j..]
+            .iter()
+            .map(|line| DiffLine::Added(line.to_string())),
+    );
+    lines
+}
+
+/// Renders changed lines with `context` unchanged lines around each change
+pub fn render_diff(lines: &[DiffLine], context: usize) -> String {
+    let changed: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut output = String::new();
+    let mut last_shown: Option<usize> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let near_change = changed
+            .iter()
+            .any(|&c| index + context >= c && index <= c + context);
+        if !near_change {
+            continue;
+        }
+        if last_shown.is_some_and(|last| index > last + 1) {
+            output.push_str(&format!("{}\n", "...".dimmed()));
+        }
+        last_shown = Some(index);
+
+        let rendered = match line {
+            DiffLine::Same(text) => format!("  {}", text),
+            DiffLine::Removed(text) => format!("- {}", text).red().to_string(),
+            DiffLine::Added(text) => format!("+ {}", text).green().to_string(),
+        };
+        output.push_str(&rendered);
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::strip_ansi;
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc\n", "a\nc\nd\n");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+        assert!(diff_lines("same\n", "same\n")
+            .iter()
+            .all(|line| matches!(line, DiffLine::Same(_))));
+    }
+
+    #[test]
+    fn test_render_diff_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\n5\n6\n7\neight\n";
+
+        let rendered = strip_ansi(&render_diff(&diff_lines(old, new), 1));
+        assert_eq!(rendered, "  7\n- 8\n+ eight\n");
+
+        let rendered = strip_ansi(&render_diff(
+            &diff_lines("x\n1\n2\n3\ny\n", "X\n1\n2\n3\nY\n"),
+            0,
+        ));
+        assert_eq!(rendered, "- x\n+ X\n...\n- y\n+ Y\n");
+    }
+}
@@ -0,0 +1,398 @@
+use crate::backup::is_record;
+use crate::core::walk::RepoWalker;
+use crate::error::{DotfilesError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// File inside an incremental backup listing every file it holds
+///
+/// Never copied back out on restore.
+pub const MANIFEST_FILE: &str = ".dotfiles-backup-manifest.json";
+
+/// One file in an incremental backup
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Path relative to the backup root
+    pub path: PathBuf,
+    pub size: u64,
+    /// Modification time of the original, in nanoseconds since the Unix epoch
+    pub mtime: i64,
+    /// Hex-encoded SHA-256 of the contents
+    pub hash: String,
+}
+
+/// Everything an incremental backup holds
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Manifest {
+    /// Name of the backup unchanged files were hard-linked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    /// Directories, relative to the backup root, so empty ones come back too
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+    pub files: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the incremental backup at `backup`
+    pub fn load(backup: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(
+            backup.join(MANIFEST_FILE),
+        )?)?)
+    }
+
+    /// Fails unless every listed path stays inside the backup root
+    ///
+    /// A manifest fetched from a remote could otherwise name absolute paths
+    /// or `..` and have restore write outside its target.
+    fn check_paths(&self, backup: &Path) -> Result<()> {
+        let paths = self
+            .dirs
+            .iter()
+            .chain(self.files.iter().map(|entry| &entry.path));
+        for path in paths {
+            let contained = path.components().next().is_some()
+                && path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_)));
+            if !contained {
+                return Err(DotfilesError::Config(format!(
+                    "Backup {} lists a path outside the backup: {}",
+                    backup.display(),
+                    path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn save(&self, backup: &Path) -> Result<()> {
+        fs::write(
+            backup.join(MANIFEST_FILE),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// How many files an incremental backup copied and how many it linked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IncrementalStats {
+    pub copied: usize,
+    pub linked: usize,
+}
+
+/// Returns true if the directory backup at `backup` has a manifest
+pub fn is_incremental(backup: &Path) -> bool {
+    backup.join(MANIFEST_FILE).is_file()
+}
+
+/// Backs up `src` into the directory `backup`, building on the incremental backup `previous`
+///
+/// Files whose size and modification time (or, failing that, contents)
+/// match `previous`'s manifest are hard-linked from it instead of copied,
+/// so every backup is still a complete tree that can be pruned on its own.
+/// Falls back to copying where linking fails (e.g. across filesystems).
+pub fn create(src: &Path, backup: &Path, previous: Option<&Path>) -> Result<IncrementalStats> {
+    let known: HashMap<PathBuf, ManifestEntry> = previous
+        .and_then(|previous| Manifest::load(previous).ok())
+        .map(|manifest| {
+            manifest
+                .files
+                .into_iter()
+                .map(|entry| (entry.path.clone(), entry))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut writer = Writer {
+        previous: previous.filter(|_| !known.is_empty()),
+        known,
+        manifest: Manifest {
+            previous: previous
+                .and_then(|previous| previous.file_name())
+                .map(|name| name.to_string_lossy().into_owned()),
+            ..Default::default()
+        },
+        stats: IncrementalStats::default(),
+    };
+    fs::create_dir_all(backup)?;
+    writer.add_dir(src, backup, Path::new(""))?;
+    writer.manifest.save(backup)?;
+    Ok(writer.stats)
+}
+
+struct Writer<'a> {
+    previous: Option<&'a Path>,
+    known: HashMap<PathBuf, ManifestEntry>,
+    manifest: Manifest,
+    stats: IncrementalStats,
+}
+
+impl Writer<'_> {
+    /// Mirrors `src` into `backup` the way `copy_dir_recursive` copies it
+    fn add_dir(&mut self, src: &Path, backup: &Path, rel: &Path) -> Result<()> {
+        for src_path in RepoWalker::new(src)
+            .respect_ignores(false)
+            .max_depth(1)
+            .entries()?
+        {
+            let name = src_path.file_name().unwrap_or_default();
+            if is_record(name) {
+                continue;
+            }
+            let rel_path = rel.join(name);
+
+            if src_path.is_dir() {
+                fs::create_dir_all(backup.join(&rel_path))?;
+                self.manifest.dirs.push(rel_path.clone());
+                self.add_dir(&src_path, backup, &rel_path)?;
+            } else {
+                self.add_file(&src_path, backup, rel_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_file(&mut self, src_path: &Path, backup: &Path, rel_path: PathBuf) -> Result<()> {
+        let metadata = fs::metadata(src_path)?;
+        let size = metadata.len();
+        let mtime = mtime_nanos(&metadata);
+        let before = self.known.get(&rel_path);
+        let hash = match before {
+            Some(entry) if entry.size == size && entry.mtime == mtime => entry.hash.clone(),
+            _ => hash_file(src_path)?,
+        };
+
+        let destination = backup.join(&rel_path);
+        let linked = match (self.previous, before) {
+            (Some(previous), Some(entry)) if entry.hash == hash => {
+                fs::hard_link(previous.join(&rel_path), &destination).is_ok()
+            }
+            _ => false,
+        };
+        if linked {
+            self.stats.linked += 1;
+        } else {
+            fs::copy(src_path, &destination)?;
+            self.stats.copied += 1;
+        }
+
+        self.manifest.files.push(ManifestEntry {
+            path: rel_path,
+            size,
+            mtime,
+            hash,
+        });
+        Ok(())
+    }
+}
+
+/// Checks that every file in the manifest is in the backup with the contents it had
+pub fn check(backup: &Path) -> Result<()> {
+    let manifest = Manifest::load(backup)?;
+    manifest.check_paths(backup)?;
+    for entry in &manifest.files {
+        let stored = backup.join(&entry.path);
+        let intact = fs::metadata(&stored).is_ok_and(|metadata| metadata.len() == entry.size)
+            && hash_file(&stored)? == entry.hash;
+        if !intact {
+            return Err(DotfilesError::Config(format!(
+                "Backup {} is damaged: {} is missing or changed",
+                backup.display(),
+                entry.path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reassembles the full tree of the incremental backup at `backup` into `target`
+///
+/// Files are copied, never linked, so editing them can't change other
+/// backups, and get back the modification times they were backed up with.
+pub fn restore(backup: &Path, target: &Path) -> Result<()> {
+    let manifest = Manifest::load(backup)?;
+    manifest.check_paths(backup)?;
+    fs::create_dir_all(target)?;
+    for dir in &manifest.dirs {
+        fs::create_dir_all(target.join(dir))?;
+    }
+    for entry in &manifest.files {
+        let destination = target.join(&entry.path);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(backup.join(&entry.path), &destination)?;
+        // Best effort: a read-only file keeps the time it was restored at
+        if let Ok(nanos) = u64::try_from(entry.mtime) {
+            let modified = UNIX_EPOCH + Duration::from_nanos(nanos);
+            let _ = File::options()
+                .write(true)
+                .open(&destination)
+                .and_then(|file| file.set_modified(modified));
+        }
+    }
+    Ok(())
+}
+
+fn mtime_nanos(metadata: &fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .and_then(|since| i64::try_from(since.as_nanos()).ok())
+        .unwrap_or(0)
+}
+
+fn hash_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+    use tempfile::TempDir;
+
+    fn set_mtime(path: &Path, at: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(at)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_incremental_backup_links_unchanged_files() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("dotfiles");
+        fs::create_dir_all(src.join("nvim/lua")).unwrap();
+        fs::create_dir_all(src.join("empty")).unwrap();
+        fs::write(src.join(".zshrc"), "export A=1\n").unwrap();
+        fs::write(src.join("nvim/init.lua"), "require('x')\n").unwrap();
+        fs::write(src.join("nvim/lua/x.lua"), "return {}\n").unwrap();
+
+        let first = temp.path().join("backups/one");
+        let stats = create(&src, &first, None).unwrap();
+        assert_eq!(
+            stats,
+            IncrementalStats {
+                copied: 3,
+                linked: 0
+            }
+        );
+
+        // Touched but unchanged files are still linked
+        fs::write(src.join(".zshrc"), "export A=2\n").unwrap();
+        set_mtime(
+            &src.join("nvim/init.lua"),
+            SystemTime::now() + Duration::from_secs(60),
+        );
+        let second = temp.path().join("backups/two");
+        let stats = create(&src, &second, Some(&first)).unwrap();
+        assert_eq!(
+            stats,
+            IncrementalStats {
+                copied: 1,
+                linked: 2
+            }
+        );
+
+        let manifest = Manifest::load(&second).unwrap();
+        assert_eq!(manifest.previous.as_deref(), Some("one"));
+        assert_eq!(manifest.files.len(), 3);
+        assert!(manifest.dirs.contains(&PathBuf::from("empty")));
+        assert_eq!(
+            fs::read_to_string(first.join(".zshrc")).unwrap(),
+            "export A=1\n"
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let inode = |path: &Path| fs::metadata(path).unwrap().ino();
+            assert_eq!(
+                inode(&first.join("nvim/lua/x.lua")),
+                inode(&second.join("nvim/lua/x.lua"))
+            );
+            assert_ne!(inode(&first.join(".zshrc")), inode(&second.join(".zshrc")));
+        }
+    }
+
+    #[test]
+    fn test_incremental_restore() {
+        let temp = TempDir::new().unwrap();
+        let src = temp.path().join("dotfiles");
+        fs::create_dir_all(src.join("git")).unwrap();
+        fs::create_dir_all(src.join("empty")).unwrap();
+        fs::write(src.join("git/.gitconfig"), "[user]\n").unwrap();
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        set_mtime(&src.join("git/.gitconfig"), modified);
+
+        let first = temp.path().join("backups/one");
+        create(&src, &first, None).unwrap();
+        let second = temp.path().join("backups/two");
+        create(&src, &second, Some(&first)).unwrap();
+        assert!(is_incremental(&second));
+        check(&second).unwrap();
+
+        // The newer backup stands on its own once the older one is pruned
+        fs::remove_dir_all(&first).unwrap();
+        let target = temp.path().join("restored");
+        restore(&second, &target).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("git/.gitconfig")).unwrap(),
+            "[user]\n"
+        );
+        assert!(target.join("empty").is_dir());
+        assert!(!target.join(MANIFEST_FILE).exists());
+        assert_eq!(
+            fs::metadata(target.join("git/.gitconfig"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            modified
+        );
+
+        fs::write(second.join("git/.gitconfig"), "[core]\n").unwrap();
+        assert!(check(&second).is_err());
+    }
+
+    #[test]
+    fn test_manifest_paths_outside_backup_are_refused() {
+        let temp = TempDir::new().unwrap();
+        let backup = temp.path().join("backups/one");
+        fs::create_dir_all(&backup).unwrap();
+        fs::write(backup.join("evil"), "x").unwrap();
+        let target = temp.path().join("restored/home");
+
+        for path in ["../evil", "/tmp/evil", "a/../../evil"] {
+            Manifest {
+                previous: None,
+                dirs: Vec::new(),
+                files: vec![ManifestEntry {
+                    path: PathBuf::from(path),
+                    size: 1,
+                    mtime: 0,
+                    hash: hash_file(&backup.join("evil")).unwrap(),
+                }],
+            }
+            .save(&backup)
+            .unwrap();
+
+            assert!(check(&backup).is_err(), "{}", path);
+            assert!(restore(&backup, &target).is_err(), "{}", path);
+        }
+        assert!(!temp.path().join("restored/evil").exists());
+        assert!(!target.exists());
+    }
+}
@@ -1,7 +1,33 @@
-use crate::backup::{self, secrets};
+use crate::backup::{self, secret_store, secrets, BackupLayout};
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandRunner};
+use crate::core::context::AppContext;
+use crate::core::hooks::{self, HookPoint, HookRun};
+use crate::core::summary::Outcome;
+use crate::core::walk::{RepoWalker, ALWAYS_SKIPPED};
 use crate::error::{DotfilesError, Result};
-use crate::symlink::{self, SymlinkReport, Symlinker};
-use std::path::{Path, PathBuf};
+use crate::install::repos;
+use crate::symlink::{self, LinkIssue, SymlinkReport};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+/// How the old setup's files are organized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceLayout {
+    /// Dotfiles at the top level (`.zshrc`, `.config/nvim`), as this tool expects
+    Flat,
+    /// GNU Stow packages (`zsh/.zshrc`, `nvim/.config/nvim`), unpacked on the way in
+    Packages,
+}
+
+/// Where a migration replaces secret values with `${VAR}` references
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretRedaction {
+    /// Rewrite the old setup's files before they are copied
+    InPlace,
+    /// Rewrite only the copies in the target repo, before they are committed
+    IntoTarget,
+}
 
 /// Migration options
 #[derive(Debug, Clone)]
@@ -10,23 +36,51 @@ pub struct MigrationOptions {
     pub source: PathBuf,
     /// Target dotfiles directory (new setup)
     pub target: PathBuf,
+    /// Where the migrated files are linked (usually $HOME)
+    pub home: PathBuf,
+    /// Layout of `source`, or None to detect it
+    pub layout: Option<SourceLayout>,
     /// Whether to extract secrets
     pub extract_secrets: bool,
+    /// When a random-looking string is reported as a candidate secret
+    pub entropy: secrets::EntropySettings,
+    /// Where extracted secrets are replaced with `${VAR}` references, if anywhere
+    ///
+    /// With None, files are copied as they are; a copy still holding a
+    /// detected secret stops the migration before anything is committed.
+    pub redact_secrets: Option<SecretRedaction>,
+    /// Keep extracted secrets in this store instead of a plaintext `.env`
+    pub secret_store: Option<secret_store::Backend>,
+    /// Hooks filtered out for this machine by `[hooks.when]` (`<point>.d/<script>`)
+    pub skipped_hooks: Vec<String>,
+    /// Top-level entries of `source` not to migrate or link (see `Config::skipped_links`)
+    pub skipped_links: Vec<String>,
     /// Whether to create backup before migration
     pub create_backup: bool,
+    /// Where that backup goes
+    pub backups: BackupLayout,
     /// Dry run mode (no actual changes)
     pub dry_run: bool,
 }
 
 impl MigrationOptions {
-    pub fn new(source: PathBuf, target: PathBuf) -> Self {
-        Self {
+    /// Options linking into the context's home and backing up to its `[backups]` layout
+    pub fn new(context: &AppContext, source: PathBuf, target: PathBuf) -> Result<Self> {
+        Ok(Self {
             source,
             target,
+            home: context.home.clone(),
+            layout: None,
             extract_secrets: true,
+            entropy: secrets::EntropySettings::default(),
+            redact_secrets: None,
+            secret_store: None,
+            skipped_hooks: Vec::new(),
+            skipped_links: Vec::new(),
             create_backup: true,
+            backups: BackupLayout::from_context(context)?,
             dry_run: false,
-        }
+        })
     }
 }
 
@@ -34,51 +88,133 @@ impl MigrationOptions {
 #[derive(Debug)]
 pub struct MigrationResult {
     pub backup_path: Option<PathBuf>,
+    pub layout: SourceLayout,
+    /// Files copied into the target repo (or that would be, in a dry run)
+    pub copied: Vec<PathBuf>,
     pub secrets_extracted: usize,
+    /// Lines whose secrets were replaced (or would be, in a dry run)
+    pub redactions: Vec<secrets::Replacement>,
     pub symlink_report: Option<SymlinkReport>,
     pub conflicts: Vec<(PathBuf, String)>,
+    /// The target repo's pre- and post-migrate hooks that ran
+    pub hooks: Vec<HookRun>,
 }
 
 /// Migrates dotfiles from old setup to new setup
+///
+/// Files are copied into the target repo in the flat layout the tool links
+/// (unpacking Stow packages, leaving out [`symlink::EXCLUSIONS`] and
+/// `skipped_links`), committed, and only then linked into `home` from the
+/// repo. Links in `home` that still point into the old source are replaced;
+/// anything else in the way is a conflict, and nothing is changed. Once the
+/// conflict checks pass, detected secrets are saved outside the repo and,
+/// with `redact_secrets`, replaced with `${VAR}` references; if a copied file
+/// still holds one, nothing is committed or linked and an error is returned.
 pub fn migrate(options: &MigrationOptions) -> Result<MigrationResult> {
+    // Step 1: Validate source exists
+    if !options.source.exists() {
+        return Err(DotfilesError::Config(format!(
+            "Source directory does not exist: {:?}",
+            options.source
+        )));
+    }
+
+    let layout = options
+        .layout
+        .unwrap_or_else(|| detect_layout(&options.source, &options.skipped_links));
     let mut result = MigrationResult {
         backup_path: None,
+        layout,
+        copied: Vec::new(),
         secrets_extracted: 0,
+        redactions: Vec::new(),
         symlink_report: None,
         conflicts: Vec::new(),
+        hooks: Vec::new(),
     };
 
-    // Step 1: Validate source exists
-    if !options.source.exists() {
-        return Err(DotfilesError::Config(format!(
-            "Source directory does not exist: {:?}",
-            options.source
+    // Step 1b: Run the target repo's pre-migrate hooks; a failing one stops the migration
+    result.hooks = hooks::run(
+        &options.target,
+        &options.home,
+        HookPoint::PreMigrate,
+        &options.skipped_hooks,
+        options.dry_run,
+    );
+    if let Some(failed) = result
+        .hooks
+        .iter()
+        .find(|run| run.step.outcome == Outcome::Failed)
+    {
+        print!("{}", hooks::render_output(&result.hooks));
+        return Err(DotfilesError::InstallationFailed(format!(
+            "{}: {}",
+            failed.step.name,
+            failed.step.detail.as_deref().unwrap_or("failed")
         )));
     }
 
     // Step 2: Create backup if requested
     if options.create_backup && !options.dry_run {
         println!("Creating backup before migration...");
-        let backup_path = backup::create_backup(&options.source, None)?;
-        result.backup_path = Some(backup_path);
+        let created = backup::create_backup_in(&options.source, &options.backups)?;
+        println!("✓ Created backup at {}", created.path.display());
+        result.backup_path = Some(created.path);
     }
 
-    // Step 3: Extract secrets if requested
+    // Step 3: Map files into the target repo's layout
+    println!(
+        "Mapping {} layout into {:?}...",
+        match layout {
+            SourceLayout::Flat => "flat",
+            SourceLayout::Packages => "Stow package",
+        },
+        options.target
+    );
+    let copies = plan_copies(
+        &options.source,
+        &options.target,
+        layout,
+        &options.skipped_links,
+    )?;
+    let linked = top_level_names(&copies, &options.target);
+
+    // Step 4: Extract secrets from every file being copied, at any depth
+    // Nothing is saved or rewritten until the conflict checks pass
+    let mut redaction = None;
+    let mut env = Vec::new();
+    let mut secret_values = Vec::new();
     if options.extract_secrets {
         println!("Scanning for secrets...");
-        let found_secrets = secrets::scan_directory(&options.source)?;
+        let sources: Vec<PathBuf> = copies.iter().map(|(from, _)| from.clone()).collect();
+        let found_secrets = secrets::scan_files_with(&options.source, &sources, &options.entropy);
 
         if !found_secrets.is_empty() {
             println!("{}", secrets::summarize_secrets(&found_secrets));
 
-            if !options.dry_run {
-                let env_path = options.target.join(".env");
-                secrets::extract_to_env(&found_secrets, &env_path)?;
-                println!(
-                    "✓ Extracted {} secrets to {:?}",
-                    found_secrets.len(),
-                    env_path
-                );
+            secret_values = found_secrets
+                .iter()
+                .map(|secret| secret.value.clone())
+                .collect();
+            match options.redact_secrets {
+                Some(mode) => {
+                    let planned = secrets::plan_redaction(&options.source, &found_secrets)?;
+                    println!(
+                        "{} {} line(s) with ${{VAR}} references:",
+                        if options.dry_run {
+                            "Would rewrite"
+                        } else {
+                            "Rewriting"
+                        },
+                        planned.replacements.len()
+                    );
+                    print!("{}", planned.preview());
+
+                    env = planned.env.clone();
+                    result.redactions = planned.replacements.clone();
+                    redaction = Some((mode, planned));
+                }
+                None => env = secrets::env_entries(&found_secrets),
             }
 
             result.secrets_extracted = found_secrets.len();
@@ -87,9 +223,23 @@ pub fn migrate(options: &MigrationOptions) -> Result<MigrationResult> {
         }
     }
 
-    // Step 4: Detect conflicts
+    // Step 5: Detect conflicts
     println!("Checking for conflicts...");
-    let conflicts = symlink::detect_conflicts(&options.source, &options.target);
+    let rewritten = match &redaction {
+        Some((SecretRedaction::InPlace, planned)) => planned
+            .files
+            .iter()
+            .map(|(file, content)| (options.source.join(file), content.clone()))
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+    let mut conflicts = repo_conflicts(&copies, &rewritten);
+    conflicts.extend(link_conflicts(
+        &linked,
+        &options.target,
+        &options.home,
+        &options.source,
+    ));
 
     if !conflicts.is_empty() {
         println!("⚠ Found {} conflict(s):", conflicts.len());
@@ -99,17 +249,77 @@ pub fn migrate(options: &MigrationOptions) -> Result<MigrationResult> {
         result.conflicts = conflicts;
     }
 
-    // Step 5: Create symlinks (if no conflicts or dry run)
+    // Step 6: Copy, commit, and link from the repo (if no conflicts or dry run)
     if result.conflicts.is_empty() || options.dry_run {
+        if !options.dry_run && result.secrets_extracted > 0 {
+            save_secrets(&env, options)?;
+            if let Some((SecretRedaction::InPlace, planned)) = &redaction {
+                write_redacted(planned, |file| Some(options.source.join(file)))?;
+            }
+        }
+
+        result.copied = copy_into_repo(&copies, options.dry_run)?;
+        println!(
+            "✓ {} {} file(s) into {:?}",
+            if options.dry_run {
+                "Would copy"
+            } else {
+                "Copied"
+            },
+            result.copied.len(),
+            options.target
+        );
+
+        if let Some((SecretRedaction::IntoTarget, planned)) = &redaction {
+            if !options.dry_run {
+                // Only copies made just now; files already in the repo are left alone
+                write_redacted(planned, |file| {
+                    let from = options.source.join(file);
+                    copies
+                        .iter()
+                        .find(|(source, to)| *source == from && result.copied.contains(to))
+                        .map(|(_, to)| to.clone())
+                })?;
+            }
+        }
+
+        if !options.dry_run {
+            // Every destination, so copies left by an earlier stopped run are checked too
+            let destinations: Vec<PathBuf> = copies.iter().map(|(_, to)| to.clone()).collect();
+            let leaked = files_with_secrets(&destinations, &secret_values);
+            if !leaked.is_empty() {
+                return Err(DotfilesError::Config(format!(
+                    "Not committing: detected secrets are still in {}; replace them with ${{VAR}} references or remove them from {:?}",
+                    leaked
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    options.target
+                )));
+            }
+            if !result.copied.is_empty() {
+                commit_migrated(&options.target, &result.copied, &options.source)?;
+                println!("✓ Committed the migrated files");
+            }
+        }
+
         println!("Creating symlinks...");
 
-        // Use manual symlinker for migration (more control)
+        // Links still pointing into the old source are the ones being migrated
         let symlinker = symlink::manual::ManualSymlinker {
             dry_run: options.dry_run,
-            force: false,
+            force: true,
+            skip: Vec::new(),
+            config_home: None,
         };
 
-        let report = symlinker.symlink(&options.source, &options.target)?;
+        let mut report = SymlinkReport::new();
+        for name in &linked {
+            report.add(
+                symlinker.create_symlink(&options.target.join(name), &options.home.join(name))?,
+            );
+        }
 
         if options.dry_run {
             println!("Dry run - no changes made");
@@ -117,6 +327,16 @@ pub fn migrate(options: &MigrationOptions) -> Result<MigrationResult> {
 
         println!("✓ Symlink operation: {}", report.summary());
         result.symlink_report = Some(report);
+
+        // Step 7: Run the target repo's post-migrate hooks
+        result.hooks.extend(hooks::run(
+            &options.target,
+            &options.home,
+            HookPoint::PostMigrate,
+            &options.skipped_hooks,
+            options.dry_run,
+        ));
+        print!("{}", hooks::render_output(&result.hooks));
     } else {
         println!("⚠ Migration aborted due to conflicts");
         println!("  Resolve conflicts manually or use --force flag");
@@ -125,18 +345,280 @@ pub fn migrate(options: &MigrationOptions) -> Result<MigrationResult> {
     Ok(result)
 }
 
-/// Rolls back a migration by restoring from the most recent backup
-pub fn rollback(target: &Path) -> Result<()> {
+/// Guesses the old setup's layout: Stow packages if any top-level directory
+/// without a leading dot holds dotfiles
+pub fn detect_layout(source: &Path, skipped: &[String]) -> SourceLayout {
+    let top_level = RepoWalker::new(source)
+        .max_depth(1)
+        .skip(skipped)
+        .entries()
+        .unwrap_or_default();
+    if top_level.iter().any(|path| is_package(path)) {
+        SourceLayout::Packages
+    } else {
+        SourceLayout::Flat
+    }
+}
+
+/// Pairs every file to migrate with its place in the target repo
+///
+/// Top-level entries are taken as they are, except that in a package layout
+/// each package's contents move up to the top level (`zsh/.zshrc` -> `.zshrc`).
+/// Ignored files are left behind, and so are [`symlink::EXCLUSIONS`] and
+/// `skipped` entries at the source's top level; inside a package those names
+/// are ordinary files.
+pub fn plan_copies(
+    source: &Path,
+    target: &Path,
+    layout: SourceLayout,
+    skipped: &[String],
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut entries = Vec::new();
+    for path in RepoWalker::new(source)
+        .max_depth(1)
+        .skip(skipped)
+        .entries()?
+    {
+        if layout == SourceLayout::Packages && is_package(&path) {
+            entries.extend(RepoWalker::new(&path).nested().max_depth(1).entries()?);
+        } else {
+            entries.push(path);
+        }
+    }
+
+    let mut copies = Vec::new();
+    for entry in entries {
+        let Some(name) = entry.file_name() else {
+            continue;
+        };
+        let destination = target.join(name);
+        if entry.is_dir() && !entry.is_symlink() {
+            for file in RepoWalker::new(&entry).respect_ignores(false).files()? {
+                let rel = file.strip_prefix(&entry).unwrap_or(&file);
+                let junk = rel.components().any(|component| {
+                    ALWAYS_SKIPPED.contains(&component.as_os_str().to_string_lossy().as_ref())
+                });
+                if !junk {
+                    copies.push((file.clone(), destination.join(rel)));
+                }
+            }
+        } else if entry.is_file() {
+            copies.push((entry, destination));
+        }
+    }
+    Ok(copies)
+}
+
+fn is_package(path: &Path) -> bool {
+    let is_dotted = |path: &Path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    };
+    path.is_dir()
+        && !is_dotted(path)
+        && std::fs::read_dir(path)
+            .map(|entries| entries.flatten().any(|entry| is_dotted(&entry.path())))
+            .unwrap_or(false)
+}
+
+/// Top-level repo entries the copies create, each linked into $HOME as a whole
+fn top_level_names(copies: &[(PathBuf, PathBuf)], target: &Path) -> Vec<PathBuf> {
+    let names: BTreeSet<PathBuf> = copies
+        .iter()
+        .filter_map(
+            |(_, to)| match to.strip_prefix(target).ok()?.components().next()? {
+                Component::Normal(name) => Some(PathBuf::from(name)),
+                _ => None,
+            },
+        )
+        .collect();
+    names.into_iter().collect()
+}
+
+/// Files already in the repo with different content, or claimed by two sources
+///
+/// `rewritten` holds the content a source will have once redacted in place.
+fn repo_conflicts(
+    copies: &[(PathBuf, PathBuf)],
+    rewritten: &BTreeMap<PathBuf, String>,
+) -> Vec<(PathBuf, String)> {
+    let mut claimed = BTreeSet::new();
+    let mut conflicts = Vec::new();
+    for (from, to) in copies {
+        let content = match rewritten.get(from) {
+            Some(content) => Some(content.clone().into_bytes()),
+            None => std::fs::read(from).ok(),
+        };
+        if !claimed.insert(to) {
+            conflicts.push((to.clone(), format!("Also provided by {:?}", from)));
+        } else if to.exists() && std::fs::read(to).ok() != content {
+            conflicts.push((
+                to.clone(),
+                "Already in the repo with different content".to_string(),
+            ));
+        }
+    }
+    conflicts
+}
+
+/// Paths in $HOME that linking `names` from the repo would clobber
+///
+/// Links into the old source are expected (that is what is being migrated),
+/// and so are links already pointing at the repo.
+fn link_conflicts(
+    names: &[PathBuf],
+    target: &Path,
+    home: &Path,
+    source: &Path,
+) -> Vec<(PathBuf, String)> {
+    let mut conflicts = Vec::new();
+    for name in names {
+        let link = home.join(name);
+        if link.is_symlink() {
+            let Ok(points_to) = std::fs::read_link(&link) else {
+                continue;
+            };
+            if points_to != target.join(name) && !points_to.starts_with(source) {
+                conflicts.push((link, format!("Symlink points to {:?}", points_to)));
+            }
+        } else if link.is_dir() {
+            conflicts.push((link, "Directory already exists".to_string()));
+        } else if link.exists() {
+            conflicts.push((link, "File already exists".to_string()));
+        }
+    }
+    conflicts
+}
+
+/// Copies the planned files, skipping ones already in place; returns the repo paths
+fn copy_into_repo(copies: &[(PathBuf, PathBuf)], dry_run: bool) -> Result<Vec<PathBuf>> {
+    let mut copied = Vec::new();
+    for (from, to) in copies {
+        if to.exists() {
+            continue;
+        }
+        if !dry_run {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(from, to)?;
+            audit::record(Action::FileCopied {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+        copied.push(to.clone());
+    }
+    Ok(copied)
+}
+
+/// Keeps extracted secrets in the configured store, or the target repo's `.env` without one
+fn save_secrets(entries: &[(String, String)], options: &MigrationOptions) -> Result<()> {
+    // Saved before anything is copied, so the repo may not exist yet
+    std::fs::create_dir_all(&options.target)?;
+    match &options.secret_store {
+        Some(backend) => {
+            let store = backend.open();
+            let snippet_path = options.target.join(secret_store::SNIPPET_FILE);
+            secret_store::store_all(store.as_ref(), entries, &snippet_path)?;
+            println!(
+                "✓ Stored {} variable(s) in the {}; source {:?} to export them",
+                entries.len(),
+                store.name(),
+                snippet_path
+            );
+        }
+        None => {
+            let env_path = options.target.join(".env");
+            secrets::write_env(entries, &env_path)?;
+            println!("✓ Wrote {} variable(s) to {:?}", entries.len(), env_path);
+        }
+    }
+    Ok(())
+}
+
+/// Writes each redacted file to where `destination` puts it, skipping files it has no place for
+fn write_redacted(
+    redaction: &secrets::Redaction,
+    destination: impl Fn(&str) -> Option<PathBuf>,
+) -> Result<()> {
+    for (file, content) in &redaction.files {
+        if let Some(path) = destination(file) {
+            std::fs::write(&path, content)?;
+            audit::record(Action::FileWritten {
+                path,
+                reason: "secrets replaced with ${VAR} references".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the files that contain any of `values` verbatim
+fn files_with_secrets(files: &[PathBuf], values: &[String]) -> Vec<PathBuf> {
+    files
+        .iter()
+        .filter(|file| {
+            std::fs::read_to_string(file).is_ok_and(|content| {
+                values
+                    .iter()
+                    .any(|value| !value.is_empty() && content.contains(value.as_str()))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Commits the migrated files, creating the repo first if needed
+///
+/// Only the copied paths are staged, so the `.env` written next to them
+/// stays out of history. Callers check the copies hold no detected secrets.
+fn commit_migrated(target: &Path, files: &[PathBuf], source: &Path) -> Result<()> {
+    let git = |args: &[&str]| {
+        CommandRunner::new("git")
+            .arg("-C")
+            .arg(target)
+            .args(args)
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run()
+    };
+
+    if !repos::is_git_repo(target) {
+        git(&["init", "-q"])?;
+    }
+    CommandRunner::new("git")
+        .arg("-C")
+        .arg(target)
+        .args(["add", "--"])
+        .args(files)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    git(&[
+        "commit",
+        "-q",
+        "-m",
+        &format!("Migrate dotfiles from {}", source.display()),
+    ])?;
+    Ok(())
+}
+
+/// Rolls back a migration by restoring from the most recent backup in `backups`
+pub fn rollback(backups: &BackupLayout, target: &Path) -> Result<()> {
     println!("Rolling back migration...");
 
     // Find the most recent backup
-    let backup = backup::get_latest_backup(None)?
+    let backup = backup::get_latest_backup(backups)?
         .ok_or_else(|| DotfilesError::Config("No backup found to rollback from".to_string()))?;
 
-    println!("Restoring from backup: {}", backup.timestamp);
+    println!(
+        "Restoring from backup: {}",
+        backup.when(chrono::Local::now())
+    );
 
     // Restore the backup
-    backup::restore_backup(&backup, target)?;
+    backup::restore_backup(&backup, target, backups)?;
 
     println!("✓ Rollback complete");
 
@@ -144,10 +626,10 @@ pub fn rollback(target: &Path) -> Result<()> {
 }
 
 /// Verifies migration was successful
-pub fn verify_migration(source: &Path, target: &Path) -> Result<Vec<(PathBuf, String)>> {
+pub fn verify_migration(source: &Path, target: &Path) -> Result<Vec<(PathBuf, LinkIssue)>> {
     println!("Verifying migration...");
 
-    let issues = symlink::validate_symlinks(source, target)?;
+    let issues = symlink::validate_symlinks(source, target, None, &[])?;
 
     if issues.is_empty() {
         println!("✓ All symlinks are valid");
@@ -167,13 +649,33 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    /// Creates a git repo with a commit identity at `target`; returns a git runner for it
+    fn init_repo(target: &Path) -> impl Fn(&[&str]) -> command::CommandOutput + '_ {
+        let git = move |args: &[&str]| {
+            CommandRunner::new("git")
+                .arg("-C")
+                .arg(target)
+                .args(args)
+                .stream(false)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.name", "Test"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git
+    }
+
     #[test]
     fn test_migration_options_new() {
         let source = PathBuf::from("/source");
         let target = PathBuf::from("/target");
-        let options = MigrationOptions::new(source.clone(), target.clone());
+        let context = AppContext::for_home("/home/user");
+        let options = MigrationOptions::new(&context, source.clone(), target.clone()).unwrap();
 
         assert_eq!(options.source, source);
+        assert_eq!(options.home, context.home);
+        assert_eq!(options.backups.dir, context.state_dir.join("backups"));
         assert_eq!(options.target, target);
         assert!(options.extract_secrets);
         assert!(options.create_backup);
@@ -192,17 +694,110 @@ mod tests {
 
         fs::create_dir(&target).unwrap();
 
-        let mut options = MigrationOptions::new(source, target.clone());
+        let mut options = MigrationOptions::new(
+            &AppContext::for_home(temp_dir.path()),
+            source,
+            target.clone(),
+        )
+        .unwrap();
         options.dry_run = true;
         options.create_backup = false;
 
         let result = migrate(&options).unwrap();
 
         assert_eq!(result.secrets_extracted, 0);
+        assert_eq!(result.copied, vec![target.join("config.txt")]);
         assert!(result.symlink_report.is_some());
 
-        // In dry run, no symlinks should actually be created
+        // In dry run, nothing should actually be copied or linked
         assert!(!target.join("config.txt").exists());
+        assert!(!temp_dir.path().join("config.txt").exists());
+    }
+
+    #[test]
+    fn test_plan_copies_unpacks_packages() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+
+        fs::create_dir_all(source.join("zsh")).unwrap();
+        fs::write(source.join("zsh/.zshrc"), "").unwrap();
+        fs::create_dir_all(source.join("nvim/.config/nvim/.git")).unwrap();
+        fs::write(source.join("nvim/.config/nvim/init.lua"), "").unwrap();
+        fs::write(source.join("nvim/.config/nvim/.git/HEAD"), "").unwrap();
+        fs::write(source.join("README.md"), "").unwrap();
+
+        assert_eq!(detect_layout(&source, &[]), SourceLayout::Packages);
+        let copies = plan_copies(&source, &target, SourceLayout::Packages, &[]).unwrap();
+        let destinations: Vec<&Path> = copies.iter().map(|(_, to)| to.as_path()).collect();
+        assert_eq!(
+            destinations,
+            vec![
+                target.join(".config/nvim/init.lua").as_path(),
+                target.join(".zshrc").as_path(),
+            ]
+        );
+        assert_eq!(
+            top_level_names(&copies, &target),
+            vec![PathBuf::from(".config"), PathBuf::from(".zshrc")]
+        );
+
+        // Names excluded at the repo's top level are ordinary files inside a package
+        fs::create_dir_all(source.join("git/.config/git/hooks")).unwrap();
+        fs::write(source.join("git/.config/git/hooks/pre-commit"), "").unwrap();
+        fs::write(source.join("git/Brewfile"), "").unwrap();
+        let copies = plan_copies(&source, &target, SourceLayout::Packages, &[]).unwrap();
+        for kept in [".config/git/hooks/pre-commit", "Brewfile"] {
+            assert!(copies.iter().any(|(_, to)| *to == target.join(kept)));
+        }
+        fs::remove_dir_all(source.join("git")).unwrap();
+
+        // Skipped top-level entries are left behind
+        let skipped = vec!["zsh".to_string()];
+        let copies = plan_copies(&source, &target, SourceLayout::Packages, &skipped).unwrap();
+        assert!(!copies.iter().any(|(_, to)| *to == target.join(".zshrc")));
+
+        // The same file from two packages is a conflict
+        fs::create_dir_all(source.join("bash")).unwrap();
+        fs::write(source.join("bash/.zshrc"), "other").unwrap();
+        let copies = plan_copies(&source, &target, SourceLayout::Packages, &[]).unwrap();
+        assert_eq!(repo_conflicts(&copies, &BTreeMap::new()).len(), 1);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_relinks_from_repo() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join(".zshrc"), "export EDITOR=nvim\n").unwrap();
+        fs::create_dir_all(&home).unwrap();
+        std::os::unix::fs::symlink(source.join(".zshrc"), home.join(".zshrc")).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home.clone()), source, target.clone())
+                .unwrap();
+        options.create_backup = false;
+        options.extract_secrets = false;
+
+        let result = migrate(&options).unwrap();
+
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            fs::read_to_string(target.join(".zshrc")).unwrap(),
+            "export EDITOR=nvim\n"
+        );
+        assert_eq!(
+            fs::read_link(home.join(".zshrc")).unwrap(),
+            target.join(".zshrc")
+        );
+        let log = git(&["log", "--oneline"]);
+        assert!(log.stdout.contains("Migrate dotfiles from"));
     }
 
     #[test]
@@ -217,7 +812,12 @@ mod tests {
 
         fs::create_dir(&target).unwrap();
 
-        let mut options = MigrationOptions::new(source, target.clone());
+        let mut options = MigrationOptions::new(
+            &AppContext::for_home(temp_dir.path()),
+            source,
+            target.clone(),
+        )
+        .unwrap();
         options.dry_run = true;
         options.create_backup = false;
 
@@ -226,13 +826,220 @@ mod tests {
         assert_eq!(result.secrets_extracted, 1);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_redacts_secrets_into_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(
+            source.join(".zshrc"),
+            "export EDITOR=nvim\nexport API_TOKEN=secret123\n",
+        )
+        .unwrap();
+        fs::create_dir_all(&target).unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home), source.clone(), target.clone())
+                .unwrap();
+        options.create_backup = false;
+        options.redact_secrets = Some(SecretRedaction::IntoTarget);
+
+        // A dry run only previews
+        options.dry_run = true;
+        let result = migrate(&options).unwrap();
+        assert_eq!(result.redactions.len(), 1);
+        assert!(!target.join(".env").exists());
+
+        options.dry_run = false;
+        migrate(&options).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join(".zshrc")).unwrap(),
+            "export EDITOR=nvim\nexport API_TOKEN=${API_TOKEN}\n"
+        );
+        assert!(fs::read_to_string(target.join(".env"))
+            .unwrap()
+            .contains("API_TOKEN=secret123"));
+        assert!(fs::read_to_string(source.join(".zshrc"))
+            .unwrap()
+            .contains("secret123"));
+        let committed = git(&["show", "HEAD:.zshrc"]);
+        assert!(!committed.stdout.contains("secret123"));
+        let tracked = git(&["ls-files"]);
+        assert!(!tracked.stdout.contains(".env"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_conflict_leaves_source_and_secrets_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(source.join(".zshrc"), "export API_TOKEN=secret123\n").unwrap();
+        fs::write(home.join(".zshrc"), "# not a link\n").unwrap();
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home), source.clone(), target.clone())
+                .unwrap();
+        options.create_backup = false;
+        options.redact_secrets = Some(SecretRedaction::InPlace);
+        let result = migrate(&options).unwrap();
+
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(
+            fs::read_to_string(source.join(".zshrc")).unwrap(),
+            "export API_TOKEN=secret123\n"
+        );
+        assert!(!target.join(".env").exists());
+        assert!(!target.join(".zshrc").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_never_commits_detected_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(source.join(".zshrc"), "export API_TOKEN=secret123\n").unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home.clone()), source, target.clone())
+                .unwrap();
+        options.create_backup = false;
+        assert!(migrate(&options).is_err());
+
+        assert!(!git(&["rev-parse", "-q", "--verify", "HEAD"]).success());
+        assert!(fs::symlink_metadata(home.join(".zshrc")).is_err());
+
+        // The copy left behind is still checked when the migration is run again
+        assert!(migrate(&options).is_err());
+        assert!(fs::symlink_metadata(home.join(".zshrc")).is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_redaction_keeps_secrets_out_of_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(
+            source.join(".zshrc"),
+            "export API_TOKEN=secret123\nexport GITHUB_TOKEN=ghp_abcdef123456\n",
+        )
+        .unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home), source, target.clone()).unwrap();
+        options.create_backup = false;
+        options.redact_secrets = Some(SecretRedaction::IntoTarget);
+        let result = migrate(&options).unwrap();
+        assert_eq!(result.secrets_extracted, 2);
+
+        for value in ["secret123", "ghp_abcdef123456"] {
+            assert!(!git(&["grep", "-q", value, "HEAD"]).success());
+        }
+        assert!(git(&["show", "HEAD:.zshrc"])
+            .stdout
+            .contains("${API_TOKEN}"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_does_not_commit_secrets_it_cannot_redact() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(source.join(".config/app")).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(
+            source.join(".config/app/settings.json"),
+            "{ \"token\": \"API_TOKEN=secret123\" }\n",
+        )
+        .unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home), source, target.clone()).unwrap();
+        options.create_backup = false;
+        options.redact_secrets = Some(SecretRedaction::IntoTarget);
+        let error = migrate(&options).unwrap_err().to_string();
+        assert!(error.contains("settings.json"));
+        assert!(!git(&["rev-parse", "-q", "--verify", "HEAD"]).success());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_migrate_scans_nested_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        let target = temp_dir.path().join("target");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(source.join(".config/gh")).unwrap();
+        fs::write(
+            source.join(".config/gh/hosts.yml"),
+            "GH_TOKEN=gho_nested123\n",
+        )
+        .unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        let git = init_repo(&target);
+
+        let mut options =
+            MigrationOptions::new(&AppContext::for_home(home), source, target.clone()).unwrap();
+        options.create_backup = false;
+        options.dry_run = true;
+        let result = migrate(&options).unwrap();
+        assert_eq!(result.secrets_extracted, 1);
+
+        options.dry_run = false;
+        let _ = migrate(&options);
+        assert!(!git(&["grep", "-q", "gho_nested123", "HEAD"]).success());
+    }
+
+    #[test]
+    fn test_files_with_secrets() {
+        let temp_dir = TempDir::new().unwrap();
+        let clean = temp_dir.path().join("clean");
+        let leaky = temp_dir.path().join("leaky");
+        fs::write(&clean, "export API_TOKEN=${API_TOKEN}\n").unwrap();
+        fs::write(&leaky, "token = secret123\n").unwrap();
+
+        let files = vec![clean, leaky.clone()];
+        assert_eq!(
+            files_with_secrets(&files, &["secret123".to_string(), String::new()]),
+            vec![leaky]
+        );
+        assert!(files_with_secrets(&files, &[]).is_empty());
+    }
+
     #[test]
     fn test_migrate_nonexistent_source() {
         let temp_dir = TempDir::new().unwrap();
         let source = temp_dir.path().join("nonexistent");
         let target = temp_dir.path().join("target");
 
-        let options = MigrationOptions::new(source, target);
+        let context = AppContext::for_home(temp_dir.path());
+        let options = MigrationOptions::new(&context, source, target).unwrap();
         let result = migrate(&options);
 
         assert!(result.is_err());
@@ -264,7 +1071,8 @@ mod tests {
         fs::create_dir(&target).unwrap();
 
         // Should fail because no backup exists
-        let result = rollback(&target);
+        let backups = BackupLayout::from_context(&AppContext::for_home(temp_dir.path())).unwrap();
+        let result = rollback(&backups, &target);
         assert!(result.is_err());
     }
 }
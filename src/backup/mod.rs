@@ -1,39 +1,338 @@
+pub mod bare;
+pub mod browse;
+pub mod diff;
+pub mod incremental;
 pub mod migrate;
+pub mod remote;
+pub mod secret_store;
 pub mod secrets;
 
+use crate::core::audit::{self, Action};
+use crate::core::config::{BackupFormat, BackupSettings};
+use crate::core::context::AppContext;
+use crate::core::state;
+use crate::core::timing::format_age;
+use crate::core::walk::RepoWalker;
 use crate::error::{DotfilesError, Result};
-use chrono::Local;
-use std::fs;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// File inside each backup recording the directory it was taken from
+///
+/// Never copied back out on restore.
+pub const SOURCE_FILE: &str = ".dotfiles-backup-source";
+
+/// Default strftime-style name for a backup directory
+pub const DEFAULT_NAME_FORMAT: &str = "dotfiles-%Y%m%d-%H%M%S";
+
+/// Prefix of backups made before the location was configurable, kept directly in `$HOME`
+pub const LEGACY_PREFIX: &str = ".dotfiles-backup-";
+
+/// Total backup size, in MB, above which doctor suggests pruning
+pub const DEFAULT_MAX_SIZE_MB: u64 = 1024;
+
+/// Number of backups above which doctor suggests pruning
+pub const DEFAULT_MAX_COUNT: usize = 10;
+
+/// Appended to the name of a backup kept as a compressed archive
+pub const ARCHIVE_EXTENSION: &str = ".tar.gz";
+
+/// Appended after [`ARCHIVE_EXTENSION`] when the archive is encrypted with age
+pub const ENCRYPTED_EXTENSION: &str = ".age";
+
+/// age identity file that decrypts backups unless `[backups] age_identity` says otherwise
+pub const DEFAULT_AGE_IDENTITY: &str = ".config/age/key.txt";
+
+/// How `BackupInfo::timestamp` is written, whatever the directory name
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Timestamps found after [`LEGACY_PREFIX`], besides a Unix time
+const LEGACY_TIMESTAMP_FORMATS: &[&str] = &[
+    TIMESTAMP_FORMAT,
+    "%Y%m%d_%H%M%S",
+    "%Y-%m-%d_%H-%M-%S",
+    "%Y-%m-%dT%H-%M-%S",
+];
+
+/// Where backups go and how they are named
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupLayout {
+    pub dir: PathBuf,
+    pub name_format: String,
+    /// Format of new backups; every format is always read
+    pub format: BackupFormat,
+    /// age public key encrypted archives are written to
+    pub age_recipient: Option<String>,
+    /// age identity file that decrypts them
+    pub age_identity: PathBuf,
+}
+
+impl BackupLayout {
+    /// Resolves `[backups]`, falling back to `~/.local/state/dotfiles/backups`
+    pub fn from_settings(settings: &BackupSettings, home_dir: &Path) -> Self {
+        Self {
+            dir: settings
+                .dir
+                .clone()
+                .unwrap_or_else(|| state::state_dir(home_dir).join("backups")),
+            name_format: settings
+                .name
+                .clone()
+                .unwrap_or_else(|| DEFAULT_NAME_FORMAT.to_string()),
+            format: settings.format.unwrap_or_default(),
+            age_recipient: settings.age_recipient.clone(),
+            age_identity: settings
+                .age_identity
+                .clone()
+                .unwrap_or_else(|| home_dir.join(DEFAULT_AGE_IDENTITY)),
+        }
+    }
+
+    /// The layout from the context's config, or the defaults if there is no config file
+    pub fn from_context(context: &AppContext) -> Result<Self> {
+        let settings = if context.config_path.exists() {
+            context.config()?.backups.clone()
+        } else {
+            BackupSettings::default()
+        };
+        Ok(Self::from_settings(&settings, &context.home))
+    }
+
+    /// This layout with backups kept in `dir` instead, keeping its naming, format, and keys
+    pub fn in_dir(&self, dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            ..self.clone()
+        }
+    }
+
+    /// The public key encrypted backups are written to
+    fn recipient(&self) -> Result<age::x25519::Recipient> {
+        let key = self.age_recipient.as_deref().ok_or_else(|| {
+            DotfilesError::Config(
+                "Set [backups] age_recipient to encrypt backups (create a key with: age-keygen -o ~/.config/age/key.txt)"
+                    .to_string(),
+            )
+        })?;
+        key.parse()
+            .map_err(|e| DotfilesError::Config(format!("backups.age_recipient: {}", e)))
+    }
+
+    /// Directory name for a backup taken at `at`
+    pub fn name(&self, at: NaiveDateTime) -> String {
+        let mut name = String::new();
+        if write!(name, "{}", at.format(&self.name_format)).is_err() {
+            name.clear();
+            write!(name, "{}", at.format(DEFAULT_NAME_FORMAT)).ok();
+        }
+        name
+    }
+
+    /// Recovers a backup's timestamp from its directory or archive name
+    ///
+    /// Names in the configured format, the default format, and the legacy
+    /// `.dotfiles-backup-<timestamp>` form are all recognized.
+    pub fn timestamp(&self, name: &str) -> Option<String> {
+        let name = strip_extensions(name);
+        if let Some(timestamp) = name.strip_prefix(LEGACY_PREFIX) {
+            return Some(timestamp.to_string());
+        }
+        self.parse(name)
+            .map(|at| at.format(TIMESTAMP_FORMAT).to_string())
+    }
+
+    /// When a backup was taken, going by its name, in local time
+    ///
+    /// Legacy names may carry one of several older timestamp formats or a
+    /// Unix time; None if the name gives nothing that parses.
+    pub fn taken_at(&self, name: &str) -> Option<NaiveDateTime> {
+        let name = strip_extensions(name);
+        let Some(timestamp) = name.strip_prefix(LEGACY_PREFIX) else {
+            return self.parse(name);
+        };
+        LEGACY_TIMESTAMP_FORMATS
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(timestamp, format).ok())
+            .or_else(|| {
+                let secs = timestamp.parse().ok()?;
+                let utc = DateTime::from_timestamp(secs, 0)?;
+                Some(utc.with_timezone(&Local).naive_local())
+            })
+    }
+
+    fn parse(&self, name: &str) -> Option<NaiveDateTime> {
+        [self.name_format.as_str(), DEFAULT_NAME_FORMAT]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(name, format).ok())
+    }
+}
+
+fn strip_extensions(name: &str) -> &str {
+    let name = name.strip_suffix(ENCRYPTED_EXTENSION).unwrap_or(name);
+    name.strip_suffix(ARCHIVE_EXTENSION).unwrap_or(name)
+}
+
+/// Explains why a `[backups] name` can't name backups, or None if it can
+///
+/// The name has to encode the full date and time, so that it can be parsed
+/// back to sort backups, and must not contain a path separator.
+pub fn name_format_problem(format: &str) -> Option<String> {
+    let now = Local::now().naive_local();
+    let mut name = String::new();
+    if write!(name, "{}", now.format(format)).is_err() {
+        return Some(format!("{} is not a valid strftime format", format));
+    }
+    if name.contains('/') {
+        return Some(format!("{} would create nested directories", format));
+    }
+    if NaiveDateTime::parse_from_str(&name, format).is_err() {
+        return Some(format!(
+            "{} doesn't include the full date and time (down to seconds)",
+            format
+        ));
+    }
+    None
+}
+
+/// How many backups, and how much space, to keep before pruning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupLimits {
+    pub max_count: usize,
+    pub max_bytes: u64,
+}
+
+impl BackupLimits {
+    /// Resolves `[backups] max_count` and `max_size_mb`, falling back to the defaults
+    pub fn from_settings(settings: &BackupSettings) -> Self {
+        Self {
+            max_count: settings.max_count.unwrap_or(DEFAULT_MAX_COUNT),
+            max_bytes: settings.max_size_mb.unwrap_or(DEFAULT_MAX_SIZE_MB) * 1024 * 1024,
+        }
+    }
+
+    /// How many of the newest backups fit within the limits, given sizes newest first
+    ///
+    /// The newest backup is always kept, however large.
+    pub fn keep(&self, sizes: &[u64]) -> usize {
+        let mut total = 0;
+        let mut keep = 0;
+        for size in sizes.iter().take(self.max_count.max(1)) {
+            total += size;
+            if keep > 0 && total > self.max_bytes {
+                break;
+            }
+            keep += 1;
+        }
+        keep
+    }
+}
 
 /// Backup metadata
 #[derive(Debug, Clone)]
 pub struct BackupInfo {
     pub path: PathBuf,
+    /// Identifies the backup on the command line (`YYYYMMDD-HHMMSS` for most)
     pub timestamp: String,
+    /// When the backup was taken, from its name or else its modification time
+    pub created: DateTime<Local>,
+    /// Directory the backup was taken from (empty if not recorded)
     pub source: PathBuf,
 }
 
 impl BackupInfo {
     /// Parses a backup directory name to extract timestamp
-    pub fn from_path(path: PathBuf, source: PathBuf) -> Option<Self> {
-        let dir_name = path.file_name()?.to_str()?;
-
-        if dir_name.starts_with(".dotfiles-backup-") {
-            let timestamp = dir_name.strip_prefix(".dotfiles-backup-")?.to_string();
-            Some(Self {
-                path,
-                timestamp,
-                source,
-            })
-        } else {
-            None
-        }
+    ///
+    /// A backup whose name doesn't parse (renamed, or written under another
+    /// `name_format`) is still recognized by its recorded source or archive
+    /// format, and dated by its modification time.
+    pub fn from_path(path: PathBuf, source: PathBuf, layout: &BackupLayout) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let modified = || -> Option<DateTime<Local>> {
+            Some(DateTime::from(
+                fs::symlink_metadata(&path).ok()?.modified().ok()?,
+            ))
+        };
+        let (timestamp, created) = match layout.timestamp(name) {
+            Some(timestamp) => {
+                // Ambiguous local times (the hour a DST change repeats) take the earlier one
+                let created = layout
+                    .taken_at(name)
+                    .and_then(|at| Local.from_local_datetime(&at).earliest())
+                    .or_else(modified)
+                    .unwrap_or_else(|| DateTime::from(UNIX_EPOCH));
+                (timestamp, created)
+            }
+            None if !source.as_os_str().is_empty() || is_archive(&path) => {
+                let created = modified()?;
+                (created.format(TIMESTAMP_FORMAT).to_string(), created)
+            }
+            None => return None,
+        };
+        Some(Self {
+            path,
+            timestamp,
+            created,
+            source,
+        })
+    }
+
+    /// Local time the backup was taken and how long before `now` (`... (2 days ago)`)
+    pub fn when(&self, now: DateTime<Local>) -> String {
+        format!(
+            "{} ({})",
+            self.created.format("%Y-%m-%d %H:%M:%S"),
+            format_age(now - self.created)
+        )
     }
+
+    /// Returns true if the backup is a `.tar.gz` archive rather than a directory
+    pub fn is_archive(&self) -> bool {
+        is_archive(&self.path)
+    }
+
+    /// Returns true if the backup is an archive encrypted with age
+    pub fn is_encrypted(&self) -> bool {
+        is_encrypted(&self.path)
+    }
+}
+
+/// Returns true for the files a backup keeps about itself, which are never restored
+pub(crate) fn is_record(name: &OsStr) -> bool {
+    name == SOURCE_FILE || name == incremental::MANIFEST_FILE
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.is_file() && {
+        let name = path.to_string_lossy();
+        name.ends_with(ARCHIVE_EXTENSION) || is_encrypted(path)
+    }
+}
+
+fn is_encrypted(path: &Path) -> bool {
+    path.to_string_lossy()
+        .ends_with(&format!("{}{}", ARCHIVE_EXTENSION, ENCRYPTED_EXTENSION))
+}
+
+/// A backup `create_backup_in` just wrote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreatedBackup {
+    pub path: PathBuf,
+    /// Files copied and hard-linked, for incremental backups
+    pub incremental: Option<incremental::IncrementalStats>,
 }
 
-/// Creates a timestamped backup of a directory
-pub fn create_backup(source: &Path, backup_dir: Option<&Path>) -> Result<PathBuf> {
+/// Creates a timestamped backup of a directory in `layout`'s directory and format
+///
+/// Prints nothing; callers report the result in their own words.
+pub fn create_backup_in(source: &Path, layout: &BackupLayout) -> Result<CreatedBackup> {
     if !source.exists() {
         return Err(DotfilesError::Config(format!(
             "Source directory does not exist: {:?}",
@@ -41,42 +340,105 @@ pub fn create_backup(source: &Path, backup_dir: Option<&Path>) -> Result<PathBuf
         )));
     }
 
-    // Generate timestamp
-    let timestamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
-
-    // Determine backup location
-    let backup_parent = if let Some(dir) = backup_dir {
-        dir.to_path_buf()
-    } else {
-        dirs::home_dir().ok_or_else(|| {
-            DotfilesError::Config("Could not determine home directory".to_string())
-        })?
+    let name = layout.name(Local::now().naive_local());
+    let mut incremental_stats = None;
+    let resolved = source
+        .canonicalize()
+        .unwrap_or_else(|_| source.to_path_buf());
+
+    let backup_path = match layout.format {
+        BackupFormat::Directory => {
+            let backup_path = layout.dir.join(name);
+            fs::create_dir_all(&backup_path)?;
+            copy_dir_recursive(source, &backup_path)?;
+            fs::write(
+                backup_path.join(SOURCE_FILE),
+                resolved.to_string_lossy().as_bytes(),
+            )?;
+            backup_path
+        }
+        BackupFormat::Incremental => {
+            let backup_path = layout.dir.join(name);
+            let previous = list_in(layout)?
+                .into_iter()
+                .find(|backup| incremental::is_incremental(&backup.path));
+            let written = incremental::create(
+                source,
+                &backup_path,
+                previous.as_ref().map(|backup| backup.path.as_path()),
+            )
+            .and_then(|stats| {
+                fs::write(
+                    backup_path.join(SOURCE_FILE),
+                    resolved.to_string_lossy().as_bytes(),
+                )?;
+                Ok(stats)
+            });
+            match written {
+                Ok(stats) => incremental_stats = Some(stats),
+                Err(e) => {
+                    let _ = fs::remove_dir_all(&backup_path);
+                    return Err(e);
+                }
+            }
+            backup_path
+        }
+        BackupFormat::Archive => {
+            let backup_path = layout.dir.join(format!("{}{}", name, ARCHIVE_EXTENSION));
+            fs::create_dir_all(&layout.dir)?;
+            let written = File::create(&backup_path)
+                .map_err(DotfilesError::from)
+                .and_then(|file| write_archive(source, &resolved, file));
+            if let Err(e) = written {
+                let _ = fs::remove_file(&backup_path);
+                return Err(e);
+            }
+            backup_path
+        }
+        BackupFormat::EncryptedArchive => {
+            let recipient = layout.recipient()?;
+            let backup_path = layout.dir.join(format!(
+                "{}{}{}",
+                name, ARCHIVE_EXTENSION, ENCRYPTED_EXTENSION
+            ));
+            fs::create_dir_all(&layout.dir)?;
+            if let Err(e) = write_encrypted_archive(source, &resolved, &backup_path, &recipient) {
+                let _ = fs::remove_file(&backup_path);
+                return Err(e);
+            }
+            backup_path
+        }
     };
+    let source = resolved;
 
-    let backup_name = format!(".dotfiles-backup-{}", timestamp);
-    let backup_path = backup_parent.join(backup_name);
+    audit::record(Action::FileCopied {
+        from: source,
+        to: backup_path.clone(),
+    });
 
-    // Create backup directory
-    fs::create_dir_all(&backup_path)?;
-
-    // Copy contents
-    copy_dir_recursive(source, &backup_path)?;
-
-    println!("✓ Created backup at {:?}", backup_path);
-
-    Ok(backup_path)
+    Ok(CreatedBackup {
+        path: backup_path,
+        incremental: incremental_stats,
+    })
 }
 
-/// Copies a directory recursively
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
+/// Copies a directory recursively, leaving out the backup's own records
+pub(crate) fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     if !dst.exists() {
         fs::create_dir_all(dst)?;
     }
 
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
+    // A backup must be complete, so ignore rules are off
+    for src_path in RepoWalker::new(src)
+        .respect_ignores(false)
+        .max_depth(1)
+        .entries()?
+    {
+        let name = src_path.file_name().unwrap_or_default();
+        if is_record(name) {
+            continue;
+        }
+        let dst_path = dst.join(name);
 
         if src_path.is_dir() {
             copy_dir_recursive(&src_path, &dst_path)?;
@@ -88,47 +450,260 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Lists all backups in a directory
-pub fn list_backups(backup_dir: Option<&Path>) -> Result<Vec<BackupInfo>> {
-    let search_dir = if let Some(dir) = backup_dir {
-        dir.to_path_buf()
+/// Writes `src` to a gzip-compressed tarball, recording `source` as where it came from
+///
+/// Returns `out` once the tarball is complete.
+fn write_archive<W: io::Write>(src: &Path, source: &Path, out: W) -> Result<W> {
+    let mut builder = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+
+    // The source record goes first so listing backups reads a single entry
+    let record = source.to_string_lossy();
+    let mut header = tar::Header::new_gnu();
+    header.set_size(record.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, SOURCE_FILE, record.as_bytes())?;
+
+    append_dir_recursive(&mut builder, src, Path::new(""))?;
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn write_encrypted_archive(
+    src: &Path,
+    source: &Path,
+    archive_path: &Path,
+    recipient: &age::x25519::Recipient,
+) -> Result<()> {
+    let encryptor =
+        age::Encryptor::with_recipients(std::iter::once(recipient as &dyn age::Recipient))
+            .map_err(|e| DotfilesError::Config(format!("Can't encrypt backup: {}", e)))?;
+    let out = encryptor.wrap_output(File::create(archive_path)?)?;
+    write_archive(src, source, out)?.finish()?;
+    Ok(())
+}
+
+/// Opens an archived backup, decrypting it with the `identity` file if it is encrypted
+fn open_archive(
+    archive_path: &Path,
+    identity: &Path,
+) -> Result<tar::Archive<GzDecoder<Box<dyn Read>>>> {
+    let file = File::open(archive_path)?;
+    let reader: Box<dyn Read> = if is_encrypted(archive_path) {
+        Box::new(decrypt(file, identity)?)
     } else {
-        dirs::home_dir().ok_or_else(|| {
-            DotfilesError::Config("Could not determine home directory".to_string())
-        })?
+        Box::new(file)
+    };
+    Ok(tar::Archive::new(GzDecoder::new(reader)))
+}
+
+fn decrypt(file: File, identity: &Path) -> Result<impl Read> {
+    let failed = |e: &dyn std::fmt::Display| {
+        DotfilesError::Config(format!(
+            "Can't decrypt backup with {}: {}",
+            identity.display(),
+            e
+        ))
     };
+    let identities = age::IdentityFile::from_file(identity.to_string_lossy().into_owned())
+        .map_err(|e| failed(&e))?
+        .into_identities()
+        .map_err(|e| failed(&e))?;
+    age::Decryptor::new(io::BufReader::new(file))
+        .and_then(|decryptor| {
+            decryptor.decrypt(identities.iter().map(|identity| identity.as_ref() as _))
+        })
+        .map_err(|e| failed(&e))
+}
+
+/// Adds a directory to an archive the way [`copy_dir_recursive`] copies it
+fn append_dir_recursive<W: io::Write>(
+    builder: &mut tar::Builder<W>,
+    src: &Path,
+    rel: &Path,
+) -> Result<()> {
+    for src_path in RepoWalker::new(src)
+        .respect_ignores(false)
+        .max_depth(1)
+        .entries()?
+    {
+        let name = src_path.file_name().unwrap_or_default();
+        if is_record(name) {
+            continue;
+        }
+        let rel_path = rel.join(name);
+
+        if src_path.is_dir() {
+            builder.append_dir(&rel_path, &src_path)?;
+            append_dir_recursive(builder, &src_path, &rel_path)?;
+        } else {
+            builder.append_path_with_name(&src_path, &rel_path)?;
+        }
+    }
+
+    Ok(())
+}
 
-    if !search_dir.exists() {
+/// Reads the source recorded at the start of an archive (empty if there is none)
+fn archive_source(archive_path: &Path, identity: &Path) -> PathBuf {
+    let read = || -> Result<Option<String>> {
+        let mut archive = open_archive(archive_path, identity)?;
+        let Some(entry) = archive.entries()?.next() else {
+            return Ok(None);
+        };
+        let mut entry = entry?;
+        if entry.path()?.as_ref() != Path::new(SOURCE_FILE) {
+            return Ok(None);
+        }
+        let mut recorded = String::new();
+        entry.read_to_string(&mut recorded)?;
+        Ok(Some(recorded))
+    };
+    read()
+        .ok()
+        .flatten()
+        .map(|recorded| PathBuf::from(recorded.trim()))
+        .unwrap_or_default()
+}
+
+/// Extracts an archived backup into `target`, leaving out its source record
+///
+/// `identity` is the age identity file, used only for encrypted archives.
+pub fn unpack(archive_path: &Path, target: &Path, identity: &Path) -> Result<()> {
+    let mut archive = open_archive(archive_path, identity)?;
+    fs::create_dir_all(target)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.path()?.as_ref() == Path::new(SOURCE_FILE) {
+            continue;
+        }
+        entry.unpack_in(target)?;
+    }
+    Ok(())
+}
+
+/// Deletes a backup of either format
+pub(crate) fn remove_backup(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Lists the backups in `layout`'s directory, newest first
+pub fn list_in(layout: &BackupLayout) -> Result<Vec<BackupInfo>> {
+    if !layout.dir.exists() {
         return Ok(Vec::new());
     }
 
     let mut backups = Vec::new();
 
-    for entry in fs::read_dir(search_dir)? {
+    for entry in fs::read_dir(&layout.dir)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.is_dir() {
-            if let Some(backup) = BackupInfo::from_path(path, PathBuf::new()) {
-                backups.push(backup);
-            }
+        let source = if path.is_dir() {
+            fs::read_to_string(path.join(SOURCE_FILE))
+                .map(|recorded| PathBuf::from(recorded.trim()))
+                .unwrap_or_default()
+        } else if is_archive(&path) {
+            archive_source(&path, &layout.age_identity)
+        } else {
+            continue;
+        };
+        if let Some(backup) = BackupInfo::from_path(path, source, layout) {
+            backups.push(backup);
         }
     }
 
-    // Sort by timestamp (newest first)
-    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    sort_newest_first(&mut backups);
 
     Ok(backups)
 }
 
-/// Gets the most recent backup
-pub fn get_latest_backup(backup_dir: Option<&Path>) -> Result<Option<BackupInfo>> {
-    let backups = list_backups(backup_dir)?;
+/// Sorts backups by when they were taken, newest first
+fn sort_newest_first(backups: &mut [BackupInfo]) {
+    backups.sort_by(|a, b| {
+        b.created
+            .cmp(&a.created)
+            .then_with(|| b.timestamp.cmp(&a.timestamp))
+    });
+}
+
+/// Lists backups left in `$HOME` under the old `.dotfiles-backup-<timestamp>` names
+pub fn legacy_backups(home_dir: &Path, layout: &BackupLayout) -> Result<Vec<BackupInfo>> {
+    let legacy = layout.in_dir(home_dir);
+    Ok(list_in(&legacy)?
+        .into_iter()
+        .filter(|backup| {
+            !backup.is_archive()
+                && backup
+                    .path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with(LEGACY_PREFIX))
+        })
+        .collect())
+}
+
+/// Moves legacy backups from `$HOME` into `layout.dir`, renaming them to its format
+///
+/// Returns the (old, new) path of each backup moved. A backup whose new name
+/// is already taken is left where it is.
+pub fn migrate_legacy_backups(
+    home_dir: &Path,
+    layout: &BackupLayout,
+) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut moved = Vec::new();
+    for backup in legacy_backups(home_dir, layout)? {
+        let Ok(at) = NaiveDateTime::parse_from_str(&backup.timestamp, TIMESTAMP_FORMAT) else {
+            continue;
+        };
+        let destination = layout.dir.join(layout.name(at));
+        if destination.exists() {
+            continue;
+        }
+        fs::create_dir_all(&layout.dir)?;
+        if fs::rename(&backup.path, &destination).is_err() {
+            // Across filesystems: copy, then remove the original
+            copy_dir_recursive(&backup.path, &destination)?;
+            if let Ok(source) = fs::read(backup.path.join(SOURCE_FILE)) {
+                fs::write(destination.join(SOURCE_FILE), source)?;
+            }
+            fs::remove_dir_all(&backup.path)?;
+        }
+        audit::record(Action::FileMoved {
+            from: backup.path.clone(),
+            to: destination.clone(),
+        });
+        moved.push((backup.path, destination));
+    }
+    Ok(moved)
+}
+
+/// Gets the most recent backup in `layout`'s directory
+pub fn get_latest_backup(layout: &BackupLayout) -> Result<Option<BackupInfo>> {
+    let backups = list_in(layout)?;
     Ok(backups.into_iter().next())
 }
 
-/// Restores from a backup
-pub fn restore_backup(backup: &BackupInfo, target: &Path) -> Result<()> {
+/// Finds a backup by its timestamp or directory name
+pub fn find_backup<'a>(backups: &'a [BackupInfo], query: &str) -> Option<&'a BackupInfo> {
+    backups.iter().find(|backup| {
+        backup.timestamp == query || backup.path.file_name().is_some_and(|name| name == query)
+    })
+}
+
+/// Restores from a backup, replacing `target`
+///
+/// The current contents of `target` are backed up into `layout` first;
+/// returns that safety backup's path, or None if `target` didn't exist.
+/// Encrypted backups are decrypted with `layout.age_identity`.
+pub fn restore_backup(
+    backup: &BackupInfo,
+    target: &Path,
+    layout: &BackupLayout,
+) -> Result<Option<PathBuf>> {
     if !backup.path.exists() {
         return Err(DotfilesError::Config(format!(
             "Backup does not exist: {:?}",
@@ -136,49 +711,116 @@ pub fn restore_backup(backup: &BackupInfo, target: &Path) -> Result<()> {
         )));
     }
 
-    if target.exists() {
-        // Create a backup of the current state before restoring
-        create_backup(target, None)?;
+    // Clearing a directory that holds the backups would delete them too
+    let resolved = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if resolved(&layout.dir).starts_with(resolved(target))
+        || resolved(&backup.path).starts_with(resolved(target))
+    {
+        return Err(DotfilesError::Config(format!(
+            "Backups are kept inside {}; restore into a directory that doesn't contain them",
+            target.display()
+        )));
+    }
+
+    // Fail on a missing or wrong key, or a damaged backup, before anything is touched
+    if backup.is_encrypted() {
+        open_archive(&backup.path, &layout.age_identity)?;
+    }
+    if incremental::is_incremental(&backup.path) {
+        incremental::check(&backup.path)?;
     }
 
+    // Create a backup of the current state before restoring
+    let safety = if target.exists() {
+        Some(create_backup_in(target, layout)?.path)
+    } else {
+        None
+    };
+
     // Clear target directory
     if target.exists() {
         fs::remove_dir_all(target)?;
     }
 
     // Restore from backup
-    copy_dir_recursive(&backup.path, target)?;
+    if backup.is_archive() {
+        unpack(&backup.path, target, &layout.age_identity)?;
+    } else if incremental::is_incremental(&backup.path) {
+        incremental::restore(&backup.path, target)?;
+    } else {
+        copy_dir_recursive(&backup.path, target)?;
+    }
+    audit::record(Action::FileCopied {
+        from: backup.path.clone(),
+        to: target.to_path_buf(),
+    });
 
     println!("✓ Restored from backup: {}", backup.timestamp);
 
-    Ok(())
+    Ok(safety)
 }
 
 /// Verifies that a backup is valid
-pub fn verify_backup(backup_path: &Path) -> Result<bool> {
+///
+/// An archive has to decompress cleanly all the way through (with
+/// `layout.age_identity` if encrypted), and every file in an incremental
+/// backup's manifest has to be there unchanged.
+pub fn verify_backup(backup_path: &Path, layout: &BackupLayout) -> Result<bool> {
     if !backup_path.exists() {
         return Ok(false);
     }
 
+    if is_archive(backup_path) {
+        return Ok(archive_has_contents(backup_path, &layout.age_identity).unwrap_or(false));
+    }
+
     if !backup_path.is_dir() {
         return Ok(false);
     }
 
+    if incremental::is_incremental(backup_path) {
+        return Ok(incremental::check(backup_path).is_ok());
+    }
+
     // Check if backup has any contents
-    let entries = fs::read_dir(backup_path)?;
-    let has_contents = entries.count() > 0;
+    let has_contents = fs::read_dir(backup_path)?
+        .flatten()
+        .any(|entry| entry.file_name() != SOURCE_FILE);
+
+    Ok(has_contents)
+}
 
+fn archive_has_contents(archive_path: &Path, identity: &Path) -> Result<bool> {
+    let mut archive = open_archive(archive_path, identity)?;
+    let mut has_contents = false;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        has_contents |= entry.path()?.as_ref() != Path::new(SOURCE_FILE);
+        io::copy(&mut entry, &mut io::sink())?;
+    }
+    // Reading to the end checks the gzip trailer's CRC
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
     Ok(has_contents)
 }
 
-/// Deletes old backups, keeping only the N most recent
-pub fn cleanup_old_backups(keep: usize, backup_dir: Option<&Path>) -> Result<Vec<PathBuf>> {
-    let backups = list_backups(backup_dir)?;
+/// Lists the configured directory's backups and legacy ones in `$HOME`, newest first
+pub fn all_backups(home_dir: &Path, layout: &BackupLayout) -> Result<Vec<BackupInfo>> {
+    let mut backups = list_in(layout)?;
+    if layout.dir != home_dir {
+        backups.extend(legacy_backups(home_dir, layout)?);
+    }
+    sort_newest_first(&mut backups);
+    Ok(backups)
+}
+
+/// Deletes old backups in `layout`'s directory, keeping only the N most recent
+pub fn cleanup_old_backups(keep: usize, layout: &BackupLayout) -> Result<Vec<PathBuf>> {
+    let backups = list_in(layout)?;
     let mut deleted = Vec::new();
 
     for backup in backups.iter().skip(keep) {
         if backup.path.exists() {
-            fs::remove_dir_all(&backup.path)?;
+            remove_backup(&backup.path)?;
             deleted.push(backup.path.clone());
             println!("✓ Deleted old backup: {}", backup.timestamp);
         }
@@ -193,10 +835,16 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    /// The default layout, with backups kept in `dir`
+    pub(crate) fn test_layout(dir: &Path, home_dir: &Path) -> BackupLayout {
+        BackupLayout::from_settings(&BackupSettings::default(), home_dir).in_dir(dir)
+    }
+
     #[test]
     fn test_backup_info_from_path() {
+        let layout = test_layout(Path::new("/home/user"), Path::new("/home/user"));
         let path = PathBuf::from("/home/user/.dotfiles-backup-20260129-143022");
-        let backup = BackupInfo::from_path(path.clone(), PathBuf::new());
+        let backup = BackupInfo::from_path(path.clone(), PathBuf::new(), &layout);
 
         assert!(backup.is_some());
         let backup = backup.unwrap();
@@ -206,12 +854,33 @@ mod tests {
 
     #[test]
     fn test_backup_info_from_invalid_path() {
+        let layout = test_layout(Path::new("/home/user"), Path::new("/home/user"));
         let path = PathBuf::from("/home/user/not-a-backup");
-        let backup = BackupInfo::from_path(path, PathBuf::new());
+        let backup = BackupInfo::from_path(path, PathBuf::new(), &layout);
 
         assert!(backup.is_none());
     }
 
+    #[test]
+    fn test_backup_info_unparsed_name_falls_back_to_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
+        let path = temp_dir.path().join("before-upgrade");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join(SOURCE_FILE), "/home/user/dotfiles").unwrap();
+
+        let backup =
+            BackupInfo::from_path(path.clone(), PathBuf::from("/home/user/dotfiles"), &layout)
+                .unwrap();
+        let modified = DateTime::<Local>::from(fs::metadata(&path).unwrap().modified().unwrap());
+        assert_eq!(backup.created, modified);
+        assert_eq!(
+            backup.timestamp,
+            modified.format(TIMESTAMP_FORMAT).to_string()
+        );
+        assert_eq!(list_in(&layout).unwrap().len(), 1);
+    }
+
     #[test]
     fn test_create_backup() {
         let temp_dir = TempDir::new().unwrap();
@@ -226,7 +895,8 @@ mod tests {
         fs::create_dir(&backup_parent).unwrap();
 
         // Create backup
-        let backup_path = create_backup(&source_dir, Some(&backup_parent)).unwrap();
+        let layout = test_layout(&backup_parent, temp_dir.path());
+        let backup_path = create_backup_in(&source_dir, &layout).unwrap().path;
 
         // Verify backup exists
         assert!(backup_path.exists());
@@ -238,6 +908,169 @@ mod tests {
 
         let content1 = fs::read_to_string(backup_path.join("file1.txt")).unwrap();
         assert_eq!(content1, "content1");
+
+        // Source is recorded and read back by list_backups
+        let listed = list_in(&layout).unwrap();
+        assert_eq!(listed[0].source, source_dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_archive_backup_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        fs::create_dir_all(source_dir.join(".config/nvim")).unwrap();
+        fs::write(source_dir.join(".zshrc"), "export A=1").unwrap();
+        fs::write(source_dir.join(".config/nvim/init.lua"), "vim.o.nu = true").unwrap();
+
+        let layout = BackupLayout {
+            format: BackupFormat::Archive,
+            ..test_layout(&temp_dir.path().join("backups"), temp_dir.path())
+        };
+        let archive = create_backup_in(&source_dir, &layout).unwrap().path;
+        assert!(archive.is_file());
+        assert!(archive.to_string_lossy().ends_with(ARCHIVE_EXTENSION));
+        assert!(verify_backup(&archive, &layout).unwrap());
+
+        // Listed next to directory backups, with its source read from the archive
+        fs::create_dir_all(layout.dir.join("dotfiles-20000101-000000")).unwrap();
+        let listed = list_in(&layout).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed[0].is_archive());
+        assert_eq!(listed[0].source, source_dir.canonicalize().unwrap());
+
+        let target = temp_dir.path().join("target");
+        unpack(&archive, &target, Path::new("")).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join(".config/nvim/init.lua")).unwrap(),
+            "vim.o.nu = true"
+        );
+        assert!(!target.join(SOURCE_FILE).exists());
+
+        // A truncated archive no longer verifies
+        let bytes = fs::read(&archive).unwrap();
+        fs::write(&archive, &bytes[..bytes.len() - 8]).unwrap();
+        assert!(!verify_backup(&archive, &layout).unwrap());
+
+        remove_backup(&archive).unwrap();
+        assert_eq!(list_in(&layout).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_encrypted_backup_round_trip() {
+        use age::secrecy::ExposeSecret;
+
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join(".env"), "GITHUB_TOKEN=ghp_secret").unwrap();
+
+        let key = age::x25519::Identity::generate();
+        let identity = temp_dir.path().join("key.txt");
+        fs::write(&identity, key.to_string().expose_secret()).unwrap();
+        let layout = BackupLayout {
+            format: BackupFormat::EncryptedArchive,
+            age_recipient: Some(key.to_public().to_string()),
+            age_identity: identity.clone(),
+            ..test_layout(&temp_dir.path().join("backups"), temp_dir.path())
+        };
+
+        let archive = create_backup_in(&source_dir, &layout).unwrap().path;
+        assert!(archive.to_string_lossy().ends_with(".tar.gz.age"));
+        assert!(!fs::read(&archive)
+            .unwrap()
+            .windows(10)
+            .any(|window| window == b"ghp_secret"));
+
+        let listed = list_in(&layout).unwrap();
+        assert!(listed[0].is_encrypted());
+        assert_eq!(listed[0].source, source_dir.canonicalize().unwrap());
+        assert!(archive_has_contents(&archive, &identity).unwrap());
+
+        let target = temp_dir.path().join("target");
+        unpack(&archive, &target, &identity).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join(".env")).unwrap(),
+            "GITHUB_TOKEN=ghp_secret"
+        );
+
+        // Another key can't read it, and a layout without a recipient can't write one
+        let other = temp_dir.path().join("other.txt");
+        let other_key = age::x25519::Identity::generate();
+        fs::write(&other, other_key.to_string().expose_secret()).unwrap();
+        assert!(unpack(&archive, &temp_dir.path().join("nope"), &other).is_err());
+        let unkeyed = BackupLayout {
+            age_recipient: None,
+            ..layout
+        };
+        assert!(create_backup_in(&source_dir, &unkeyed).is_err());
+    }
+
+    #[test]
+    fn test_backup_layout_names() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings = BackupSettings {
+            dir: None,
+            name: Some("snapshot_%Y-%m-%d_%H%M%S".to_string()),
+            ..Default::default()
+        };
+        let layout = BackupLayout::from_settings(&settings, temp_dir.path());
+        assert_eq!(
+            layout.dir,
+            state::state_dir(temp_dir.path()).join("backups")
+        );
+
+        let at = NaiveDateTime::parse_from_str("20260129-143022", TIMESTAMP_FORMAT).unwrap();
+        assert_eq!(layout.name(at), "snapshot_2026-01-29_143022");
+        for name in [
+            "snapshot_2026-01-29_143022",
+            "dotfiles-20260129-143022",
+            ".dotfiles-backup-20260129-143022",
+            "dotfiles-20260129-143022.tar.gz",
+            "dotfiles-20260129-143022.tar.gz.age",
+        ] {
+            assert_eq!(layout.timestamp(name).as_deref(), Some("20260129-143022"));
+        }
+        assert_eq!(layout.timestamp("snapshot_notes"), None);
+
+        // Legacy names in older formats still get a time; unparseable ones keep their name
+        let expected = NaiveDateTime::parse_from_str("20260129-143022", TIMESTAMP_FORMAT).ok();
+        for name in [
+            ".dotfiles-backup-20260129_143022",
+            ".dotfiles-backup-2026-01-29_14-30-22",
+        ] {
+            assert_eq!(layout.taken_at(name), expected);
+        }
+        assert!(layout.taken_at(".dotfiles-backup-1769697022").is_some());
+        assert_eq!(layout.taken_at(".dotfiles-backup-old"), None);
+        assert_eq!(
+            layout.timestamp(".dotfiles-backup-old").as_deref(),
+            Some("old")
+        );
+
+        assert_eq!(name_format_problem(DEFAULT_NAME_FORMAT), None);
+        assert!(name_format_problem("backup-%Y%m%d").is_some());
+        assert!(name_format_problem("%Y/%m/%d-%H%M%S").is_some());
+        assert!(name_format_problem("backup-%Q").is_some());
+    }
+
+    #[test]
+    fn test_migrate_legacy_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        let legacy = home.join(".dotfiles-backup-20260129-120000");
+        fs::create_dir(&legacy).unwrap();
+        fs::write(legacy.join("file.txt"), "content").unwrap();
+        fs::create_dir(home.join("not-a-backup")).unwrap();
+
+        let layout = test_layout(&home.join(".local/state/dotfiles/backups"), home);
+        let moved = migrate_legacy_backups(home, &layout).unwrap();
+
+        let destination = layout.dir.join("dotfiles-20260129-120000");
+        assert_eq!(moved, vec![(legacy.clone(), destination.clone())]);
+        assert!(!legacy.exists());
+        assert!(destination.join("file.txt").exists());
+        assert!(legacy_backups(home, &layout).unwrap().is_empty());
+        assert_eq!(list_in(&layout).unwrap()[0].timestamp, "20260129-120000");
     }
 
     #[test]
@@ -246,7 +1079,7 @@ mod tests {
         let source_dir = temp_dir.path().join("nonexistent");
         let backup_parent = temp_dir.path();
 
-        let result = create_backup(&source_dir, Some(backup_parent));
+        let result = create_backup_in(&source_dir, &test_layout(backup_parent, backup_parent));
         assert!(result.is_err());
     }
 
@@ -276,13 +1109,14 @@ mod tests {
     #[test]
     fn test_list_backups() {
         let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
 
         // Create some backup directories
         fs::create_dir(temp_dir.path().join(".dotfiles-backup-20260129-120000")).unwrap();
         fs::create_dir(temp_dir.path().join(".dotfiles-backup-20260129-130000")).unwrap();
         fs::create_dir(temp_dir.path().join("not-a-backup")).unwrap();
 
-        let backups = list_backups(Some(temp_dir.path())).unwrap();
+        let backups = list_in(&layout).unwrap();
 
         assert_eq!(backups.len(), 2);
         // Should be sorted newest first
@@ -290,15 +1124,36 @@ mod tests {
         assert_eq!(backups[1].timestamp, "20260129-120000");
     }
 
+    #[test]
+    fn test_list_backups_sorts_chronologically() {
+        let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
+        // A legacy name in an older format sorts as a string before both others
+        fs::create_dir(temp_dir.path().join("dotfiles-20260129-120000")).unwrap();
+        fs::create_dir(temp_dir.path().join(".dotfiles-backup-2026-01-30_08-00-00")).unwrap();
+        fs::write(temp_dir.path().join("dotfiles-20260101-090000.tar.gz"), "").unwrap();
+
+        let backups = list_in(&layout).unwrap();
+        let timestamps: Vec<&str> = backups.iter().map(|b| b.timestamp.as_str()).collect();
+        assert_eq!(
+            timestamps,
+            vec!["2026-01-30_08-00-00", "20260129-120000", "20260101-090000"]
+        );
+
+        let now = backups[1].created + chrono::TimeDelta::days(2);
+        assert_eq!(backups[1].when(now), "2026-01-29 12:00:00 (2 days ago)");
+    }
+
     #[test]
     fn test_get_latest_backup() {
         let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
 
         // Create backups
         fs::create_dir(temp_dir.path().join(".dotfiles-backup-20260129-120000")).unwrap();
         fs::create_dir(temp_dir.path().join(".dotfiles-backup-20260129-130000")).unwrap();
 
-        let latest = get_latest_backup(Some(temp_dir.path())).unwrap();
+        let latest = get_latest_backup(&layout).unwrap();
 
         assert!(latest.is_some());
         assert_eq!(latest.unwrap().timestamp, "20260129-130000");
@@ -307,32 +1162,35 @@ mod tests {
     #[test]
     fn test_get_latest_backup_none() {
         let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
 
-        let latest = get_latest_backup(Some(temp_dir.path())).unwrap();
+        let latest = get_latest_backup(&layout).unwrap();
         assert!(latest.is_none());
     }
 
     #[test]
     fn test_verify_backup() {
         let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
         let backup_dir = temp_dir.path().join("backup");
 
         // Empty directory - invalid
         fs::create_dir(&backup_dir).unwrap();
-        assert!(!verify_backup(&backup_dir).unwrap());
+        assert!(!verify_backup(&backup_dir, &layout).unwrap());
 
         // Directory with contents - valid
         fs::write(backup_dir.join("file.txt"), "content").unwrap();
-        assert!(verify_backup(&backup_dir).unwrap());
+        assert!(verify_backup(&backup_dir, &layout).unwrap());
 
         // Nonexistent - invalid
         let nonexistent = temp_dir.path().join("nonexistent");
-        assert!(!verify_backup(&nonexistent).unwrap());
+        assert!(!verify_backup(&nonexistent, &layout).unwrap());
     }
 
     #[test]
     fn test_cleanup_old_backups() {
         let temp_dir = TempDir::new().unwrap();
+        let layout = test_layout(temp_dir.path(), temp_dir.path());
 
         // Create 5 backups
         for i in 1..=5 {
@@ -341,17 +1199,54 @@ mod tests {
         }
 
         // Keep only 2 most recent
-        let deleted = cleanup_old_backups(2, Some(temp_dir.path())).unwrap();
+        let deleted = cleanup_old_backups(2, &layout).unwrap();
 
         assert_eq!(deleted.len(), 3);
 
         // Verify only 2 remain
-        let remaining = list_backups(Some(temp_dir.path())).unwrap();
+        let remaining = list_in(&layout).unwrap();
         assert_eq!(remaining.len(), 2);
         assert_eq!(remaining[0].timestamp, "20260125-120000");
         assert_eq!(remaining[1].timestamp, "20260124-120000");
     }
 
+    #[test]
+    fn test_backup_limits_keep() {
+        const MB: u64 = 1024 * 1024;
+        let limits = BackupLimits {
+            max_count: 3,
+            max_bytes: 100 * MB,
+        };
+        assert_eq!(limits.keep(&[]), 0);
+        assert_eq!(limits.keep(&[10 * MB; 5]), 3);
+        assert_eq!(limits.keep(&[60 * MB, 30 * MB, 20 * MB]), 2);
+        assert_eq!(limits.keep(&[500 * MB, MB]), 1);
+        assert_eq!(
+            BackupLimits::from_settings(&BackupSettings::default()).max_count,
+            DEFAULT_MAX_COUNT
+        );
+    }
+
+    #[test]
+    fn test_all_backups_include_legacy() {
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path();
+        let layout = BackupLayout::from_settings(&BackupSettings::default(), home);
+        fs::create_dir_all(layout.dir.join("dotfiles-20260201-090000")).unwrap();
+        fs::create_dir_all(home.join(".dotfiles-backup-20260301-090000")).unwrap();
+        fs::create_dir_all(home.join(".dotfiles-backup-20260101-090000")).unwrap();
+
+        let timestamps: Vec<String> = all_backups(home, &layout)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.timestamp)
+            .collect();
+        assert_eq!(
+            timestamps,
+            ["20260301-090000", "20260201-090000", "20260101-090000"]
+        );
+    }
+
     #[test]
     fn test_restore_backup() {
         let temp_dir = TempDir::new().unwrap();
@@ -363,18 +1258,31 @@ mod tests {
         fs::create_dir(&source_dir).unwrap();
         fs::write(source_dir.join("file.txt"), "original").unwrap();
 
-        let backup_path = create_backup(&source_dir, Some(backup_parent)).unwrap();
-        let backup = BackupInfo::from_path(backup_path.clone(), source_dir.clone()).unwrap();
+        let layout = test_layout(backup_parent, backup_parent);
+        let backup_path = create_backup_in(&source_dir, &layout).unwrap().path;
+        let backup =
+            BackupInfo::from_path(backup_path.clone(), source_dir.clone(), &layout).unwrap();
 
         // Modify source
         fs::write(source_dir.join("file.txt"), "modified").unwrap();
 
         // Restore to target
-        restore_backup(&backup, &target_dir).unwrap();
+        restore_backup(&backup, &target_dir, &layout).unwrap();
 
         // Verify restoration
         assert!(target_dir.join("file.txt").exists());
         let content = fs::read_to_string(target_dir.join("file.txt")).unwrap();
         assert_eq!(content, "original");
+        assert!(!target_dir.join(SOURCE_FILE).exists());
+
+        let backups = [backup];
+        let name = backup_path.file_name().unwrap().to_str().unwrap();
+        assert!(find_backup(&backups, name).is_some());
+        assert!(find_backup(&backups, &backups[0].timestamp).is_some());
+        assert!(find_backup(&backups, "20000101-000000").is_none());
+
+        // A target holding the backup itself is refused before anything is touched
+        assert!(restore_backup(&backups[0], temp_dir.path(), &layout).is_err());
+        assert!(backup_path.exists());
     }
 }
@@ -0,0 +1,317 @@
+use crate::backup::{is_archive, write_archive, ARCHIVE_EXTENSION, SOURCE_FILE};
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::BackupSettings;
+use crate::error::{DotfilesError, Result};
+use crate::install::repos;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Where `[backups] remote` keeps a copy of every pushed backup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Remote {
+    /// A git repository each backup is committed to, as a `.tar.gz` archive
+    Git(String),
+    /// An rsync destination (`host:path`, `rsync://...`, or a local path)
+    Rsync(String),
+}
+
+impl Remote {
+    /// Reads a `[backups] remote` value
+    ///
+    /// A `git+` or `rsync:` prefix says which it is. Otherwise git URLs
+    /// (`https://`, `ssh://`, `git://`, `file://`), `git@host:` and known git
+    /// hosts, and paths ending in `.git` are git repositories; anything else
+    /// (`host:path`, `rsync://`, a local directory) is an rsync destination.
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim().trim_end_matches('/');
+        if let Some(url) = value.strip_prefix("git+") {
+            return Self::Git(url.to_string());
+        }
+        if !value.starts_with("rsync://") {
+            if let Some(destination) = value.strip_prefix("rsync:") {
+                return Self::Rsync(destination.to_string());
+            }
+        }
+
+        if is_git_remote(value) {
+            Self::Git(value.to_string())
+        } else {
+            Self::Rsync(value.to_string())
+        }
+    }
+
+    /// The remote set in `[backups] remote`
+    pub fn from_settings(settings: &BackupSettings) -> Result<Self> {
+        settings
+            .remote
+            .as_deref()
+            .filter(|remote| !remote.trim().is_empty())
+            .map(Self::parse)
+            .ok_or_else(|| {
+                DotfilesError::Config(
+                    "Set [backups] remote to a git repository URL or an rsync destination"
+                        .to_string(),
+                )
+            })
+    }
+
+    /// Where fetched backups are kept locally, under the state directory
+    pub fn cache_dir(&self, state_dir: &Path) -> PathBuf {
+        let kind = match self {
+            Self::Git(_) => "git",
+            Self::Rsync(_) => "rsync",
+        };
+        state_dir.join("remote-backups").join(kind)
+    }
+}
+
+/// Hosts that only serve git, so `host:path` there is never an rsync target
+const GIT_HOSTS: &[&str] = &[
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+    "codeberg.org",
+    "git.sr.ht",
+];
+
+/// Classifies a remote without a `git+`/`rsync:` prefix by its scheme and host
+fn is_git_remote(value: &str) -> bool {
+    if value.ends_with(".git") {
+        return true;
+    }
+    if let Some((scheme, _)) = value.split_once("://") {
+        return matches!(scheme, "https" | "http" | "ssh" | "git" | "file");
+    }
+    // scp-style `[user@]host:path`; a `/` before the colon makes it a local path
+    match value.split_once(':') {
+        Some((login, _)) if !login.contains('/') => {
+            let (user, host) = login.rsplit_once('@').unwrap_or(("", login));
+            user == "git" || GIT_HOSTS.contains(&host)
+        }
+        _ => false,
+    }
+}
+
+impl std::fmt::Display for Remote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Git(url) | Self::Rsync(url) => f.write_str(url),
+        }
+    }
+}
+
+/// Uploads the backup at `backup` to `remote`
+///
+/// A git remote is kept checked out in `cache`; directory backups are
+/// committed to it as archives so nested `.git` directories survive.
+pub fn push(remote: &Remote, backup: &Path, cache: &Path) -> Result<()> {
+    match remote {
+        Remote::Rsync(destination) => rsync(backup, format!("{}/", destination)),
+        Remote::Git(url) => {
+            checkout(url, cache)?;
+            let name = backup
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let stored = if is_archive(backup) {
+                fs::copy(backup, cache.join(&name))?;
+                name
+            } else {
+                let archive = format!("{}{}", name, ARCHIVE_EXTENSION);
+                let source = fs::read_to_string(backup.join(SOURCE_FILE))
+                    .map(|recorded| PathBuf::from(recorded.trim()))
+                    .unwrap_or_default();
+                write_archive(backup, &source, File::create(cache.join(&archive))?)?;
+                archive
+            };
+
+            git(cache, &["add", "--", &stored])?;
+            let message = format!("Add backup {}", stored);
+            let mut commit = vec!["commit", "--quiet", "-m", &message];
+            if identity_missing(cache) {
+                commit.splice(
+                    0..0,
+                    [
+                        "-c",
+                        "user.name=dotfiles",
+                        "-c",
+                        "user.email=dotfiles@localhost",
+                    ],
+                );
+            }
+            git(cache, &commit)?;
+            git(
+                cache,
+                &["push", "--quiet", "--set-upstream", "origin", "HEAD"],
+            )
+        }
+    }
+}
+
+/// Brings every backup on `remote` into `cache` and returns the directory holding them
+pub fn fetch(remote: &Remote, cache: &Path) -> Result<PathBuf> {
+    match remote {
+        Remote::Rsync(source) => {
+            fs::create_dir_all(cache)?;
+            rsync(format!("{}/", source), cache)?;
+        }
+        Remote::Git(url) => checkout(url, cache)?,
+    }
+    Ok(cache.to_path_buf())
+}
+
+/// Clones `url` into `dir`, or brings an existing clone up to date
+fn checkout(url: &str, dir: &Path) -> Result<()> {
+    if dir.join(".git").is_dir() && repos::remote_url(dir).as_deref() == Some(url) {
+        // A repository nothing has been pushed to yet has no upstream to pull
+        let upstream = CommandRunner::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["rev-parse", "--verify", "--quiet", "@{upstream}"])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()?;
+        if upstream.success() {
+            git(dir, &["pull", "--quiet", "--ff-only"])?;
+        }
+        return Ok(());
+    }
+
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    CommandRunner::new("git")
+        .args(["clone", "--quiet", url])
+        .arg(dir)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()
+        .map_err(|e| {
+            DotfilesError::Config(format!("Could not clone backup remote {}: {}", url, e))
+        })?;
+    Ok(())
+}
+
+/// Returns true if commits in `dir` would fail for want of a name or email
+fn identity_missing(dir: &Path) -> bool {
+    ["user.name", "user.email"].iter().any(|key| {
+        !CommandRunner::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["config", key])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .is_ok_and(|output| output.success())
+    })
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    CommandRunner::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Copies `from` to `to`, keeping hard links and resuming partial transfers
+fn rsync(from: impl AsRef<OsStr>, to: impl AsRef<OsStr>) -> Result<()> {
+    CommandRunner::new("rsync")
+        .args(["--archive", "--hard-links", "--partial"])
+        .arg(from)
+        .arg(to)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()
+        .map_err(|e| match e {
+            DotfilesError::CommandFailed(detail) => {
+                DotfilesError::CommandFailed(format!("rsync failed: {}", detail))
+            }
+            other => other,
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::tests::test_layout;
+    use crate::backup::{list_in, BackupLayout};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_remote_parse() {
+        assert_eq!(
+            Remote::parse("git@github.com:me/backups.git"),
+            Remote::Git("git@github.com:me/backups.git".to_string())
+        );
+        assert_eq!(
+            Remote::parse("nas:/srv/backups/"),
+            Remote::Rsync("nas:/srv/backups".to_string())
+        );
+        for git in [
+            "https://github.com/me/backups",
+            "ssh://git@nas/srv/backups",
+            "github.com:me/backups",
+            "git@nas:backups",
+        ] {
+            assert_eq!(Remote::parse(git), Remote::Git(git.to_string()));
+        }
+        for rsync in ["rsync://nas/backups", "me@nas:backups", "/mnt/usb/backups"] {
+            assert_eq!(Remote::parse(rsync), Remote::Rsync(rsync.to_string()));
+        }
+        assert_eq!(
+            Remote::parse("git+nas:/srv/backups"),
+            Remote::Git("nas:/srv/backups".to_string())
+        );
+        assert_eq!(
+            Remote::parse("rsync:git@nas:/srv/backups"),
+            Remote::Rsync("git@nas:/srv/backups".to_string())
+        );
+        let state = Path::new("/state");
+        assert_eq!(
+            Remote::parse("/mnt/usb/backups.git").cache_dir(state),
+            state.join("remote-backups/git")
+        );
+    }
+
+    #[test]
+    fn test_push_and_fetch_git_remote() {
+        let temp = TempDir::new().unwrap();
+        let bare = temp.path().join("backups.git");
+        CommandRunner::new("git")
+            .args(["init", "--quiet", "--bare"])
+            .arg(&bare)
+            .stream(false)
+            .run()
+            .unwrap();
+        let remote = Remote::parse(&bare.to_string_lossy());
+
+        let backup = temp.path().join("local/dotfiles-20240102-030405");
+        fs::create_dir_all(backup.join("nvim/.git")).unwrap();
+        fs::write(backup.join("nvim/.git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(backup.join(".zshrc"), "export A=1\n").unwrap();
+        fs::write(backup.join(SOURCE_FILE), "/home/me/dotfiles").unwrap();
+        push(&remote, &backup, &temp.path().join("push-cache")).unwrap();
+
+        // Another machine sees it in a fresh checkout
+        let fetched = fetch(&remote, &temp.path().join("fetch-cache")).unwrap();
+        let layout = BackupLayout {
+            dir: fetched,
+            ..test_layout(temp.path(), temp.path())
+        };
+        let backups = list_in(&layout).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].is_archive());
+        assert_eq!(backups[0].source, PathBuf::from("/home/me/dotfiles"));
+        assert_eq!(backups[0].timestamp, "20240102-030405");
+    }
+}
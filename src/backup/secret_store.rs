@@ -0,0 +1,318 @@
+use crate::core::command::{self, CommandRunner};
+use crate::detect::tools;
+use crate::error::{DotfilesError, Result};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Shell snippet a migration writes next to the repo's files when secrets go to a store
+///
+/// It holds lookups only, never values, so it is safe to commit.
+pub const SNIPPET_FILE: &str = "secrets.sh";
+
+/// Somewhere extracted secrets can live instead of a plaintext `.env`
+pub trait SecretStore {
+    /// Name shown in output (e.g. "macOS Keychain")
+    fn name(&self) -> &str;
+
+    /// Saves `value` under `key`, replacing any value stored before
+    fn store(&self, key: &str, value: &str) -> Result<()>;
+
+    /// Shell command that prints the value of `key`
+    fn lookup_command(&self, key: &str) -> String;
+}
+
+/// Which store `migrate` keeps extracted secrets in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// The login keychain, through `security`
+    Keychain,
+    /// A 1Password vault, through the `op` CLI
+    OnePassword { vault: String },
+}
+
+impl Backend {
+    /// Returns the store this backend names
+    pub fn open(&self) -> Box<dyn SecretStore> {
+        match self {
+            Backend::Keychain => Box::new(Keychain::default()),
+            Backend::OnePassword { vault } => Box::new(OnePassword {
+                vault: vault.clone(),
+            }),
+        }
+    }
+}
+
+/// Generic passwords in the macOS login keychain, one per variable
+///
+/// Every item shares `service`, with the variable name as its account.
+#[derive(Debug, Clone)]
+pub struct Keychain {
+    pub service: String,
+}
+
+impl Default for Keychain {
+    fn default() -> Self {
+        Self {
+            service: "dotfiles".to_string(),
+        }
+    }
+}
+
+impl SecretStore for Keychain {
+    fn name(&self) -> &str {
+        "macOS Keychain"
+    }
+
+    fn store(&self, key: &str, value: &str) -> Result<()> {
+        if !cfg!(target_os = "macos") || !tools::is_installed("security") {
+            return Err(DotfilesError::DependencyMissing(
+                "The Keychain backend needs macOS and its security command".to_string(),
+            ));
+        }
+        // Sent through `security -i` on stdin so the value never appears in argv.
+        // -U updates an existing item instead of failing on the duplicate.
+        let command = format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            quote_interactive(&self.service),
+            quote_interactive(key),
+            quote_interactive(value)
+        );
+        CommandRunner::new("security")
+            .arg("-i")
+            .stdin(command)
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run()
+            .map_err(|_| {
+                DotfilesError::CommandFailed(format!("Could not store {} in the Keychain", key))
+            })?;
+        Ok(())
+    }
+
+    fn lookup_command(&self, key: &str) -> String {
+        format!(
+            "security find-generic-password -s '{}' -a '{}' -w",
+            self.service, key
+        )
+    }
+}
+
+/// Quotes an argument for a `security -i` command line
+fn quote_interactive(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Password items in a 1Password vault, one per variable, titled with its name
+#[derive(Debug, Clone)]
+pub struct OnePassword {
+    pub vault: String,
+}
+
+impl OnePassword {
+    /// Returns the `op://` reference to the item stored for `key`
+    pub fn reference(&self, key: &str) -> String {
+        format!("op://{}/{}/password", self.vault, key)
+    }
+
+    /// Returns true if `op` has a signed-in account to read from
+    pub fn is_signed_in() -> bool {
+        op(&["whoami"]).is_ok_and(|output| output.success())
+    }
+
+    /// Returns true if `reference` (`op://vault/item/field`) reads successfully
+    pub fn resolves(reference: &str) -> bool {
+        op(&["read", "--no-newline", reference]).is_ok_and(|output| output.success())
+    }
+}
+
+impl SecretStore for OnePassword {
+    fn name(&self) -> &str {
+        "1Password vault"
+    }
+
+    fn store(&self, key: &str, value: &str) -> Result<()> {
+        if !tools::is_installed("op") {
+            return Err(DotfilesError::DependencyMissing(
+                "op not found; install the 1Password CLI (brew install 1password-cli)".to_string(),
+            ));
+        }
+        // The value goes in a private template file, never on argv where `ps`
+        // (or a command error message) would show it
+        let mut template = tempfile::NamedTempFile::new()?;
+        serde_json::to_writer(&mut template, &item_template(key, value))?;
+        template.flush()?;
+        let template_path = template.path().to_string_lossy().into_owned();
+
+        let exists = op(&["item", "get", key, "--vault", &self.vault])?.success();
+        let output = if exists {
+            op(&[
+                "item",
+                "edit",
+                key,
+                "--vault",
+                &self.vault,
+                "--template",
+                &template_path,
+            ])?
+        } else {
+            op(&[
+                "item",
+                "create",
+                "--vault",
+                &self.vault,
+                "--template",
+                &template_path,
+            ])?
+        };
+        if !output.success() {
+            return Err(DotfilesError::CommandFailed(format!(
+                "Could not store {} in 1Password vault {}: {}",
+                key,
+                self.vault,
+                output.stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    fn lookup_command(&self, key: &str) -> String {
+        format!("op read '{}'", self.reference(key))
+    }
+}
+
+/// Returns an `op` item template for a password item titled `key`
+fn item_template(key: &str, value: &str) -> serde_json::Value {
+    serde_json::json!({
+        "title": key,
+        "category": "PASSWORD",
+        "fields": [{
+            "id": "password",
+            "type": "CONCEALED",
+            "purpose": "PASSWORD",
+            "label": "password",
+            "value": value,
+        }],
+    })
+}
+
+fn op(args: &[&str]) -> Result<command::CommandOutput> {
+    CommandRunner::new("op")
+        .args(args)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .output()
+}
+
+/// Stores every entry in `store` and writes a snippet exporting them to `snippet_path`
+///
+/// Sourcing the snippet from a shell rc file gives the same variables the
+/// `.env` would have, read from the store each time a shell starts.
+pub fn store_all(
+    store: &dyn SecretStore,
+    entries: &[(String, String)],
+    snippet_path: &Path,
+) -> Result<()> {
+    for (key, value) in entries {
+        store.store(key, value)?;
+    }
+    fs::write(snippet_path, render_snippet(store, entries))?;
+    Ok(())
+}
+
+/// Renders `export NAME="$(lookup)"` lines for each entry
+pub fn render_snippet(store: &dyn SecretStore, entries: &[(String, String)]) -> String {
+    let mut snippet = format!(
+        "# Secrets kept in the {} - safe to commit, holds no values\n\n",
+        store.name()
+    );
+    for (key, _) in entries {
+        snippet.push_str(&format!(
+            "export {}=\"$({} 2>/dev/null)\"\n",
+            key,
+            store.lookup_command(key)
+        ));
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use tempfile::TempDir;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        stored: RefCell<Vec<(String, String)>>,
+    }
+
+    impl SecretStore for MemoryStore {
+        fn name(&self) -> &str {
+            "test store"
+        }
+
+        fn store(&self, key: &str, value: &str) -> Result<()> {
+            self.stored
+                .borrow_mut()
+                .push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+
+        fn lookup_command(&self, key: &str) -> String {
+            format!("lookup {}", key)
+        }
+    }
+
+    #[test]
+    fn test_store_all_writes_snippet_without_values() {
+        let temp = TempDir::new().unwrap();
+        let snippet_path = temp.path().join(SNIPPET_FILE);
+        let store = MemoryStore::default();
+        let entries = vec![
+            ("API_TOKEN".to_string(), "secret123".to_string()),
+            ("DB_PASSWORD".to_string(), "hunter2".to_string()),
+        ];
+        store_all(&store, &entries, &snippet_path).unwrap();
+
+        assert_eq!(*store.stored.borrow(), entries);
+        let snippet = fs::read_to_string(&snippet_path).unwrap();
+        assert!(snippet.contains("export API_TOKEN=\"$(lookup API_TOKEN 2>/dev/null)\"\n"));
+        assert!(snippet.contains("export DB_PASSWORD="));
+        assert!(!snippet.contains("secret123"));
+        assert!(!snippet.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_keychain_lookup_command() {
+        assert_eq!(
+            Keychain::default().lookup_command("API_TOKEN"),
+            "security find-generic-password -s 'dotfiles' -a 'API_TOKEN' -w"
+        );
+    }
+
+    #[test]
+    fn test_quote_interactive() {
+        assert_eq!(quote_interactive("plain"), "\"plain\"");
+        assert_eq!(quote_interactive(r#"a "b" c\d"#), r#""a \"b\" c\\d""#);
+    }
+
+    #[test]
+    fn test_item_template() {
+        let template = item_template("API_TOKEN", "secret123");
+        assert_eq!(template["title"], "API_TOKEN");
+        assert_eq!(template["fields"][0]["id"], "password");
+        assert_eq!(template["fields"][0]["value"], "secret123");
+    }
+
+    #[test]
+    fn test_one_password_lookup_command() {
+        let store = OnePassword {
+            vault: "Private".to_string(),
+        };
+        assert_eq!(
+            store.lookup_command("API_TOKEN"),
+            "op read 'op://Private/API_TOKEN/password'"
+        );
+    }
+}
@@ -1,17 +1,47 @@
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use crate::core::walk::RepoWalker;
 use crate::error::Result;
 
 /// Detected secret
 #[derive(Debug, Clone, PartialEq)]
 pub struct Secret {
+    /// Variable or field the value is assigned to, or empty for a bare literal
     pub key: String,
     pub value: String,
     pub file: String,
     pub line_number: usize,
+    pub kind: SecretKind,
+}
+
+/// How a secret was detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    /// Assigned to a variable with a secret-like name
+    Keyword,
+    /// A random-looking string, flagged as a candidate to review
+    HighEntropy,
+}
+
+/// When a string is random-looking enough to be flagged as a candidate secret
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropySettings {
+    /// Minimum Shannon entropy, in bits per character
+    pub threshold: f64,
+    /// Shorter strings are never flagged
+    pub min_length: usize,
+}
+
+impl Default for EntropySettings {
+    fn default() -> Self {
+        Self {
+            threshold: 4.0,
+            min_length: 20,
+        }
+    }
 }
 
 /// Secret patterns to detect
@@ -24,6 +54,8 @@ pub struct SecretPatterns {
     pub token: Regex,
     /// Matches passwords
     pub password: Regex,
+    /// Matches token-like strings, with the name they are assigned to if any
+    pub literal: Regex,
 }
 
 impl SecretPatterns {
@@ -37,6 +69,8 @@ impl SecretPatterns {
             token: Regex::new(r#"(?:token|access[_-]?token)[:\s=]+['"]?([^'"\s]+)['"]?"#).unwrap(),
             // Matches: password: "value"
             password: Regex::new(r#"(?:password|passwd)[:\s=]+['"]?([^'"\s]+)['"]?"#).unwrap(),
+            // Matches: client_id = "value", "clientId": "value", or a bare value
+            literal: Regex::new(r#"(?:['"]?([A-Za-z_][A-Za-z0-9_]*)['"]?\s*[:=]\s*)?['"]?([A-Za-z0-9+/=_\-]+)"#).unwrap(),
         }
     }
 }
@@ -47,8 +81,13 @@ impl Default for SecretPatterns {
     }
 }
 
-/// Scans a file for secrets
+/// Scans a file for secrets, flagging high-entropy strings with the default settings
 pub fn scan_file(file_path: &Path) -> Result<Vec<Secret>> {
+    scan_file_with(file_path, &EntropySettings::default())
+}
+
+/// Scans a file for secrets, flagging strings that pass `entropy` as candidates
+pub fn scan_file_with(file_path: &Path, entropy: &EntropySettings) -> Result<Vec<Secret>> {
     let patterns = SecretPatterns::new();
     let mut secrets = Vec::new();
 
@@ -67,27 +106,75 @@ pub fn scan_file(file_path: &Path) -> Result<Vec<Secret>> {
         }
 
         // Check environment variable pattern
+        let mut keyword_value = None;
         if let Some(captures) = patterns.env_var.captures(line) {
             if let (Some(key), Some(value)) = (captures.get(1), captures.get(2)) {
                 // Skip common non-secret variables
                 let key_str = key.as_str();
-                if !is_likely_secret(key_str) {
-                    continue;
+                if is_likely_secret(key_str) {
+                    keyword_value = Some(value.as_str());
+                    secrets.push(Secret {
+                        key: key_str.to_string(),
+                        value: value.as_str().to_string(),
+                        file: file_name.clone(),
+                        line_number: line_num + 1,
+                        kind: SecretKind::Keyword,
+                    });
                 }
+            }
+        }
 
-                secrets.push(Secret {
-                    key: key_str.to_string(),
-                    value: value.as_str().to_string(),
-                    file: file_name.clone(),
-                    line_number: line_num + 1,
-                });
+        // Random-looking strings the keyword match didn't already cover
+        for captures in patterns.literal.captures_iter(line) {
+            let Some(value) = captures.get(2).map(|value| value.as_str()) else {
+                continue;
+            };
+            if Some(value) == keyword_value || !is_high_entropy(value, entropy) {
+                continue;
             }
+            secrets.push(Secret {
+                key: captures
+                    .get(1)
+                    .map(|key| key.as_str().to_string())
+                    .unwrap_or_default(),
+                value: value.to_string(),
+                file: file_name.clone(),
+                line_number: line_num + 1,
+                kind: SecretKind::HighEntropy,
+            });
         }
     }
 
     Ok(secrets)
 }
 
+/// Returns true if `value` is long and random enough to be a token
+///
+/// Needs both letters and digits, so long words and paths made of them
+/// aren't flagged.
+fn is_high_entropy(value: &str, settings: &EntropySettings) -> bool {
+    value.len() >= settings.min_length
+        && value.chars().any(|c| c.is_ascii_digit())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+        && shannon_entropy(value) >= settings.threshold
+}
+
+/// Average information per character of `value`, in bits
+fn shannon_entropy(value: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    for c in value.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let len = value.chars().count() as f64;
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
 /// Determines if a key name is likely to be a secret
 fn is_likely_secret(key: &str) -> bool {
     let key_upper = key.to_uppercase();
@@ -111,8 +198,13 @@ fn is_likely_secret(key: &str) -> bool {
     has_secret_keyword && !is_non_secret
 }
 
-/// Scans a directory for secrets
+/// Scans a directory for secrets, flagging high-entropy strings with the default settings
 pub fn scan_directory(dir_path: &Path) -> Result<Vec<Secret>> {
+    scan_directory_with(dir_path, &EntropySettings::default())
+}
+
+/// Scans a directory for secrets, flagging strings that pass `entropy` as candidates
+pub fn scan_directory_with(dir_path: &Path, entropy: &EntropySettings) -> Result<Vec<Secret>> {
     let mut all_secrets = Vec::new();
 
     if !dir_path.exists() {
@@ -124,25 +216,21 @@ pub fn scan_directory(dir_path: &Path) -> Result<Vec<Secret>> {
         "sh", "bash", "zsh", "fish", "rc", "conf", "config", "toml", "yaml", "yml", "json", "env",
     ];
 
-    for entry in fs::read_dir(dir_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            let should_scan = if let Some(ext) = path.extension() {
-                config_extensions.contains(&ext.to_str().unwrap_or(""))
-            } else {
-                // Scan dotfiles without extension
-                path.file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n.starts_with('.'))
-                    .unwrap_or(false)
-            };
-
-            if should_scan {
-                if let Ok(secrets) = scan_file(&path) {
-                    all_secrets.extend(secrets);
-                }
+    // Ignored files never reach git, so secrets in them are not a leak
+    for path in RepoWalker::new(dir_path).max_depth(1).files()? {
+        let should_scan = if let Some(ext) = path.extension() {
+            config_extensions.contains(&ext.to_str().unwrap_or(""))
+        } else {
+            // Scan dotfiles without extension
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with('.'))
+                .unwrap_or(false)
+        };
+
+        if should_scan {
+            if let Ok(secrets) = scan_file_with(&path, entropy) {
+                all_secrets.extend(secrets);
             }
         }
     }
@@ -150,24 +238,347 @@ pub fn scan_directory(dir_path: &Path) -> Result<Vec<Secret>> {
     Ok(all_secrets)
 }
 
+/// Scans each of `files`, naming them by their path below `root`
+///
+/// Used for files at any depth, so secrets in `.config/app/credentials` are
+/// told apart from ones in a top-level `credentials`. Files that aren't text
+/// are skipped.
+pub fn scan_files_with(root: &Path, files: &[PathBuf], entropy: &EntropySettings) -> Vec<Secret> {
+    let mut all_secrets = Vec::new();
+    for path in files {
+        let Ok(mut secrets) = scan_file_with(path, entropy) else {
+            continue;
+        };
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned();
+        for secret in &mut secrets {
+            secret.file = rel.clone();
+        }
+        all_secrets.extend(secrets);
+    }
+    all_secrets
+}
+
 /// Extracts secrets to a .env file
+///
+/// Candidates without a usable variable name are left out.
 pub fn extract_to_env(secrets: &[Secret], output_path: &Path) -> Result<()> {
+    write_env(&env_entries(secrets), output_path)
+}
+
+/// Returns one `(NAME, value)` pair per variable name, keeping the first occurrence
+///
+/// Candidates without a usable variable name are left out.
+pub fn env_entries(secrets: &[Secret]) -> Vec<(String, String)> {
+    let mut seen_keys = std::collections::HashSet::new();
+    secrets
+        .iter()
+        .filter(|secret| is_env_name(&secret.key) && seen_keys.insert(&secret.key))
+        .map(|secret| (secret.key.clone(), secret.value.clone()))
+        .collect()
+}
+
+/// Writes `NAME=value` lines to a .env file under a do-not-commit header
+///
+/// The file is also added to the `.gitignore` in its directory.
+pub fn write_env(entries: &[(String, String)], output_path: &Path) -> Result<()> {
     let mut env_content = String::new();
     env_content.push_str("# Extracted secrets - DO NOT COMMIT THIS FILE\n");
-    env_content.push_str("# Add this file to .gitignore\n\n");
+    env_content.push_str("# Listed in .gitignore\n\n");
 
-    // Deduplicate secrets by key (keep first occurrence)
-    let mut seen_keys = std::collections::HashSet::new();
+    for (key, value) in entries {
+        env_content.push_str(&format!("{}={}\n", key, value));
+    }
+
+    fs::write(output_path, env_content)?;
+
+    if let (Some(dir), Some(name)) = (
+        output_path.parent(),
+        output_path.file_name().and_then(|n| n.to_str()),
+    ) {
+        ensure_gitignored(dir, &[name])?;
+    }
+
+    Ok(())
+}
+
+/// Appends each of `patterns` missing from `dir/.gitignore`, creating the file if needed
+///
+/// Returns the patterns that were added.
+pub fn ensure_gitignored(dir: &Path, patterns: &[&str]) -> Result<Vec<String>> {
+    let path = dir.join(".gitignore");
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    let missing: Vec<String> = patterns
+        .iter()
+        .filter(|pattern| {
+            !content
+                .lines()
+                .map(str::trim)
+                .any(|line| line == **pattern || line == format!("/{}", pattern))
+        })
+        .map(|pattern| pattern.to_string())
+        .collect();
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str("# Secrets extracted by dotfiles\n");
+    for pattern in &missing {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    fs::write(&path, content)?;
+    Ok(missing)
+}
+
+/// One line whose secrets were replaced with `${VAR}` references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pub file: String,
+    pub line_number: usize,
+    /// The line before, with the secrets masked
+    pub before: String,
+    pub after: String,
+}
+
+/// What replacing secrets with `${VAR}` references changes, and the .env that supplies them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redaction {
+    pub replacements: Vec<Replacement>,
+    /// New contents of each changed file, keyed by file name
+    pub files: BTreeMap<String, String>,
+    /// Variables the references need, in order
+    pub env: Vec<(String, String)>,
+    /// Files holding secrets that were left alone because nothing expands `${VAR}` in them
+    pub skipped: Vec<String>,
+}
+
+impl Redaction {
+    /// Lists each changed line, secrets masked, for a dry-run preview
+    pub fn preview(&self) -> String {
+        let mut preview: String = self
+            .replacements
+            .iter()
+            .map(|replacement| {
+                format!(
+                    "  {}:{}\n    - {}\n    + {}\n",
+                    replacement.file,
+                    replacement.line_number,
+                    replacement.before.trim(),
+                    replacement.after.trim()
+                )
+            })
+            .collect();
+        for file in &self.skipped {
+            preview.push_str(&format!(
+                "  {}: left as is, nothing expands ${{VAR}} there\n",
+                file
+            ));
+        }
+        preview
+    }
+}
+
+/// Extensions of files a shell reads, where `${VAR}` is expanded
+const SHELL_EXTENSIONS: &[&str] = &["sh", "bash", "zsh", "ksh", "env"];
+
+/// Shell startup files, which have no extension to go by
+const SHELL_FILES: &[&str] = &[
+    ".zshrc",
+    ".zshenv",
+    ".zprofile",
+    ".zlogin",
+    ".bashrc",
+    ".bash_profile",
+    ".bash_login",
+    ".profile",
+    ".kshrc",
+    ".env",
+    ".envrc",
+];
+
+/// Returns true if `file` is read by a shell, so a `${VAR}` reference in it is expanded
+///
+/// JSON, TOML, YAML, and other formats would keep the reference literally.
+pub fn expands_env_refs(file: &Path) -> bool {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    SHELL_FILES.contains(&name.as_ref())
+        || name.starts_with(".env.")
+        || file
+            .extension()
+            .is_some_and(|ext| SHELL_EXTENSIONS.contains(&ext.to_string_lossy().as_ref()))
+}
+
+/// Plans replacing each secret found in `dir` with a `${VAR}` reference
+///
+/// Only keyword matches (`export API_TOKEN=...`) in files a shell reads are
+/// replaced; high-entropy candidates, and secrets in other formats, are left
+/// alone and the files listed in `skipped`. A name that holds different values
+/// in different places gets one numbered variable per value (`API_TOKEN`,
+/// `API_TOKEN_2`), so every reference keeps its own value.
+pub fn plan_redaction(dir: &Path, secrets: &[Secret]) -> Result<Redaction> {
+    let mut redaction = Redaction::default();
+    let mut names: HashMap<(&str, &str), String> = HashMap::new();
+    let mut taken: HashMap<&str, usize> = HashMap::new();
+    let mut by_line: BTreeMap<(&str, usize), Vec<(&str, String)>> = BTreeMap::new();
 
     for secret in secrets {
-        if seen_keys.insert(&secret.key) {
-            env_content.push_str(&format!("{}={}\n", secret.key, secret.value));
+        if !expands_env_refs(Path::new(&secret.file)) {
+            if !redaction.skipped.contains(&secret.file) {
+                redaction.skipped.push(secret.file.clone());
+            }
+            continue;
         }
+        if secret.kind != SecretKind::Keyword || !is_env_name(&secret.key) {
+            continue;
+        }
+        let var = names
+            .entry((secret.key.as_str(), secret.value.as_str()))
+            .or_insert_with(|| {
+                let count = taken.entry(secret.key.as_str()).or_default();
+                *count += 1;
+                let var = match *count {
+                    1 => secret.key.clone(),
+                    n => format!("{}_{}", secret.key, n),
+                };
+                redaction.env.push((var.clone(), secret.value.clone()));
+                var
+            })
+            .clone();
+        by_line
+            .entry((secret.file.as_str(), secret.line_number))
+            .or_default()
+            .push((secret.value.as_str(), var));
     }
 
-    fs::write(output_path, env_content)?;
+    let mut contents: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for ((file, line_number), values) in by_line {
+        if !contents.contains_key(file) {
+            let content = fs::read_to_string(dir.join(file))?;
+            contents.insert(
+                file,
+                content.split_inclusive('\n').map(String::from).collect(),
+            );
+        }
+        let Some(line) = contents
+            .get_mut(file)
+            .and_then(|lines| lines.get_mut(line_number - 1))
+        else {
+            continue;
+        };
 
-    Ok(())
+        let mut before = line.clone();
+        let mut after = line.clone();
+        for (value, var) in &values {
+            before = before.replacen(value, REDACTED, 1);
+            after = after.replacen(value, &format!("${{{}}}", var), 1);
+        }
+        if after != *line {
+            redaction.replacements.push(Replacement {
+                file: file.to_string(),
+                line_number,
+                before: before.trim_end_matches('\n').to_string(),
+                after: after.trim_end_matches('\n').to_string(),
+            });
+            *line = after;
+        }
+    }
+
+    redaction.files = contents
+        .into_iter()
+        .filter(|(file, _)| {
+            redaction
+                .replacements
+                .iter()
+                .any(|replacement| replacement.file == *file)
+        })
+        .map(|(file, lines)| (file.to_string(), lines.concat()))
+        .collect();
+    Ok(redaction)
+}
+
+/// Returns the variable names assigned in a `.env` file, in order
+pub fn env_keys(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, _) = line
+                .strip_prefix("export ")
+                .unwrap_or(line)
+                .split_once('=')?;
+            let key = key.trim();
+            is_env_name(key).then(|| key.to_string())
+        })
+        .collect()
+}
+
+fn is_env_name(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Placeholder substituted for secret values by `redact`
+pub const REDACTED: &str = "********";
+
+/// Masks secret values in text using the same patterns as scanning
+pub fn redact(content: &str) -> String {
+    let patterns = SecretPatterns::new();
+    let mut redacted: Vec<String> = content
+        .lines()
+        .map(|line| redact_line(line, &patterns))
+        .collect();
+    if content.ends_with('\n') {
+        redacted.push(String::new());
+    }
+    redacted.join("\n")
+}
+
+fn redact_line(line: &str, patterns: &SecretPatterns) -> String {
+    let mut ranges = Vec::new();
+
+    for captures in patterns.env_var.captures_iter(line) {
+        if let (Some(key), Some(value)) = (captures.get(1), captures.get(2)) {
+            if is_likely_secret(key.as_str()) {
+                ranges.push(value.range());
+            }
+        }
+    }
+    for pattern in [&patterns.api_key, &patterns.token, &patterns.password] {
+        for captures in pattern.captures_iter(line) {
+            if let Some(value) = captures.get(1) {
+                ranges.push(value.range());
+            }
+        }
+    }
+
+    if ranges.is_empty() {
+        return line.to_string();
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    let mut output = String::new();
+    let mut cursor = 0;
+    for range in ranges {
+        if range.start < cursor {
+            cursor = cursor.max(range.end);
+            continue;
+        }
+        output.push_str(&line[cursor..range.start]);
+        output.push_str(REDACTED);
+        cursor = range.end;
+    }
+    output.push_str(&line[cursor..]);
+    output
 }
 
 /// Generates a summary report of found secrets
@@ -188,7 +599,20 @@ pub fn summarize_secrets(secrets: &[Secret]) -> String {
     for (file, file_secrets) in by_file.iter() {
         summary.push_str(&format!("{}:\n", file));
         for secret in file_secrets {
-            summary.push_str(&format!("  Line {}: {}\n", secret.line_number, secret.key));
+            let key = if secret.key.is_empty() {
+                "string literal"
+            } else {
+                &secret.key
+            };
+            match secret.kind {
+                SecretKind::Keyword => {
+                    summary.push_str(&format!("  Line {}: {}\n", secret.line_number, key))
+                }
+                SecretKind::HighEntropy => summary.push_str(&format!(
+                    "  Line {}: {} (high entropy, review before committing)\n",
+                    secret.line_number, key
+                )),
+            }
         }
         summary.push('\n');
     }
@@ -280,6 +704,63 @@ mod tests {
         assert_eq!(secrets[1].value, "xyz789");
     }
 
+    #[test]
+    fn test_scan_file_flags_high_entropy_strings() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("config.json");
+
+        fs::write(
+            &file_path,
+            concat!(
+                "{\n",
+                "  \"clientId\": \"q8Zt3mK1vR7xW2pL9sN4bY6c\",\n",
+                "  \"theme\": \"solarized-dark-high-contrast\",\n",
+                "  \"commit\": \"1111111111222222222233333333334444444444\"\n",
+                "}\n",
+                "export API_TOKEN=ghp_q8Zt3mK1vR7xW2pL9sN4bY6cD5\n",
+                "Yf83kQz0Lm2Np7Rt5Vw9Xa1Cd4\n",
+            ),
+        )
+        .unwrap();
+
+        let secrets = scan_file(&file_path).unwrap();
+        let found: Vec<(&str, SecretKind, usize)> = secrets
+            .iter()
+            .map(|secret| (secret.key.as_str(), secret.kind, secret.line_number))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("clientId", SecretKind::HighEntropy, 2),
+                ("API_TOKEN", SecretKind::Keyword, 6),
+                ("", SecretKind::HighEntropy, 7),
+            ]
+        );
+
+        // A longer minimum length leaves only the keyword match
+        let strict = EntropySettings {
+            threshold: 4.0,
+            min_length: 30,
+        };
+        let secrets = scan_file_with(&file_path, &strict).unwrap();
+        assert_eq!(secrets.len(), 1);
+
+        // Bare literals have no name to write to a .env
+        let env_path = temp_dir.path().join(".env");
+        extract_to_env(&scan_file(&file_path).unwrap(), &env_path).unwrap();
+        assert_eq!(
+            env_keys(&fs::read_to_string(&env_path).unwrap()),
+            vec!["clientId", "API_TOKEN"]
+        );
+    }
+
+    #[test]
+    fn test_shannon_entropy() {
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert_eq!(shannon_entropy("abcd"), 2.0);
+        assert!(!is_high_entropy("abcd1234", &EntropySettings::default()));
+    }
+
     #[test]
     fn test_scan_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -303,6 +784,24 @@ mod tests {
         assert_eq!(secrets.len(), 2);
     }
 
+    #[test]
+    fn test_scan_files_names_nested_files_by_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join(".config/app/credentials");
+        fs::create_dir_all(nested.parent().unwrap()).unwrap();
+        fs::write(&nested, "API_TOKEN=abc123\n").unwrap();
+        let binary = temp_dir.path().join("font.ttf");
+        fs::write(&binary, [0xff, 0xfe, 0x00]).unwrap();
+
+        let secrets = scan_files_with(
+            temp_dir.path(),
+            &[nested, binary],
+            &EntropySettings::default(),
+        );
+        assert_eq!(secrets.len(), 1);
+        assert_eq!(secrets[0].file, ".config/app/credentials");
+    }
+
     #[test]
     fn test_extract_to_env() {
         let temp_dir = TempDir::new().unwrap();
@@ -314,12 +813,14 @@ mod tests {
                 value: "abc123".to_string(),
                 file: "config.sh".to_string(),
                 line_number: 1,
+                kind: SecretKind::Keyword,
             },
             Secret {
                 key: "GITHUB_TOKEN".to_string(),
                 value: "xyz789".to_string(),
                 file: "config.sh".to_string(),
                 line_number: 2,
+                kind: SecretKind::Keyword,
             },
         ];
 
@@ -329,6 +830,34 @@ mod tests {
         assert!(content.contains("API_TOKEN=abc123"));
         assert!(content.contains("GITHUB_TOKEN=xyz789"));
         assert!(content.contains("DO NOT COMMIT"));
+        assert_eq!(env_keys(&content), vec!["API_TOKEN", "GITHUB_TOKEN"]);
+        let gitignore = fs::read_to_string(temp_dir.path().join(".gitignore")).unwrap();
+        assert!(gitignore.lines().any(|line| line == ".env"));
+        assert_eq!(
+            env_keys("export NPM_TOKEN=x\n# OLD=y\nnot a pair\n"),
+            vec!["NPM_TOKEN"]
+        );
+    }
+
+    #[test]
+    fn test_ensure_gitignored_is_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let gitignore = temp_dir.path().join(".gitignore");
+        fs::write(&gitignore, ".DS_Store").unwrap();
+
+        let added = ensure_gitignored(temp_dir.path(), &[".env"]).unwrap();
+        assert_eq!(added, vec![".env"]);
+        let added = ensure_gitignored(temp_dir.path(), &[".env"]).unwrap();
+        assert!(added.is_empty());
+        assert_eq!(
+            fs::read_to_string(&gitignore).unwrap(),
+            ".DS_Store\n# Secrets extracted by dotfiles\n.env\n"
+        );
+
+        fs::write(&gitignore, "/.env\n").unwrap();
+        assert!(ensure_gitignored(temp_dir.path(), &[".env"])
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
@@ -342,12 +871,14 @@ mod tests {
                 value: "abc123".to_string(),
                 file: "config1.sh".to_string(),
                 line_number: 1,
+                kind: SecretKind::Keyword,
             },
             Secret {
                 key: "API_TOKEN".to_string(),
                 value: "different".to_string(),
                 file: "config2.sh".to_string(),
                 line_number: 1,
+                kind: SecretKind::Keyword,
             },
         ];
 
@@ -359,6 +890,91 @@ mod tests {
         assert!(content.contains("API_TOKEN=abc123"));
     }
 
+    #[test]
+    fn test_plan_redaction() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(".zshrc"),
+            "export EDITOR=nvim\nexport API_TOKEN=abc123\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("work.sh"),
+            "export API_TOKEN=\"xyz789\"\nexport GITHUB_TOKEN=ghp_1 # personal\n",
+        )
+        .unwrap();
+
+        let secrets = scan_directory(temp_dir.path()).unwrap();
+        let redaction = plan_redaction(temp_dir.path(), &secrets).unwrap();
+
+        assert_eq!(
+            redaction.files[".zshrc"],
+            "export EDITOR=nvim\nexport API_TOKEN=${API_TOKEN}\n"
+        );
+        assert_eq!(
+            redaction.files["work.sh"],
+            "export API_TOKEN=\"${API_TOKEN_2}\"\nexport GITHUB_TOKEN=${GITHUB_TOKEN} # personal\n"
+        );
+        assert_eq!(
+            redaction.env,
+            vec![
+                ("API_TOKEN".to_string(), "abc123".to_string()),
+                ("API_TOKEN_2".to_string(), "xyz789".to_string()),
+                ("GITHUB_TOKEN".to_string(), "ghp_1".to_string()),
+            ]
+        );
+
+        // The preview never shows the values
+        let preview = redaction.preview();
+        assert!(preview.contains("work.sh:2"));
+        assert!(preview.contains("- export GITHUB_TOKEN=******** # personal"));
+        assert!(!preview.contains("xyz789"));
+        // Sources are only read
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join(".zshrc")).unwrap(),
+            "export EDITOR=nvim\nexport API_TOKEN=abc123\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_redaction_only_rewrites_shell_assignments() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(".zshrc"),
+            "export API_TOKEN=abc123\nexport THEME=q8Zt3mK1vR7xW2pL9sN4bY6c\n",
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("settings.json"),
+            "{\n  \"API_TOKEN\": \"xyz789\",\n  API_TOKEN=xyz789\n}\n",
+        )
+        .unwrap();
+
+        let secrets = scan_directory(temp_dir.path()).unwrap();
+        assert!(secrets
+            .iter()
+            .any(|secret| secret.kind == SecretKind::HighEntropy && secret.file == ".zshrc"));
+        let redaction = plan_redaction(temp_dir.path(), &secrets).unwrap();
+
+        // The high-entropy candidate is reported, not rewritten
+        assert_eq!(
+            redaction.files[".zshrc"],
+            "export API_TOKEN=${API_TOKEN}\nexport THEME=q8Zt3mK1vR7xW2pL9sN4bY6c\n"
+        );
+        assert_eq!(
+            redaction.env,
+            vec![("API_TOKEN".to_string(), "abc123".to_string())]
+        );
+        // Nothing would expand a reference in JSON
+        assert!(!redaction.files.contains_key("settings.json"));
+        assert_eq!(redaction.skipped, vec!["settings.json"]);
+        assert!(redaction.preview().contains("settings.json: left as is"));
+
+        assert!(expands_env_refs(Path::new("zsh/.zshenv")));
+        assert!(expands_env_refs(Path::new("aliases.sh")));
+        assert!(!expands_env_refs(Path::new(".config/app/config.toml")));
+    }
+
     #[test]
     fn test_summarize_secrets() {
         let secrets = vec![
@@ -367,12 +983,14 @@ mod tests {
                 value: "abc123".to_string(),
                 file: "config.sh".to_string(),
                 line_number: 5,
+                kind: SecretKind::Keyword,
             },
             Secret {
                 key: "GITHUB_TOKEN".to_string(),
                 value: "xyz789".to_string(),
                 file: "config.sh".to_string(),
                 line_number: 10,
+                kind: SecretKind::HighEntropy,
             },
         ];
 
@@ -381,6 +999,24 @@ mod tests {
         assert!(summary.contains("Found 2 secret(s)"));
         assert!(summary.contains("config.sh"));
         assert!(summary.contains("Line 5: API_TOKEN"));
-        assert!(summary.contains("Line 10: GITHUB_TOKEN"));
+        assert!(summary.contains("Line 10: GITHUB_TOKEN (high entropy"));
+    }
+
+    #[test]
+    fn test_redact() {
+        let content = "export GITHUB_TOKEN=ghp_abc123\napi_key: \"sk-live\"\nEDITOR=nvim\n";
+        let redacted = redact(content);
+
+        assert_eq!(
+            redacted,
+            "export GITHUB_TOKEN=********\napi_key: \"********\"\nEDITOR=nvim\n"
+        );
+        assert!(!redacted.contains("ghp_abc123"));
+    }
+
+    #[test]
+    fn test_redact_leaves_non_secrets() {
+        let content = "SSH_KEY_PATH=/home/me/.ssh/id_ed25519";
+        assert_eq!(redact(content), content);
     }
 }
@@ -0,0 +1,174 @@
+use crate::core::audit::{self, Action};
+use crate::core::context::AppContext;
+use crate::error::{DotfilesError, Result};
+use crate::symlink::manual::ManualSymlinker;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Moves a config file into the dotfiles repo and links it back in place
+///
+/// Files under `xdg_config_home` land in the repo's `.config`; anything else
+/// keeps its path relative to `$HOME`.
+pub fn run(context: &AppContext, path: &Path) -> Result<()> {
+    let (home, config) = (&context.home, context.machine_config()?);
+
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    if path.is_symlink() {
+        return Err(DotfilesError::SymlinkConflict(format!(
+            "{} is already a symlink",
+            path.display()
+        )));
+    }
+    if !path.exists() {
+        return Err(DotfilesError::Config(format!(
+            "{} does not exist",
+            path.display()
+        )));
+    }
+
+    let destination = repo_destination(&path, home, &config.xdg_config_home, &config.dotfiles_dir)?;
+    if destination.exists() {
+        return Err(DotfilesError::SymlinkConflict(format!(
+            "{} already exists in the dotfiles repo",
+            destination.display()
+        )));
+    }
+
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    move_path(&path, &destination)?;
+    audit::record(Action::FileMoved {
+        from: path.clone(),
+        to: destination.clone(),
+    });
+    ManualSymlinker::new().create_symlink(&destination, &path)?;
+
+    println!(
+        "{}",
+        format!("✓ Adopted {} -> {}", path.display(), destination.display()).green()
+    );
+    if let Ok(rel) = destination.strip_prefix(&config.dotfiles_dir) {
+        println!(
+            "  Commit it: {}",
+            format!(
+                "git -C {} add {}",
+                config.dotfiles_dir.display(),
+                rel.display()
+            )
+            .cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns where `path` belongs inside the dotfiles repo
+pub fn repo_destination(
+    path: &Path,
+    home_dir: &Path,
+    xdg_config_home: &Path,
+    dotfiles_dir: &Path,
+) -> Result<PathBuf> {
+    if path.starts_with(dotfiles_dir) {
+        return Err(DotfilesError::Config(format!(
+            "{} is already inside the dotfiles repo",
+            path.display()
+        )));
+    }
+
+    if let Ok(rel) = path.strip_prefix(xdg_config_home) {
+        if !rel.as_os_str().is_empty() {
+            return Ok(dotfiles_dir.join(".config").join(rel));
+        }
+    }
+
+    match path.strip_prefix(home_dir) {
+        Ok(rel) if !rel.as_os_str().is_empty() => Ok(dotfiles_dir.join(rel)),
+        _ => Err(DotfilesError::Config(format!(
+            "{} is not inside your home directory",
+            path.display()
+        ))),
+    }
+}
+
+/// Renames `from` to `to`, copying files across filesystems when needed
+fn move_path(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        return Err(DotfilesError::Config(format!(
+            "Could not move {} into the repo (different filesystem?)",
+            from.display()
+        )));
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_repo_destination() {
+        let home = Path::new("/home/me");
+        let xdg = Path::new("/home/me/.config");
+        let dotfiles = Path::new("/home/me/dotfiles");
+
+        assert_eq!(
+            repo_destination(Path::new("/home/me/.psqlrc"), home, xdg, dotfiles).unwrap(),
+            PathBuf::from("/home/me/dotfiles/.psqlrc")
+        );
+        assert_eq!(
+            repo_destination(
+                Path::new("/home/me/.config/gh/config.yml"),
+                home,
+                xdg,
+                dotfiles
+            )
+            .unwrap(),
+            PathBuf::from("/home/me/dotfiles/.config/gh/config.yml")
+        );
+        assert!(repo_destination(Path::new("/etc/hosts"), home, xdg, dotfiles).is_err());
+        assert!(
+            repo_destination(Path::new("/home/me/dotfiles/.zshrc"), home, xdg, dotfiles).is_err()
+        );
+    }
+
+    #[test]
+    fn test_repo_destination_custom_xdg() {
+        let home = Path::new("/home/me");
+        let xdg = Path::new("/data/config");
+        let dotfiles = Path::new("/home/me/dotfiles");
+
+        assert_eq!(
+            repo_destination(Path::new("/data/config/starship.toml"), home, xdg, dotfiles).unwrap(),
+            PathBuf::from("/home/me/dotfiles/.config/starship.toml")
+        );
+    }
+
+    #[test]
+    fn test_move_path() {
+        let temp = TempDir::new().unwrap();
+        let from = temp.path().join(".psqlrc");
+        let to = temp.path().join("dotfiles/.psqlrc");
+        fs::write(&from, "\\timing").unwrap();
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+
+        move_path(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "\\timing");
+    }
+}
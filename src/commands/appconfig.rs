@@ -0,0 +1,102 @@
+use crate::core::context::AppContext;
+use crate::detect::os::{detect_os, OS};
+use crate::error::{DotfilesError, Result};
+use crate::install::appconfig::{self, App, Source, APPS};
+use colored::Colorize;
+use std::path::Path;
+
+/// Lists the supported apps and whether their settings are in the repo
+pub fn list(context: &AppContext) -> Result<()> {
+    let config = context.machine_config()?;
+    for app in APPS {
+        let path = app.repo_path(&config.dotfiles_dir);
+        let status = if path.exists() {
+            "exported".green()
+        } else {
+            "not exported".dimmed()
+        };
+        println!("{:<10} {:<10} {}", app.id.cyan(), app.name, status);
+    }
+    Ok(())
+}
+
+/// Copies an app's settings into the repo
+pub fn export(context: &AppContext, name: &str, from: Option<&Path>) -> Result<()> {
+    let app = lookup(name)?;
+    let (home, config) = (&context.home, context.machine_config()?);
+
+    let destination = appconfig::export(app, &config.dotfiles_dir, home, from)?;
+    println!(
+        "{}",
+        format!(
+            "✓ Exported {} settings to {}",
+            app.name,
+            destination.display()
+        )
+        .green()
+    );
+    if let Ok(rel) = destination.strip_prefix(&config.dotfiles_dir) {
+        println!(
+            "  Commit it: {}",
+            format!(
+                "git -C {} add {}",
+                config.dotfiles_dir.display(),
+                rel.display()
+            )
+            .cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Applies an app's settings from the repo to this machine
+pub fn import(context: &AppContext, name: &str) -> Result<()> {
+    let app = lookup(name)?;
+    let (home, config) = (&context.home, context.machine_config()?);
+
+    appconfig::import(app, &config.dotfiles_dir, home)?;
+    match app.source {
+        Source::Defaults { domain } => {
+            println!("{}", format!("✓ Imported {} settings", app.name).green());
+            println!(
+                "  Previous settings saved under {}",
+                appconfig::previous_settings_path(home, domain)
+                    .parent()
+                    .unwrap_or(home)
+                    .display()
+            );
+        }
+        Source::ExportFile { .. } => println!(
+            "{}",
+            format!(
+                "✓ Opened the export in {}; confirm the import there",
+                app.name
+            )
+            .green()
+        ),
+        Source::SyncFolder { .. } => {
+            println!(
+                "{}",
+                format!("✓ {} now reads its settings from the repo", app.name).green()
+            );
+            println!("  Restart {} to pick them up", app.name);
+        }
+    }
+    Ok(())
+}
+
+fn lookup(name: &str) -> Result<&'static App> {
+    if detect_os() != OS::MacOS {
+        return Err(DotfilesError::Config(
+            "App settings export and import are only supported on macOS".to_string(),
+        ));
+    }
+    appconfig::find(name).ok_or_else(|| {
+        let ids: Vec<&str> = APPS.iter().map(|app| app.id).collect();
+        DotfilesError::Config(format!(
+            "Unknown app {}. Supported: {}",
+            name,
+            ids.join(", ")
+        ))
+    })
+}
@@ -0,0 +1,414 @@
+use crate::backup::browse::{self, BrowseEntry, EntryStatus};
+use crate::backup::remote::{self, Remote};
+use crate::backup::{self, diff, BackupInfo, BackupLayout, BackupLimits};
+use crate::core::config::{BackupFormat, BackupSettings};
+use crate::core::context::AppContext;
+use crate::core::sudo::is_interactive;
+use crate::core::timing;
+use crate::error::{DotfilesError, Result};
+use chrono::Local;
+use colored::Colorize;
+use dialoguer::{Confirm, Select};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many unchanged lines `backup browse` prints on either side of a change
+/// when comparing a backed-up file with the current one
+const DIFF_CONTEXT: usize = 3;
+
+/// Backs up the dotfiles repo in the configured format, or encrypted with `encrypt`
+///
+/// With `push` the new backup is also uploaded to `[backups] remote`, which
+/// needs an encrypted backup unless `allow_unencrypted` is set.
+pub fn create(
+    context: &AppContext,
+    encrypt: bool,
+    push: bool,
+    allow_unencrypted: bool,
+) -> Result<()> {
+    let config = context.machine_config()?;
+    let mut layout = BackupLayout::from_settings(&config.backups, &context.home);
+    if encrypt {
+        layout.format = BackupFormat::EncryptedArchive;
+    }
+    // Checked first so a missing remote doesn't leave a backup that was meant to be pushed
+    let remote = if push {
+        let remote = Remote::from_settings(&config.backups)?;
+        check_push_encryption(&remote, layout.format, allow_unencrypted)?;
+        Some(remote)
+    } else {
+        None
+    };
+
+    let created = backup::create_backup_in(&config.dotfiles_dir, &layout)?;
+    let path = created.path;
+    println!(
+        "{}",
+        format!("✓ Created backup at {}", path.display()).green()
+    );
+    if let Some(stats) = created.incremental {
+        println!(
+            "  Copied {} new or changed files, linked {} unchanged",
+            stats.copied, stats.linked
+        );
+    }
+    if let Some(remote) = remote {
+        if layout.format != BackupFormat::EncryptedArchive {
+            println!(
+                "{}",
+                "  Pushing an unencrypted backup (--allow-unencrypted)".yellow()
+            );
+        }
+        remote::push(&remote, &path, &remote.cache_dir(&context.state_dir))?;
+        println!("{}", format!("✓ Pushed backup to {}", remote).green());
+    }
+    if layout.format == BackupFormat::EncryptedArchive {
+        println!(
+            "  Restoring it needs the age identity in {}",
+            layout.age_identity.display()
+        );
+    }
+    println!(
+        "  Restore with: {}",
+        format!(
+            "dotfiles restore --timestamp {}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        )
+        .cyan()
+    );
+    Ok(())
+}
+
+/// Refuses to push a plaintext backup unless the user opted in
+fn check_push_encryption(
+    remote: &Remote,
+    format: BackupFormat,
+    allow_unencrypted: bool,
+) -> Result<()> {
+    if format == BackupFormat::EncryptedArchive || allow_unencrypted {
+        return Ok(());
+    }
+    Err(DotfilesError::Config(format!(
+        "Not pushing an unencrypted backup to {}; use --encrypt, or --allow-unencrypted if the remote is private",
+        remote
+    )))
+}
+
+/// Interactively browses backups: drill into them, view or diff files, restore entries
+///
+/// Esc goes back one level; Esc on the backup list quits.
+pub fn browse(context: &AppContext) -> Result<()> {
+    let home = &context.home;
+    let layout = BackupLayout::from_context(context)?;
+    let backups = backup::list_in(&layout)?;
+    let legacy = backup::legacy_backups(home, &layout)?;
+    if !legacy.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "{} old .dotfiles-backup-* directories in {}; move them here with: dotfiles backup migrate",
+                legacy.len(),
+                home.display()
+            )
+            .dimmed()
+        );
+    }
+
+    if backups.is_empty() {
+        println!(
+            "{}",
+            format!("No backups in {}", layout.dir.display()).yellow()
+        );
+        return Ok(());
+    }
+
+    let now = Local::now();
+    let labels: Vec<String> = backups
+        .iter()
+        .map(|backup| format!("{}  {}", backup.when(now), source_label(backup, home)))
+        .collect();
+
+    loop {
+        let Some(index) = select("Backups (Esc to quit)", &labels)? else {
+            return Ok(());
+        };
+        let backup = &backups[index];
+        let current_root = if backup.source.as_os_str().is_empty() {
+            home.clone()
+        } else {
+            backup.source.clone()
+        };
+        if backup.is_archive() {
+            // Browse a private temporary extraction, removed when dropped;
+            // restored entries are copied out of it
+            let unpacked = tempfile::Builder::new()
+                .prefix("dotfiles-browse-")
+                .tempdir()?;
+            backup::unpack(&backup.path, unpacked.path(), &layout.age_identity)?;
+            browse_backup(
+                &BackupInfo {
+                    path: unpacked.path().to_path_buf(),
+                    ..backup.clone()
+                },
+                &current_root,
+            )?;
+        } else {
+            browse_backup(backup, &current_root)?;
+        }
+    }
+}
+
+/// Moves old `~/.dotfiles-backup-*` directories into the configured backup directory
+pub fn migrate(context: &AppContext) -> Result<()> {
+    let home = &context.home;
+    let layout = BackupLayout::from_context(context)?;
+
+    let moved = backup::migrate_legacy_backups(home, &layout)?;
+    if moved.is_empty() {
+        println!("No .dotfiles-backup-* directories to move");
+    }
+    for (from, to) in &moved {
+        println!(
+            "{}",
+            format!("✓ Moved {} -> {}", from.display(), to.display()).green()
+        );
+    }
+    let left = backup::legacy_backups(home, &layout)?;
+    if !left.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "⚠ Left {} backup(s) in {} whose new name is already taken",
+                left.len(),
+                home.display()
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Deletes the oldest backups, including legacy ones in `$HOME`
+///
+/// Keeps the newest `keep`, or as many as fit within `[backups] max_count`
+/// and `max_size_mb` when `keep` is None; the newest backup is always kept.
+/// Asks before deleting, or needs `assume_yes` when it can't ask.
+pub fn prune(
+    context: &AppContext,
+    keep: Option<usize>,
+    dry_run: bool,
+    assume_yes: bool,
+) -> Result<()> {
+    let home = &context.home;
+    let settings = if context.config_path.exists() {
+        context.config()?.backups.clone()
+    } else {
+        BackupSettings::default()
+    };
+    let layout = BackupLayout::from_settings(&settings, home);
+    let backups = backup::all_backups(home, &layout)?;
+    let sizes = timing::disk_sizes(backups.iter().map(|backup| backup.path.as_path()));
+    let keep = keep
+        .unwrap_or_else(|| BackupLimits::from_settings(&settings).keep(&sizes))
+        .max(1);
+
+    let pruned = backups.get(keep..).unwrap_or_default();
+    if pruned.is_empty() {
+        println!("Nothing to prune ({} backup(s))", backups.len());
+        return Ok(());
+    }
+    let savings: u64 = sizes[keep..].iter().sum();
+    let now = Local::now();
+    for backup in pruned {
+        println!("  {}  {}", backup.when(now), backup.path.display());
+    }
+    if dry_run {
+        println!(
+            "Would delete {} backup(s), freeing {}",
+            pruned.len(),
+            timing::format_size(savings)
+        );
+        return Ok(());
+    }
+    if !assume_yes {
+        if !is_interactive() {
+            return Err(DotfilesError::Config(format!(
+                "Not deleting {} backup(s) without confirmation; re-run with --yes",
+                pruned.len()
+            )));
+        }
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Delete {} backup(s), freeing {}? The newest {} are kept",
+                pruned.len(),
+                timing::format_size(savings),
+                keep
+            ))
+            .default(false)
+            .interact()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    for backup in pruned {
+        backup::remove_backup(&backup.path)?;
+    }
+    println!(
+        "{}",
+        format!(
+            "✓ Deleted {} backup(s), freed {}",
+            pruned.len(),
+            timing::format_size(savings)
+        )
+        .green()
+    );
+    Ok(())
+}
+
+/// Describes where a backup was taken from, for menus
+pub(crate) fn source_label(backup: &BackupInfo, home: &Path) -> String {
+    if backup.source.as_os_str().is_empty() {
+        format!("(source not recorded, compared with {})", home.display())
+            .dimmed()
+            .to_string()
+    } else {
+        format!("of {}", backup.source.display())
+    }
+}
+
+fn browse_backup(backup: &BackupInfo, current_root: &Path) -> Result<()> {
+    let mut rel = PathBuf::new();
+
+    loop {
+        let entries = browse::list_entries(&backup.path.join(&rel), &current_root.join(&rel))?;
+        let labels: Vec<String> = entries.iter().map(entry_label).collect();
+        let prompt = format!("{}/{} (Esc to go back)", backup.timestamp, rel.display());
+
+        match select(&prompt, &labels)? {
+            None if rel.as_os_str().is_empty() => return Ok(()),
+            None => {
+                rel.pop();
+            }
+            Some(index) if entries[index].is_dir => {
+                let entry = &entries[index];
+                let actions = ["Open", "Restore directory"];
+                match select(&entry.name, &actions)? {
+                    Some(0) => rel.push(&entry.name),
+                    Some(_) => restore(entry)?,
+                    None => {}
+                }
+            }
+            Some(index) => file_menu(&entries[index])?,
+        }
+    }
+}
+
+fn entry_label(entry: &BrowseEntry) -> String {
+    let marker = match entry.status {
+        EntryStatus::Unchanged => entry.status.marker().normal(),
+        EntryStatus::Modified => entry.status.marker().yellow(),
+        EntryStatus::Deleted => entry.status.marker().red(),
+    };
+    let name = if entry.is_dir {
+        format!("{}/", entry.name).bold().to_string()
+    } else {
+        entry.name.clone()
+    };
+    format!("{} {}", marker, name)
+}
+
+fn file_menu(entry: &BrowseEntry) -> Result<()> {
+    let actions = ["View", "Diff against current", "Restore", "Back"];
+
+    loop {
+        match select(&entry.name, &actions)? {
+            Some(0) => match fs::read_to_string(&entry.backup_path) {
+                Ok(content) => println!("{}", content),
+                Err(_) => println!("{}", "(binary file)".dimmed()),
+            },
+            Some(1) => print_diff(entry),
+            Some(2) => return restore(entry),
+            _ => return Ok(()),
+        }
+    }
+}
+
+fn print_diff(entry: &BrowseEntry) {
+    match entry.status {
+        EntryStatus::Unchanged => println!("{}", "No differences".green()),
+        EntryStatus::Deleted => println!(
+            "{}",
+            format!("{} no longer exists", entry.current_path.display()).yellow()
+        ),
+        EntryStatus::Modified => {
+            let (Ok(old), Ok(new)) = (
+                fs::read_to_string(&entry.backup_path),
+                fs::read_to_string(&entry.current_path),
+            ) else {
+                println!("{}", "(binary file differs)".dimmed());
+                return;
+            };
+            println!(
+                "{}",
+                format!("--- backup\n+++ {}", entry.current_path.display()).bold()
+            );
+            print!(
+                "{}",
+                diff::render_diff(&diff::diff_lines(&old, &new), DIFF_CONTEXT)
+            );
+        }
+    }
+}
+
+fn restore(entry: &BrowseEntry) -> Result<()> {
+    if entry.status == EntryStatus::Unchanged {
+        println!("{}", "Already matches the backup".green());
+        return Ok(());
+    }
+
+    let confirmed = Confirm::new()
+        .with_prompt(format!(
+            "Overwrite {} with the backup copy?",
+            entry.current_path.display()
+        ))
+        .default(false)
+        .interact()
+        .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+
+    if confirmed {
+        browse::restore_entry(entry)?;
+        println!(
+            "{}",
+            format!("✓ Restored {}", entry.current_path.display()).green()
+        );
+    }
+
+    Ok(())
+}
+
+/// Shows a menu, returning None when the user presses Esc
+fn select<T: ToString>(prompt: &str, items: &[T]) -> Result<Option<usize>> {
+    Select::new()
+        .with_prompt(prompt)
+        .items(items)
+        .default(0)
+        .interact_opt()
+        .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_push_encryption() {
+        let remote = Remote::Git("git@example.com:me/backups.git".to_string());
+        assert!(check_push_encryption(&remote, BackupFormat::EncryptedArchive, false).is_ok());
+        assert!(check_push_encryption(&remote, BackupFormat::Directory, true).is_ok());
+
+        let err = check_push_encryption(&remote, BackupFormat::Directory, false).unwrap_err();
+        assert!(err.to_string().contains("--allow-unencrypted"));
+    }
+}
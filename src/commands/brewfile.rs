@@ -0,0 +1,88 @@
+use crate::core::context::AppContext;
+use crate::error::{DotfilesError, Result};
+use crate::install::brewfile::{self, Installed};
+use crate::install::homebrew::{self, PackageKind};
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+/// Writes a Brewfile for this machine's package set into the dotfiles repo
+///
+/// `output` overrides the default `<dotfiles_dir>/Brewfile`; with `dry_run`
+/// the Brewfile is printed instead of written.
+pub fn export(context: &AppContext, output: Option<&Path>, dry_run: bool) -> Result<()> {
+    let config = context.machine_config()?;
+    let rendered = brewfile::export(&config.packages).render();
+    if dry_run {
+        print!("{}", rendered);
+        return Ok(());
+    }
+
+    let destination = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| brewfile::path(&config.dotfiles_dir));
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&destination, rendered)?;
+    println!("{}", format!("✓ Wrote {}", destination.display()).green());
+    if let Ok(rel) = destination.strip_prefix(&config.dotfiles_dir) {
+        println!(
+            "  Commit it: {}",
+            format!(
+                "git -C {} add {}",
+                config.dotfiles_dir.display(),
+                rel.display()
+            )
+            .cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Installs whatever the repo's Brewfile lists that isn't installed yet
+pub fn install(context: &AppContext, dry_run: bool) -> Result<()> {
+    let config = context.machine_config()?;
+    let brewfile = brewfile::read(&config.dotfiles_dir)?.ok_or_else(|| {
+        DotfilesError::Config(format!(
+            "No Brewfile in {}. Create one with: dotfiles brewfile export",
+            config.dotfiles_dir.display()
+        ))
+    })?;
+    if !homebrew::is_installed() {
+        return Err(DotfilesError::DependencyMissing("Homebrew".to_string()));
+    }
+    for line in &brewfile.unsupported {
+        eprintln!("Warning: Skipping unsupported Brewfile line: {}", line);
+    }
+
+    let drift = brewfile.drift(&Installed::query());
+    if drift.missing_taps.is_empty() && drift.missing.is_empty() {
+        println!("{}", "✓ Everything in the Brewfile is installed".green());
+        return Ok(());
+    }
+
+    if dry_run {
+        for tap in &drift.missing_taps {
+            println!("Would tap {}", tap);
+        }
+        for (kind, name) in &drift.missing {
+            match kind {
+                PackageKind::Formula => println!("Would install {}", name),
+                PackageKind::Cask => println!("Would install {} (cask)", name),
+            }
+        }
+        return Ok(());
+    }
+
+    let installed = brewfile::install(&drift)?;
+    println!(
+        "{}",
+        format!(
+            "✓ Installed {} package(s) from the Brewfile",
+            installed.len()
+        )
+        .green()
+    );
+    Ok(())
+}
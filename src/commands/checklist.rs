@@ -0,0 +1,187 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::context::AppContext;
+use crate::core::state::State;
+use crate::detect::os::OS;
+use crate::error::{DotfilesError, Result};
+use colored::Colorize;
+
+/// A manual step that setup cannot automate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// How to do it
+    pub hint: &'static str,
+    pub macos_only: bool,
+}
+
+/// Manual steps remaining after setup, in the order they are usually done
+pub const ITEMS: &[ChecklistItem] = &[
+    ChecklistItem {
+        id: "gh-auth",
+        title: "Authenticate the GitHub CLI",
+        hint: "gh auth login",
+        macos_only: false,
+    },
+    ChecklistItem {
+        id: "app-store",
+        title: "Sign in to the App Store",
+        hint: "Open App Store.app and sign in (needed for mas installs)",
+        macos_only: true,
+    },
+    ChecklistItem {
+        id: "iterm-prefs",
+        title: "Import iTerm2 preferences",
+        hint: "dotfiles appconfig import iterm2 (with iTerm2 quit)",
+        macos_only: true,
+    },
+    ChecklistItem {
+        id: "gui-licenses",
+        title: "License GUI apps",
+        hint: "Enter license keys for paid apps from your password manager",
+        macos_only: true,
+    },
+];
+
+/// Returns the checklist items that apply to this OS
+pub fn applicable_items(os: OS) -> Vec<&'static ChecklistItem> {
+    ITEMS
+        .iter()
+        .filter(|item| !item.macos_only || os == OS::MacOS)
+        .collect()
+}
+
+/// Prints the checklist with each item's status
+pub fn show(context: &AppContext) -> Result<()> {
+    let state = State::load(&context.state_path)?;
+    print!("{}", render(&state, context.os));
+    Ok(())
+}
+
+/// Marks an item done
+pub fn done(context: &AppContext, id: &str) -> Result<()> {
+    let item = find(id)?;
+    let path = &context.state_path;
+    let mut state = State::load(path)?;
+    state.checklist_done.insert(
+        item.id.to_string(),
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    );
+    state.save(path)?;
+
+    println!("{}", format!("✓ {}", item.title).green());
+    Ok(())
+}
+
+/// Marks an item not done again
+pub fn undo(context: &AppContext, id: &str) -> Result<()> {
+    let item = find(id)?;
+    let path = &context.state_path;
+    let mut state = State::load(path)?;
+    if state.checklist_done.remove(item.id).is_some() {
+        state.save(path)?;
+    }
+
+    println!("☐ {}", item.title);
+    Ok(())
+}
+
+/// Renders the checklist; `gh-auth` also counts as done when `gh` is logged in
+pub fn render(state: &State, os: OS) -> String {
+    let items = applicable_items(os);
+    let is_done = |item: &ChecklistItem| {
+        state.checklist_done.contains_key(item.id)
+            || (item.id == "gh-auth" && is_gh_authenticated())
+    };
+    let statuses: Vec<bool> = items.iter().map(|item| is_done(item)).collect();
+    let completed = statuses.iter().filter(|done| **done).count();
+
+    let mut out = format!(
+        "{}\n",
+        format!(
+            "📋 Bootstrap checklist ({}/{} done)",
+            completed,
+            items.len()
+        )
+        .bold()
+    );
+    for (item, done) in items.iter().zip(statuses) {
+        if done {
+            out.push_str(&format!("  {} {}\n", "✓".green(), item.title.dimmed()));
+        } else {
+            out.push_str(&format!(
+                "  ☐ {} {}\n",
+                item.title,
+                format!("[{}]", item.id).dimmed()
+            ));
+            out.push_str(&format!("      {}\n", item.hint.cyan()));
+        }
+    }
+
+    if completed < items.len() {
+        out.push_str(&format!(
+            "\n  Mark items done: {}\n",
+            "dotfiles checklist done <item>".cyan()
+        ));
+    }
+    out
+}
+
+fn find(id: &str) -> Result<&'static ChecklistItem> {
+    ITEMS.iter().find(|item| item.id == id).ok_or_else(|| {
+        let ids: Vec<&str> = ITEMS.iter().map(|item| item.id).collect();
+        DotfilesError::Config(format!(
+            "Unknown checklist item '{}' (expected one of: {})",
+            id,
+            ids.join(", ")
+        ))
+    })
+}
+
+fn is_gh_authenticated() -> bool {
+    CommandRunner::new("gh")
+        .args(["auth", "status"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .is_ok_and(|output| output.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::strip_ansi;
+
+    #[test]
+    fn test_applicable_items() {
+        assert_eq!(applicable_items(OS::MacOS).len(), ITEMS.len());
+
+        let linux: Vec<&str> = applicable_items(OS::Linux)
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        assert_eq!(linux, vec!["gh-auth"]);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find("iterm-prefs").unwrap().id, "iterm-prefs");
+
+        let err = find("nope").unwrap_err().to_string();
+        assert!(err.contains("gh-auth"));
+    }
+
+    #[test]
+    fn test_render_marks_done_items() {
+        let mut state = State::default();
+        state
+            .checklist_done
+            .insert("app-store".to_string(), "2026-01-01 10:00:00".to_string());
+
+        let rendered = strip_ansi(&render(&state, OS::MacOS));
+
+        assert!(rendered.contains("✓ Sign in to the App Store"));
+        assert!(rendered.contains("☐ Import iTerm2 preferences [iterm-prefs]"));
+        assert!(rendered.contains("dotfiles checklist done <item>"));
+    }
+}
@@ -0,0 +1,354 @@
+use crate::commands::doctor::{self, DoctorOutput};
+use crate::core::context::AppContext;
+use crate::detect::inventory::Inventory;
+use crate::detect::system::SystemInfo;
+use crate::error::{DotfilesError, Result};
+use crate::validate::CheckResult;
+use colored::Colorize;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// The parts of a `doctor --json` export that are compared
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DoctorExport {
+    pub system: SystemInfo,
+    pub inventory: Inventory,
+    pub checks: Vec<CheckResult>,
+}
+
+impl From<DoctorOutput> for DoctorExport {
+    fn from(output: DoctorOutput) -> Self {
+        Self {
+            system: output.system,
+            inventory: output.inventory,
+            checks: output.report.checks,
+        }
+    }
+}
+
+/// Entries present on only one of the two machines
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ListDiff {
+    pub only_here: Vec<String>,
+    pub only_there: Vec<String>,
+}
+
+impl ListDiff {
+    fn new(here: &[String], there: &[String]) -> Self {
+        let here: BTreeSet<&String> = here.iter().collect();
+        let there: BTreeSet<&String> = there.iter().collect();
+        Self {
+            only_here: here.difference(&there).map(|s| s.to_string()).collect(),
+            only_there: there.difference(&here).map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.only_here.is_empty() && self.only_there.is_empty()
+    }
+}
+
+/// A check whose worst status differs between the machines (`None` = not run)
+#[derive(Debug, PartialEq, Eq)]
+pub struct CheckDiff {
+    pub name: String,
+    pub here: Option<CheckResult>,
+    pub there: Option<CheckResult>,
+}
+
+/// Everything that differs between the local doctor run and an export
+#[derive(Debug, Default)]
+pub struct MachineDiff {
+    /// (field, here, there) for system info that differs
+    pub system: Vec<(&'static str, String, String)>,
+    pub packages: ListDiff,
+    pub links: ListDiff,
+    pub runtimes: ListDiff,
+    pub checks: Vec<CheckDiff>,
+}
+
+impl MachineDiff {
+    /// Returns the number of differing entries
+    pub fn count(&self) -> usize {
+        let lists = [&self.packages, &self.links, &self.runtimes];
+        self.system.len()
+            + self.checks.len()
+            + lists
+                .iter()
+                .map(|list| list.only_here.len() + list.only_there.len())
+                .sum::<usize>()
+    }
+}
+
+/// Diffs a doctor export from another machine against the local doctor run
+///
+/// Prints system, package, link, runtime, and check differences.
+pub fn run(context: &AppContext, path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| DotfilesError::Config(format!("Could not read {}: {}", path.display(), e)))?;
+    let there: DoctorExport = serde_json::from_str(&content).map_err(|e| {
+        DotfilesError::Config(format!(
+            "{} is not a doctor export ({}). Create one with: dotfiles doctor --json > machine.json",
+            path.display(),
+            e
+        ))
+    })?;
+    let label = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "other".to_string());
+
+    println!("{}", format!("🔍 Comparing with {}", label).bold());
+    println!();
+    println!("Running local checks...");
+    let here = DoctorExport::from(doctor::collect(context, false, true));
+
+    let diff = diff_exports(&here, &there);
+    print!("{}", render(&diff, &label));
+    Ok(())
+}
+
+/// Compares two doctor exports
+pub fn diff_exports(here: &DoctorExport, there: &DoctorExport) -> MachineDiff {
+    MachineDiff {
+        system: diff_system(&here.system, &there.system),
+        packages: ListDiff::new(&here.inventory.packages, &there.inventory.packages),
+        links: ListDiff::new(&here.inventory.links, &there.inventory.links),
+        runtimes: ListDiff::new(&here.inventory.runtimes, &there.inventory.runtimes),
+        checks: diff_checks(&here.checks, &there.checks),
+    }
+}
+
+fn diff_system(here: &SystemInfo, there: &SystemInfo) -> Vec<(&'static str, String, String)> {
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "unknown".to_string());
+    let fields = [
+        ("OS", here.os.clone(), there.os.clone()),
+        (
+            "OS version",
+            show(&here.os_version),
+            show(&there.os_version),
+        ),
+        ("Arch", here.arch.clone(), there.arch.clone()),
+        ("Shell", show(&here.shell), show(&there.shell)),
+        (
+            "Brew prefix",
+            show(&here.brew_prefix),
+            show(&there.brew_prefix),
+        ),
+        ("Tags", here.tags.join(", "), there.tags.join(", ")),
+        (
+            "Tool version",
+            here.tool_version.clone(),
+            there.tool_version.clone(),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, here, there)| here != there)
+        .collect()
+}
+
+/// Pairs checks by name (keeping the worst result per name) and returns those that differ
+fn diff_checks(here: &[CheckResult], there: &[CheckResult]) -> Vec<CheckDiff> {
+    let here = worst_by_name(here);
+    let there = worst_by_name(there);
+    let names: BTreeSet<&str> = here.keys().chain(there.keys()).copied().collect();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (a, b) = (here.get(name), there.get(name));
+            let differs = match (a, b) {
+                (Some(a), Some(b)) => severity(a) != severity(b),
+                _ => true,
+            };
+            differs.then(|| CheckDiff {
+                name: name.to_string(),
+                here: a.map(|c| (*c).clone()),
+                there: b.map(|c| (*c).clone()),
+            })
+        })
+        .collect()
+}
+
+fn worst_by_name(checks: &[CheckResult]) -> BTreeMap<&str, &CheckResult> {
+    let mut worst: BTreeMap<&str, &CheckResult> = BTreeMap::new();
+    for check in checks {
+        let entry = worst.entry(check.name()).or_insert(check);
+        if severity(check) > severity(entry) {
+            *entry = check;
+        }
+    }
+    worst
+}
+
+fn severity(check: &CheckResult) -> u8 {
+    match check {
+        CheckResult::Pass { .. } => 0,
+        CheckResult::Warn { .. } => 1,
+        CheckResult::Error { .. } => 2,
+    }
+}
+
+fn status_label(check: Option<&CheckResult>) -> String {
+    match check {
+        Some(CheckResult::Pass { .. }) => "pass".green().to_string(),
+        Some(CheckResult::Warn { .. }) => "warn".yellow().to_string(),
+        Some(CheckResult::Error { .. }) => "error".red().to_string(),
+        None => "not run".dimmed().to_string(),
+    }
+}
+
+/// Renders the diff, labelling the other machine with `label`
+pub fn render(diff: &MachineDiff, label: &str) -> String {
+    let mut out = String::new();
+
+    if !diff.system.is_empty() {
+        out.push_str(&format!("\n{}\n", "System".bold().underline()));
+        for (field, here, there) in &diff.system {
+            out.push_str(&format!(
+                "  {}: {} (here) vs {} ({})\n",
+                field.bold(),
+                here,
+                there,
+                label
+            ));
+        }
+    }
+
+    for (heading, list) in [
+        ("Packages", &diff.packages),
+        ("Links", &diff.links),
+        ("Runtimes", &diff.runtimes),
+    ] {
+        if list.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n{}\n", heading.bold().underline()));
+        for item in &list.only_here {
+            out.push_str(&format!(
+                "  {} {} {}\n",
+                "+".green(),
+                item,
+                "(only here)".dimmed()
+            ));
+        }
+        for item in &list.only_there {
+            out.push_str(&format!(
+                "  {} {} {}\n",
+                "-".red(),
+                item,
+                format!("(only on {})", label).dimmed()
+            ));
+        }
+    }
+
+    if !diff.checks.is_empty() {
+        out.push_str(&format!("\n{}\n", "Checks".bold().underline()));
+        for check in &diff.checks {
+            out.push_str(&format!(
+                "  {} - {} here, {} on {}\n",
+                check.name.bold(),
+                status_label(check.here.as_ref()),
+                status_label(check.there.as_ref()),
+                label
+            ));
+            // Show why the failing side failed
+            for (side, result) in [("here", &check.here), (label, &check.there)] {
+                if let Some(result) = result.as_ref().filter(|r| !r.is_pass()) {
+                    out.push_str(&format!("    {}: {}\n", side, result.message().dimmed()));
+                }
+            }
+        }
+    }
+
+    out.push_str(&format!("\n{}\n", "Summary".bold().underline()));
+    match diff.count() {
+        0 => out.push_str(&format!("  {} No differences\n", "✓".green())),
+        n => out.push_str(&format!("  {} differences\n", n)),
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::strip_ansi;
+
+    fn export(packages: &[&str], checks: Vec<CheckResult>) -> DoctorExport {
+        DoctorExport {
+            system: SystemInfo {
+                os: "macos".to_string(),
+                arch: "aarch64".to_string(),
+                ..SystemInfo::default()
+            },
+            inventory: Inventory {
+                packages: packages.iter().map(|p| p.to_string()).collect(),
+                ..Inventory::default()
+            },
+            checks,
+        }
+    }
+
+    #[test]
+    fn test_diff_exports() {
+        let here = export(
+            &["git", "ripgrep"],
+            vec![
+                CheckResult::pass("Symlinks:~/.zshrc", "ok"),
+                CheckResult::pass("Homebrew", "Installed"),
+                CheckResult::pass("Essential Package", "ok"),
+            ],
+        );
+        let mut there = export(
+            &["git", "fd"],
+            vec![
+                CheckResult::error("Symlinks:~/.zshrc", "missing", None::<String>),
+                CheckResult::pass("Homebrew", "Installed"),
+                CheckResult::pass("Essential Package", "ok"),
+                CheckResult::warn("Essential Package", "Missing: jq", None::<String>),
+            ],
+        );
+        there.system.arch = "x86_64".to_string();
+
+        let diff = diff_exports(&here, &there);
+        assert_eq!(
+            diff.system,
+            vec![("Arch", "aarch64".to_string(), "x86_64".to_string())]
+        );
+        assert_eq!(diff.packages.only_here, vec!["ripgrep".to_string()]);
+        assert_eq!(diff.packages.only_there, vec!["fd".to_string()]);
+        let names: Vec<&str> = diff.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Essential Package", "Symlinks:~/.zshrc"]);
+        assert!(diff.checks[1].there.as_ref().unwrap().is_error());
+        assert_eq!(diff.count(), 5);
+    }
+
+    #[test]
+    fn test_export_parses_doctor_json() {
+        let json = r#"{
+            "system": {"os": "linux", "arch": "x86_64", "tags": ["work"]},
+            "checks": [{"status": "warn", "name": "Git:pager", "message": "unset", "suggestion": null}],
+            "timings": [],
+            "wall_time_ms": 12
+        }"#;
+        let export: DoctorExport = serde_json::from_str(json).unwrap();
+        assert_eq!(export.system.tags, vec!["work".to_string()]);
+        assert!(export.inventory.packages.is_empty());
+        assert!(export.checks[0].is_warn());
+    }
+
+    #[test]
+    fn test_render_no_differences() {
+        let here = export(&["git"], Vec::new());
+        let rendered = strip_ansi(&render(
+            &diff_exports(&here, &export(&["git"], Vec::new())),
+            "laptop",
+        ));
+        assert!(rendered.contains("No differences"));
+        assert!(!rendered.contains("Packages"));
+    }
+}
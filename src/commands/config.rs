@@ -0,0 +1,38 @@
+use crate::commands::setup::print_problems;
+use crate::core::context::AppContext;
+use crate::error::Result;
+use colored::Colorize;
+
+/// Prints the effective configuration and any problems with it
+pub fn show(context: &AppContext) -> Result<()> {
+    let config_path = &context.config_path;
+
+    if !config_path.exists() {
+        println!(
+            "{}",
+            format!("⚠ {} not found", config_path.display()).yellow()
+        );
+        println!("  Run: {} to create it", "dotfiles setup".cyan());
+        return Ok(());
+    }
+
+    let config = context.config()?;
+
+    println!(
+        "{}",
+        format!("📄 {}", config_path.display()).bold().underline()
+    );
+    println!();
+    print!("{}", toml::to_string_pretty(&config)?);
+    println!();
+
+    let problems = config.validate();
+    if problems.is_empty() {
+        println!("{}", "✓ No problems found".green());
+    } else {
+        println!("{}", "🔎 Problems".bold().underline());
+        print_problems(&problems);
+    }
+
+    Ok(())
+}
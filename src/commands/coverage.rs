@@ -0,0 +1,496 @@
+use crate::core::config::Config;
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::core::paths;
+use crate::core::paths::display_path;
+use crate::core::state::State;
+use crate::core::walk::RepoWalker;
+use crate::error::Result;
+use crate::install::templates::{self, TEMPLATE_DIR};
+use crate::install::{copies, karabiner, kubernetes, obsidian};
+use crate::symlink::{self, EXCLUSIONS, INDIVIDUAL_FILE_SYMLINK_DIRS};
+use colored::Colorize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// What setup does with one file in the repo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Handling {
+    /// Linked into place, and the link is there
+    Linked(PathBuf),
+    /// Copied into place (`[files]` with `mode = "copy"`)
+    Copied(PathBuf),
+    /// Rendered from `templates/home`
+    Rendered(PathBuf),
+    /// Deliberately left out of `$HOME`, and why
+    Excluded(String),
+    /// Meant to end up in `$HOME` but doesn't, and why
+    Unhandled(String),
+}
+
+impl Handling {
+    fn label(&self) -> &'static str {
+        match self {
+            Handling::Linked(_) => "linked",
+            Handling::Copied(_) => "copied",
+            Handling::Rendered(_) => "rendered",
+            Handling::Excluded(_) => "excluded",
+            Handling::Unhandled(_) => "unhandled",
+        }
+    }
+}
+
+/// A file or link in `$HOME` the tool put there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedTarget {
+    pub target: PathBuf,
+    /// The repo file it comes from, if it is still known
+    pub source: Option<PathBuf>,
+    /// "link", "copy", or "template"
+    pub kind: &'static str,
+}
+
+impl ManagedTarget {
+    fn source_exists(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| source.symlink_metadata().is_ok())
+    }
+}
+
+/// Reports what happens to every file in the repo, and whether every
+/// tool-managed target in `$HOME` still has its source
+///
+/// Exits with `warnings` when a file is unhandled or a target is orphaned.
+pub fn run(context: &AppContext) -> Result<ExitStatus> {
+    let (home, config) = (&context.home, context.machine_config()?);
+    let link_root = config.link_root(home);
+    let state = State::load(&context.state_path)?;
+
+    let files = repo_coverage(&config, home, &link_root)?;
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    println!(
+        "{}",
+        format!("Files in {}", config.dotfiles_dir.display()).bold()
+    );
+    for (rel, handling) in &files {
+        *counts.entry(handling.label()).or_default() += 1;
+        let label = format!("{:<10}", handling.label());
+        let line = match handling {
+            Handling::Linked(target) | Handling::Copied(target) | Handling::Rendered(target) => {
+                format!(
+                    "{} -> {}",
+                    rel.display(),
+                    display_path(target, home).dimmed()
+                )
+            }
+            Handling::Excluded(reason) | Handling::Unhandled(reason) => {
+                format!("{}  {}", rel.display(), format!("({})", reason).dimmed())
+            }
+        };
+        let label = match handling {
+            Handling::Excluded(_) => label.dimmed(),
+            Handling::Unhandled(_) => label.yellow(),
+            _ => label.green(),
+        };
+        println!("  {} {}", label, line);
+    }
+    println!(
+        "  {}",
+        ["linked", "copied", "rendered", "excluded", "unhandled"]
+            .iter()
+            .map(|label| format!("{} {}", counts.get(label).unwrap_or(&0), label))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    );
+
+    let targets = managed_targets(&config, home, &link_root, &state);
+    let orphaned: Vec<&ManagedTarget> = targets.iter().filter(|t| !t.source_exists()).collect();
+    println!();
+    println!(
+        "{}",
+        format!("Managed targets in {}", link_root.display()).bold()
+    );
+    println!(
+        "  {}",
+        format!(
+            "✓ {} of {} still have their source",
+            targets.len() - orphaned.len(),
+            targets.len()
+        )
+        .green()
+    );
+    for target in &orphaned {
+        let source = match &target.source {
+            Some(source) => format!("{} is gone", source.display()),
+            None => "no longer in the config".to_string(),
+        };
+        println!(
+            "  {}",
+            format!(
+                "✗ {} ({}, {})",
+                display_path(&target.target, home),
+                target.kind,
+                source
+            )
+            .yellow()
+        );
+    }
+    if !orphaned.is_empty() {
+        println!(
+            "  {}",
+            "Delete these, or restore their source in the repo".dimmed()
+        );
+    }
+
+    let unhandled = counts.get("unhandled").copied().unwrap_or(0);
+    Ok(if unhandled > 0 || !orphaned.is_empty() {
+        ExitStatus::Warnings
+    } else {
+        ExitStatus::Ok
+    })
+}
+
+/// Classifies every file in the repo, sorted by path
+fn repo_coverage(
+    config: &Config,
+    home: &Path,
+    link_root: &Path,
+) -> Result<Vec<(PathBuf, Handling)>> {
+    let repo = &config.dotfiles_dir;
+    let walked: BTreeSet<PathBuf> = RepoWalker::new(repo).files()?.into_iter().collect();
+    let copied = copies::find(config, link_root);
+    let linked_targets = config.linked_targets();
+    let skipped = config.skipped_links();
+    let templates: BTreeMap<PathBuf, PathBuf> = templates::find_templates(repo, home)?
+        .into_iter()
+        .map(|template| (template.source, template.target))
+        .collect();
+
+    // Directories linked somewhere other than the link root
+    let mut mapped: Vec<(&str, Option<PathBuf>)> = INDIVIDUAL_FILE_SYMLINK_DIRS
+        .iter()
+        .map(|dir| (*dir, Some(link_root.join(dir))))
+        .collect();
+    mapped.push((
+        karabiner::REPO_CONFIG_DIR,
+        Some(karabiner::config_dir(home)),
+    ));
+    mapped.push((
+        kubernetes::REPO_CONFIG_DIR,
+        Some(kubernetes::snippets_dir(home)),
+    ));
+    mapped.push((
+        obsidian::REPO_CONFIG_DIR,
+        config
+            .obsidian
+            .vault
+            .as_ref()
+            .map(|vault| vault.join(".obsidian")),
+    ));
+
+    let mut files = Vec::new();
+    for source in RepoWalker::new(repo).respect_ignores(false).files()? {
+        let Ok(rel) = source.strip_prefix(repo).map(Path::to_path_buf) else {
+            continue;
+        };
+        let top = first_component(&rel);
+
+        let handling = if let Some(target) = templates.get(&source) {
+            placed(
+                target,
+                "not rendered yet; run: dotfiles setup",
+                Handling::Rendered,
+            )
+        } else if rel.starts_with(TEMPLATE_DIR) {
+            Handling::Excluded("template matched by an ignore file".to_string())
+        } else if let Some((file, rest)) = copied
+            .iter()
+            .find_map(|file| Some((file, rel.strip_prefix(&file.name).ok()?)))
+        {
+            placed(
+                &join_rest(&file.target, rest),
+                "not copied yet; run: dotfiles setup",
+                Handling::Copied,
+            )
+        } else if let Some((target, rest)) = linked_targets
+            .iter()
+            .find_map(|(name, target)| Some((target, rel.strip_prefix(name).ok()?)))
+        {
+            linked(
+                &source,
+                &join_rest(&symlink::expand_target(target, link_root), rest),
+            )
+        } else if let Some((_, target_dir)) = mapped.iter().find(|(dir, _)| *dir == top) {
+            match target_dir {
+                Some(dir) => linked(&source, &dir.join(rel.strip_prefix(top).unwrap_or(&rel))),
+                None => Handling::Excluded("no [obsidian] vault set".to_string()),
+            }
+        } else if EXCLUSIONS.contains(&top) {
+            Handling::Excluded(exclusion_reason(top).to_string())
+        } else if config.links.skip.iter().any(|name| name == top) {
+            Handling::Excluded("listed in [links] skip".to_string())
+        } else if skipped.iter().any(|name| name == top) {
+            Handling::Excluded("not for this machine's tags or profile".to_string())
+        } else if !walked.contains(&source) {
+            Handling::Excluded("matched by an ignore file".to_string())
+        } else {
+            linked(&source, &link_root.join(&rel))
+        };
+        files.push((rel, handling));
+    }
+    Ok(files)
+}
+
+/// Why a built-in exclusion isn't linked
+fn exclusion_reason(name: &str) -> &'static str {
+    match name {
+        "appconfig" => "read by dotfiles appconfig import",
+        "Brewfile" => "read by dotfiles brewfile install",
+        "hooks" => "run by setup and migrate",
+        "templates" => "copied by scaffolding steps",
+        _ => "repo metadata",
+    }
+}
+
+/// Linked if `target` resolves to `source`, whether through its own link or a linked parent
+fn linked(source: &Path, target: &Path) -> Handling {
+    let resolves = matches!(
+        (target.canonicalize(), source.canonicalize()),
+        (Ok(a), Ok(b)) if a == b
+    );
+    if resolves {
+        Handling::Linked(target.to_path_buf())
+    } else if target.symlink_metadata().is_ok() {
+        Handling::Unhandled(format!(
+            "{} exists but isn't linked to it",
+            target.display()
+        ))
+    } else {
+        Handling::Unhandled("not linked yet; run: dotfiles setup".to_string())
+    }
+}
+
+fn placed(target: &Path, missing: &str, handling: fn(PathBuf) -> Handling) -> Handling {
+    if target.exists() {
+        handling(target.to_path_buf())
+    } else {
+        Handling::Unhandled(missing.to_string())
+    }
+}
+
+fn join_rest(target: &Path, rest: &Path) -> PathBuf {
+    if rest.as_os_str().is_empty() {
+        target.to_path_buf()
+    } else {
+        target.join(rest)
+    }
+}
+
+fn first_component(rel: &Path) -> &str {
+    match rel.components().next() {
+        Some(Component::Normal(name)) => name.to_str().unwrap_or_default(),
+        _ => "",
+    }
+}
+
+/// Every link, copy, and rendered file the tool manages, sorted by target
+fn managed_targets(
+    config: &Config,
+    home: &Path,
+    link_root: &Path,
+    state: &State,
+) -> Vec<ManagedTarget> {
+    let repo = &config.dotfiles_dir;
+    let mut targets: BTreeMap<PathBuf, ManagedTarget> = BTreeMap::new();
+
+    let mut links = Vec::new();
+    find_links(link_root, repo, repo, &mut links);
+    for dir in [
+        link_root.join(".claude"),
+        karabiner::config_dir(home),
+        kubernetes::snippets_dir(home),
+    ] {
+        find_links(&dir, Path::new(""), repo, &mut links);
+    }
+    if let Some(vault) = &config.obsidian.vault {
+        find_links(&vault.join(".obsidian"), Path::new(""), repo, &mut links);
+    }
+    for target in config.linked_targets().values() {
+        let target = symlink::expand_target(target, link_root);
+        if let Some(source) = link_source(&target).filter(|source| source.starts_with(repo)) {
+            links.push((target, source));
+        }
+    }
+    for (target, source) in links {
+        targets.insert(
+            target.clone(),
+            ManagedTarget {
+                target,
+                source: Some(source),
+                kind: "link",
+            },
+        );
+    }
+
+    let copied = copies::find(config, link_root);
+    for key in state.copied.keys() {
+        let target = PathBuf::from(key);
+        let source = copied
+            .iter()
+            .find(|file| file.target == target)
+            .map(|file| file.source.clone());
+        targets.insert(
+            target.clone(),
+            ManagedTarget {
+                target,
+                source,
+                kind: "copy",
+            },
+        );
+    }
+    for key in state.rendered.keys() {
+        let target = PathBuf::from(key);
+        let source = target
+            .strip_prefix(home)
+            .ok()
+            .map(|rel| repo.join(TEMPLATE_DIR).join(rel));
+        targets.insert(
+            target.clone(),
+            ManagedTarget {
+                target,
+                source,
+                kind: "template",
+            },
+        );
+    }
+
+    targets.into_values().collect()
+}
+
+/// Collects links in `dir` pointing into `repo`
+///
+/// Descends into real directories the repo has too (`mirror`), where stow
+/// links individual entries instead of the whole directory. An empty
+/// `mirror` only looks at `dir` itself.
+fn find_links(dir: &Path, mirror: &Path, repo: &Path, links: &mut Vec<(PathBuf, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_symlink() {
+            if let Some(source) = link_source(&path).filter(|source| source.starts_with(repo)) {
+                links.push((path, source));
+            }
+        } else if path.is_dir() && !mirror.as_os_str().is_empty() {
+            let mirrored = mirror.join(entry.file_name());
+            if mirrored.is_dir() {
+                find_links(&path, &mirrored, repo, links);
+            }
+        }
+    }
+}
+
+/// Where the link at `path` points, resolved against its directory but not followed further
+fn link_source(path: &Path) -> Option<PathBuf> {
+    let link = fs::read_link(path).ok()?;
+    Some(match path.parent() {
+        Some(parent) if link.is_relative() => paths::normalize(&parent.join(link)),
+        _ => link,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(dotfiles_dir: &Path) -> Config {
+        toml::from_str(&format!(
+            "dotfiles_dir = {:?}\nxdg_config_home = \"/x/.config\"\nlanguage_manager = \"Asdf\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\n\n[files.\"vscode.json\"]\nmode = \"copy\"\n",
+            dotfiles_dir
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_repo_coverage_and_managed_targets() {
+        use std::os::unix::fs::symlink;
+
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let repo = temp.path().join("dotfiles");
+        fs::create_dir_all(repo.join(".config/nvim")).unwrap();
+        fs::create_dir_all(repo.join("templates/home")).unwrap();
+        fs::create_dir_all(home.join(".config")).unwrap();
+        for file in [
+            ".zshrc",
+            ".bashrc",
+            ".config/nvim/init.lua",
+            "README.md",
+            "notes.txt",
+            "vscode.json",
+            "templates/home/.gitconfig",
+        ] {
+            fs::write(repo.join(file), "x\n").unwrap();
+        }
+        fs::write(repo.join(".dotfilesignore"), "notes.txt\n").unwrap();
+
+        symlink(repo.join(".zshrc"), home.join(".zshrc")).unwrap();
+        symlink(repo.join(".config/nvim"), home.join(".config/nvim")).unwrap();
+        fs::write(home.join(".bashrc"), "local\n").unwrap();
+        fs::write(home.join(".gitconfig"), "rendered\n").unwrap();
+        // Left behind after .oldrc was deleted from the repo
+        symlink("../dotfiles/.oldrc", home.join(".oldrc")).unwrap();
+
+        let config = config(&repo);
+        let files = repo_coverage(&config, &home, &home).unwrap();
+        let labels: Vec<(String, &str)> = files
+            .iter()
+            .map(|(rel, handling)| (rel.display().to_string(), handling.label()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                (".bashrc".to_string(), "unhandled"),
+                (".config/nvim/init.lua".to_string(), "linked"),
+                (".dotfilesignore".to_string(), "excluded"),
+                (".zshrc".to_string(), "linked"),
+                ("README.md".to_string(), "excluded"),
+                ("notes.txt".to_string(), "excluded"),
+                ("templates/home/.gitconfig".to_string(), "rendered"),
+                ("vscode.json".to_string(), "unhandled"),
+            ]
+        );
+        assert_eq!(
+            files[1].1,
+            Handling::Linked(home.join(".config/nvim/init.lua"))
+        );
+
+        let mut state = State::default();
+        state
+            .rendered
+            .insert(home.join(".gitconfig").display().to_string(), String::new());
+        state
+            .copied
+            .insert(home.join(".old.json").display().to_string(), String::new());
+        let targets = managed_targets(&config, &home, &home, &state);
+        let summary: Vec<(PathBuf, &str, bool)> = targets
+            .iter()
+            .map(|t| (t.target.clone(), t.kind, t.source_exists()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (home.join(".config/nvim"), "link", true),
+                (home.join(".gitconfig"), "template", true),
+                (home.join(".old.json"), "copy", false),
+                (home.join(".oldrc"), "link", false),
+                (home.join(".zshrc"), "link", true),
+            ]
+        );
+    }
+}
@@ -1,41 +1,749 @@
-use crate::error::Result;
+use crate::backup::{BackupLayout, BackupLimits};
+use crate::core::config::{default_critical_symlinks, Config, PackageSettings};
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::core::state::State;
+use crate::core::{audit, notify, userspace};
+use crate::detect::backend;
+use crate::detect::inventory::Inventory;
+use crate::detect::os::OS;
+use crate::detect::system::SystemInfo;
+use crate::error::{DotfilesError, Result};
 use crate::install;
-use crate::validate;
+use crate::install::nvim::{HealthArea, HealthLevel};
+use crate::symlink::{manual::ManualSymlinker, SymlinkStatus};
+use crate::validate::cache::CheckCache;
+use crate::validate::score::{self, ScoreEntry};
+use crate::validate::{self, CheckReport, CheckResult, FixAction};
 use colored::Colorize;
+use serde::Serialize;
+use std::time::Instant;
+
+/// Machine info plus the health report, as printed by `doctor --json`
+#[derive(Debug, Serialize)]
+pub struct DoctorOutput {
+    pub system: SystemInfo,
+    /// Installed packages, repo links, and runtimes, for `doctor compare`
+    pub inventory: Inventory,
+    /// Setup completeness from 0 to 100, see `validate::score`
+    pub score: u8,
+    #[serde(flatten)]
+    pub report: CheckReport,
+}
+
+/// Machine-readable formats for the doctor report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Parses a `--format` value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(OutputFormat::Json),
+            "yaml" => Some(OutputFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Serializes the doctor output in this format
+    pub fn render(&self, output: &DoctorOutput) -> Result<String> {
+        Ok(match self {
+            OutputFormat::Json => serde_json::to_string_pretty(output)?,
+            OutputFormat::Yaml => serde_yaml::to_string(output)?,
+        })
+    }
+}
 
 /// Runs the doctor command to validate the dotfiles setup
-pub fn run() -> Result<()> {
-    println!("{}", "🏥 Dotfiles Health Check".bold());
-    println!();
+///
+/// With a `format`, progress output is suppressed and the full report
+/// (including a summary, per-check category and severity, and per-step
+/// timings) is printed as JSON or YAML instead. With `fix` set,
+/// missing nvim providers are installed before the checks run, and the fixes
+/// attached to failed checks are applied afterwards, followed by a re-check.
+/// With `no_cache` set, files are rescanned even if unchanged since the last
+/// run. The status reflects the worst check: errors, then warnings.
+pub fn run(
+    context: &AppContext,
+    format: Option<OutputFormat>,
+    fix: bool,
+    no_cache: bool,
+) -> Result<ExitStatus> {
+    let progress = format.is_none();
+    if progress {
+        println!("{}", "🏥 Dotfiles Health Check".bold());
+        println!();
+    }
+
+    if fix {
+        fix_nvim_providers(progress);
+    }
+
+    let mut output = collect(context, progress, !no_cache);
+    if fix && apply_fixes(context, &output.report, progress) > 0 {
+        output = collect(context, false, !no_cache);
+    }
+
+    let previous = record_score(context, output.score);
+    if let Some(last) = previous.last().filter(|last| last.score > output.score) {
+        let errors: Vec<&str> = output
+            .report
+            .checks
+            .iter()
+            .filter(|c| c.is_error())
+            .map(|c| c.name())
+            .collect();
+        notify::notify_unattended(
+            "dotfiles doctor regressed",
+            &format!(
+                "Setup completeness dropped from {} to {} since {}. Errors: {}",
+                last.score,
+                output.score,
+                last.at,
+                if errors.is_empty() {
+                    "none".to_string()
+                } else {
+                    errors.join(", ")
+                }
+            ),
+        );
+    }
+
+    // Print formatted report
+    match format {
+        Some(format) => print!("{}", format.render(&output)?),
+        None => {
+            println!("{}", output.report.format_colored());
+            println!();
+            println!("{}", format_score(output.score, &previous));
+        }
+    }
+
+    Ok(ExitStatus::from_report(&output.report))
+}
+
+/// Adds a score to the history in the state file, returning the earlier entries
+fn record_score(context: &AppContext, score: u8) -> Vec<ScoreEntry> {
+    let path = &context.state_path;
+    let Ok(mut state) = State::load(path) else {
+        return Vec::new();
+    };
+    let previous = state.score_history.clone();
+    let now = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    score::record(&mut state.score_history, now, score);
+    let _ = state.save(path);
+    previous
+}
+
+/// Formats the score line, with progress since the first recorded run
+fn format_score(score: u8, previous: &[ScoreEntry]) -> String {
+    let colored = match score {
+        90.. => format!("{}/100", score).green(),
+        60..=89 => format!("{}/100", score).yellow(),
+        _ => format!("{}/100", score).red(),
+    };
+    let mut line = format!("{} {}", "Setup completeness:".bold(), colored);
+    if let Some(first) = previous.first() {
+        let change = i16::from(score) - i16::from(first.score);
+        let trend = match change {
+            0 => "no change".to_string(),
+            c if c > 0 => format!("up {}", c),
+            c => format!("down {}", -c),
+        };
+        line.push_str(&format!(
+            " ({} since the first check on {})",
+            trend, first.at
+        ));
+    }
+    line
+}
+
+/// Installs nvim providers that `:checkhealth` reports as missing
+fn fix_nvim_providers(progress: bool) {
+    let Some(output) = install::nvim::checkhealth() else {
+        return;
+    };
+    let items = install::nvim::parse_checkhealth(&output);
+
+    for area in HealthArea::ALL.into_iter().filter(|area| {
+        area.is_fixable()
+            && items
+                .iter()
+                .any(|item| item.area == *area && item.level != HealthLevel::Ok)
+    }) {
+        let result = install::nvim::install_provider(area);
+        if progress {
+            match result {
+                Ok(()) => println!("{}", format!("🔧 Installed nvim {}", area.label()).green()),
+                Err(e) => println!(
+                    "{}",
+                    format!("⚠ Could not fix nvim {}: {}", area.label(), e).yellow()
+                ),
+            }
+        }
+    }
+    if progress {
+        println!();
+    }
+}
+
+/// Applies each distinct fix attached to a failed check, returning how many succeeded
+fn apply_fixes(context: &AppContext, report: &CheckReport, progress: bool) -> usize {
+    let mut fixes: Vec<&FixAction> = Vec::new();
+    for fix in report.checks.iter().filter_map(CheckResult::fix) {
+        if !fixes.contains(&fix) {
+            fixes.push(fix);
+        }
+    }
+
+    let mut applied = 0;
+    for fix in fixes {
+        match apply_fix(context, fix) {
+            Ok(()) => {
+                applied += 1;
+                if progress {
+                    println!("{}", format!("🔧 {}", fix.describe()).green());
+                }
+            }
+            Err(e) => {
+                if progress {
+                    println!(
+                        "{}",
+                        format!("⚠ Could not fix: {}: {}", fix.describe(), e).yellow()
+                    );
+                }
+            }
+        }
+    }
+    if progress && applied > 0 {
+        println!("{}", "Re-checking after fixes...".dimmed());
+        println!();
+    }
+    applied
+}
+
+fn apply_fix(context: &AppContext, fix: &FixAction) -> Result<()> {
+    match fix {
+        FixAction::Relink { source, target } => {
+            // A dangling link isn't replaced by the symlinker, so clear it first
+            if target.is_symlink() && !target.exists() {
+                std::fs::remove_file(target)?;
+            }
+            // Anything else still in the way comes back as a conflict
+            let symlinker = ManualSymlinker {
+                dry_run: false,
+                force: false,
+                skip: Vec::new(),
+                config_home: None,
+            };
+            match symlinker.create_symlink(source, target)? {
+                SymlinkStatus::Conflict { reason, .. } => {
+                    Err(DotfilesError::SymlinkConflict(reason))
+                }
+                _ => Ok(()),
+            }
+        }
+        FixAction::InstallPackages { packages } => {
+            let config = context.machine_config().ok();
+            let userspace_only = config
+                .as_ref()
+                .map_or_else(userspace::forced, |c| c.userspace_only);
+            let settings = config.map(|c| c.packages).unwrap_or_default();
+            // Prebuilt binaries install into ~/.local/bin, so userspace-only mode allows them
+            if userspace_only && settings.backend.is_none() {
+                return Err(DotfilesError::Config(format!(
+                    "skipped ({})",
+                    userspace::SKIP_REASON
+                )));
+            }
+            for package in packages {
+                install::packages::install_package(package, &settings)?;
+            }
+            Ok(())
+        }
+        FixAction::SourceScript {
+            shell_rc,
+            script,
+            name,
+        } => install::shell::ensure_script_sourced(shell_rc, script, name),
+        FixAction::UntrackEnv { repo, path } => validate::secrets::untrack_env(repo, path),
+    }
+}
+
+/// Runs every doctor check, printing section progress when `progress` is set
+///
+/// Per-file results are cached in the state file; `reuse_cache` false
+/// rescans every file (and refreshes the cache).
+pub fn collect(context: &AppContext, progress: bool, reuse_cache: bool) -> DoctorOutput {
+    let started = Instant::now();
+    let home = &context.home;
+    let mut saved_state = State::load(&context.state_path).ok();
+    let cache = CheckCache::new(
+        saved_state
+            .as_mut()
+            .map(|s| std::mem::take(&mut s.check_cache))
+            .unwrap_or_default(),
+        reuse_cache,
+    );
 
     // Collect all validation results
-    let mut overall_report = validate::CheckReport::new();
+    let mut overall_report = CheckReport::new();
+
+    let config = context.machine_config().ok();
+    let system = SystemInfo::collect(config.as_ref());
+    if progress {
+        println!("{}", system.render());
+        println!();
+    }
+
+    let package_settings = config
+        .as_ref()
+        .map(|c| c.packages.clone())
+        .unwrap_or_default();
+    let critical_symlinks = config
+        .as_ref()
+        .map(|c| c.critical_symlinks.clone())
+        .unwrap_or_else(default_critical_symlinks);
+    // Shared-machine mode: nothing system-level is expected to be set up
+    let userspace_only = config
+        .as_ref()
+        .map_or_else(userspace::forced, |c| c.userspace_only);
+
+    let mut section = |heading: &str, name: &str, check: &dyn Fn() -> CheckReport| {
+        if progress {
+            println!("{}", heading.bold());
+        }
+        overall_report.merge(CheckReport::timed(name, check));
+        if progress {
+            println!();
+        }
+    };
+
+    // 0. Validate ~/.dotfiles.conf itself
+    section("Checking configuration...", "Configuration", &|| {
+        validate::configs::validate_dotfiles_config(&context.config_path)
+    });
+
+    // 0a. Validate the dotfiles repo itself (the active profile may point at a different checkout)
+    let dotfiles_dir = config.as_ref().map_or_else(
+        || Config::default().dotfiles_dir,
+        |c| c.dotfiles_dir.clone(),
+    );
+    if dotfiles_dir.exists() {
+        let branch = config.as_ref().and_then(|c| c.dotfiles_branch.clone());
+        let generated = audit::load(&audit::log_path(home))
+            .map(|entries| audit::written_paths(&entries))
+            .unwrap_or_default();
+        section("Checking dotfiles repo...", "Dotfiles repo", &|| {
+            validate::repo::validate_repo(&dotfiles_dir, branch.as_deref(), &generated)
+        });
+    }
+
+    // 0a2. Check for other dotfiles checkouts that links may still point into
+    if let Some(config) = &config {
+        section(
+            "Checking for other dotfiles checkouts...",
+            "Dotfiles sources",
+            &|| {
+                let mut report = validate::sources::validate_sources(
+                    home,
+                    &config.xdg_config_home,
+                    &config.dotfiles_dir,
+                );
+                report.merge(validate::sources::validate_bare_repo(
+                    home,
+                    &config.dotfiles_dir,
+                ));
+                report
+            },
+        );
+    }
+
+    // 0b. Validate hostname, timezone, and locale against [machine]
+    if let Some(settings) = config
+        .as_ref()
+        .map(|c| &c.machine)
+        .filter(|m| !m.is_empty() && !userspace_only)
+    {
+        section("Checking machine identity...", "Machine identity", &|| {
+            validate::machine::validate_machine(settings, context.os)
+        });
+    }
 
     // 1. Validate dependencies
-    println!("{}", "Checking dependencies...".bold());
-    let dep_report = validate::dependencies::validate_all();
-    for check in dep_report.checks {
-        overall_report.add(check);
+    section("Checking dependencies...", "Dependencies", &|| {
+        validate::dependencies::validate_all(&package_settings, userspace_only)
+    });
+
+    // 1a. Validate version manager plugins
+    if let Some(config) = &config {
+        if !config.plugins.is_empty() {
+            section(
+                "Checking version manager plugins...",
+                "Version manager plugins",
+                &|| {
+                    validate::plugins::validate_plugins(
+                        install::version_manager::detect(),
+                        &config.plugins,
+                    )
+                },
+            );
+        }
+    }
+
+    // 1a2. Validate that configured languages resolve through shims
+    if let Some(config) = &config {
+        if config
+            .languages
+            .keys()
+            .any(|name| crate::language::get_installer(name).is_some())
+        {
+            section("Checking language shims...", "Language shims", &|| {
+                let languages: Vec<_> = config
+                    .languages
+                    .keys()
+                    .filter_map(|name| crate::language::get_installer(name))
+                    .collect();
+                validate::languages::validate_language_shims(
+                    install::version_manager::detect(),
+                    home,
+                    &languages,
+                )
+            });
+        }
+    }
+
+    // 1a3. Validate no other version manager shadows the configured one
+    if let Some(config) = &config {
+        if let Some(active) =
+            install::version_manager::VersionManager::from_language_manager(config.language_manager)
+        {
+            section(
+                "Checking for leftover version managers...",
+                "Version manager leftovers",
+                &|| validate::languages::validate_stale_managers(active, home),
+            );
+        }
     }
-    println!();
 
     // 1b. Validate brew packages (categorized)
-    println!("{}", "Checking brew packages...".bold());
-    let pkg_status = install::packages::package_status();
+    if !userspace_only {
+        section("Checking brew packages...", "Brew packages", &|| {
+            check_brew_packages(&package_settings)
+        });
+    }
+
+    // 1b0. Validate Homebrew prefix ownership and the Command Line Tools
+    if !userspace_only && (install::homebrew::is_installed() || context.os == OS::MacOS) {
+        section(
+            "Checking Homebrew permissions...",
+            "Homebrew permissions",
+            &|| validate::homebrew::validate_homebrew_permissions(context.os),
+        );
+    }
+
+    // 1b1. Validate installed packages against the repo's Brewfile
+    if let Some(config) = config.as_ref().filter(|_| !userspace_only) {
+        if install::brewfile::path(&config.dotfiles_dir).is_file() {
+            section("Checking Brewfile...", "Brewfile", &|| {
+                validate::brewfile::validate_brewfile(&config.dotfiles_dir)
+            });
+        }
+    }
+
+    // 1c. Validate nvim providers and tooling
+    if crate::detect::tools::is_installed("nvim") {
+        section("Checking Neovim health...", "Neovim", &|| {
+            validate::nvim::validate_nvim()
+        });
+    }
+
+    let config_dir = config.as_ref().map_or_else(
+        || Config::default().xdg_config_home,
+        |c| c.xdg_config_home.clone(),
+    );
+    let link_root = config
+        .as_ref()
+        .map(|c| c.link_root(home))
+        .or_else(crate::core::paths::link_root_override)
+        .unwrap_or_else(|| home.clone());
+
+    // 2. Validate symlinks (if dotfiles dir exists)
+    if dotfiles_dir.exists() {
+        let skipped = config
+            .as_ref()
+            .map(Config::skipped_links)
+            .unwrap_or_default();
+        section("Checking symlinks...", "Symlinks", &|| {
+            validate::symlinks::validate_symlinks(
+                &dotfiles_dir,
+                &link_root,
+                Some(&config_dir),
+                &skipped,
+            )
+        });
+    }
+
+    // 2a. Copy-only files that differ from the repo
+    if let Some(config) = config.as_ref().filter(|c| !c.copied_files().is_empty()) {
+        section("Checking copied files...", "Copied files", &|| {
+            validate::copies::validate_copies(
+                &install::copies::find(config, &link_root),
+                saved_state.as_ref(),
+            )
+        });
+    }
+
+    // 3. Check for hardcoded paths
+    if config_dir.exists() {
+        section(
+            "Scanning for hardcoded paths...",
+            "Hardcoded paths",
+            &|| validate::paths::scan_directory(&config_dir, &cache),
+        );
+    }
+
+    // 4. Validate config file syntax
+    if config_dir.exists() {
+        section("Validating config files...", "Config files", &|| {
+            validate::configs::scan_directory(&config_dir, &cache)
+        });
+    }
+
+    // 5. Validate critical symlinks
+    if dotfiles_dir.exists() {
+        section(
+            "Checking critical symlinks...",
+            "Critical symlinks",
+            &|| {
+                let mut report = validate::symlinks::validate_critical_symlinks(
+                    &link_root,
+                    &dotfiles_dir,
+                    &config_dir,
+                    &critical_symlinks,
+                    &cache,
+                );
+                if let Some(config) = &config {
+                    report.merge(validate::symlinks::validate_targets(
+                        &link_root,
+                        &dotfiles_dir,
+                        &config.linked_targets(),
+                        &cache,
+                    ));
+                }
+                report
+            },
+        );
+    }
+
+    // 5a. Well-known configs not yet in the repo
+    if dotfiles_dir.exists() {
+        section(
+            "Checking for unmanaged dotfiles...",
+            "Unmanaged dotfiles",
+            &|| validate::unmanaged::validate_unmanaged(home, &config_dir, &dotfiles_dir),
+        );
+    }
+
+    // 6. Validate .claude directory
+    if dotfiles_dir.exists() {
+        section(
+            "Checking .claude configuration...",
+            "Claude configuration",
+            &|| validate::claude::validate_claude_directory(home, &dotfiles_dir),
+        );
+    }
+
+    // 7. Validate shell integration
+    if dotfiles_dir.exists() {
+        section(
+            "Checking shell integration...",
+            "Shell integration",
+            &|| validate::shell::validate_shell_integration(home, &dotfiles_dir),
+        );
+    }
+
+    // 7a. Validate environment exported through the managed shell block
+    if let Some(config) = &config {
+        let expected = install::shell::managed_env_vars(config);
+        section(
+            "Checking managed shell environment...",
+            "Managed shell environment",
+            &|| {
+                let mut report = validate::shell::validate_managed_exports(home, &expected);
+                report.merge(validate::shell::validate_live_env(&expected));
+                report
+            },
+        );
+    }
+
+    // 7a1. Validate that extracted secrets are loaded, used, and untracked, and 1Password references resolve
+    if dotfiles_dir.exists() {
+        section(
+            "Checking extracted secrets...",
+            "Extracted secrets",
+            &|| {
+                let mut report = validate::secrets::validate_env_consumption(home, &dotfiles_dir);
+                report.merge(validate::secrets::validate_tracked_env(&dotfiles_dir));
+                report.merge(validate::secrets::validate_op_references(&dotfiles_dir));
+                report
+            },
+        );
+    }
+
+    // 7a2. Validate git pager, difftool, and aliases
+    if let Some(config) = &config {
+        section("Checking git configuration...", "Git", &|| {
+            validate::git::validate_git(home, &config.git)
+        });
+    }
+
+    // 7a3. Validate the running tmux server and its plugins
+    if crate::detect::tools::is_installed("tmux") {
+        section("Checking tmux...", "tmux", &|| {
+            validate::tmux::validate_tmux(home, &config_dir)
+        });
+    }
+
+    // 7b. Validate Obsidian vault
+    if let Some(vault) = config.as_ref().and_then(|c| c.obsidian.vault.clone()) {
+        section("Checking Obsidian vault...", "Obsidian", &|| {
+            validate::obsidian::validate_obsidian(&vault, &dotfiles_dir)
+        });
+    }
+
+    // 7c. Validate kubeconfig contexts and krew plugins
+    if let Some(config) = config.as_ref().filter(|c| c.kubernetes.enabled) {
+        section("Checking Kubernetes...", "Kubernetes", &|| {
+            let mut report = CheckReport::new();
+            let missing = install::packages::check_kubernetes_packages(&config.packages);
+            if !missing.is_empty() {
+                report.add(CheckResult::warn(
+                    "Kubernetes Tools",
+                    format!("Missing kubernetes tools: {}", missing.join(", ")),
+                    Some(format!("Run: {}", backend::install_command(&missing))),
+                ));
+            }
+            report.merge(validate::kubernetes::validate_kubernetes(
+                &config.dotfiles_dir,
+                home,
+                &config.kubernetes.krew_plugins,
+            ));
+            report
+        });
+    }
+
+    // 7d. Validate AWS profiles and credentials
+    if home.join(".aws").is_dir() {
+        section("Checking AWS configuration...", "AWS", &|| {
+            validate::aws::validate_aws(home)
+        });
+    }
+
+    // 8. Validate iTerm2 configuration (macOS only)
+    #[cfg(target_os = "macos")]
+    if dotfiles_dir.exists() {
+        section("Checking iTerm2 configuration...", "iTerm2", &|| {
+            validate::iterm::validate_iterm_config(&dotfiles_dir)
+        });
+    }
+
+    // 8a. Validate Karabiner-Elements configuration (macOS only)
+    #[cfg(target_os = "macos")]
+    if install::karabiner::repo_config(&dotfiles_dir).is_file()
+        || install::karabiner::config_dir(&home).exists()
+    {
+        section("Checking Karabiner configuration...", "Karabiner", &|| {
+            validate::karabiner::validate_karabiner(&home, &dotfiles_dir)
+        });
+    }
+
+    // 9. Validate tool-managed LaunchAgents (macOS only)
+    #[cfg(target_os = "macos")]
+    {
+        let declared = config
+            .as_ref()
+            .map(|c| c.launch_agents.clone())
+            .unwrap_or_default();
+        section("Checking launch agents...", "Launch agents", &|| {
+            validate::launch_agents::validate_launch_agents(&home, &declared)
+        });
+    }
+
+    // 10. Check how much space old backups take
+    let backup_settings = config
+        .as_ref()
+        .map(|c| c.backups.clone())
+        .unwrap_or_default();
+    section("Checking backups...", "Backups", &|| {
+        validate::backups::validate_backup_usage(
+            home,
+            &BackupLayout::from_settings(&backup_settings, home),
+            BackupLimits::from_settings(&backup_settings),
+        )
+    });
+
+    // 11. Check for a newer release of this tool
+    section("Checking for updates...", "Tool version", &|| {
+        validate::updates::validate_tool_version(home)
+    });
+
+    let inventory = match &config {
+        Some(config) => Inventory::collect(config, home),
+        None => Inventory::default(),
+    };
+
+    if progress && cache.hits() > 0 {
+        println!(
+            "{}",
+            format!(
+                "Reused {} results for unchanged files (--no-cache to rescan)",
+                cache.hits()
+            )
+            .dimmed()
+        );
+    }
+    if let Some(mut state) = saved_state {
+        state.check_cache = cache.into_entries();
+        let _ = state.save(&context.state_path);
+    }
+
+    overall_report.wall_time = started.elapsed();
+
+    DoctorOutput {
+        system,
+        inventory,
+        score: score::score(&overall_report),
+        report: overall_report,
+    }
+}
+
+/// Checks brew packages by group, with severity depending on the group
+fn check_brew_packages(package_settings: &PackageSettings) -> CheckReport {
+    let mut report = CheckReport::new();
+    let pkg_status = install::packages::package_status(package_settings);
 
     // Essential packages (errors if missing)
     for pkg in &pkg_status.missing_essential {
-        overall_report.add(validate::CheckResult::error(
-            "Essential Package",
-            format!("Missing essential package: {}", pkg),
-            Some(format!("Run: brew install {}", pkg)),
-        ));
+        report.add(
+            CheckResult::error(
+                "Essential Package",
+                format!("Missing essential package: {}", pkg),
+                Some(format!("Run: {}", backend::install_command(&[pkg]))),
+            )
+            .with_fix(FixAction::InstallPackages {
+                packages: vec![pkg.clone()],
+            }),
+        );
     }
 
     // Development packages (warnings if missing)
-    let missing_dev = install::packages::check_development_packages();
+    let missing_dev = install::packages::check_development_packages(package_settings);
     if !missing_dev.is_empty() {
-        overall_report.add(validate::CheckResult::warn(
+        report.add(CheckResult::warn(
             "Development Tools",
             format!(
                 "Missing {} development tools: {}",
@@ -45,35 +753,38 @@ pub fn run() -> Result<()> {
             Some("Run: dotfiles setup (or manually install)"),
         ));
     } else if !install::packages::DEVELOPMENT_PACKAGES.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Development Tools",
             "All development tools installed",
         ));
     }
 
     // Cloud packages (warnings if missing)
-    let missing_cloud = install::packages::check_cloud_packages();
+    let missing_cloud = install::packages::check_cloud_packages(package_settings);
     if !missing_cloud.is_empty() {
-        overall_report.add(validate::CheckResult::warn(
+        report.add(CheckResult::warn(
             "Cloud Tools",
             format!(
                 "Missing {} cloud tools: {}",
                 missing_cloud.len(),
                 missing_cloud.join(", ")
             ),
-            Some("Run: brew install awscli opentofu"),
+            Some(format!(
+                "Run: {}",
+                backend::install_command(&["awscli", "opentofu"])
+            )),
         ));
     } else if !install::packages::CLOUD_PACKAGES.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Cloud Tools",
             "All cloud tools installed",
         ));
     }
 
     // Productivity packages (info only)
-    let missing_productivity = install::packages::check_productivity_packages();
+    let missing_productivity = install::packages::check_productivity_packages(package_settings);
     if !missing_productivity.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Productivity Tools",
             format!(
                 "Optional: {} productivity tools available for install ({})",
@@ -82,16 +793,16 @@ pub fn run() -> Result<()> {
             ),
         ));
     } else if !install::packages::PRODUCTIVITY_PACKAGES.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Productivity Tools",
             "All productivity tools installed",
         ));
     }
 
     // Editor packages (info only)
-    let missing_editors = install::packages::check_editor_packages();
+    let missing_editors = install::packages::check_editor_packages(package_settings);
     if !missing_editors.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Editor Tools",
             format!(
                 "Optional: {} editor tools available for install ({})",
@@ -100,114 +811,16 @@ pub fn run() -> Result<()> {
             ),
         ));
     } else if !install::packages::EDITOR_PACKAGES.is_empty() {
-        overall_report.add(validate::CheckResult::pass(
+        report.add(CheckResult::pass(
             "Editor Tools",
             "All editor tools installed",
         ));
     }
 
-    println!();
-
-    // 2. Validate symlinks (if dotfiles dir exists)
-    if let Some(home) = dirs::home_dir() {
-        let dotfiles_dir = home.join("dotfiles");
-        if dotfiles_dir.exists() {
-            println!("{}", "Checking symlinks...".bold());
-            let symlink_report = validate::symlinks::validate_symlinks(&dotfiles_dir, &home);
-            for check in symlink_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 3. Check for hardcoded paths
-    if let Some(home) = dirs::home_dir() {
-        let config_dir = home.join(".config");
-        if config_dir.exists() {
-            println!("{}", "Scanning for hardcoded paths...".bold());
-            let paths_report = validate::paths::scan_directory(&config_dir);
-            for check in paths_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 4. Validate config file syntax
-    if let Some(home) = dirs::home_dir() {
-        let config_dir = home.join(".config");
-        if config_dir.exists() {
-            println!("{}", "Validating config files...".bold());
-            let config_report = validate::configs::scan_directory(&config_dir);
-            for check in config_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 5. Validate critical symlinks
-    if let Some(home) = dirs::home_dir() {
-        let dotfiles_dir = home.join("dotfiles");
-        if dotfiles_dir.exists() {
-            println!("{}", "Checking critical symlinks...".bold());
-            let critical_symlinks_report =
-                validate::symlinks::validate_critical_symlinks(&home, &dotfiles_dir);
-            for check in critical_symlinks_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 6. Validate .claude directory
-    if let Some(home) = dirs::home_dir() {
-        let dotfiles_dir = home.join("dotfiles");
-        if dotfiles_dir.exists() {
-            println!("{}", "Checking .claude configuration...".bold());
-            let claude_report = validate::claude::validate_claude_directory(&home, &dotfiles_dir);
-            for check in claude_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 7. Validate shell integration
-    if let Some(home) = dirs::home_dir() {
-        let dotfiles_dir = home.join("dotfiles");
-        if dotfiles_dir.exists() {
-            println!("{}", "Checking shell integration...".bold());
-            let shell_report = validate::shell::validate_shell_integration(&home, &dotfiles_dir);
-            for check in shell_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // 8. Validate iTerm2 configuration (macOS only)
-    #[cfg(target_os = "macos")]
-    if let Some(home) = dirs::home_dir() {
-        let dotfiles_dir = home.join("dotfiles");
-        if dotfiles_dir.exists() {
-            println!("{}", "Checking iTerm2 configuration...".bold());
-            let iterm_report = validate::iterm::validate_iterm_config(&dotfiles_dir);
-            for check in iterm_report.checks {
-                overall_report.add(check);
-            }
-            println!();
-        }
-    }
-
-    // Print formatted report
-    println!("{}", overall_report.format_colored());
-
-    // Exit with error code if there are errors
-    if overall_report.has_errors() {
-        std::process::exit(1);
-    }
+    report.merge(validate::dependencies::validate_pins(package_settings));
+    report.merge(validate::dependencies::validate_brew_health(
+        package_settings,
+    ));
 
-    Ok(())
+    report
 }
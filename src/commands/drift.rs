@@ -0,0 +1,253 @@
+use crate::backup::diff;
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::core::paths::display_path;
+use crate::core::state::State;
+use crate::core::sudo;
+use crate::error::{DotfilesError, Result};
+use crate::install::copies::{self, CopiedFile, CopyStatus};
+use crate::install::templates::{self, Template};
+use colored::Colorize;
+use dialoguer::Select;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Context kept around each hunk of a live-versus-repo diff, enough to place
+/// the change without paging through the whole file
+const DIFF_CONTEXT: usize = 3;
+
+/// A copied or rendered file whose live version differs from what the repo produces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drifted {
+    Copy(CopiedFile, CopyStatus),
+    /// A template and what it renders to now
+    Template(Template, String),
+}
+
+impl Drifted {
+    fn target(&self) -> &Path {
+        match self {
+            Drifted::Copy(file, _) => &file.target,
+            Drifted::Template(template, _) => &template.target,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Drifted::Copy(_, status) => format!("copy, {}", status.describe()),
+            Drifted::Template(template, _) if !template.target.exists() => {
+                "template, not rendered yet".to_string()
+            }
+            Drifted::Template(..) => "template, differs from the rendered output".to_string(),
+        }
+    }
+
+    /// What overwriting the live file would put there
+    fn repo_content(&self) -> Option<String> {
+        match self {
+            Drifted::Copy(file, _) => fs::read_to_string(&file.source).ok(),
+            Drifted::Template(_, rendered) => Some(rendered.clone()),
+        }
+    }
+}
+
+/// Lists copy-only and templated files that differ from the repo, offering
+/// to diff, overwrite, or (for copies) adopt each one
+///
+/// With `list`, or without a terminal, only prints them. Exits with
+/// `warnings` while anything is left drifted.
+pub fn run(context: &AppContext, list: bool) -> Result<ExitStatus> {
+    let (home, config) = (&context.home, context.machine_config()?);
+    let state_path = &context.state_path;
+    let mut state = State::load(state_path)?;
+    let vars = templates::load_vars(&templates::vars_path(home))?;
+
+    let copied = copies::find(&config, &config.link_root(home));
+    let found = templates::find_templates(&config.dotfiles_dir, home)?;
+    let (drifted, unrendered) = find_drift(&copied, &found, &vars, &state)?;
+
+    for template in &unrendered {
+        println!(
+            "  {}",
+            format!(
+                "⚠ {} has unanswered variables (run: dotfiles setup)",
+                display_path(&template.target, home)
+            )
+            .yellow()
+        );
+    }
+    if drifted.is_empty() {
+        println!(
+            "{}",
+            "✓ Every copied and rendered file matches the repo".green()
+        );
+        return Ok(ExitStatus::Ok);
+    }
+
+    println!("{}", "Files that differ from the repo:".bold());
+    for item in &drifted {
+        println!(
+            "  {} {}",
+            display_path(item.target(), home),
+            format!("({})", item.describe()).dimmed()
+        );
+    }
+    if list || !sudo::is_interactive() {
+        return Ok(ExitStatus::Warnings);
+    }
+
+    let mut remaining = 0;
+    for item in &drifted {
+        println!();
+        if !resolve(item, &mut state, home)? {
+            remaining += 1;
+        }
+        state.save(state_path)?;
+    }
+
+    Ok(if remaining > 0 {
+        ExitStatus::Warnings
+    } else {
+        ExitStatus::Ok
+    })
+}
+
+/// Returns the drifted files, and the templates that can't be rendered yet
+pub fn find_drift(
+    copied: &[CopiedFile],
+    found: &[Template],
+    vars: &BTreeMap<String, String>,
+    state: &State,
+) -> Result<(Vec<Drifted>, Vec<Template>)> {
+    let mut drifted = Vec::new();
+    let mut unrendered = Vec::new();
+
+    for file in copied {
+        let status = copies::status(file, state)?;
+        if status != CopyStatus::InSync {
+            drifted.push(Drifted::Copy(file.clone(), status));
+        }
+    }
+    for template in found {
+        match templates::render(&template.content, vars) {
+            Ok(rendered) => {
+                if fs::read_to_string(&template.target).ok().as_ref() != Some(&rendered) {
+                    drifted.push(Drifted::Template(template.clone(), rendered));
+                }
+            }
+            Err(_) => unrendered.push(template.clone()),
+        }
+    }
+
+    Ok((drifted, unrendered))
+}
+
+/// Offers the actions for one file until it is resolved or skipped
+///
+/// Returns true if the live file now matches the repo.
+fn resolve(item: &Drifted, state: &mut State, home: &Path) -> Result<bool> {
+    let name = display_path(item.target(), home);
+    let mut actions = vec!["Show diff", "Overwrite the live file from the repo"];
+    if matches!(item, Drifted::Copy(..)) {
+        actions.push("Adopt the live file into the repo");
+    }
+    actions.push("Skip");
+
+    loop {
+        let choice = Select::new()
+            .with_prompt(format!("{} ({})", name, item.describe()))
+            .items(&actions)
+            .default(0)
+            .interact_opt()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+
+        match (choice, item) {
+            (Some(0), _) => print_diff(item, &name),
+            (Some(1), Drifted::Copy(file, _)) => {
+                copies::pull(file, state)?;
+                println!("  {}", format!("✓ Replaced {}", name).green());
+                return Ok(true);
+            }
+            (Some(1), Drifted::Template(template, rendered)) => {
+                templates::write(template, rendered, state)?;
+                println!("  {}", format!("✓ Rendered {}", name).green());
+                return Ok(true);
+            }
+            (Some(2), Drifted::Copy(file, _)) => {
+                copies::push(file, state)?;
+                println!(
+                    "  {}",
+                    format!("✓ Copied {} into the repo; commit it", name).green()
+                );
+                return Ok(true);
+            }
+            _ => return Ok(false),
+        }
+    }
+}
+
+fn print_diff(item: &Drifted, name: &str) {
+    let live = match fs::read_to_string(item.target()) {
+        Ok(live) => live,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(_) => {
+            println!("{}", "(binary file differs)".dimmed());
+            return;
+        }
+    };
+    let Some(repo) = item.repo_content() else {
+        println!("{}", "(binary file differs)".dimmed());
+        return;
+    };
+
+    println!("{}", format!("--- {}\n+++ repo", name).bold());
+    print!(
+        "{}",
+        diff::render_diff(&diff::diff_lines(&live, &repo), DIFF_CONTEXT)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_drift() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        fs::create_dir_all(&home).unwrap();
+        fs::write(temp.path().join("app.json"), "{}").unwrap();
+        fs::write(home.join(".gitconfig.work"), "email = old@b.c\n").unwrap();
+        fs::write(home.join(".in-sync"), "same\n").unwrap();
+
+        let copied = [CopiedFile {
+            name: "app.json".to_string(),
+            source: temp.path().join("app.json"),
+            target: home.join("app.json"),
+        }];
+        let template = |name: &str, content: &str| Template {
+            source: temp.path().join(name),
+            target: home.join(name),
+            content: content.to_string(),
+        };
+        let found = [
+            template(".gitconfig.work", "email = {{ email }}\n"),
+            template(".in-sync", "same\n"),
+            template(".ssh/config", "User {{ user }}\n"),
+        ];
+        let vars = BTreeMap::from([("email".to_string(), "new@b.c".to_string())]);
+
+        let (drifted, unrendered) = find_drift(&copied, &found, &vars, &State::default()).unwrap();
+        assert_eq!(
+            drifted,
+            vec![
+                Drifted::Copy(copied[0].clone(), CopyStatus::Missing),
+                Drifted::Template(found[0].clone(), "email = new@b.c\n".to_string()),
+            ]
+        );
+        assert_eq!(unrendered, vec![found[2].clone()]);
+        assert_eq!(drifted[1].repo_content().unwrap(), "email = new@b.c\n");
+    }
+}
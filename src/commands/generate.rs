@@ -0,0 +1,266 @@
+use crate::core::context::AppContext;
+use crate::error::{DotfilesError, Result};
+use crate::install;
+use colored::Colorize;
+use std::path::Path;
+
+/// GitHub repository that publishes dotfiles release binaries
+pub const DEFAULT_RELEASE_REPO: &str = "mattcuento/dotfiles-tool";
+
+/// Writes (or prints) a POSIX sh bootstrap script for brand-new machines
+///
+/// The repository URL defaults to the `origin` remote of the configured
+/// dotfiles directory.
+pub fn bootstrap_script(
+    context: &AppContext,
+    url: Option<&str>,
+    release_repo: &str,
+    output: Option<&Path>,
+) -> Result<()> {
+    let repo_url = match url {
+        Some(url) => url.to_string(),
+        None => configured_remote(context).ok_or_else(|| {
+            DotfilesError::Config(
+                "Could not determine the dotfiles repository URL; pass --url <repo>".to_string(),
+            )
+        })?,
+    };
+
+    let script = render_bootstrap_script(&repo_url, release_repo);
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, &script)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+            }
+            eprintln!(
+                "{}",
+                format!("✓ Wrote bootstrap script to {}", path.display()).green()
+            );
+            eprintln!("  Commit it to your dotfiles repo, then on a new machine run:");
+            eprintln!("    curl -fsSL <raw URL of {}> | sh", path.display());
+        }
+        None => print!("{}", script),
+    }
+
+    Ok(())
+}
+
+/// Prints the tool's shortcuts in the syntax of `shell` (default: `$SHELL`)
+///
+/// For shells setup doesn't write a managed block for, such as fish; paste
+/// the output into their startup file.
+pub fn aliases(context: &AppContext, shell: Option<&str>) -> Result<()> {
+    let shell = match shell {
+        Some(name) => install::shell::Shell::from_name(name).ok_or_else(|| {
+            DotfilesError::Config(format!(
+                "Unsupported shell '{}'; use zsh, bash, or fish",
+                name
+            ))
+        })?,
+        None => install::shell::Shell::current(),
+    };
+    let dotfiles_dir = context
+        .machine_config()
+        .map(|config| config.dotfiles_dir)
+        .unwrap_or_else(|_| context.home.join("dotfiles"));
+
+    for line in install::shell::tool_alias_lines(shell, &dotfiles_dir) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Returns the remote URL of the dotfiles repo named in ~/.dotfiles.conf
+fn configured_remote(context: &AppContext) -> Option<String> {
+    install::repos::remote_url(&context.machine_config().ok()?.dotfiles_dir)
+}
+
+/// Quotes a value for safe use in a POSIX shell script
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Renders the bootstrap script
+///
+/// The script installs the dotfiles binary from the latest GitHub release
+/// (verifying its published checksum, and refusing the binary when the
+/// checksum can't be fetched), falls back to `cargo install`, then
+/// hands off to `dotfiles init --url <repo> --yes`.
+pub fn render_bootstrap_script(repo_url: &str, release_repo: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+# Bootstrap dotfiles on a new machine.
+# Generated by `dotfiles generate bootstrap-script`; usage:
+#   curl -fsSL <raw URL of this file> | sh
+set -eu
+
+DOTFILES_REPO={repo}
+RELEASE_REPO={release}
+INSTALL_DIR="${{DOTFILES_INSTALL_DIR:-$HOME/.local/bin}}"
+
+install_from_release() {{
+  case "$(uname -s)" in
+    Darwin) os=macos ;;
+    Linux) os=linux ;;
+    *) return 1 ;;
+  esac
+  case "$(uname -m)" in
+    arm64 | aarch64) arch=aarch64 ;;
+    x86_64 | amd64) arch=x86_64 ;;
+    *) return 1 ;;
+  esac
+
+  asset="dotfiles-$os-$arch"
+  base="https://github.com/$RELEASE_REPO/releases/latest/download"
+  tmp=$(mktemp -d)
+
+  curl -fsSL "$base/$asset" -o "$tmp/dotfiles" || return 1
+  # Never run a binary that couldn't be verified
+  if ! curl -fsSL "$base/$asset.sha256" -o "$tmp/dotfiles.sha256"; then
+    echo "error: could not download the checksum for $asset; refusing to install it" >&2
+    rm -rf "$tmp"
+    exit 1
+  fi
+  expected=$(cut -d ' ' -f 1 "$tmp/dotfiles.sha256")
+  if command -v shasum >/dev/null 2>&1; then
+    actual=$(shasum -a 256 "$tmp/dotfiles" | cut -d ' ' -f 1)
+  elif command -v sha256sum >/dev/null 2>&1; then
+    actual=$(sha256sum "$tmp/dotfiles" | cut -d ' ' -f 1)
+  else
+    echo "error: shasum or sha256sum is needed to verify $asset" >&2
+    rm -rf "$tmp"
+    exit 1
+  fi
+  if [ -z "$expected" ] || [ "$expected" != "$actual" ]; then
+    echo "error: checksum mismatch for $asset" >&2
+    rm -rf "$tmp"
+    exit 1
+  fi
+
+  mkdir -p "$INSTALL_DIR"
+  mv "$tmp/dotfiles" "$INSTALL_DIR/dotfiles"
+  chmod +x "$INSTALL_DIR/dotfiles"
+  rm -rf "$tmp"
+}}
+
+if ! command -v dotfiles >/dev/null 2>&1; then
+  echo "Installing dotfiles..."
+  if ! install_from_release; then
+    if command -v cargo >/dev/null 2>&1; then
+      cargo install --git "https://github.com/$RELEASE_REPO"
+    else
+      echo "error: no release binary for this platform and cargo is not installed" >&2
+      exit 1
+    fi
+  fi
+  PATH="$INSTALL_DIR:$HOME/.cargo/bin:$PATH"
+  export PATH
+fi
+
+# Reattach to the terminal when piped through sh so sudo can prompt
+if [ ! -t 0 ] && [ -r /dev/tty ]; then
+  exec dotfiles init --url "$DOTFILES_REPO" --yes </dev/tty
+fi
+exec dotfiles init --url "$DOTFILES_REPO" --yes
+"#,
+        repo = shell_quote(repo_url),
+        release = shell_quote(release_repo),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_render_bootstrap_script_contents() {
+        let script = render_bootstrap_script("https://github.com/me/dotfiles.git", "me/tool");
+
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("DOTFILES_REPO='https://github.com/me/dotfiles.git'"));
+        assert!(script.contains("RELEASE_REPO='me/tool'"));
+        assert!(script.contains("dotfiles init --url \"$DOTFILES_REPO\" --yes"));
+    }
+
+    #[test]
+    fn test_render_bootstrap_script_is_valid_sh() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("bootstrap.sh");
+        std::fs::write(
+            &path,
+            render_bootstrap_script("git@github.com:me/dotfiles.git", DEFAULT_RELEASE_REPO),
+        )
+        .unwrap();
+
+        let status = Command::new("sh").arg("-n").arg(&path).status().unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_bootstrap_script_refuses_unverified_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let script = temp.path().join("bootstrap.sh");
+        std::fs::write(
+            &script,
+            render_bootstrap_script("https://example.com/dotfiles.git", "me/tool"),
+        )
+        .unwrap();
+        // curl that serves the binary but not its .sha256
+        let bin = temp.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        std::fs::write(
+            bin.join("curl"),
+            "#!/bin/sh\nfor arg; do case $arg in http*) url=$arg ;; esac; out=$arg; done\ncase \"$url\" in *.sha256) exit 22 ;; esac\necho binary > \"$out\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(bin.join("curl"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let output = Command::new("sh")
+            .arg(&script)
+            .env("PATH", format!("{}:/usr/bin:/bin", bin.display()))
+            .env("HOME", temp.path())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("could not download the checksum"));
+        assert!(!temp.path().join(".local/bin/dotfiles").exists());
+    }
+
+    #[test]
+    fn test_shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn test_bootstrap_script_writes_executable_file() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("bootstrap.sh");
+
+        bootstrap_script(
+            &AppContext::for_home(temp.path()),
+            Some("https://example.com/dotfiles.git"),
+            "me/tool",
+            Some(&path),
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("https://example.com/dotfiles.git"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+}
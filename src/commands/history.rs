@@ -0,0 +1,128 @@
+use crate::core::audit::{self, Action, Run};
+use crate::core::context::AppContext;
+use crate::core::paths::display_path;
+use crate::error::{DotfilesError, Result};
+use colored::Colorize;
+use std::path::Path;
+
+/// Number of runs listed without a run ID
+const RECENT_RUNS: usize = 20;
+
+/// Lists recent runs that changed something, or with `run_id`, the changes one run made
+///
+/// Run IDs can be shortened to any unique prefix.
+pub fn run(context: &AppContext, run_id: Option<&str>) -> Result<()> {
+    let runs = audit::group_runs(audit::load(&audit::log_path(&context.home))?);
+
+    match run_id {
+        None => {
+            if runs.is_empty() {
+                println!("No changes recorded yet");
+            }
+            let skip = runs.len().saturating_sub(RECENT_RUNS);
+            for run in runs.iter().skip(skip).rev() {
+                println!("{}", summary_line(run));
+            }
+        }
+        Some(id) => {
+            let matches: Vec<&Run> = runs.iter().filter(|run| run.id.starts_with(id)).collect();
+            let run = match matches.as_slice() {
+                [run] => *run,
+                [] => {
+                    return Err(DotfilesError::Config(format!(
+                        "No run matches {}. Run `dotfiles history` to list runs",
+                        id
+                    )))
+                }
+                _ => {
+                    return Err(DotfilesError::Config(format!(
+                        "{} matches {} runs; use more of the run ID",
+                        id,
+                        matches.len()
+                    )))
+                }
+            };
+            print_run(run, &context.home);
+        }
+    }
+
+    Ok(())
+}
+
+fn summary_line(run: &Run) -> String {
+    let status = match run.status.as_deref() {
+        Some("ok") => "ok".green(),
+        Some(status) => status.yellow(),
+        None => "unfinished".red(),
+    };
+    format!(
+        "{}  {}  {:<24} {} changes  {}",
+        run.id.cyan(),
+        short_time(&run.started),
+        run.command,
+        run.actions.len(),
+        status
+    )
+}
+
+fn print_run(run: &Run, home_dir: &Path) {
+    println!("{}", summary_line(run).bold());
+    for entry in &run.actions {
+        println!(
+            "  {}  {}",
+            short_time(&entry.timestamp).dimmed(),
+            describe(&entry.action, home_dir)
+        );
+    }
+}
+
+/// One-line description of a recorded action
+pub fn describe(action: &Action, home_dir: &Path) -> String {
+    let tilde = |path: &Path| display_path(path, home_dir);
+
+    match action {
+        Action::RunStarted { command } => format!("started {}", command),
+        Action::RunFinished { status } => format!("finished ({})", status),
+        Action::LinkCreated { link, target } => {
+            format!("linked {} -> {}", tilde(link), tilde(target))
+        }
+        Action::LinkRemoved { link } => format!("removed link {}", tilde(link)),
+        Action::Stowed { package, target } => {
+            format!("stowed {} into {}", tilde(package), tilde(target))
+        }
+        Action::FileMoved { from, to } => format!("moved {} to {}", tilde(from), tilde(to)),
+        Action::FileCopied { from, to } => format!("copied {} to {}", tilde(from), tilde(to)),
+        Action::FileWritten { path, reason } => format!("wrote {} ({})", tilde(path), reason),
+        Action::PackageInstalled { package, manager } => {
+            format!("installed {} with {}", package, manager)
+        }
+    }
+}
+
+/// Trims an RFC 3339 timestamp to `YYYY-MM-DD HH:MM:SS`
+fn short_time(timestamp: &str) -> String {
+    timestamp.get(..19).unwrap_or(timestamp).replace('T', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_describe() {
+        let home = Path::new("/home/me");
+        let action = Action::LinkCreated {
+            link: PathBuf::from("/home/me/.zshrc"),
+            target: PathBuf::from("/home/me/dotfiles/.zshrc"),
+        };
+        assert_eq!(
+            describe(&action, home),
+            "linked ~/.zshrc -> ~/dotfiles/.zshrc"
+        );
+        assert_eq!(
+            short_time("2026-10-16T16:18:37.123456+02:00"),
+            "2026-10-16 16:18:37"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::backup::bare;
+use crate::core::config::Config;
+use crate::core::context::AppContext;
+use crate::error::{DotfilesError, Result};
+use colored::Colorize;
+
+/// Converts a bare-repo dotfiles setup into a checkout at the configured dotfiles directory
+pub fn run(context: &AppContext, dry_run: bool) -> Result<()> {
+    let home = &context.home;
+    let config = if context.config_path.exists() {
+        context.machine_config()?
+    } else {
+        Config::default()
+    };
+
+    let repo = bare::detect(home).ok_or_else(|| {
+        DotfilesError::Config(format!(
+            "No bare dotfiles repo found (looked in ~/{})",
+            bare::CANDIDATE_DIRS.join(", ~/")
+        ))
+    })?;
+    println!(
+        "Found bare repo {} with work tree {}",
+        repo.git_dir.display().to_string().cyan(),
+        repo.work_tree.display()
+    );
+
+    let conversion = bare::convert(&repo, &config.dotfiles_dir, dry_run)?;
+    let verb = if dry_run { "Would clone" } else { "Cloned" };
+    println!(
+        "{}",
+        format!(
+            "✓ {} {} tracked file(s) into {}",
+            verb,
+            conversion.tracked,
+            config.dotfiles_dir.display()
+        )
+        .green()
+    );
+    for path in &conversion.linked {
+        println!(
+            "  {} {}",
+            if dry_run { "Would link" } else { "Linked" },
+            path.display()
+        );
+    }
+    for path in &conversion.left_in_place {
+        println!(
+            "  {} {} (a directory, which usually holds untracked files too)",
+            "Left in place:".yellow(),
+            path.display()
+        );
+    }
+
+    if dry_run {
+        println!("{}", "Dry run - no changes made".yellow());
+    } else {
+        println!(
+            "  The bare repo is untouched; once you're happy, remove it and the `config` alias: rm -rf {}",
+            repo.git_dir.display()
+        );
+        if !context.config_path.exists() {
+            println!("  Next: {}", "dotfiles setup".cyan());
+        }
+    }
+    Ok(())
+}
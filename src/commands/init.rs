@@ -1,5 +1,8 @@
+use crate::commands::setup::{self, SetupOptions};
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
 use crate::core::prompt;
-use crate::error::Result;
+use crate::error::{DotfilesError, Result};
 use crate::install;
 use colored::Colorize;
 use dialoguer::{Confirm, Input};
@@ -7,22 +10,45 @@ use dialoguer::{Confirm, Input};
 /// Default dotfiles repository URL (should be configured by user)
 const DEFAULT_DOTFILES_REPO: &str = "https://github.com/YOUR_USERNAME/dotfiles.git";
 
+/// Options controlling how init runs
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    /// Repository to clone instead of prompting
+    pub url: Option<String>,
+    /// Continue into setup after cloning without asking
+    pub run_setup: bool,
+    /// Accept defaults for every prompt (clone location, setup choices)
+    pub assume_yes: bool,
+}
+
 /// Runs the init/bootstrap command for first-time setup
-pub fn run() -> Result<()> {
+pub fn run(context: &AppContext, options: InitOptions) -> Result<ExitStatus> {
     println!("{}", "🌟 Dotfiles Bootstrap".bold());
     println!();
     println!("This will set up your dotfiles on a fresh system.");
     println!();
 
     // Step 1: Prompt for dotfiles repository URL
-    let repo_url: String = Input::new()
-        .with_prompt("Dotfiles repository URL")
-        .default(DEFAULT_DOTFILES_REPO.to_string())
-        .interact_text()
-        .map_err(|e| crate::error::DotfilesError::Config(format!("Prompt error: {}", e)))?;
+    let repo_url: String = match (&options.url, options.assume_yes) {
+        (Some(url), _) => url.clone(),
+        (None, true) => {
+            return Err(DotfilesError::Config(
+                "--yes requires --url <repo>".to_string(),
+            ))
+        }
+        (None, false) => Input::new()
+            .with_prompt("Dotfiles repository URL")
+            .default(DEFAULT_DOTFILES_REPO.to_string())
+            .interact_text()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?,
+    };
 
     // Step 2: Prompt for target directory
-    let target_dir = prompt::prompt_dotfiles_dir()?;
+    let target_dir = if options.assume_yes {
+        prompt::default_dotfiles_dir(&context.home)
+    } else {
+        prompt::prompt_dotfiles_dir(&context.home)?
+    };
 
     // Step 3: Confirm
     println!();
@@ -34,15 +60,17 @@ pub fn run() -> Result<()> {
     );
     println!();
 
-    let confirmed = Confirm::new()
-        .with_prompt("Clone dotfiles repository?")
-        .default(true)
-        .interact()
-        .map_err(|e| crate::error::DotfilesError::Config(format!("Prompt error: {}", e)))?;
+    if !options.assume_yes {
+        let confirmed = Confirm::new()
+            .with_prompt("Clone dotfiles repository?")
+            .default(true)
+            .interact()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
 
-    if !confirmed {
-        println!("{}", "Bootstrap cancelled".yellow());
-        return Ok(());
+        if !confirmed {
+            println!("{}", "Bootstrap cancelled".yellow());
+            return Ok(ExitStatus::Ok);
+        }
     }
 
     // Step 4: Clone dotfiles repository
@@ -53,6 +81,29 @@ pub fn run() -> Result<()> {
     println!();
     println!("{}", "✓ Bootstrap complete!".green().bold());
     println!();
+
+    // Step 5: Continue into setup (asks unless --run-setup or --yes)
+    let run_setup = options.run_setup
+        || options.assume_yes
+        || Confirm::new()
+            .with_prompt("Continue into setup now?")
+            .default(true)
+            .interact()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+
+    if run_setup {
+        println!();
+        return setup::run(
+            context,
+            SetupOptions {
+                dry_run: false,
+                assume_yes: options.assume_yes,
+                dotfiles_dir: Some(target_dir),
+                profile: None,
+            },
+        );
+    }
+
     println!("Next steps:");
     println!(
         "  1. Review configuration files in {}",
@@ -60,5 +111,5 @@ pub fn run() -> Result<()> {
     );
     println!("  2. Run: {} to complete setup", "dotfiles setup".cyan());
 
-    Ok(())
+    Ok(ExitStatus::Ok)
 }
@@ -0,0 +1,151 @@
+use crate::core::context::AppContext;
+use crate::error::{DotfilesError, Result};
+use crate::install::version_manager;
+use crate::language;
+use colored::Colorize;
+
+/// Removes a language runtime (or a single version of it)
+pub fn remove(context: &AppContext, language_name: &str, version: Option<&str>) -> Result<()> {
+    let installer = language::get_installer(language_name)
+        .ok_or_else(|| DotfilesError::Config(format!("Unknown language: {}", language_name)))?;
+
+    let vm = version_manager::detect().ok_or_else(|| {
+        DotfilesError::DependencyMissing("version manager (asdf, mise, or rtx)".to_string())
+    })?;
+
+    println!(
+        "{}",
+        format!("🗑  Removing {}", installer.display_name()).bold()
+    );
+    println!();
+
+    // Step 1: Determine which versions to uninstall
+    let versions: Vec<String> = match version {
+        Some(v) => vec![v.to_string()],
+        None => version_manager::installed_versions(vm, installer.language_name()),
+    };
+
+    if versions.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "  ⚠ No installed versions of {} found in {}",
+                installer.display_name(),
+                vm.display_name()
+            )
+            .yellow()
+        );
+    }
+
+    // Step 2: Uninstall each version
+    let mut failed = Vec::new();
+    let mut uninstalled = Vec::new();
+    for v in &versions {
+        match installer.remove(vm, v) {
+            Ok(()) => {
+                println!(
+                    "{}",
+                    format!("  ✓ Uninstalled {} {}", installer.display_name(), v).green()
+                );
+                uninstalled.push(v.as_str());
+            }
+            Err(e) => {
+                println!("{}", format!("  ✗ Failed: {}", e).red());
+                failed.push(v.as_str());
+            }
+        }
+    }
+
+    // Step 3: Clear what was uninstalled from the global .tool-versions
+    let home = &context.home;
+    let tool_versions = home.join(".tool-versions");
+
+    for removal in tool_versions_removals(version, &uninstalled, &failed) {
+        if version_manager::remove_from_tool_versions(
+            &tool_versions,
+            installer.language_name(),
+            removal,
+        )? {
+            let what = match removal {
+                Some(v) => format!("{} {}", installer.language_name(), v),
+                None => installer.language_name().to_string(),
+            };
+            println!(
+                "{}",
+                format!("  ✓ Removed {} from {}", what, tool_versions.display()).green()
+            );
+        }
+    }
+
+    // Step 4: Warn about shims that will keep shadowing system binaries
+    let shim_dir = vm.shim_dir(home);
+    let remaining = version_manager::installed_versions(vm, installer.language_name());
+    let shims = version_manager::leftover_shims(&shim_dir, installer.binaries());
+
+    if remaining.is_empty() && !shims.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "  ⚠ {} shim(s) remain in {}",
+                shims.len(),
+                shim_dir.display()
+            )
+            .yellow()
+        );
+        for shim in &shims {
+            println!("    - {}", shim.display());
+        }
+        if version_manager::is_on_path(&shim_dir) {
+            println!(
+                "{}",
+                "    These shims are on your PATH and will shadow system binaries".yellow()
+            );
+        }
+        println!("    Run: {} reshim", vm.command());
+    }
+
+    println!();
+    if !failed.is_empty() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Could not uninstall {} {}; left in {}",
+            installer.display_name(),
+            failed.join(", "),
+            tool_versions.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Returns what to drop from `.tool-versions`: `None` for the whole language
+///
+/// After a failure only the versions that were really uninstalled are dropped.
+fn tool_versions_removals<'a>(
+    version: Option<&'a str>,
+    uninstalled: &[&'a str],
+    failed: &[&str],
+) -> Vec<Option<&'a str>> {
+    if failed.is_empty() {
+        vec![version]
+    } else {
+        uninstalled.iter().map(|v| Some(*v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_versions_removals() {
+        assert_eq!(tool_versions_removals(None, &["3.12.1"], &[]), vec![None]);
+        assert_eq!(
+            tool_versions_removals(Some("3.12.1"), &["3.12.1"], &[]),
+            vec![Some("3.12.1")]
+        );
+        assert_eq!(
+            tool_versions_removals(None, &["3.11.7"], &["3.12.1"]),
+            vec![Some("3.11.7")]
+        );
+        assert!(tool_versions_removals(Some("3.12.1"), &[], &["3.12.1"]).is_empty());
+    }
+}
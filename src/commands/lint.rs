@@ -0,0 +1,28 @@
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::error::Result;
+use crate::validate::layout;
+use colored::Colorize;
+
+/// Checks the dotfiles repo itself for layout problems
+///
+/// Unlike `doctor`, nothing on this machine is inspected besides the repo, so
+/// it is safe to run in CI or before pushing.
+pub fn run(context: &AppContext) -> Result<ExitStatus> {
+    let (home, config) = (&context.home, context.machine_config()?);
+
+    println!(
+        "{}",
+        format!("🧹 Linting {}", config.dotfiles_dir.display()).bold()
+    );
+
+    let report = layout::lint_repo(
+        &config.dotfiles_dir,
+        home,
+        &config.targets,
+        &config.skipped_links(),
+    );
+    println!("{}", report.format_colored());
+
+    Ok(ExitStatus::from_report(&report))
+}
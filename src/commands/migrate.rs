@@ -0,0 +1,146 @@
+use crate::backup::migrate::{self, MigrationOptions, SecretRedaction, SourceLayout};
+use crate::backup::secret_store::Backend;
+use crate::backup::secrets::EntropySettings;
+use crate::core::config::{Config, SecretSettings, SecretStoreKind};
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::error::Result;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// What `dotfiles migrate` was asked to do
+#[derive(Debug, Clone)]
+pub struct MigrateOptions {
+    /// The existing dotfiles directory
+    pub source: PathBuf,
+    /// Repo to migrate into, or None for the configured `dotfiles_dir`
+    pub target: Option<PathBuf>,
+    /// Layout of `source`, or None to detect it
+    pub layout: Option<SourceLayout>,
+    /// Scan the copied files for secrets
+    pub extract_secrets: bool,
+    /// Replace detected secrets with `${VAR}` references, and where
+    pub redact_secrets: Option<SecretRedaction>,
+    /// Where extracted secrets go, overriding `[secrets] store`
+    pub secret_store: Option<SecretStoreKind>,
+    /// Back up `source` first
+    pub create_backup: bool,
+    pub dry_run: bool,
+}
+
+/// Copies an existing dotfiles setup into the dotfiles repo, commits it, and links it from there
+pub fn run(context: &AppContext, options: MigrateOptions) -> Result<ExitStatus> {
+    let config = if context.config_path.exists() {
+        context.machine_config()?
+    } else {
+        Config::default()
+    };
+    let target = options.target.unwrap_or(config.dotfiles_dir.clone());
+
+    let mut migration = MigrationOptions::new(context, options.source, target)?;
+    migration.home = config.link_root(&context.home);
+    migration.layout = options.layout;
+    migration.extract_secrets = options.extract_secrets;
+    migration.entropy = entropy_settings(&config.secrets);
+    migration.redact_secrets = options.redact_secrets;
+    migration.skipped_hooks = config.skipped_hooks();
+    migration.skipped_links = config.skipped_links();
+    migration.secret_store = secret_backend(
+        options.secret_store.or(config.secrets.store),
+        config.secrets.vault(),
+    );
+    migration.create_backup = options.create_backup;
+    migration.dry_run = options.dry_run;
+
+    let result = migrate::migrate(&migration)?;
+
+    if !result.conflicts.is_empty() {
+        return Ok(ExitStatus::Conflicts);
+    }
+    if !options.dry_run {
+        println!(
+            "{}",
+            format!(
+                "✓ Migrated {} file(s) into {}",
+                result.copied.len(),
+                migration.target.display()
+            )
+            .green()
+        );
+    }
+    Ok(ExitStatus::Ok)
+}
+
+/// Returns the entropy heuristic tuned by `[secrets]`, with defaults for what it leaves out
+fn entropy_settings(settings: &SecretSettings) -> EntropySettings {
+    let defaults = EntropySettings::default();
+    EntropySettings {
+        threshold: settings.entropy_threshold.unwrap_or(defaults.threshold),
+        min_length: settings.min_length.unwrap_or(defaults.min_length),
+    }
+}
+
+/// Returns the store extracted secrets are kept in, or None for the repo's `.env`
+fn secret_backend(kind: Option<SecretStoreKind>, vault: &str) -> Option<Backend> {
+    match kind? {
+        SecretStoreKind::Env => None,
+        SecretStoreKind::Keychain => Some(Backend::Keychain),
+        SecretStoreKind::OnePassword => Some(Backend::OnePassword {
+            vault: vault.to_string(),
+        }),
+    }
+}
+
+/// Parses a `--layout` value
+pub fn parse_layout(name: &str) -> Option<SourceLayout> {
+    match name {
+        "flat" => Some(SourceLayout::Flat),
+        "packages" => Some(SourceLayout::Packages),
+        _ => None,
+    }
+}
+
+/// Parses a `--redact` value
+pub fn parse_redaction(name: &str) -> Option<SecretRedaction> {
+    match name {
+        "repo" => Some(SecretRedaction::IntoTarget),
+        "in-place" => Some(SecretRedaction::InPlace),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entropy_settings() {
+        let defaults = entropy_settings(&SecretSettings::default());
+        assert_eq!(defaults.threshold, 4.0);
+        assert_eq!(defaults.min_length, 20);
+
+        let tuned = entropy_settings(&SecretSettings {
+            entropy_threshold: Some(3.5),
+            min_length: Some(12),
+            ..SecretSettings::default()
+        });
+        assert_eq!(tuned.threshold, 3.5);
+        assert_eq!(tuned.min_length, 12);
+    }
+
+    #[test]
+    fn test_secret_backend() {
+        assert_eq!(secret_backend(None, "Private"), None);
+        assert_eq!(secret_backend(Some(SecretStoreKind::Env), "Private"), None);
+        assert_eq!(
+            secret_backend(Some(SecretStoreKind::Keychain), "Private"),
+            Some(Backend::Keychain)
+        );
+        assert_eq!(
+            secret_backend(Some(SecretStoreKind::OnePassword), "Dev"),
+            Some(Backend::OnePassword {
+                vault: "Dev".to_string()
+            })
+        );
+    }
+}
@@ -1,6 +1,28 @@
+pub mod adopt;
+pub mod appconfig;
+pub mod backup;
+pub mod brewfile;
+pub mod checklist;
+pub mod compare;
+pub mod config;
+pub mod coverage;
 pub mod doctor;
+pub mod drift;
+pub mod generate;
+pub mod history;
+pub mod import_bare;
 pub mod init;
+pub mod languages;
+pub mod lint;
+pub mod migrate;
+pub mod new_repo;
+pub mod report_issue;
+pub mod restore;
 pub mod setup;
+pub mod status;
+pub mod sync;
+pub mod unlink;
+pub mod vm;
 
 pub use doctor::run as doctor;
 pub use init::run as init;
@@ -0,0 +1,290 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::context::AppContext;
+use crate::core::prompt;
+use crate::error::{DotfilesError, Result};
+use crate::install::brewfile::{Brewfile, BrewfileGroup};
+use crate::install::packages::ESSENTIAL_PACKAGES;
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One directory per program, laid out like `$HOME` (as with GNU Stow)
+///
+/// Each entry is (package, file inside it, top-level link, what the link
+/// points at). The top-level links are what gets linked into `$HOME`, so the
+/// package directories themselves are listed in `.dotfilesignore`.
+const PACKAGES: &[(&str, &str, &str, &str)] = &[
+    ("zsh", ".zshrc", ".zshrc", "zsh/.zshrc"),
+    ("tmux", ".tmux.conf", ".tmux.conf", "tmux/.tmux.conf"),
+    (
+        "nvim",
+        ".config/nvim/init.lua",
+        ".config/nvim",
+        "../nvim/.config/nvim",
+    ),
+];
+
+const ZSHRC: &str = r#"# ~/.zshrc, linked from your dotfiles repo
+#
+# `dotfiles setup` appends a managed block below for PATH, the version
+# manager, and shell integrations; keep your own settings above it.
+
+export EDITOR=nvim
+
+HISTFILE=~/.zsh_history
+HISTSIZE=50000
+SAVEHIST=50000
+setopt share_history hist_ignore_dups
+
+alias ll='ls -lah'
+"#;
+
+const TMUX_CONF: &str = r#"# ~/.tmux.conf, linked from your dotfiles repo
+
+set -g mouse on
+set -g base-index 1
+set -g history-limit 50000
+set -sg escape-time 10
+"#;
+
+const NVIM_INIT: &str = r#"-- ~/.config/nvim/init.lua, linked from your dotfiles repo
+
+vim.g.mapleader = " "
+
+vim.opt.number = true
+vim.opt.relativenumber = true
+vim.opt.expandtab = true
+vim.opt.shiftwidth = 2
+vim.opt.ignorecase = true
+vim.opt.smartcase = true
+"#;
+
+/// Kept in the repo but never linked into $HOME
+const DOTFILESIGNORE: &str = r#"# Stays in the repo, never linked into $HOME (.gitignore syntax)
+.gitignore
+
+# Packages, reached through the top-level links
+/zsh/
+/tmux/
+/nvim/
+"#;
+
+const GITIGNORE: &str = r#"# Secrets extracted from your configs
+.env
+.DS_Store
+"#;
+
+/// Options controlling how new-repo runs
+#[derive(Debug, Clone, Default)]
+pub struct NewRepoOptions {
+    /// Where to create the repo (default: the usual dotfiles location)
+    pub path: Option<PathBuf>,
+    /// Create a GitHub repo with gh and push to it
+    pub github: bool,
+    /// Make the GitHub repo public instead of private
+    pub public: bool,
+}
+
+/// Scaffolds a new dotfiles repository, initializes git, and optionally creates it on GitHub
+pub fn run(context: &AppContext, options: NewRepoOptions) -> Result<()> {
+    let dir = options
+        .path
+        .unwrap_or_else(|| prompt::default_dotfiles_dir(&context.home));
+    let created = scaffold(&dir)?;
+    for path in &created {
+        println!(
+            "  Created {}",
+            path.strip_prefix(&dir).unwrap_or(path).display()
+        );
+    }
+
+    git_init(&dir)?;
+    println!(
+        "{}",
+        format!("✓ Created a dotfiles repo in {}", dir.display()).green()
+    );
+
+    if options.github {
+        create_github_repo(&dir, options.public)?;
+    }
+
+    println!();
+    println!("Next steps:");
+    println!("  1. Edit the stubs, or move your own dotfiles in with: dotfiles adopt <file>");
+    if !options.github {
+        println!(
+            "  2. Publish it: gh repo create --private --source {} --push",
+            dir.display()
+        );
+    }
+    println!(
+        "  {}. Link everything: dotfiles setup (dotfiles directory: {})",
+        if options.github { 2 } else { 3 },
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Writes the starter files into `dir`, which must be missing or empty
+///
+/// Returns the files and directories created, in order.
+fn scaffold(dir: &Path) -> Result<Vec<PathBuf>> {
+    if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+        return Err(DotfilesError::Config(format!(
+            "{} already exists and is not empty; pick another path",
+            dir.display()
+        )));
+    }
+
+    let mut created = Vec::new();
+    let stubs = [ZSHRC, TMUX_CONF, NVIM_INIT];
+    for ((package, rel, _, _), content) in PACKAGES.iter().zip(stubs) {
+        created.push(write_file(&dir.join(package).join(rel), content)?);
+    }
+    for (_, _, link, points_at) in PACKAGES {
+        let path = dir.join(link);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        relative_link(points_at, &path)?;
+        created.push(path);
+    }
+    created.push(write_file(&dir.join(".dotfilesignore"), DOTFILESIGNORE)?);
+    created.push(write_file(&dir.join(".gitignore"), GITIGNORE)?);
+    created.push(write_file(&dir.join("Brewfile"), &starter_brewfile())?);
+
+    Ok(created)
+}
+
+/// Writes `content` to `path`, creating its parent directories
+fn write_file(path: &Path, content: &str) -> Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(path.to_path_buf())
+}
+
+/// Links `path` to `points_at`, relative so the repo still works once cloned elsewhere
+#[cfg(unix)]
+fn relative_link(points_at: &str, path: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(points_at, path)?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn relative_link(points_at: &str, path: &Path) -> Result<()> {
+    let resolved = path.parent().unwrap_or(path).join(points_at);
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(points_at, path)?;
+    } else {
+        std::os::windows::fs::symlink_file(points_at, path)?;
+    }
+    Ok(())
+}
+
+/// A Brewfile with the essentials; `dotfiles brewfile export` fills in the rest
+fn starter_brewfile() -> String {
+    Brewfile {
+        groups: vec![BrewfileGroup::new("essential", ESSENTIAL_PACKAGES)],
+        ..Default::default()
+    }
+    .render()
+}
+
+/// Initializes the repo on `main` and commits the scaffold
+///
+/// A failed commit (usually no git identity yet) leaves the files staged.
+fn git_init(dir: &Path) -> Result<()> {
+    let git = |args: &[&str]| {
+        CommandRunner::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run()
+    };
+
+    git(&["init", "-q", "-b", "main"])?;
+    git(&["add", "-A"])?;
+    if git(&["commit", "-q", "-m", "Initial dotfiles"]).is_err() {
+        println!(
+            "{}",
+            format!(
+                "⚠ Could not commit; set git's user.name and user.email, then run: git -C {} commit -m \"Initial dotfiles\"",
+                dir.display()
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Creates a GitHub repo named after `dir` with gh and pushes to it
+fn create_github_repo(dir: &Path, public: bool) -> Result<()> {
+    if !crate::detect::tools::is_installed("gh") {
+        return Err(DotfilesError::DependencyMissing(
+            "GitHub CLI (gh); install it, or drop --github and push the repo yourself".to_string(),
+        ));
+    }
+    let name = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| DotfilesError::Config(format!("{} has no name", dir.display())))?;
+
+    println!("Creating GitHub repo {}...", name);
+    CommandRunner::new("gh")
+        .args(["repo", "create", &name])
+        .arg(if public { "--public" } else { "--private" })
+        .arg("--source")
+        .arg(dir)
+        .args(["--remote", "origin", "--push"])
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    println!("{}", format!("✓ Pushed to GitHub as {}", name).green());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::walk::RepoWalker;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_scaffold() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("dotfiles");
+        let created = scaffold(&dir).unwrap();
+        assert!(created.contains(&dir.join("nvim/.config/nvim/init.lua")));
+        assert!(!dir.join("hooks").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join(".config/nvim/init.lua")).unwrap(),
+            NVIM_INIT
+        );
+        assert_eq!(
+            fs::read_link(dir.join(".zshrc")).unwrap(),
+            PathBuf::from("zsh/.zshrc")
+        );
+        assert!(fs::read_to_string(dir.join("Brewfile"))
+            .unwrap()
+            .contains("brew \"stow\""));
+
+        // Only the top-level links are linked into $HOME, not the packages
+        let linked: Vec<PathBuf> = RepoWalker::new(&dir)
+            .max_depth(1)
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            linked,
+            [".config", ".tmux.conf", ".zshrc"].map(PathBuf::from)
+        );
+
+        assert!(scaffold(&dir).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use crate::backup::secrets;
+use crate::commands::doctor::{self, DoctorOutput};
+use crate::core::audit;
+use crate::core::context::AppContext;
+use crate::error::Result;
+use crate::install::sync_agent;
+use colored::Colorize;
+use std::path::Path;
+
+/// Lines kept from the end of each log file
+const LOG_TAIL_LINES: usize = 50;
+
+/// Builds a sanitized markdown report to attach to bug reports
+///
+/// Bundles the environment summary, the redacted `~/.dotfiles.conf`, the
+/// doctor JSON, and the end of the run history and sync agent logs. Secrets
+/// are masked and the home directory is replaced with `~`. Writes to
+/// `output`, or stdout when no path is given.
+pub fn run(context: &AppContext, output: Option<&Path>) -> Result<()> {
+    if output.is_some() {
+        println!("{}", "Collecting doctor report...".bold());
+    }
+    let doctor = doctor::collect(context, false, true);
+
+    let config = std::fs::read_to_string(&context.config_path).ok();
+
+    let logs: Vec<(String, String)> = [
+        audit::log_path(&context.home),
+        sync_agent::log_path(&context.home),
+    ]
+    .iter()
+    .filter_map(|path| {
+        let content = std::fs::read_to_string(path).ok()?;
+        let name = path.file_name()?.to_string_lossy().into_owned();
+        Some((name, tail(&content, LOG_TAIL_LINES)))
+    })
+    .collect();
+
+    let report = render_report(&doctor, config.as_deref(), &logs)?;
+    let report = sanitize(&report, Some(&context.home));
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, &report)?;
+            println!(
+                "{}",
+                format!("✓ Wrote issue report to {}", path.display()).green()
+            );
+            println!("  Review it, then attach it to your bug report");
+        }
+        None => print!("{}", report),
+    }
+
+    Ok(())
+}
+
+/// Renders the report as markdown (before sanitizing)
+///
+/// `logs` holds the tail of each log file found, by file name.
+pub fn render_report(
+    doctor: &DoctorOutput,
+    config: Option<&str>,
+    logs: &[(String, String)],
+) -> Result<String> {
+    let system = &doctor.system;
+    let mut out = String::from("## dotfiles issue report\n\n");
+
+    out.push_str("### Environment\n\n| | |\n|---|---|\n");
+    let rows = [
+        ("OS", Some(system.os.clone())),
+        ("OS version", system.os_version.clone()),
+        ("Arch", Some(system.arch.clone())),
+        ("Shell", system.shell.clone()),
+        ("Brew prefix", system.brew_prefix.clone()),
+        ("Dotfiles", system.dotfiles_dir.clone()),
+        ("Tool version", Some(system.tool_version.clone())),
+        ("Last setup", system.last_setup.clone()),
+        ("Last sync", system.last_sync.clone()),
+    ];
+    for (label, value) in rows {
+        out.push_str(&format!(
+            "| {} | {} |\n",
+            label,
+            value.as_deref().unwrap_or("unknown")
+        ));
+    }
+
+    out.push_str("\n### Configuration (`~/.dotfiles.conf`)\n\n");
+    match config {
+        Some(config) => out.push_str(&format!("```toml\n{}\n```\n", config.trim_end())),
+        None => out.push_str("_Not found_\n"),
+    }
+
+    out.push_str("\n### Doctor\n\n");
+    out.push_str(&format!("{}\n\n", doctor.report.summary()));
+    for check in doctor.report.checks.iter().filter(|c| !c.is_pass()) {
+        let marker = if check.is_error() { "✗" } else { "⚠" };
+        out.push_str(&format!(
+            "- {} **{}** - {}\n",
+            marker,
+            check.name(),
+            check.message()
+        ));
+    }
+    out.push_str(&format!(
+        "\n<details><summary>doctor --json</summary>\n\n```json\n{}\n```\n\n</details>\n",
+        serde_json::to_string_pretty(doctor)?
+    ));
+
+    if !logs.is_empty() {
+        out.push_str("\n### Recent logs\n");
+        for (name, content) in logs {
+            out.push_str(&format!(
+                "\n<details><summary>{} (last {} lines)</summary>\n\n```\n{}\n```\n\n</details>\n",
+                name,
+                LOG_TAIL_LINES,
+                content.trim_end()
+            ));
+        }
+    }
+
+    Ok(out)
+}
+
+/// The last `lines` lines of `content`
+fn tail(content: &str, lines: usize) -> String {
+    let all: Vec<&str> = content.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Masks secrets and replaces the home directory with `~`
+pub fn sanitize(report: &str, home: Option<&Path>) -> String {
+    let redacted = secrets::redact(report);
+    match home.map(|h| h.to_string_lossy().into_owned()) {
+        Some(home) if home.len() > 1 => redacted.replace(&home, "~"),
+        _ => redacted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detect::inventory::Inventory;
+    use crate::detect::system::SystemInfo;
+    use crate::validate::{CheckReport, CheckResult};
+
+    fn doctor_output() -> DoctorOutput {
+        let mut report = CheckReport::new();
+        report.add(CheckResult::pass("Homebrew", "Installed"));
+        report.add(CheckResult::warn(
+            "Shell Env:XDG_CONFIG_HOME",
+            "not exported",
+            Some("Run: dotfiles setup"),
+        ));
+        DoctorOutput {
+            system: SystemInfo {
+                os: "macos".to_string(),
+                arch: "aarch64".to_string(),
+                tool_version: "0.2.0".to_string(),
+                dotfiles_dir: Some("/Users/me/dotfiles".to_string()),
+                ..SystemInfo::default()
+            },
+            inventory: Inventory::default(),
+            score: 83,
+            report,
+        }
+    }
+
+    #[test]
+    fn test_render_report_sections() {
+        let logs = [(
+            "audit.jsonl".to_string(),
+            "{\"command\":\"setup\"}".to_string(),
+        )];
+        let report =
+            render_report(&doctor_output(), Some("dotfiles_dir = \"/x\"\n"), &logs).unwrap();
+
+        assert!(report.contains("### Environment"));
+        assert!(report.contains("| Arch | aarch64 |"));
+        assert!(report.contains("```toml\ndotfiles_dir = \"/x\"\n```"));
+        assert!(report.contains("Passed: 1, Warnings: 1, Errors: 0"));
+        assert!(report.contains("⚠ **Shell Env:XDG_CONFIG_HOME** - not exported"));
+        assert!(report.contains("\"checks\""));
+        assert!(report.contains("audit.jsonl (last 50 lines)"));
+        assert!(report.contains("```\n{\"command\":\"setup\"}\n```"));
+    }
+
+    #[test]
+    fn test_render_report_without_config() {
+        let report = render_report(&doctor_output(), None, &[]).unwrap();
+        assert!(report.contains("_Not found_"));
+        assert!(!report.contains("### Recent logs"));
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("a\nb\nc\n", 2), "b\nc");
+        assert_eq!(tail("a\n", 5), "a");
+    }
+
+    #[test]
+    fn test_sanitize() {
+        let report = "dotfiles_dir = \"/Users/me/dotfiles\"\nexport NPM_TOKEN=abc123\n";
+        let sanitized = sanitize(report, Some(Path::new("/Users/me")));
+
+        assert_eq!(
+            sanitized,
+            "dotfiles_dir = \"~/dotfiles\"\nexport NPM_TOKEN=********\n"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use crate::backup::remote::{self, Remote};
+use crate::backup::{self, BackupInfo, BackupLayout};
+use crate::commands::backup::source_label;
+use crate::core::context::AppContext;
+use crate::core::sudo::is_interactive;
+use crate::error::{DotfilesError, Result};
+use chrono::Local;
+use colored::Colorize;
+use dialoguer::{Confirm, Select};
+use std::path::{Path, PathBuf};
+
+/// Restores a whole backup, picking one interactively unless `timestamp` names it
+///
+/// The backup goes back to the directory it was taken from unless `target`
+/// says otherwise. The target's current contents are backed up first. With
+/// `from_remote` the backups are fetched from `[backups] remote` and listed
+/// instead of the local ones.
+pub fn run(
+    context: &AppContext,
+    timestamp: Option<&str>,
+    target: Option<&Path>,
+    from_remote: bool,
+) -> Result<()> {
+    let home = &context.home;
+    let layout = BackupLayout::from_context(context)?;
+    // Listed from the remote's checkout; the safety backup still goes to `layout`
+    let listed = if from_remote {
+        let remote = Remote::from_settings(&context.config()?.backups)?;
+        println!("Fetching backups from {}...", remote);
+        BackupLayout {
+            dir: remote::fetch(&remote, &remote.cache_dir(&context.state_dir))?,
+            ..layout.clone()
+        }
+    } else {
+        layout.clone()
+    };
+    let backups = backup::list_in(&listed)?;
+    if backups.is_empty() {
+        println!(
+            "{}",
+            format!("No backups in {}", listed.dir.display()).yellow()
+        );
+        return Ok(());
+    }
+
+    let backup = match timestamp {
+        Some(timestamp) => backup::find_backup(&backups, timestamp).ok_or_else(|| {
+            DotfilesError::Config(format!(
+                "No backup matches {}; available: {}",
+                timestamp,
+                available(&backups)
+            ))
+        })?,
+        None if !is_interactive() => {
+            return Err(DotfilesError::Config(format!(
+                "Pass --timestamp to choose a backup; available: {}",
+                available(&backups)
+            )));
+        }
+        None => {
+            let now = Local::now();
+            let labels: Vec<String> = backups
+                .iter()
+                .map(|backup| format!("{}  {}", backup.when(now), source_label(backup, home)))
+                .collect();
+            let picked = Select::new()
+                .with_prompt("Backup to restore (Esc to cancel)")
+                .items(&labels)
+                .default(0)
+                .interact_opt()
+                .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+            match picked {
+                Some(index) => &backups[index],
+                None => return Ok(()),
+            }
+        }
+    };
+
+    let target = restore_target(backup, target)?;
+    if is_interactive() {
+        let confirmed = Confirm::new()
+            .with_prompt(format!(
+                "Replace {} with the backup from {}? Its current contents are backed up first",
+                target.display(),
+                backup.when(Local::now())
+            ))
+            .default(false)
+            .interact()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+        if !confirmed {
+            return Ok(());
+        }
+    }
+
+    let safety = backup::restore_backup(backup, &target, &layout)?;
+    if let Some(name) = safety.as_deref().and_then(Path::file_name) {
+        println!(
+            "  Undo with: {}",
+            format!(
+                "dotfiles restore --timestamp {} --target {}",
+                name.to_string_lossy(),
+                target.display()
+            )
+            .cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Where a backup goes: `--target`, or the directory it was taken from
+fn restore_target(backup: &BackupInfo, target: Option<&Path>) -> Result<PathBuf> {
+    match target {
+        Some(target) => Ok(target.to_path_buf()),
+        None if !backup.source.as_os_str().is_empty() => Ok(backup.source.clone()),
+        None => Err(DotfilesError::Config(format!(
+            "Backup {} doesn't record where it was taken from; pass --target",
+            backup.timestamp
+        ))),
+    }
+}
+
+fn available(backups: &[BackupInfo]) -> String {
+    backups
+        .iter()
+        .map(|backup| backup.timestamp.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
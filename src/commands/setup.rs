@@ -1,11 +1,53 @@
-use crate::core::{config::Config, prompt};
-use crate::error::Result;
-use crate::{install, language, symlink};
+use crate::backup::bare::{self, BareRepo};
+use crate::core::cancel;
+use crate::core::config::{
+    Config, ConfigProblem, HomebrewSettings, LanguageVersions, MachineSettings, PackageSettings,
+    PackageSource,
+};
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::core::hooks::{self, HookPoint};
+use crate::core::pool::Pool;
+use crate::core::prompt;
+use crate::core::state::{self, State};
+use crate::core::summary::{Outcome, SetupSummary, StepResult};
+use crate::core::timing;
+use crate::core::userspace;
+use crate::detect::backend;
+use crate::detect::os::OS;
+use crate::error::{DotfilesError, Result};
+use crate::install::copies::CopyStatus;
+use crate::install::homebrew::PackageKind;
+use crate::install::packages::{InstallStep, PackagePlan, PlannedAction};
+use crate::install::templates::RenderOutcome;
+use crate::install::version_manager::VersionManager;
+use crate::{commands, install, language, symlink};
 use colored::Colorize;
-use dialoguer::{Confirm, Input, MultiSelect};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Options controlling how setup runs
+#[derive(Debug, Clone, Default)]
+pub struct SetupOptions {
+    /// Show what would happen without making changes
+    pub dry_run: bool,
+    /// Accept configured or default values instead of prompting
+    pub assume_yes: bool,
+    /// Dotfiles directory to use instead of prompting (e.g. a fresh clone)
+    pub dotfiles_dir: Option<PathBuf>,
+    /// Profile to select, saved to the config for later runs
+    pub profile: Option<String>,
+}
 
 /// Runs the interactive setup command
-pub fn run(dry_run: bool) -> Result<()> {
+///
+/// Returns `Conflicts` when existing files blocked symlinks and `Errors`
+/// when any other step failed.
+pub fn run(context: &AppContext, options: SetupOptions) -> Result<ExitStatus> {
+    let dry_run = options.dry_run;
+    let assume_yes = options.assume_yes;
+
     println!("{}", "🚀 Interactive Dotfiles Setup".bold());
     println!();
 
@@ -14,64 +56,233 @@ pub fn run(dry_run: bool) -> Result<()> {
         println!();
     }
 
+    // Existing configuration (if any) supplies settings that setup doesn't prompt for
+    let config_path = &context.config_path;
+    let existing_config = load_base_config(config_path, options.dotfiles_dir.as_deref())?;
+    let existing_languages = existing_config.languages.clone();
+    if let Some(name) = &options.profile {
+        check_profile(&existing_config, name)?;
+    }
+
     // Step 1: Prompt for configuration
     println!("{}", "📝 Configuration".bold().underline());
     println!();
 
-    let dotfiles_dir = prompt::prompt_dotfiles_dir()?;
-    let xdg_config_home = prompt::prompt_xdg_config_home()?;
-    let language_manager = prompt::prompt_language_manager()?;
+    let (dotfiles_dir, xdg_config_home, language_manager) = if assume_yes {
+        (
+            options
+                .dotfiles_dir
+                .clone()
+                .unwrap_or_else(|| existing_config.dotfiles_dir.clone()),
+            existing_config.xdg_config_home.clone(),
+            existing_config.language_manager,
+        )
+    } else {
+        (
+            match &options.dotfiles_dir {
+                Some(dir) => dir.clone(),
+                None => prompt::prompt_dotfiles_dir(&context.home)?,
+            },
+            prompt::prompt_xdg_config_home(&context.home)?,
+            prompt::prompt_language_manager()?,
+        )
+    };
+
+    // Machine identity is only asked for on first-time setup; later runs use [machine]
+    let machine = if assume_yes || existing_config.last_setup.is_some() {
+        existing_config.machine.clone()
+    } else {
+        prompt::prompt_machine_identity(&existing_config.machine)?
+    };
+
+    // Offer a starter nvim config on first-time setup if the repo doesn't ship one
+    let nvim = if assume_yes
+        || existing_config.last_setup.is_some()
+        || !existing_config.nvim.is_empty()
+        || dotfiles_dir.join(".config/nvim").exists()
+    {
+        existing_config.nvim.clone()
+    } else {
+        prompt::prompt_nvim_distro()?
+    };
 
     // Step 2: Language selection
     println!();
     println!("{}", "🔧 Language Selection".bold().underline());
-    println!("Select languages to install (Space to select, Enter to continue):");
+    if !assume_yes {
+        println!("Select languages to install (Space to select, Enter to continue):");
+    }
     println!();
 
     let available_languages = language::all_languages();
     let language_names: Vec<String> = available_languages
         .iter()
-        .map(|l| format!("{} ({})", l.display_name(), l.default_version()))
+        .map(|l| match existing_languages.get(l.language_name()) {
+            Some(configured) => {
+                format!("{} ({})", l.display_name(), configured.versions.join(", "))
+            }
+            None => format!("{} ({})", l.display_name(), l.default_version()),
+        })
+        .collect();
+    let preselected: Vec<bool> = available_languages
+        .iter()
+        .map(|l| existing_languages.contains_key(l.language_name()))
         .collect();
 
-    let selections = MultiSelect::new()
-        .items(&language_names)
-        .interact()
-        .map_err(|e| crate::error::DotfilesError::Config(format!("Prompt error: {}", e)))?;
+    let selections: Vec<usize> = if assume_yes {
+        preselected
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &selected)| selected.then_some(i))
+            .collect()
+    } else {
+        MultiSelect::new()
+            .items(&language_names)
+            .defaults(&preselected)
+            .interact()
+            .map_err(|e| crate::error::DotfilesError::Config(format!("Prompt error: {}", e)))?
+    };
 
     let selected_languages: Vec<_> = selections
         .iter()
         .map(|&i| available_languages[i].language_name().to_string())
         .collect();
 
+    // Configured version sets win; otherwise install the default version only
+    let language_versions: BTreeMap<String, LanguageVersions> = selections
+        .iter()
+        .map(|&i| {
+            let lang = &available_languages[i];
+            let versions = existing_languages
+                .get(lang.language_name())
+                .cloned()
+                .unwrap_or_else(|| LanguageVersions::single(lang.default_version()));
+            (lang.language_name().to_string(), versions)
+        })
+        .collect();
+
+    // Step 2b: Optional packages
+    let selected_packages = if assume_yes {
+        existing_config.packages.selected.clone()
+    } else {
+        println!();
+        println!("{}", "📦 Package Selection".bold().underline());
+        println!();
+        prompt::prompt_packages(&existing_config.packages.selected)?
+    };
+
+    let config = Config {
+        dotfiles_dir,
+        xdg_config_home,
+        language_manager,
+        languages: language_versions,
+        machine,
+        nvim,
+        packages: PackageSettings {
+            selected: selected_packages,
+            ..existing_config.packages.clone()
+        },
+        profile: options.profile.or(existing_config.profile.clone()),
+        ..existing_config
+    };
+
+    // Preflight: catch config values that would make setup misbehave
+    let problems = config.validate();
+    if !problems.is_empty() {
+        println!();
+        println!("{}", "🔎 Configuration Problems".bold().underline());
+        print_problems(&problems);
+
+        if !dry_run && problems.iter().any(|p| p.is_error()) {
+            return Err(DotfilesError::Config(
+                "fix the configuration problems above and re-run setup".to_string(),
+            ));
+        }
+    }
+
     // Step 3: Show summary and confirm
+    let machine_config = config.clone().for_machine();
     println!();
     println!("{}", "📋 Setup Summary".bold().underline());
+    if let Some(profile) = &machine_config.profile {
+        println!("  Profile: {}", profile.cyan());
+    }
     println!(
         "  Dotfiles directory: {}",
-        dotfiles_dir.display().to_string().cyan()
+        machine_config.dotfiles_dir.display().to_string().cyan()
     );
     println!(
         "  XDG config home: {}",
-        xdg_config_home.display().to_string().cyan()
+        config.xdg_config_home.display().to_string().cyan()
     );
     println!(
         "  Language manager: {}",
-        format!("{:?}", language_manager).cyan()
+        format!("{:?}", config.language_manager).cyan()
     );
 
     if selected_languages.is_empty() {
         println!("  Languages: {}", "None selected".yellow());
     } else {
         println!("  Languages:");
-        for lang in &selected_languages {
-            println!("    - {}", lang.cyan());
+        for (lang, versions) in &config.languages {
+            let global = versions.global_version().unwrap_or_default();
+            let listed: Vec<String> = versions
+                .versions
+                .iter()
+                .map(|v| {
+                    if v == global {
+                        format!("{} (global)", v)
+                    } else {
+                        v.clone()
+                    }
+                })
+                .collect();
+            println!("    - {} {}", lang.cyan(), listed.join(", "));
+        }
+    }
+    if let Some(root) = &config.link_root {
+        println!("  Link root: {}", root.display().to_string().cyan());
+    }
+    if config.userspace_only {
+        println!(
+            "  Userspace only: {}",
+            "no sudo, Homebrew, packages, or machine identity changes".cyan()
+        );
+    }
+    // A bare-repo setup is cloned into the dotfiles directory if that doesn't exist yet
+    let bare_repo = if machine_config.dotfiles_dir.exists() {
+        None
+    } else {
+        bare::detect(&context.home)
+    };
+    if let Some(repo) = &bare_repo {
+        println!(
+            "  Bare repo: {} (will be cloned into the dotfiles directory)",
+            repo.git_dir.display().to_string().cyan()
+        );
+    }
+    if !machine_config.packages.selected.is_empty() {
+        println!(
+            "  Optional packages: {}",
+            machine_config.packages.selected.join(", ").cyan()
+        );
+    }
+    if let Some(distro) = config.nvim.distro {
+        println!("  Neovim config: {}", distro.display_name().cyan());
+    }
+    for (label, value) in [
+        ("Computer name", &config.machine.hostname),
+        ("Timezone", &config.machine.timezone),
+        ("Locale", &config.machine.locale),
+    ] {
+        if let Some(value) = value {
+            println!("  {}: {}", label, value.cyan());
         }
     }
 
     println!();
 
-    if !dry_run {
+    if !dry_run && !assume_yes {
         let confirmed = Confirm::new()
             .with_prompt("Proceed with setup?")
             .default(true)
@@ -80,264 +291,1648 @@ pub fn run(dry_run: bool) -> Result<()> {
 
         if !confirmed {
             println!("{}", "Setup cancelled".yellow());
-            return Ok(());
+            return Ok(ExitStatus::Ok);
         }
     }
 
+    // Ask for sudo once up front rather than in the middle of the run
+    let _sudo = if dry_run || config.userspace_only {
+        None
+    } else {
+        let os = context.os;
+        let identity_changes = install::machine::drift(&config.machine, os)
+            .iter()
+            .any(|d| d.setting.needs_sudo(os));
+        // Only the package steps' own lists are checked; later steps ask for sudo themselves
+        let packages = &machine_config.packages;
+        let package_sudo = install::package_manager::current(packages).needs_sudo()
+            && !(install::packages::check_essential_packages(packages).is_empty()
+                && install::packages::check_selected_packages(packages).is_empty());
+        let sudo_steps = crate::core::sudo::plan_steps(
+            os,
+            uses_homebrew(&config) && !install::homebrew::is_installed(),
+            package_sudo,
+            identity_changes,
+        );
+        crate::core::sudo::acquire(&sudo_steps)?
+    };
+
     // Step 4: Execute setup
     println!();
     println!("{}", "🔨 Starting setup...".bold());
     println!();
 
-    // 4a. Install Homebrew (macOS only)
+    let mut summary = SetupSummary::new();
+    let home = &context.home;
+
+    // Steps only see this machine's profile and tags; the full config is saved
+    let mut saved_config = config;
+    let config = machine_config;
+
+    // 4a0. Turn a bare-repo setup into the dotfiles checkout the later steps use
+    if let Some(repo) = &bare_repo {
+        summary.run(|| step_bare_repo(repo, &config.dotfiles_dir, dry_run));
+        abort_on_failure(&summary)?;
+    }
+
+    // 4a1. Run the repo's pre-setup hooks; a failing one stops setup
+    let skipped_hooks = config.skipped_hooks();
+    let mut hook_runs = hooks::run(
+        &config.dotfiles_dir,
+        home,
+        HookPoint::PreSetup,
+        &skipped_hooks,
+        dry_run,
+    );
+    for run in &hook_runs {
+        summary.add(run.step.clone());
+    }
+    if summary.has_failures() {
+        print!("{}", hooks::render_output(&hook_runs));
+    }
+    abort_on_failure(&summary)?;
+
+    // 4a. Install the Command Line Tools Homebrew builds with, then Homebrew (macOS only)
     if cfg!(target_os = "macos") {
-        println!("{}", "Checking Homebrew...".bold());
-        if !install::homebrew::is_installed() {
-            if dry_run {
-                println!("{}", "  Would install Homebrew".yellow());
-            } else {
-                install::homebrew::install()?;
+        summary.run(|| {
+            system_step(&config, "Command Line Tools", || {
+                step_command_line_tools(dry_run)
+            })
+        });
+        abort_on_failure(&summary)?;
+        summary.run(|| {
+            if !uses_homebrew(&config) {
+                return StepResult::skipped("Homebrew", "packages come from prebuilt binaries");
             }
-        } else {
-            println!("{}", "  ✓ Homebrew already installed".green());
-        }
-        println!();
+            system_step(&config, "Homebrew", || {
+                step_homebrew(&config.homebrew, home, dry_run, assume_yes)
+            })
+        });
+        abort_on_failure(&summary)?;
     }
 
     // 4b. Install version manager
-    println!("{}", "Checking version manager...".bold());
-    if install::version_manager::detect().is_none() {
-        if dry_run {
-            println!("{}", "  Would install version manager".yellow());
+    summary.run(|| {
+        if install::version_manager::detect().is_some() {
+            step_version_manager(dry_run)
         } else {
-            install::version_manager::install_preferred()?;
+            system_step(&config, "Version manager", || step_version_manager(dry_run))
         }
-    } else {
-        let vm = install::version_manager::detect().unwrap();
-        println!(
-            "{}",
-            format!("  ✓ {} already installed", vm.display_name()).green()
-        );
+    });
+    abort_on_failure(&summary)?;
+
+    // 4b2. Configure version manager (config files, plugins, .tool-versions)
+    summary.run(|| step_vm_config(&config, home, dry_run));
+
+    // 4c. Install essential packages
+    summary.run(|| {
+        package_step(&config, "Essential packages", || {
+            step_packages(&config, dry_run)
+        })
+    });
+
+    // 4c1. Install the optional packages picked above
+    if !config.packages.selected.is_empty() {
+        summary.run(|| {
+            package_step(&config, "Optional packages", || {
+                step_selected_packages(&config, dry_run)
+            })
+        });
+    }
+    stop_if_cancelled(&summary)?;
+
+    // 4c2. Install kubernetes tools, kubeconfig snippets, and krew plugins
+    if config.kubernetes.enabled {
+        summary.run(|| {
+            system_step(&config, "Kubernetes", || {
+                step_kubernetes(&config, home, dry_run)
+            })
+        });
+        stop_if_cancelled(&summary)?;
+    }
+
+    // 4d/4e. Install selected languages concurrently, cloning TPM alongside
+    let (tagged, untagged): (Vec<&String>, Vec<&String>) = selected_languages
+        .iter()
+        .partition(|name| config.languages.contains_key(*name));
+    for lang_name in untagged {
+        summary.add(StepResult::skipped(
+            format!("Language: {}", lang_name),
+            "not tagged for this machine",
+        ));
+    }
+    let (language_steps, dev_tools_step) = std::thread::scope(|scope| {
+        let dev_tools = scope.spawn(|| timing::measure(|| step_dev_tools(home, dry_run)));
+        let languages = Pool::new().map(&tagged, |lang_name| {
+            timing::measure(|| step_language(&config, lang_name, dry_run))
+        });
+        (
+            languages,
+            dev_tools.join().expect("dev tools step panicked"),
+        )
+    });
+    // Globals are set one at a time afterwards: asdf and mise rewrite the same
+    // global version file without locking it
+    let language_steps =
+        language_steps
+            .into_iter()
+            .zip(&tagged)
+            .map(|((step, elapsed), lang_name)| {
+                (step_language_global(&config, lang_name, step), elapsed)
+            });
+    for (mut step, elapsed) in language_steps.chain([dev_tools_step]) {
+        if step.duration.is_zero() {
+            step.duration = elapsed;
+        }
+        summary.add(step);
+    }
+    stop_if_cancelled(&summary)?;
+
+    // 4f. Create symlinks
+    for step in step_symlinks(&config, &config.link_root(home), dry_run) {
+        summary.add(step);
+    }
+
+    // 4f2. Copy files marked `mode = "copy"` in [files]
+    if !config.copied_files().is_empty() {
+        summary.run(|| step_copies(&config, home, dry_run));
+    }
+
+    // 4g. Configure shell integration
+    summary.run(|| step_shell_integration(&config, home, dry_run));
+
+    // 4g2. Write managed shell block (environment exports from config); with
+    // no lines left this removes a block written by an earlier run
+    let managed_lines = install::shell::managed_block_lines(&config, home);
+    summary.run(|| step_managed_block(&managed_lines, home, dry_run));
+
+    // 4g3. Configure git pager, difftool, and aliases
+    if !install::git::desired_entries(&config.git).is_empty() {
+        summary.run(|| step_git(&config, home, dry_run));
+    }
+
+    // 4g4. Configure the Obsidian vault
+    if let Some(vault) = &config.obsidian.vault {
+        summary.run(|| step_obsidian(&config.dotfiles_dir, vault, dry_run));
+    }
+
+    // 4g4b. Link and reload Karabiner-Elements config (macOS only)
+    if cfg!(target_os = "macos") && install::karabiner::repo_config(&config.dotfiles_dir).is_file()
+    {
+        summary.run(|| step_karabiner(&config.dotfiles_dir, home, dry_run));
+    }
+
+    // 4g4c. Clone the chosen nvim distro and sync its plugins
+    if config.nvim.distro.is_some() {
+        summary.run(|| step_nvim_distro(&config, dry_run));
+    }
+
+    // 4g5. Scaffold cloud tool profiles from repo templates
+    if config
+        .dotfiles_dir
+        .join(install::aws::CONFIG_TEMPLATE)
+        .is_file()
+    {
+        summary.run(|| step_aws_config(&config.dotfiles_dir, home, dry_run));
+    }
+
+    // 4g6. Render templates, asking for variables not answered yet
+    if config
+        .dotfiles_dir
+        .join(install::templates::TEMPLATE_DIR)
+        .is_dir()
+    {
+        let step = step_templates(&config.dotfiles_dir, home, dry_run, assume_yes)?;
+        summary.add(step);
     }
+
+    // 4g7. Set hostname, timezone, and locale from [machine]
+    if !config.machine.is_empty() {
+        summary.run(|| {
+            system_step(&config, "Machine identity", || {
+                step_machine(&config.machine, context.os, dry_run)
+            })
+        });
+    }
+
+    // 4h. Clone claude repository if needed
+    let claude_step = step_claude_repo(home, dry_run, assume_yes)?;
+    summary.add(claude_step);
+
+    // Step 5: Save configuration
+    if !dry_run {
+        saved_config.last_setup =
+            Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        summary.run(|| match saved_config.save(config_path) {
+            Ok(()) => StepResult::ok("Save configuration")
+                .with_detail(format!("Saved to {}", config_path.display())),
+            Err(e) => StepResult::failed("Save configuration", e.to_string())
+                .with_action(format!("Check permissions on {}", config_path.display())),
+        });
+    }
+
+    // Step 5b: Run the repo's post-setup hooks
+    let post_setup = hooks::run(
+        &config.dotfiles_dir,
+        home,
+        HookPoint::PostSetup,
+        &skipped_hooks,
+        dry_run,
+    );
+    for run in &post_setup {
+        summary.add(run.step.clone());
+    }
+    hook_runs.extend(post_setup);
+
+    // Step 6: Summary and post-install instructions
+    println!();
+    println!("{}", "📋 Setup Summary".bold());
+    print!("{}", summary.render());
     println!();
 
-    // 4c. Install essential packages
-    println!("{}", "Installing essential packages...".bold());
-    if dry_run {
+    let hook_output = hooks::render_output(&hook_runs);
+    if !hook_output.is_empty() {
+        println!("{}", "🪝 Hook output".bold());
+        print!("{}", hook_output);
+        println!();
+    }
+
+    if summary.has_failures() {
         println!(
             "{}",
-            "  Would install packages: stow, fzf, bat, fd, tree, nvim, tmux".yellow()
+            "⚠ Setup finished with failures (see follow-up actions above)"
+                .bold()
+                .yellow()
         );
     } else {
-        let status = install::packages::package_status();
-        if !status.is_complete() {
-            install::packages::install_essential_packages()?;
-        } else {
-            println!("{}", "  ✓ All essential packages already installed".green());
-        }
+        println!("{}", "✅ Setup Complete!".bold().green());
     }
     println!();
+    println!("{}", "📝 Next Steps:".bold());
+    println!("  1. Restart your shell or run: source ~/.zshrc");
+    println!("  2. Verify installation: dotfiles doctor");
+    println!();
+    let state = State::load(&context.state_path).unwrap_or_default();
+    print!("{}", commands::checklist::render(&state, context.os));
+    println!();
 
-    // 4d. Install selected languages
-    if !selected_languages.is_empty() {
-        println!("{}", "Installing languages...".bold());
+    Ok(summary.exit_status())
+}
 
-        if dry_run {
-            for lang in &selected_languages {
-                println!("{}", format!("  Would install {}", lang).yellow());
-            }
-        } else if let Some(vm) = install::version_manager::detect() {
-            for lang_name in &selected_languages {
-                if let Some(installer) = language::get_installer(lang_name) {
-                    println!("  Installing {}...", installer.display_name());
-                    match installer.install(vm, None) {
-                        Ok(()) => println!(
-                            "{}",
-                            format!("    ✓ {} installed", installer.display_name()).green()
-                        ),
-                        Err(e) => println!("{}", format!("    ✗ Failed: {}", e).red()),
-                    }
-                }
-            }
+/// Loads the configuration setup starts from
+///
+/// `~/.dotfiles.conf` wins; on a fresh machine the `.dotfiles.conf` committed
+/// to the dotfiles repo is used instead, falling back to built-in defaults.
+/// A file that exists but does not parse is an error, so setup never saves
+/// over it.
+fn load_base_config(config_path: &Path, dotfiles_dir: Option<&Path>) -> Result<Config> {
+    if config_path.exists() {
+        return Config::load(&config_path.to_path_buf());
+    }
+
+    match dotfiles_dir.map(|dir| dir.join(".dotfiles.conf")) {
+        Some(repo_config) if repo_config.exists() => Config::load(&repo_config),
+        _ => Ok(Config::default().with_overrides()),
+    }
+}
+
+/// Prints configuration problems with their suggested fixes
+pub fn print_problems(problems: &[ConfigProblem]) {
+    for problem in problems {
+        let line = format!("  {}: {}", problem.field, problem.message);
+        if problem.is_error() {
+            println!("{} {}", "✗".red(), line.red());
         } else {
-            println!(
-                "{}",
-                "  ⚠ No version manager available, skipping language installation".yellow()
-            );
+            println!("{} {}", "⚠".yellow(), line.yellow());
         }
-        println!();
+        println!("      → {}", problem.suggestion);
+    }
+}
+
+/// Stops setup after a failed prerequisite, printing what ran so far
+fn abort_on_failure(summary: &SetupSummary) -> Result<()> {
+    let Some(failed) = summary.steps.iter().find(|s| s.outcome == Outcome::Failed) else {
+        return Ok(());
+    };
+
+    println!();
+    print!("{}", summary.render());
+    Err(DotfilesError::InstallationFailed(format!(
+        "{}: {}",
+        failed.name,
+        failed.detail.as_deref().unwrap_or("failed")
+    )))
+}
+
+/// Stops setup after Ctrl-C, printing what ran so far
+fn stop_if_cancelled(summary: &SetupSummary) -> Result<()> {
+    if !cancel::is_cancelled() {
+        return Ok(());
+    }
+
+    println!();
+    print!("{}", summary.render());
+    Err(DotfilesError::Cancelled("setup".to_string()))
+}
+
+/// Runs a step that needs sudo or changes the system, unless in userspace-only mode
+fn system_step(config: &Config, name: &str, step: impl FnOnce() -> StepResult) -> StepResult {
+    if config.userspace_only {
+        return StepResult::skipped(name, userspace::SKIP_REASON);
+    }
+    step()
+}
+
+/// What to do about packages a package step could not install
+fn missing_packages_action(settings: &PackageSettings, missing: &[String]) -> String {
+    match settings.backend {
+        Some(PackageSource::Prebuilt) => format!(
+            "Install {} another way, or add them to [packages] skip",
+            missing.join(", ")
+        ),
+        None => format!("Run: {}", backend::install_command(missing)),
+    }
+}
+
+/// Rejects a `--profile` with no `[profiles.<name>]` table, before anything is asked or changed
+fn check_profile(config: &Config, name: &str) -> Result<()> {
+    if config.profiles.contains_key(name) {
+        return Ok(());
+    }
+    let known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+    Err(DotfilesError::Config(if known.is_empty() {
+        format!(
+            "Unknown profile '{}': ~/.dotfiles.conf defines no [profiles]",
+            name
+        )
+    } else {
+        format!("Unknown profile '{}'; defined: {}", name, known.join(", "))
+    }))
+}
+
+/// Whether packages come from Homebrew, rather than `[packages] backend = "prebuilt"`
+fn uses_homebrew(config: &Config) -> bool {
+    config.packages.backend != Some(PackageSource::Prebuilt)
+}
+
+/// Runs a package step, which stays in userspace when `[packages] backend` installs into `~/.local/bin`
+fn package_step(config: &Config, name: &str, step: impl FnOnce() -> StepResult) -> StepResult {
+    if can_install_packages(config) {
+        return step();
     }
+    system_step(config, name, step)
+}
 
-    // 4e. Install TPM and setup Mason info
-    println!("{}", "Installing development tools...".bold());
+/// Whether packages may be installed: outside userspace-only mode, or with a `[packages] backend`
+fn can_install_packages(config: &Config) -> bool {
+    config.packages.backend.is_some() || !config.userspace_only
+}
+
+/// Prints the progress line for a step
+fn announce(message: &str) {
+    println!("{}", message.bold());
+}
+
+fn step_command_line_tools(dry_run: bool) -> StepResult {
+    const NAME: &str = "Command Line Tools";
+    announce("Checking Xcode Command Line Tools...");
+
+    if install::xcode::is_installed() {
+        return StepResult::ok(NAME).with_detail("already installed");
+    }
     if dry_run {
-        println!("{}", "  Would install TPM (tmux plugin manager)".yellow());
         println!(
             "{}",
-            "  Would display Mason (nvim LSP manager) info".yellow()
+            "  Would run xcode-select --install and wait for it to finish".yellow()
         );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    if let Err(e) = install::xcode::request_install() {
+        return StepResult::failed(NAME, e.to_string())
+            .with_action("Run: xcode-select --install, then re-run setup");
+    }
+    println!(
+        "  Finish the installer dialog that just opened; waiting up to {} minutes...",
+        install::xcode::INSTALL_WAIT.as_secs() / 60
+    );
+    if install::xcode::wait_until(
+        install::xcode::is_installed,
+        install::xcode::INSTALL_WAIT,
+        install::xcode::POLL_INTERVAL,
+    ) {
+        StepResult::ok(NAME).with_detail("installed")
     } else {
-        let home = dirs::home_dir().unwrap();
-        match install::tools::install_tpm(&home) {
-            Ok(()) => {}
-            Err(e) => println!("{}", format!("  ⚠ TPM installation failed: {}", e).yellow()),
-        }
+        StepResult::failed(NAME, "not installed yet")
+            .with_action("Finish the Command Line Tools installer (or run: xcode-select --install), then re-run setup")
+    }
+}
 
-        match install::tools::setup_mason_info() {
-            Ok(()) => {}
-            Err(e) => println!("{}", format!("  ⚠ Note: {}", e).yellow()),
-        }
+fn step_homebrew(
+    settings: &HomebrewSettings,
+    home: &Path,
+    dry_run: bool,
+    assume_yes: bool,
+) -> StepResult {
+    const NAME: &str = "Homebrew";
+    announce("Checking Homebrew...");
+
+    if install::homebrew::is_installed() {
+        return StepResult::ok(NAME).with_detail("already installed");
     }
-    println!();
 
-    // 4f. Create symlinks
-    println!("{}", "Creating symlinks...".bold());
+    let state_file = state::state_path(home);
+    let mut state = State::load(&state_file).unwrap_or_default();
+    let partial = install::homebrew::partial_install();
+    let resuming = state.homebrew_install_started.is_some() || partial.is_some();
+    let url = settings
+        .install_script_url
+        .as_deref()
+        .unwrap_or(install::homebrew::HOMEBREW_INSTALL_URL);
+
     if dry_run {
         println!(
             "{}",
-            "  Would create symlinks from dotfiles to home".yellow()
+            format!(
+                "  Would {} Homebrew with the install script from {}",
+                if resuming {
+                    "resume installing"
+                } else {
+                    "install"
+                },
+                url
+            )
+            .yellow()
         );
-    } else {
-        // Determine which symlinker to use
-        let status = install::packages::package_status();
-        let has_stow = status.installed_essential.iter().any(|p| p == "stow");
-
-        let symlinker: Box<dyn symlink::Symlinker> = if has_stow {
-            println!("  Using GNU Stow");
-            Box::new(symlink::stow::StowSymlinker::new())
-        } else {
-            println!("  Using manual symlinks");
-            Box::new(symlink::manual::ManualSymlinker::new())
-        };
+        return StepResult::skipped(NAME, "dry run");
+    }
 
-        let home = dirs::home_dir().unwrap();
+    let script = match install::homebrew::download_install_script(url) {
+        Ok(script) => script,
+        Err(e) => {
+            return StepResult::failed(NAME, e.to_string())
+                .with_action("Re-run setup, or install Homebrew manually from https://brew.sh")
+        }
+    };
+    if let Err(e) =
+        install::homebrew::verify_install_script(&script, settings.install_script_sha256.as_deref())
+    {
+        return StepResult::failed(NAME, e.to_string()).with_action(
+            "Review the new script, then update homebrew.install_script_sha256 (or pin homebrew.install_script_url to a commit)",
+        );
+    }
+    if settings.install_script_sha256.is_none() {
+        println!(
+            "  Install script sha256: {} (set homebrew.install_script_sha256 to pin it)",
+            script.sha256.dimmed()
+        );
+    }
 
-        // First, create main dotfiles symlinks
-        match symlinker.symlink(&dotfiles_dir, &home) {
-            Ok(report) => {
-                println!("{}", format!("  ✓ {}", report.summary()).green());
-            }
-            Err(e) => {
-                println!("{}", format!("  ✗ Error creating symlinks: {}", e).red());
+    if !assume_yes {
+        loop {
+            let choice = Select::new()
+                .with_prompt("Run the Homebrew install script?")
+                .items(&["Run it", "Show the script first", "Cancel"])
+                .default(0)
+                .interact();
+            match choice {
+                Ok(0) => break,
+                Ok(1) => {
+                    // Through sh, so a $PAGER with arguments (e.g. `less -R`) works
+                    let _ = std::process::Command::new("sh")
+                        .args(["-c", "${PAGER:-less} \"$1\"", "sh"])
+                        .arg(script.path())
+                        .status();
+                }
+                _ => {
+                    return StepResult::failed(NAME, "cancelled")
+                        .with_action("Re-run setup when you're ready to install Homebrew")
+                }
             }
         }
+    }
 
-        // Then, handle special directories that need individual file symlinks
-        println!("  Creating individual file symlinks for special directories...");
-        match symlink::symlink_individual_files(symlinker.as_ref(), &dotfiles_dir, &home) {
-            Ok(report) => {
-                if report.total() > 0 {
-                    println!("{}", format!("    ✓ {}", report.summary()).green());
+    if resuming {
+        let since = state
+            .homebrew_install_started
+            .as_deref()
+            .map(|started| format!(" (started {})", started))
+            .unwrap_or_default();
+        println!("  Resuming an interrupted Homebrew install{}", since);
+        if let Some(repository) = &partial {
+            if install::homebrew::install_running(repository) {
+                println!(
+                    "{}",
+                    "  Leaving git locks in place: brew or its installer may still be running"
+                        .yellow()
+                );
+            } else {
+                for lock in install::homebrew::clear_stale_locks(repository) {
+                    println!("  Removed stale lock {}", lock.display());
                 }
             }
-            Err(e) => {
-                println!("{}", format!("    ⚠ Warning: {}", e).yellow());
-            }
         }
     }
-    println!();
 
-    // 4g. Configure shell integration
-    println!("{}", "Configuring shell integration...".bold());
+    state.homebrew_install_started =
+        Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+    let _ = state.save(&state_file);
+
+    match install::homebrew::install(&script, assume_yes) {
+        Ok(()) => {
+            state.homebrew_install_started = None;
+            let _ = state.save(&state_file);
+            StepResult::ok(NAME).with_detail(if resuming {
+                "installed (resumed)"
+            } else {
+                "installed"
+            })
+        }
+        Err(e) => StepResult::failed(NAME, e.to_string()).with_action(
+            "Re-run setup to resume where the install stopped, or see https://brew.sh",
+        ),
+    }
+}
+
+fn step_version_manager(dry_run: bool) -> StepResult {
+    const NAME: &str = "Version manager";
+    announce("Checking version manager...");
+
+    if let Some(vm) = install::version_manager::detect() {
+        return StepResult::ok(NAME)
+            .with_detail(format!("{} already installed", vm.display_name()));
+    }
+    if dry_run {
+        println!("{}", "  Would install version manager".yellow());
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    match install::version_manager::install_preferred() {
+        Ok(vm) => StepResult::ok(NAME).with_detail(format!("installed {}", vm.display_name())),
+        Err(e) => StepResult::failed(NAME, e.to_string())
+            .with_action(format!("Run: {}", backend::install_command(&["mise"]))),
+    }
+}
+
+fn step_vm_config(config: &Config, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Version manager config";
+    announce("Configuring version manager...");
+
     if dry_run {
         println!(
             "{}",
-            "  Would add check-claude-changes.sh to .zshrc".yellow()
+            "  Would link version manager config from dotfiles".yellow()
         );
-    } else {
-        let home = dirs::home_dir().unwrap();
-        let zshrc = home.join(".zshrc");
-        let script_path = dotfiles_dir.join("scripts/check-claude-changes.sh");
-
-        if script_path.exists() {
-            match install::shell::ensure_script_sourced(
-                &zshrc,
-                &script_path,
-                "check-claude-changes.sh",
-            ) {
-                Ok(()) => {}
-                Err(e) => println!(
-                    "{}",
-                    format!("  ⚠ Shell integration failed: {}", e).yellow()
-                ),
-            }
-        } else {
+        for (name, url) in &config.plugins {
             println!(
                 "{}",
-                "  ⚠ check-claude-changes.sh not found in dotfiles/scripts".yellow()
+                format!("  Would add plugin {} ({})", name, url).yellow()
             );
         }
+        return StepResult::skipped(NAME, "dry run");
     }
-    println!();
 
-    // 4h. Clone claude repository if needed
-    println!("{}", "Checking claude repository...".bold());
-    if dry_run {
-        println!("{}", "  Would clone claude repository if missing".yellow());
-    } else {
-        let home = dirs::home_dir().unwrap();
-        let claude_dir = home.join(".claude");
+    let Some(vm) = install::version_manager::detect() else {
+        return StepResult::skipped(NAME, "no version manager available");
+    };
 
-        if !claude_dir.exists() {
-            println!("  Claude repository not found, cloning...");
+    let mut problems = Vec::new();
 
-            // Prompt for claude repo URL (or use default)
-            let claude_repo_url: String = Input::new()
-                .with_prompt("Claude repository URL")
-                .default("https://github.com/YOUR_USERNAME/claudefiles.git".to_string())
-                .interact_text()
-                .map_err(|e| crate::error::DotfilesError::Config(format!("Prompt error: {}", e)))?;
+    match install::vm_config::link_config(vm, &config.dotfiles_dir, home, &config.xdg_config_home) {
+        Ok(report) if !report.is_success() => {
+            problems.push(format!("{} config conflicts", report.conflicts.len()))
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(format!("config linking: {}", e)),
+    }
 
-            match install::repos::clone_claude_repo(&claude_repo_url) {
-                Ok(()) => {}
-                Err(e) => println!("{}", format!("  ⚠ Claude clone failed: {}", e).yellow()),
-            }
-        } else if !install::repos::is_git_repo(&claude_dir) {
-            println!(
-                "{}",
-                "  ⚠ ~/.claude exists but is not a git repository".yellow()
-            );
-            println!(
-                "{}",
-                "    Consider initializing: cd ~/.claude && git init".yellow()
-            );
-        } else {
-            println!("{}", "  ✓ Claude repository exists".green());
+    let added = match install::vm_config::ensure_plugins(vm, &config.plugins) {
+        Ok(added) => added.len(),
+        Err(e) => {
+            problems.push(format!("plugins: {}", e));
+            0
         }
+    };
+
+    if let Err(e) = install::vm_config::ensure_tool_versions(home, &config.languages) {
+        problems.push(format!(".tool-versions: {}", e));
     }
-    println!();
 
-    // Step 5: Save configuration
-    if !dry_run {
-        println!("{}", "Saving configuration...".bold());
-        let config = Config {
-            dotfiles_dir,
-            xdg_config_home,
-            language_manager,
-            symlink_method: crate::core::config::SymlinkMethod::Stow,
-            install_oh_my_zsh: false,
-        };
+    if problems.is_empty() {
+        StepResult::ok(NAME).with_detail(format!("{} plugins added", added))
+    } else {
+        StepResult::failed(NAME, problems.join("; "))
+            .with_action("Run: dotfiles doctor for plugin and config details")
+    }
+}
 
-        let config_path = dirs::home_dir().unwrap().join(".dotfiles.conf");
-        config.save(&config_path)?;
-        println!(
-            "{}",
-            format!("  ✓ Configuration saved to {}", config_path.display()).green()
+fn step_packages(config: &Config, dry_run: bool) -> StepResult {
+    const NAME: &str = "Essential packages";
+    announce("Installing essential packages...");
+
+    if dry_run {
+        let plan = install::packages::plan_group(
+            "essential",
+            install::packages::ESSENTIAL_PACKAGES,
+            &config.packages,
         );
-        println!();
+        print_package_plan(&plan);
+        let installing = plan.names(|action| *action == PlannedAction::Install);
+        let pins = install::packages::pins_needed(&config.packages, &installing);
+        if !pins.is_empty() {
+            println!("{}", format!("  Would pin: {}", pins.join(", ")).yellow());
+        }
+        return StepResult::skipped(NAME, "dry run");
     }
 
-    // Step 6: Post-install instructions
-    println!();
-    println!("{}", "✅ Setup Complete!".bold().green());
-    println!();
-    println!("{}", "📝 Next Steps:".bold());
-    println!("  1. Restart your shell or run: source ~/.zshrc");
-    println!("  2. Verify installation: dotfiles doctor");
-    println!("  3. Configure additional tools manually:");
-    println!("     - iTerm2 preferences");
-    println!("     - GitHub CLI: gh auth login");
-    println!();
+    let status = install::packages::package_status(&config.packages);
+    if !status.is_complete() {
+        if let Err(e) = install::packages::install_essential_packages(&config.packages) {
+            return StepResult::failed(NAME, e.to_string());
+        }
+    }
+
+    if let Err(e) = install::packages::ensure_pins(&config.packages) {
+        return StepResult::failed(NAME, format!("pinning: {}", e))
+            .with_action("Run: brew pin <package> for each pinned package");
+    }
+
+    let missing = install::packages::check_essential_packages(&config.packages);
+    if missing.is_empty() {
+        StepResult::ok(NAME)
+    } else {
+        StepResult::failed(NAME, format!("missing: {}", missing.join(", ")))
+            .with_action(missing_packages_action(&config.packages, &missing))
+    }
+}
+
+fn step_selected_packages(config: &Config, dry_run: bool) -> StepResult {
+    const NAME: &str = "Optional packages";
+    announce("Installing optional packages...");
+
+    if dry_run {
+        let selected: Vec<&str> = config
+            .packages
+            .selected
+            .iter()
+            .map(String::as_str)
+            .collect();
+        print_package_plan(&install::packages::plan_group(
+            "optional",
+            &selected,
+            &config.packages,
+        ));
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    if let Err(e) = install::packages::install_selected_packages(&config.packages) {
+        return StepResult::failed(NAME, e.to_string());
+    }
+
+    let missing = install::packages::check_selected_packages(&config.packages);
+    if missing.is_empty() {
+        StepResult::ok(NAME).with_detail(format!("{} packages", config.packages.selected.len()))
+    } else {
+        StepResult::failed(NAME, format!("missing: {}", missing.join(", ")))
+            .with_action(missing_packages_action(&config.packages, &missing))
+    }
+}
+
+/// Prints what installing a package group would do, one line per outcome
+fn print_package_plan(plan: &PackagePlan) {
+    let mut install = Vec::new();
+    let mut installed = Vec::new();
+    let mut outdated = Vec::new();
+    let mut skipped = Vec::new();
+    for package in &plan.packages {
+        let name = match package.kind {
+            Some(PackageKind::Cask) => format!("{} (cask)", package.name),
+            _ => package.name.clone(),
+        };
+        match &package.action {
+            PlannedAction::Install => install.push(name),
+            PlannedAction::Installed { version: Some(v) } => {
+                installed.push(format!("{} {}", name, v))
+            }
+            PlannedAction::Installed { version: None } => installed.push(name),
+            PlannedAction::Outdated { version, latest } => {
+                outdated.push(format!("{} {} → {}", name, version, latest))
+            }
+            PlannedAction::Skipped => skipped.push(name),
+        }
+    }
+
+    if !plan.taps.is_empty() {
+        println!(
+            "{}",
+            format!("  Would tap: {}", plan.taps.join(", ")).yellow()
+        );
+    }
+    if install.is_empty() {
+        println!("  No {} packages to install", plan.label);
+    } else {
+        println!(
+            "{}",
+            format!(
+                "  Would install {} {} packages: {}",
+                install.len(),
+                plan.label,
+                install.join(", ")
+            )
+            .yellow()
+        );
+    }
+    let required: Vec<&str> = plan
+        .steps
+        .iter()
+        .filter_map(|step| match step {
+            InstallStep::Install(name) if !plan.packages.iter().any(|p| &p.name == name) => {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    if !required.is_empty() {
+        println!(
+            "{}",
+            format!("  Would first install (required): {}", required.join(", ")).yellow()
+        );
+    }
+    for step in &plan.steps {
+        if let InstallStep::PostInstall { package, action } = step {
+            println!(
+                "{}",
+                format!("  Would set up {} after {}", action.label(), package).yellow()
+            );
+        }
+    }
+    if !installed.is_empty() {
+        println!("  Already installed: {}", installed.join(", "));
+    }
+    if !outdated.is_empty() {
+        println!(
+            "  Outdated, left as is (run brew upgrade): {}",
+            outdated.join(", ")
+        );
+    }
+    if !skipped.is_empty() {
+        println!("  Skipped by [packages] config: {}", skipped.join(", "));
+    }
+}
+
+fn step_kubernetes(config: &Config, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Kubernetes";
+    announce("Setting up Kubernetes tools...");
+
+    if dry_run {
+        print_package_plan(&install::packages::plan_group(
+            "kubernetes",
+            install::packages::KUBERNETES_PACKAGES,
+            &config.packages,
+        ));
+        println!(
+            "{}",
+            "  Would link kubeconfig snippets into ~/.kube/configs".yellow()
+        );
+        for plugin in &config.kubernetes.krew_plugins {
+            println!(
+                "{}",
+                format!("  Would install krew plugin {}", plugin).yellow()
+            );
+        }
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let mut problems = Vec::new();
+
+    if let Err(e) = install::packages::install_kubernetes_packages(&config.packages) {
+        problems.push(format!("packages: {}", e));
+    }
+
+    let snippets = match install::kubernetes::link_snippets(&config.dotfiles_dir, home) {
+        Ok(report) if !report.is_success() => {
+            problems.push(format!("{} kubeconfig conflicts", report.conflicts.len()));
+            0
+        }
+        Ok(report) => report.total(),
+        Err(e) => {
+            problems.push(format!("kubeconfig: {}", e));
+            0
+        }
+    };
+
+    let added = match install::kubernetes::ensure_krew_plugins(&config.kubernetes.krew_plugins) {
+        Ok(added) => added.len(),
+        Err(e) => {
+            problems.push(format!("krew: {}", e));
+            0
+        }
+    };
+
+    if problems.is_empty() {
+        StepResult::ok(NAME).with_detail(format!(
+            "{} kubeconfig snippets, {} krew plugins added",
+            snippets, added
+        ))
+    } else {
+        StepResult::failed(NAME, problems.join("; "))
+            .with_action("Run: dotfiles doctor for kubernetes details")
+    }
+}
+
+fn step_language(config: &Config, lang_name: &str, dry_run: bool) -> StepResult {
+    let Some(installer) = language::get_installer(lang_name) else {
+        return StepResult::skipped(lang_name, "unknown language");
+    };
+    let name = format!("Language: {}", installer.display_name());
+    let versions = &config.languages[lang_name];
+    announce(&format!("Installing {}...", installer.display_name()));
+
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "  Would install {} {}",
+                lang_name,
+                versions.versions.join(", ")
+            )
+            .yellow()
+        );
+        return StepResult::skipped(name, "dry run");
+    }
+
+    let Some(vm) = install::version_manager::detect() else {
+        return StepResult::skipped(name, "no version manager available");
+    };
+
+    match installer.install_inactive(vm, versions) {
+        Ok(()) => StepResult::ok(name).with_detail(versions.versions.join(", ")),
+        Err(e) => StepResult::failed(name, e.to_string()).with_action(format!(
+            "Run: {} install {}",
+            vm.command(),
+            lang_name
+        )),
+    }
+}
+
+/// Sets the global version of a language `step_language` installed
+fn step_language_global(config: &Config, lang_name: &str, step: StepResult) -> StepResult {
+    if step.outcome != Outcome::Ok {
+        return step;
+    }
+    let (Some(installer), Some(vm)) = (
+        language::get_installer(lang_name),
+        install::version_manager::detect(),
+    ) else {
+        return step;
+    };
+
+    let global = installer.global_version(&config.languages[lang_name]);
+    match install::version_manager::set_global(vm, installer.language_name(), global) {
+        Ok(()) => step,
+        Err(e) => {
+            let command = match vm {
+                VersionManager::Asdf => format!("asdf global {} {}", lang_name, global),
+                VersionManager::Mise | VersionManager::Rtx => {
+                    format!("{} use --global {}@{}", vm.command(), lang_name, global)
+                }
+            };
+            StepResult::failed(step.name, e.to_string()).with_action(format!("Run: {}", command))
+        }
+    }
+}
+
+fn step_dev_tools(home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Development tools";
+    announce("Installing development tools...");
+
+    if dry_run {
+        println!("{}", "  Would install TPM (tmux plugin manager)".yellow());
+        println!(
+            "{}",
+            "  Would display Mason (nvim LSP manager) info".yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let result = match install::tools::install_tpm(home) {
+        Ok(()) => StepResult::ok(NAME),
+        Err(e) => StepResult::failed(NAME, format!("TPM: {}", e))
+            .with_action("Run: git clone https://github.com/tmux-plugins/tpm ~/.tmux/plugins/tpm"),
+    };
+
+    // Mason info is purely informational
+    let _ = install::tools::setup_mason_info();
+
+    result
+}
+
+fn step_symlinks(config: &Config, link_root: &Path, dry_run: bool) -> Vec<StepResult> {
+    announce("Creating symlinks...");
+
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "  Would create symlinks from dotfiles to {}",
+                link_root.display()
+            )
+            .yellow()
+        );
+        return vec![StepResult::skipped("Symlinks", "dry run")];
+    }
+
+    // Determine which symlinker to use
+    let status = install::packages::package_status(&config.packages);
+    let has_stow = status.installed_essential.iter().any(|p| p == "stow");
+
+    let symlinker: Box<dyn symlink::Symlinker> = if has_stow {
+        Box::new(
+            symlink::stow::StowSymlinker::new()
+                .skipping(config.skipped_links())
+                .with_config_home(config.xdg_config_home.clone()),
+        )
+    } else {
+        Box::new(
+            symlink::manual::ManualSymlinker::new()
+                .skipping(config.skipped_links())
+                .with_config_home(config.xdg_config_home.clone()),
+        )
+    };
+    let name = format!("Symlinks ({})", symlinker.name());
+
+    let mut steps = Vec::new();
+
+    // First, create main dotfiles symlinks
+    steps.push(match symlinker.symlink(&config.dotfiles_dir, link_root) {
+        Ok(report) => {
+            let slowest: Vec<String> = report.slowest(3).iter().map(|t| t.display()).collect();
+            if !slowest.is_empty() {
+                println!("{}", format!("  Slowest: {}", slowest.join(", ")).dimmed());
+            }
+            StepResult::from_symlink_report(name, &report)
+        }
+        Err(e) => StepResult::failed(name, e.to_string()),
+    });
+
+    // Then, handle special directories that need individual file symlinks
+    match symlink::symlink_individual_files(symlinker.as_ref(), &config.dotfiles_dir, link_root) {
+        Ok(report) if report.total() > 0 => steps.push(StepResult::from_symlink_report(
+            "Special directories",
+            &report,
+        )),
+        Ok(_) => {}
+        Err(e) => steps.push(StepResult::failed("Special directories", e.to_string())),
+    }
+
+    // Finally, files mapped to explicit destinations in [targets]
+    let targets = config.linked_targets();
+    if !targets.is_empty() {
+        let manual = symlink::manual::ManualSymlinker::new();
+        steps.push(
+            match symlink::link_targets(&manual, &config.dotfiles_dir, link_root, &targets) {
+                Ok(report) => StepResult::from_symlink_report("Mapped targets", &report),
+                Err(e) => StepResult::failed("Mapped targets", e.to_string()),
+            },
+        );
+    }
+
+    steps
+}
+
+fn step_copies(config: &Config, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Copied files";
+    announce("Copying copy-only files...");
+
+    let files = install::copies::find(config, &config.link_root(home));
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "  Would copy {} file(s) instead of linking them",
+                files.len()
+            )
+            .yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let state_path = state::state_path(home);
+    let outcomes = State::load(&state_path).and_then(|mut state| {
+        let outcomes = install::copies::copy_all(&files, &mut state)?;
+        state.save(&state_path)?;
+        Ok(outcomes)
+    });
+    let outcomes = match outcomes {
+        Ok(outcomes) => outcomes,
+        Err(e) => return StepResult::failed(NAME, e.to_string()),
+    };
+    commands::sync::print_copy_outcomes(&outcomes, home);
+
+    let copied = outcomes
+        .iter()
+        .filter(|(_, status)| {
+            matches!(
+                status,
+                CopyStatus::Missing | CopyStatus::RepoChanged | CopyStatus::Symlinked
+            )
+        })
+        .count();
+    let diverged = outcomes
+        .iter()
+        .filter(|(_, status)| status.needs_choice())
+        .count();
+
+    let step = StepResult::ok(NAME).with_detail(format!(
+        "{} copied, {} unchanged, {} changed locally",
+        copied,
+        outcomes.len() - copied - diverged,
+        diverged
+    ));
+    if diverged > 0 {
+        step.with_action("Push or pull the local changes with: dotfiles sync")
+    } else {
+        step
+    }
+}
+
+fn step_shell_integration(config: &Config, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Shell integration";
+    announce("Configuring shell integration...");
+
+    if dry_run {
+        println!(
+            "{}",
+            "  Would add check-claude-changes.sh to .zshrc".yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let zshrc = home.join(".zshrc");
+    let script_path = config.dotfiles_dir.join("scripts/check-claude-changes.sh");
+
+    if !script_path.exists() {
+        return StepResult::skipped(
+            NAME,
+            "check-claude-changes.sh not found in dotfiles/scripts",
+        );
+    }
+
+    match install::shell::ensure_script_sourced(&zshrc, &script_path, "check-claude-changes.sh") {
+        Ok(()) => StepResult::ok(NAME),
+        Err(e) => StepResult::failed(NAME, e.to_string()).with_action(format!(
+            "Add `source {}` to ~/.zshrc",
+            script_path.display()
+        )),
+    }
+}
+
+fn step_managed_block(lines: &[String], home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Managed shell block";
+    announce("Updating managed shell block...");
+
+    if dry_run {
+        for line in lines {
+            println!("{}", format!("  Would add to .zshrc: {}", line).yellow());
+        }
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    match install::shell::ensure_managed_block(&home.join(".zshrc"), lines) {
+        Ok(true) => StepResult::ok(NAME).with_detail("updated"),
+        Ok(false) => StepResult::ok(NAME).with_detail("already up to date"),
+        Err(e) => StepResult::failed(NAME, e.to_string()),
+    }
+}
+
+fn step_git(config: &Config, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Git config";
+    announce("Configuring git...");
+
+    let local = install::git::local_config_path(home);
+    let entries = install::git::desired_entries(&config.git);
+    let install_delta = config.git.delta
+        && !config.packages.is_skipped(install::git::DELTA_PACKAGE)
+        && can_install_packages(config);
 
-    Ok(())
+    if dry_run {
+        if install_delta {
+            println!(
+                "{}",
+                format!("  Would install {}", install::git::DELTA_PACKAGE).yellow()
+            );
+        }
+        for (key, value) in &entries {
+            println!(
+                "{}",
+                format!("  Would set {} = {} in {}", key, value, local.display()).yellow()
+            );
+        }
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let mut problems = Vec::new();
+
+    if install_delta {
+        if let Err(e) =
+            install::packages::install_package(install::git::DELTA_PACKAGE, &config.packages)
+        {
+            problems.push(format!("{}: {}", install::git::DELTA_PACKAGE, e));
+        }
+    }
+
+    let changed = match install::git::apply_entries(&local, &entries) {
+        Ok(changed) => changed,
+        Err(e) => {
+            problems.push(format!("{}: {}", local.display(), e));
+            0
+        }
+    };
+
+    let mut detail = format!("{} settings updated", changed);
+    if config.git.delta && !can_install_packages(config) {
+        detail.push_str(&format!(
+            "; {} not installed ({})",
+            install::git::DELTA_PACKAGE,
+            userspace::SKIP_REASON
+        ));
+    }
+    match install::git::ensure_local_config_included(home) {
+        Ok(Some(file))
+            if config
+                .dotfiles_dir
+                .canonicalize()
+                .is_ok_and(|dir| file.starts_with(dir)) =>
+        {
+            detail.push_str(&format!(
+                "; include added to {} in the repo, commit it",
+                file.display()
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(format!("include: {}", e)),
+    }
+
+    if problems.is_empty() {
+        StepResult::ok(NAME).with_detail(detail)
+    } else {
+        StepResult::failed(NAME, problems.join("; "))
+            .with_action("Run: dotfiles doctor for git details")
+    }
+}
+
+fn step_obsidian(dotfiles_dir: &Path, vault: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Obsidian vault";
+    announce("Configuring Obsidian vault...");
+
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "  Would set obsidian-cli default vault to {}",
+                vault.display()
+            )
+            .yellow()
+        );
+        println!(
+            "{}",
+            format!(
+                "  Would link obsidian config into {}/.obsidian",
+                vault.display()
+            )
+            .yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    if !vault.is_dir() {
+        return StepResult::failed(NAME, format!("{} does not exist", vault.display()))
+            .with_action("Create or sync the vault, then re-run setup");
+    }
+
+    let mut problems = Vec::new();
+
+    match install::obsidian::link_vault_config(dotfiles_dir, vault) {
+        Ok(report) if !report.is_success() => {
+            problems.push(format!("{} config conflicts", report.conflicts.len()))
+        }
+        Ok(_) => {}
+        Err(e) => problems.push(format!("config linking: {}", e)),
+    }
+
+    if let Err(e) = install::obsidian::set_default_vault(vault) {
+        problems.push(format!("obsidian-cli: {}", e));
+    }
+
+    if problems.is_empty() {
+        StepResult::ok(NAME).with_detail(vault.display().to_string())
+    } else {
+        StepResult::failed(NAME, problems.join("; "))
+            .with_action("Run: dotfiles doctor for Obsidian details")
+    }
+}
+
+fn step_karabiner(dotfiles_dir: &Path, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Karabiner";
+    announce("Configuring Karabiner-Elements...");
+
+    // A file Karabiner can't load disables every remapping, so don't link one
+    let repo_config = install::karabiner::repo_config(dotfiles_dir);
+    let problems = std::fs::read_to_string(&repo_config)
+        .map(|content| install::karabiner::structure_problems(&content))
+        .unwrap_or_else(|e| vec![e.to_string()]);
+    if !problems.is_empty() {
+        return StepResult::failed(
+            NAME,
+            format!("karabiner.json is invalid: {}", problems.join("; ")),
+        )
+        .with_action(format!("Fix {}, then re-run setup", repo_config.display()));
+    }
+
+    if dry_run {
+        println!(
+            "{}",
+            "  Would link ~/.config/karabiner and reload Karabiner".yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    match install::karabiner::link_config(dotfiles_dir, home) {
+        Ok(status) if status.is_conflict() => {
+            return StepResult::failed(NAME, "~/.config/karabiner already exists").with_action(
+                "Move it aside (mv ~/.config/karabiner ~/.config/karabiner.bak) and re-run setup",
+            )
+        }
+        Ok(_) => {}
+        Err(e) => return StepResult::failed(NAME, e.to_string()),
+    }
+
+    if !install::karabiner::cli_installed() {
+        return StepResult::ok(NAME)
+            .with_detail("linked; Karabiner-Elements not installed")
+            .with_action("Run: brew install --cask karabiner-elements");
+    }
+    match install::karabiner::reload() {
+        Ok(()) => StepResult::ok(NAME).with_detail("linked and reloaded"),
+        Err(e) => StepResult::failed(NAME, format!("reload failed: {}", e))
+            .with_action("Open Karabiner-Elements to load the new config"),
+    }
+}
+
+fn step_nvim_distro(config: &Config, dry_run: bool) -> StepResult {
+    const NAME: &str = "Neovim config";
+    announce("Installing Neovim config...");
+
+    let config_dir = config.xdg_config_home.join("nvim");
+    let url = install::nvim::distro_url(&config.nvim);
+    if dry_run {
+        if let Some(url) = url {
+            println!(
+                "{}",
+                format!("  Would clone {} into {}", url, config_dir.display()).yellow()
+            );
+        }
+        println!(
+            "{}",
+            "  Would sync plugins (nvim --headless) if the config uses lazy.nvim".yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let mut detail = config
+        .nvim
+        .distro
+        .map(|distro| distro.display_name().to_string())
+        .unwrap_or_default();
+    if let Some(url) = url {
+        match install::nvim::install_distro(url, &config_dir) {
+            Ok(true) => detail.push_str(", cloned"),
+            Ok(false) => {}
+            Err(e) => {
+                return StepResult::failed(NAME, e.to_string()).with_action(format!(
+                    "Move {} aside, or remove [nvim] from ~/.dotfiles.conf, and re-run setup",
+                    config_dir.display()
+                ))
+            }
+        }
+    }
+
+    match install::nvim::sync_plugins(&config_dir) {
+        Ok(true) => StepResult::ok(NAME).with_detail(format!("{}, plugins synced", detail)),
+        Ok(false) => StepResult::ok(NAME).with_detail(format!("{}, no lazy.nvim to sync", detail)),
+        Err(e) => StepResult::failed(NAME, format!("plugin sync failed: {}", e))
+            .with_action("Open nvim and run :Lazy sync"),
+    }
+}
+
+fn step_machine(settings: &MachineSettings, os: OS, dry_run: bool) -> StepResult {
+    const NAME: &str = "Machine identity";
+    announce("Checking hostname, timezone, and locale...");
+
+    let drift = install::machine::drift(settings, os);
+    if drift.is_empty() {
+        return StepResult::ok(NAME).with_detail("already set");
+    }
+
+    if dry_run {
+        for d in &drift {
+            println!(
+                "{}",
+                format!(
+                    "  Would run: {}",
+                    install::machine::display_commands(&install::machine::commands(
+                        d.setting, &d.desired, os
+                    ))
+                )
+                .yellow()
+            );
+        }
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    let mut changed = Vec::new();
+    for d in &drift {
+        if let Err(e) = install::machine::apply(d.setting, &d.desired, os) {
+            return StepResult::failed(NAME, format!("setting {}: {}", d.setting.name(), e))
+                .with_action(format!(
+                    "Run: {}",
+                    install::machine::display_commands(&install::machine::commands(
+                        d.setting, &d.desired, os
+                    ))
+                ));
+        }
+        changed.push(format!("{} = {}", d.setting.name(), d.desired));
+    }
+    let step = StepResult::ok(NAME).with_detail(changed.join(", "));
+    if drift
+        .iter()
+        .any(|d| d.setting == install::machine::Setting::Locale)
+    {
+        step.with_action("Log out and back in for the new locale to take effect")
+    } else {
+        step
+    }
+}
+
+fn step_bare_repo(repo: &BareRepo, dotfiles_dir: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "Bare repo import";
+    announce("Importing bare dotfiles repo...");
+
+    match bare::convert(repo, dotfiles_dir, dry_run) {
+        Ok(conversion) if dry_run => {
+            println!(
+                "{}",
+                format!(
+                    "  Would clone {} ({} tracked files) into {} and link {} top-level file(s)",
+                    repo.git_dir.display(),
+                    conversion.tracked,
+                    dotfiles_dir.display(),
+                    conversion.linked.len()
+                )
+                .yellow()
+            );
+            StepResult::skipped(NAME, "dry run")
+        }
+        Ok(conversion) => {
+            let step = StepResult::ok(NAME).with_detail(format!(
+                "{} tracked files, {} linked",
+                conversion.tracked,
+                conversion.linked.len()
+            ));
+            if conversion.left_in_place.is_empty() {
+                step.with_action(format!(
+                    "Once you're happy, remove {}",
+                    repo.git_dir.display()
+                ))
+            } else {
+                step.with_action(format!(
+                    "Review {} (left in place, directories usually hold untracked files)",
+                    conversion
+                        .left_in_place
+                        .iter()
+                        .map(|path| path.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        }
+        Err(e) => StepResult::failed(NAME, e.to_string()),
+    }
+}
+
+fn step_aws_config(dotfiles_dir: &Path, home: &Path, dry_run: bool) -> StepResult {
+    const NAME: &str = "AWS config";
+    announce("Scaffolding AWS config...");
+
+    if dry_run {
+        println!(
+            "{}",
+            format!(
+                "  Would create ~/.aws/config from {}",
+                install::aws::CONFIG_TEMPLATE
+            )
+            .yellow()
+        );
+        return StepResult::skipped(NAME, "dry run");
+    }
+
+    match install::aws::scaffold_config(dotfiles_dir, home) {
+        Ok(Some(path)) => StepResult::ok(NAME)
+            .with_detail(format!("created {}", path.display()))
+            .with_action("Run: aws sso login --profile <name>"),
+        Ok(None) => StepResult::skipped(NAME, "~/.aws/config already exists"),
+        Err(e) => StepResult::failed(NAME, e.to_string()),
+    }
+}
+
+fn step_templates(
+    dotfiles_dir: &Path,
+    home: &Path,
+    dry_run: bool,
+    assume_yes: bool,
+) -> Result<StepResult> {
+    const NAME: &str = "Templates";
+    announce("Rendering templates...");
+
+    let templates = match install::templates::find_templates(dotfiles_dir, home) {
+        Ok(templates) => templates,
+        Err(e) => return Ok(StepResult::failed(NAME, e.to_string())),
+    };
+
+    if dry_run {
+        println!(
+            "{}",
+            format!("  Would render {} template(s) into ~", templates.len()).yellow()
+        );
+        return Ok(StepResult::skipped(NAME, "dry run"));
+    }
+
+    let vars_path = install::templates::vars_path(home);
+    let mut vars = install::templates::load_vars(&vars_path)?;
+    let missing = install::templates::missing_variables(&templates, &vars);
+    if !missing.is_empty() && !assume_yes {
+        for name in missing {
+            let value: String = Input::new()
+                .with_prompt(format!("Template variable `{}`", name))
+                .interact_text()
+                .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+            vars.insert(name, value);
+        }
+        install::templates::save_vars(&vars_path, &vars)?;
+    }
+
+    let state_path = state::state_path(home);
+    let mut state = State::load(&state_path)?;
+    let outcomes = match install::templates::render_all(&templates, &vars, &mut state) {
+        Ok(outcomes) => outcomes,
+        Err(e) => return Ok(StepResult::failed(NAME, e.to_string())),
+    };
+    state.save(&state_path)?;
+    commands::sync::print_outcomes(&outcomes, home);
+
+    let written = outcomes
+        .iter()
+        .filter(|(_, outcome)| *outcome == RenderOutcome::Written)
+        .count();
+    let skipped = outcomes
+        .iter()
+        .filter(|(_, outcome)| {
+            matches!(
+                outcome,
+                RenderOutcome::MissingVars(_) | RenderOutcome::ModifiedLocally
+            )
+        })
+        .count();
+
+    let step = StepResult::ok(NAME).with_detail(format!(
+        "{} rendered, {} unchanged, {} skipped",
+        written,
+        outcomes.len() - written - skipped,
+        skipped
+    ));
+    Ok(if skipped > 0 {
+        step.with_action(format!(
+            "Answer variables in {} or re-run: dotfiles setup",
+            vars_path.display()
+        ))
+    } else {
+        step
+    })
+}
+
+fn step_claude_repo(home: &Path, dry_run: bool, assume_yes: bool) -> Result<StepResult> {
+    const NAME: &str = "Claude repository";
+    announce("Checking claude repository...");
+
+    if dry_run {
+        println!("{}", "  Would clone claude repository if missing".yellow());
+        return Ok(StepResult::skipped(NAME, "dry run"));
+    }
+
+    let claude_dir = home.join(".claude");
+    let started = std::time::Instant::now();
+
+    let step = if !claude_dir.exists() && assume_yes {
+        StepResult::skipped(NAME, "not cloned in non-interactive mode")
+            .with_action("Run: git clone <claude repo> ~/.claude")
+    } else if !claude_dir.exists() {
+        // Prompt for claude repo URL (or use default)
+        let claude_repo_url: String = Input::new()
+            .with_prompt("Claude repository URL")
+            .default("https://github.com/YOUR_USERNAME/claudefiles.git".to_string())
+            .interact_text()
+            .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+
+        match install::repos::clone_claude_repo(&claude_repo_url) {
+            Ok(()) => StepResult::ok(NAME).with_detail("cloned"),
+            Err(e) => StepResult::failed(NAME, e.to_string())
+                .with_action(format!("Run: git clone {} ~/.claude", claude_repo_url)),
+        }
+    } else if !install::repos::is_git_repo(&claude_dir) {
+        StepResult::skipped(NAME, "~/.claude is not a git repository")
+            .with_action("Consider initializing: cd ~/.claude && git init")
+    } else {
+        StepResult::ok(NAME).with_detail("already exists")
+    };
+
+    Ok(step.with_duration(started.elapsed()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::LanguageManager;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_profile() {
+        let mut config = Config::default();
+        assert!(check_profile(&config, "work").is_err());
+        config
+            .profiles
+            .insert("work".to_string(), Default::default());
+        assert!(check_profile(&config, "work").is_ok());
+        let err = check_profile(&config, "wrok").unwrap_err().to_string();
+        assert!(err.contains("defined: work"));
+    }
+
+    #[test]
+    fn test_uses_homebrew() {
+        let mut config = Config::default();
+        assert!(uses_homebrew(&config));
+        config.packages.backend = Some(PackageSource::Prebuilt);
+        assert!(!uses_homebrew(&config));
+    }
+
+    #[test]
+    fn test_load_base_config_prefers_home_config() {
+        let temp = TempDir::new().unwrap();
+        let home_config = temp.path().join(".dotfiles.conf");
+        let repo = temp.path().join("dotfiles");
+        std::fs::create_dir_all(&repo).unwrap();
+
+        let mut config = Config {
+            language_manager: LanguageManager::Asdf,
+            ..Config::default()
+        };
+        config.save(&home_config).unwrap();
+        config.language_manager = LanguageManager::Rtx;
+        config.save(&repo.join(".dotfiles.conf")).unwrap();
+
+        let loaded = load_base_config(&home_config, Some(&repo)).unwrap();
+        assert_eq!(loaded.language_manager, LanguageManager::Asdf);
+    }
+
+    #[test]
+    fn test_load_base_config_falls_back_to_repo_config() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path().join("dotfiles");
+        std::fs::create_dir_all(&repo).unwrap();
+
+        let config = Config {
+            language_manager: LanguageManager::Rtx,
+            ..Config::default()
+        };
+        config.save(&repo.join(".dotfiles.conf")).unwrap();
+
+        let loaded = load_base_config(&temp.path().join(".dotfiles.conf"), Some(&repo)).unwrap();
+        assert_eq!(loaded.language_manager, LanguageManager::Rtx);
+    }
+
+    #[test]
+    fn test_load_base_config_defaults() {
+        let temp = TempDir::new().unwrap();
+        let loaded = load_base_config(&temp.path().join(".dotfiles.conf"), None).unwrap();
+        assert_eq!(loaded.language_manager, Config::default().language_manager);
+    }
+
+    #[test]
+    fn test_load_base_config_rejects_invalid_home_config() {
+        let temp = TempDir::new().unwrap();
+        let home_config = temp.path().join(".dotfiles.conf");
+        std::fs::write(&home_config, "dotfiles_dir = [\n").unwrap();
+
+        let error = load_base_config(&home_config, None).unwrap_err();
+        assert!(error.to_string().contains("not a valid dotfiles config"));
+    }
 }
@@ -0,0 +1,315 @@
+use crate::core::config::Config;
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::core::paths::display_path;
+use crate::core::userspace;
+use crate::core::walk::RepoWalker;
+use crate::error::Result;
+use crate::install::{self, packages::PackageStatus};
+use crate::symlink::{self, LinkIssue};
+use chrono::NaiveDateTime;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+
+/// Format setup writes `last_setup` in
+const LAST_SETUP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How the top-level repo entries are linked into the link root
+#[derive(Debug, Default, PartialEq, Eq)]
+struct LinkCounts {
+    linked: usize,
+    unlinked: Vec<PathBuf>,
+    conflicting: Vec<(PathBuf, String)>,
+}
+
+/// Whether ~/.dotfiles.conf still reflects the repo
+#[derive(Debug, PartialEq, Eq)]
+enum ConfigState {
+    Missing,
+    Invalid(String),
+    NeverSetUp,
+    /// The repo has commits newer than the last completed setup
+    Stale {
+        last_setup: String,
+    },
+    Current {
+        last_setup: String,
+    },
+}
+
+/// Prints a one-screen summary of link, package, and config drift
+///
+/// Exits with `conflicts` when existing files block links, `errors` when the
+/// config, the dotfiles repo, or essential packages are missing, and
+/// `warnings` for anything else out of date.
+pub fn run(context: &AppContext) -> Result<ExitStatus> {
+    let home = &context.home;
+    let loaded = context
+        .config_path
+        .exists()
+        .then(|| context.machine_config().map_err(|e| e.to_string()));
+    let config = loaded.as_ref().and_then(|result| result.as_ref().ok());
+
+    let dotfiles_dir = config.map_or_else(
+        || Config::default().dotfiles_dir,
+        |c| c.dotfiles_dir.clone(),
+    );
+    let link_root = config.map_or_else(
+        || crate::core::paths::link_root_override().unwrap_or_else(|| home.clone()),
+        |c| c.link_root(home),
+    );
+    let mut status = ExitStatus::Ok;
+
+    if dotfiles_dir.is_dir() {
+        let skipped = config.map(Config::skipped_links).unwrap_or_default();
+        let config_home = config.map(|c| c.xdg_config_home.as_path());
+        let issues = symlink::validate_symlinks(&dotfiles_dir, &link_root, config_home, &skipped)?;
+        let links = link_counts(&dotfiles_dir, &skipped, issues);
+        status = status.worst(print_links(&links, home));
+    } else {
+        row(
+            "Symlinks",
+            format!(
+                "no dotfiles repo at {}; run: dotfiles init",
+                display_path(&dotfiles_dir, home)
+            )
+            .red(),
+        );
+        status = ExitStatus::Errors;
+    }
+
+    let userspace_only = config.map_or_else(userspace::forced, |c| c.userspace_only);
+    if userspace_only {
+        row(
+            "Packages",
+            format!("skipped ({})", userspace::SKIP_REASON).dimmed(),
+        );
+    } else {
+        let settings = config.map(|c| c.packages.clone()).unwrap_or_default();
+        status = status.worst(print_packages(&install::packages::package_status(
+            &settings,
+        )));
+    }
+
+    let state = match &loaded {
+        None => ConfigState::Missing,
+        Some(Err(e)) => ConfigState::Invalid(e.clone()),
+        Some(Ok(config)) => config_state(
+            config.last_setup.as_deref(),
+            install::repos::last_commit_time(&config.dotfiles_dir),
+        ),
+    };
+    status = status.worst(print_config(&state));
+
+    Ok(status)
+}
+
+/// Sorts `validate_symlinks` issues into unlinked and conflicting entries
+///
+/// `skipped` entries aren't meant to be linked, so they don't count as linked either.
+fn link_counts(source: &Path, skipped: &[String], issues: Vec<(PathBuf, LinkIssue)>) -> LinkCounts {
+    let total = RepoWalker::new(source)
+        .max_depth(1)
+        .skip(skipped)
+        .entries()
+        .map_or(0, |entries| entries.len());
+    let mut counts = LinkCounts::default();
+
+    for (path, issue) in issues {
+        if issue.is_unlinked() {
+            counts.unlinked.push(path);
+        } else {
+            counts.conflicting.push((path, issue.to_string()));
+        }
+    }
+    counts.linked = total.saturating_sub(counts.unlinked.len() + counts.conflicting.len());
+    counts
+}
+
+fn config_state(last_setup: Option<&str>, last_commit: Option<NaiveDateTime>) -> ConfigState {
+    let Some(last_setup) = last_setup else {
+        return ConfigState::NeverSetUp;
+    };
+    let setup_time = NaiveDateTime::parse_from_str(last_setup, LAST_SETUP_FORMAT).ok();
+    let last_setup = last_setup.to_string();
+    match (setup_time, last_commit) {
+        (Some(setup), Some(commit)) if commit > setup => ConfigState::Stale { last_setup },
+        _ => ConfigState::Current { last_setup },
+    }
+}
+
+/// Prints a labelled row of the status table
+fn row(label: &str, value: impl std::fmt::Display) {
+    println!("{}{}", format!("{:<10}", label).bold(), value);
+}
+
+/// Prints an indented detail line under a row
+fn detail(value: impl std::fmt::Display) {
+    println!("{:<10}{}", "", value);
+}
+
+fn print_links(links: &LinkCounts, home: &Path) -> ExitStatus {
+    row(
+        "Symlinks",
+        format!(
+            "{} linked · {} unlinked · {} conflicting",
+            links.linked,
+            links.unlinked.len(),
+            links.conflicting.len()
+        ),
+    );
+    for (path, reason) in &links.conflicting {
+        detail(format!("✗ {}  {}", display_path(path, home), reason).red());
+    }
+    for path in &links.unlinked {
+        detail(format!("○ {}  unlinked", display_path(path, home)).yellow());
+    }
+
+    if !links.conflicting.is_empty() {
+        ExitStatus::Conflicts
+    } else if !links.unlinked.is_empty() {
+        ExitStatus::Warnings
+    } else {
+        ExitStatus::Ok
+    }
+}
+
+fn print_packages(packages: &PackageStatus) -> ExitStatus {
+    let groups = [
+        (
+            "essential",
+            &packages.installed_essential,
+            &packages.missing_essential,
+        ),
+        (
+            "development",
+            &packages.installed_development,
+            &packages.missing_development,
+        ),
+        ("cloud", &packages.installed_cloud, &packages.missing_cloud),
+    ];
+    let counts: Vec<String> = groups
+        .iter()
+        .map(|(name, installed, missing)| {
+            format!(
+                "{} {}/{}",
+                name,
+                installed.len(),
+                installed.len() + missing.len()
+            )
+        })
+        .collect();
+    row("Packages", counts.join(" · "));
+
+    let missing: Vec<&str> = groups
+        .iter()
+        .flat_map(|(_, _, missing)| missing.iter().map(String::as_str))
+        .collect();
+    if !missing.is_empty() {
+        detail(format!("missing: {}", missing.join(", ")).yellow());
+    }
+
+    if !packages.is_complete() {
+        ExitStatus::Errors
+    } else if !missing.is_empty() {
+        ExitStatus::Warnings
+    } else {
+        ExitStatus::Ok
+    }
+}
+
+fn print_config(state: &ConfigState) -> ExitStatus {
+    match state {
+        ConfigState::Missing => {
+            row(
+                "Config",
+                "~/.dotfiles.conf not found; run: dotfiles setup".red(),
+            );
+            ExitStatus::Errors
+        }
+        ConfigState::Invalid(e) => {
+            row("Config", format!("~/.dotfiles.conf: {}", e).red());
+            ExitStatus::Errors
+        }
+        ConfigState::NeverSetUp => {
+            row(
+                "Config",
+                "~/.dotfiles.conf present, setup never completed".yellow(),
+            );
+            ExitStatus::Warnings
+        }
+        ConfigState::Stale { last_setup } => {
+            row(
+                "Config",
+                format!(
+                    "stale: the repo changed after the last setup ({}); run: dotfiles setup",
+                    last_setup
+                )
+                .yellow(),
+            );
+            ExitStatus::Warnings
+        }
+        ConfigState::Current { last_setup } => {
+            row("Config", format!("up to date, last setup {}", last_setup));
+            ExitStatus::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_counts() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("dotfiles");
+        let target = temp.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        for name in [".zshrc", ".vimrc", ".tmux.conf"] {
+            fs::write(source.join(name), name).unwrap();
+        }
+        std::os::unix::fs::symlink(source.join(".zshrc"), target.join(".zshrc")).unwrap();
+        fs::write(target.join(".vimrc"), "local").unwrap();
+
+        let issues = symlink::validate_symlinks(&source, &target, None, &[]).unwrap();
+        let counts = link_counts(&source, &[], issues);
+        assert_eq!(counts.linked, 1);
+        assert_eq!(counts.unlinked, vec![target.join(".tmux.conf")]);
+        assert_eq!(counts.conflicting[0].0, target.join(".vimrc"));
+
+        // A skipped entry is neither linked nor missing
+        fs::create_dir_all(source.join("obsidian")).unwrap();
+        let skipped = vec!["obsidian".to_string()];
+        let issues = symlink::validate_symlinks(&source, &target, None, &skipped).unwrap();
+        let counts = link_counts(&source, &skipped, issues);
+        assert_eq!(counts.linked, 1);
+        assert_eq!(counts.unlinked, vec![target.join(".tmux.conf")]);
+    }
+
+    #[test]
+    fn test_config_state() {
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, LAST_SETUP_FORMAT).ok();
+        assert_eq!(config_state(None, None), ConfigState::NeverSetUp);
+        assert_eq!(
+            config_state(Some("2026-01-01 10:00:00"), at("2026-02-01 09:00:00")),
+            ConfigState::Stale {
+                last_setup: "2026-01-01 10:00:00".to_string()
+            }
+        );
+        assert_eq!(
+            config_state(Some("2026-03-01 10:00:00"), at("2026-02-01 09:00:00")),
+            ConfigState::Current {
+                last_setup: "2026-03-01 10:00:00".to_string()
+            }
+        );
+        assert!(matches!(
+            config_state(Some("2026-03-01 10:00:00"), None),
+            ConfigState::Current { .. }
+        ));
+    }
+}
@@ -0,0 +1,371 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::context::AppContext;
+use crate::core::paths::display_path;
+use crate::core::state::State;
+use crate::core::{cancel, notify, sudo};
+use crate::error::{DotfilesError, Result};
+use crate::install::copies::{CopiedFile, CopyStatus};
+use crate::install::repos::RepoStatus;
+use crate::install::templates::RenderOutcome;
+use crate::install::{copies, karabiner, nvim, repos, sync_agent, templates};
+use colored::Colorize;
+use dialoguer::Select;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Pulls the dotfiles repo, updates its submodules, and re-renders templates that changed
+///
+/// Templates with unanswered variables are skipped with a pointer to
+/// `dotfiles setup`, which prompts for them. Copy-only files are refreshed
+/// from the repo; ones changed on this machine are offered for push or pull
+/// when run in a terminal. A distro chosen in `[nvim]` is fast-forwarded
+/// and its plugins synced.
+pub fn run(context: &AppContext) -> Result<()> {
+    let (home, config) = (&context.home, context.machine_config()?);
+
+    if repos::is_git_repo(&config.dotfiles_dir) {
+        let karabiner_config = karabiner::repo_config(&config.dotfiles_dir);
+        let karabiner_before = std::fs::read_to_string(&karabiner_config).ok();
+
+        println!("{}", "Pulling dotfiles...".bold());
+        match pull(&config.dotfiles_dir) {
+            Ok(()) => println!("  {}", "✓ Up to date".green()),
+            Err(e) => println!("  {}", format!("⚠ Pull failed: {}", e).yellow()),
+        }
+        if repos::has_submodules(&config.dotfiles_dir) {
+            match repos::update_submodules(&config.dotfiles_dir) {
+                Ok(()) => println!("  {}", "✓ Submodules updated".green()),
+                Err(e) => println!("  {}", format!("⚠ Submodule update failed: {}", e).yellow()),
+            }
+        }
+
+        if let Ok(after) = std::fs::read_to_string(&karabiner_config) {
+            if cfg!(target_os = "macos") && karabiner_before.as_ref() != Some(&after) {
+                reload_karabiner(&after);
+            }
+        }
+        println!();
+    }
+
+    if config.nvim.distro.is_some() {
+        println!("{}", "Updating Neovim config...".bold());
+        let nvim_dir = config.xdg_config_home.join("nvim");
+        if let Some(url) = nvim::distro_url(&config.nvim) {
+            match nvim::update_distro(url, &nvim_dir) {
+                Ok(nvim::DistroUpdate::Updated) => println!("  {}", "✓ Distro up to date".green()),
+                Ok(nvim::DistroUpdate::Diverged) => println!(
+                    "  {}",
+                    format!(
+                        "⚠ {} has local changes; left as is (pull upstream yourself to update)",
+                        display_path(&nvim_dir, home)
+                    )
+                    .yellow()
+                ),
+                Err(e) => println!("  {}", format!("⚠ Distro update failed: {}", e).yellow()),
+            }
+        }
+        match nvim::sync_plugins(&nvim_dir) {
+            Ok(true) => println!("  {}", "✓ Plugins synced".green()),
+            Ok(false) => println!(
+                "  {}",
+                "– No lazy.nvim in the config; plugins not synced".dimmed()
+            ),
+            Err(e) => println!("  {}", format!("⚠ Plugin sync failed: {}", e).yellow()),
+        }
+        println!();
+    }
+
+    let state_path = &context.state_path;
+    let mut state = State::load(state_path)?;
+
+    let found = templates::find_templates(&config.dotfiles_dir, home)?;
+    if !found.is_empty() {
+        println!("{}", "Rendering templates...".bold());
+        let vars = templates::load_vars(&templates::vars_path(home))?;
+        let outcomes = templates::render_all(&found, &vars, &mut state)?;
+        state.save(state_path)?;
+        print_outcomes(&outcomes, home);
+    }
+
+    let files = copies::find(&config, &config.link_root(home));
+    if !files.is_empty() {
+        if !found.is_empty() {
+            println!();
+        }
+        println!("{}", "Copying copy-only files...".bold());
+        let outcomes = copies::copy_all(&files, &mut state)?;
+        state.save(state_path)?;
+        print_copy_outcomes(&outcomes, home);
+
+        let changed: Vec<(&CopiedFile, CopyStatus)> = files
+            .iter()
+            .zip(&outcomes)
+            .filter(|(_, (_, status))| status.needs_choice())
+            .map(|(file, (_, status))| (file, *status))
+            .collect();
+        if !changed.is_empty() && !sudo::is_interactive() {
+            println!(
+                "  {}",
+                "Run dotfiles sync in a terminal to push or pull local changes".dimmed()
+            );
+        }
+        for (file, status) in changed.into_iter().filter(|_| sudo::is_interactive()) {
+            resolve_copy(file, status, &mut state, home)?;
+            state.save(state_path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the dotfiles repo and ~/.claude every `interval_minutes`, notifying on changes
+///
+/// Meant to run as a background agent (see `dotfiles sync agent`). Each pass
+/// fetches upstream and looks for commits to pull, unpushed commits, and
+/// uncommitted edits. A desktop notification is sent when the set of
+/// problems changes, so an unchanged backlog doesn't notify every pass.
+pub fn watch(context: &AppContext, interval_minutes: u64, once: bool) -> Result<()> {
+    let (home, config) = (&context.home, context.machine_config()?);
+    let repos = [
+        ("dotfiles".to_string(), config.dotfiles_dir.clone()),
+        ("~/.claude".to_string(), home.join(".claude")),
+    ];
+    let interval = Duration::from_secs(interval_minutes.max(1) * 60);
+    let mut notified: Vec<String> = Vec::new();
+
+    loop {
+        let problems = check_repos(&repos);
+        let stamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        if problems.is_empty() {
+            println!("[{}] {}", stamp, "✓ Everything in sync".green());
+        }
+        for problem in &problems {
+            println!("[{}] {}", stamp, format!("⚠ {}", problem).yellow());
+        }
+
+        if !problems.is_empty() && problems != notified {
+            if let Err(e) = notify::notify("Dotfiles need attention", &problems.join("\n")) {
+                println!(
+                    "  {}",
+                    format!("⚠ Could not send notification: {}", e).yellow()
+                );
+            }
+        }
+        notified = problems;
+
+        if once {
+            return Ok(());
+        }
+        let deadline = Instant::now() + interval;
+        while Instant::now() < deadline {
+            cancel::check("sync watch")?;
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+/// Installs (or with `remove`, uninstalls) the launchd/systemd agent running `sync watch`
+pub fn agent(context: &AppContext, interval_minutes: u64, remove: bool) -> Result<()> {
+    let home = &context.home;
+
+    if remove {
+        if sync_agent::remove(home)? {
+            println!("{}", "✓ Removed the sync watcher".green());
+        } else {
+            println!("Sync watcher is not installed");
+        }
+        return Ok(());
+    }
+
+    let path = sync_agent::install(home, interval_minutes)?;
+    println!(
+        "{}",
+        format!(
+            "✓ Sync watcher checks every {} minutes ({})",
+            interval_minutes,
+            path.display()
+        )
+        .green()
+    );
+    Ok(())
+}
+
+/// Returns one line per repo that needs a pull, push, or commit
+fn check_repos(repos: &[(String, PathBuf)]) -> Vec<String> {
+    repos
+        .iter()
+        .filter(|(_, path)| repos::is_git_repo(path))
+        .filter_map(|(name, path)| match repos::status(path) {
+            Ok(status) => describe_status(name, &status),
+            Err(e) => Some(format!("{}: could not check status ({})", name, e)),
+        })
+        .collect()
+}
+
+/// Summarizes what a repo needs, or None if it is in sync
+fn describe_status(name: &str, status: &RepoStatus) -> Option<String> {
+    let mut needs = Vec::new();
+    if status.behind > 0 {
+        needs.push(if name == "dotfiles" {
+            format!("{} upstream commits (run: dotfiles sync)", status.behind)
+        } else {
+            format!("{} upstream commits to pull", status.behind)
+        });
+    }
+    if status.changed > 0 {
+        needs.push(format!("{} uncommitted changes", status.changed));
+    }
+    if status.ahead > 0 {
+        needs.push(format!("{} unpushed commits", status.ahead));
+    }
+
+    (!needs.is_empty()).then(|| format!("{}: {}", name, needs.join(", ")))
+}
+
+/// Reloads Karabiner after a pull changed `karabiner.json`, unless the new file is broken
+fn reload_karabiner(content: &str) {
+    let problems = karabiner::structure_problems(content);
+    if !problems.is_empty() {
+        println!(
+            "  {}",
+            format!(
+                "⚠ Pulled karabiner.json will not load ({}); fix it before Karabiner restarts",
+                problems.join("; ")
+            )
+            .yellow()
+        );
+        return;
+    }
+    if !karabiner::cli_installed() {
+        return;
+    }
+    match karabiner::reload() {
+        Ok(()) => println!("  {}", "✓ Reloaded Karabiner".green()),
+        Err(e) => println!("  {}", format!("⚠ Karabiner reload failed: {}", e).yellow()),
+    }
+}
+
+fn pull(dotfiles_dir: &Path) -> Result<()> {
+    CommandRunner::new("git")
+        .arg("-C")
+        .arg(dotfiles_dir)
+        .args(["pull", "--ff-only"])
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Asks whether to push a changed copy-only file into the repo or pull the repo's version
+fn resolve_copy(
+    file: &CopiedFile,
+    status: CopyStatus,
+    state: &mut State,
+    home_dir: &Path,
+) -> Result<()> {
+    let name = display_path(&file.target, home_dir);
+    let choice = Select::new()
+        .with_prompt(format!("{} {} (Esc to skip)", name, status.describe()))
+        .items(&[
+            "Push: copy it into the dotfiles repo",
+            "Pull: replace it with the repo's version",
+            "Skip",
+        ])
+        .default(0)
+        .interact_opt()
+        .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+
+    match choice {
+        Some(0) => {
+            copies::push(file, state)?;
+            println!(
+                "  {}",
+                format!("✓ Copied {} into the repo; commit it", name).green()
+            );
+        }
+        Some(1) => {
+            copies::pull(file, state)?;
+            println!("  {}", format!("✓ Replaced {}", name).green());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Prints one line per copy-only file that was copied or needs a push or pull
+pub fn print_copy_outcomes(outcomes: &[(PathBuf, CopyStatus)], home_dir: &Path) {
+    for (target, status) in outcomes {
+        let name = display_path(target, home_dir);
+        match status {
+            CopyStatus::Missing | CopyStatus::RepoChanged => {
+                println!("  {}", format!("✓ Copied {}", name).green())
+            }
+            CopyStatus::Symlinked => {
+                println!(
+                    "  {}",
+                    format!("✓ Replaced the link at {} with a copy", name).green()
+                )
+            }
+            CopyStatus::InSync => {}
+            CopyStatus::LiveChanged | CopyStatus::Diverged => println!(
+                "  {}",
+                format!("⚠ Kept {} ({})", name, status.describe()).yellow()
+            ),
+        }
+    }
+}
+
+/// Prints one line per template that needs attention or was written
+pub fn print_outcomes(outcomes: &[(PathBuf, RenderOutcome)], home_dir: &Path) {
+    for (target, outcome) in outcomes {
+        let name = display_path(target, home_dir);
+        match outcome {
+            RenderOutcome::Written => println!("  {}", format!("✓ Rendered {}", name).green()),
+            RenderOutcome::Unchanged => {}
+            RenderOutcome::MissingVars(missing) => println!(
+                "  {}",
+                format!(
+                    "⚠ Skipped {} (no value for {}; run: dotfiles setup)",
+                    name,
+                    missing.join(", ")
+                )
+                .yellow()
+            ),
+            RenderOutcome::ModifiedLocally => println!(
+                "  {}",
+                format!("⚠ Skipped {} (edited since last render)", name).yellow()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_status() {
+        assert_eq!(describe_status("dotfiles", &RepoStatus::default()), None);
+
+        let status = RepoStatus {
+            behind: 2,
+            ahead: 1,
+            changed: 3,
+        };
+        assert_eq!(
+            describe_status("dotfiles", &status).unwrap(),
+            "dotfiles: 2 upstream commits (run: dotfiles sync), 3 uncommitted changes, 1 unpushed commits"
+        );
+        assert_eq!(
+            describe_status(
+                "~/.claude",
+                &RepoStatus {
+                    behind: 1,
+                    ..status
+                }
+            )
+            .unwrap(),
+            "~/.claude: 1 upstream commits to pull, 3 uncommitted changes, 1 unpushed commits"
+        );
+    }
+}
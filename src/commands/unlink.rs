@@ -0,0 +1,70 @@
+use crate::core::context::AppContext;
+use crate::error::Result;
+use crate::symlink::{self, manual::ManualSymlinker, stow::StowSymlinker, Symlinker};
+use colored::Colorize;
+
+/// Removes every link setup made from `$HOME` (or `link_root`) into the dotfiles repo
+///
+/// Covers the top-level links (through stow when it is installed), the
+/// per-file links in special directories like `.claude`, and `[targets]`.
+/// Links pointing anywhere else and regular files are left alone.
+pub fn run(context: &AppContext, dry_run: bool) -> Result<()> {
+    let (home, config) = (&context.home, context.machine_config()?);
+    let link_root = config.link_root(home);
+
+    let manual = if dry_run {
+        ManualSymlinker::dry_run()
+    } else {
+        ManualSymlinker::new()
+    }
+    .with_config_home(config.xdg_config_home.clone())
+    .skipping(config.skipped_links());
+    let stow = if dry_run {
+        StowSymlinker::dry_run()
+    } else {
+        StowSymlinker::new()
+    }
+    .with_config_home(config.xdg_config_home.clone());
+    let symlinker: &dyn Symlinker = if stow.is_available() { &stow } else { &manual };
+
+    let mut report = symlinker.remove(&config.dotfiles_dir, &link_root)?;
+    report.merge(symlink::remove_individual_files(
+        symlinker,
+        &config.dotfiles_dir,
+        &link_root,
+    )?);
+    report.merge(symlink::unlink_targets(
+        &manual,
+        &config.dotfiles_dir,
+        &link_root,
+        &config.linked_targets(),
+    )?);
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for target in &report.created {
+        println!("  {} {}", verb, target.display());
+    }
+    for (target, reason) in &report.conflicts {
+        println!(
+            "{}",
+            format!("  ⚠ {}: {}", target.display(), reason).yellow()
+        );
+    }
+
+    println!(
+        "{}",
+        format!(
+            "✓ {} {} link(s) ({}); left {} in place, skipped {}",
+            verb,
+            report.created.len(),
+            symlinker.name(),
+            report.conflicts.len(),
+            report.skipped.len()
+        )
+        .green()
+    );
+    if !dry_run && !report.created.is_empty() {
+        println!("  Re-create them with: {}", "dotfiles setup".cyan());
+    }
+    Ok(())
+}
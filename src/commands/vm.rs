@@ -0,0 +1,133 @@
+use crate::core::context::AppContext;
+use crate::core::exit::ExitStatus;
+use crate::error::{DotfilesError, Result};
+use crate::install::shell;
+use crate::install::version_manager::{self, VersionManager};
+use colored::Colorize;
+use std::fs;
+
+/// Switches to another version manager
+///
+/// Installs the new manager, reinstalls every runtime listed in
+/// `~/.tool-versions` with it, and swaps the activation hook in the managed
+/// block of `~/.zshrc`. With `remove_old`, the previous manager is uninstalled
+/// along with its installs and shims.
+///
+/// Exits with `errors` when any runtime fails to reinstall; the old manager is
+/// then kept even with `remove_old`, since it still has that runtime.
+pub fn switch(context: &AppContext, name: &str, remove_old: bool) -> Result<ExitStatus> {
+    let target = VersionManager::from_name(name).ok_or_else(|| {
+        DotfilesError::Config(format!(
+            "Unknown version manager: {} (expected mise, asdf, or rtx)",
+            name
+        ))
+    })?;
+
+    let home = &context.home;
+    // Saved as it is on disk; the profile only applies to the managed block
+    let mut config = context.config()?.clone();
+    let previous =
+        VersionManager::from_language_manager(config.language_manager).filter(|vm| *vm != target);
+
+    println!(
+        "{}",
+        format!("🔁 Switching to {}", target.display_name()).bold()
+    );
+    println!();
+
+    // Step 1: Install the new manager
+    version_manager::install(target)?;
+
+    // Step 2: Reinstall runtimes from .tool-versions (first version is global)
+    let tool_versions = home.join(".tool-versions");
+    let runtimes = fs::read_to_string(&tool_versions)
+        .map(|content| version_manager::parse_tool_versions(&content))
+        .unwrap_or_default();
+    let mut failed = Vec::new();
+    for (language, versions) in &runtimes {
+        let result = versions
+            .iter()
+            .try_for_each(|version| version_manager::install_version(target, language, version))
+            .and_then(|()| version_manager::set_global(target, language, &versions[0]));
+        match result {
+            Ok(()) => println!(
+                "{}",
+                format!("  ✓ {} {}", language, versions.join(", ")).green()
+            ),
+            Err(e) => {
+                println!("{}", format!("  ✗ {}: {}", language, e).red());
+                failed.push(language.as_str());
+            }
+        }
+    }
+    let status = if failed.is_empty() {
+        ExitStatus::Ok
+    } else {
+        ExitStatus::Errors
+    };
+
+    // Step 3: Record the switch and swap the hook in the managed block
+    config.language_manager = target.language_manager();
+    config.save(&context.config_path)?;
+
+    let zshrc = home.join(".zshrc");
+    let block = shell::managed_block_lines(&config.clone().for_machine(), home);
+    if shell::ensure_managed_block(&zshrc, &block)? {
+        println!(
+            "{}",
+            format!("  ✓ Activated {} in the managed block", target.command()).green()
+        );
+    }
+
+    let Some(previous) = previous else {
+        return Ok(status);
+    };
+
+    // Step 4: Hooks for the old manager outside the managed block are the user's
+    let content = fs::read_to_string(&zshrc).unwrap_or_default();
+    let lines = shell::find_hook_lines(&content, previous);
+    if !lines.is_empty() {
+        let numbers: Vec<String> = lines.iter().map(|n| n.to_string()).collect();
+        println!(
+            "{}",
+            format!(
+                "  ⚠ ~/.zshrc still activates {} on line {}; delete it so it cannot shadow {}",
+                previous.command(),
+                numbers.join(", "),
+                target.command()
+            )
+            .yellow()
+        );
+    }
+
+    // Step 5: Uninstall the old manager, or say how to
+    if remove_old && !failed.is_empty() {
+        println!(
+            "{}",
+            format!(
+                "  ⚠ Kept {}: {} did not reinstall with {}",
+                previous.display_name(),
+                failed.join(", "),
+                target.command()
+            )
+            .yellow()
+        );
+    } else if remove_old {
+        version_manager::uninstall(previous, home)?;
+        println!(
+            "{}",
+            format!("  ✓ Uninstalled {}", previous.display_name()).green()
+        );
+    } else if previous.data_dir(home).exists() {
+        println!(
+            "  Run: {} to remove {} and its shims",
+            format!("dotfiles vm switch {} --remove-old", target.command()).cyan(),
+            previous.data_dir(home).display()
+        );
+    }
+
+    println!();
+    println!("Restart your shell, then run: {}", "dotfiles doctor".cyan());
+
+    Ok(status)
+}
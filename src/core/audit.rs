@@ -0,0 +1,286 @@
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A change made to the machine, as recorded in the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    /// First entry of a run, written before its first change
+    RunStarted {
+        command: String,
+    },
+    /// Last entry of a run, written only if the run changed something
+    RunFinished {
+        status: String,
+    },
+    LinkCreated {
+        link: PathBuf,
+        target: PathBuf,
+    },
+    LinkRemoved {
+        link: PathBuf,
+    },
+    /// A stow package linked into a target directory
+    Stowed {
+        package: PathBuf,
+        target: PathBuf,
+    },
+    FileMoved {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    FileCopied {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// A file written or edited in place (rc files, templates, agents)
+    FileWritten {
+        path: PathBuf,
+        reason: String,
+    },
+    PackageInstalled {
+        package: String,
+        manager: String,
+    },
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub run_id: String,
+    /// RFC 3339 local time
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// Appends the current run's actions to a JSON-lines file
+///
+/// Nothing is written for runs that change nothing: the `run_started`
+/// entry goes out with the first real action.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    run_id: String,
+    command: String,
+    started: bool,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf, command: impl Into<String>) -> Self {
+        let run_id = format!(
+            "{}-{:04x}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            std::process::id() & 0xffff
+        );
+        Self {
+            path,
+            run_id,
+            command: command.into(),
+            started: false,
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Appends an action, preceded by `run_started` if this is the run's first
+    pub fn record(&mut self, action: Action) -> Result<()> {
+        if !self.started {
+            self.started = true;
+            self.append(Action::RunStarted {
+                command: self.command.clone(),
+            })?;
+        }
+        self.append(action)
+    }
+
+    /// Closes the run with its exit status, if anything was recorded
+    pub fn finish(&mut self, status: &str) -> Result<()> {
+        if self.started {
+            self.append(Action::RunFinished {
+                status: status.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn append(&self, action: Action) -> Result<()> {
+        let entry = Entry {
+            run_id: self.run_id.clone(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+            action,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+}
+
+/// The log for this process, set up by `start`
+static LOG: Mutex<Option<AuditLog>> = Mutex::new(None);
+
+/// Returns the audit log path, next to the state file
+pub fn log_path(home_dir: &Path) -> PathBuf {
+    crate::core::state::state_path(home_dir).with_file_name("audit.jsonl")
+}
+
+/// Starts recording this process's actions under a new run ID
+pub fn start(home_dir: &Path, command: &str) {
+    *lock() = Some(AuditLog::new(log_path(home_dir), command));
+}
+
+/// Records an action for the current run; a no-op until `start` is called
+///
+/// Write failures are ignored: a full disk shouldn't fail the change itself.
+pub fn record(action: Action) {
+    if let Some(log) = lock().as_mut() {
+        let _ = log.record(action);
+    }
+}
+
+/// Writes `run_finished` for the current run if it recorded anything
+pub fn finish(status: &str) {
+    if let Some(log) = lock().as_mut() {
+        let _ = log.finish(status);
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<AuditLog>> {
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads every parseable entry from the audit log, oldest first
+pub fn load(path: &Path) -> Result<Vec<Entry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Returns every path this tool moved, copied, or wrote, per the audit log
+pub fn written_paths(entries: &[Entry]) -> Vec<PathBuf> {
+    entries
+        .iter()
+        .filter_map(|entry| match &entry.action {
+            Action::FileMoved { to, .. } | Action::FileCopied { to, .. } => Some(to.clone()),
+            Action::FileWritten { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The entries of one run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub id: String,
+    pub command: String,
+    pub started: String,
+    /// Exit status, or None if the run never finished (crashed or killed)
+    pub status: Option<String>,
+    /// Recorded changes, without the start and finish markers
+    pub actions: Vec<Entry>,
+}
+
+/// Groups entries into runs, in the order the runs started
+pub fn group_runs(entries: Vec<Entry>) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+    for entry in entries {
+        let index = match runs.iter().position(|run| run.id == entry.run_id) {
+            Some(index) => index,
+            None => {
+                runs.push(Run {
+                    id: entry.run_id.clone(),
+                    command: String::new(),
+                    started: entry.timestamp.clone(),
+                    status: None,
+                    actions: Vec::new(),
+                });
+                runs.len() - 1
+            }
+        };
+        let run = &mut runs[index];
+        match entry.action {
+            Action::RunStarted { command } => run.command = command,
+            Action::RunFinished { status } => run.status = Some(status),
+            _ => run.actions.push(entry),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_log_writes_only_when_something_changed() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state/audit.jsonl");
+
+        let mut idle = AuditLog::new(path.clone(), "doctor");
+        idle.finish("ok").unwrap();
+        assert!(!path.exists());
+
+        let mut log = AuditLog::new(path.clone(), "setup --yes");
+        log.record(Action::LinkCreated {
+            link: PathBuf::from("/home/me/.zshrc"),
+            target: PathBuf::from("/home/me/dotfiles/.zshrc"),
+        })
+        .unwrap();
+        log.record(Action::PackageInstalled {
+            package: "stow".to_string(),
+            manager: "brew".to_string(),
+        })
+        .unwrap();
+        log.finish("warnings").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 4);
+        assert!(content.contains(r#""action":"link_created""#));
+
+        let runs = group_runs(load(&path).unwrap());
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].id, log.run_id());
+        assert_eq!(runs[0].command, "setup --yes");
+        assert_eq!(runs[0].status.as_deref(), Some("warnings"));
+        assert_eq!(runs[0].actions.len(), 2);
+    }
+
+    #[test]
+    fn test_load_skips_bad_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("audit.jsonl");
+        std::fs::write(
+            &path,
+            "not json\n{\"run_id\":\"a\",\"timestamp\":\"t\",\"action\":\"link_removed\",\"link\":\"/x\"}\n",
+        )
+        .unwrap();
+
+        let runs = group_runs(load(&path).unwrap());
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, None);
+        assert_eq!(
+            runs[0].actions[0].action,
+            Action::LinkRemoved {
+                link: PathBuf::from("/x")
+            }
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::error::{DotfilesError, Result};
+use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set by the Ctrl-C handler; checked by running commands and worker pools
+pub static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the Ctrl-C handler
+///
+/// The first Ctrl-C asks running work to stop: child processes are killed,
+/// queued jobs are skipped, and setup stops after the current step. A second
+/// Ctrl-C exits immediately.
+pub fn install_handler() {
+    let _ = ctrlc::set_handler(|| {
+        if CANCELLED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        eprintln!(
+            "\n{}",
+            "Cancelling... (press Ctrl-C again to quit immediately)".yellow()
+        );
+    });
+}
+
+/// Returns true once the user has pressed Ctrl-C
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Fails with `Cancelled` once the user has pressed Ctrl-C
+pub fn check(what: &str) -> Result<()> {
+    if is_cancelled() {
+        return Err(DotfilesError::Cancelled(what.to_string()));
+    }
+    Ok(())
+}
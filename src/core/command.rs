@@ -0,0 +1,348 @@
+use crate::core::cancel;
+use crate::error::{DotfilesError, Result};
+use std::ffi::{OsStr, OsString};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Timeout for quick local queries (e.g. `brew list <pkg>`)
+pub const QUICK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Timeout for network operations such as git clones
+pub const NETWORK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Timeout for package and runtime installs, which may compile from source
+pub const INSTALL_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often a running child is polled for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables live streaming of external command output
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Returns true if external command output should be streamed
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Captured result of an external command
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// Returns true if the command exited successfully
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+}
+
+/// Runs external commands with a timeout, optional live output, and captured stderr
+#[derive(Debug, Clone)]
+pub struct CommandRunner {
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    current_dir: Option<PathBuf>,
+    timeout: Option<Duration>,
+    stream: bool,
+    input: Option<Input>,
+}
+
+/// Bytes written to the child's stdin, kept out of `Debug` output since they may be secret
+#[derive(Clone)]
+struct Input(Vec<u8>);
+
+impl std::fmt::Debug for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} bytes>", self.0.len())
+    }
+}
+
+impl CommandRunner {
+    /// Creates a runner for the given program, streaming when verbose mode is on
+    pub fn new(program: impl AsRef<OsStr>) -> Self {
+        Self {
+            program: program.as_ref().to_os_string(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            current_dir: None,
+            timeout: None,
+            stream: is_verbose(),
+            input: None,
+        }
+    }
+
+    /// Adds an argument
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    /// Adds several arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+
+    /// Sets an environment variable for the child
+    pub fn env(mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> Self {
+        self.envs
+            .push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+        self
+    }
+
+    /// Sets the working directory for the child
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Kills the child if it runs longer than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Overrides whether output is streamed line by line as it arrives
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Writes `input` to the child's stdin instead of leaving it empty
+    ///
+    /// Also the way to pass values that must not show up in `ps` or in `display()`.
+    pub fn stdin(mut self, input: impl Into<Vec<u8>>) -> Self {
+        self.input = Some(Input(input.into()));
+        self
+    }
+
+    /// Returns a printable form of the command line
+    pub fn display(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(self.args.iter())
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Runs the command and returns its output regardless of exit status
+    ///
+    /// Errors only if the command cannot be started, exceeds its timeout, or
+    /// is cancelled with Ctrl-C (the child is killed).
+    pub fn output(&self) -> Result<CommandOutput> {
+        cancel::check(&self.display())?;
+
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(if self.input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        for (key, value) in &self.envs {
+            command.env(key, value);
+        }
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| DotfilesError::CommandFailed(format!("{}: {}", self.display(), e)))?;
+
+        // Written on its own thread so a child that doesn't read stdin can't block us
+        if let (Some(mut pipe), Some(Input(input))) = (child.stdin.take(), self.input.clone()) {
+            thread::spawn(move || {
+                let _ = pipe.write_all(&input);
+            });
+        }
+
+        let stdout = child
+            .stdout
+            .take()
+            .map(|s| collect_lines(s, self.stream, false));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|s| collect_lines(s, self.stream, true));
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if cancel::is_cancelled() {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(DotfilesError::Cancelled(self.display()));
+            }
+            if let Some(timeout) = self.timeout {
+                if started.elapsed() >= timeout {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(DotfilesError::CommandTimeout(format!(
+                        "{} (after {}s)",
+                        self.display(),
+                        timeout.as_secs()
+                    )));
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        let stdout = stdout
+            .map(|h| h.join().unwrap_or_default())
+            .unwrap_or_default();
+        let stderr = stderr
+            .map(|h| h.join().unwrap_or_default())
+            .unwrap_or_default();
+
+        Ok(CommandOutput {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+
+    /// Runs the command, failing with captured stderr if it exits unsuccessfully
+    pub fn run(&self) -> Result<CommandOutput> {
+        let output = self.output()?;
+
+        if !output.success() {
+            let stderr = output.stderr.trim();
+            let detail = if stderr.is_empty() {
+                output.status.to_string()
+            } else {
+                stderr.to_string()
+            };
+            return Err(DotfilesError::CommandFailed(format!(
+                "{}: {}",
+                self.display(),
+                detail
+            )));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Reads a child stream on a background thread, optionally echoing each line
+fn collect_lines<R: Read + Send + 'static>(
+    stream: R,
+    echo: bool,
+    is_stderr: bool,
+) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut collected = String::new();
+        for line in BufReader::new(stream).lines().map_while(|l| l.ok()) {
+            if echo {
+                if is_stderr {
+                    eprintln!("    {}", line);
+                } else {
+                    println!("    {}", line);
+                }
+            }
+            collected.push_str(&line);
+            collected.push('\n');
+        }
+        collected
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_captures_stdout_and_stderr() {
+        let output = CommandRunner::new("sh")
+            .args(["-c", "echo out; echo err >&2"])
+            .stream(false)
+            .output()
+            .unwrap();
+
+        assert!(output.success());
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+    }
+
+    #[test]
+    fn test_stdin_is_written_to_child() {
+        let runner = CommandRunner::new("cat").stdin("hunter2").stream(false);
+        assert_eq!(runner.output().unwrap().stdout, "hunter2\n");
+        assert!(!runner.display().contains("hunter2"));
+        assert!(!format!("{:?}", runner).contains("hunter2"));
+    }
+
+    #[test]
+    fn test_run_attaches_stderr_to_error() {
+        let result = CommandRunner::new("sh")
+            .args(["-c", "echo boom >&2; exit 3"])
+            .stream(false)
+            .run();
+
+        match result {
+            Err(DotfilesError::CommandFailed(msg)) => assert!(msg.contains("boom")),
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_timeout_kills_child() {
+        let started = Instant::now();
+        let result = CommandRunner::new("sleep")
+            .arg("5")
+            .timeout(Duration::from_millis(200))
+            .stream(false)
+            .output();
+
+        assert!(matches!(result, Err(DotfilesError::CommandTimeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_missing_program() {
+        let result = CommandRunner::new("definitely-not-a-real-program-xyz").output();
+        assert!(matches!(result, Err(DotfilesError::CommandFailed(_))));
+    }
+
+    #[test]
+    fn test_env_and_current_dir() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let output = CommandRunner::new("sh")
+            .args(["-c", "echo $DOTFILES_TEST_VAR; pwd"])
+            .env("DOTFILES_TEST_VAR", "hello")
+            .current_dir(temp.path())
+            .stream(false)
+            .run()
+            .unwrap();
+
+        assert!(output.stdout.starts_with("hello\n"));
+    }
+
+    #[test]
+    fn test_display() {
+        let runner = CommandRunner::new("brew").args(["install", "stow"]);
+        assert_eq!(runner.display(), "brew install stow");
+    }
+}
@@ -1,17 +1,142 @@
-use crate::error::Result;
+use crate::core::paths;
+use crate::error::{DotfilesError, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub dotfiles_dir: PathBuf,
+    /// Branch the dotfiles repo should have checked out (defaults to the remote's default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dotfiles_branch: Option<String>,
     pub xdg_config_home: PathBuf,
+    /// Directory links are created in instead of `$HOME`, e.g. a prefix on a shared server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_root: Option<PathBuf>,
+    /// Skip every step that needs sudo or changes the system (packages, Homebrew, [machine])
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub userspace_only: bool,
     pub language_manager: LanguageManager,
     pub symlink_method: SymlinkMethod,
     pub install_oh_my_zsh: bool,
+    /// Add `dfs` (sync) and `dfe <name>` (edit) shortcuts to the managed shell block
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shell_aliases: bool,
+    /// Profile this machine uses, set by `setup --profile`; otherwise matched by hostname
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Named overrides for groups of machines (e.g. `[profiles.work]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Tags describing this machine (e.g. "laptop", "work"), matched by `when` filters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Language runtimes to install, keyed by language name (e.g. "java", "nodejs")
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageVersions>,
+    /// Version manager plugins to add, mapping plugin name to source URL
+    #[serde(default)]
+    pub plugins: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub homebrew: HomebrewSettings,
+
+    #[serde(default)]
+    pub packages: PackageSettings,
+
+    #[serde(default)]
+    pub obsidian: ObsidianSettings,
+
+    #[serde(default)]
+    pub kubernetes: KubernetesSettings,
+
+    #[serde(default)]
+    pub git: GitSettings,
+
+    #[serde(default, skip_serializing_if = "NvimSettings::is_empty")]
+    pub nvim: NvimSettings,
+
+    #[serde(default, skip_serializing_if = "MachineSettings::is_empty")]
+    pub machine: MachineSettings,
+
+    #[serde(default, skip_serializing_if = "NotifySettings::is_empty")]
+    pub notify: NotifySettings,
+
+    #[serde(default, skip_serializing_if = "BackupSettings::is_empty")]
+    pub backups: BackupSettings,
+
+    #[serde(default, skip_serializing_if = "SecretSettings::is_empty")]
+    pub secrets: SecretSettings,
+
+    /// Links doctor requires, and whether a missing one is an error or a warning
+    #[serde(
+        default = "default_critical_symlinks",
+        skip_serializing_if = "is_default_critical_symlinks"
+    )]
+    pub critical_symlinks: Vec<CriticalSymlink>,
+
+    /// Explicit link destinations, keyed by path relative to `dotfiles_dir`
+    ///
+    /// Values may start with `~`. Used for files that belong outside `$HOME`
+    /// and XDG, such as VS Code settings in `~/Library/Application Support`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub links: LinkSettings,
+
+    #[serde(default, skip_serializing_if = "HookSettings::is_empty")]
+    pub hooks: HookSettings,
+
+    /// Per-path settings, keyed like `[links.when]`: a top-level entry or a `[targets]` key
+    ///
+    /// `mode = "copy"` copies a file instead of linking it, for apps that
+    /// replace their config atomically and so break symlinks.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, FileSettings>,
+
+    /// LaunchAgent labels managed by the tool, checked for drift by doctor
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub launch_agents: Vec<String>,
+
+    /// When `dotfiles setup` last completed (written by setup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_setup: Option<String>,
+}
+
+/// Versions of a single language runtime to install
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct LanguageVersions {
+    /// All versions to install
+    pub versions: Vec<String>,
+    /// Version to set as the global default (defaults to the last entry)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<String>,
+    /// Tag filter limiting which machines install this language (see [`tags_match`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+}
+
+impl LanguageVersions {
+    /// Creates a version set with a single version
+    pub fn single(version: impl Into<String>) -> Self {
+        Self {
+            versions: vec![version.into()],
+            global: None,
+            when: None,
+        }
+    }
+
+    /// Returns the version that should be set as global
+    pub fn global_version(&self) -> Option<&str> {
+        self.global
+            .as_deref()
+            .or_else(|| self.versions.last().map(|v| v.as_str()))
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum LanguageManager {
     Asdf,
     Mise,
@@ -19,17 +144,814 @@ pub enum LanguageManager {
     None,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum SymlinkMethod {
     Stow,
     Manual,
 }
 
+/// Homebrew behaviour exported through the managed shell block, and how it is installed
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HomebrewSettings {
+    /// Export HOMEBREW_NO_ANALYTICS=1
+    #[serde(default)]
+    pub no_analytics: bool,
+
+    /// Export HOMEBREW_NO_AUTO_UPDATE=1
+    #[serde(default)]
+    pub no_auto_update: bool,
+
+    /// Install script to download instead of the latest one (e.g. pinned to a commit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_script_url: Option<String>,
+
+    /// Expected SHA-256 of the install script; setup won't run a script that differs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_script_sha256: Option<String>,
+}
+
+impl HomebrewSettings {
+    /// Returns the environment variables implied by these settings
+    pub fn env_vars(&self) -> Vec<(&'static str, &'static str)> {
+        let mut vars = Vec::new();
+        if self.no_analytics {
+            vars.push(("HOMEBREW_NO_ANALYTICS", "1"));
+        }
+        if self.no_auto_update {
+            vars.push(("HOMEBREW_NO_AUTO_UPDATE", "1"));
+        }
+        vars
+    }
+}
+
+/// Per-package overrides for the built-in package groups
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PackageSettings {
+    /// Packages never installed or checked, even when part of a group
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skip: Vec<String>,
+
+    /// Packages chosen in setup's picker, installed along with the essentials
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selected: Vec<String>,
+
+    /// Packages never upgraded (pinned in Homebrew)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned: Vec<String>,
+
+    /// Tag filters for packages that only belong on some machines
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub when: BTreeMap<String, String>,
+
+    /// Where packages come from instead of the system package manager
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<PackageSource>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageSource {
+    /// Release binaries from GitHub in `~/.local/bin`, for machines where Homebrew is not allowed
+    Prebuilt,
+}
+
+impl PackageSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if the package should be left alone entirely
+    pub fn is_skipped(&self, package: &str) -> bool {
+        self.skip.iter().any(|p| p == package)
+    }
+
+    /// Returns true if the package must not be upgraded
+    pub fn is_pinned(&self, package: &str) -> bool {
+        self.pinned.iter().any(|p| p == package)
+    }
+}
+
+/// Overrides for the machines using a profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Profile {
+    /// Hostnames that select this profile when `profile` is not set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    /// Dotfiles directory used instead of the top-level one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dotfiles_dir: Option<PathBuf>,
+    /// Package lists replacing the top-level ones; empty lists keep those
+    #[serde(default, skip_serializing_if = "PackageSettings::is_empty")]
+    pub packages: PackageSettings,
+    /// Top-level repo entries not linked on this profile's machines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Repo entries that are never linked, and tag filters for those that only
+/// belong on some machines
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkSettings {
+    /// Top-level entries not to link on any machine
+    #[serde(
+        default = "default_link_skip",
+        skip_serializing_if = "is_default_link_skip"
+    )]
+    pub skip: Vec<String>,
+
+    /// Filters keyed by repo-relative path: a top-level entry or a `[targets]` key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub when: BTreeMap<String, String>,
+}
+
+impl Default for LinkSettings {
+    fn default() -> Self {
+        Self {
+            skip: default_link_skip(),
+            when: BTreeMap::new(),
+        }
+    }
+}
+
+/// Entries left unlinked when `[links] skip` is not configured
+///
+/// `obsidian` holds the vault config that setup links into `<vault>/.obsidian`.
+pub fn default_link_skip() -> Vec<String> {
+    vec!["obsidian".to_string()]
+}
+
+fn is_default_link_skip(skip: &[String]) -> bool {
+    skip == default_link_skip().as_slice()
+}
+
+/// Tag filters for repo hook scripts that only belong on some machines
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HookSettings {
+    /// Filters keyed by `<point>.d/<script>`, e.g. `post-setup.d/10-mas.sh`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub when: BTreeMap<String, String>,
+}
+
+impl HookSettings {
+    pub fn is_empty(&self) -> bool {
+        self.when.is_empty()
+    }
+}
+
+/// How a repo file is placed on the machine
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileSettings {
+    #[serde(default)]
+    pub mode: FileMode,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileMode {
+    /// Linked into place (the default)
+    #[default]
+    Symlink,
+    /// Copied into place, with divergence detected by `sync` and doctor
+    Copy,
+}
+
+/// Returns true if a machine with `tags` satisfies a `when` filter
+///
+/// Filters are comma-separated tags: the machine needs any one of the plain
+/// tags (if there are any) and none of the `!`-prefixed ones, so `"work"`,
+/// `"laptop, work"`, and `"!server"` are all valid.
+pub fn tags_match(filter: &str, tags: &[String]) -> bool {
+    let has = |tag: &str| tags.iter().any(|t| t == tag);
+    let terms: Vec<&str> = filter
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let (excluded, wanted): (Vec<&str>, Vec<&str>) =
+        terms.iter().partition(|term| term.starts_with('!'));
+    let wanted_ok = wanted.is_empty() || wanted.iter().any(|tag| has(tag));
+    let excluded_ok = excluded.iter().all(|tag| !has(&tag[1..]));
+    wanted_ok && excluded_ok
+}
+
+/// Obsidian vault configured during setup
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ObsidianSettings {
+    /// Vault directory; set as the obsidian-cli default and given the repo's `.obsidian` config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<PathBuf>,
+}
+
+/// The nvim config setup installs when the repo doesn't ship one
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NvimSettings {
+    /// Starter config cloned into `~/.config/nvim` and kept updated by `sync`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distro: Option<NvimDistro>,
+
+    /// Repository to clone for `distro = "own"`; without one, the repo's `.config/nvim` is linked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl NvimSettings {
+    /// Returns true if no distro was chosen
+    pub fn is_empty(&self) -> bool {
+        self.distro.is_none() && self.url.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NvimDistro {
+    /// The LazyVim starter
+    LazyVim,
+    /// kickstart.nvim
+    Kickstart,
+    /// The user's own config, from `url` or the dotfiles repo
+    Own,
+}
+
+impl NvimDistro {
+    /// Returns the name shown in setup output
+    pub fn display_name(&self) -> &str {
+        match self {
+            NvimDistro::LazyVim => "LazyVim",
+            NvimDistro::Kickstart => "kickstart.nvim",
+            NvimDistro::Own => "own config",
+        }
+    }
+}
+
+/// Opt-in Kubernetes tooling
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KubernetesSettings {
+    /// Install the kubernetes package group and link kubeconfig snippets
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// krew plugins to install (e.g. "ctx", "ns")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub krew_plugins: Vec<String>,
+}
+
+/// Hostname, timezone, and locale this machine should have
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MachineSettings {
+    /// Computer name (e.g. "Work Laptop"); the network hostname is derived from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    /// IANA timezone (e.g. "Europe/Berlin")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+
+    /// Locale (e.g. "en_US.UTF-8")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+impl MachineSettings {
+    /// Returns true if nothing is configured
+    pub fn is_empty(&self) -> bool {
+        self.hostname.is_none() && self.timezone.is_none() && self.locale.is_none()
+    }
+}
+
+/// Where to report failures of commands run unattended (launchd, cron, systemd)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotifySettings {
+    /// Show a desktop notification
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub desktop: bool,
+
+    /// URL to POST a JSON message to (Slack-compatible `text` field)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+
+    /// Address to email via the local `sendmail`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl NotifySettings {
+    /// Returns true if no sink is configured
+    pub fn is_empty(&self) -> bool {
+        !self.desktop && self.webhook.is_none() && self.email.is_none()
+    }
+}
+
+/// Where backups are kept and how their directories are named
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BackupSettings {
+    /// Directory holding backups (default: `~/.local/state/dotfiles/backups`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+
+    /// strftime-style name for each backup (default: `dotfiles-%Y%m%d-%H%M%S`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Total size, in MB, above which doctor suggests pruning (default: 1024)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<u64>,
+
+    /// Number of backups above which doctor suggests pruning (default: 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_count: Option<usize>,
+
+    /// Whether new backups are directories, incremental directories, or archives (default: directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<BackupFormat>,
+
+    /// age public key (`age1...`) encrypted backups are written to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_recipient: Option<String>,
+
+    /// age identity file that decrypts them (default: `~/.config/age/key.txt`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub age_identity: Option<PathBuf>,
+
+    /// Git repository or rsync destination `backup --push` uploads to (see `Remote::parse`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BackupFormat {
+    /// A plain copy of the directory tree (the default)
+    #[default]
+    #[serde(rename = "directory")]
+    Directory,
+    /// One gzip-compressed tarball per backup
+    #[serde(rename = "tar.gz")]
+    Archive,
+    /// A tarball encrypted to `age_recipient`
+    #[serde(rename = "tar.gz.age")]
+    EncryptedArchive,
+    /// A directory tree with a manifest; unchanged files are hard-linked from the previous one
+    #[serde(rename = "incremental")]
+    Incremental,
+}
+
+impl BackupSettings {
+    /// Returns true if every setting is left at its default
+    pub fn is_empty(&self) -> bool {
+        self.dir.is_none()
+            && self.name.is_none()
+            && self.max_size_mb.is_none()
+            && self.max_count.is_none()
+            && self.format.is_none()
+            && self.age_recipient.is_none()
+            && self.age_identity.is_none()
+            && self.remote.is_none()
+    }
+}
+
+/// How `migrate` finds the secrets it extracts, and where it keeps them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SecretSettings {
+    /// Store used instead of a git-ignored `.env` in the repo (default: env)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<SecretStoreKind>,
+    /// 1Password vault the `1password` store writes to (default: Private)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+    /// Bits of entropy per character above which a long string is flagged (default: 4.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entropy_threshold: Option<f64>,
+    /// Strings shorter than this are never flagged for entropy (default: 20)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretStoreKind {
+    /// `NAME=value` lines in a git-ignored `.env` in the dotfiles repo
+    Env,
+    /// The macOS login keychain, read back by a committed `secrets.sh`
+    Keychain,
+    /// A 1Password vault, read back with `op read` by a committed `secrets.sh`
+    #[serde(rename = "1password")]
+    OnePassword,
+}
+
+impl SecretStoreKind {
+    /// Parses a `--secret-store` value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "env" => Some(SecretStoreKind::Env),
+            "keychain" => Some(SecretStoreKind::Keychain),
+            "1password" => Some(SecretStoreKind::OnePassword),
+            _ => None,
+        }
+    }
+}
+
+impl SecretSettings {
+    /// Returns true if every setting is left at its default
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns the 1Password vault secrets go to
+    pub fn vault(&self) -> &str {
+        self.vault.as_deref().unwrap_or("Private")
+    }
+}
+
+/// A link doctor requires, relative to both home and the dotfiles repo
+///
+/// Paths under `.config` resolve against `xdg_config_home`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CriticalSymlink {
+    pub path: String,
+    #[serde(default)]
+    pub severity: LinkSeverity,
+}
+
+impl CriticalSymlink {
+    /// Creates an entry reported as an error when broken
+    pub fn error(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            severity: LinkSeverity::Error,
+        }
+    }
+}
+
+/// How doctor reports a broken critical symlink
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkSeverity {
+    #[default]
+    Error,
+    Warning,
+}
+
+/// Links checked by doctor when `critical_symlinks` is not configured
+pub fn default_critical_symlinks() -> Vec<CriticalSymlink> {
+    [
+        ".config",
+        ".zshrc",
+        ".gitconfig",
+        ".tmux.conf",
+        ".tmux",
+        ".zsh",
+    ]
+    .into_iter()
+    .map(CriticalSymlink::error)
+    .collect()
+}
+
+fn is_default_critical_symlinks(links: &[CriticalSymlink]) -> bool {
+    links == default_critical_symlinks().as_slice()
+}
+
+/// Machine-local git pager, difftool, and aliases
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct GitSettings {
+    /// Install git-delta and use it as pager and interactive diff filter
+    #[serde(default)]
+    pub delta: bool,
+
+    /// `diff.tool` to configure (e.g. "difftastic", "nvimdiff")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difftool: Option<String>,
+
+    /// Git aliases, mapping alias name to command
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+}
+
+/// How serious a configuration problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemSeverity {
+    /// Setup would misbehave; must be fixed
+    Error,
+    /// Probably a mistake, but setup can continue
+    Warning,
+}
+
+/// A semantic problem found in an otherwise well-formed configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Config key the problem relates to (e.g. `dotfiles_dir`, `languages.java`)
+    pub field: String,
+    pub severity: ProblemSeverity,
+    pub message: String,
+    /// How to fix it
+    pub suggestion: String,
+}
+
+impl ConfigProblem {
+    fn error(
+        field: impl Into<String>,
+        message: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            severity: ProblemSeverity::Error,
+            message: message.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+
+    fn warning(
+        field: impl Into<String>,
+        message: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            severity: ProblemSeverity::Warning,
+            message: message.into(),
+            suggestion: suggestion.into(),
+        }
+    }
+
+    /// Returns true if this problem must be fixed before setup
+    pub fn is_error(&self) -> bool {
+        self.severity == ProblemSeverity::Error
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let home = crate::core::paths::home_dir().unwrap_or_default();
+        Self {
+            dotfiles_dir: home.join("dotfiles"),
+            dotfiles_branch: None,
+            xdg_config_home: home.join(".config"),
+            link_root: None,
+            userspace_only: false,
+            language_manager: LanguageManager::Mise,
+            symlink_method: SymlinkMethod::Stow,
+            install_oh_my_zsh: false,
+            shell_aliases: false,
+            profile: None,
+            profiles: BTreeMap::new(),
+            tags: Vec::new(),
+            languages: BTreeMap::new(),
+            plugins: BTreeMap::new(),
+            homebrew: HomebrewSettings::default(),
+            packages: PackageSettings::default(),
+            obsidian: ObsidianSettings::default(),
+            kubernetes: KubernetesSettings::default(),
+            git: GitSettings::default(),
+            nvim: NvimSettings::default(),
+            machine: MachineSettings::default(),
+            notify: NotifySettings::default(),
+            backups: BackupSettings::default(),
+            secrets: SecretSettings::default(),
+            critical_symlinks: default_critical_symlinks(),
+            targets: BTreeMap::new(),
+            links: LinkSettings::default(),
+            hooks: HookSettings::default(),
+            files: BTreeMap::new(),
+            launch_agents: Vec::new(),
+            last_setup: None,
+        }
+    }
+}
+
 impl Config {
+    /// Returns top-level repo entries not to link: listed in `[links] skip`,
+    /// filtered out by `[links.when]`, excluded by the profile, or copy-only
+    pub fn skipped_links(&self) -> Vec<String> {
+        let listed = self.links.skip.iter().cloned();
+        let filtered = self
+            .links
+            .when
+            .iter()
+            .filter(|(path, filter)| !path.contains('/') && !tags_match(filter, &self.tags))
+            .map(|(path, _)| path.clone());
+        let copied = self
+            .copied_files()
+            .into_iter()
+            .filter(|path| !path.contains('/'));
+
+        let mut skipped: Vec<String> = listed
+            .chain(filtered)
+            .chain(self.profile_excludes())
+            .chain(copied)
+            .collect();
+        skipped.sort();
+        skipped.dedup();
+        skipped
+    }
+
+    /// Returns the `<point>.d/<script>` hooks filtered out by `[hooks.when]`
+    pub fn skipped_hooks(&self) -> Vec<String> {
+        self.hooks
+            .when
+            .iter()
+            .filter(|(_, filter)| !tags_match(filter, &self.tags))
+            .map(|(hook, _)| hook.clone())
+            .collect()
+    }
+
+    /// Returns the name of the profile this machine uses, if any
+    ///
+    /// `profile` wins; otherwise the first profile (by name) listing this
+    /// machine's hostname in `hosts`.
+    pub fn active_profile(&self) -> Option<String> {
+        if let Some(name) = &self.profile {
+            return Some(name.clone());
+        }
+        if self
+            .profiles
+            .values()
+            .all(|profile| profile.hosts.is_empty())
+        {
+            return None;
+        }
+        let os = crate::detect::os::detect_os();
+        let hostname =
+            crate::install::machine::current(crate::install::machine::Setting::Hostname, os)?;
+        self.profile_for_host(&hostname).map(str::to_string)
+    }
+
+    /// Returns the profile whose `hosts` include `hostname`, ignoring case
+    ///
+    /// Names are compared in their network form, so a computer name such as
+    /// "Jo's Laptop" matches "Jos-Laptop".
+    fn profile_for_host(&self, hostname: &str) -> Option<&str> {
+        let network_name = crate::install::machine::local_host_name;
+        let hostname = network_name(hostname);
+        self.profiles
+            .iter()
+            .find(|(_, profile)| {
+                profile
+                    .hosts
+                    .iter()
+                    .any(|host| network_name(host).eq_ignore_ascii_case(&hostname))
+            })
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns this config with a profile's overrides applied, recording it as `profile`
+    fn with_profile(mut self, name: Option<String>) -> Self {
+        let Some(profile) = name
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .cloned()
+        else {
+            return self;
+        };
+
+        if let Some(dir) = profile.dotfiles_dir {
+            self.dotfiles_dir = dir;
+        }
+        let packages = &mut self.packages;
+        for (list, replacement) in [
+            (&mut packages.skip, profile.packages.skip),
+            (&mut packages.selected, profile.packages.selected),
+            (&mut packages.pinned, profile.packages.pinned),
+        ] {
+            if !replacement.is_empty() {
+                *list = replacement;
+            }
+        }
+        packages.when.extend(profile.packages.when);
+        if profile.packages.backend.is_some() {
+            packages.backend = profile.packages.backend;
+        }
+        self.profile = name;
+
+        self
+    }
+
+    /// Returns the top-level entries the active `profile` excludes
+    fn profile_excludes(&self) -> Vec<String> {
+        self.profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .map(|profile| profile.exclude.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the repo paths marked `mode = "copy"` in `[files]`
+    pub fn copied_files(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .filter(|(_, settings)| settings.mode == FileMode::Copy)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Returns the `[targets]` entries that are linked rather than copied
+    pub fn linked_targets(&self) -> BTreeMap<String, String> {
+        self.targets
+            .iter()
+            .filter(|(source, _)| {
+                self.files
+                    .get(*source)
+                    .is_none_or(|settings| settings.mode == FileMode::Symlink)
+            })
+            .map(|(source, target)| (source.clone(), target.clone()))
+            .collect()
+    }
+
+    /// Returns this config as it applies to this machine: the active profile's
+    /// overrides applied, and everything not meant for its tags removed
+    ///
+    /// Filtered packages are added to `packages.skip`; filtered languages,
+    /// `[targets]` entries, and critical symlinks are dropped. Not meant to be
+    /// saved back, since the filtered entries would be lost.
+    pub fn for_machine(self) -> Self {
+        let profile = self.active_profile();
+        let mut config = self.with_profile(profile);
+        let tags = config.tags.clone();
+        let excluded_links: Vec<String> = config
+            .links
+            .when
+            .iter()
+            .filter(|(_, filter)| !tags_match(filter, &tags))
+            .map(|(path, _)| path.clone())
+            .chain(config.profile_excludes())
+            .collect();
+        let is_excluded = |path: &str| {
+            excluded_links
+                .iter()
+                .any(|link| path == link || path.starts_with(&format!("{}/", link)))
+        };
+
+        for (package, filter) in &config.packages.when {
+            if !tags_match(filter, &tags) && !config.packages.is_skipped(package) {
+                config.packages.skip.push(package.clone());
+            }
+        }
+        config.languages.retain(|_, versions| {
+            versions
+                .when
+                .as_deref()
+                .is_none_or(|filter| tags_match(filter, &tags))
+        });
+        config.targets.retain(|source, _| !is_excluded(source));
+        config
+            .critical_symlinks
+            .retain(|link| !is_excluded(&link.path));
+
+        config
+    }
+
+    /// Reads a config file, expanding `~` and `$VAR` in its directory settings
     pub fn load(path: &PathBuf) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
-        let config = toml::from_str(&content)?;
-        Ok(config)
+        let mut config: Self = toml::from_str(&content).map_err(|e| {
+            DotfilesError::Config(format!(
+                "{} is not a valid dotfiles config: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        config.expand_paths()?;
+        Ok(config.with_overrides())
+    }
+
+    /// Applies `--target-dir` and `--userspace-only` on top of the file's values
+    ///
+    /// Setup saves the result, so later runs keep linking into the same place.
+    pub fn with_overrides(mut self) -> Self {
+        if let Some(root) = paths::link_root_override() {
+            self.link_root = Some(root);
+        }
+        self.userspace_only |= crate::core::userspace::forced();
+        self
+    }
+
+    /// Directory links are created in: `link_root`, or else `home_dir`
+    pub fn link_root(&self, home_dir: &Path) -> PathBuf {
+        self.link_root
+            .clone()
+            .unwrap_or_else(|| home_dir.to_path_buf())
+    }
+
+    fn expand_paths(&mut self) -> Result<()> {
+        self.dotfiles_dir = paths::expand(&self.dotfiles_dir)?;
+        self.xdg_config_home = paths::expand(&self.xdg_config_home)?;
+        if let Some(vault) = &self.obsidian.vault {
+            self.obsidian.vault = Some(paths::expand(vault)?);
+        }
+        if let Some(dir) = &self.backups.dir {
+            self.backups.dir = Some(paths::expand(dir)?);
+        }
+        if let Some(identity) = &self.backups.age_identity {
+            self.backups.age_identity = Some(paths::expand(identity)?);
+        }
+        if let Some(root) = &self.link_root {
+            self.link_root = Some(paths::expand(root)?);
+        }
+        for profile in self.profiles.values_mut() {
+            if let Some(dir) = &profile.dotfiles_dir {
+                profile.dotfiles_dir = Some(paths::expand(dir)?);
+            }
+        }
+        Ok(())
     }
 
     pub fn save(&self, path: &PathBuf) -> Result<()> {
@@ -37,6 +959,470 @@ impl Config {
         std::fs::write(path, toml)?;
         Ok(())
     }
+
+    /// Checks the configuration for values that parse but don't make sense
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+
+        // Paths
+        if !self.dotfiles_dir.is_absolute() {
+            problems.push(ConfigProblem::error(
+                "dotfiles_dir",
+                format!("{} is not an absolute path", self.dotfiles_dir.display()),
+                "Use a full path such as /Users/you/dotfiles",
+            ));
+        } else if !self.dotfiles_dir.is_dir() {
+            problems.push(ConfigProblem::error(
+                "dotfiles_dir",
+                format!("{} does not exist", self.dotfiles_dir.display()),
+                "Clone your dotfiles with `dotfiles init` or fix the path",
+            ));
+        }
+
+        if self
+            .dotfiles_dir
+            .components()
+            .any(|c| matches!(c, Component::Normal(name) if name.to_string_lossy().starts_with(crate::backup::LEGACY_PREFIX)))
+        {
+            problems.push(ConfigProblem::error(
+                "dotfiles_dir",
+                format!("{} is inside a backup directory", self.dotfiles_dir.display()),
+                "Point dotfiles_dir at your live dotfiles repository, not a backup copy",
+            ));
+        }
+
+        if !self.xdg_config_home.is_absolute() {
+            problems.push(ConfigProblem::error(
+                "xdg_config_home",
+                format!("{} is not an absolute path", self.xdg_config_home.display()),
+                "Use a full path such as /Users/you/.config",
+            ));
+        } else if !self.xdg_config_home.exists() {
+            problems.push(ConfigProblem::warning(
+                "xdg_config_home",
+                format!("{} does not exist yet", self.xdg_config_home.display()),
+                "It will be created during setup",
+            ));
+        }
+
+        if let Some(root) = &self.link_root {
+            if !root.is_absolute() || !root.is_dir() {
+                problems.push(ConfigProblem::error(
+                    "link_root",
+                    format!("{} is not an existing absolute directory", root.display()),
+                    "Create the directory, or remove link_root to link into $HOME",
+                ));
+            } else if root.starts_with(&self.dotfiles_dir) {
+                problems.push(ConfigProblem::error(
+                    "link_root",
+                    format!("{} is inside dotfiles_dir", root.display()),
+                    "Links into the repo would point at themselves; pick a directory outside it",
+                ));
+            }
+        }
+        if self.userspace_only && !self.machine.is_empty() {
+            problems.push(ConfigProblem::warning(
+                "machine",
+                "[machine] is set but userspace_only skips hostname, timezone, and locale changes",
+                "Remove [machine] on shared machines, or turn off userspace_only",
+            ));
+        }
+
+        // Languages and version manager
+        if self.language_manager == LanguageManager::None && !self.languages.is_empty() {
+            problems.push(ConfigProblem::error(
+                "language_manager",
+                "languages are configured but language_manager is None",
+                "Set language_manager to \"Mise\" or \"Asdf\", or remove [languages]",
+            ));
+        }
+        if self.language_manager == LanguageManager::None && !self.plugins.is_empty() {
+            problems.push(ConfigProblem::warning(
+                "plugins",
+                "plugins are configured but language_manager is None",
+                "Set language_manager or remove [plugins]",
+            ));
+        }
+
+        for (name, versions) in &self.languages {
+            let field = format!("languages.{}", name);
+            if crate::language::get_installer(name).is_none() {
+                problems.push(ConfigProblem::warning(
+                    &field,
+                    format!("unknown language '{}'", name),
+                    "Supported languages: java, nodejs, python, rust, golang",
+                ));
+            }
+            if versions.versions.is_empty() {
+                problems.push(ConfigProblem::error(
+                    &field,
+                    "no versions listed",
+                    "Add at least one entry to `versions`",
+                ));
+            }
+            if let Some(global) = &versions.global {
+                if !versions.versions.contains(global) {
+                    problems.push(ConfigProblem::error(
+                        &field,
+                        format!("global version {} is not in versions", global),
+                        format!("Add {} to `versions` or change `global`", global),
+                    ));
+                }
+            }
+        }
+
+        // Packages
+        for package in &self.packages.pinned {
+            if self.packages.is_skipped(package) {
+                problems.push(ConfigProblem::warning(
+                    "packages.pinned",
+                    format!("{} is both skipped and pinned", package),
+                    format!("Remove {} from one of the lists", package),
+                ));
+            }
+        }
+
+        // Notifications
+        if let Some(url) = &self.notify.webhook {
+            if !(url.starts_with("https://") || url.starts_with("http://")) {
+                problems.push(ConfigProblem::error(
+                    "notify.webhook",
+                    format!("{} is not an http(s) URL", url),
+                    "Use the full webhook URL, e.g. https://hooks.slack.com/services/...",
+                ));
+            }
+        }
+        if let Some(email) = &self.notify.email {
+            if !email.contains('@') || email.contains(char::is_whitespace) {
+                problems.push(ConfigProblem::error(
+                    "notify.email",
+                    format!("{} is not an email address", email),
+                    "Use a single address, e.g. you@example.com",
+                ));
+            }
+        }
+
+        // Neovim
+        match (self.nvim.distro, &self.nvim.url) {
+            (Some(NvimDistro::Own), _) | (_, None) => {}
+            (_, Some(_)) => problems.push(ConfigProblem::warning(
+                "nvim.url",
+                "url is only used with distro = \"own\"",
+                "Set distro = \"own\" to clone it, or remove url",
+            )),
+        }
+
+        // Backups
+        if let Some(dir) = &self.backups.dir {
+            if !dir.is_absolute() {
+                problems.push(ConfigProblem::error(
+                    "backups.dir",
+                    format!("{} is not an absolute path", dir.display()),
+                    "Use a full path such as ~/.local/state/dotfiles/backups",
+                ));
+            } else if dir.starts_with(&self.dotfiles_dir) {
+                problems.push(ConfigProblem::error(
+                    "backups.dir",
+                    format!("{} is inside dotfiles_dir", dir.display()),
+                    "Keep backups outside the repo, such as in ~/.local/state/dotfiles/backups",
+                ));
+            }
+        }
+        if let Some(name) = &self.backups.name {
+            if let Some(problem) = crate::backup::name_format_problem(name) {
+                problems.push(ConfigProblem::error(
+                    "backups.name",
+                    problem,
+                    "Include the full date and time, e.g. dotfiles-%Y%m%d-%H%M%S",
+                ));
+            }
+        }
+        if self.backups.max_count == Some(0) {
+            problems.push(ConfigProblem::error(
+                "backups.max_count",
+                "0 would prune every backup",
+                "Keep at least one, e.g. max_count = 10",
+            ));
+        }
+        match &self.backups.age_recipient {
+            Some(recipient) if recipient.parse::<age::x25519::Recipient>().is_err() => {
+                problems.push(ConfigProblem::error(
+                    "backups.age_recipient",
+                    format!("{} is not an age public key", recipient),
+                    "Use the age1... key printed by: age-keygen -y ~/.config/age/key.txt",
+                ));
+            }
+            None if self.backups.format == Some(BackupFormat::EncryptedArchive) => {
+                problems.push(ConfigProblem::error(
+                    "backups.format",
+                    "tar.gz.age backups need backups.age_recipient",
+                    "Create a key with age-keygen -o ~/.config/age/key.txt and set age_recipient to its public key",
+                ));
+            }
+            _ => {}
+        }
+
+        // Secrets
+        if self.secrets.store == Some(SecretStoreKind::Keychain) && !cfg!(target_os = "macos") {
+            problems.push(ConfigProblem::error(
+                "secrets.store",
+                "the keychain store needs macOS",
+                "Use store = \"env\" on this machine, or set it in a macOS-only profile",
+            ));
+        }
+        if self
+            .secrets
+            .vault
+            .as_deref()
+            .is_some_and(|vault| vault.trim().is_empty())
+        {
+            problems.push(ConfigProblem::error(
+                "secrets.vault",
+                "the 1Password vault name is empty",
+                "Set vault to a vault name, or remove it to use Private",
+            ));
+        }
+        if let Some(threshold) = self.secrets.entropy_threshold {
+            // Bytes carry at most 8 bits each, so nothing could pass a higher bar
+            if !threshold.is_finite() || threshold <= 0.0 || threshold > 8.0 {
+                problems.push(ConfigProblem::error(
+                    "secrets.entropy_threshold",
+                    format!("{} is outside 0-8 bits per character", threshold),
+                    "Use a value like 4.0; lower flags more strings",
+                ));
+            }
+        }
+        if self.secrets.min_length == Some(0) {
+            problems.push(ConfigProblem::error(
+                "secrets.min_length",
+                "a minimum length of 0 flags every string",
+                "Use a length like 20, or remove it for the default",
+            ));
+        }
+
+        // Homebrew
+        if let Some(url) = &self.homebrew.install_script_url {
+            if !url.starts_with("https://") {
+                problems.push(ConfigProblem::error(
+                    "homebrew.install_script_url",
+                    format!("{} is not an https:// URL", url),
+                    "Use https://raw.githubusercontent.com/Homebrew/install/<commit>/install.sh",
+                ));
+            }
+        }
+        if let Some(sha) = &self.homebrew.install_script_sha256 {
+            if sha.len() != 64 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+                problems.push(ConfigProblem::error(
+                    "homebrew.install_script_sha256",
+                    "not a SHA-256 hex digest",
+                    "Use the 64-character output of `shasum -a 256 install.sh`",
+                ));
+            }
+        }
+
+        // Kubernetes
+        if !self.kubernetes.enabled && !self.kubernetes.krew_plugins.is_empty() {
+            problems.push(ConfigProblem::warning(
+                "kubernetes.krew_plugins",
+                "krew plugins are listed but kubernetes is not enabled",
+                "Set `enabled = true` under [kubernetes]",
+            ));
+        }
+
+        // Critical symlinks
+        for link in &self.critical_symlinks {
+            let path = std::path::Path::new(&link.path);
+            if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+                problems.push(ConfigProblem::error(
+                    "critical_symlinks",
+                    format!("{} must be relative to your home directory", link.path),
+                    "Use a path such as \".zshrc\" or \".config/nvim\"",
+                ));
+            }
+        }
+
+        // Link targets
+        for (source, target) in &self.targets {
+            let path = std::path::Path::new(source);
+            if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+                problems.push(ConfigProblem::error(
+                    format!("targets.{}", source),
+                    "source must be a path inside the dotfiles repo",
+                    "Use a repo-relative key such as \"vscode/settings.json\"",
+                ));
+            }
+            if !(target.starts_with('/') || target == "~" || target.starts_with("~/")) {
+                problems.push(ConfigProblem::error(
+                    format!("targets.{}", source),
+                    format!("{} is not an absolute or ~/ path", target),
+                    "Use a destination such as \"~/Library/Application Support/Code/User/settings.json\"",
+                ));
+            }
+        }
+
+        // Machine tags and `when` filters
+        for tag in &self.tags {
+            if tag.is_empty() || tag.starts_with('!') || tag.contains([',', ' ']) {
+                problems.push(ConfigProblem::error(
+                    "tags",
+                    format!("'{}' is not a valid tag", tag),
+                    "Use plain words such as \"laptop\" or \"work\"",
+                ));
+            }
+        }
+
+        let filters = self
+            .packages
+            .when
+            .iter()
+            .map(|(name, filter)| (format!("packages.when.{}", name), filter.as_str()))
+            .chain(self.languages.iter().filter_map(|(name, versions)| {
+                let filter = versions.when.as_deref()?;
+                Some((format!("languages.{}.when", name), filter))
+            }))
+            .chain(
+                self.links
+                    .when
+                    .iter()
+                    .map(|(path, filter)| (format!("links.when.{}", path), filter.as_str())),
+            )
+            .chain(
+                self.hooks
+                    .when
+                    .iter()
+                    .map(|(hook, filter)| (format!("hooks.when.{}", hook), filter.as_str())),
+            );
+        for (field, filter) in filters {
+            if filter
+                .split(',')
+                .all(|term| term.trim().trim_start_matches('!').is_empty())
+            {
+                problems.push(ConfigProblem::warning(
+                    field,
+                    "empty filter matches every machine",
+                    "Name at least one tag, e.g. \"work\" or \"!server\"",
+                ));
+            }
+        }
+
+        for hook in self.hooks.when.keys() {
+            let known = crate::core::hooks::HookPoint::ALL
+                .iter()
+                .any(|point| hook.starts_with(&format!("{}.d/", point.name())));
+            if !known {
+                problems.push(ConfigProblem::warning(
+                    format!("hooks.when.{}", hook),
+                    "not a hook script path",
+                    "Use <point>.d/<script>, e.g. \"post-setup.d/10-taps.sh\"",
+                ));
+            }
+        }
+
+        for path in self.links.when.keys() {
+            if path.contains('/') && !self.targets.contains_key(path) {
+                problems.push(ConfigProblem::warning(
+                    format!("links.when.{}", path),
+                    "only top-level entries and [targets] keys can be filtered",
+                    "Use the top-level directory name, or add the path to [targets]",
+                ));
+            }
+        }
+
+        // Profiles
+        if let Some(name) = &self.profile {
+            if !self.profiles.contains_key(name) {
+                problems.push(ConfigProblem::error(
+                    "profile",
+                    format!("no [profiles.{}] table", name),
+                    "Add the profile, or remove `profile` to match profiles by hostname",
+                ));
+            }
+        }
+        let mut hosts: BTreeMap<String, &str> = BTreeMap::new();
+        for (name, profile) in &self.profiles {
+            if let Some(dir) = &profile.dotfiles_dir {
+                if !dir.is_absolute() {
+                    problems.push(ConfigProblem::error(
+                        format!("profiles.{}.dotfiles_dir", name),
+                        format!("{} is not an absolute path", dir.display()),
+                        "Use a full path such as ~/work-dotfiles",
+                    ));
+                }
+            }
+            for entry in profile.exclude.iter().filter(|entry| entry.contains('/')) {
+                problems.push(ConfigProblem::warning(
+                    format!("profiles.{}.exclude", name),
+                    format!("'{}' is not a top-level entry", entry),
+                    "Exclude the top-level directory, or list the file in .dotfilesignore",
+                ));
+            }
+            for host in &profile.hosts {
+                match hosts.get(&host.to_lowercase()) {
+                    Some(first) => problems.push(ConfigProblem::warning(
+                        format!("profiles.{}.hosts", name),
+                        format!(
+                            "'{}' is also listed by [profiles.{}], which wins",
+                            host, first
+                        ),
+                        "List each hostname in one profile only",
+                    )),
+                    None => {
+                        hosts.insert(host.to_lowercase(), name);
+                    }
+                }
+            }
+        }
+
+        for path in self.files.keys() {
+            if path.contains('/') && !self.targets.contains_key(path) {
+                problems.push(ConfigProblem::warning(
+                    format!("files.{}", path),
+                    "only top-level entries and [targets] keys can have a mode",
+                    "Use the top-level file name, or add the path to [targets]",
+                ));
+            } else if self.dotfiles_dir.join(path).is_dir() {
+                problems.push(ConfigProblem::warning(
+                    format!("files.{}", path),
+                    "only files can be copied, not directories",
+                    "Name the file inside the directory, and add it to [targets]",
+                ));
+            }
+        }
+
+        // Machine identity
+        if let Some(hostname) = &self.machine.hostname {
+            if crate::install::machine::local_host_name(hostname).is_empty() {
+                problems.push(ConfigProblem::error(
+                    "machine.hostname",
+                    format!("'{}' has no letters or digits", hostname),
+                    "Use a name such as \"Work Laptop\"",
+                ));
+            }
+        }
+        if let Some(timezone) = &self.machine.timezone {
+            let zoneinfo = std::path::Path::new(crate::install::machine::ZONEINFO_DIR);
+            if zoneinfo.is_dir() && !crate::install::machine::is_known_timezone(timezone) {
+                problems.push(ConfigProblem::error(
+                    "machine.timezone",
+                    format!("unknown timezone '{}'", timezone),
+                    "Use a name from /usr/share/zoneinfo, such as \"Europe/Berlin\"",
+                ));
+            }
+        }
+
+        // Obsidian
+        if let Some(vault) = &self.obsidian.vault {
+            if !vault.is_absolute() {
+                problems.push(ConfigProblem::error(
+                    "obsidian.vault",
+                    format!("{} is not an absolute path", vault.display()),
+                    "Use a full path such as /Users/you/Notes",
+                ));
+            }
+        }
+
+        problems
+    }
 }
 
 #[cfg(test)]
@@ -47,14 +1433,756 @@ mod tests {
     fn test_config_roundtrip() {
         let config = Config {
             dotfiles_dir: PathBuf::from("/home/user/dotfiles"),
+            dotfiles_branch: None,
             xdg_config_home: PathBuf::from("/home/user/.config"),
+            link_root: None,
+            userspace_only: false,
             language_manager: LanguageManager::Asdf,
             symlink_method: SymlinkMethod::Stow,
             install_oh_my_zsh: true,
+            shell_aliases: true,
+            profile: None,
+            profiles: BTreeMap::new(),
+            tags: vec!["work".to_string()],
+            languages: BTreeMap::new(),
+            plugins: BTreeMap::new(),
+            homebrew: HomebrewSettings::default(),
+            packages: PackageSettings::default(),
+            obsidian: ObsidianSettings::default(),
+            kubernetes: KubernetesSettings::default(),
+            git: GitSettings::default(),
+            nvim: NvimSettings::default(),
+            machine: MachineSettings::default(),
+            notify: NotifySettings::default(),
+            backups: BackupSettings::default(),
+            secrets: SecretSettings::default(),
+            critical_symlinks: default_critical_symlinks(),
+            targets: BTreeMap::new(),
+            links: LinkSettings::default(),
+            hooks: HookSettings::default(),
+            files: BTreeMap::new(),
+            launch_agents: Vec::new(),
+            last_setup: None,
         };
 
         let toml = toml::to_string(&config).unwrap();
         let parsed: Config = toml::from_str(&toml).unwrap();
         assert_eq!(config.dotfiles_dir, parsed.dotfiles_dir);
+        assert!(parsed.shell_aliases);
+    }
+
+    #[test]
+    fn test_config_languages_parse() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [languages.java]
+            versions = ["openjdk-17", "openjdk-21"]
+            global = "openjdk-21"
+
+            [languages.nodejs]
+            versions = ["20.18.0", "22.12.0"]
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.languages.len(), 2);
+        assert_eq!(config.languages["java"].versions.len(), 2);
+        assert_eq!(
+            config.languages["java"].global_version(),
+            Some("openjdk-21")
+        );
+        assert_eq!(config.languages["nodejs"].global_version(), Some("22.12.0"));
+    }
+
+    #[test]
+    fn test_config_without_languages() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Asdf"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.languages.is_empty());
+    }
+
+    #[test]
+    fn test_language_versions_single() {
+        let versions = LanguageVersions::single("3.12.1");
+        assert_eq!(versions.versions, vec!["3.12.1"]);
+        assert_eq!(versions.global_version(), Some("3.12.1"));
+    }
+
+    #[test]
+    fn test_homebrew_settings_parse() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [homebrew]
+            no_analytics = true
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.homebrew.no_analytics);
+        assert!(!config.homebrew.no_auto_update);
+        assert_eq!(
+            config.homebrew.env_vars(),
+            vec![("HOMEBREW_NO_ANALYTICS", "1")]
+        );
+    }
+
+    #[test]
+    fn test_homebrew_settings_default_empty() {
+        assert!(HomebrewSettings::default().env_vars().is_empty());
+    }
+
+    #[test]
+    fn test_package_settings_parse() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [packages]
+            skip = ["nvim"]
+            pinned = ["tmux"]
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.packages.is_skipped("nvim"));
+        assert!(!config.packages.is_skipped("tmux"));
+        assert!(config.packages.is_pinned("tmux"));
+    }
+
+    #[test]
+    fn test_critical_symlinks_parse() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [[critical_symlinks]]
+            path = ".zshrc"
+
+            [[critical_symlinks]]
+            path = ".config/nvim"
+            severity = "warning"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.critical_symlinks,
+            vec![
+                CriticalSymlink::error(".zshrc"),
+                CriticalSymlink {
+                    path: ".config/nvim".to_string(),
+                    severity: LinkSeverity::Warning,
+                },
+            ]
+        );
+
+        let config: Config = toml::from_str(
+            "dotfiles_dir = \"/d\"\nxdg_config_home = \"/c\"\nlanguage_manager = \"Mise\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\n",
+        )
+        .unwrap();
+        assert_eq!(config.critical_symlinks, default_critical_symlinks());
+    }
+
+    #[test]
+    fn test_validate_critical_symlink_paths() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.critical_symlinks = vec![
+            CriticalSymlink::error("/etc/zshrc"),
+            CriticalSymlink::error("../outside"),
+        ];
+
+        let problems = config.validate();
+        assert_eq!(
+            problems
+                .iter()
+                .filter(|p| p.field == "critical_symlinks")
+                .count(),
+            2
+        );
+    }
+
+    fn valid_config(dir: &std::path::Path) -> Config {
+        let dotfiles_dir = dir.join("dotfiles");
+        let xdg_config_home = dir.join(".config");
+        std::fs::create_dir_all(&dotfiles_dir).unwrap();
+        std::fs::create_dir_all(&xdg_config_home).unwrap();
+
+        Config {
+            dotfiles_dir,
+            xdg_config_home,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_clean_config() {
+        let temp = tempfile::TempDir::new().unwrap();
+        assert!(valid_config(temp.path()).validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_missing_and_relative_paths() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.dotfiles_dir = temp.path().join("missing");
+        config.xdg_config_home = PathBuf::from("relative/.config");
+
+        let problems = config.validate();
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|p| p.is_error()));
+        assert_eq!(problems[0].field, "dotfiles_dir");
+        assert_eq!(problems[1].field, "xdg_config_home");
+    }
+
+    #[test]
+    fn test_validate_dotfiles_inside_backup() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.dotfiles_dir = temp
+            .path()
+            .join(".dotfiles-backup-20250101-120000/dotfiles");
+        std::fs::create_dir_all(&config.dotfiles_dir).unwrap();
+
+        let problems = config.validate();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].message.contains("backup"));
+    }
+
+    #[test]
+    fn test_validate_backups_dir() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+
+        // A directory holding the repo, such as $HOME, is fine
+        config.backups.dir = Some(temp.path().to_path_buf());
+        assert!(config.validate().is_empty());
+
+        config.backups.dir = Some(config.dotfiles_dir.join("backups"));
+        let problems = config.validate();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].field, "backups.dir");
+    }
+
+    #[test]
+    fn test_validate_language_combinations() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.language_manager = LanguageManager::None;
+        config.languages.insert(
+            "java".to_string(),
+            LanguageVersions {
+                versions: vec!["openjdk-21".to_string()],
+                global: Some("openjdk-17".to_string()),
+                when: None,
+            },
+        );
+        config
+            .languages
+            .insert("cobol".to_string(), LanguageVersions::single("1"));
+
+        let fields: Vec<(String, bool)> = config
+            .validate()
+            .into_iter()
+            .map(|p| (p.field.clone(), p.is_error()))
+            .collect();
+        assert!(fields.contains(&("language_manager".to_string(), true)));
+        assert!(fields.contains(&("languages.java".to_string(), true)));
+        assert!(fields.contains(&("languages.cobol".to_string(), false)));
+    }
+
+    #[test]
+    fn test_validate_skipped_and_pinned() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.packages.skip = vec!["nvim".to_string()];
+        config.packages.pinned = vec!["nvim".to_string()];
+
+        let problems = config.validate();
+        assert_eq!(problems.len(), 1);
+        assert!(!problems[0].is_error());
+    }
+
+    #[test]
+    fn test_tags_match() {
+        let tags = vec!["laptop".to_string(), "work".to_string()];
+        assert!(tags_match("work", &tags));
+        assert!(tags_match("server, laptop", &tags));
+        assert!(tags_match("!server", &tags));
+        assert!(!tags_match("server", &tags));
+        assert!(!tags_match("laptop, !work", &tags));
+        assert!(!tags_match("work", &[]));
+        assert!(tags_match("!work", &[]));
+    }
+
+    #[test]
+    fn test_for_machine_filters_by_tags() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+            tags = ["server"]
+
+            [languages.java]
+            versions = ["openjdk-21"]
+            when = "work"
+
+            [languages.python]
+            versions = ["3.12.1"]
+
+            [packages.when]
+            neovide = "laptop"
+            tmux = "!laptop"
+
+            [targets]
+            "mac/karabiner.json" = "~/.config/karabiner/karabiner.json"
+            "vscode/settings.json" = "~/.config/Code/User/settings.json"
+
+            [links.when]
+            mac = "laptop"
+            ".aerospace.toml" = "laptop"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.skipped_links(),
+            vec![".aerospace.toml", "mac", "obsidian"]
+        );
+
+        let machine = config.for_machine();
+        assert_eq!(machine.packages.skip, vec!["neovide"]);
+        assert_eq!(machine.languages.keys().collect::<Vec<_>>(), vec!["python"]);
+        assert_eq!(
+            machine.targets.keys().collect::<Vec<_>>(),
+            vec!["vscode/settings.json"]
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [packages]
+            skip = ["neovide"]
+            selected = ["htop"]
+
+            [profiles.home]
+            hosts = ["Jo's Laptop"]
+            exclude = ["work"]
+
+            [profiles.work]
+            hosts = ["build-01"]
+            dotfiles_dir = "/home/user/work-dotfiles"
+            exclude = [".aerospace.toml"]
+
+            [profiles.work.packages]
+            selected = ["awscli", "kubernetes-cli"]
+            backend = "prebuilt"
+
+            [[critical_symlinks]]
+            path = ".aerospace.toml"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.profile_for_host("BUILD-01"), Some("work"));
+        assert_eq!(config.profile_for_host("Jos-Laptop"), Some("home"));
+        assert_eq!(config.profile_for_host("other"), None);
+        assert_eq!(config.skipped_links(), vec!["obsidian"]);
+
+        let work = Config {
+            profile: Some("work".to_string()),
+            ..config.clone()
+        }
+        .for_machine();
+        assert_eq!(work.profile.as_deref(), Some("work"));
+        assert_eq!(work.dotfiles_dir, PathBuf::from("/home/user/work-dotfiles"));
+        assert_eq!(work.packages.selected, vec!["awscli", "kubernetes-cli"]);
+        assert_eq!(work.packages.skip, vec!["neovide"]);
+        assert_eq!(work.packages.backend, Some(PackageSource::Prebuilt));
+        assert_eq!(config.packages.backend, None);
+        assert_eq!(work.skipped_links(), vec![".aerospace.toml", "obsidian"]);
+        assert!(work.critical_symlinks.is_empty());
+
+        // An unknown profile changes nothing, and validation reports it
+        let unknown = Config {
+            profile: Some("server".to_string()),
+            ..config
+        };
+        assert_eq!(
+            unknown
+                .clone()
+                .with_profile(Some("server".to_string()))
+                .dotfiles_dir,
+            PathBuf::from("/home/user/dotfiles")
+        );
+        assert!(unknown
+            .validate()
+            .iter()
+            .any(|p| p.field == "profile" && p.is_error()));
+    }
+
+    #[test]
+    fn test_validate_profiles() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.profiles.insert(
+            "home".to_string(),
+            Profile {
+                hosts: vec!["mini".to_string()],
+                exclude: vec![".config/work".to_string()],
+                ..Profile::default()
+            },
+        );
+        config.profiles.insert(
+            "work".to_string(),
+            Profile {
+                hosts: vec!["Mini".to_string()],
+                dotfiles_dir: Some(PathBuf::from("work-dotfiles")),
+                ..Profile::default()
+            },
+        );
+
+        let fields: Vec<(String, bool)> = config
+            .validate()
+            .into_iter()
+            .map(|p| (p.field.clone(), p.is_error()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("profiles.home.exclude".to_string(), false),
+                ("profiles.work.dotfiles_dir".to_string(), true),
+                ("profiles.work.hosts".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_copied_files() {
+        let toml = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+
+            [targets]
+            "mac/settings.json" = "~/Library/Application Support/App/settings.json"
+            "vscode/settings.json" = "~/.config/Code/User/settings.json"
+
+            [files.".rectangle.json"]
+            mode = "copy"
+
+            [files."mac/settings.json"]
+            mode = "copy"
+
+            [files."vscode/settings.json"]
+            mode = "symlink"
+        "#;
+
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(
+            config.copied_files(),
+            vec![".rectangle.json", "mac/settings.json"]
+        );
+        assert_eq!(config.skipped_links(), vec![".rectangle.json", "obsidian"]);
+        assert_eq!(
+            config.linked_targets().keys().collect::<Vec<_>>(),
+            vec!["vscode/settings.json"]
+        );
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = Config {
+            files: config.files,
+            ..valid_config(temp.path())
+        };
+        std::fs::create_dir_all(temp.path().join("dotfiles/.rectangle.json")).unwrap();
+        let fields: Vec<String> = config.validate().into_iter().map(|p| p.field).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "files..rectangle.json",
+                "files.mac/settings.json",
+                "files.vscode/settings.json"
+            ]
+        );
+        config.targets.insert(
+            "mac/settings.json".to_string(),
+            "~/settings.json".to_string(),
+        );
+        assert_eq!(config.validate().len(), 2);
+    }
+
+    #[test]
+    fn test_link_skip_default() {
+        let base = r#"
+            dotfiles_dir = "/home/user/dotfiles"
+            xdg_config_home = "/home/user/.config"
+            language_manager = "Mise"
+            symlink_method = "Stow"
+            install_oh_my_zsh = false
+        "#;
+
+        let config: Config = toml::from_str(base).unwrap();
+        assert_eq!(config.links.skip, vec!["obsidian"]);
+        assert!(!toml::to_string(&config).unwrap().contains("skip"));
+
+        // An explicit list replaces the default, so a package named obsidian can be linked
+        let config: Config =
+            toml::from_str(&format!("{}\n[links]\nskip = [\"work\"]\n", base)).unwrap();
+        assert_eq!(config.skipped_links(), vec!["work"]);
+    }
+
+    #[test]
+    fn test_validate_tags_and_filters() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let mut config = valid_config(temp.path());
+        config.tags = vec!["work".to_string(), "!bad".to_string()];
+        config
+            .packages
+            .when
+            .insert("neovide".to_string(), " , ".to_string());
+        config
+            .links
+            .when
+            .insert(".config/aerospace".to_string(), "laptop".to_string());
+        config
+            .hooks
+            .when
+            .insert("post-setup.d/10-mas.sh".to_string(), "laptop".to_string());
+        config
+            .hooks
+            .when
+            .insert("10-mas.sh".to_string(), "laptop".to_string());
+
+        let fields: Vec<(String, bool)> = config
+            .validate()
+            .into_iter()
+            .map(|p| (p.field.clone(), p.is_error()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("tags".to_string(), true),
+                ("packages.when.neovide".to_string(), false),
+                ("hooks.when.10-mas.sh".to_string(), false),
+                ("links.when..config/aerospace".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_machine_settings() {
+        let toml_str = r#"
+            dotfiles_dir = "/tmp"
+            xdg_config_home = "/tmp"
+            language_manager = "Mise"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+
+            [machine]
+            hostname = "!!"
+            timezone = "Not/AZone"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.machine.hostname.as_deref(), Some("!!"));
+        assert!(config.machine.locale.is_none());
+
+        let fields: Vec<String> = config.validate().into_iter().map(|p| p.field).collect();
+        assert!(fields.contains(&"machine.hostname".to_string()));
+        if std::path::Path::new(crate::install::machine::ZONEINFO_DIR).is_dir() {
+            assert!(fields.contains(&"machine.timezone".to_string()));
+        }
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("[machine]"));
+    }
+
+    #[test]
+    fn test_notify_settings() {
+        let toml_str = r#"
+            dotfiles_dir = "/tmp"
+            xdg_config_home = "/tmp"
+            language_manager = "Mise"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+
+            [notify]
+            desktop = true
+            webhook = "hooks.example.com/x"
+            email = "me at example.com"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert!(config.notify.desktop);
+
+        let fields: Vec<String> = config.validate().into_iter().map(|p| p.field).collect();
+        assert!(fields.contains(&"notify.webhook".to_string()));
+        assert!(fields.contains(&"notify.email".to_string()));
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("[notify]"));
+    }
+
+    #[test]
+    fn test_backup_settings() {
+        let toml_str = r#"
+            dotfiles_dir = "/tmp/dotfiles"
+            xdg_config_home = "/tmp"
+            language_manager = "Mise"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+
+            [backups]
+            dir = "/tmp/dotfiles/backups"
+            name = "backup-%Y%m%d"
+            max_size_mb = 200
+            max_count = 0
+            format = "tar.gz.age"
+            age_recipient = "age1notakey"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.backups.dir,
+            Some(PathBuf::from("/tmp/dotfiles/backups"))
+        );
+        assert_eq!(config.backups.max_size_mb, Some(200));
+        assert_eq!(config.backups.format, Some(BackupFormat::EncryptedArchive));
+
+        let problems = config.validate();
+        assert!(problems.iter().any(|p| p.field == "backups.dir"));
+        assert!(problems.iter().any(|p| p.field == "backups.name"));
+        assert!(problems.iter().any(|p| p.field == "backups.max_count"));
+        assert!(problems.iter().any(|p| p.field == "backups.age_recipient"));
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("[backups]"));
+    }
+
+    #[test]
+    fn test_secret_settings() {
+        let config: Config = toml::from_str(
+            "dotfiles_dir = \"/d\"\nxdg_config_home = \"/x\"\nlanguage_manager = \"Mise\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\n[secrets]\nstore = \"keychain\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.secrets.store, Some(SecretStoreKind::Keychain));
+        let flagged = config
+            .validate()
+            .iter()
+            .any(|problem| problem.field == "secrets.store");
+        assert_eq!(flagged, !cfg!(target_os = "macos"));
+
+        assert_eq!(
+            SecretStoreKind::from_name("env"),
+            Some(SecretStoreKind::Env)
+        );
+        assert_eq!(SecretStoreKind::from_name("vault"), None);
+        assert!(Config::default().secrets.is_empty());
+        assert_eq!(Config::default().secrets.vault(), "Private");
+
+        let config: Config = toml::from_str(
+            "dotfiles_dir = \"/d\"\nxdg_config_home = \"/x\"\nlanguage_manager = \"Mise\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\n[secrets]\nstore = \"1password\"\nvault = \"Dev\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.secrets.store, Some(SecretStoreKind::OnePassword));
+        assert_eq!(config.secrets.vault(), "Dev");
+        assert!(config
+            .validate()
+            .iter()
+            .all(|problem| !problem.field.starts_with("secrets")));
+        assert!(toml::to_string(&config)
+            .unwrap()
+            .contains("store = \"1password\""));
+    }
+
+    #[test]
+    fn test_nvim_settings() {
+        let toml_str = r#"
+            dotfiles_dir = "/tmp"
+            xdg_config_home = "/tmp"
+            language_manager = "Mise"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+
+            [nvim]
+            distro = "lazyvim"
+            url = "https://github.com/me/nvim"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.nvim.distro, Some(NvimDistro::LazyVim));
+        assert!(config.validate().iter().any(|p| p.field == "nvim.url"));
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("[nvim]"));
+    }
+
+    #[test]
+    fn test_shared_machine_settings() {
+        let toml_str = r#"
+            dotfiles_dir = "/tmp/dotfiles"
+            xdg_config_home = "/tmp"
+            link_root = "/tmp/dotfiles/home"
+            userspace_only = true
+            language_manager = "Mise"
+            symlink_method = "Manual"
+            install_oh_my_zsh = false
+
+            [machine]
+            timezone = "UTC"
+        "#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.link_root(Path::new("/home/me")),
+            PathBuf::from("/tmp/dotfiles/home")
+        );
+
+        let problems = config.validate();
+        assert!(problems.iter().any(|p| p.field == "link_root"));
+        assert!(problems
+            .iter()
+            .any(|p| p.field == "machine" && !p.is_error()));
+
+        let defaults = Config::default();
+        assert_eq!(
+            defaults.link_root(Path::new("/home/me")),
+            PathBuf::from("/home/me")
+        );
+        let saved = toml::to_string(&defaults).unwrap();
+        assert!(!saved.contains("link_root") && !saved.contains("userspace_only"));
+    }
+
+    #[test]
+    fn test_load_reports_path_on_parse_error() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(".dotfiles.conf");
+        std::fs::write(&path, "language_manager = 42\n").unwrap();
+
+        let err = Config::load(&path).unwrap_err().to_string();
+        assert!(err.contains(".dotfiles.conf"));
     }
 }
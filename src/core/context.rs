@@ -0,0 +1,118 @@
+use crate::core::config::Config;
+use crate::core::{paths, state};
+use crate::detect::os::{detect_os, OS};
+use crate::error::{DotfilesError, Result};
+use crate::install::shell::Shell;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// What every command needs to know about where and on what it runs
+///
+/// Built once at startup, after `--home` has been applied, and passed to
+/// commands. The config is read the first time a command asks for it, so
+/// commands that don't need one still run without `~/.dotfiles.conf`.
+#[derive(Debug, Clone)]
+pub struct AppContext {
+    pub home: PathBuf,
+    /// `~/.dotfiles.conf`
+    pub config_path: PathBuf,
+    /// Directory holding the state file, backups, and caches
+    pub state_dir: PathBuf,
+    /// The state file
+    pub state_path: PathBuf,
+    pub os: OS,
+    pub arch: &'static str,
+    /// Login shell, from `$SHELL`
+    pub shell: Shell,
+    config: OnceLock<Config>,
+}
+
+impl AppContext {
+    /// The context for the home directory in effect (`--home`, `DOTFILES_HOME_OVERRIDE`, or the real one)
+    pub fn from_env() -> Result<Self> {
+        paths::home_dir()
+            .map(Self::for_home)
+            .ok_or_else(|| DotfilesError::Config("Could not determine home directory".to_string()))
+    }
+
+    /// The context for `home`, detecting the platform from the running system
+    pub fn for_home(home: impl Into<PathBuf>) -> Self {
+        let home = home.into();
+        Self {
+            config_path: home.join(".dotfiles.conf"),
+            state_dir: state::state_dir(&home),
+            state_path: state::state_path(&home),
+            os: detect_os(),
+            arch: std::env::consts::ARCH,
+            shell: Shell::current(),
+            config: OnceLock::new(),
+            home,
+        }
+    }
+
+    /// Uses `config` instead of reading `~/.dotfiles.conf`
+    pub fn with_config(self, config: Config) -> Self {
+        Self {
+            config: OnceLock::from(config),
+            ..self
+        }
+    }
+
+    /// Returns the config, reading `~/.dotfiles.conf` on first use
+    pub fn config(&self) -> Result<&Config> {
+        if let Some(config) = self.config.get() {
+            return Ok(config);
+        }
+        if !self.config_path.exists() {
+            return Err(DotfilesError::Config(
+                "~/.dotfiles.conf not found. Run: dotfiles setup".to_string(),
+            ));
+        }
+        let config = Config::load(&self.config_path)?;
+        Ok(self.config.get_or_init(|| config))
+    }
+
+    /// Returns the config narrowed to this machine's tags and profile
+    pub fn machine_config(&self) -> Result<Config> {
+        Ok(self.config()?.clone().for_machine())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_context_for_home() {
+        let temp = TempDir::new().unwrap();
+        let context = AppContext::for_home(temp.path());
+        assert_eq!(context.config_path, temp.path().join(".dotfiles.conf"));
+        assert_eq!(context.state_path, state::state_path(temp.path()));
+        assert_eq!(context.os, detect_os());
+
+        let missing = context.config().unwrap_err().to_string();
+        assert!(missing.contains("Run: dotfiles setup"));
+    }
+
+    #[test]
+    fn test_context_config() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles_dir = temp.path().join("dotfiles");
+        let config: Config = toml::from_str(&format!(
+            "dotfiles_dir = {:?}\nxdg_config_home = \"/x/.config\"\nlanguage_manager = \"Asdf\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\n",
+            dotfiles_dir
+        ))
+        .unwrap();
+        let context = AppContext::for_home(temp.path()).with_config(config.clone());
+        assert_eq!(context.config().unwrap().dotfiles_dir, dotfiles_dir);
+        assert!(!context.config_path.exists());
+
+        // Read once from disk, then served from memory
+        let context = AppContext::for_home(temp.path());
+        config.save(&context.config_path).unwrap();
+        assert_eq!(context.config().unwrap().dotfiles_dir, dotfiles_dir);
+        std::fs::remove_file(&context.config_path).unwrap();
+        assert!(context.config().is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::error::DotfilesError;
+use crate::validate::CheckReport;
+
+/// How a command finished, mapped to the process exit code
+///
+/// | Code | Status      | Meaning                                          |
+/// |------|-------------|--------------------------------------------------|
+/// | 0    | `ok`        | Everything succeeded                             |
+/// | 1    | `errors`    | The command failed or checks reported errors     |
+/// | 2    | `warnings`  | Succeeded, but checks reported warnings          |
+/// | 3    | `conflicts` | Existing files blocked symlinks                  |
+/// | 4    | `usage`     | Invalid arguments                                |
+/// | 130  | `cancelled` | Interrupted with Ctrl-C                          |
+///
+/// When several apply, the more severe wins (see [`ExitStatus::worst`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Ok,
+    Warnings,
+    Conflicts,
+    Errors,
+    Usage,
+    Cancelled,
+}
+
+impl ExitStatus {
+    /// Returns the process exit code
+    pub fn code(&self) -> u8 {
+        match self {
+            ExitStatus::Ok => 0,
+            ExitStatus::Errors => 1,
+            ExitStatus::Warnings => 2,
+            ExitStatus::Conflicts => 3,
+            ExitStatus::Usage => 4,
+            ExitStatus::Cancelled => 130,
+        }
+    }
+
+    /// Returns the name used in the status line
+    pub fn label(&self) -> &str {
+        match self {
+            ExitStatus::Ok => "ok",
+            ExitStatus::Errors => "errors",
+            ExitStatus::Warnings => "warnings",
+            ExitStatus::Conflicts => "conflicts",
+            ExitStatus::Usage => "usage",
+            ExitStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Status for a check report: errors, then warnings, else ok
+    pub fn from_report(report: &CheckReport) -> Self {
+        if report.has_errors() {
+            ExitStatus::Errors
+        } else if report.warn_count() > 0 {
+            ExitStatus::Warnings
+        } else {
+            ExitStatus::Ok
+        }
+    }
+
+    /// Returns the more severe of two statuses
+    ///
+    /// Severity runs ok < warnings < conflicts < errors < usage < cancelled.
+    pub fn worst(self, other: Self) -> Self {
+        // Variants are declared in order of severity
+        if (other as u8) > (self as u8) {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// Formats the single line printed in `--quiet` mode
+    ///
+    /// `status=<label> code=<n>`, plus `error="<message>"` when the command failed.
+    pub fn status_line(&self, error: Option<&DotfilesError>) -> String {
+        let mut line = format!("status={} code={}", self.label(), self.code());
+        if let Some(error) = error {
+            line.push_str(&format!(" error={:?}", error.to_string()));
+        }
+        line
+    }
+}
+
+impl From<()> for ExitStatus {
+    fn from(_: ()) -> Self {
+        ExitStatus::Ok
+    }
+}
+
+impl From<&DotfilesError> for ExitStatus {
+    fn from(error: &DotfilesError) -> Self {
+        match error {
+            DotfilesError::SymlinkConflict(_) => ExitStatus::Conflicts,
+            DotfilesError::Cancelled(_) => ExitStatus::Cancelled,
+            _ => ExitStatus::Errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::CheckResult;
+
+    #[test]
+    fn test_from_report() {
+        let mut report = CheckReport::new();
+        report.add(CheckResult::pass("A", "ok"));
+        assert_eq!(ExitStatus::from_report(&report), ExitStatus::Ok);
+
+        report.add(CheckResult::warn("B", "hmm", None::<String>));
+        assert_eq!(ExitStatus::from_report(&report).code(), 2);
+
+        report.add(CheckResult::error("C", "bad", None::<String>));
+        assert_eq!(ExitStatus::from_report(&report).code(), 1);
+    }
+
+    #[test]
+    fn test_worst() {
+        assert_eq!(
+            ExitStatus::Warnings.worst(ExitStatus::Conflicts),
+            ExitStatus::Conflicts
+        );
+        assert_eq!(
+            ExitStatus::Errors.worst(ExitStatus::Conflicts),
+            ExitStatus::Errors
+        );
+        assert_eq!(ExitStatus::Ok.worst(ExitStatus::Ok), ExitStatus::Ok);
+    }
+
+    #[test]
+    fn test_status_line() {
+        assert_eq!(
+            ExitStatus::Warnings.status_line(None),
+            "status=warnings code=2"
+        );
+
+        let error = DotfilesError::SymlinkConflict("~/.zshrc exists".to_string());
+        let status = ExitStatus::from(&error);
+        assert_eq!(
+            status.status_line(Some(&error)),
+            "status=conflicts code=3 error=\"Symlink conflict: ~/.zshrc exists\""
+        );
+    }
+}
@@ -0,0 +1,381 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::state;
+use crate::error::{DotfilesError, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+
+/// Base URL of the GitHub REST API
+pub const API_URL: &str = "https://api.github.com";
+
+/// How long a cached response is reused before GitHub is asked again
+///
+/// Even then the request is conditional (`If-None-Match`), and a `304 Not
+/// Modified` doesn't count against the rate limit.
+const MAX_AGE_MINUTES: i64 = 60;
+
+/// How cache timestamps are written
+const FETCHED_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A response kept for conditional requests, keyed by API path
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+struct CachedResponse {
+    etag: Option<String>,
+    fetched_at: String,
+    body: String,
+}
+
+/// Status, headers (lowercased names), and body of an API response
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Response {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
+/// Returns a GitHub token from `GITHUB_TOKEN`, `GH_TOKEN`, or `gh auth token`
+///
+/// Authenticated requests get 5000 per hour instead of the 60 per IP that
+/// everyone behind a shared office IP splits between them.
+pub fn token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+        .or_else(|| {
+            if !crate::detect::tools::is_installed("gh") {
+                return None;
+            }
+            CommandRunner::new("gh")
+                .args(["auth", "token"])
+                .timeout(command::QUICK_TIMEOUT)
+                .stream(false)
+                .output()
+                .ok()
+                .filter(|output| output.success())
+                .map(|output| output.stdout.trim().to_string())
+                .filter(|token| !token.is_empty())
+        })
+}
+
+/// GETs an API path (e.g. `/repos/owner/name/releases/latest`) and returns the body
+///
+/// Responses are cached under the state directory and revalidated with
+/// their ETag. Hitting the rate limit is a `RateLimited` error explaining
+/// when it resets and how to raise it; a stale cached body is not returned
+/// in its place.
+pub fn get(home_dir: &Path, path: &str) -> Result<String> {
+    let cache_file = cache_path(home_dir);
+    let mut cache = load_cache(&cache_file);
+    let cached = cache.get(path).cloned();
+    if let Some(cached) = &cached {
+        if is_fresh(cached, Local::now().naive_local()) {
+            return Ok(cached.body.clone());
+        }
+    }
+
+    let token = token();
+    let response = request(path, token.as_deref(), cached.as_ref())?;
+    match response.status {
+        304 => {
+            let mut cached = cached.unwrap_or_default();
+            cached.fetched_at = Local::now().format(FETCHED_FORMAT).to_string();
+            let body = cached.body.clone();
+            cache.insert(path.to_string(), cached);
+            save_cache(&cache_file, &cache);
+            Ok(body)
+        }
+        200..=299 => {
+            cache.insert(
+                path.to_string(),
+                CachedResponse {
+                    etag: response.headers.get("etag").cloned(),
+                    fetched_at: Local::now().format(FETCHED_FORMAT).to_string(),
+                    body: response.body.clone(),
+                },
+            );
+            save_cache(&cache_file, &cache);
+            Ok(response.body)
+        }
+        _ => Err(
+            rate_limit_error(&response, token.is_some()).unwrap_or_else(|| {
+                DotfilesError::CommandFailed(format!(
+                    "GitHub API {}: HTTP {}{}",
+                    path,
+                    response.status,
+                    api_message(&response.body)
+                        .map(|message| format!(" ({})", message))
+                        .unwrap_or_default()
+                ))
+            }),
+        ),
+    }
+}
+
+/// Returns the tag of a repository's latest published release
+pub fn latest_release(home_dir: &Path, repo: &str) -> Result<String> {
+    #[derive(Deserialize)]
+    struct Release {
+        tag_name: String,
+    }
+
+    let body = get(home_dir, &format!("/repos/{}/releases/latest", repo))?;
+    let release: Release = serde_json::from_str(&body)?;
+    Ok(release.tag_name)
+}
+
+fn request(path: &str, token: Option<&str>, cached: Option<&CachedResponse>) -> Result<Response> {
+    let mut runner = CommandRunner::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--include",
+            "--proto",
+            "=https",
+            "--max-time",
+            "20",
+            "--header",
+            "Accept: application/vnd.github+json",
+            "--header",
+            "X-GitHub-Api-Version: 2022-11-28",
+            "--header",
+        ])
+        .arg(format!(
+            "User-Agent: dotfiles-tool/{}",
+            env!("CARGO_PKG_VERSION")
+        ));
+    if let Some(etag) = cached.and_then(|cached| cached.etag.as_deref()) {
+        runner = runner
+            .arg("--header")
+            .arg(format!("If-None-Match: {}", etag));
+    }
+
+    // The token goes through a private file so it never shows up in `ps`;
+    // the file is removed when `header_file` is dropped
+    let header_file = match token {
+        Some(token) => {
+            let file = write_private(&format!("Authorization: Bearer {}\n", token))?;
+            runner = runner
+                .arg("--header")
+                .arg(format!("@{}", file.path().display()));
+            Some(file)
+        }
+        None => None,
+    };
+
+    let output = runner
+        .arg(format!("{}{}", API_URL, path))
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+    drop(header_file);
+
+    let output = output?;
+    if !output.success() {
+        return Err(DotfilesError::CommandFailed(format!(
+            "Could not reach GitHub: {}",
+            output.stderr.trim()
+        )));
+    }
+    parse_response(&output.stdout).ok_or_else(|| {
+        DotfilesError::CommandFailed(format!("Unexpected response from GitHub for {}", path))
+    })
+}
+
+/// Writes `content` to a new temp file with a random name, readable only by the owner
+fn write_private(content: &str) -> Result<NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("dotfiles-github-")
+        .tempfile()?;
+    file.write_all(content.as_bytes())?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// Splits `curl --include` output into status, headers, and body
+fn parse_response(raw: &str) -> Option<Response> {
+    let (head, body) = raw
+        .split_once("\r\n\r\n")
+        .or_else(|| raw.split_once("\n\n"))
+        .unwrap_or((raw, ""));
+    let mut lines = head.lines();
+    let status = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    Some(Response {
+        status,
+        headers,
+        body: body.to_string(),
+    })
+}
+
+/// Explains a rate-limited response, or None if the response wasn't rate limited
+fn rate_limit_error(response: &Response, authenticated: bool) -> Option<DotfilesError> {
+    let exhausted = response
+        .headers
+        .get("x-ratelimit-remaining")
+        .is_some_and(|remaining| remaining == "0");
+    let secondary = response.headers.contains_key("retry-after");
+    if !(response.status == 429 || (response.status == 403 && (exhausted || secondary))) {
+        return None;
+    }
+
+    let resets = response
+        .headers
+        .get("x-ratelimit-reset")
+        .and_then(|reset| reset.parse::<i64>().ok())
+        .and_then(|epoch| DateTime::from_timestamp(epoch, 0))
+        .map(|at| format!(" until {}", at.with_timezone(&Local).format("%H:%M")))
+        .or_else(|| {
+            response
+                .headers
+                .get("retry-after")
+                .map(|seconds| format!(" for {}s", seconds))
+        })
+        .unwrap_or_default();
+    let advice = if authenticated {
+        "wait for it to reset"
+    } else {
+        "unauthenticated requests share 60 per hour per IP; run `gh auth login` or set GITHUB_TOKEN"
+    };
+    Some(DotfilesError::RateLimited(format!(
+        "GitHub API limit reached{}; {}",
+        resets, advice
+    )))
+}
+
+fn api_message(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("message")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn is_fresh(cached: &CachedResponse, now: NaiveDateTime) -> bool {
+    NaiveDateTime::parse_from_str(&cached.fetched_at, FETCHED_FORMAT)
+        .is_ok_and(|fetched| (now - fetched).num_minutes() < MAX_AGE_MINUTES)
+}
+
+fn cache_path(home_dir: &Path) -> PathBuf {
+    state::state_dir(home_dir).join("github-cache.json")
+}
+
+fn load_cache(path: &Path) -> BTreeMap<String, CachedResponse> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(path: &Path, cache: &BTreeMap<String, CachedResponse>) {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(content) = serde_json::to_string_pretty(cache) {
+        let _ = fs::write(path, content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = write_private("Authorization: Bearer t\n").unwrap();
+        let path = file.path().to_path_buf();
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Authorization: Bearer t\n"
+        );
+        assert_ne!(path, write_private("").unwrap().path());
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let raw = "HTTP/2 304 \r\netag: W/\"abc\"\r\nX-RateLimit-Remaining: 59\r\n\r\n";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.status, 304);
+        assert_eq!(response.headers["etag"], "W/\"abc\"");
+        assert_eq!(response.headers["x-ratelimit-remaining"], "59");
+        assert_eq!(response.body, "");
+
+        let raw =
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"tag_name\":\"v1.2.0\"}";
+        assert_eq!(
+            parse_response(raw).unwrap().body,
+            "{\"tag_name\":\"v1.2.0\"}"
+        );
+        assert!(parse_response("").is_none());
+    }
+
+    #[test]
+    fn test_rate_limit_error() {
+        let response = |status: u16, headers: &[(&str, &str)]| Response {
+            status,
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: "{\"message\":\"API rate limit exceeded\"}".to_string(),
+        };
+
+        let limited = response(
+            403,
+            &[
+                ("x-ratelimit-remaining", "0"),
+                ("x-ratelimit-reset", "1767225600"),
+            ],
+        );
+        let message = rate_limit_error(&limited, false).unwrap().to_string();
+        assert!(message.contains("until"));
+        assert!(message.contains("gh auth login"));
+        assert!(!rate_limit_error(&limited, true)
+            .unwrap()
+            .to_string()
+            .contains("gh auth login"));
+
+        let secondary = response(429, &[("retry-after", "30")]);
+        assert!(rate_limit_error(&secondary, true)
+            .unwrap()
+            .to_string()
+            .contains("for 30s"));
+
+        // A 403 for anything else (private repo, bad token) is not a rate limit
+        let forbidden = response(403, &[("x-ratelimit-remaining", "4999")]);
+        assert!(rate_limit_error(&forbidden, true).is_none());
+        assert_eq!(
+            api_message(&forbidden.body).as_deref(),
+            Some("API rate limit exceeded")
+        );
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let now = NaiveDateTime::parse_from_str("2026-03-01 12:00:00", FETCHED_FORMAT).unwrap();
+        let fetched = |at: &str| CachedResponse {
+            fetched_at: at.to_string(),
+            ..Default::default()
+        };
+        assert!(is_fresh(&fetched("2026-03-01 11:30:00"), now));
+        assert!(!is_fresh(&fetched("2026-03-01 10:59:00"), now));
+        assert!(!is_fresh(&fetched(""), now));
+    }
+}
@@ -0,0 +1,289 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::summary::{Outcome, StepResult};
+use crate::core::trust;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory in the dotfiles repo holding one `<point>.d/` directory per hook point
+pub const HOOKS_DIR: &str = "hooks";
+
+/// When a set of repo-defined hook scripts runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    PreSetup,
+    PostSetup,
+    PreMigrate,
+    PostMigrate,
+}
+
+impl HookPoint {
+    pub const ALL: [HookPoint; 4] = [
+        HookPoint::PreSetup,
+        HookPoint::PostSetup,
+        HookPoint::PreMigrate,
+        HookPoint::PostMigrate,
+    ];
+
+    /// Returns the hook point's name, e.g. `pre-setup`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookPoint::PreSetup => "pre-setup",
+            HookPoint::PostSetup => "post-setup",
+            HookPoint::PreMigrate => "pre-migrate",
+            HookPoint::PostMigrate => "post-migrate",
+        }
+    }
+
+    /// Returns the directory holding this point's scripts
+    pub fn dir(&self, dotfiles_dir: &Path) -> PathBuf {
+        dotfiles_dir
+            .join(HOOKS_DIR)
+            .join(format!("{}.d", self.name()))
+    }
+}
+
+/// One hook script's result and everything it printed
+#[derive(Debug, Clone)]
+pub struct HookRun {
+    pub step: StepResult,
+    pub output: String,
+}
+
+/// Returns the executable scripts for `point`, in file name order
+///
+/// Hidden files, files without an executable bit (a README, a disabled
+/// hook), and `skipped` hooks (`<point>.d/<script>`, filtered out for this
+/// machine by `[hooks.when]`) are left out.
+pub fn find(dotfiles_dir: &Path, point: HookPoint, skipped: &[String]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(point.dir(dotfiles_dir)) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            !path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        })
+        .filter(|path| is_executable(path))
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            !skipped.contains(&format!("{}.d/{}", point.name(), name))
+        })
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Runs the scripts for `point` one after another, stopping at the first failure
+///
+/// Each script runs from the dotfiles repo with `DOTFILES_DIR` and `DRY_RUN`
+/// (`1` or `0`) set, so it can honor a dry run itself. Scripts are approved
+/// like other repo-defined commands, by content, before their first run.
+pub fn run(
+    dotfiles_dir: &Path,
+    home: &Path,
+    point: HookPoint,
+    skipped: &[String],
+    dry_run: bool,
+) -> Vec<HookRun> {
+    let mut runs = Vec::new();
+    for script in find(dotfiles_dir, point, skipped) {
+        let run = run_script(&script, dotfiles_dir, home, point, dry_run);
+        let failed = run.step.outcome == Outcome::Failed;
+        runs.push(run);
+        if failed {
+            break;
+        }
+    }
+    runs
+}
+
+fn run_script(
+    script: &Path,
+    dotfiles_dir: &Path,
+    home: &Path,
+    point: HookPoint,
+    dry_run: bool,
+) -> HookRun {
+    let file_name = script
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let key = format!("hook:{}.d/{}", point.name(), file_name);
+    let name = format!("Hook: {}/{}", point.name(), file_name);
+    let finish = |step: StepResult| HookRun {
+        step,
+        output: String::new(),
+    };
+
+    let content = match fs::read(script) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(e) => return finish(StepResult::failed(name, e.to_string())),
+    };
+    match trust::ensure_trusted(home, &key, &content) {
+        Ok(true) => {}
+        Ok(false) => return finish(StepResult::skipped(name, "not approved")),
+        Err(e) => {
+            return finish(
+                StepResult::failed(name, e.to_string())
+                    .with_action("Run again in a terminal to review the hook, or pass --trust-all"),
+            )
+        }
+    }
+
+    execute(script, &name, dotfiles_dir, dry_run)
+}
+
+/// Runs an approved script and captures its output
+fn execute(script: &Path, name: &str, dotfiles_dir: &Path, dry_run: bool) -> HookRun {
+    let output = CommandRunner::new(script)
+        .env("DOTFILES_DIR", dotfiles_dir)
+        .env("DRY_RUN", if dry_run { "1" } else { "0" })
+        .current_dir(dotfiles_dir)
+        .timeout(command::INSTALL_TIMEOUT)
+        .output();
+    match output {
+        Ok(output) => {
+            let printed = format!("{}{}", output.stdout, output.stderr);
+            let step = if output.success() {
+                StepResult::ok(name)
+            } else {
+                let last = printed.lines().rev().find(|line| !line.trim().is_empty());
+                StepResult::failed(name, last.unwrap_or("exited unsuccessfully").trim())
+                    .with_action(format!(
+                        "Fix {} and re-run ({})",
+                        script.display(),
+                        output.status
+                    ))
+            };
+            HookRun {
+                step,
+                output: printed,
+            }
+        }
+        Err(e) => HookRun {
+            step: StepResult::failed(name, e.to_string()),
+            output: String::new(),
+        },
+    }
+}
+
+/// Renders what each hook printed, for the end of the run's report
+pub fn render_output(runs: &[HookRun]) -> String {
+    let mut rendered = String::new();
+    for run in runs.iter().filter(|run| !run.output.trim().is_empty()) {
+        rendered.push_str(&format!("  {}\n", run.step.name));
+        for line in run.output.trim_end().lines() {
+            rendered.push_str(&format!("    {}\n", line));
+        }
+    }
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn write_hook(dotfiles: &Path, point: HookPoint, name: &str, script: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = point.dir(dotfiles);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(name), script).unwrap();
+        fs::set_permissions(dir.join(name), fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_orders_executable_scripts() {
+        let temp = TempDir::new().unwrap();
+        let point = HookPoint::PostSetup;
+        write_hook(temp.path(), point, "20-second", "#!/bin/sh\n", 0o755);
+        write_hook(temp.path(), point, "10-first", "#!/bin/sh\n", 0o755);
+        write_hook(temp.path(), point, "README", "docs\n", 0o644);
+        write_hook(temp.path(), point, ".30-hidden", "#!/bin/sh\n", 0o755);
+
+        let dir = point.dir(temp.path());
+        assert_eq!(
+            find(temp.path(), point, &[]),
+            vec![dir.join("10-first"), dir.join("20-second")]
+        );
+        assert!(find(temp.path(), HookPoint::PreSetup, &[]).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_skips_hooks_for_other_tags() {
+        let temp = TempDir::new().unwrap();
+        let point = HookPoint::PostSetup;
+        write_hook(temp.path(), point, "10-common", "#!/bin/sh\n", 0o755);
+        write_hook(temp.path(), point, "20-mas", "#!/bin/sh\n", 0o755);
+
+        let config: crate::core::config::Config = toml::from_str(
+            "dotfiles_dir = \"/x\"\nxdg_config_home = \"/x/.config\"\nlanguage_manager = \"Asdf\"\nsymlink_method = \"Stow\"\ninstall_oh_my_zsh = false\ntags = [\"server\"]\n\n[hooks.when]\n\"post-setup.d/20-mas\" = \"laptop\"\n\"post-setup.d/10-common\" = \"!laptop\"\n",
+        )
+        .unwrap();
+        let skipped = config.skipped_hooks();
+        assert_eq!(skipped, vec!["post-setup.d/20-mas"]);
+
+        let dir = point.dir(temp.path());
+        assert_eq!(
+            find(temp.path(), point, &skipped),
+            vec![dir.join("10-common")]
+        );
+        // The same name under another point is not affected
+        write_hook(
+            temp.path(),
+            HookPoint::PreSetup,
+            "20-mas",
+            "#!/bin/sh\n",
+            0o755,
+        );
+        assert_eq!(find(temp.path(), HookPoint::PreSetup, &skipped).len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_passes_environment_and_output() {
+        let temp = TempDir::new().unwrap();
+        let point = HookPoint::PreMigrate;
+        write_hook(
+            temp.path(),
+            point,
+            "10-env",
+            "#!/bin/sh\necho \"dry=$DRY_RUN dir=$DOTFILES_DIR\"\n",
+            0o755,
+        );
+        write_hook(
+            temp.path(),
+            point,
+            "20-fail",
+            "#!/bin/sh\necho 'tap failed' >&2\nexit 3\n",
+            0o755,
+        );
+        let dir = point.dir(temp.path());
+
+        let ok = execute(&dir.join("10-env"), "env", temp.path(), true);
+        assert_eq!(ok.step.outcome, Outcome::Ok);
+        assert_eq!(ok.output, format!("dry=1 dir={}\n", temp.path().display()));
+
+        let failed = execute(&dir.join("20-fail"), "fail", temp.path(), false);
+        assert_eq!(failed.step.outcome, Outcome::Failed);
+        assert_eq!(failed.step.detail.as_deref(), Some("tap failed"));
+        assert_eq!(render_output(&[ok, failed]).lines().count(), 4);
+    }
+}
@@ -1,3 +1,21 @@
+pub mod audit;
+pub mod cancel;
+pub mod command;
 pub mod config;
+pub mod context;
+pub mod exit;
+pub mod github;
+pub mod hooks;
 pub mod logger;
+pub mod notify;
+pub mod paths;
+pub mod pool;
 pub mod prompt;
+pub mod quiet;
+pub mod state;
+pub mod sudo;
+pub mod summary;
+pub mod timing;
+pub mod trust;
+pub mod userspace;
+pub mod walk;
@@ -0,0 +1,217 @@
+use crate::core::command::{CommandRunner, NETWORK_TIMEOUT, QUICK_TIMEOUT};
+use crate::core::config::{Config, NotifySettings};
+use crate::detect::os::detect_os;
+use crate::error::Result;
+use crate::install::machine::{self, Setting};
+use std::path::Path;
+
+/// Set to `1` by whatever schedules a run (the sync watch agent, a cron entry) to opt into `[notify]`
+pub const UNATTENDED_VAR: &str = "DOTFILES_UNATTENDED";
+
+/// Where sendmail lives on macOS and most Linux distributions
+const SENDMAIL_PATHS: &[&str] = &["/usr/sbin/sendmail", "/usr/lib/sendmail"];
+
+/// Shows a desktop notification (osascript on macOS, notify-send elsewhere)
+pub fn notify(title: &str, message: &str) -> Result<()> {
+    let runner = if cfg!(target_os = "macos") {
+        CommandRunner::new("osascript").args([
+            "-e",
+            &format!(
+                "display notification {} with title {}",
+                applescript_string(message),
+                applescript_string(title)
+            ),
+        ])
+    } else {
+        CommandRunner::new("notify-send").args(["--app-name=dotfiles", title, message])
+    };
+
+    runner.timeout(QUICK_TIMEOUT).stream(false).run()?;
+    Ok(())
+}
+
+/// Sends a message to every sink configured in `[notify]`
+///
+/// Returns a description of each sink that failed; the others still get
+/// the message.
+pub fn send(settings: &NotifySettings, title: &str, message: &str) -> Vec<String> {
+    let mut failures = Vec::new();
+    if settings.desktop {
+        if let Err(e) = notify(title, message) {
+            failures.push(format!("desktop: {}", e));
+        }
+    }
+    if let Some(url) = &settings.webhook {
+        if let Err(e) = webhook(url, title, message) {
+            failures.push(format!("webhook: {}", e));
+        }
+    }
+    if let Some(to) = &settings.email {
+        if let Err(e) = email(to, title, message) {
+            failures.push(format!("email: {}", e));
+        }
+    }
+    failures
+}
+
+/// Returns true if `DOTFILES_UNATTENDED` marks this as a scheduled run
+///
+/// A missing terminal alone doesn't count: pipes, CI, and editors run
+/// without one while someone is still watching the output.
+pub fn is_unattended() -> bool {
+    std::env::var(UNATTENDED_VAR).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// Notifies the `[notify]` sinks, but only for runs marked unattended
+///
+/// Meant for commands run from launchd, cron, or systemd, whose failures
+/// would otherwise only end up in a log file.
+pub fn notify_unattended(title: &str, message: &str) {
+    if !is_unattended() {
+        return;
+    }
+    let Some(config) = crate::core::paths::home_dir()
+        .and_then(|home| Config::load(&home.join(".dotfiles.conf")).ok())
+    else {
+        return;
+    };
+    if config.notify.is_empty() {
+        return;
+    }
+
+    let host = machine::current(Setting::Hostname, detect_os()).unwrap_or_default();
+    let title = if host.is_empty() {
+        title.to_string()
+    } else {
+        format!("{} ({})", title, host)
+    };
+    for failure in send(&config.notify, &title, message) {
+        eprintln!("Warning: could not send notification via {}", failure);
+    }
+}
+
+/// POSTs a JSON message to a webhook
+///
+/// The URL usually carries a token, so it goes to curl as a config file on
+/// stdin rather than on the command line, where `ps` would show it.
+pub fn webhook(url: &str, title: &str, message: &str) -> Result<()> {
+    CommandRunner::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--max-time",
+            "20",
+            "--header",
+            "Content-Type: application/json",
+            "--data",
+            &webhook_payload(title, message),
+            "--config",
+            "-",
+        ])
+        .stdin(curl_config("url", url))
+        .timeout(NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// A `key = "value"` line for a curl config file, escaped the way curl reads it
+fn curl_config(key: &str, value: &str) -> String {
+    let mut quoted = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    format!("{} = \"{}\"\n", key, quoted)
+}
+
+/// Builds the webhook body; `text` is what Slack and compatible services display
+fn webhook_payload(title: &str, message: &str) -> String {
+    serde_json::json!({
+        "text": format!("{}\n{}", title, message),
+        "title": title,
+        "message": message,
+    })
+    .to_string()
+}
+
+/// Emails a message through the local sendmail
+pub fn email(to: &str, title: &str, message: &str) -> Result<()> {
+    let sendmail = SENDMAIL_PATHS
+        .iter()
+        .find(|path| Path::new(path).exists())
+        .copied()
+        .unwrap_or("sendmail");
+
+    CommandRunner::new(sendmail)
+        .arg("-t")
+        .stdin(email_message(to, title, message))
+        .timeout(NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Formats a plain-text email for `sendmail -t`
+fn email_message(to: &str, title: &str, message: &str) -> String {
+    // Header values must stay on one line
+    let subject = title.replace(['\r', '\n'], " ");
+    format!(
+        "To: {}\nSubject: {}\nContent-Type: text/plain; charset=utf-8\n\n{}\n",
+        to.trim(),
+        subject,
+        message
+    )
+}
+
+/// Quotes a string as an AppleScript literal
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_formats() {
+        let payload: serde_json::Value = serde_json::from_str(&webhook_payload(
+            "dotfiles sync failed",
+            "pull: \"conflict\"",
+        ))
+        .unwrap();
+        assert_eq!(payload["text"], "dotfiles sync failed\npull: \"conflict\"");
+        assert_eq!(payload["title"], "dotfiles sync failed");
+
+        assert_eq!(
+            email_message("me@example.com", "dotfiles\nfailed", "details"),
+            "To: me@example.com\nSubject: dotfiles failed\n\
+             Content-Type: text/plain; charset=utf-8\n\ndetails\n"
+        );
+        assert!(send(&NotifySettings::default(), "title", "message").is_empty());
+    }
+
+    #[test]
+    fn test_curl_config() {
+        assert_eq!(
+            curl_config("url", "https://hooks.example.com/T0/B1?x=\"a\\b\""),
+            "url = \"https://hooks.example.com/T0/B1?x=\\\"a\\\\b\\\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_applescript_string() {
+        assert_eq!(
+            applescript_string(r#"run "dotfiles sync""#),
+            r#""run \"dotfiles sync\"""#
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crate::error::{DotfilesError, Result};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Environment variable equivalent of `--home`
+pub const HOME_OVERRIDE_VAR: &str = "DOTFILES_HOME_OVERRIDE";
+
+/// Environment variable equivalent of `--target-dir`
+pub const LINK_ROOT_VAR: &str = "DOTFILES_TARGET_DIR";
+
+static HOME_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+static LINK_ROOT_OVERRIDE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Points every home directory lookup at `dir` instead of the real home (`--home`)
+pub fn set_home_override(dir: Option<PathBuf>) {
+    *HOME_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// Returns the home directory everything should read and write under
+///
+/// `--home` wins, then `DOTFILES_HOME_OVERRIDE`, then the user's real home.
+/// Use this instead of `dirs::home_dir()` so a sandboxed run never touches
+/// the real home.
+pub fn home_dir() -> Option<PathBuf> {
+    if let Some(dir) = HOME_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        return Some(dir);
+    }
+    match std::env::var_os(HOME_OVERRIDE_VAR) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::home_dir(),
+    }
+}
+
+/// XDG base directories at their defaults under `home`
+///
+/// Exported along with `HOME` under `--home`, so that neither this tool nor
+/// the programs it runs (git, nvim, brew, ...) reach into the real home
+/// through an inherited `XDG_*_HOME`.
+pub fn xdg_dirs(home: &Path) -> [(&'static str, PathBuf); 4] {
+    [
+        ("XDG_CONFIG_HOME", home.join(".config")),
+        ("XDG_DATA_HOME", home.join(".local/share")),
+        ("XDG_STATE_HOME", home.join(".local/state")),
+        ("XDG_CACHE_HOME", home.join(".cache")),
+    ]
+}
+
+/// Links into `dir` instead of the home directory (`--target-dir`)
+///
+/// Unlike `--home`, the config, state, and shell files stay where they are.
+pub fn set_link_root_override(dir: Option<PathBuf>) {
+    *LINK_ROOT_OVERRIDE.lock().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// Returns the link root from `--target-dir` or `DOTFILES_TARGET_DIR`, if either is set
+pub fn link_root_override() -> Option<PathBuf> {
+    if let Some(dir) = LINK_ROOT_OVERRIDE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        return Some(dir);
+    }
+    std::env::var_os(LINK_ROOT_VAR)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Checks a `--home` directory and returns it as an absolute path
+pub fn resolve_home_override(dir: &Path) -> Result<PathBuf> {
+    if !dir.is_dir() {
+        return Err(DotfilesError::Config(format!(
+            "--home {} is not a directory; create it first",
+            dir.display()
+        )));
+    }
+    Ok(dir.canonicalize()?)
+}
+
+/// Checks a `--target-dir` directory and returns it as an absolute path
+pub fn resolve_link_root(dir: &Path) -> Result<PathBuf> {
+    if !dir.is_dir() {
+        return Err(DotfilesError::Config(format!(
+            "--target-dir {} is not a directory; create it first",
+            dir.display()
+        )));
+    }
+    Ok(dir.canonicalize()?)
+}
+
+/// Expands `~`, `$VAR`, and `${VAR}` in a user-supplied path and tidies it up
+///
+/// Used for paths from prompts, the config file, and command-line flags.
+/// `~` and `$HOME` follow `--home`. `.` components are dropped and `..` is
+/// folded where it can be; a relative path stays relative. A variable that
+/// isn't set is an error rather than an empty string, so `$DOTFILES/zsh`
+/// never quietly becomes `/zsh`.
+pub fn expand(path: &Path) -> Result<PathBuf> {
+    let home = home_dir();
+    expand_with(path, home.as_deref(), |name| std::env::var(name).ok())
+}
+
+fn expand_with(
+    path: &Path,
+    home: Option<&Path>,
+    var: impl Fn(&str) -> Option<String>,
+) -> Result<PathBuf> {
+    // Non-UTF-8 paths can't contain anything to expand in a form we'd recognize
+    let Some(input) = path.to_str() else {
+        return Ok(normalize(path));
+    };
+    let home_str = || {
+        home.map(|home| home.to_string_lossy().into_owned())
+            .ok_or_else(|| {
+                DotfilesError::Config(format!(
+                    "Could not determine home directory to expand {}",
+                    input
+                ))
+            })
+    };
+    let lookup = |name: &str| -> Result<String> {
+        if name == "HOME" {
+            return home_str();
+        }
+        var(name).ok_or_else(|| {
+            DotfilesError::Config(format!("{} uses ${}, which is not set", input, name))
+        })
+    };
+
+    let mut expanded = String::new();
+    let mut rest = input;
+    if rest == "~" || rest.starts_with("~/") {
+        expanded.push_str(&home_str()?);
+        rest = &rest[1..];
+    } else if rest.starts_with('~') {
+        return Err(DotfilesError::Config(format!(
+            "{}: ~user paths are not supported; write the full path",
+            input
+        )));
+    }
+
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(braced) = after.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| DotfilesError::Config(format!("{} has an unclosed ${{", input)))?;
+            expanded.push_str(&lookup(&braced[..end])?);
+            rest = &braced[end + 1..];
+            continue;
+        }
+        let len = after
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        if len == 0 || after.starts_with(|c: char| c.is_ascii_digit()) {
+            // A lone `$` is just a character
+            expanded.push('$');
+            rest = after;
+        } else {
+            expanded.push_str(&lookup(&after[..len])?);
+            rest = &after[len..];
+        }
+    }
+    expanded.push_str(rest);
+
+    Ok(normalize(Path::new(&expanded)))
+}
+
+/// Shows a path under `home_dir` as `~/...`, and `home_dir` itself as `~`
+pub fn display_path(path: &Path, home_dir: &Path) -> String {
+    match path.strip_prefix(home_dir) {
+        Ok(rel) if rel.as_os_str().is_empty() => "~".to_string(),
+        Ok(rel) => format!("~/{}", rel.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Drops `.` components and folds `..` without touching the filesystem
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) => {}
+                _ => normalized.push(".."),
+            },
+            other => normalized.push(other),
+        }
+    }
+    if normalized.as_os_str().is_empty() && !path.as_os_str().is_empty() {
+        normalized.push(".");
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_path() {
+        let home = Path::new("/home/me");
+        assert_eq!(display_path(Path::new("/home/me/.zshrc"), home), "~/.zshrc");
+        assert_eq!(display_path(home, home), "~");
+        assert_eq!(display_path(Path::new("/etc/hosts"), home), "/etc/hosts");
+    }
+
+    #[test]
+    fn test_xdg_dirs_stay_under_home() {
+        let home = Path::new("/tmp/sandbox");
+        for (var, dir) in xdg_dirs(home) {
+            assert!(var.starts_with("XDG_"));
+            assert!(dir.starts_with(home), "{} escapes the sandbox", var);
+        }
+    }
+
+    #[test]
+    fn test_resolve_home_override() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let resolved = resolve_home_override(temp.path()).unwrap();
+        assert!(resolved.is_absolute());
+        assert_eq!(resolved, temp.path().canonicalize().unwrap());
+        assert!(resolve_home_override(&temp.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_expand() {
+        let home = Path::new("/home/me");
+        let var = |name: &str| (name == "DOTFILES").then(|| "/src/dots".to_string());
+        let expand = |path: &str| expand_with(Path::new(path), Some(home), var);
+
+        assert_eq!(
+            expand("~/Development/dotfiles").unwrap(),
+            PathBuf::from("/home/me/Development/dotfiles")
+        );
+        assert_eq!(expand("~").unwrap(), PathBuf::from("/home/me"));
+        assert_eq!(
+            expand("$HOME/.config/").unwrap(),
+            PathBuf::from("/home/me/.config")
+        );
+        assert_eq!(
+            expand("${DOTFILES}/zsh/../nvim/./init.lua").unwrap(),
+            PathBuf::from("/src/dots/nvim/init.lua")
+        );
+        assert_eq!(expand("/cost$/x").unwrap(), PathBuf::from("/cost$/x"));
+        assert_eq!(expand("../dots").unwrap(), PathBuf::from("../dots"));
+        assert_eq!(expand("/..").unwrap(), PathBuf::from("/"));
+
+        let err = expand("$UNSET_VAR/zsh").unwrap_err().to_string();
+        assert!(err.contains("$UNSET_VAR, which is not set"), "{}", err);
+        assert!(expand("${DOTFILES").is_err());
+        assert!(expand("~bob/dotfiles").is_err());
+        assert!(expand_with(Path::new("~/x"), None, var).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::core::cancel;
+use crate::error::{DotfilesError, Result};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Upper bound on workers, so package queries don't flood Homebrew
+const MAX_WORKERS: usize = 8;
+
+/// Progress of one job in a pool, reported from the worker running it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress<'a> {
+    Started(&'a str),
+    Finished {
+        name: &'a str,
+        ok: bool,
+        elapsed: Duration,
+    },
+    /// Not started because the run was cancelled
+    Skipped(&'a str),
+}
+
+/// Runs independent jobs on a fixed set of scoped worker threads
+///
+/// Jobs are taken in order; results come back in input order. Once the
+/// cancellation flag is set, jobs that have not started are skipped.
+#[derive(Debug, Clone, Copy)]
+pub struct Pool {
+    workers: usize,
+    cancelled: &'static AtomicBool,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pool {
+    /// Creates a pool sized to the machine, cancelled by Ctrl-C
+    pub fn new() -> Self {
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(MAX_WORKERS);
+        Self {
+            workers,
+            cancelled: &cancel::CANCELLED,
+        }
+    }
+
+    /// Sets the number of worker threads (at least one)
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Uses another cancellation flag instead of the Ctrl-C one
+    pub fn cancelled_by(mut self, flag: &'static AtomicBool) -> Self {
+        self.cancelled = flag;
+        self
+    }
+
+    /// Applies `f` to every item concurrently
+    ///
+    /// For quick queries that cannot fail; cancellation is left to the
+    /// commands `f` runs.
+    pub fn map<T, R, F>(&self, items: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        self.execute(items, |item| Some(f(item)))
+            .into_iter()
+            .map(|result| result.expect("map jobs always run"))
+            .collect()
+    }
+
+    /// Runs a named, fallible job per item, reporting progress as jobs start and finish
+    ///
+    /// Jobs skipped after cancellation return `DotfilesError::Cancelled`.
+    pub fn run<T, R, N, F, P>(&self, items: &[T], name: N, f: F, progress: P) -> Vec<Result<R>>
+    where
+        T: Sync,
+        R: Send,
+        N: Fn(&T) -> String + Sync,
+        F: Fn(&T) -> Result<R> + Sync,
+        P: Fn(Progress) + Sync,
+    {
+        let results = self.execute(items, |item| {
+            let name = name(item);
+            if self.cancelled.load(Ordering::SeqCst) {
+                progress(Progress::Skipped(&name));
+                return None;
+            }
+
+            progress(Progress::Started(&name));
+            let started = Instant::now();
+            let result = f(item);
+            progress(Progress::Finished {
+                name: &name,
+                ok: result.is_ok(),
+                elapsed: started.elapsed(),
+            });
+            Some(result)
+        });
+
+        results
+            .into_iter()
+            .zip(items)
+            .map(|(result, item)| {
+                result.unwrap_or_else(|| Err(DotfilesError::Cancelled(name(item))))
+            })
+            .collect()
+    }
+
+    /// Hands items to workers in order, collecting `None` for jobs that did not run
+    fn execute<T, R, F>(&self, items: &[T], job: F) -> Vec<Option<R>>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> Option<R> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let slots: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(items.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = job(item);
+                    *slots[index].lock().unwrap() = result;
+                });
+            }
+        });
+
+        slots
+            .into_iter()
+            .map(|slot| slot.into_inner().unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_keeps_input_order() {
+        let items: Vec<u64> = (0..20).collect();
+        let doubled = Pool::new().workers(4).map(&items, |n| {
+            thread::sleep(Duration::from_millis(20 - n));
+            n * 2
+        });
+        assert_eq!(doubled, (0..20).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_run_overlaps_jobs() {
+        let items = vec![1, 2, 3, 4];
+        let started = Instant::now();
+        let results = Pool::new().workers(4).run(
+            &items,
+            |n| format!("job {}", n),
+            |_| {
+                thread::sleep(Duration::from_millis(100));
+                Ok(())
+            },
+            |_| {},
+        );
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert!(started.elapsed() < Duration::from_millis(350));
+    }
+
+    #[test]
+    fn test_run_skips_after_cancel() {
+        static FLAG: AtomicBool = AtomicBool::new(false);
+        let items = vec!["a", "b", "c"];
+        let skipped = Mutex::new(Vec::new());
+
+        let results = Pool::new().workers(1).cancelled_by(&FLAG).run(
+            &items,
+            |item| item.to_string(),
+            |item| {
+                if *item == "a" {
+                    FLAG.store(true, Ordering::SeqCst);
+                    Ok(())
+                } else {
+                    Err(DotfilesError::CommandFailed(item.to_string()))
+                }
+            },
+            |event| {
+                if let Progress::Skipped(name) = event {
+                    skipped.lock().unwrap().push(name.to_string());
+                }
+            },
+        );
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(DotfilesError::Cancelled(ref n)) if n == "b"));
+        assert!(matches!(results[2], Err(DotfilesError::Cancelled(_))));
+        assert_eq!(*skipped.lock().unwrap(), vec!["b", "c"]);
+    }
+}
@@ -1,33 +1,84 @@
-use crate::core::config::LanguageManager;
+use crate::core::config::{LanguageManager, MachineSettings, NvimDistro, NvimSettings};
+use crate::core::paths;
+use crate::detect::os::detect_os;
 use crate::error::Result;
-use dialoguer::{Confirm, Input, Select};
-use std::path::PathBuf;
+use crate::install::homebrew::PackageKind;
+use crate::install::machine::{self, Setting};
+use crate::install::packages;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::path::{Path, PathBuf};
 
-pub fn prompt_dotfiles_dir() -> Result<PathBuf> {
-    let default = dirs::home_dir()
-        .unwrap()
-        .join("Development")
-        .join("dotfiles");
+/// Returns the suggested location for the dotfiles repository
+pub fn default_dotfiles_dir(home: &Path) -> PathBuf {
+    home.join("Development").join("dotfiles")
+}
+
+pub fn prompt_dotfiles_dir(home: &Path) -> Result<PathBuf> {
+    let default = default_dotfiles_dir(home);
+
+    prompt_path("Dotfiles directory location", &default)
+}
+
+pub fn prompt_xdg_config_home(home: &Path) -> Result<PathBuf> {
+    let default = home.join(".config");
+
+    prompt_path("XDG_CONFIG_HOME location", &default)
+}
 
+/// Asks for a path, expanding `~` and `$VAR` and asking again if that fails
+fn prompt_path(prompt: &str, default: &Path) -> Result<PathBuf> {
     let path: String = Input::new()
-        .with_prompt("Dotfiles directory location")
+        .with_prompt(prompt)
         .default(default.to_string_lossy().to_string())
+        .validate_with(|input: &String| paths::expand(Path::new(input)).map(|_| ()))
         .interact_text()
         .map_err(|e| crate::error::DotfilesError::Config(e.to_string()))?;
 
-    Ok(PathBuf::from(path))
+    paths::expand(Path::new(&path))
 }
 
-pub fn prompt_xdg_config_home() -> Result<PathBuf> {
-    let default = dirs::home_dir().unwrap().join(".config");
+/// Asks which optional packages to install, listed by category with descriptions
+///
+/// Packages in `selected` start out checked.
+pub fn prompt_packages(selected: &[String]) -> Result<Vec<String>> {
+    let choices = packages::pickable_packages();
+    let items: Vec<String> = choices
+        .iter()
+        .map(|info| {
+            let cask = if info.kind == PackageKind::Cask {
+                " (cask)"
+            } else {
+                ""
+            };
+            format!(
+                "{:<13} {:<26} {}{}",
+                info.category.label(),
+                info.name,
+                info.description,
+                cask
+            )
+        })
+        .collect();
+    let defaults: Vec<bool> = choices
+        .iter()
+        .map(|info| selected.iter().any(|name| name == info.name))
+        .collect();
 
-    let path: String = Input::new()
-        .with_prompt("XDG_CONFIG_HOME location")
-        .default(default.to_string_lossy().to_string())
-        .interact_text()
+    let picked = MultiSelect::new()
+        .with_prompt("Optional packages (Space to select, Enter to continue)")
+        .items(&items)
+        .defaults(&defaults)
+        .interact()
         .map_err(|e| crate::error::DotfilesError::Config(e.to_string()))?;
 
-    Ok(PathBuf::from(path))
+    // Keep hand-added entries that the picker doesn't list
+    let mut packages: Vec<String> = selected
+        .iter()
+        .filter(|name| !choices.iter().any(|info| info.name == name.as_str()))
+        .cloned()
+        .collect();
+    packages.extend(picked.into_iter().map(|i| choices[i].name.to_string()));
+    Ok(packages)
 }
 
 pub fn prompt_language_manager() -> Result<LanguageManager> {
@@ -47,6 +98,80 @@ pub fn prompt_language_manager() -> Result<LanguageManager> {
     })
 }
 
+/// Asks for the computer name and timezone, defaulting to configured or current values
+///
+/// A value is only kept if it was already configured or differs from the
+/// machine's current one, so pressing Enter leaves the setting unmanaged.
+pub fn prompt_machine_identity(configured: &MachineSettings) -> Result<MachineSettings> {
+    let os = detect_os();
+    let ask = |setting: Setting, label: &str, configured: &Option<String>| {
+        let current = machine::current(setting, os);
+        let mut input = Input::<String>::new().with_prompt(label).allow_empty(true);
+        if let Some(default) = configured.clone().or_else(|| current.clone()) {
+            input = input.default(default);
+        }
+        if setting == Setting::Timezone {
+            input = input.validate_with(|zone: &String| {
+                if zone.is_empty() || machine::is_known_timezone(zone) {
+                    Ok(())
+                } else {
+                    Err(format!("{} is not in {}", zone, machine::ZONEINFO_DIR))
+                }
+            });
+        }
+        let value = input
+            .interact_text()
+            .map_err(|e| crate::error::DotfilesError::Config(e.to_string()))?;
+        Ok::<_, crate::error::DotfilesError>(
+            (!value.is_empty() && (configured.is_some() || current.as_ref() != Some(&value)))
+                .then_some(value),
+        )
+    };
+
+    Ok(MachineSettings {
+        hostname: ask(Setting::Hostname, "Computer name", &configured.hostname)?,
+        timezone: ask(Setting::Timezone, "Timezone", &configured.timezone)?,
+        locale: configured.locale.clone(),
+    })
+}
+
+/// Asks which nvim config to install when the dotfiles repo doesn't ship one
+pub fn prompt_nvim_distro() -> Result<NvimSettings> {
+    let options = vec![
+        "LazyVim",
+        "kickstart.nvim",
+        "My own (git repository)",
+        "None",
+    ];
+    let selection = Select::new()
+        .with_prompt("Neovim config")
+        .items(&options)
+        .default(0)
+        .interact()
+        .map_err(|e| crate::error::DotfilesError::Config(e.to_string()))?;
+
+    Ok(match selection {
+        0 => NvimSettings {
+            distro: Some(NvimDistro::LazyVim),
+            url: None,
+        },
+        1 => NvimSettings {
+            distro: Some(NvimDistro::Kickstart),
+            url: None,
+        },
+        2 => NvimSettings {
+            distro: Some(NvimDistro::Own),
+            url: Some(
+                Input::<String>::new()
+                    .with_prompt("Repository URL")
+                    .interact_text()
+                    .map_err(|e| crate::error::DotfilesError::Config(e.to_string()))?,
+            ),
+        },
+        _ => NvimSettings::default(),
+    })
+}
+
 pub fn confirm_install_deps() -> Result<bool> {
     Confirm::new()
         .with_prompt("Install missing dependencies?")
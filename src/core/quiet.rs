@@ -0,0 +1,72 @@
+use std::io::Write;
+
+/// Keeps stdout and stderr pointed at /dev/null until dropped
+///
+/// Works at the file-descriptor level, so output from child processes
+/// (brew, git, ...) is silenced too.
+pub struct Silenced {
+    #[cfg(unix)]
+    saved: [libc::c_int; 2],
+}
+
+/// Redirects stdout and stderr to /dev/null, for `--quiet`
+///
+/// Returns None (leaving output alone) if the redirect cannot be set up.
+#[cfg(unix)]
+pub fn silence() -> Option<Silenced> {
+    use std::os::fd::AsRawFd;
+
+    let null = std::fs::OpenOptions::new()
+        .write(true)
+        .open("/dev/null")
+        .ok()?;
+    flush();
+
+    // Dropping a partially set up guard restores whatever was redirected
+    let mut silenced = Silenced { saved: [-1; 2] };
+    for (slot, fd) in silenced
+        .saved
+        .iter_mut()
+        .zip([libc::STDOUT_FILENO, libc::STDERR_FILENO])
+    {
+        // SAFETY: dup/dup2 on the process's own standard descriptors
+        unsafe {
+            *slot = libc::dup(fd);
+            if *slot < 0 || libc::dup2(null.as_raw_fd(), fd) < 0 {
+                return None;
+            }
+        }
+    }
+
+    Some(silenced)
+}
+
+/// Output cannot be redirected here; `--quiet` only adds the status line
+#[cfg(not(unix))]
+pub fn silence() -> Option<Silenced> {
+    None
+}
+
+impl Drop for Silenced {
+    fn drop(&mut self) {
+        flush();
+        #[cfg(unix)]
+        for (saved, fd) in self
+            .saved
+            .iter()
+            .zip([libc::STDOUT_FILENO, libc::STDERR_FILENO])
+            .filter(|(saved, _)| **saved >= 0)
+        {
+            // SAFETY: restores descriptors saved by `silence`
+            unsafe {
+                libc::dup2(*saved, fd);
+                libc::close(*saved);
+            }
+        }
+    }
+}
+
+fn flush() {
+    let _ = std::io::stdout().flush();
+    let _ = std::io::stderr().flush();
+}
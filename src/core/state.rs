@@ -0,0 +1,127 @@
+use crate::error::{DotfilesError, Result};
+use crate::validate::cache::CachedCheck;
+use crate::validate::score::ScoreEntry;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Machine-local state the tool records between runs
+///
+/// Unlike `~/.dotfiles.conf`, nothing here is meant to be edited or shared
+/// between machines. Stored as JSON under `$XDG_STATE_HOME/dotfiles`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct State {
+    /// Checklist items marked done, with when they were marked
+    #[serde(default)]
+    pub checklist_done: BTreeMap<String, String>,
+    /// Fingerprint of each rendered template target, keyed by target path
+    #[serde(default)]
+    pub rendered: BTreeMap<String, String>,
+    /// Fingerprint of each copy-only file as last copied, keyed by target path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub copied: BTreeMap<String, String>,
+    /// Per-file doctor results, reused while the files are unchanged
+    #[serde(default)]
+    pub check_cache: BTreeMap<String, CachedCheck>,
+    /// Fingerprints of repo-defined commands the user approved, keyed by source
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub trusted_commands: BTreeMap<String, String>,
+    /// Setup completeness from recent doctor runs, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub score_history: Vec<ScoreEntry>,
+    /// When a Homebrew install started that hasn't finished yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homebrew_install_started: Option<String>,
+}
+
+impl State {
+    /// Loads state, returning the empty state if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                DotfilesError::Config(format!("Invalid state file {}: {}", path.display(), e))
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Writes state, creating the parent directory if needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Returns the state file path, honoring `XDG_STATE_HOME`
+pub fn state_path(home_dir: &Path) -> PathBuf {
+    state_dir(home_dir).join("state.json")
+}
+
+/// Returns the tool's directory under `XDG_STATE_HOME` (`~/.local/state/dotfiles`)
+pub fn state_dir(home_dir: &Path) -> PathBuf {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home_dir.join(".local/state"));
+    state_home.join("dotfiles")
+}
+
+/// SHA-256 of `content` as hex, for the fingerprints kept in [`State`]
+///
+/// Stable across toolchains and machines, since it is compared between runs.
+pub fn fingerprint(content: impl AsRef<[u8]>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_ref());
+    format!("{:x}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_is_default() {
+        let temp = TempDir::new().unwrap();
+        let state = State::load(&temp.path().join("state.json")).unwrap();
+        assert_eq!(state, State::default());
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("dotfiles/state.json");
+
+        let mut state = State::default();
+        state
+            .checklist_done
+            .insert("gh-auth".to_string(), "2026-01-01 10:00:00".to_string());
+        state.save(&path).unwrap();
+
+        assert_eq!(State::load(&path).unwrap(), state);
+    }
+
+    #[test]
+    fn test_fingerprint_is_sha256() {
+        assert_eq!(
+            fingerprint("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(fingerprint(b"abc"), fingerprint("abc"));
+    }
+
+    #[test]
+    fn test_load_invalid_reports_path() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("state.json");
+        std::fs::write(&path, "not json").unwrap();
+
+        let err = State::load(&path).unwrap_err().to_string();
+        assert!(err.contains("state.json"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::detect::os::OS;
+use crate::error::{DotfilesError, Result};
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the sudo timestamp is refreshed (sudo's default timeout is 5 minutes)
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Granularity for noticing that the keep-alive should stop
+const KEEPALIVE_TICK: Duration = Duration::from_millis(250);
+
+/// Planned setup steps that require elevated privileges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SudoStep {
+    /// The Homebrew installer creates its prefix with sudo
+    HomebrewInstall,
+    /// apt, dnf, and pacman install packages system-wide
+    PackageInstall,
+    /// Hostname, timezone, and system locale are system-wide settings
+    MachineIdentity,
+}
+
+impl SudoStep {
+    /// Returns a human-readable description of why sudo is needed
+    pub fn description(&self) -> &str {
+        match self {
+            SudoStep::HomebrewInstall => "Install Homebrew (creates the Homebrew prefix)",
+            SudoStep::PackageInstall => "Install packages with the system package manager",
+            SudoStep::MachineIdentity => "Set the hostname, timezone, or system locale",
+        }
+    }
+}
+
+/// Determines which planned steps will need sudo
+///
+/// `installs_homebrew` says whether setup will run the Homebrew installer
+/// (missing, and not replaced by `[packages] backend`). `package_sudo` says
+/// whether packages are missing and this machine's package manager needs
+/// sudo to install them (apt, dnf, or pacman, unless running as root).
+pub fn plan_steps(
+    os: OS,
+    installs_homebrew: bool,
+    package_sudo: bool,
+    identity_changes: bool,
+) -> Vec<SudoStep> {
+    let mut steps = Vec::new();
+
+    if os == OS::MacOS && installs_homebrew {
+        steps.push(SudoStep::HomebrewInstall);
+    }
+    if package_sudo {
+        steps.push(SudoStep::PackageInstall);
+    }
+    if identity_changes {
+        steps.push(SudoStep::MachineIdentity);
+    }
+
+    steps
+}
+
+/// The effective user id of this process
+#[cfg(unix)]
+pub fn effective_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+/// Returns true when running as root, where nothing needs sudo
+pub fn is_root() -> bool {
+    #[cfg(unix)]
+    return effective_uid() == 0;
+    #[cfg(not(unix))]
+    return false;
+}
+
+static NON_INTERACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Treats the session as non-interactive even on a terminal (`--quiet` hides prompts)
+pub fn set_non_interactive(non_interactive: bool) {
+    NON_INTERACTIVE.store(non_interactive, Ordering::Relaxed);
+}
+
+/// Returns true if stdin is attached to a terminal (so sudo can prompt)
+pub fn is_interactive() -> bool {
+    !NON_INTERACTIVE.load(Ordering::Relaxed) && std::io::stdin().is_terminal()
+}
+
+/// Returns true if sudo credentials are already cached
+pub fn has_cached_credentials() -> bool {
+    Command::new("sudo")
+        .arg("-n")
+        .arg("true")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Keeps the sudo timestamp fresh until dropped
+pub struct SudoKeepAlive {
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl SudoKeepAlive {
+    /// Starts refreshing the sudo timestamp in the background
+    pub fn start() -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = Arc::clone(&running);
+
+        let handle = thread::spawn(move || {
+            let mut elapsed = Duration::ZERO;
+            while flag.load(Ordering::Relaxed) {
+                thread::sleep(KEEPALIVE_TICK);
+                elapsed += KEEPALIVE_TICK;
+                if elapsed >= KEEPALIVE_INTERVAL {
+                    elapsed = Duration::ZERO;
+                    let _ = Command::new("sudo")
+                        .arg("-n")
+                        .arg("-v")
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status();
+                }
+            }
+        });
+
+        Self {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for SudoKeepAlive {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Prompts for sudo once up front if any planned step needs it
+///
+/// Returns a keep-alive guard that holds the sudo timestamp for the rest of the
+/// run, or None if no step needs elevation. Fails early when sudo is needed but
+/// there is no terminal to prompt on and no cached credentials.
+pub fn acquire(steps: &[SudoStep]) -> Result<Option<SudoKeepAlive>> {
+    if steps.is_empty() {
+        return Ok(None);
+    }
+
+    if !has_cached_credentials() {
+        let reasons: Vec<&str> = steps.iter().map(|s| s.description()).collect();
+
+        if !is_interactive() {
+            return Err(DotfilesError::SudoRequired(format!(
+                "{} (run `sudo -v` before this command in non-interactive mode)",
+                reasons.join(", ")
+            )));
+        }
+
+        println!("The following steps need administrator privileges:");
+        for reason in &reasons {
+            println!("  - {}", reason);
+        }
+
+        let status = Command::new("sudo").arg("-v").status()?;
+        if !status.success() {
+            return Err(DotfilesError::SudoRequired(
+                "sudo authentication failed".to_string(),
+            ));
+        }
+    }
+
+    Ok(Some(SudoKeepAlive::start()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_steps_homebrew_missing_on_macos() {
+        let steps = plan_steps(OS::MacOS, true, false, false);
+        assert_eq!(steps, vec![SudoStep::HomebrewInstall]);
+    }
+
+    #[test]
+    fn test_plan_steps_nothing_needed() {
+        assert!(plan_steps(OS::MacOS, false, false, false).is_empty());
+        assert!(plan_steps(OS::Linux, true, false, false).is_empty());
+    }
+
+    #[test]
+    fn test_plan_steps_machine_identity() {
+        assert_eq!(
+            plan_steps(OS::Linux, false, false, true),
+            vec![SudoStep::MachineIdentity]
+        );
+    }
+
+    #[test]
+    fn test_plan_steps_apt() {
+        assert_eq!(
+            plan_steps(OS::Linux, false, true, true),
+            vec![SudoStep::PackageInstall, SudoStep::MachineIdentity]
+        );
+    }
+
+    #[test]
+    fn test_acquire_no_steps() {
+        assert!(acquire(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_keepalive_stops_on_drop() {
+        let keepalive = SudoKeepAlive::start();
+        let running = Arc::clone(&keepalive.running);
+        drop(keepalive);
+        assert!(!running.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_set_non_interactive() {
+        set_non_interactive(true);
+        assert!(!is_interactive());
+        set_non_interactive(false);
+    }
+
+    #[test]
+    fn test_step_description() {
+        assert!(SudoStep::HomebrewInstall.description().contains("Homebrew"));
+    }
+}
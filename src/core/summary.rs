@@ -0,0 +1,279 @@
+use crate::core::exit::ExitStatus;
+use crate::core::timing::{self, format_duration};
+use crate::symlink::SymlinkReport;
+use colored::Colorize;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Outcome of a single setup step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Ok,
+    Skipped,
+    Failed,
+}
+
+impl Outcome {
+    /// Returns the label shown in the summary table
+    pub fn label(&self) -> &str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Skipped => "skipped",
+            Outcome::Failed => "failed",
+        }
+    }
+}
+
+/// Result of one setup step, as shown in the summary table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepResult {
+    pub name: String,
+    pub outcome: Outcome,
+    #[serde(rename = "duration_ms", serialize_with = "timing::serialize_millis")]
+    pub duration: Duration,
+    /// Short explanation (what changed, why it was skipped, or the error)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// What the user should do next, if anything
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    /// Existing files that blocked symlinks in this step
+    #[serde(skip)]
+    pub conflicts: usize,
+}
+
+impl StepResult {
+    fn new(name: impl Into<String>, outcome: Outcome) -> Self {
+        Self {
+            name: name.into(),
+            outcome,
+            duration: Duration::ZERO,
+            detail: None,
+            action: None,
+            conflicts: 0,
+        }
+    }
+
+    /// Creates a successful step
+    pub fn ok(name: impl Into<String>) -> Self {
+        Self::new(name, Outcome::Ok)
+    }
+
+    /// Creates a skipped step with the reason it was skipped
+    pub fn skipped(name: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::new(name, Outcome::Skipped).with_detail(reason)
+    }
+
+    /// Creates a failed step with the error that caused it
+    pub fn failed(name: impl Into<String>, error: impl Into<String>) -> Self {
+        Self::new(name, Outcome::Failed).with_detail(error)
+    }
+
+    /// Builds a step result from a symlink report
+    ///
+    /// Conflicts fail the step; the report's wall time becomes the duration.
+    pub fn from_symlink_report(name: impl Into<String>, report: &SymlinkReport) -> Self {
+        let result = if report.is_success() {
+            Self::ok(name)
+        } else {
+            Self::failed(name, format!("{} conflicts", report.conflicts.len()))
+                .with_action("Move conflicting files aside and re-run setup")
+        };
+
+        // Keeps the conflict count a failed step starts with
+        let detail = match &result.detail {
+            Some(detail) => format!("{}; {}", detail, report.summary()),
+            None => report.summary(),
+        };
+        Self {
+            conflicts: report.conflicts.len(),
+            ..result.with_detail(detail).with_duration(report.wall_time)
+        }
+    }
+
+    /// Sets the explanation shown next to the outcome
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the follow-up action
+    pub fn with_action(mut self, action: impl Into<String>) -> Self {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Sets the step duration
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+}
+
+/// Collects step results over a setup run and renders them as a table
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SetupSummary {
+    pub steps: Vec<StepResult>,
+}
+
+impl SetupSummary {
+    /// Creates an empty summary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a step result as-is
+    pub fn add(&mut self, step: StepResult) {
+        self.steps.push(step);
+    }
+
+    /// Runs a step, timing it unless the step reported its own duration
+    pub fn run(&mut self, step: impl FnOnce() -> StepResult) {
+        let (mut result, elapsed) = timing::measure(step);
+        if result.duration.is_zero() {
+            result.duration = elapsed;
+        }
+        self.add(result);
+    }
+
+    /// Returns true if any step failed
+    pub fn has_failures(&self) -> bool {
+        self.steps.iter().any(|s| s.outcome == Outcome::Failed)
+    }
+
+    /// Maps the run to an exit status: symlink conflicts only, or any other failure
+    pub fn exit_status(&self) -> ExitStatus {
+        let failed = self.steps.iter().filter(|s| s.outcome == Outcome::Failed);
+        failed.fold(ExitStatus::Ok, |status, step| {
+            status.worst(if step.conflicts > 0 {
+                ExitStatus::Conflicts
+            } else {
+                ExitStatus::Errors
+            })
+        })
+    }
+
+    /// Returns the number of steps with the given outcome
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.steps.iter().filter(|s| s.outcome == outcome).count()
+    }
+
+    /// Renders the summary as an aligned table
+    pub fn render(&self) -> String {
+        let name_width = self
+            .steps
+            .iter()
+            .map(|s| s.name.chars().count())
+            .chain(std::iter::once("Step".len()))
+            .max()
+            .unwrap_or(0);
+        let outcome_width = "skipped".len();
+        let time_width = self
+            .steps
+            .iter()
+            .map(|s| format_duration(s.duration).len())
+            .chain(std::iter::once("Time".len()))
+            .max()
+            .unwrap_or(0);
+
+        let mut output = format!(
+            "  {:<name_width$}  {:<outcome_width$}  {:>time_width$}  {}\n",
+            "Step", "Outcome", "Time", "Follow-up"
+        )
+        .bold()
+        .to_string();
+
+        for step in &self.steps {
+            let outcome = format!("{:<outcome_width$}", step.outcome.label());
+            let outcome = match step.outcome {
+                Outcome::Ok => outcome.green(),
+                Outcome::Skipped => outcome.yellow(),
+                Outcome::Failed => outcome.red(),
+            };
+
+            let follow_up = match (&step.action, &step.detail, step.outcome) {
+                (Some(action), _, _) => action.clone(),
+                (None, Some(detail), Outcome::Skipped | Outcome::Failed) => detail.clone(),
+                _ => "-".to_string(),
+            };
+
+            output.push_str(&format!(
+                "  {:<name_width$}  {}  {:>time_width$}  {}\n",
+                step.name,
+                outcome,
+                format_duration(step.duration),
+                follow_up
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symlink::SymlinkStatus;
+    use crate::test_support::strip_ansi;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_run_times_steps() {
+        let mut summary = SetupSummary::new();
+        summary.run(|| StepResult::ok("Homebrew"));
+        summary.run(|| StepResult::ok("Symlinks").with_duration(Duration::from_secs(2)));
+
+        assert_eq!(summary.steps.len(), 2);
+        assert_eq!(summary.steps[1].duration, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_failures_and_counts() {
+        let mut summary = SetupSummary::new();
+        summary.add(StepResult::ok("Homebrew"));
+        summary.add(StepResult::skipped("Languages", "none selected"));
+        assert!(!summary.has_failures());
+
+        summary.add(StepResult::failed("TPM", "clone failed"));
+        assert!(summary.has_failures());
+        assert_eq!(summary.count(Outcome::Skipped), 1);
+    }
+
+    #[test]
+    fn test_from_symlink_report_with_conflicts() {
+        let mut report = SymlinkReport::new();
+        report.add(SymlinkStatus::Conflict {
+            target: PathBuf::from("/home/user/.zshrc"),
+            reason: "File already exists".to_string(),
+        });
+        report.wall_time = Duration::from_millis(300);
+
+        let step = StepResult::from_symlink_report("Symlinks", &report);
+        assert_eq!(step.outcome, Outcome::Failed);
+        assert_eq!(step.duration, Duration::from_millis(300));
+        assert!(step.action.is_some());
+        let detail = step.detail.unwrap();
+        assert!(detail.starts_with("1 conflicts; Created: 0"), "{}", detail);
+    }
+
+    #[test]
+    fn test_render_shows_follow_up() {
+        let mut summary = SetupSummary::new();
+        summary.add(StepResult::ok("Homebrew"));
+        summary.add(
+            StepResult::failed("Languages", "python failed")
+                .with_action("Run: mise install python"),
+        );
+        summary.add(StepResult::skipped("Claude repo", "already cloned"));
+
+        let table = strip_ansi(&summary.render());
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("Outcome"));
+        assert!(lines[1].trim_end().ends_with('-'));
+        assert!(lines[2].contains("failed") && lines[2].contains("Run: mise install python"));
+        assert!(lines[3].contains("skipped") && lines[3].contains("already cloned"));
+    }
+}
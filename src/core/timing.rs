@@ -0,0 +1,268 @@
+use serde::{Serialize, Serializer};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long a single step or item took, and optionally how much data it covered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Timing {
+    pub name: String,
+    #[serde(rename = "duration_ms", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    /// Size in bytes of the item (e.g. a linked directory tree), when known
+    #[serde(rename = "size_bytes", skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl Timing {
+    /// Creates a timing entry without size information
+    pub fn new(name: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            name: name.into(),
+            duration,
+            size: None,
+        }
+    }
+
+    /// Attaches a size in bytes
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Formats the entry as `name (1.2s, 3.4 MB)`
+    pub fn display(&self) -> String {
+        match self.size {
+            Some(size) => format!(
+                "{} ({}, {})",
+                self.name,
+                format_duration(self.duration),
+                format_size(size)
+            ),
+            None => format!("{} ({})", self.name, format_duration(self.duration)),
+        }
+    }
+}
+
+/// Runs `f` and returns its result together with the elapsed time
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let started = Instant::now();
+    let value = f();
+    (value, started.elapsed())
+}
+
+/// Returns the `n` slowest entries, slowest first
+pub fn slowest(timings: &[Timing], n: usize) -> Vec<&Timing> {
+    let mut sorted: Vec<&Timing> = timings.iter().collect();
+    sorted.sort_by_key(|t| std::cmp::Reverse(t.duration));
+    sorted.truncate(n);
+    sorted
+}
+
+/// Formats a duration for humans (`850ms`, `1.4s`, `2m05s`)
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        let secs = duration.as_secs();
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Formats how long ago something happened (`just now`, `5 minutes ago`, `2 days ago`)
+pub fn format_age(elapsed: chrono::TimeDelta) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+
+    let secs = elapsed.num_seconds();
+    let (count, unit) = if secs < MINUTE {
+        return "just now".to_string();
+    } else if secs < HOUR {
+        (secs / MINUTE, "minute")
+    } else if secs < DAY {
+        (secs / HOUR, "hour")
+    } else if secs < 30 * DAY {
+        (secs / DAY, "day")
+    } else if secs < 365 * DAY {
+        (secs / (30 * DAY), "month")
+    } else {
+        (secs / (365 * DAY), "year")
+    };
+    format!(
+        "{} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+/// Formats a byte count for humans (`512 B`, `4.0 KB`, `1.2 MB`)
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Returns the total size of regular files under `path` without following symlinks
+pub fn disk_size(path: &Path) -> u64 {
+    sized(path, &mut None)
+}
+
+/// Returns the size of each path, counting a hard-linked file only the first time it is seen
+///
+/// Incremental backups hard-link unchanged files to the previous backup, so
+/// summing `disk_size` over them would count the shared data once per backup.
+/// Each path is charged only for files no earlier path holds, which is what
+/// deleting it (after the earlier ones are kept) would free.
+pub fn disk_sizes<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Vec<u64> {
+    let mut seen = Some(HashSet::new());
+    paths
+        .into_iter()
+        .map(|path| sized(path, &mut seen))
+        .collect()
+}
+
+/// Sums file sizes below `path`, skipping files whose (device, inode) is in `seen`
+fn sized(path: &Path, seen: &mut Option<HashSet<(u64, u64)>>) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+
+    if metadata.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| sized(&entry.path(), seen))
+                    .sum()
+            })
+            .unwrap_or(0)
+    } else if metadata.is_file() {
+        let counted = match (seen.as_mut(), file_id(&metadata)) {
+            (Some(seen), Some(id)) => !seen.insert(id),
+            _ => false,
+        };
+        if counted {
+            0
+        } else {
+            metadata.len()
+        }
+    } else {
+        0
+    }
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Serializes a duration as whole milliseconds
+pub fn serialize_millis<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(1400)), "1.4s");
+        assert_eq!(format_duration(Duration::from_secs(125)), "2m05s");
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(4096), "4.0 KB");
+        assert_eq!(format_size(1024 * 1024 * 3 / 2), "1.5 MB");
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(chrono::TimeDelta::seconds(-5)), "just now");
+        assert_eq!(format_age(chrono::TimeDelta::minutes(1)), "1 minute ago");
+        assert_eq!(format_age(chrono::TimeDelta::hours(5)), "5 hours ago");
+        assert_eq!(format_age(chrono::TimeDelta::days(2)), "2 days ago");
+        assert_eq!(format_age(chrono::TimeDelta::days(400)), "1 year ago");
+    }
+
+    #[test]
+    fn test_slowest() {
+        let timings = vec![
+            Timing::new("fast", Duration::from_millis(10)),
+            Timing::new("slow", Duration::from_millis(500)),
+            Timing::new("medium", Duration::from_millis(100)),
+        ];
+
+        let names: Vec<&str> = slowest(&timings, 2)
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["slow", "medium"]);
+    }
+
+    #[test]
+    fn test_disk_size() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("nvim/lua")).unwrap();
+        fs::write(temp.path().join("nvim/init.lua"), "1234").unwrap();
+        fs::write(temp.path().join("nvim/lua/plugins.lua"), "123456").unwrap();
+
+        assert_eq!(disk_size(&temp.path().join("nvim")), 10);
+        assert_eq!(disk_size(&temp.path().join("missing")), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_disk_sizes_count_hard_links_once() {
+        let temp = TempDir::new().unwrap();
+        let newer = temp.path().join("newer");
+        let older = temp.path().join("older");
+        fs::create_dir_all(&newer).unwrap();
+        fs::create_dir_all(&older).unwrap();
+        fs::write(newer.join("shared"), "1234").unwrap();
+        fs::hard_link(newer.join("shared"), older.join("shared")).unwrap();
+        fs::write(older.join("own"), "123456").unwrap();
+
+        assert_eq!(disk_size(&older), 10);
+        assert_eq!(disk_sizes([newer.as_path(), older.as_path()]), vec![4, 6]);
+    }
+
+    #[test]
+    fn test_timing_serializes_millis() {
+        let timing = Timing::new("brew", Duration::from_millis(1500)).with_size(42);
+        let json = serde_json::to_string(&timing).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"brew","duration_ms":1500,"size_bytes":42}"#
+        );
+    }
+}
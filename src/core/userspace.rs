@@ -0,0 +1,23 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable equivalent of `--userspace-only`
+pub const USERSPACE_ONLY_VAR: &str = "DOTFILES_USERSPACE_ONLY";
+
+static USERSPACE_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Turns off every system-level step for this run (`--userspace-only`)
+pub fn set_userspace_only(userspace_only: bool) {
+    USERSPACE_ONLY.store(userspace_only, Ordering::Relaxed);
+}
+
+/// Returns true if `--userspace-only` or `DOTFILES_USERSPACE_ONLY` asks for it
+///
+/// `userspace_only = true` in `~/.dotfiles.conf` is folded in when the
+/// config is loaded, so code with a `Config` should read that instead.
+pub fn forced() -> bool {
+    USERSPACE_ONLY.load(Ordering::Relaxed)
+        || std::env::var(USERSPACE_ONLY_VAR).is_ok_and(|value| value == "1" || value == "true")
+}
+
+/// Reason shown for steps skipped in userspace-only mode
+pub const SKIP_REASON: &str = "userspace-only mode";
@@ -0,0 +1,457 @@
+use crate::error::Result;
+use crate::symlink::EXCLUSIONS;
+use ignore::{DirEntry, WalkBuilder};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Per-repo ignore file, using `.gitignore` syntax
+///
+/// Lists files that should stay in the repo but never be linked, scanned, or
+/// validated (e.g. notes, scripts, machine-specific leftovers).
+pub const IGNORE_FILE: &str = ".dotfilesignore";
+
+/// GNU Stow's per-package ignore file: one Perl regex per line
+///
+/// Honored here too, so the manual backend and conflict detection skip
+/// exactly what stow skips.
+pub const STOW_IGNORE_FILE: &str = ".stow-local-ignore";
+
+/// Stow's built-in ignore list, used for a package without a `.stow-local-ignore`
+const STOW_DEFAULT_IGNORE: &str = r"RCS
+.+,v
+CVS
+\.\#.+
+\.cvsignore
+\.svn
+_darcs
+\.hg
+\.git
+\.gitignore
+\.gitmodules
+.+~
+\#.*\#
+^/README.*
+^/LICENSE.*
+^/COPYING
+";
+
+/// Names skipped at any depth, regardless of ignore files
+pub(crate) const ALWAYS_SKIPPED: &[&str] = &[".git", ".DS_Store"];
+
+/// Walks a dotfiles directory the same way for every scanner
+///
+/// By default honors `.gitignore`, `.git/info/exclude`, `.dotfilesignore`,
+/// Stow's ignore rules (see [`StowIgnores`]), and the built-in
+/// [`EXCLUSIONS`] (top level only). Hidden files are included, symlinks are not followed, and
+/// entries come back sorted by path.
+#[derive(Debug, Clone)]
+pub struct RepoWalker {
+    root: PathBuf,
+    max_depth: Option<usize>,
+    respect_ignores: bool,
+    nested: bool,
+    skipped: Vec<String>,
+}
+
+impl RepoWalker {
+    /// Creates a recursive walker rooted at `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            max_depth: None,
+            respect_ignores: true,
+            nested: false,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Limits how deep to descend (1 = direct children only)
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets whether ignore files and exclusions apply (default: true)
+    ///
+    /// Turn off for operations that must see everything, such as backups.
+    pub fn respect_ignores(mut self, respect: bool) -> Self {
+        self.respect_ignores = respect;
+        self
+    }
+
+    /// Treats the root as a directory inside a repo (e.g. a Stow package)
+    /// rather than the repo itself, so [`EXCLUSIONS`] don't apply to its children
+    pub fn nested(mut self) -> Self {
+        self.nested = true;
+        self
+    }
+
+    /// Skips these direct children too, on top of the ignore files
+    ///
+    /// Used for entries that only belong on some machines (see `Config::skipped_links`).
+    pub fn skip(mut self, names: &[String]) -> Self {
+        self.skipped.extend(names.iter().cloned());
+        self
+    }
+
+    /// Returns every file and directory below the root (the root itself excluded)
+    pub fn entries(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.walk()?.into_iter().map(DirEntry::into_path).collect())
+    }
+
+    /// Returns the regular files below the root, following file symlinks
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .walk()?
+            .into_iter()
+            .map(DirEntry::into_path)
+            .filter(|path| path.is_file())
+            .collect())
+    }
+
+    /// Returns the names of direct children that the walker skips
+    pub fn ignored_children(&self) -> Result<Vec<String>> {
+        let kept = self.clone().max_depth(1).entries()?;
+        let mut ignored: Vec<String> = std::fs::read_dir(&self.root)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| !kept.contains(path))
+            .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .collect();
+        ignored.sort();
+        Ok(ignored)
+    }
+
+    fn walk(&self) -> Result<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+
+        for result in self.builder().build() {
+            match result {
+                Ok(entry) if entry.depth() == 0 => {}
+                Ok(entry) => entries.push(entry),
+                Err(err) => {
+                    // A malformed ignore pattern only drops that pattern
+                    if let Some(err) = err.into_io_error() {
+                        return Err(err.into());
+                    }
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn builder(&self) -> WalkBuilder {
+        let respect = self.respect_ignores;
+        let nested = self.nested;
+        let skipped = self.skipped.clone();
+        let stow_ignores = StowIgnores::new(&self.root);
+        let mut builder = WalkBuilder::new(&self.root);
+        builder
+            .standard_filters(false)
+            .git_ignore(respect)
+            .git_exclude(respect)
+            .require_git(false)
+            .follow_links(false)
+            .max_depth(self.max_depth)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .filter_entry(move |entry| {
+                let excluded = respect
+                    && (is_excluded(entry, nested) || stow_ignores.is_ignored(entry.path()));
+                !excluded && !is_skipped(entry, &skipped)
+            });
+        if respect {
+            builder.add_custom_ignore_filename(IGNORE_FILE);
+        }
+        builder
+    }
+}
+
+/// Stow's ignore rules for every package under a walker root
+///
+/// The root is a package, and so is any directory below it with its own
+/// `.stow-local-ignore` (e.g. `.config`, which is stowed on its own when
+/// linked into a separate config home). As with Stow, a package's file
+/// replaces the built-in list rather than adding to it, and applies to
+/// everything in the package.
+#[derive(Debug)]
+struct StowIgnores {
+    root: PathBuf,
+    defaults: StowIgnore,
+    /// Each directory's `.stow-local-ignore`, loaded the first time it's needed
+    local: Mutex<HashMap<PathBuf, Option<StowIgnore>>>,
+}
+
+impl StowIgnores {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            defaults: StowIgnore::parse(STOW_DEFAULT_IGNORE),
+            local: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn is_ignored(&self, path: &Path) -> bool {
+        if path.file_name() == Some(STOW_IGNORE_FILE.as_ref()) {
+            return true;
+        }
+        let mut local = self.local.lock().unwrap_or_else(|e| e.into_inner());
+        for package in path.ancestors().skip(1) {
+            if !package.starts_with(&self.root) {
+                break;
+            }
+            let ignore = local
+                .entry(package.to_path_buf())
+                .or_insert_with(|| StowIgnore::load(package));
+            if let (Some(ignore), Ok(rel)) = (ignore, path.strip_prefix(package)) {
+                return ignore.is_ignored(rel);
+            }
+        }
+        path.strip_prefix(&self.root)
+            .is_ok_and(|rel| self.defaults.is_ignored(rel))
+    }
+}
+
+/// Patterns from a `.stow-local-ignore`, matched the way Stow matches them
+///
+/// Patterns containing `/` match the whole path below the root, written with
+/// a leading `/` (e.g. `^/README.*`); the rest match any single file or
+/// directory name. Either way a pattern has to match all of it.
+#[derive(Debug, Clone, Default)]
+struct StowIgnore {
+    path: Option<Regex>,
+    segment: Option<Regex>,
+}
+
+impl StowIgnore {
+    fn load(package: &Path) -> Option<Self> {
+        std::fs::read_to_string(package.join(STOW_IGNORE_FILE))
+            .ok()
+            .map(|content| Self::parse(&content))
+    }
+
+    fn parse(content: &str) -> Self {
+        let mut paths = Vec::new();
+        let mut segments = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // Comments may follow a pattern; `\#` is a literal #
+            let pattern = line
+                .split_once(" #")
+                .map_or(line, |(pattern, _)| pattern)
+                .trim_end()
+                .replace("\\#", "#");
+            // A malformed pattern only drops that pattern
+            if Regex::new(&pattern).is_err() {
+                continue;
+            }
+            if pattern.contains('/') {
+                paths.push(pattern);
+            } else {
+                segments.push(pattern);
+            }
+        }
+
+        let anchored = |patterns: Vec<String>| {
+            (!patterns.is_empty())
+                .then(|| Regex::new(&format!("^(?:{})$", patterns.join("|"))).ok())
+                .flatten()
+        };
+        Self {
+            path: anchored(paths),
+            segment: anchored(segments),
+        }
+    }
+
+    fn is_ignored(&self, rel: &Path) -> bool {
+        let path = format!("/{}", rel.to_string_lossy().replace('\\', "/"));
+        let name = rel
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        self.path.as_ref().is_some_and(|re| re.is_match(&path))
+            || self.segment.as_ref().is_some_and(|re| re.is_match(&name))
+    }
+}
+
+fn is_excluded(entry: &DirEntry, nested: bool) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    ALWAYS_SKIPPED.contains(&name.as_ref())
+        || (!nested && entry.depth() == 1 && EXCLUSIONS.contains(&name.as_ref()))
+}
+
+fn is_skipped(entry: &DirEntry, skipped: &[String]) -> bool {
+    entry.depth() == 1
+        && skipped
+            .iter()
+            .any(|name| entry.file_name().to_string_lossy() == name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn repo() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".git/HEAD"), "").unwrap();
+        fs::create_dir_all(root.join(".config/nvim")).unwrap();
+        fs::write(root.join(".config/nvim/init.lua"), "").unwrap();
+        fs::write(root.join(".config/nvim/.DS_Store"), "").unwrap();
+        fs::write(root.join(".zshrc"), "").unwrap();
+        fs::write(root.join("README.md"), "").unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("debug.log"), "").unwrap();
+        fs::write(root.join(IGNORE_FILE), "notes/\n").unwrap();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join("notes/todo.txt"), "").unwrap();
+        temp
+    }
+
+    fn relative(root: &Path, paths: Vec<PathBuf>) -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_files_honor_ignores_and_exclusions() {
+        let temp = repo();
+        let files = RepoWalker::new(temp.path()).files().unwrap();
+
+        assert_eq!(
+            relative(temp.path(), files),
+            vec![".config/nvim/init.lua", ".zshrc"]
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let temp = repo();
+        let entries = RepoWalker::new(temp.path()).max_depth(1).entries().unwrap();
+
+        assert_eq!(relative(temp.path(), entries), vec![".config", ".zshrc"]);
+    }
+
+    #[test]
+    fn test_skip_applies_to_direct_children_only() {
+        let temp = repo();
+        fs::write(temp.path().join(".config/.zshrc"), "").unwrap();
+        let entries = RepoWalker::new(temp.path())
+            .skip(&[".zshrc".to_string()])
+            .entries()
+            .unwrap();
+
+        assert_eq!(
+            relative(temp.path(), entries),
+            vec![
+                ".config",
+                ".config/.zshrc",
+                ".config/nvim",
+                ".config/nvim/init.lua"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_root_keeps_excluded_names() {
+        let temp = repo();
+        let package = temp.path().join("git");
+        fs::create_dir_all(package.join("hooks")).unwrap();
+        fs::write(package.join("hooks/pre-commit"), "").unwrap();
+        fs::write(package.join(".DS_Store"), "").unwrap();
+
+        assert!(RepoWalker::new(&package).files().unwrap().is_empty());
+        assert_eq!(
+            relative(
+                &package,
+                RepoWalker::new(&package).nested().files().unwrap()
+            ),
+            vec!["hooks/pre-commit"]
+        );
+    }
+
+    #[test]
+    fn test_without_ignores_sees_everything() {
+        let temp = repo();
+        let entries = RepoWalker::new(temp.path())
+            .respect_ignores(false)
+            .max_depth(1)
+            .entries()
+            .unwrap();
+
+        assert_eq!(entries.len(), 8);
+    }
+
+    #[test]
+    fn test_ignored_children() {
+        let temp = repo();
+        assert_eq!(
+            RepoWalker::new(temp.path()).ignored_children().unwrap(),
+            vec![
+                ".dotfilesignore",
+                ".git",
+                ".gitignore",
+                "README.md",
+                "debug.log",
+                "notes"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stow_local_ignore() {
+        let temp = repo();
+        let root = temp.path();
+        fs::write(root.join(".config/nvim/lazy-lock.json"), "").unwrap();
+        fs::write(root.join(".zshrc.bak"), "").unwrap();
+        fs::write(
+            root.join(STOW_IGNORE_FILE),
+            "# Kept out of $HOME\n.+\\.bak\n^/\\.config/nvim/lazy-lock\\.json  # machine-specific\n(unclosed\n",
+        )
+        .unwrap();
+
+        let files = RepoWalker::new(root).files().unwrap();
+        assert_eq!(
+            relative(root, files),
+            vec![".config/nvim/init.lua", ".gitignore", ".zshrc"]
+        );
+
+        // A package's own file applies below it, instead of the one above
+        fs::write(root.join(".config/.gitignore"), "").unwrap();
+        fs::write(
+            root.join(".config").join(STOW_IGNORE_FILE),
+            "^/nvim/init\\.lua\n",
+        )
+        .unwrap();
+        let files = RepoWalker::new(root).files().unwrap();
+        assert_eq!(
+            relative(root, files),
+            vec![
+                ".config/.gitignore",
+                ".config/nvim/lazy-lock.json",
+                ".gitignore",
+                ".zshrc"
+            ]
+        );
+
+        // Names match whole, not as substrings
+        let ignore = StowIgnore::parse("nvim\n");
+        assert!(ignore.is_ignored(Path::new(".config/nvim")));
+        assert!(!ignore.is_ignored(Path::new(".config/nvim-old")));
+    }
+
+    #[test]
+    fn test_missing_root_is_an_error() {
+        assert!(RepoWalker::new("/nonexistent/dotfiles").entries().is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::detect::os::LinuxFamily;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// The package manager that install suggestions are written for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageBackend {
+    /// Homebrew; holds the full path when brew is not on PATH
+    Brew(Option<PathBuf>),
+    Apt,
+    Dnf,
+    Pacman,
+    Unknown,
+}
+
+/// Package names that differ from the Homebrew formula, per backend
+///
+/// `None` means the package is not in that distro's default repositories.
+struct Rename {
+    formula: &'static str,
+    apt: Option<&'static str>,
+    dnf: Option<&'static str>,
+    pacman: Option<&'static str>,
+}
+
+const RENAMES: &[Rename] = &[
+    Rename {
+        formula: "nvim",
+        apt: Some("neovim"),
+        dnf: Some("neovim"),
+        pacman: Some("neovim"),
+    },
+    Rename {
+        formula: "fd",
+        apt: Some("fd-find"),
+        dnf: Some("fd-find"),
+        pacman: Some("fd"),
+    },
+    Rename {
+        formula: "awscli",
+        apt: Some("awscli"),
+        dnf: Some("awscli2"),
+        pacman: Some("aws-cli"),
+    },
+    Rename {
+        formula: "obsidian",
+        apt: None,
+        dnf: None,
+        pacman: Some("obsidian"),
+    },
+    Rename {
+        formula: "k9s",
+        apt: None,
+        dnf: None,
+        pacman: Some("k9s"),
+    },
+    Rename {
+        formula: "krew",
+        apt: None,
+        dnf: None,
+        pacman: None,
+    },
+    Rename {
+        formula: "kubernetes-cli",
+        apt: None,
+        dnf: Some("kubernetes-client"),
+        pacman: Some("kubectl"),
+    },
+    Rename {
+        formula: "tree-sitter",
+        apt: None,
+        dnf: Some("tree-sitter-cli"),
+        pacman: Some("tree-sitter-cli"),
+    },
+    Rename {
+        formula: "mise",
+        apt: None,
+        dnf: None,
+        pacman: Some("mise"),
+    },
+    Rename {
+        formula: "opentofu",
+        apt: None,
+        dnf: None,
+        pacman: Some("opentofu"),
+    },
+    Rename {
+        formula: "terraform",
+        apt: None,
+        dnf: None,
+        pacman: Some("terraform"),
+    },
+];
+
+/// Commands Debian and Ubuntu install under another name, to avoid clashing with older packages
+const APT_COMMANDS: &[(&str, &str)] = &[("fd", "fdfind"), ("bat", "batcat")];
+
+impl PackageBackend {
+    /// Detects the backend for this machine (cached for the rest of the run)
+    ///
+    /// Homebrew wins wherever it is installed, including Linuxbrew; otherwise
+    /// the manager of the distro family in `/etc/os-release`, falling back to
+    /// the first of apt, dnf, and pacman found on PATH.
+    pub fn detect() -> &'static PackageBackend {
+        static BACKEND: OnceLock<PackageBackend> = OnceLock::new();
+        BACKEND.get_or_init(|| {
+            let on_path = crate::detect::tools::is_installed;
+            let family = match crate::detect::os::linux_family() {
+                Some(LinuxFamily::Debian) => Some((PackageBackend::Apt, "apt-get")),
+                Some(LinuxFamily::Fedora) => Some((PackageBackend::Dnf, "dnf")),
+                Some(LinuxFamily::Arch) => Some((PackageBackend::Pacman, "pacman")),
+                None => None,
+            };
+            if on_path("brew") {
+                PackageBackend::Brew(None)
+            } else if let Some(brew) = crate::install::homebrew::get_brew_path() {
+                PackageBackend::Brew(Some(brew))
+            } else if let Some((backend, _)) = family.filter(|(_, tool)| on_path(tool)) {
+                backend
+            } else if on_path("apt-get") {
+                PackageBackend::Apt
+            } else if on_path("dnf") {
+                PackageBackend::Dnf
+            } else if on_path("pacman") {
+                PackageBackend::Pacman
+            } else {
+                PackageBackend::Unknown
+            }
+        })
+    }
+
+    /// Returns the command that installs the given Homebrew formulae
+    ///
+    /// Formulae are translated to distro package names; any without a
+    /// distro package are listed separately for a manual install.
+    pub fn install_command<S: AsRef<str>>(&self, formulae: &[S]) -> String {
+        let formulae: Vec<&str> = formulae.iter().map(|f| f.as_ref()).collect();
+        let prefix = match self {
+            PackageBackend::Brew(None) => "brew install".to_string(),
+            PackageBackend::Brew(Some(path)) => format!("{} install", path.display()),
+            PackageBackend::Apt => "sudo apt install".to_string(),
+            PackageBackend::Dnf => "sudo dnf install".to_string(),
+            PackageBackend::Pacman => "sudo pacman -S".to_string(),
+            PackageBackend::Unknown => {
+                return format!("install {} with your package manager", formulae.join(" "));
+            }
+        };
+
+        let (packaged, manual): (Vec<_>, Vec<_>) = formulae
+            .iter()
+            .map(|formula| (*formula, self.package_name(formula)))
+            .partition(|(_, name)| name.is_some());
+
+        let mut command = String::new();
+        if !packaged.is_empty() {
+            let names: Vec<&str> = packaged.iter().filter_map(|(_, name)| *name).collect();
+            command = format!("{} {}", prefix, names.join(" "));
+        }
+        if !manual.is_empty() {
+            let names: Vec<&str> = manual.iter().map(|(formula, _)| *formula).collect();
+            if !command.is_empty() {
+                command.push_str(", then ");
+            }
+            command.push_str(&format!(
+                "install {} from upstream (not packaged for {})",
+                names.join(" "),
+                self.name()
+            ));
+        }
+        command
+    }
+
+    /// Returns the package name for a Homebrew formula, if the backend has one
+    pub fn package_name<'a>(&self, formula: &'a str) -> Option<&'a str> {
+        let rename = RENAMES.iter().find(|r| r.formula == formula);
+        match (self, rename) {
+            (PackageBackend::Brew(_) | PackageBackend::Unknown, _) | (_, None) => Some(formula),
+            (PackageBackend::Apt, Some(r)) => r.apt,
+            (PackageBackend::Dnf, Some(r)) => r.dnf,
+            (PackageBackend::Pacman, Some(r)) => r.pacman,
+        }
+    }
+
+    /// Returns the command a Homebrew formula's package puts on PATH
+    ///
+    /// The same as the formula except where a distro renames the binary,
+    /// like `batcat` for bat on Debian.
+    pub fn command_name<'a>(&self, formula: &'a str) -> &'a str {
+        match self {
+            PackageBackend::Apt => APT_COMMANDS
+                .iter()
+                .find(|(name, _)| *name == formula)
+                .map_or(formula, |(_, command)| command),
+            _ => formula,
+        }
+    }
+
+    /// Returns a short display name
+    pub fn name(&self) -> &str {
+        match self {
+            PackageBackend::Brew(_) => "Homebrew",
+            PackageBackend::Apt => "apt",
+            PackageBackend::Dnf => "dnf",
+            PackageBackend::Pacman => "pacman",
+            PackageBackend::Unknown => "unknown",
+        }
+    }
+}
+
+/// Returns the install command for this machine's package backend
+pub fn install_command<S: AsRef<str>>(formulae: &[S]) -> String {
+    PackageBackend::detect().install_command(formulae)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_command_per_backend() {
+        let pkgs = ["stow", "nvim", "fd"];
+        assert_eq!(
+            PackageBackend::Brew(None).install_command(&pkgs),
+            "brew install stow nvim fd"
+        );
+        assert_eq!(
+            PackageBackend::Brew(Some(PathBuf::from("/home/linuxbrew/.linuxbrew/bin/brew")))
+                .install_command(&["fd"]),
+            "/home/linuxbrew/.linuxbrew/bin/brew install fd"
+        );
+        assert_eq!(
+            PackageBackend::Apt.install_command(&pkgs),
+            "sudo apt install stow neovim fd-find"
+        );
+        assert_eq!(
+            PackageBackend::Pacman.install_command(&pkgs),
+            "sudo pacman -S stow neovim fd"
+        );
+    }
+
+    #[test]
+    fn test_command_name() {
+        assert_eq!(PackageBackend::Apt.command_name("bat"), "batcat");
+        assert_eq!(PackageBackend::Apt.command_name("fd"), "fdfind");
+        assert_eq!(PackageBackend::Apt.command_name("tmux"), "tmux");
+        assert_eq!(PackageBackend::Brew(None).command_name("bat"), "bat");
+    }
+
+    #[test]
+    fn test_install_command_unpackaged() {
+        assert_eq!(
+            PackageBackend::Apt.install_command(&["awscli", "opentofu"]),
+            "sudo apt install awscli, then install opentofu from upstream (not packaged for apt)"
+        );
+        assert_eq!(
+            PackageBackend::Dnf.install_command(&["mise"]),
+            "install mise from upstream (not packaged for dnf)"
+        );
+        assert_eq!(
+            PackageBackend::Unknown.install_command(&["tmux"]),
+            "install tmux with your package manager"
+        );
+    }
+}
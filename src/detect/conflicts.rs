@@ -1,12 +1,16 @@
 use crate::error::Result;
 use std::path::{Path, PathBuf};
 
-pub fn detect_conflicts(home: &Path) -> Result<Vec<PathBuf>> {
+pub fn detect_conflicts(home: &Path, xdg_config_home: &Path) -> Result<Vec<PathBuf>> {
     let mut conflicts = Vec::new();
-    let files = vec![".zshrc", ".tmux.conf", ".config/nvim", ".gitconfig"];
+    let files = vec![".zshrc", ".tmux.conf", ".gitconfig"];
 
-    for file in files {
-        let path = home.join(file);
+    let paths = files
+        .into_iter()
+        .map(|file| home.join(file))
+        .chain(std::iter::once(xdg_config_home.join("nvim")));
+
+    for path in paths {
         if path.exists() && !path.is_symlink() {
             conflicts.push(path);
         }
@@ -36,7 +40,7 @@ mod tests {
         #[cfg(unix)]
         std::os::unix::fs::symlink(&target, home.join(".tmux.conf")).unwrap();
 
-        let conflicts = detect_conflicts(home).unwrap();
+        let conflicts = detect_conflicts(home, &home.join(".config")).unwrap();
         assert_eq!(conflicts.len(), 1);
         assert!(conflicts[0].ends_with(".zshrc"));
     }
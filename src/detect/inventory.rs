@@ -0,0 +1,108 @@
+use crate::core::config::Config;
+use crate::core::paths::display_path;
+use crate::install::{packages, version_manager};
+use crate::symlink::expand_target;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What is actually installed and linked on this machine, for comparing machines
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Inventory {
+    /// Installed brew packages from the built-in groups
+    pub packages: Vec<String>,
+    /// Paths (with `~` for home) that are symlinks into the dotfiles repo
+    pub links: Vec<String>,
+    /// Installed runtimes as `<language> <version>`
+    pub runtimes: Vec<String>,
+}
+
+impl Inventory {
+    /// Gathers the inventory described by `config`
+    pub fn collect(config: &Config, home: &Path) -> Self {
+        let runtimes = match version_manager::detect() {
+            Some(vm) => config
+                .languages
+                .keys()
+                .filter_map(|name| crate::language::get_installer(name))
+                .flat_map(|installer| {
+                    let language = installer.language_name().to_string();
+                    version_manager::installed_versions(vm, &language)
+                        .into_iter()
+                        .map(move |version| format!("{} {}", language, version))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let mut packages = packages::installed_managed_packages(&config.packages);
+        packages.sort();
+
+        Self {
+            packages,
+            links: linked_paths(config, home),
+            runtimes,
+        }
+    }
+}
+
+/// Lists symlinks in home, the XDG config dir, and `[targets]` that point into the repo
+pub fn linked_paths(config: &Config, home: &Path) -> Vec<String> {
+    let mut candidates: Vec<PathBuf> = [home, config.xdg_config_home.as_path()]
+        .into_iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .collect();
+    candidates.extend(
+        config
+            .targets
+            .values()
+            .map(|target| expand_target(target, home)),
+    );
+
+    let mut links: Vec<String> = candidates
+        .into_iter()
+        .filter(|path| {
+            fs::read_link(path)
+                .map(|target| path.parent().unwrap_or(home).join(target))
+                .is_ok_and(|target| target.starts_with(&config.dotfiles_dir))
+        })
+        .map(|path| display_path(&path, home))
+        .collect();
+    links.sort();
+    links.dedup();
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_linked_paths() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().to_path_buf();
+        let dotfiles = home.join("dotfiles");
+        fs::create_dir_all(dotfiles.join("nvim")).unwrap();
+        fs::create_dir_all(home.join(".config")).unwrap();
+        fs::write(dotfiles.join(".zshrc"), "").unwrap();
+        fs::write(home.join("elsewhere"), "").unwrap();
+
+        std::os::unix::fs::symlink(dotfiles.join(".zshrc"), home.join(".zshrc")).unwrap();
+        std::os::unix::fs::symlink(dotfiles.join("nvim"), home.join(".config/nvim")).unwrap();
+        std::os::unix::fs::symlink(home.join("elsewhere"), home.join(".other")).unwrap();
+
+        let config = Config {
+            dotfiles_dir: dotfiles,
+            xdg_config_home: home.join(".config"),
+            ..Config::default()
+        };
+        assert_eq!(
+            linked_paths(&config, &home),
+            vec!["~/.config/nvim".to_string(), "~/.zshrc".to_string()]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+pub mod backend;
 pub mod conflicts;
+pub mod inventory;
 pub mod os;
+pub mod system;
 pub mod tools;
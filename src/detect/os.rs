@@ -13,10 +13,83 @@ pub fn detect_os() -> OS {
     }
 }
 
+/// Linux distribution families, by the package manager they share
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LinuxFamily {
+    /// Debian, Ubuntu, and derivatives (apt)
+    Debian,
+    /// Fedora, RHEL, CentOS, and derivatives (dnf)
+    Fedora,
+    /// Arch, Manjaro, EndeavourOS, and derivatives (pacman)
+    Arch,
+}
+
+/// Reads this machine's distro family from `/etc/os-release`
+///
+/// None when not on Linux or the distro isn't one of the known families.
+pub fn linux_family() -> Option<LinuxFamily> {
+    if detect_os() != OS::Linux {
+        return None;
+    }
+    std::fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|content| parse_os_release(&content))
+}
+
+/// Reads `key` from `/etc/os-release` content, with surrounding quotes removed
+pub fn os_release_value(content: &str, key: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// Maps `ID`, then `ID_LIKE` (for derivatives), to a family
+fn parse_os_release(content: &str) -> Option<LinuxFamily> {
+    let ids = os_release_value(content, "ID")
+        .into_iter()
+        .chain(os_release_value(content, "ID_LIKE"));
+
+    ids.flat_map(|ids| {
+        ids.split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    })
+    .find_map(|id| match id.as_str() {
+        "debian" | "ubuntu" => Some(LinuxFamily::Debian),
+        "fedora" | "rhel" | "centos" => Some(LinuxFamily::Fedora),
+        "arch" | "manjaro" => Some(LinuxFamily::Arch),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_os_release_value() {
+        let content = "NAME=\"Ubuntu\"\nPRETTY_NAME=\"Ubuntu 24.04 LTS\"\nID=ubuntu\n";
+        assert_eq!(
+            os_release_value(content, "PRETTY_NAME").as_deref(),
+            Some("Ubuntu 24.04 LTS")
+        );
+        assert_eq!(os_release_value(content, "ID").as_deref(), Some("ubuntu"));
+        assert_eq!(os_release_value(content, "ID_LIKE"), None);
+    }
+
+    #[test]
+    fn test_parse_os_release() {
+        let ubuntu = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\n";
+        assert_eq!(parse_os_release(ubuntu), Some(LinuxFamily::Debian));
+        let rocky = "ID=\"rocky\"\nID_LIKE=\"rhel centos fedora\"\n";
+        assert_eq!(parse_os_release(rocky), Some(LinuxFamily::Fedora));
+        let endeavour = "ID=endeavouros\nID_LIKE=arch\n";
+        assert_eq!(parse_os_release(endeavour), Some(LinuxFamily::Arch));
+        assert_eq!(parse_os_release("ID=alpine\n"), None);
+        assert_eq!(parse_os_release(""), None);
+    }
+
     #[test]
     fn test_detect_os() {
         let os = detect_os();
@@ -0,0 +1,178 @@
+use crate::core::command::{CommandRunner, QUICK_TIMEOUT};
+use crate::core::config::Config;
+use crate::detect::os::os_release_value;
+use crate::install::homebrew;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Machine context printed at the top of doctor output for triage
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemInfo {
+    pub os: String,
+    pub os_version: Option<String>,
+    pub arch: String,
+    pub shell: Option<String>,
+    pub brew_prefix: Option<String>,
+    pub dotfiles_dir: Option<String>,
+    /// Active `[profiles.<name>]`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Machine tags from config, which decide what setup installs and links
+    pub tags: Vec<String>,
+    pub tool_version: String,
+    /// When `dotfiles setup` last completed
+    pub last_setup: Option<String>,
+    /// When the dotfiles repo was last fetched or pulled
+    pub last_sync: Option<String>,
+}
+
+impl SystemInfo {
+    /// Gathers system information, using config for dotfiles paths when available
+    pub fn collect(config: Option<&Config>) -> Self {
+        let dotfiles_dir = config
+            .map(|c| c.dotfiles_dir.clone())
+            .unwrap_or_else(|| Config::default().dotfiles_dir);
+
+        Self {
+            os: std::env::consts::OS.to_string(),
+            os_version: os_version(),
+            arch: std::env::consts::ARCH.to_string(),
+            shell: std::env::var("SHELL").ok(),
+            brew_prefix: homebrew::prefix().map(|prefix| prefix.display().to_string()),
+            dotfiles_dir: Some(dotfiles_dir.display().to_string()),
+            profile: config.and_then(|c| c.profile.clone()),
+            tags: config.map(|c| c.tags.clone()).unwrap_or_default(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            last_setup: config.and_then(|c| c.last_setup.clone()),
+            last_sync: last_sync(&dotfiles_dir),
+        }
+    }
+
+    /// Renders the info as an aligned, colored block
+    pub fn render(&self) -> String {
+        let unknown = || "unknown".dimmed().to_string();
+        let os = match &self.os_version {
+            Some(version) => format!("{} {}", self.os, version),
+            None => self.os.clone(),
+        };
+
+        let rows = [
+            ("OS", os),
+            ("Arch", self.arch.clone()),
+            ("Shell", self.shell.clone().unwrap_or_else(unknown)),
+            (
+                "Brew prefix",
+                self.brew_prefix.clone().unwrap_or_else(unknown),
+            ),
+            (
+                "Dotfiles",
+                self.dotfiles_dir.clone().unwrap_or_else(unknown),
+            ),
+            (
+                "Profile",
+                self.profile
+                    .clone()
+                    .unwrap_or_else(|| "none".dimmed().to_string()),
+            ),
+            (
+                "Tags",
+                if self.tags.is_empty() {
+                    "none".dimmed().to_string()
+                } else {
+                    self.tags.join(", ")
+                },
+            ),
+            ("Tool version", self.tool_version.clone()),
+            (
+                "Last setup",
+                self.last_setup.clone().unwrap_or_else(unknown),
+            ),
+            ("Last sync", self.last_sync.clone().unwrap_or_else(unknown)),
+        ];
+
+        rows.iter()
+            .map(|(label, value)| {
+                let label = format!("{:<13}", format!("{}:", label));
+                format!("  {} {}", label.bold(), value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn os_version() -> Option<String> {
+    if cfg!(target_os = "macos") {
+        CommandRunner::new("sw_vers")
+            .arg("-productVersion")
+            .timeout(QUICK_TIMEOUT)
+            .stream(false)
+            .run()
+            .ok()
+            .map(|output| output.stdout.trim().to_string())
+    } else {
+        std::fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|content| os_release_value(&content, "PRETTY_NAME"))
+    }
+}
+
+/// Returns when the dotfiles repo last fetched, from `.git/FETCH_HEAD`
+fn last_sync(dotfiles_dir: &Path) -> Option<String> {
+    let modified = std::fs::metadata(dotfiles_dir.join(".git/FETCH_HEAD"))
+        .ok()?
+        .modified()
+        .ok()?;
+    let local: chrono::DateTime<chrono::Local> = modified.into();
+    Some(local.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_last_sync() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(last_sync(temp.path()), None);
+
+        std::fs::create_dir_all(temp.path().join(".git")).unwrap();
+        std::fs::write(temp.path().join(".git/FETCH_HEAD"), "").unwrap();
+        assert!(last_sync(temp.path()).is_some());
+    }
+
+    #[test]
+    fn test_render_lists_every_field() {
+        let info = SystemInfo {
+            os: "macos".to_string(),
+            os_version: Some("15.1".to_string()),
+            arch: "aarch64".to_string(),
+            tool_version: "0.2.0".to_string(),
+            ..SystemInfo::default()
+        };
+
+        let rendered = info.render();
+        assert!(rendered.contains("macos 15.1"));
+        assert!(rendered.contains("aarch64"));
+        assert!(rendered.contains("Last sync"));
+        assert!(rendered.contains("Profile"));
+        assert!(rendered.contains("Tags"));
+        assert!(rendered.contains("unknown"));
+    }
+
+    #[test]
+    fn test_collect_uses_config() {
+        let config = Config {
+            dotfiles_dir: "/tmp/my-dotfiles".into(),
+            last_setup: Some("2026-01-01 10:00:00".to_string()),
+            ..Config::default()
+        };
+
+        let info = SystemInfo::collect(Some(&config));
+        assert_eq!(info.dotfiles_dir.as_deref(), Some("/tmp/my-dotfiles"));
+        assert_eq!(info.last_setup.as_deref(), Some("2026-01-01 10:00:00"));
+        assert_eq!(info.tool_version, env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -26,11 +26,32 @@ pub enum DotfilesError {
     #[error("Symlink operation failed: {0}")]
     SymlinkFailed(String),
 
+    #[error("Command failed: {0}")]
+    CommandFailed(String),
+
+    #[error("Command timed out: {0}")]
+    CommandTimeout(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Administrator privileges required: {0}")]
+    SudoRequired(String),
+
     #[error("TOML error: {0}")]
     Toml(#[from] toml::de::Error),
 
     #[error("TOML serialization error: {0}")]
     TomlSer(#[from] toml::ser::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
 }
 
 pub type Result<T> = std::result::Result<T, DotfilesError>;
@@ -0,0 +1,412 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandRunner};
+use crate::core::state::state_path;
+use crate::error::{DotfilesError, Result};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Directory in the dotfiles repo holding exported app settings, one subdirectory per app
+pub const REPO_DIR: &str = "appconfig";
+
+/// Where an app keeps settings that can be exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// A preferences domain, exported and imported with `defaults`
+    Defaults { domain: &'static str },
+    /// A file the app exports from its own UI and imports when opened
+    ExportFile {
+        extension: &'static str,
+        /// Where in the app to find its export button
+        export_hint: &'static str,
+    },
+    /// A preferences bundle the app reads from a configurable sync folder
+    SyncFolder {
+        domain: &'static str,
+        key: &'static str,
+        bundle: &'static str,
+        /// Bundle location relative to `$HOME` when no sync folder is set
+        default_dir: &'static str,
+    },
+}
+
+/// An app whose settings can be kept in the dotfiles repo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct App {
+    pub id: &'static str,
+    pub name: &'static str,
+    /// Process name, checked before importing over the app's preferences
+    pub process: &'static str,
+    pub source: Source,
+}
+
+/// Apps with known settings locations (macOS)
+pub const APPS: &[App] = &[
+    App {
+        id: "alfred",
+        name: "Alfred",
+        process: "Alfred",
+        source: Source::SyncFolder {
+            domain: "com.runningwithcrayons.Alfred-Preferences",
+            key: "syncfolder",
+            bundle: "Alfred.alfredpreferences",
+            default_dir: "Library/Application Support/Alfred",
+        },
+    },
+    App {
+        id: "iterm2",
+        name: "iTerm2",
+        process: "iTerm2",
+        source: Source::Defaults {
+            domain: "com.googlecode.iterm2",
+        },
+    },
+    App {
+        id: "raycast",
+        name: "Raycast",
+        process: "Raycast",
+        source: Source::ExportFile {
+            extension: "rayconfig",
+            export_hint: "Raycast > Settings > Advanced > Export",
+        },
+    },
+    App {
+        id: "rectangle",
+        name: "Rectangle",
+        process: "Rectangle",
+        source: Source::Defaults {
+            domain: "com.knollsoft.Rectangle",
+        },
+    },
+];
+
+/// Top-level plist keys that change on every launch and only add noise to diffs
+const VOLATILE_KEY_PREFIXES: &[&str] = &[
+    "NSWindow Frame",
+    "NSSplitView Subview Frames",
+    "NSStatusItem Preferred Position",
+    "NSNavLastRootDirectory",
+    "NSNavPanelExpandedSize",
+    "SULastCheckTime",
+    "SUHasLaunchedBefore",
+    "NoSyncBFCPersistentInstallDate",
+];
+
+/// Looks up an app by id or name, ignoring case
+pub fn find(name: &str) -> Option<&'static App> {
+    APPS.iter()
+        .find(|app| app.id.eq_ignore_ascii_case(name) || app.name.eq_ignore_ascii_case(name))
+}
+
+impl App {
+    /// Returns where this app's settings live in the dotfiles repo
+    pub fn repo_path(&self, dotfiles_dir: &Path) -> PathBuf {
+        let dir = dotfiles_dir.join(REPO_DIR).join(self.id);
+        match self.source {
+            Source::Defaults { domain } => dir.join(format!("{}.plist", domain)),
+            Source::ExportFile { extension, .. } => dir.join(format!("{}.{}", self.id, extension)),
+            Source::SyncFolder { bundle, .. } => dir.join(bundle),
+        }
+    }
+
+    /// Returns true if the app is running
+    pub fn is_running(&self) -> bool {
+        CommandRunner::new("pgrep")
+            .args(["-x", self.process])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .is_ok_and(|output| output.success())
+    }
+}
+
+/// Copies an app's current settings into the repo, returning the repo path
+///
+/// Plists are stored as XML without window positions and update
+/// timestamps. Export files are taken from `from`, or the newest one in
+/// `~/Downloads`.
+pub fn export(
+    app: &App,
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    from: Option<&Path>,
+) -> Result<PathBuf> {
+    let destination = app.repo_path(dotfiles_dir);
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    match app.source {
+        Source::Defaults { domain } => {
+            run_tool(
+                "defaults",
+                &["export".as_ref(), domain.as_ref(), destination.as_os_str()],
+            )?;
+            run_tool(
+                "plutil",
+                &[
+                    "-convert".as_ref(),
+                    "xml1".as_ref(),
+                    destination.as_os_str(),
+                ],
+            )?;
+            let xml = std::fs::read_to_string(&destination)?;
+            std::fs::write(&destination, strip_volatile(&xml))?;
+        }
+        Source::ExportFile {
+            extension,
+            export_hint,
+        } => {
+            let source = match from {
+                Some(path) => path.to_path_buf(),
+                None => newest_export(&home_dir.join("Downloads"), extension).ok_or_else(|| {
+                    DotfilesError::Config(format!(
+                        "No .{} file in ~/Downloads. Export one from {}, or pass --from <file>",
+                        extension, export_hint
+                    ))
+                })?,
+            };
+            std::fs::copy(&source, &destination)?;
+        }
+        Source::SyncFolder {
+            domain,
+            key,
+            bundle,
+            default_dir,
+        } => {
+            let folder = read_default(domain, key)
+                .map(|dir| expand_tilde(&dir, home_dir))
+                .unwrap_or_else(|| home_dir.join(default_dir));
+            let source = folder.join(bundle);
+            if source == destination {
+                return Ok(destination);
+            }
+            if !source.exists() {
+                return Err(DotfilesError::Config(format!(
+                    "{} preferences not found at {}",
+                    app.name,
+                    source.display()
+                )));
+            }
+            if destination.exists() {
+                std::fs::remove_dir_all(&destination)?;
+            }
+            crate::backup::copy_dir_recursive(&source, &destination)?;
+        }
+    }
+
+    audit::record(Action::FileWritten {
+        path: destination.clone(),
+        reason: format!("exported {} settings", app.name),
+    });
+    Ok(destination)
+}
+
+/// Applies the settings stored in the repo to this machine
+///
+/// Preferences domains are saved to the state directory first and the app
+/// must be quit, since it rewrites its preferences on exit. Export files
+/// are opened so the app can run its own import. Sync folders are pointed
+/// at the repo, so later changes land there directly.
+pub fn import(app: &App, dotfiles_dir: &Path, home_dir: &Path) -> Result<()> {
+    let stored = app.repo_path(dotfiles_dir);
+    if !stored.exists() {
+        return Err(DotfilesError::Config(format!(
+            "No {} settings in the repo. Run: dotfiles appconfig export {}",
+            app.name, app.id
+        )));
+    }
+
+    match app.source {
+        Source::Defaults { domain } => {
+            if app.is_running() {
+                return Err(DotfilesError::Config(format!(
+                    "Quit {} first; it overwrites its preferences when it exits",
+                    app.name
+                )));
+            }
+            let previous = previous_settings_path(home_dir, domain);
+            if let Some(parent) = previous.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            run_tool(
+                "defaults",
+                &["export".as_ref(), domain.as_ref(), previous.as_os_str()],
+            )?;
+            run_tool(
+                "defaults",
+                &["import".as_ref(), domain.as_ref(), stored.as_os_str()],
+            )?;
+            audit::record(Action::FileCopied {
+                from: stored,
+                to: home_dir.join(format!("Library/Preferences/{}.plist", domain)),
+            });
+        }
+        Source::ExportFile { .. } => {
+            run_tool("open", &[stored.as_os_str()])?;
+        }
+        Source::SyncFolder { domain, key, .. } => {
+            let folder = stored.parent().unwrap_or(dotfiles_dir);
+            run_tool(
+                "defaults",
+                &[
+                    "write".as_ref(),
+                    domain.as_ref(),
+                    key.as_ref(),
+                    "-string".as_ref(),
+                    folder.as_os_str(),
+                ],
+            )?;
+            audit::record(Action::FileWritten {
+                path: home_dir.join(format!("Library/Preferences/{}.plist", domain)),
+                reason: format!("set {} sync folder", app.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns where `import` saves a domain's settings before replacing them
+pub fn previous_settings_path(home_dir: &Path, domain: &str) -> PathBuf {
+    state_path(home_dir)
+        .with_file_name("appconfig")
+        .join(format!(
+            "{}.{}.plist",
+            domain,
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ))
+}
+
+/// Removes top-level entries matching `VOLATILE_KEY_PREFIXES` from an XML plist
+///
+/// Relies on `plutil -convert xml1` layout: top-level keys are indented one
+/// tab, and volatile values are scalars on the following line.
+pub fn strip_volatile(xml: &str) -> String {
+    let mut out = String::with_capacity(xml.len());
+    let mut lines = xml.lines().peekable();
+    while let Some(line) = lines.next() {
+        let volatile = line
+            .strip_prefix("\t<key>")
+            .and_then(|rest| rest.strip_suffix("</key>"))
+            .is_some_and(|key| {
+                VOLATILE_KEY_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            });
+        let scalar_follows = lines
+            .peek()
+            .and_then(|next| next.strip_prefix('\t'))
+            .is_some_and(|value| {
+                !value.starts_with('\t') && (value.ends_with("/>") || value.contains("</"))
+            });
+        if volatile && scalar_follows {
+            lines.next();
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns the most recently modified file with `extension` in `dir`
+fn newest_export(dir: &Path, extension: &str) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn read_default(domain: &str, key: &str) -> Option<String> {
+    let output = CommandRunner::new("defaults")
+        .args(["read", domain, key])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+    output
+        .success()
+        .then(|| output.stdout.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn expand_tilde(path: &str, home_dir: &Path) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => home_dir.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+fn run_tool(program: &str, args: &[&OsStr]) -> Result<()> {
+    CommandRunner::new(program)
+        .args(args)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_and_repo_path() {
+        let dotfiles = Path::new("/home/me/dotfiles");
+        assert_eq!(
+            find("iTerm2").unwrap().repo_path(dotfiles),
+            dotfiles.join("appconfig/iterm2/com.googlecode.iterm2.plist")
+        );
+        assert_eq!(
+            find("raycast").unwrap().repo_path(dotfiles),
+            dotfiles.join("appconfig/raycast/raycast.rayconfig")
+        );
+        assert_eq!(
+            find("Alfred").unwrap().repo_path(dotfiles),
+            dotfiles.join("appconfig/alfred/Alfred.alfredpreferences")
+        );
+        assert!(find("textedit").is_none());
+    }
+
+    #[test]
+    fn test_strip_volatile() {
+        let xml = "<plist version=\"1.0\">\n<dict>\n\
+            \t<key>NSWindow Frame Preferences</key>\n\t<string>10 10 400 300</string>\n\
+            \t<key>SUHasLaunchedBefore</key>\n\t<true/>\n\
+            \t<key>almostMaximizeHeight</key>\n\t<real>0.9</real>\n\
+            \t<key>NSWindow Frame Shortcuts</key>\n\t<dict>\n\t\t<key>x</key>\n\t</dict>\n\
+            </dict>\n</plist>\n";
+        let stripped = strip_volatile(xml);
+        assert!(!stripped.contains("Preferences</key>"));
+        assert!(!stripped.contains("SUHasLaunchedBefore"));
+        assert!(stripped.contains("<key>almostMaximizeHeight</key>\n\t<real>0.9</real>"));
+        // Only scalar values are removed; anything nested is left intact
+        assert!(stripped.contains("NSWindow Frame Shortcuts"));
+    }
+
+    #[test]
+    fn test_newest_export() {
+        let temp = TempDir::new().unwrap();
+        assert!(newest_export(temp.path(), "rayconfig").is_none());
+
+        let old = temp.path().join("Raycast 2026-01-01.rayconfig");
+        let new = temp.path().join("Raycast 2026-02-01.rayconfig");
+        fs::write(&old, "").unwrap();
+        fs::write(temp.path().join("notes.txt"), "").unwrap();
+        fs::write(&new, "").unwrap();
+        let earlier = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+
+        assert_eq!(newest_export(temp.path(), "rayconfig"), Some(new));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::{DotfilesError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Secret-free `~/.aws/config` template in the dotfiles repo
+pub const CONFIG_TEMPLATE: &str = "templates/aws/config";
+
+/// Keys that hold credentials and must never appear in the template
+pub const SECRET_KEYS: &[&str] = &[
+    "aws_access_key_id",
+    "aws_secret_access_key",
+    "aws_session_token",
+];
+
+/// Returns the secret keys set in an AWS config/credentials file
+pub fn find_secret_keys(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim().to_lowercase())
+        .filter(|key| SECRET_KEYS.contains(&key.as_str()))
+        .collect()
+}
+
+/// Writes `~/.aws/config` from the repo template if it does not exist yet
+///
+/// Credentials are left to `aws sso login`; a template containing secret keys
+/// is rejected. Returns the path written, or None when there is no template
+/// or the config already exists.
+pub fn scaffold_config(dotfiles_dir: &Path, home_dir: &Path) -> Result<Option<PathBuf>> {
+    let template = dotfiles_dir.join(CONFIG_TEMPLATE);
+    if !template.is_file() {
+        return Ok(None);
+    }
+
+    let target = home_dir.join(".aws/config");
+    if target.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&template)?;
+    let secrets = find_secret_keys(&content);
+    if !secrets.is_empty() {
+        return Err(DotfilesError::Config(format!(
+            "{} contains credentials ({}); keep only profiles and regions in the template",
+            template.display(),
+            secrets.join(", ")
+        )));
+    }
+
+    let aws_dir = home_dir.join(".aws");
+    fs::create_dir_all(&aws_dir)?;
+    fs::write(&target, content)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&aws_dir, fs::Permissions::from_mode(0o700))?;
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(Some(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const TEMPLATE: &str = "[profile dev]\nsso_session = work\nsso_account_id = 123456789012\nsso_role_name = Developer\nregion = us-east-1\n";
+
+    fn write_template(dotfiles: &Path, content: &str) {
+        let path = dotfiles.join(CONFIG_TEMPLATE);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_secret_keys() {
+        assert!(find_secret_keys(TEMPLATE).is_empty());
+        assert_eq!(
+            find_secret_keys("[default]\nAWS_SECRET_ACCESS_KEY = abc\n"),
+            vec!["aws_secret_access_key"]
+        );
+    }
+
+    #[test]
+    fn test_scaffold_config() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        write_template(&dotfiles, TEMPLATE);
+
+        let written = scaffold_config(&dotfiles, &home).unwrap();
+        assert_eq!(written, Some(home.join(".aws/config")));
+        assert_eq!(
+            fs::read_to_string(home.join(".aws/config")).unwrap(),
+            TEMPLATE
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(home.join(".aws/config"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // Existing config is never overwritten
+        assert_eq!(scaffold_config(&dotfiles, &home).unwrap(), None);
+    }
+
+    #[test]
+    fn test_scaffold_config_without_template() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(scaffold_config(temp.path(), temp.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_scaffold_config_rejects_secrets() {
+        let temp = TempDir::new().unwrap();
+        write_template(temp.path(), "[default]\naws_access_key_id = AKIA...\n");
+
+        let result = scaffold_config(temp.path(), &temp.path().join("home"));
+        assert!(result.is_err());
+        assert!(!temp.path().join("home/.aws/config").exists());
+    }
+}
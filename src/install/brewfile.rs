@@ -0,0 +1,436 @@
+use crate::core::config::PackageSettings;
+use crate::error::{DotfilesError, Result};
+use crate::install::homebrew::{self, PackageKind};
+use crate::install::packages::{self, package_info, PackageCategory};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Package groups written to an exported Brewfile
+///
+/// Kubernetes tools follow `[kubernetes] enabled`, so they stay out.
+const EXPORTED_CATEGORIES: &[PackageCategory] = &[
+    PackageCategory::Essential,
+    PackageCategory::Optional,
+    PackageCategory::Development,
+    PackageCategory::Cloud,
+    PackageCategory::Productivity,
+    PackageCategory::Editor,
+];
+
+/// Taps every Homebrew install has, which `brew bundle` doesn't need listed
+const DEFAULT_TAPS: &[&str] = &["homebrew/core", "homebrew/cask"];
+
+const HEADER: &str = "# Packages for this machine; install with: brew bundle\n";
+
+/// A Brewfile: taps first, then packages under a comment per group
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Brewfile {
+    pub taps: Vec<String>,
+    pub groups: Vec<BrewfileGroup>,
+    /// Lines read from a Brewfile that aren't `tap`, `brew`, or `cask` (e.g. `mas`)
+    pub unsupported: Vec<String>,
+}
+
+/// Packages listed together under a `# heading` comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrewfileGroup {
+    pub heading: String,
+    pub packages: Vec<(PackageKind, String)>,
+}
+
+impl BrewfileGroup {
+    /// A group of packages named by formula, as casks where the catalog says so
+    pub fn new(heading: &str, packages: &[&str]) -> Self {
+        BrewfileGroup {
+            heading: heading.to_string(),
+            packages: packages
+                .iter()
+                .map(|name| {
+                    let kind = package_info(name).map_or(PackageKind::Formula, |info| info.kind);
+                    (kind, name.to_string())
+                })
+                .collect(),
+        }
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.packages.iter().any(|(_, package)| package == name)
+    }
+}
+
+impl Brewfile {
+    /// Parses a Brewfile, keeping each `# comment` as the heading of the packages after it
+    ///
+    /// Only the name is read from each entry; options such as `args:` are ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut brewfile = Brewfile::default();
+        let mut group = BrewfileGroup::new("", &[]);
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix('#') {
+                let next = BrewfileGroup::new(comment.trim(), &[]);
+                brewfile.groups.push(std::mem::replace(&mut group, next));
+                continue;
+            }
+
+            let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match (keyword, quoted(rest)) {
+                ("tap", Some(name)) => brewfile.taps.push(name),
+                ("brew", Some(name)) => group.packages.push((PackageKind::Formula, name)),
+                ("cask", Some(name)) => group.packages.push((PackageKind::Cask, name)),
+                _ => brewfile.unsupported.push(line.to_string()),
+            }
+        }
+        brewfile.groups.push(group);
+        brewfile.groups.retain(|group| !group.packages.is_empty());
+        brewfile
+    }
+
+    /// Every package listed, in order
+    pub fn packages(&self) -> impl Iterator<Item = &(PackageKind, String)> {
+        self.groups.iter().flat_map(|group| &group.packages)
+    }
+
+    /// Compares the Brewfile with what Homebrew has installed
+    pub fn drift(&self, installed: &Installed) -> Drift {
+        let has = |list: &[String], name: &str| {
+            list.iter()
+                .any(|entry| short_name(entry) == short_name(name))
+        };
+
+        let missing_taps = self
+            .taps
+            .iter()
+            .filter(|tap| !installed.taps.contains(tap))
+            .cloned()
+            .collect();
+        let missing = self
+            .packages()
+            .filter(|(kind, name)| match kind {
+                PackageKind::Formula => !has(&installed.formulae, name),
+                PackageKind::Cask => !has(&installed.casks, name),
+            })
+            .cloned()
+            .collect();
+
+        let listed = |kind: PackageKind, name: &str| {
+            self.packages()
+                .any(|(k, n)| *k == kind && short_name(n) == short_name(name))
+        };
+        let extra = installed
+            .leaves
+            .iter()
+            .filter(|name| !listed(PackageKind::Formula, name))
+            .map(|name| (PackageKind::Formula, name.clone()))
+            .chain(
+                installed
+                    .casks
+                    .iter()
+                    .filter(|name| !listed(PackageKind::Cask, name))
+                    .map(|name| (PackageKind::Cask, name.clone())),
+            )
+            .collect();
+
+        Drift {
+            missing_taps,
+            missing,
+            extra,
+        }
+    }
+
+    /// Renders the Brewfile in `brew bundle` syntax
+    pub fn render(&self) -> String {
+        let mut out = String::from(HEADER);
+        if !self.taps.is_empty() {
+            out.push('\n');
+            for tap in &self.taps {
+                out.push_str(&format!("tap \"{}\"\n", tap));
+            }
+        }
+        for group in self
+            .groups
+            .iter()
+            .filter(|group| !group.packages.is_empty())
+        {
+            out.push('\n');
+            if !group.heading.is_empty() {
+                out.push_str(&format!("# {}\n", group.heading));
+            }
+            for (kind, name) in &group.packages {
+                let keyword = match kind {
+                    PackageKind::Formula => "brew",
+                    PackageKind::Cask => "cask",
+                };
+                out.push_str(&format!("{} \"{}\"\n", keyword, name));
+            }
+        }
+        out
+    }
+}
+
+/// What Homebrew has installed, to compare a Brewfile against
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Installed {
+    pub taps: Vec<String>,
+    /// Every installed formula, dependencies included
+    pub formulae: Vec<String>,
+    /// Formulae nothing else depends on, i.e. installed on purpose
+    pub leaves: Vec<String>,
+    pub casks: Vec<String>,
+}
+
+impl Installed {
+    pub fn query() -> Self {
+        Installed {
+            taps: homebrew::tapped(),
+            formulae: homebrew::installed_formulae(),
+            leaves: homebrew::leaves(),
+            casks: homebrew::installed_casks(),
+        }
+    }
+}
+
+/// Differences between a Brewfile and what is installed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Drift {
+    /// Taps the Brewfile lists that Homebrew hasn't added
+    pub missing_taps: Vec<String>,
+    /// Packages the Brewfile lists that aren't installed
+    pub missing: Vec<(PackageKind, String)>,
+    /// Formulae installed on purpose, and casks, that the Brewfile doesn't list
+    pub extra: Vec<(PackageKind, String)>,
+}
+
+/// Where the Brewfile lives in the dotfiles repo
+pub fn path(dotfiles_dir: &Path) -> PathBuf {
+    dotfiles_dir.join("Brewfile")
+}
+
+/// Reads the repo's Brewfile, or None if it has none
+pub fn read(dotfiles_dir: &Path) -> Result<Option<Brewfile>> {
+    let path = path(dotfiles_dir);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(Brewfile::parse(&fs::read_to_string(path)?)))
+}
+
+/// Adds the missing taps and installs the missing packages, continuing past failures
+///
+/// Returns the packages installed, or an error naming everything that failed.
+pub fn install(drift: &Drift) -> Result<Vec<String>> {
+    let mut failed = Vec::new();
+    for tap in &drift.missing_taps {
+        if let Err(e) = homebrew::tap(tap) {
+            eprintln!("Warning: Failed to tap {}: {}", tap, e);
+            failed.push(tap.clone());
+        }
+    }
+
+    let mut installed = Vec::new();
+    for (kind, name) in &drift.missing {
+        if let Some(tap) = homebrew::tap_of(name).filter(|tap| failed.contains(tap)) {
+            eprintln!("Warning: Skipping {}: {} failed", name, tap);
+            failed.push(name.clone());
+            continue;
+        }
+        let result = match kind {
+            PackageKind::Formula => homebrew::install_package(name),
+            PackageKind::Cask => homebrew::install_cask(name),
+        };
+        match result {
+            Ok(()) => installed.push(name.clone()),
+            Err(e) => {
+                eprintln!("Warning: Failed to install {}: {}", name, e);
+                failed.push(name.clone());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "{} from the Brewfile failed: {}",
+            failed.len(),
+            failed.join(", ")
+        )));
+    }
+    Ok(installed)
+}
+
+/// A formula or cask name without its tap
+fn short_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// The first quoted string in `text`, e.g. the name in `"jq", args: [...]`
+fn quoted(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let quote = text.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let rest = &text[1..];
+    rest.find(quote).map(|end| rest[..end].to_string())
+}
+
+/// Builds a Brewfile for this machine's package set
+///
+/// Covers the built-in groups minus `[packages] skip`, the taps they need or
+/// that Homebrew has added, and installed casks outside the groups.
+pub fn export(settings: &PackageSettings) -> Brewfile {
+    build(settings, homebrew::tapped(), homebrew::installed_casks())
+}
+
+fn build(settings: &PackageSettings, tapped: Vec<String>, casks: Vec<String>) -> Brewfile {
+    let mut groups: Vec<BrewfileGroup> = EXPORTED_CATEGORIES
+        .iter()
+        .map(|category| {
+            BrewfileGroup::new(
+                category.label(),
+                &packages::active_packages(category.packages(), settings),
+            )
+        })
+        .collect();
+
+    let mut taps: Vec<String> = groups
+        .iter()
+        .flat_map(|group| &group.packages)
+        .flat_map(|(_, name)| packages::required_taps(name))
+        .chain(tapped)
+        .filter(|tap| !DEFAULT_TAPS.contains(&tap.as_str()))
+        .collect();
+    taps.sort();
+    taps.dedup();
+
+    let other_casks: Vec<(PackageKind, String)> = casks
+        .into_iter()
+        .filter(|cask| !groups.iter().any(|group| group.contains(cask)))
+        .map(|cask| (PackageKind::Cask, cask))
+        .collect();
+    groups.push(BrewfileGroup {
+        heading: "other installed casks".to_string(),
+        packages: other_casks,
+    });
+
+    Brewfile {
+        taps,
+        groups,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let brewfile = Brewfile {
+            taps: vec!["yakitrak/tap".to_string()],
+            groups: vec![
+                BrewfileGroup::new("essential", &["stow", "fzf"]),
+                BrewfileGroup::new("empty", &[]),
+                BrewfileGroup::new("productivity", &["obsidian"]),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            brewfile.render(),
+            "# Packages for this machine; install with: brew bundle\n\
+             \n\
+             tap \"yakitrak/tap\"\n\
+             \n\
+             # essential\n\
+             brew \"stow\"\n\
+             brew \"fzf\"\n\
+             \n\
+             # productivity\n\
+             cask \"obsidian\"\n"
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        let settings = PackageSettings {
+            skip: vec!["fzf".to_string()],
+            ..Default::default()
+        };
+        let brewfile = build(
+            &settings,
+            vec!["homebrew/core".to_string(), "hashicorp/tap".to_string()],
+            vec!["firefox".to_string(), "obsidian".to_string()],
+        );
+        assert_eq!(brewfile.taps, ["hashicorp/tap", "yakitrak/tap"]);
+        assert!(!brewfile.groups.iter().any(|group| group.contains("fzf")));
+        assert!(!brewfile
+            .groups
+            .iter()
+            .any(|group| group.contains("kubernetes-cli")));
+
+        let rendered = brewfile.render();
+        assert!(rendered.contains("# essential\nbrew \"stow\"\n"));
+        assert!(rendered.contains("# other installed casks\ncask \"firefox\"\n"));
+        assert_eq!(rendered.matches("cask \"obsidian\"").count(), 1);
+    }
+
+    #[test]
+    fn test_parse() {
+        let brewfile = Brewfile::parse(
+            "tap \"yakitrak/tap\"\n\
+             brew \"git\"\n\
+             \n\
+             # editors\n\
+             brew 'nvim', args: [\"HEAD\"]\n\
+             cask \"obsidian\"\n\
+             mas \"Xcode\", id: 497799835\n",
+        );
+        assert_eq!(brewfile.taps, ["yakitrak/tap"]);
+        assert_eq!(brewfile.groups.len(), 2);
+        assert_eq!(brewfile.groups[1].heading, "editors");
+        assert_eq!(
+            brewfile.groups[1].packages,
+            [
+                (PackageKind::Formula, "nvim".to_string()),
+                (PackageKind::Cask, "obsidian".to_string())
+            ]
+        );
+        assert_eq!(brewfile.unsupported, ["mas \"Xcode\", id: 497799835"]);
+
+        // Rendering and parsing round-trip
+        let exported = build(&PackageSettings::default(), Vec::new(), Vec::new());
+        assert_eq!(
+            Brewfile::parse(&exported.render()).render(),
+            exported.render()
+        );
+    }
+
+    #[test]
+    fn test_drift() {
+        let brewfile = Brewfile::parse(
+            "tap \"yakitrak/tap\"\n\
+             brew \"git\"\n\
+             brew \"yakitrak/tap/obsidian-cli\"\n\
+             cask \"obsidian\"\n",
+        );
+        let strings = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let installed = Installed {
+            taps: strings(&["yakitrak/tap"]),
+            formulae: strings(&["git", "obsidian-cli", "pcre2"]),
+            leaves: strings(&["git", "yakitrak/tap/obsidian-cli", "htop"]),
+            casks: strings(&["firefox"]),
+        };
+        let drift = brewfile.drift(&installed);
+        assert!(drift.missing_taps.is_empty());
+        assert_eq!(drift.missing, [(PackageKind::Cask, "obsidian".to_string())]);
+        assert_eq!(
+            drift.extra,
+            [
+                (PackageKind::Formula, "htop".to_string()),
+                (PackageKind::Cask, "firefox".to_string())
+            ]
+        );
+
+        let drift = brewfile.drift(&Installed::default());
+        assert_eq!(drift.missing_taps, ["yakitrak/tap"]);
+        assert_eq!(drift.missing.len(), 3);
+    }
+}
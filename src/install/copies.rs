@@ -0,0 +1,307 @@
+use crate::core::audit::{self, Action};
+use crate::core::config::{tags_match, Config};
+use crate::core::state::{fingerprint, State};
+use crate::error::{DotfilesError, Result};
+use crate::symlink::expand_target;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A repo file marked `mode = "copy"` and where its live copy goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopiedFile {
+    /// Path relative to the dotfiles repo, as written in `[files]`
+    pub name: String,
+    pub source: PathBuf,
+    pub target: PathBuf,
+}
+
+/// How a live copy compares with the repo, relative to the last copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyStatus {
+    /// Live copy matches the repo
+    InSync,
+    /// Not copied yet
+    Missing,
+    /// The repo changed since the last copy; safe to copy again
+    RepoChanged,
+    /// The live copy changed since the last copy (e.g. the app saved settings)
+    LiveChanged,
+    /// Both changed, or the live file was there before the first copy
+    Diverged,
+    /// A symlink is where the copy belongs (e.g. left from when the file was linked)
+    Symlinked,
+}
+
+impl CopyStatus {
+    /// Whether copying the repo file over the live one would lose edits
+    pub fn needs_choice(self) -> bool {
+        matches!(self, CopyStatus::LiveChanged | CopyStatus::Diverged)
+    }
+
+    /// Short description used in sync and doctor output
+    pub fn describe(self) -> &'static str {
+        match self {
+            CopyStatus::InSync => "in sync",
+            CopyStatus::Missing => "not copied yet",
+            CopyStatus::RepoChanged => "changed in the repo",
+            CopyStatus::LiveChanged => "changed on this machine",
+            CopyStatus::Diverged => "changed both in the repo and on this machine",
+            CopyStatus::Symlinked => "a symlink instead of a copy",
+        }
+    }
+}
+
+/// Returns the copy-only files meant for this machine
+///
+/// Top-level entries are copied into `link_root`; `[targets]` keys go to
+/// their mapped destination.
+pub fn find(config: &Config, link_root: &Path) -> Vec<CopiedFile> {
+    config
+        .copied_files()
+        .into_iter()
+        .filter(|name| {
+            config
+                .links
+                .when
+                .get(name)
+                .is_none_or(|filter| tags_match(filter, &config.tags))
+        })
+        .map(|name| {
+            let target = match config.targets.get(&name) {
+                Some(target) => expand_target(target, link_root),
+                None => link_root.join(&name),
+            };
+            CopiedFile {
+                source: config.dotfiles_dir.join(&name),
+                target,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Compares a live copy with the repo file
+///
+/// A symlink at the target is never read through: it may point at the repo
+/// file itself, which would look like a copy in sync.
+pub fn status(file: &CopiedFile, state: &State) -> Result<CopyStatus> {
+    let repo = fs::read(&file.source)?;
+    match fs::symlink_metadata(&file.target) {
+        Ok(meta) if meta.file_type().is_symlink() => return Ok(CopyStatus::Symlinked),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(CopyStatus::Missing),
+        Err(e) => return Err(e.into()),
+    }
+    let live = fs::read(&file.target)?;
+    if live == repo {
+        return Ok(CopyStatus::InSync);
+    }
+
+    Ok(match state.copied.get(&state_key(file)) {
+        Some(copied) if *copied == fingerprint(&live) => CopyStatus::RepoChanged,
+        Some(copied) if *copied == fingerprint(&repo) => CopyStatus::LiveChanged,
+        _ => CopyStatus::Diverged,
+    })
+}
+
+/// Copies every file that is missing or only changed in the repo
+///
+/// Symlinks in the way are replaced with copies. Live copies with their own
+/// edits are left alone; the returned status says which ones need `pull` or `push`.
+pub fn copy_all(files: &[CopiedFile], state: &mut State) -> Result<Vec<(PathBuf, CopyStatus)>> {
+    let mut outcomes = Vec::new();
+
+    for file in files {
+        let status = status(file, state)?;
+        match status {
+            CopyStatus::Missing | CopyStatus::RepoChanged | CopyStatus::Symlinked => {
+                pull(file, state)?
+            }
+            CopyStatus::InSync => record(file, state)?,
+            CopyStatus::LiveChanged | CopyStatus::Diverged => {}
+        }
+        outcomes.push((file.target.clone(), status));
+    }
+
+    Ok(outcomes)
+}
+
+/// Overwrites the live copy with the repo file
+///
+/// A symlink at the target is removed first rather than written through.
+pub fn pull(file: &CopiedFile, state: &mut State) -> Result<()> {
+    if let Some(parent) = file.target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if file.target.is_symlink() {
+        fs::remove_file(&file.target)?;
+    }
+    fs::copy(&file.source, &file.target)?;
+    audit::record(Action::FileWritten {
+        path: file.target.clone(),
+        reason: "copied from dotfiles".to_string(),
+    });
+    record(file, state)
+}
+
+/// Overwrites the repo file with the live copy
+///
+/// Refuses when the target is a symlink, which has no edits of its own to push.
+pub fn push(file: &CopiedFile, state: &mut State) -> Result<()> {
+    if file.target.is_symlink() {
+        return Err(DotfilesError::Config(format!(
+            "{} is a symlink, not a copy; pull the repo's version instead",
+            file.target.display()
+        )));
+    }
+    fs::copy(&file.target, &file.source)?;
+    audit::record(Action::FileWritten {
+        path: file.source.clone(),
+        reason: "copied back from the live file".to_string(),
+    });
+    record(file, state)
+}
+
+fn record(file: &CopiedFile, state: &mut State) -> Result<()> {
+    let content = fs::read(&file.target)?;
+    state.copied.insert(state_key(file), fingerprint(&content));
+    Ok(())
+}
+
+fn state_key(file: &CopiedFile) -> String {
+    file.target.display().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{FileMode, FileSettings};
+    use tempfile::TempDir;
+
+    fn setup() -> (TempDir, CopiedFile) {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(&dotfiles).unwrap();
+        fs::write(dotfiles.join("settings.json"), "{}\n").unwrap();
+        let file = CopiedFile {
+            name: "settings.json".to_string(),
+            source: dotfiles.join("settings.json"),
+            target: temp.path().join("home/.app/settings.json"),
+        };
+        (temp, file)
+    }
+
+    #[test]
+    fn test_find() {
+        let mut config = Config {
+            dotfiles_dir: PathBuf::from("/dots"),
+            ..Config::default()
+        };
+        for name in [".rectangle.json", "mac/app.json", "work.json"] {
+            config.files.insert(
+                name.to_string(),
+                FileSettings {
+                    mode: FileMode::Copy,
+                },
+            );
+        }
+        config
+            .targets
+            .insert("mac/app.json".to_string(), "~/Library/app.json".to_string());
+        config
+            .links
+            .when
+            .insert("work.json".to_string(), "work".to_string());
+
+        let files = find(&config, Path::new("/home/me"));
+        assert_eq!(
+            files
+                .iter()
+                .map(|f| (f.source.clone(), f.target.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/dots/.rectangle.json"),
+                    PathBuf::from("/home/me/.rectangle.json")
+                ),
+                (
+                    PathBuf::from("/dots/mac/app.json"),
+                    PathBuf::from("/home/me/Library/app.json")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_copy_all_tracks_which_side_changed() {
+        let (_temp, file) = setup();
+        let mut state = State::default();
+        let files = [file.clone()];
+
+        assert_eq!(
+            copy_all(&files, &mut state).unwrap()[0].1,
+            CopyStatus::Missing
+        );
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "{}\n");
+        assert_eq!(status(&file, &state).unwrap(), CopyStatus::InSync);
+
+        // Repo changed: copied again
+        fs::write(&file.source, "{\"a\": 1}\n").unwrap();
+        assert_eq!(
+            copy_all(&files, &mut state).unwrap()[0].1,
+            CopyStatus::RepoChanged
+        );
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "{\"a\": 1}\n");
+
+        // The app rewrote its settings: left alone until pushed
+        fs::write(&file.target, "{\"a\": 2}\n").unwrap();
+        assert_eq!(
+            copy_all(&files, &mut state).unwrap()[0].1,
+            CopyStatus::LiveChanged
+        );
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "{\"a\": 2}\n");
+        push(&file, &mut state).unwrap();
+        assert_eq!(fs::read_to_string(&file.source).unwrap(), "{\"a\": 2}\n");
+        assert_eq!(status(&file, &state).unwrap(), CopyStatus::InSync);
+
+        // Both sides changed
+        fs::write(&file.source, "{\"a\": 3}\n").unwrap();
+        fs::write(&file.target, "{\"a\": 4}\n").unwrap();
+        assert_eq!(status(&file, &state).unwrap(), CopyStatus::Diverged);
+        pull(&file, &mut state).unwrap();
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "{\"a\": 3}\n");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_is_replaced_not_written_through() {
+        let (_temp, file) = setup();
+        fs::create_dir_all(file.target.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(&file.source, &file.target).unwrap();
+
+        let mut state = State::default();
+        assert_eq!(status(&file, &state).unwrap(), CopyStatus::Symlinked);
+        assert!(push(&file, &mut state).is_err());
+        assert_eq!(fs::read_to_string(&file.source).unwrap(), "{}\n");
+
+        let outcomes = copy_all(std::slice::from_ref(&file), &mut state).unwrap();
+        assert_eq!(outcomes[0].1, CopyStatus::Symlinked);
+        assert!(!file.target.is_symlink());
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "{}\n");
+        assert_eq!(fs::read_to_string(&file.source).unwrap(), "{}\n");
+        assert_eq!(status(&file, &state).unwrap(), CopyStatus::InSync);
+    }
+
+    #[test]
+    fn test_existing_live_file_is_not_overwritten() {
+        let (_temp, file) = setup();
+        fs::create_dir_all(file.target.parent().unwrap()).unwrap();
+        fs::write(&file.target, "local\n").unwrap();
+
+        let mut state = State::default();
+        let outcomes = copy_all(std::slice::from_ref(&file), &mut state).unwrap();
+        assert_eq!(outcomes[0].1, CopyStatus::Diverged);
+        assert!(outcomes[0].1.needs_choice());
+        assert_eq!(fs::read_to_string(&file.target).unwrap(), "local\n");
+    }
+}
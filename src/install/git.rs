@@ -0,0 +1,256 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::GitSettings;
+use crate::error::{DotfilesError, Result};
+use std::path::{Path, PathBuf};
+
+/// Machine-local gitconfig, included from the repo-managed `~/.gitconfig`
+pub const LOCAL_CONFIG: &str = ".gitconfig.local";
+
+/// Homebrew formula providing the `delta` pager
+pub const DELTA_PACKAGE: &str = "git-delta";
+
+/// Returns the path of the machine-local gitconfig
+pub fn local_config_path(home_dir: &Path) -> PathBuf {
+    home_dir.join(LOCAL_CONFIG)
+}
+
+/// Returns the gitconfig entries implied by the settings, in write order
+pub fn desired_entries(settings: &GitSettings) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+
+    if settings.delta {
+        entries.push(("core.pager".to_string(), "delta".to_string()));
+        entries.push((
+            "interactive.diffFilter".to_string(),
+            "delta --color-only".to_string(),
+        ));
+        entries.push(("delta.navigate".to_string(), "true".to_string()));
+    }
+
+    if let Some(tool) = &settings.difftool {
+        entries.push(("diff.tool".to_string(), tool.clone()));
+        entries.push(("difftool.prompt".to_string(), "false".to_string()));
+        if tool == "difftastic" {
+            entries.push((
+                "difftool.difftastic.cmd".to_string(),
+                "difft \"$LOCAL\" \"$REMOTE\"".to_string(),
+            ));
+        }
+    }
+
+    for (name, command) in &settings.aliases {
+        entries.push((format!("alias.{}", name), command.clone()));
+    }
+
+    entries
+}
+
+/// Returns the binary a git difftool name runs
+pub fn difftool_binary(tool: &str) -> &str {
+    match tool {
+        "nvimdiff" => "nvim",
+        "vimdiff" | "gvimdiff" => "vim",
+        "difftastic" => "difft",
+        other => other,
+    }
+}
+
+/// Reads a value from a gitconfig file, or the effective config when `file` is None
+pub fn get(file: Option<&Path>, key: &str) -> Option<String> {
+    let mut runner = CommandRunner::new("git").arg("config");
+    if let Some(file) = file {
+        runner = runner.arg("--file").arg(file);
+    }
+
+    runner
+        .args(["--get", key])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()
+        .filter(|output| output.success())
+        .map(|output| output.stdout.trim_end_matches('\n').to_string())
+}
+
+/// Writes entries into a gitconfig file, skipping values that already match
+///
+/// Returns the number of entries changed.
+pub fn apply_entries(file: &Path, entries: &[(String, String)]) -> Result<usize> {
+    let mut changed = 0;
+
+    for (key, value) in entries {
+        if get(Some(file), key).as_deref() == Some(value.as_str()) {
+            continue;
+        }
+        CommandRunner::new("git")
+            .args(["config", "--file"])
+            .arg(file)
+            .args([key, value])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run()?;
+        changed += 1;
+    }
+
+    Ok(changed)
+}
+
+/// Returns the global gitconfig under `home_dir` that git reads and writes
+///
+/// `~/.gitconfig` when it exists (or is a link), else `$XDG_CONFIG_HOME/git/config`
+/// when that exists, else `~/.gitconfig`, matching `git config --global`.
+pub fn global_config_path(home_dir: &Path) -> PathBuf {
+    let home_config = home_dir.join(".gitconfig");
+    if home_config.exists() || home_config.is_symlink() {
+        return home_config;
+    }
+    let xdg_config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home_dir.join(".config"))
+        .join("git/config");
+    if xdg_config.exists() {
+        xdg_config
+    } else {
+        home_config
+    }
+}
+
+/// Returns true if the global gitconfig includes the machine-local file
+pub fn is_local_config_included(home_dir: &Path) -> bool {
+    let local = local_config_path(home_dir);
+    let output = CommandRunner::new("git")
+        .args(["config", "--file"])
+        .arg(global_config_path(home_dir))
+        .args(["--get-all", "include.path"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+
+    output.is_ok_and(|output| {
+        output.stdout.lines().any(|path| {
+            let path = path.trim();
+            path == format!("~/{}", LOCAL_CONFIG) || Path::new(path) == local
+        })
+    })
+}
+
+/// Adds `[include] path = ~/.gitconfig.local` to the global gitconfig if missing
+///
+/// When `~/.gitconfig` is a symlink (usually into the dotfiles repo), the
+/// include is written to the file it points to, on purpose rather than by
+/// git following the link; a dangling link is refused. Returns the file
+/// the include was added to, or None if it was already there.
+pub fn ensure_local_config_included(home_dir: &Path) -> Result<Option<PathBuf>> {
+    if is_local_config_included(home_dir) {
+        return Ok(None);
+    }
+
+    let global = global_config_path(home_dir);
+    let file = if global.is_symlink() {
+        global.canonicalize().map_err(|_| {
+            DotfilesError::Config(format!(
+                "{} is a broken link; fix it before adding the include",
+                global.display()
+            ))
+        })?
+    } else {
+        global
+    };
+
+    CommandRunner::new("git")
+        .args(["config", "--file"])
+        .arg(&file)
+        .args(["--add", "include.path"])
+        .arg(format!("~/{}", LOCAL_CONFIG))
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+
+    Ok(Some(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_desired_entries() {
+        let settings = GitSettings {
+            delta: true,
+            difftool: Some("difftastic".to_string()),
+            aliases: BTreeMap::from([("co".to_string(), "checkout".to_string())]),
+        };
+
+        let entries = desired_entries(&settings);
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "core.pager",
+                "interactive.diffFilter",
+                "delta.navigate",
+                "diff.tool",
+                "difftool.prompt",
+                "difftool.difftastic.cmd",
+                "alias.co",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_desired_entries_without_delta() {
+        let settings = GitSettings {
+            delta: false,
+            ..GitSettings::default()
+        };
+        assert!(desired_entries(&settings).is_empty());
+    }
+
+    #[test]
+    fn test_difftool_binary() {
+        assert_eq!(difftool_binary("nvimdiff"), "nvim");
+        assert_eq!(difftool_binary("difftastic"), "difft");
+        assert_eq!(difftool_binary("meld"), "meld");
+    }
+
+    #[test]
+    fn test_apply_entries_is_idempotent() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join(LOCAL_CONFIG);
+        let entries = vec![
+            ("core.pager".to_string(), "delta".to_string()),
+            ("alias.st".to_string(), "status -sb".to_string()),
+        ];
+
+        assert_eq!(apply_entries(&file, &entries).unwrap(), 2);
+        assert_eq!(get(Some(&file), "alias.st").as_deref(), Some("status -sb"));
+        assert_eq!(apply_entries(&file, &entries).unwrap(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_include_goes_to_the_linked_repo_file() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let repo_config = temp.path().join("dotfiles/gitconfig");
+        std::fs::create_dir_all(repo_config.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(&home).unwrap();
+        std::fs::write(&repo_config, "[user]\n\tname = Me\n").unwrap();
+        std::os::unix::fs::symlink(&repo_config, home.join(".gitconfig")).unwrap();
+
+        let written = ensure_local_config_included(&home).unwrap();
+        assert_eq!(written, Some(repo_config.canonicalize().unwrap()));
+        assert!(home.join(".gitconfig").is_symlink());
+        assert!(std::fs::read_to_string(&repo_config)
+            .unwrap()
+            .contains("~/.gitconfig.local"));
+        assert!(is_local_config_included(&home));
+        assert_eq!(ensure_local_config_included(&home).unwrap(), None);
+
+        std::fs::remove_file(&repo_config).unwrap();
+        assert!(ensure_local_config_included(&home).is_err());
+    }
+}
@@ -1,6 +1,12 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandRunner};
+use crate::core::pool::Pool;
 use crate::error::{DotfilesError, Result};
+use sha2::{Digest, Sha256};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use tempfile::NamedTempFile;
 
 /// Possible Homebrew installation paths
 const HOMEBREW_PATHS: &[&str] = &[
@@ -9,9 +15,15 @@ const HOMEBREW_PATHS: &[&str] = &[
 ];
 
 /// Official Homebrew installation script URL
-const HOMEBREW_INSTALL_URL: &str =
+pub const HOMEBREW_INSTALL_URL: &str =
     "https://raw.githubusercontent.com/Homebrew/install/HEAD/install.sh";
 
+/// Homebrew repository directories a partial install leaves behind
+const HOMEBREW_REPOSITORIES: &[&str] = &["/opt/homebrew", "/usr/local/Homebrew"];
+
+/// Git lock files an interrupted install can leave in the Homebrew repository
+const STALE_LOCKS: &[&str] = &[".git/index.lock", ".git/shallow.lock", ".git/HEAD.lock"];
+
 /// Detects if Homebrew is installed and returns its path
 pub fn detect_homebrew() -> Option<PathBuf> {
     HOMEBREW_PATHS
@@ -31,21 +43,184 @@ pub fn get_brew_path() -> Option<PathBuf> {
     detect_homebrew()
 }
 
-/// Installs Homebrew using the official installation script
-pub fn install() -> Result<()> {
+/// Returns the Homebrew prefix (`/opt/homebrew` or `/usr/local`)
+pub fn prefix() -> Option<PathBuf> {
+    Some(detect_homebrew()?.parent()?.parent()?.to_path_buf())
+}
+
+/// A downloaded copy of the Homebrew install script, deleted when dropped
+///
+/// The temp file has a random name and is only readable by the user, so
+/// nobody else can swap it between the checksum check and the run.
+#[derive(Debug)]
+pub struct InstallScript {
+    file: NamedTempFile,
+    pub sha256: String,
+}
+
+impl InstallScript {
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+}
+
+/// Downloads the install script to a temp file instead of piping it into bash
+///
+/// Only HTTPS (TLS 1.2+) is allowed, and curl retries transient failures,
+/// including GitHub's 429 rate limiting, honoring `Retry-After`. The body
+/// must look like the complete script, so an HTML error page or a
+/// truncated download is never run.
+pub fn download_install_script(url: &str) -> Result<InstallScript> {
+    if !url.starts_with("https://") {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Refusing to download the Homebrew installer over plain HTTP: {}",
+            url
+        )));
+    }
+
+    let file = tempfile::Builder::new()
+        .prefix("dotfiles-homebrew-install-")
+        .suffix(".sh")
+        .tempfile()?;
+    let output = CommandRunner::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+            "--tlsv1.2",
+            "--retry",
+            "4",
+            "--retry-delay",
+            "10",
+            "--retry-max-time",
+            "120",
+            "--output",
+        ])
+        .arg(file.path())
+        .arg(url)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .output()?;
+
+    if !output.success() {
+        let reason = output.stderr.trim();
+        return Err(DotfilesError::InstallationFailed(
+            if reason.contains("429") {
+                "GitHub is rate-limiting downloads (HTTP 429); wait a few minutes and re-run setup"
+                    .to_string()
+            } else {
+                format!("Could not download the Homebrew installer: {}", reason)
+            },
+        ));
+    }
+
+    let mut content = String::new();
+    let _ = file.reopen()?.read_to_string(&mut content);
+    if !looks_like_install_script(&content) {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "{} did not return the Homebrew install script (got {} bytes)",
+            url,
+            content.len()
+        )));
+    }
+
+    Ok(InstallScript {
+        sha256: sha256_hex(content.as_bytes()),
+        file,
+    })
+}
+
+/// Returns true if `content` is a complete bash script from Homebrew
+fn looks_like_install_script(content: &str) -> bool {
+    content.starts_with("#!/bin/bash") && content.contains("Homebrew") && content.ends_with('\n')
+}
+
+/// Fails unless the script matches the pinned checksum, if there is one
+pub fn verify_install_script(script: &InstallScript, expected: Option<&str>) -> Result<()> {
+    match expected {
+        Some(expected) if !expected.trim().eq_ignore_ascii_case(&script.sha256) => {
+            Err(DotfilesError::InstallationFailed(format!(
+                "Homebrew install script checksum mismatch: got {}, expected {}",
+                script.sha256,
+                expected.trim()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Returns the Homebrew repository left by an install that didn't finish
+///
+/// The installer clones into the repository before linking `bin/brew`, so
+/// a repository without a brew executable means the last attempt died.
+pub fn partial_install() -> Option<PathBuf> {
+    if is_installed() {
+        return None;
+    }
+    HOMEBREW_REPOSITORIES
+        .iter()
+        .map(PathBuf::from)
+        .find(|repo| repo.join(".git").is_dir())
+}
+
+/// Returns true if brew or a Homebrew installer may still be running
+///
+/// Looks for processes mentioning `repository`, Homebrew, or the script
+/// [`download_install_script`] saves. When `pgrep` can't answer, assumes
+/// one is, so locks are never cleared out from under a live process.
+pub fn install_running(repository: &Path) -> bool {
+    let pattern = format!(
+        "{}|Homebrew|dotfiles-homebrew-install-",
+        regex::escape(&repository.to_string_lossy())
+    );
+    let output = CommandRunner::new("pgrep")
+        .args(["-f", &pattern])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+    // pgrep exits 1 when nothing matched
+    !matches!(output, Ok(output) if output.status.code() == Some(1))
+}
+
+/// Removes git locks an interrupted install left behind, returning what was removed
+///
+/// Only call this when no other brew or installer process is running
+/// (see [`install_running`]).
+pub fn clear_stale_locks(repository: &Path) -> Vec<PathBuf> {
+    STALE_LOCKS
+        .iter()
+        .map(|lock| repository.join(lock))
+        .filter(|lock| lock.exists() && std::fs::remove_file(lock).is_ok())
+        .collect()
+}
+
+/// Runs a downloaded install script
+///
+/// The installer is idempotent, so re-running it after an interrupted
+/// attempt picks up from the existing repository. With `noninteractive`
+/// it doesn't wait for confirmation (sudo must already be cached).
+pub fn install(script: &InstallScript, noninteractive: bool) -> Result<()> {
     if is_installed() {
         return Ok(());
     }
 
     println!("Installing Homebrew...");
 
-    let status = Command::new("bash")
-        .arg("-c")
-        .arg(format!(
-            r#"/bin/bash -c "$(curl -fsSL {})""#,
-            HOMEBREW_INSTALL_URL
-        ))
-        .status()?;
+    let mut command = Command::new("/bin/bash");
+    command.arg(script.path());
+    if noninteractive {
+        command.env("NONINTERACTIVE", "1");
+    }
+    let status = command.status()?;
 
     if !status.success() {
         return Err(DotfilesError::InstallationFailed(
@@ -59,41 +234,374 @@ pub fn install() -> Result<()> {
 
 /// Installs a package using Homebrew
 pub fn install_package(package: &str) -> Result<()> {
+    brew_install(package, &[])
+}
+
+/// Installs a cask, even if a formula has the same name
+pub fn install_cask(cask: &str) -> Result<()> {
+    brew_install(cask, &["--cask"])
+}
+
+fn brew_install(package: &str, flags: &[&str]) -> Result<()> {
     let brew_path =
         get_brew_path().ok_or_else(|| DotfilesError::DependencyMissing("Homebrew".to_string()))?;
 
     println!("Installing {}...", package);
 
-    let status = Command::new(brew_path)
+    let output = CommandRunner::new(brew_path)
         .arg("install")
+        .args(flags)
         .arg(package)
-        .status()?;
+        .timeout(command::INSTALL_TIMEOUT)
+        .output()?;
 
-    if !status.success() {
+    if !output.success() {
         return Err(DotfilesError::InstallationFailed(format!(
-            "Failed to install {}",
-            package
+            "Failed to install {}: {}",
+            package,
+            output.stderr.trim()
         )));
     }
 
+    audit::record(Action::PackageInstalled {
+        package: package.to_string(),
+        manager: "brew".to_string(),
+    });
+    Ok(())
+}
+
+/// Downloads a package's bottles and sources without installing it
+///
+/// Unlike `brew install`, fetches don't take Homebrew's install lock, so
+/// several can run at once ahead of the (serial) installs.
+pub fn fetch_package(package: &str) -> Result<()> {
+    let brew_path =
+        get_brew_path().ok_or_else(|| DotfilesError::DependencyMissing("Homebrew".to_string()))?;
+
+    CommandRunner::new(brew_path)
+        .args(["fetch", "--deps", package])
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+
     Ok(())
 }
 
 /// Checks if a package is installed via Homebrew
 pub fn is_package_installed(package: &str) -> bool {
     if let Some(brew_path) = get_brew_path() {
-        let output = Command::new(brew_path).arg("list").arg(package).output();
+        let output = CommandRunner::new(brew_path)
+            .args(["list", package])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output();
 
         if let Ok(output) = output {
-            return output.status.success();
+            return output.success();
         }
     }
     false
 }
 
+/// Link state of an installed formula, from `brew info --json=v2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormulaInfo {
+    /// Full name, including the tap for tapped formulae
+    pub name: String,
+    pub linked: bool,
+    /// Keg-only formulae are intentionally left unlinked
+    pub keg_only: bool,
+}
+
+/// Returns link state for the installed formulae among `packages` (casks are ignored)
+pub fn formula_info(packages: &[String]) -> Vec<FormulaInfo> {
+    let Some(brew_path) = get_brew_path() else {
+        return Vec::new();
+    };
+    if packages.is_empty() {
+        return Vec::new();
+    }
+
+    match CommandRunner::new(brew_path)
+        .args(["info", "--json=v2"])
+        .args(packages)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => parse_formula_info(&output.stdout),
+        _ => Vec::new(),
+    }
+}
+
+fn parse_formula_info(json: &str) -> Vec<FormulaInfo> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Vec::new();
+    };
+
+    value["formulae"]
+        .as_array()
+        .map(|formulae| {
+            formulae
+                .iter()
+                .filter(|f| f["installed"].as_array().is_some_and(|i| !i.is_empty()))
+                .filter_map(|f| {
+                    Some(FormulaInfo {
+                        name: f["full_name"].as_str().or(f["name"].as_str())?.to_string(),
+                        linked: !f["linked_keg"].is_null(),
+                        keg_only: f["keg_only"].as_bool().unwrap_or(false),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a package is a formula or a cask
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageKind {
+    Formula,
+    Cask,
+}
+
+/// Install state of a formula or cask, from `brew info --json=v2`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageDetails {
+    /// Every name the package answers to: name, full name, and aliases
+    pub names: Vec<String>,
+    pub kind: PackageKind,
+    /// Tap it comes from, e.g. `homebrew/core`
+    pub tap: Option<String>,
+    /// Installed version, or None if not installed
+    pub installed: Option<String>,
+    /// Latest available version
+    pub latest: Option<String>,
+    pub outdated: bool,
+    pub pinned: bool,
+}
+
+impl PackageDetails {
+    /// Returns true if `package` refers to this formula or cask
+    pub fn matches(&self, package: &str) -> bool {
+        self.names.iter().any(|name| name == package)
+    }
+}
+
+/// Returns install state for `packages`, both formulae and casks
+///
+/// `brew info` fails outright if any name is unknown (e.g. a formula from a
+/// tap that isn't tapped yet), so on failure each package is asked about
+/// separately and the unknown ones are left out.
+pub fn package_details(packages: &[String]) -> Vec<PackageDetails> {
+    let Some(brew_path) = get_brew_path() else {
+        return Vec::new();
+    };
+    if packages.is_empty() {
+        return Vec::new();
+    }
+
+    let info = |names: &[String]| {
+        CommandRunner::new(&brew_path)
+            .args(["info", "--json=v2"])
+            .args(names)
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .ok()
+            .filter(|output| output.success())
+            .map(|output| parse_package_details(&output.stdout))
+    };
+
+    info(packages).unwrap_or_else(|| {
+        Pool::new()
+            .map(packages, |package| {
+                info(std::slice::from_ref(package)).unwrap_or_default()
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+    })
+}
+
+fn parse_package_details(json: &str) -> Vec<PackageDetails> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return Vec::new();
+    };
+    let strings = |values: &[&serde_json::Value]| -> Vec<String> {
+        values
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect()
+    };
+
+    let formulae = value["formulae"].as_array().into_iter().flatten().map(|f| {
+        let mut names = strings(&[&f["name"], &f["full_name"]]);
+        names.extend(strings(
+            &f["aliases"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>(),
+        ));
+        PackageDetails {
+            names,
+            kind: PackageKind::Formula,
+            tap: f["tap"].as_str().map(str::to_string),
+            installed: f["installed"]
+                .as_array()
+                .and_then(|versions| versions.last())
+                .and_then(|v| v["version"].as_str())
+                .map(str::to_string),
+            latest: f["versions"]["stable"].as_str().map(str::to_string),
+            outdated: f["outdated"].as_bool().unwrap_or(false),
+            pinned: f["pinned"].as_bool().unwrap_or(false),
+        }
+    });
+    let casks = value["casks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| PackageDetails {
+            names: strings(&[&c["token"], &c["full_token"]]),
+            kind: PackageKind::Cask,
+            tap: c["tap"].as_str().map(str::to_string),
+            installed: c["installed"].as_str().map(str::to_string),
+            latest: c["version"].as_str().map(str::to_string),
+            outdated: c["outdated"].as_bool().unwrap_or(false),
+            pinned: false,
+        });
+
+    formulae.chain(casks).collect()
+}
+
+/// Lists the taps Homebrew has added
+pub fn tapped() -> Vec<String> {
+    brew_lines(&["tap"])
+}
+
+/// Lists the casks Homebrew has installed
+pub fn installed_casks() -> Vec<String> {
+    brew_lines(&["list", "--cask", "-1"])
+}
+
+/// Lists every formula Homebrew has installed, dependencies included
+pub fn installed_formulae() -> Vec<String> {
+    brew_lines(&["list", "--formula", "-1"])
+}
+
+/// Lists the installed formulae nothing else depends on, according to `brew leaves`
+pub fn leaves() -> Vec<String> {
+    brew_lines(&["leaves"])
+}
+
+/// Runs a brew command that prints one name per line
+fn brew_lines(args: &[&str]) -> Vec<String> {
+    let Some(brew_path) = get_brew_path() else {
+        return Vec::new();
+    };
+
+    match CommandRunner::new(brew_path)
+        .args(args)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => output.stdout.lines().map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the tap a fully qualified name like `user/tap/formula` comes from
+pub fn tap_of(package: &str) -> Option<String> {
+    let mut parts = package.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(user), Some(repo), Some(_)) => Some(format!("{}/{}", user, repo)),
+        _ => None,
+    }
+}
+
+/// Returns formulae with missing dependencies, according to `brew missing`
+pub fn missing_dependencies(formulae: &[String]) -> Vec<(String, Vec<String>)> {
+    let Some(brew_path) = get_brew_path() else {
+        return Vec::new();
+    };
+    if formulae.is_empty() {
+        return Vec::new();
+    }
+
+    // `brew missing` exits non-zero when it finds something, so parse regardless
+    CommandRunner::new(brew_path)
+        .arg("missing")
+        .args(formulae)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .map(|output| parse_brew_missing(&output.stdout))
+        .unwrap_or_default()
+}
+
+fn parse_brew_missing(stdout: &str) -> Vec<(String, Vec<String>)> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (formula, deps) = line.split_once(':')?;
+            let deps: Vec<String> = deps.split_whitespace().map(str::to_string).collect();
+            (!deps.is_empty()).then(|| (formula.trim().to_string(), deps))
+        })
+        .collect()
+}
+
+/// Adds a tap so its formulae can be installed
+pub fn tap(name: &str) -> Result<()> {
+    let brew_path =
+        get_brew_path().ok_or_else(|| DotfilesError::DependencyMissing("Homebrew".to_string()))?;
+
+    CommandRunner::new(brew_path)
+        .args(["tap", name])
+        .timeout(command::NETWORK_TIMEOUT)
+        .run()?;
+
+    Ok(())
+}
+
+/// Pins a package so `brew upgrade` skips it
+pub fn pin_package(package: &str) -> Result<()> {
+    let brew_path =
+        get_brew_path().ok_or_else(|| DotfilesError::DependencyMissing("Homebrew".to_string()))?;
+
+    CommandRunner::new(brew_path)
+        .args(["pin", package])
+        .timeout(command::QUICK_TIMEOUT)
+        .run()?;
+
+    Ok(())
+}
+
+/// Lists packages pinned in Homebrew
+pub fn pinned_packages() -> Vec<String> {
+    let Some(brew_path) = get_brew_path() else {
+        return Vec::new();
+    };
+
+    match CommandRunner::new(brew_path)
+        .args(["list", "--pinned"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => output
+            .stdout
+            .split_whitespace()
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
 
     #[test]
     fn test_homebrew_paths_constant() {
@@ -120,6 +628,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_install_script_checks() {
+        assert!(looks_like_install_script(
+            "#!/bin/bash\n# Homebrew installer\nexit 0\n"
+        ));
+        assert!(!looks_like_install_script("<!DOCTYPE html>Homebrew\n"));
+        assert!(!looks_like_install_script("#!/bin/bash\n# Homebrew inst"));
+        assert!(download_install_script("http://example.com/install.sh").is_err());
+
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"abc").unwrap();
+        let script = InstallScript {
+            sha256: sha256_hex(b"abc"),
+            file,
+        };
+        assert_eq!(
+            script.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(verify_install_script(&script, None).is_ok());
+        assert!(verify_install_script(&script, Some(&script.sha256.to_uppercase())).is_ok());
+        assert!(verify_install_script(&script, Some("0000")).is_err());
+        let path = script.path().to_path_buf();
+        assert!(path.exists());
+        drop(script);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_clear_stale_locks() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".git")).unwrap();
+        std::fs::write(temp.path().join(".git/index.lock"), "").unwrap();
+
+        assert_eq!(
+            clear_stale_locks(temp.path()),
+            vec![temp.path().join(".git/index.lock")]
+        );
+        assert!(clear_stale_locks(temp.path()).is_empty());
+    }
+
     #[test]
     fn test_is_installed_consistency() {
         // is_installed() should match whether detect_homebrew() returns Some
@@ -141,4 +690,76 @@ mod tests {
             let _ = is_package_installed("git");
         }
     }
+
+    #[test]
+    fn test_parse_formula_info() {
+        let json = r#"{
+            "formulae": [
+                {"name": "fzf", "full_name": "fzf", "linked_keg": "0.56.3", "keg_only": false, "installed": [{"version": "0.56.3"}]},
+                {"name": "bat", "full_name": "bat", "linked_keg": null, "keg_only": false, "installed": [{"version": "0.24.0"}]},
+                {"name": "openssl@3", "full_name": "openssl@3", "linked_keg": null, "keg_only": true, "installed": [{"version": "3.4.0"}]},
+                {"name": "fd", "full_name": "fd", "linked_keg": null, "keg_only": false, "installed": []}
+            ],
+            "casks": [{"token": "obsidian"}]
+        }"#;
+
+        let infos = parse_formula_info(json);
+        assert_eq!(infos.len(), 3);
+        assert!(infos[0].linked);
+        assert_eq!(infos[1].name, "bat");
+        assert!(!infos[1].linked);
+        assert!(infos[2].keg_only);
+        assert!(parse_formula_info("not json").is_empty());
+    }
+
+    #[test]
+    fn test_parse_package_details() {
+        let json = r#"{
+            "formulae": [
+                {"name": "neovim", "full_name": "neovim", "aliases": ["nvim"], "tap": "homebrew/core",
+                 "versions": {"stable": "0.10.4"}, "installed": [{"version": "0.10.2"}], "outdated": true, "pinned": false},
+                {"name": "obsidian-cli", "full_name": "yakitrak/tap/obsidian-cli", "aliases": [], "tap": "yakitrak/tap",
+                 "versions": {"stable": "0.2.1"}, "installed": [], "outdated": false, "pinned": false}
+            ],
+            "casks": [
+                {"token": "obsidian", "full_token": "obsidian", "tap": "homebrew/cask",
+                 "version": "1.8.9", "installed": null, "outdated": false}
+            ]
+        }"#;
+
+        let details = parse_package_details(json);
+        assert_eq!(details.len(), 3);
+        assert!(details[0].matches("nvim"));
+        assert_eq!(details[0].installed.as_deref(), Some("0.10.2"));
+        assert!(details[0].outdated);
+        assert!(details[1].matches("yakitrak/tap/obsidian-cli"));
+        assert_eq!(details[1].installed, None);
+        assert_eq!(details[2].kind, PackageKind::Cask);
+        assert_eq!(details[2].latest.as_deref(), Some("1.8.9"));
+        assert!(parse_package_details("not json").is_empty());
+
+        assert_eq!(
+            tap_of("yakitrak/tap/obsidian-cli").as_deref(),
+            Some("yakitrak/tap")
+        );
+        assert_eq!(tap_of("obsidian"), None);
+    }
+
+    #[test]
+    fn test_parse_brew_missing() {
+        let stdout = "neovim: luajit tree-sitter
+tmux: utf8proc
+";
+        assert_eq!(
+            parse_brew_missing(stdout),
+            vec![
+                (
+                    "neovim".to_string(),
+                    vec!["luajit".to_string(), "tree-sitter".to_string()]
+                ),
+                ("tmux".to_string(), vec!["utf8proc".to_string()]),
+            ]
+        );
+        assert!(parse_brew_missing("").is_empty());
+    }
 }
@@ -0,0 +1,230 @@
+use crate::core::command::{self, CommandRunner};
+use crate::error::{DotfilesError, Result};
+use crate::symlink::manual::ManualSymlinker;
+use crate::symlink::SymlinkStatus;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Directory in the dotfiles repo holding `karabiner.json` (and `assets/`)
+pub const REPO_CONFIG_DIR: &str = "karabiner";
+
+/// Karabiner-Elements' command line tool
+pub const CLI_PATH: &str =
+    "/Library/Application Support/org.pqrs/Karabiner-Elements/bin/karabiner_cli";
+
+/// Returns `karabiner.json` in the dotfiles repo
+pub fn repo_config(dotfiles_dir: &Path) -> PathBuf {
+    dotfiles_dir.join(REPO_CONFIG_DIR).join("karabiner.json")
+}
+
+/// Returns the directory Karabiner reads (always `~/.config/karabiner`, regardless of XDG)
+pub fn config_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(".config/karabiner")
+}
+
+/// Links the repo's `karabiner/` directory to `~/.config/karabiner`
+///
+/// The directory is linked rather than the file: Karabiner saves by
+/// replacing `karabiner.json`, which would turn a file link back into a
+/// plain file, and it doesn't notice edits made through a file link.
+pub fn link_config(dotfiles_dir: &Path, home_dir: &Path) -> Result<SymlinkStatus> {
+    ManualSymlinker::new()
+        .create_symlink(&dotfiles_dir.join(REPO_CONFIG_DIR), &config_dir(home_dir))
+}
+
+/// Returns why a `karabiner.json` would be rejected, or an empty list if it is usable
+///
+/// Karabiner ignores a file it cannot load and runs with no remappings at
+/// all, so the top-level structure it expects is checked up front.
+pub fn structure_problems(content: &str) -> Vec<String> {
+    let root: Value = match serde_json::from_str(content) {
+        Ok(root) => root,
+        Err(e) => return vec![format!("invalid JSON: {}", e)],
+    };
+    let Some(root) = root.as_object() else {
+        return vec!["top level is not an object".to_string()];
+    };
+
+    let mut problems = Vec::new();
+    if root.get("global").is_some_and(|g| !g.is_object()) {
+        problems.push("\"global\" is not an object".to_string());
+    }
+
+    let profiles = match root.get("profiles").map(Value::as_array) {
+        None => return vec!["missing \"profiles\"".to_string()],
+        Some(None) => return vec!["\"profiles\" is not an array".to_string()],
+        Some(Some(profiles)) if profiles.is_empty() => {
+            return vec!["\"profiles\" is empty".to_string()]
+        }
+        Some(Some(profiles)) => profiles,
+    };
+
+    let mut selected = 0;
+    for (i, profile) in profiles.iter().enumerate() {
+        let Some(profile) = profile.as_object() else {
+            problems.push(format!("profiles[{}] is not an object", i));
+            continue;
+        };
+        let name = match profile.get("name").and_then(Value::as_str) {
+            Some(name) => name.to_string(),
+            None => {
+                problems.push(format!("profiles[{}] has no \"name\"", i));
+                format!("profiles[{}]", i)
+            }
+        };
+        if profile.get("selected").and_then(Value::as_bool) == Some(true) {
+            selected += 1;
+        }
+        if profile
+            .get("simple_modifications")
+            .is_some_and(|m| !m.is_array())
+        {
+            problems.push(format!(
+                "{}: \"simple_modifications\" is not an array",
+                name
+            ));
+        }
+        if let Some(complex) = profile.get("complex_modifications") {
+            problems.extend(rule_problems(&name, complex));
+        }
+    }
+    if selected > 1 {
+        problems.push(format!("{} profiles are marked selected", selected));
+    }
+
+    problems
+}
+
+fn rule_problems(profile: &str, complex: &Value) -> Vec<String> {
+    let Some(rules) = complex.get("rules") else {
+        return Vec::new();
+    };
+    let Some(rules) = rules.as_array() else {
+        return vec![format!(
+            "{}: \"complex_modifications.rules\" is not an array",
+            profile
+        )];
+    };
+
+    let mut problems = Vec::new();
+    for (i, rule) in rules.iter().enumerate() {
+        let label = rule
+            .get("description")
+            .and_then(Value::as_str)
+            .map(|d| format!("rule \"{}\"", d))
+            .unwrap_or_else(|| format!("rules[{}]", i));
+        let Some(manipulators) = rule.get("manipulators").and_then(Value::as_array) else {
+            problems.push(format!(
+                "{}: {} has no \"manipulators\" array",
+                profile, label
+            ));
+            continue;
+        };
+        for (j, manipulator) in manipulators.iter().enumerate() {
+            if manipulator.get("type").and_then(Value::as_str).is_none()
+                || !manipulator.get("from").is_some_and(Value::is_object)
+            {
+                problems.push(format!(
+                    "{}: {} manipulators[{}] needs \"type\" and a \"from\" object",
+                    profile, label, j
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Returns true if Karabiner-Elements' CLI is installed
+pub fn cli_installed() -> bool {
+    Path::new(CLI_PATH).exists()
+}
+
+/// Makes Karabiner reload its configuration by reselecting the current profile
+pub fn reload() -> Result<()> {
+    if !cli_installed() {
+        return Err(DotfilesError::DependencyMissing(
+            "Karabiner-Elements".to_string(),
+        ));
+    }
+
+    let output = CommandRunner::new(CLI_PATH)
+        .arg("--show-current-profile-name")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    let profile = output.stdout.trim();
+
+    CommandRunner::new(CLI_PATH)
+        .args(["--select-profile", profile])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const VALID: &str = r#"{
+        "global": { "show_in_menu_bar": true },
+        "profiles": [{
+            "name": "Default profile",
+            "selected": true,
+            "simple_modifications": [],
+            "complex_modifications": { "rules": [{
+                "description": "Caps Lock to Escape",
+                "manipulators": [{
+                    "type": "basic",
+                    "from": { "key_code": "caps_lock" },
+                    "to": [{ "key_code": "escape" }]
+                }]
+            }]}
+        }]
+    }"#;
+
+    #[test]
+    fn test_structure_valid() {
+        assert!(structure_problems(VALID).is_empty());
+    }
+
+    #[test]
+    fn test_structure_problems() {
+        assert!(structure_problems("{ \"profiles\": [ }")[0].starts_with("invalid JSON"));
+        assert_eq!(structure_problems("[]"), vec!["top level is not an object"]);
+        assert_eq!(structure_problems("{}"), vec!["missing \"profiles\""]);
+
+        let broken = r#"{ "profiles": [
+            { "name": "A", "selected": true, "complex_modifications": { "rules": [
+                { "description": "Hyper", "manipulators": [{ "type": "basic" }] },
+                { "manipulators": {} }
+            ]}},
+            { "selected": true }
+        ]}"#;
+        assert_eq!(
+            structure_problems(broken),
+            vec![
+                "A: rule \"Hyper\" manipulators[0] needs \"type\" and a \"from\" object",
+                "A: rules[1] has no \"manipulators\" array",
+                "profiles[1] has no \"name\"",
+                "2 profiles are marked selected",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_config_links_directory() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(dotfiles.join(REPO_CONFIG_DIR)).unwrap();
+        fs::write(repo_config(&dotfiles), VALID).unwrap();
+
+        let status = link_config(&dotfiles, temp.path()).unwrap();
+        assert!(status.is_success());
+        assert!(config_dir(temp.path()).is_symlink());
+        assert!(config_dir(temp.path()).join("karabiner.json").is_file());
+    }
+}
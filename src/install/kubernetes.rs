@@ -0,0 +1,202 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::walk::RepoWalker;
+use crate::error::Result;
+use crate::symlink::{manual::ManualSymlinker, SymlinkReport, Symlinker};
+use std::path::{Path, PathBuf};
+
+/// Directory in the dotfiles repo holding kubeconfig snippets
+pub const REPO_CONFIG_DIR: &str = "kube";
+
+/// Returns the directory kubeconfig snippets are linked into (`~/.kube/configs`)
+pub fn snippets_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(".kube/configs")
+}
+
+/// Returns the kubeconfig snippets kept in the dotfiles repo, sorted by name
+pub fn repo_snippets(dotfiles_dir: &Path) -> Vec<PathBuf> {
+    RepoWalker::new(dotfiles_dir.join(REPO_CONFIG_DIR))
+        .max_depth(1)
+        .files()
+        .unwrap_or_default()
+}
+
+/// Returns the files `KUBECONFIG` should list: `~/.kube/config` then each linked snippet
+///
+/// Empty when the repo has no snippets, so the variable is only exported when needed.
+pub fn kubeconfig_paths(dotfiles_dir: &Path, home_dir: &Path) -> Vec<PathBuf> {
+    let snippets = repo_snippets(dotfiles_dir);
+    if snippets.is_empty() {
+        return Vec::new();
+    }
+
+    let linked = snippets_dir(home_dir);
+    std::iter::once(home_dir.join(".kube/config"))
+        .chain(
+            snippets
+                .iter()
+                .filter_map(|path| path.file_name())
+                .map(|name| linked.join(name)),
+        )
+        .collect()
+}
+
+/// Links the repo's kubeconfig snippets into `~/.kube/configs`
+pub fn link_snippets(dotfiles_dir: &Path, home_dir: &Path) -> Result<SymlinkReport> {
+    let source = dotfiles_dir.join(REPO_CONFIG_DIR);
+    if !source.is_dir() {
+        return Ok(SymlinkReport::new());
+    }
+
+    let target = snippets_dir(home_dir);
+    std::fs::create_dir_all(&target)?;
+
+    ManualSymlinker::new().symlink(&source, &target)
+}
+
+/// Returns the context names defined in a kubeconfig file
+pub fn context_names(content: &str) -> Vec<String> {
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(content) else {
+        return Vec::new();
+    };
+
+    value
+        .get("contexts")
+        .and_then(|contexts| contexts.as_sequence())
+        .map(|contexts| {
+            contexts
+                .iter()
+                .filter_map(|context| context.get("name")?.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Lists installed krew plugins
+pub fn installed_krew_plugins() -> Vec<String> {
+    match CommandRunner::new("kubectl")
+        .args(["krew", "list"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => parse_krew_list(&output.stdout),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses `kubectl krew list`, which older krew versions print as a table
+fn parse_krew_list(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|name| *name != "PLUGIN")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Installs declared krew plugins that are not installed yet
+///
+/// Returns the plugins that were installed.
+pub fn ensure_krew_plugins(plugins: &[String]) -> Result<Vec<String>> {
+    let installed = installed_krew_plugins();
+    let mut added = Vec::new();
+
+    for plugin in plugins {
+        if installed.contains(plugin) {
+            continue;
+        }
+        CommandRunner::new("kubectl")
+            .args(["krew", "install", plugin])
+            .timeout(command::NETWORK_TIMEOUT)
+            .run()?;
+        added.push(plugin.clone());
+    }
+
+    Ok(added)
+}
+
+/// Returns true if the cluster behind a context answers within a few seconds
+pub fn is_context_reachable(context: &str) -> bool {
+    CommandRunner::new("kubectl")
+        .args([
+            "--context",
+            context,
+            "--request-timeout=5s",
+            "get",
+            "--raw",
+            "/version",
+        ])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .is_ok_and(|output| output.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_kubeconfig_paths() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+
+        assert!(kubeconfig_paths(&dotfiles, &home).is_empty());
+
+        fs::create_dir_all(dotfiles.join("kube")).unwrap();
+        fs::write(dotfiles.join("kube/work.yaml"), "").unwrap();
+        fs::write(dotfiles.join("kube/home-lab.yaml"), "").unwrap();
+
+        assert_eq!(
+            kubeconfig_paths(&dotfiles, &home),
+            vec![
+                home.join(".kube/config"),
+                home.join(".kube/configs/home-lab.yaml"),
+                home.join(".kube/configs/work.yaml"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_link_snippets() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        fs::create_dir_all(dotfiles.join("kube")).unwrap();
+        fs::write(dotfiles.join("kube/work.yaml"), "").unwrap();
+
+        let report = link_snippets(&dotfiles, &home).unwrap();
+
+        assert!(report.is_success());
+        assert!(home.join(".kube/configs/work.yaml").is_symlink());
+    }
+
+    #[test]
+    fn test_context_names() {
+        let content = "\
+apiVersion: v1
+kind: Config
+contexts:
+  - name: prod
+    context: {cluster: prod, user: me}
+  - name: staging
+    context: {cluster: staging, user: me}
+";
+        assert_eq!(context_names(content), vec!["prod", "staging"]);
+        assert!(context_names("not: [valid").is_empty());
+        assert!(context_names("apiVersion: v1\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_krew_list() {
+        assert_eq!(
+            parse_krew_list("PLUGIN   VERSION\nctx      v0.9.5\nns       v0.9.5\n"),
+            vec!["ctx", "ns"]
+        );
+        assert_eq!(parse_krew_list("krew\nneat\n"), vec!["krew", "neat"]);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::MachineSettings;
+use crate::detect::os::OS;
+use crate::error::{DotfilesError, Result};
+use std::path::{Path, PathBuf};
+
+/// Where timezone data lives on both macOS and Linux
+pub const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// A machine identity setting managed by setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Hostname,
+    Timezone,
+    Locale,
+}
+
+impl Setting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Hostname => "hostname",
+            Setting::Timezone => "timezone",
+            Setting::Locale => "locale",
+        }
+    }
+
+    /// Returns true if changing this setting needs sudo on `os`
+    pub fn needs_sudo(&self, os: OS) -> bool {
+        !(os == OS::MacOS && *self == Setting::Locale)
+    }
+}
+
+/// A configured setting that doesn't match the machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drift {
+    pub setting: Setting,
+    /// Current value, or None if it couldn't be read
+    pub current: Option<String>,
+    /// Configured value
+    pub desired: String,
+}
+
+/// Returns the configured settings that differ from the machine's
+pub fn drift(settings: &MachineSettings, os: OS) -> Vec<Drift> {
+    [
+        (Setting::Hostname, &settings.hostname),
+        (Setting::Timezone, &settings.timezone),
+        (Setting::Locale, &settings.locale),
+    ]
+    .into_iter()
+    .filter_map(|(setting, desired)| {
+        let desired = desired.as_deref()?;
+        let current = current(setting, os);
+        (current.as_deref() != Some(expected(setting, desired, os).as_str())).then(|| Drift {
+            setting,
+            current,
+            desired: desired.to_string(),
+        })
+    })
+    .collect()
+}
+
+/// Reads a setting's current value
+pub fn current(setting: Setting, os: OS) -> Option<String> {
+    match (setting, os) {
+        (Setting::Hostname, OS::MacOS) => read_command("scutil", &["--get", "ComputerName"]),
+        (Setting::Hostname, _) => std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .map(|name| name.trim().to_string()),
+        (Setting::Timezone, _) => std::fs::read_link("/etc/localtime")
+            .ok()
+            .and_then(|link| zone_from_link(&link))
+            .or_else(|| {
+                std::fs::read_to_string("/etc/timezone")
+                    .ok()
+                    .map(|zone| zone.trim().to_string())
+            }),
+        (Setting::Locale, OS::MacOS) => read_command("defaults", &["read", "-g", "AppleLocale"]),
+        (Setting::Locale, _) => ["/etc/locale.conf", "/etc/default/locale"]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| lang_from_conf(&content)),
+    }
+}
+
+/// Returns the value `current` reports once `desired` is applied
+fn expected(setting: Setting, desired: &str, os: OS) -> String {
+    match (setting, os) {
+        (Setting::Hostname, OS::MacOS) => desired.to_string(),
+        (Setting::Hostname, _) => local_host_name(desired),
+        (Setting::Locale, OS::MacOS) => apple_locale(desired),
+        _ => desired.to_string(),
+    }
+}
+
+/// Returns the commands that set a value, as argument lists
+pub fn commands(setting: Setting, value: &str, os: OS) -> Vec<Vec<String>> {
+    let cmd = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let local = local_host_name(value);
+    match (setting, os) {
+        (Setting::Hostname, OS::MacOS) => vec![
+            cmd(&["sudo", "scutil", "--set", "ComputerName", value]),
+            cmd(&["sudo", "scutil", "--set", "LocalHostName", &local]),
+            cmd(&["sudo", "scutil", "--set", "HostName", &local]),
+        ],
+        (Setting::Hostname, _) => vec![
+            cmd(&["sudo", "hostnamectl", "set-hostname", &local]),
+            cmd(&["sudo", "hostnamectl", "set-hostname", "--pretty", value]),
+        ],
+        (Setting::Timezone, OS::MacOS) => {
+            vec![cmd(&["sudo", "systemsetup", "-settimezone", value])]
+        }
+        (Setting::Timezone, _) => vec![cmd(&["sudo", "timedatectl", "set-timezone", value])],
+        (Setting::Locale, OS::MacOS) => vec![cmd(&[
+            "defaults",
+            "write",
+            "-g",
+            "AppleLocale",
+            &apple_locale(value),
+        ])],
+        (Setting::Locale, _) => vec![cmd(&[
+            "sudo",
+            "localectl",
+            "set-locale",
+            &format!("LANG={}", value),
+        ])],
+    }
+}
+
+/// Formats commands as one shell line, quoting arguments with spaces
+pub fn display_commands(commands: &[Vec<String>]) -> String {
+    commands
+        .iter()
+        .map(|args| {
+            args.iter()
+                .map(|arg| {
+                    if arg.contains(' ') {
+                        format!("\"{}\"", arg)
+                    } else {
+                        arg.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// Sets a value, running the commands from `commands`
+pub fn apply(setting: Setting, value: &str, os: OS) -> Result<()> {
+    if os == OS::Unknown {
+        return Err(DotfilesError::Config(format!(
+            "Setting the {} is not supported on this OS",
+            setting.name()
+        )));
+    }
+    for args in commands(setting, value, os) {
+        CommandRunner::new(&args[0])
+            .args(&args[1..])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run()?;
+    }
+    let path = match (setting, os) {
+        (Setting::Locale, OS::MacOS) => crate::core::paths::home_dir()
+            .unwrap_or_default()
+            .join("Library/Preferences/.GlobalPreferences.plist"),
+        (Setting::Locale, _) => PathBuf::from("/etc/locale.conf"),
+        (Setting::Hostname, OS::MacOS) => {
+            PathBuf::from("/Library/Preferences/SystemConfiguration/preferences.plist")
+        }
+        (Setting::Hostname, _) => PathBuf::from("/etc/hostname"),
+        (Setting::Timezone, _) => PathBuf::from("/etc/localtime"),
+    };
+    audit::record(Action::FileWritten {
+        path,
+        reason: format!("set {} to {}", setting.name(), value),
+    });
+    Ok(())
+}
+
+/// Derives a network hostname from a computer name ("Jo's Laptop" -> "Jos-Laptop")
+///
+/// Keeps ASCII letters, digits, and hyphens, turning whitespace and
+/// underscores into hyphens, and limits the result to 63 characters.
+pub fn local_host_name(name: &str) -> String {
+    let mut local = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            local.push(c);
+        } else if (c.is_whitespace() || c == '_' || c == '-') && !local.ends_with('-') {
+            local.push('-');
+        }
+    }
+    local.truncate(63);
+    local.trim_matches('-').to_string()
+}
+
+/// Returns true if `zone` names a file in the system's timezone database
+pub fn is_known_timezone(zone: &str) -> bool {
+    !zone.is_empty()
+        && !zone.starts_with('/')
+        && !zone.contains("..")
+        && Path::new(ZONEINFO_DIR).join(zone).is_file()
+}
+
+/// Extracts the zone name from an `/etc/localtime` link target
+fn zone_from_link(link: &Path) -> Option<String> {
+    let link = link.to_string_lossy();
+    let (_, zone) = link.split_once("zoneinfo/")?;
+    Some(zone.to_string())
+}
+
+/// Returns `LANG` from a locale.conf-style file
+fn lang_from_conf(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let value = line.trim().strip_prefix("LANG=")?;
+        Some(value.trim_matches('"').to_string())
+    })
+}
+
+/// Converts a POSIX locale to macOS's `AppleLocale` form ("en_US.UTF-8" -> "en_US")
+fn apple_locale(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or(locale)
+        .to_string()
+}
+
+fn read_command(program: &str, args: &[&str]) -> Option<String> {
+    let output = CommandRunner::new(program)
+        .args(args)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+    output
+        .success()
+        .then(|| output.stdout.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_host_name() {
+        assert_eq!(local_host_name("Jo's Work  Laptop"), "Jos-Work-Laptop");
+        assert_eq!(local_host_name(" studio_mac "), "studio-mac");
+        assert_eq!(local_host_name("!!!"), "");
+        assert_eq!(local_host_name(&"a".repeat(80)).len(), 63);
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(
+            display_commands(&commands(Setting::Hostname, "Work Laptop", OS::MacOS)),
+            "sudo scutil --set ComputerName \"Work Laptop\" && \
+             sudo scutil --set LocalHostName Work-Laptop && \
+             sudo scutil --set HostName Work-Laptop"
+        );
+        assert_eq!(
+            display_commands(&commands(Setting::Locale, "de_DE.UTF-8", OS::MacOS)),
+            "defaults write -g AppleLocale de_DE"
+        );
+        assert_eq!(
+            display_commands(&commands(Setting::Timezone, "Europe/Berlin", OS::Linux)),
+            "sudo timedatectl set-timezone Europe/Berlin"
+        );
+        assert!(!Setting::Locale.needs_sudo(OS::MacOS));
+        assert!(Setting::Locale.needs_sudo(OS::Linux));
+    }
+
+    #[test]
+    fn test_parse_current_values() {
+        assert_eq!(
+            zone_from_link(Path::new("/var/db/timezone/zoneinfo/America/New_York")),
+            Some("America/New_York".to_string())
+        );
+        assert_eq!(zone_from_link(Path::new("/etc/UTC")), None);
+        assert_eq!(
+            lang_from_conf("# comment\nLANG=\"en_GB.UTF-8\"\nLC_TIME=C\n"),
+            Some("en_GB.UTF-8".to_string())
+        );
+        assert_eq!(apple_locale("en_US.UTF-8"), "en_US");
+        assert_eq!(
+            expected(Setting::Hostname, "Work Laptop", OS::Linux),
+            "Work-Laptop"
+        );
+    }
+}
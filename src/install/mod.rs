@@ -1,6 +1,22 @@
+pub mod appconfig;
+pub mod aws;
+pub mod brewfile;
+pub mod copies;
+pub mod git;
 pub mod homebrew;
+pub mod karabiner;
+pub mod kubernetes;
+pub mod machine;
+pub mod nvim;
+pub mod obsidian;
+pub mod package_manager;
 pub mod packages;
+pub mod prebuilt;
 pub mod repos;
 pub mod shell;
+pub mod sync_agent;
+pub mod templates;
 pub mod tools;
 pub mod version_manager;
+pub mod vm_config;
+pub mod xcode;
@@ -0,0 +1,521 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::{NvimDistro, NvimSettings};
+use crate::core::walk::RepoWalker;
+use crate::detect::tools;
+use crate::error::{DotfilesError, Result};
+use crate::install::repos;
+use crate::install::version_manager::{self, VersionManager};
+use std::path::Path;
+
+const LAZYVIM_URL: &str = "https://github.com/LazyVim/starter";
+const KICKSTART_URL: &str = "https://github.com/nvim-lua/kickstart.nvim";
+
+/// Severity of one `:checkhealth` line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthLevel {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// The parts of `:checkhealth` doctor reports on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthArea {
+    Python3,
+    Node,
+    Clipboard,
+    TreeSitter,
+}
+
+impl HealthArea {
+    pub const ALL: [HealthArea; 4] = [
+        HealthArea::Python3,
+        HealthArea::Node,
+        HealthArea::Clipboard,
+        HealthArea::TreeSitter,
+    ];
+
+    /// Returns the label used in check names
+    pub fn label(&self) -> &str {
+        match self {
+            HealthArea::Python3 => "python3 provider",
+            HealthArea::Node => "node provider",
+            HealthArea::Clipboard => "clipboard",
+            HealthArea::TreeSitter => "tree-sitter",
+        }
+    }
+
+    /// Returns true for providers `doctor --fix` can install
+    pub fn is_fixable(&self) -> bool {
+        matches!(self, HealthArea::Python3 | HealthArea::Node)
+    }
+
+    fn from_topic(topic: &str) -> Option<Self> {
+        let topic = topic.to_lowercase();
+        if topic.contains("python 3") || topic.contains("python3") {
+            Some(HealthArea::Python3)
+        } else if topic.contains("node") {
+            Some(HealthArea::Node)
+        } else if topic.contains("clipboard") {
+            Some(HealthArea::Clipboard)
+        } else if topic.contains("treesitter") || topic.contains("tree-sitter") {
+            Some(HealthArea::TreeSitter)
+        } else {
+            None
+        }
+    }
+}
+
+/// One reported line from `:checkhealth`, attributed to an area
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthItem {
+    pub area: HealthArea,
+    pub level: HealthLevel,
+    pub message: String,
+}
+
+/// Runs `:checkhealth` headlessly and returns the health buffer's text
+pub fn checkhealth() -> Option<String> {
+    if !tools::is_installed("nvim") {
+        return None;
+    }
+
+    let out = std::env::temp_dir().join(format!("dotfiles-checkhealth-{}.txt", std::process::id()));
+    // Passed through the environment, so spaces or other bytes in the path need no escaping
+    let status = CommandRunner::new("nvim")
+        .env("DOTFILES_CHECKHEALTH_OUT", &out)
+        .args([
+            "--headless",
+            "+checkhealth",
+            "+execute 'write! ' . fnameescape($DOTFILES_CHECKHEALTH_OUT)",
+            "+qa!",
+        ])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+
+    let content = std::fs::read_to_string(&out).ok();
+    let _ = std::fs::remove_file(&out);
+    status.ok().and(content)
+}
+
+/// Parses `:checkhealth` output (old `## Heading` and 0.10+ `Heading ~` formats)
+///
+/// Lines outside the areas doctor cares about are dropped.
+pub fn parse_checkhealth(output: &str) -> Vec<HealthItem> {
+    let mut items = Vec::new();
+    let mut section = String::new();
+    let mut heading = String::new();
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("==") {
+            continue;
+        }
+
+        if let Some(h) = trimmed.strip_prefix("## ") {
+            heading = h.to_string();
+        } else if let Some(h) = trimmed.strip_suffix(" ~") {
+            heading = h.to_string();
+        } else if !line.starts_with(' ') && !line.starts_with('-') {
+            // Section line, e.g. `vim.provider: ...` or `nvim-treesitter: ...`
+            if let Some((name, _)) = line.split_once(':') {
+                if !name.contains(' ') {
+                    section = name.to_string();
+                    heading.clear();
+                }
+            }
+        } else if let Some(item) = trimmed.strip_prefix("- ") {
+            let Some((level, message)) = parse_item(item) else {
+                continue;
+            };
+            let topic = format!("{} {}", section, heading);
+            if let Some(area) = HealthArea::from_topic(&topic) {
+                items.push(HealthItem {
+                    area,
+                    level,
+                    message: message.to_string(),
+                });
+            }
+        }
+    }
+
+    items
+}
+
+/// Splits `⚠️ WARNING msg` / `WARNING: msg` / `OK msg` into level and message
+fn parse_item(item: &str) -> Option<(HealthLevel, &str)> {
+    let item = item
+        .trim_start_matches(|c: char| !c.is_ascii())
+        .trim_start();
+    [
+        ("ERROR", HealthLevel::Error),
+        ("WARNING", HealthLevel::Warning),
+        ("OK", HealthLevel::Ok),
+    ]
+    .into_iter()
+    .find_map(|(keyword, level)| {
+        let rest = item.strip_prefix(keyword)?;
+        let rest = rest.strip_prefix(':').unwrap_or(rest);
+        Some((level, rest.trim()))
+    })
+}
+
+/// Installs the host package nvim needs for a provider, using the runtime on PATH
+///
+/// Runtimes come from the version manager when one is set up, so its shims
+/// are refreshed afterwards.
+pub fn install_provider(area: HealthArea) -> Result<()> {
+    let (program, args, language): (&str, &[&str], &str) = match area {
+        HealthArea::Python3 => (
+            "python3",
+            &["-m", "pip", "install", "--upgrade", "pynvim"],
+            "python",
+        ),
+        HealthArea::Node => ("npm", &["install", "-g", "neovim"], "nodejs"),
+        other => {
+            return Err(DotfilesError::InstallFailed(format!(
+                "{} cannot be fixed automatically",
+                other.label()
+            )))
+        }
+    };
+
+    if !tools::is_installed(program) {
+        return Err(DotfilesError::DependencyMissing(format!(
+            "{} not found; install the {} runtime first (dotfiles setup)",
+            program, language
+        )));
+    }
+
+    CommandRunner::new(program)
+        .args(args)
+        .timeout(command::NETWORK_TIMEOUT)
+        .run()?;
+
+    if version_manager::detect() == Some(VersionManager::Asdf) {
+        let _ = CommandRunner::new("asdf")
+            .args(["reshim", language])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .run();
+    }
+
+    Ok(())
+}
+
+/// Returns the repository `[nvim]` clones into the nvim config directory
+///
+/// None when no distro is chosen, or for `own` without a `url`, whose
+/// config is linked from the dotfiles repo like any other.
+pub fn distro_url(settings: &NvimSettings) -> Option<&str> {
+    match settings.distro? {
+        NvimDistro::LazyVim => Some(LAZYVIM_URL),
+        NvimDistro::Kickstart => Some(KICKSTART_URL),
+        NvimDistro::Own => settings.url.as_deref().filter(|url| !url.trim().is_empty()),
+    }
+}
+
+/// Clones the distro at `url` into `config_dir`; returns false if it was already there
+///
+/// Anything else at `config_dir` is left alone and reported, so a config
+/// written by hand is never replaced.
+pub fn install_distro(url: &str, config_dir: &Path) -> Result<bool> {
+    if is_distro_checkout(url, config_dir) {
+        return Ok(false);
+    }
+    if config_dir.symlink_metadata().is_ok() {
+        return Err(DotfilesError::Config(format!(
+            "{} already exists; move it aside to install {}",
+            config_dir.display(),
+            url
+        )));
+    }
+
+    if let Some(parent) = config_dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    CommandRunner::new("git")
+        .args(["clone", "--quiet", url])
+        .arg(config_dir)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(true)
+}
+
+/// What `update_distro` did with the distro clone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistroUpdate {
+    /// Fast-forwarded to upstream, or already there
+    Updated,
+    /// Left alone: it has local commits, or edits in the way of upstream's
+    Diverged,
+}
+
+/// Fast-forwards the distro clone in `config_dir` to upstream
+///
+/// Starter configs are meant to be edited, so a clone that has moved away
+/// from upstream is reported as [`DistroUpdate::Diverged`] rather than
+/// failing the update.
+pub fn update_distro(url: &str, config_dir: &Path) -> Result<DistroUpdate> {
+    if !is_distro_checkout(url, config_dir) {
+        return Err(DotfilesError::Config(format!(
+            "{} is not a clone of {}; run: dotfiles setup",
+            config_dir.display(),
+            url
+        )));
+    }
+    let status = repos::status(config_dir)?;
+    if status.ahead > 0 {
+        return Ok(DistroUpdate::Diverged);
+    }
+    if status.behind == 0 {
+        return Ok(DistroUpdate::Updated);
+    }
+    let merged = CommandRunner::new("git")
+        .arg("-C")
+        .arg(config_dir)
+        .args(["merge", "--quiet", "--ff-only", "@{upstream}"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run();
+    match merged {
+        Ok(_) => Ok(DistroUpdate::Updated),
+        Err(_) if status.changed > 0 => Ok(DistroUpdate::Diverged),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns true if the config in `config_dir` manages its plugins with lazy.nvim
+///
+/// Checked by its lockfile, or a Lua file that mentions `lazy.nvim` (the
+/// bootstrap every lazy.nvim config carries).
+pub fn uses_lazy(config_dir: &Path) -> bool {
+    if config_dir.join("lazy-lock.json").is_file() {
+        return true;
+    }
+    let root = config_dir
+        .canonicalize()
+        .unwrap_or_else(|_| config_dir.to_path_buf());
+    RepoWalker::new(root)
+        .respect_ignores(false)
+        .files()
+        .unwrap_or_default()
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "lua"))
+        .any(|path| std::fs::read_to_string(path).is_ok_and(|lua| lua.contains("lazy.nvim")))
+}
+
+/// Installs, cleans, and updates plugins headlessly through lazy.nvim
+///
+/// Returns false without running nvim when the config in `config_dir`
+/// doesn't use lazy.nvim, since `:Lazy` wouldn't exist.
+pub fn sync_plugins(config_dir: &Path) -> Result<bool> {
+    if !uses_lazy(config_dir) {
+        return Ok(false);
+    }
+    if !tools::is_installed("nvim") {
+        return Err(DotfilesError::DependencyMissing(
+            "nvim not found; install it first (dotfiles setup)".to_string(),
+        ));
+    }
+    CommandRunner::new("nvim")
+        .args(["--headless", "+Lazy! sync", "+qa"])
+        .timeout(command::INSTALL_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(true)
+}
+
+fn is_distro_checkout(url: &str, dir: &Path) -> bool {
+    !dir.is_symlink() && dir.join(".git").is_dir() && repos::remote_url(dir).as_deref() == Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODERN: &str = "\
+==============================================================================
+vim.provider:                                                     2 ⚠️  1 ❌
+
+Clipboard (optional) ~
+- ✅ OK Clipboard tool found: pbcopy
+
+Node.js provider (optional) ~
+- Node.js: v20.11.0
+- ⚠️ WARNING Missing \"neovim\" npm (or yarn, pnpm) package.
+  - ADVICE:
+    - Run in shell: npm install -g neovim
+
+Perl provider (optional) ~
+- ⚠️ WARNING \"Neovim::Ext\" cpan module is not installed
+
+Python 3 provider (optional) ~
+- ❌ ERROR Python provider error:
+  - ADVICE:
+    - If you have pynvim installed, check your $PATH
+
+==============================================================================
+nvim-treesitter: require(\"nvim-treesitter.health\").check()
+
+Installation ~
+- ⚠️ WARNING `tree-sitter` executable not found
+";
+
+    const LEGACY: &str = "\
+provider: health#provider#check
+========================================================================
+## Clipboard (optional)
+  - WARNING: No clipboard tool found.
+
+## Python 3 provider (optional)
+  - OK: Latest pynvim is installed.
+";
+
+    #[test]
+    fn test_parse_checkhealth_modern() {
+        let items = parse_checkhealth(MODERN);
+        let summary: Vec<(HealthArea, HealthLevel)> =
+            items.iter().map(|item| (item.area, item.level)).collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (HealthArea::Clipboard, HealthLevel::Ok),
+                (HealthArea::Node, HealthLevel::Warning),
+                (HealthArea::Python3, HealthLevel::Error),
+                (HealthArea::TreeSitter, HealthLevel::Warning),
+            ]
+        );
+        assert_eq!(
+            items[1].message,
+            "Missing \"neovim\" npm (or yarn, pnpm) package."
+        );
+    }
+
+    #[test]
+    fn test_parse_checkhealth_legacy() {
+        let items = parse_checkhealth(LEGACY);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].area, HealthArea::Clipboard);
+        assert_eq!(items[0].level, HealthLevel::Warning);
+        assert_eq!(items[0].message, "No clipboard tool found.");
+        assert_eq!(items[1].level, HealthLevel::Ok);
+    }
+
+    #[test]
+    fn test_parse_item() {
+        assert_eq!(
+            parse_item("WARNING: thing"),
+            Some((HealthLevel::Warning, "thing"))
+        );
+        assert_eq!(parse_item("✅ OK fine"), Some((HealthLevel::Ok, "fine")));
+        assert_eq!(parse_item("Node.js: v20"), None);
+    }
+
+    #[test]
+    fn test_install_provider_rejects_unfixable() {
+        assert!(install_provider(HealthArea::Clipboard).is_err());
+    }
+
+    #[test]
+    fn test_distro_url() {
+        let settings = |distro, url: Option<&str>| NvimSettings {
+            distro,
+            url: url.map(String::from),
+        };
+        assert_eq!(
+            distro_url(&settings(Some(NvimDistro::LazyVim), None)),
+            Some(LAZYVIM_URL)
+        );
+        assert_eq!(
+            distro_url(&settings(Some(NvimDistro::Kickstart), None)),
+            Some(KICKSTART_URL)
+        );
+        assert_eq!(
+            distro_url(&settings(
+                Some(NvimDistro::Own),
+                Some("git@host:me/nvim.git")
+            )),
+            Some("git@host:me/nvim.git")
+        );
+        assert_eq!(distro_url(&settings(Some(NvimDistro::Own), None)), None);
+        assert_eq!(distro_url(&settings(None, Some("ignored"))), None);
+    }
+
+    #[test]
+    fn test_uses_lazy() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config_dir = temp.path();
+        std::fs::create_dir_all(config_dir.join("lua/config")).unwrap();
+        std::fs::write(config_dir.join("init.lua"), "vim.opt.number = true\n").unwrap();
+        assert!(!uses_lazy(config_dir));
+        assert!(!sync_plugins(config_dir).unwrap());
+
+        std::fs::write(
+            config_dir.join("lua/config/lazy.lua"),
+            "local lazypath = vim.fn.stdpath(\"data\") .. \"/lazy/lazy.nvim\"\n",
+        )
+        .unwrap();
+        assert!(uses_lazy(config_dir));
+    }
+
+    #[test]
+    fn test_install_and_update_distro() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let upstream = temp.path().join("starter");
+        let git = |dir: &Path, args: &[&str]| {
+            CommandRunner::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .stream(false)
+                .run()
+                .unwrap();
+        };
+        std::fs::create_dir_all(&upstream).unwrap();
+        git(&upstream, &["init", "--quiet"]);
+        std::fs::write(upstream.join("init.lua"), "-- v1\n").unwrap();
+        git(&upstream, &["add", "."]);
+        git(&upstream, &["commit", "--quiet", "-m", "v1"]);
+        let url = upstream.to_string_lossy().into_owned();
+
+        let config_dir = temp.path().join(".config/nvim");
+        assert!(install_distro(&url, &config_dir).unwrap());
+        assert!(!install_distro(&url, &config_dir).unwrap());
+
+        std::fs::write(upstream.join("init.lua"), "-- v2\n").unwrap();
+        git(&upstream, &["commit", "--quiet", "-am", "v2"]);
+        assert_eq!(
+            update_distro(&url, &config_dir).unwrap(),
+            DistroUpdate::Updated
+        );
+        assert_eq!(
+            std::fs::read_to_string(config_dir.join("init.lua")).unwrap(),
+            "-- v2\n"
+        );
+
+        // A starter the user has committed to is left alone, not failed on
+        std::fs::write(config_dir.join("init.lua"), "-- mine\n").unwrap();
+        git(&config_dir, &["commit", "--quiet", "-am", "mine"]);
+        std::fs::write(upstream.join("init.lua"), "-- v3\n").unwrap();
+        git(&upstream, &["commit", "--quiet", "-am", "v3"]);
+        assert_eq!(
+            update_distro(&url, &config_dir).unwrap(),
+            DistroUpdate::Diverged
+        );
+        assert_eq!(
+            std::fs::read_to_string(config_dir.join("init.lua")).unwrap(),
+            "-- mine\n"
+        );
+
+        // A config that isn't the distro's clone is never replaced
+        let other = temp.path().join("other/nvim");
+        std::fs::create_dir_all(&other).unwrap();
+        assert!(install_distro(&url, &other).is_err());
+        assert!(update_distro(&url, &other).is_err());
+    }
+}
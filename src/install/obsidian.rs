@@ -0,0 +1,122 @@
+use crate::core::command::{self, CommandRunner};
+use crate::error::{DotfilesError, Result};
+use crate::symlink::{manual::ManualSymlinker, SymlinkReport, Symlinker};
+use std::path::Path;
+
+/// Directory in the dotfiles repo holding shared vault config (`snippets/`, `appearance.json`, ...)
+pub const REPO_CONFIG_DIR: &str = "obsidian";
+
+/// Returns the vault name obsidian-cli knows the vault by (its directory name)
+pub fn vault_name(vault: &Path) -> Option<String> {
+    vault
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Sets the obsidian-cli default vault
+pub fn set_default_vault(vault: &Path) -> Result<()> {
+    let name = vault_name(vault).ok_or_else(|| {
+        DotfilesError::Config(format!("{} is not a vault directory", vault.display()))
+    })?;
+
+    CommandRunner::new("obsidian-cli")
+        .args(["set-default", &name])
+        .timeout(command::QUICK_TIMEOUT)
+        .run()?;
+
+    Ok(())
+}
+
+/// Returns the obsidian-cli default vault name, if one is set
+pub fn default_vault() -> Option<String> {
+    let output = CommandRunner::new("obsidian-cli")
+        .arg("print-default")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()
+        .ok()?;
+
+    parse_default_vault(&output.stdout)
+}
+
+/// Extracts the vault name from `obsidian-cli print-default` output
+fn parse_default_vault(stdout: &str) -> Option<String> {
+    let lines: Vec<&str> = stdout
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    lines
+        .iter()
+        .find_map(|line| {
+            let (label, value) = line.split_once(':')?;
+            label
+                .to_lowercase()
+                .contains("name")
+                .then(|| value.trim().to_string())
+        })
+        .or_else(|| lines.first().map(|line| line.to_string()))
+}
+
+/// Links the repo's shared Obsidian config into `<vault>/.obsidian`
+///
+/// Each entry in `<dotfiles>/obsidian` (snippets directory, json settings) is
+/// linked individually so the vault keeps its own workspace and plugin data.
+pub fn link_vault_config(dotfiles_dir: &Path, vault: &Path) -> Result<SymlinkReport> {
+    let source = dotfiles_dir.join(REPO_CONFIG_DIR);
+    if !source.is_dir() {
+        return Ok(SymlinkReport::new());
+    }
+
+    let target = vault.join(".obsidian");
+    std::fs::create_dir_all(&target)?;
+
+    ManualSymlinker::new().symlink(&source, &target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_vault_name() {
+        assert_eq!(
+            vault_name(Path::new("/Users/me/Notes")).as_deref(),
+            Some("Notes")
+        );
+    }
+
+    #[test]
+    fn test_parse_default_vault() {
+        let stdout = "Default vault name:  Notes\nDefault vault path:  /Users/me/Notes\n";
+        assert_eq!(parse_default_vault(stdout).as_deref(), Some("Notes"));
+        assert_eq!(parse_default_vault("Notes\n").as_deref(), Some("Notes"));
+        assert_eq!(parse_default_vault(""), None);
+    }
+
+    #[test]
+    fn test_link_vault_config() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let vault = temp.path().join("Notes");
+        fs::create_dir_all(dotfiles.join("obsidian/snippets")).unwrap();
+        fs::write(dotfiles.join("obsidian/appearance.json"), "{}").unwrap();
+        fs::create_dir_all(&vault).unwrap();
+
+        let report = link_vault_config(&dotfiles, &vault).unwrap();
+
+        assert!(report.is_success());
+        assert!(vault.join(".obsidian/snippets").is_symlink());
+        assert!(vault.join(".obsidian/appearance.json").is_symlink());
+    }
+
+    #[test]
+    fn test_link_vault_config_without_repo_config() {
+        let temp = TempDir::new().unwrap();
+        let report = link_vault_config(temp.path(), &temp.path().join("Notes")).unwrap();
+        assert_eq!(report.total(), 0);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandOutput, CommandRunner};
+use crate::core::config::{PackageSettings, PackageSource};
+use crate::core::sudo;
+use crate::detect::backend::PackageBackend;
+use crate::detect::os::{detect_os, OS};
+use crate::error::{DotfilesError, Result};
+use crate::install::{homebrew, prebuilt};
+use std::sync::OnceLock;
+
+/// A system package manager setup installs packages with
+///
+/// Packages are always named by their Homebrew formula, as in the catalog
+/// and `~/.dotfiles.conf`; each manager translates them to its own names.
+pub trait PackageManager: Sync {
+    /// Short display name, also recorded in the audit log
+    fn name(&self) -> &'static str;
+
+    /// Checks whether a package is installed
+    fn is_installed(&self, package: &str) -> bool;
+
+    /// Installs a package
+    fn install(&self, package: &str) -> Result<()>;
+
+    /// Whether `fetch` downloads anything, so that it is worth running concurrently
+    fn can_fetch(&self) -> bool {
+        false
+    }
+
+    /// Downloads a package without installing it
+    fn fetch(&self, _package: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Adds a third-party repository (a Homebrew tap)
+    fn tap(&self, name: &str) -> Result<()>;
+
+    /// Lists the third-party repositories already added
+    fn tapped(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Whether installing needs sudo
+    fn needs_sudo(&self) -> bool {
+        false
+    }
+}
+
+/// Homebrew, on macOS and Linux (Linuxbrew)
+pub struct Homebrew;
+
+impl PackageManager for Homebrew {
+    fn name(&self) -> &'static str {
+        "Homebrew"
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        homebrew::is_package_installed(package)
+    }
+
+    fn install(&self, package: &str) -> Result<()> {
+        homebrew::install_package(package)
+    }
+
+    fn can_fetch(&self) -> bool {
+        true
+    }
+
+    fn fetch(&self, package: &str) -> Result<()> {
+        homebrew::fetch_package(package)
+    }
+
+    fn tap(&self, name: &str) -> Result<()> {
+        homebrew::tap(name)
+    }
+
+    fn tapped(&self) -> Vec<String> {
+        homebrew::tapped()
+    }
+}
+
+/// apt, on Debian and Ubuntu
+pub struct Apt;
+
+impl Apt {
+    /// `apt-get` with the given arguments, as root
+    fn apt_get(args: &[&str]) -> CommandRunner {
+        // sudo resets the environment, so the frontend is set inside it
+        as_root("env")
+            .args(["DEBIAN_FRONTEND=noninteractive", "apt-get"])
+            .args(args)
+    }
+
+    /// Refreshes the package lists, once per run
+    ///
+    /// Fresh machines and containers often ship without them.
+    fn update(&self) {
+        static UPDATED: OnceLock<()> = OnceLock::new();
+        UPDATED.get_or_init(|| {
+            println!("Updating apt package lists...");
+            let updated = Self::apt_get(&["update", "-q"])
+                .timeout(command::NETWORK_TIMEOUT)
+                .stream(false)
+                .output();
+            if !updated.is_ok_and(|output| output.success()) {
+                eprintln!("Warning: apt-get update failed; installing from the current lists");
+            }
+        });
+    }
+}
+
+impl PackageManager for Apt {
+    fn name(&self) -> &'static str {
+        "apt"
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        let Some(name) = PackageBackend::Apt.package_name(package) else {
+            return false;
+        };
+        CommandRunner::new("dpkg-query")
+            .args(["-W", "-f=${Status}", name])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .is_ok_and(|output| output.success() && output.stdout.contains("install ok installed"))
+    }
+
+    fn install(&self, package: &str) -> Result<()> {
+        let name = distro_name(&PackageBackend::Apt, package)?;
+        self.update();
+
+        announce(package, name, "apt");
+        let output = Self::apt_get(&["install", "-y", "-q", name])
+            .timeout(command::INSTALL_TIMEOUT)
+            .output()?;
+        finish_install(name, "apt", &output)
+    }
+
+    fn tap(&self, name: &str) -> Result<()> {
+        no_taps(name, "apt")
+    }
+
+    fn needs_sudo(&self) -> bool {
+        !sudo::is_root()
+    }
+}
+
+/// dnf, on Fedora and RHEL
+pub struct Dnf;
+
+impl PackageManager for Dnf {
+    fn name(&self) -> &'static str {
+        "dnf"
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        let Some(name) = PackageBackend::Dnf.package_name(package) else {
+            return false;
+        };
+        CommandRunner::new("rpm")
+            .args(["-q", name])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .is_ok_and(|output| output.success())
+    }
+
+    fn install(&self, package: &str) -> Result<()> {
+        let name = distro_name(&PackageBackend::Dnf, package)?;
+        announce(package, name, "dnf");
+        let output = as_root("dnf")
+            .args(["install", "-y", "-q", name])
+            .timeout(command::INSTALL_TIMEOUT)
+            .output()?;
+        finish_install(name, "dnf", &output)
+    }
+
+    fn tap(&self, name: &str) -> Result<()> {
+        no_taps(name, "dnf")
+    }
+
+    fn needs_sudo(&self) -> bool {
+        !sudo::is_root()
+    }
+}
+
+/// pacman, on Arch and derivatives, with yay for AUR packages when installed
+pub struct Pacman;
+
+impl Pacman {
+    /// Whether yay is available for packages outside the official repositories
+    fn has_yay() -> bool {
+        crate::detect::tools::is_installed("yay")
+    }
+
+    /// `-Sy` for the first install of the run, refreshing the package
+    /// databases fresh machines and containers ship without, `-S` after
+    fn install_flag() -> &'static str {
+        static SYNCED: OnceLock<()> = OnceLock::new();
+        let mut flag = "-S";
+        SYNCED.get_or_init(|| flag = "-Sy");
+        flag
+    }
+}
+
+impl PackageManager for Pacman {
+    fn name(&self) -> &'static str {
+        "pacman"
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        // AUR packages keep the formula name, and pacman -Q sees those too
+        let name = PackageBackend::Pacman
+            .package_name(package)
+            .unwrap_or(package);
+        CommandRunner::new("pacman")
+            .args(["-Q", name])
+            .timeout(command::QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .is_ok_and(|output| output.success())
+    }
+
+    fn install(&self, package: &str) -> Result<()> {
+        let (name, manager) = match PackageBackend::Pacman.package_name(package) {
+            Some(name) => (name, "pacman"),
+            None if Self::has_yay() => (package, "yay"),
+            None => {
+                return Err(DotfilesError::InstallationFailed(format!(
+                    "{} is not in the Arch repositories; install yay for AUR packages, or install it from upstream",
+                    package
+                )))
+            }
+        };
+        announce(package, name, manager);
+        // yay builds as the user and asks for sudo itself
+        let runner = if manager == "yay" {
+            CommandRunner::new("yay")
+        } else {
+            as_root("pacman")
+        };
+        let output = runner
+            .args([Self::install_flag(), "--needed", "--noconfirm", name])
+            .timeout(command::INSTALL_TIMEOUT)
+            .output()?;
+        finish_install(name, manager, &output)
+    }
+
+    fn tap(&self, name: &str) -> Result<()> {
+        no_taps(name, "pacman")
+    }
+
+    fn needs_sudo(&self) -> bool {
+        !sudo::is_root()
+    }
+}
+
+/// GitHub release binaries in `~/.local/bin`, for machines where Homebrew is not allowed
+///
+/// Only the tools in `prebuilt::BINARIES` can be installed; anything else
+/// counts as installed when its command is already on PATH.
+pub struct Prebuilt;
+
+impl PackageManager for Prebuilt {
+    fn name(&self) -> &'static str {
+        "prebuilt"
+    }
+
+    fn is_installed(&self, package: &str) -> bool {
+        match crate::core::paths::home_dir() {
+            Some(home) if prebuilt::find(package).is_some() => {
+                prebuilt::is_installed(&home, package)
+            }
+            _ => crate::detect::tools::is_installed(package),
+        }
+    }
+
+    fn install(&self, package: &str) -> Result<()> {
+        let home = crate::core::paths::home_dir().ok_or_else(|| {
+            DotfilesError::Config("Could not determine home directory".to_string())
+        })?;
+        prebuilt::install(&home, package)?;
+        audit::record(Action::PackageInstalled {
+            package: package.to_string(),
+            manager: self.name().to_string(),
+        });
+        Ok(())
+    }
+
+    fn tap(&self, name: &str) -> Result<()> {
+        no_taps(name, "prebuilt binaries")
+    }
+}
+
+/// The package manager for this machine
+///
+/// `[packages] backend` wins when set. Otherwise Homebrew wherever it is
+/// installed, including Linuxbrew, then the distro's own manager (see
+/// `PackageBackend::detect`). Everything else falls back to Homebrew,
+/// which reports itself as missing.
+pub fn current(settings: &PackageSettings) -> &'static dyn PackageManager {
+    match settings.backend {
+        Some(PackageSource::Prebuilt) => &Prebuilt,
+        None => select(detect_os(), PackageBackend::detect()),
+    }
+}
+
+fn select(os: OS, backend: &PackageBackend) -> &'static dyn PackageManager {
+    match (os, backend) {
+        (OS::Linux, PackageBackend::Apt) => &Apt,
+        (OS::Linux, PackageBackend::Dnf) => &Dnf,
+        (OS::Linux, PackageBackend::Pacman) => &Pacman,
+        _ => &Homebrew,
+    }
+}
+
+/// The distro package for a Homebrew formula, or an error if there isn't one
+fn distro_name<'a>(backend: &PackageBackend, package: &'a str) -> Result<&'a str> {
+    backend.package_name(package).ok_or_else(|| {
+        DotfilesError::InstallationFailed(format!(
+            "{} is not packaged for {}; install it from upstream",
+            package,
+            backend.name()
+        ))
+    })
+}
+
+fn announce(package: &str, name: &str, manager: &str) {
+    if name == package {
+        println!("Installing {}...", package);
+    } else {
+        println!("Installing {} ({}: {})...", package, manager, name);
+    }
+}
+
+/// Turns a failed install into an error, and records a successful one
+fn finish_install(name: &str, manager: &str, output: &CommandOutput) -> Result<()> {
+    if !output.success() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Failed to install {}: {}",
+            name,
+            output.stderr.trim()
+        )));
+    }
+
+    audit::record(Action::PackageInstalled {
+        package: name.to_string(),
+        manager: manager.to_string(),
+    });
+    Ok(())
+}
+
+fn no_taps(name: &str, manager: &str) -> Result<()> {
+    Err(DotfilesError::InstallationFailed(format!(
+        "{} is a Homebrew tap; {} has no equivalent",
+        name, manager
+    )))
+}
+
+/// `program` through sudo, unless already running as root
+fn as_root(program: &str) -> CommandRunner {
+    if sudo::is_root() {
+        CommandRunner::new(program)
+    } else {
+        CommandRunner::new("sudo").arg(program)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacman_syncs_once() {
+        assert_eq!(Pacman::install_flag(), "-Sy");
+        assert_eq!(Pacman::install_flag(), "-S");
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(select(OS::Linux, &PackageBackend::Apt).name(), "apt");
+        assert_eq!(
+            select(OS::Linux, &PackageBackend::Brew(None)).name(),
+            "Homebrew"
+        );
+        assert_eq!(select(OS::Linux, &PackageBackend::Dnf).name(), "dnf");
+        assert_eq!(select(OS::Linux, &PackageBackend::Pacman).name(), "pacman");
+        assert_eq!(select(OS::MacOS, &PackageBackend::Apt).name(), "Homebrew");
+        assert_eq!(
+            select(OS::Linux, &PackageBackend::Unknown).name(),
+            "Homebrew"
+        );
+    }
+
+    #[test]
+    fn test_distro_names() {
+        assert_eq!(distro_name(&PackageBackend::Apt, "fd").unwrap(), "fd-find");
+        assert_eq!(distro_name(&PackageBackend::Apt, "tmux").unwrap(), "tmux");
+        assert!(distro_name(&PackageBackend::Apt, "kubernetes-cli").is_err());
+        assert_eq!(
+            distro_name(&PackageBackend::Dnf, "awscli").unwrap(),
+            "awscli2"
+        );
+        assert!(distro_name(&PackageBackend::Dnf, "mise")
+            .unwrap_err()
+            .to_string()
+            .contains("not packaged for dnf"));
+        assert!(!Apt.is_installed("kubernetes-cli"));
+        assert!(Apt.tap("yakitrak/tap").is_err());
+        assert!(Pacman.tap("yakitrak/tap").is_err());
+        assert!(Prebuilt.tap("yakitrak/tap").is_err());
+    }
+
+    #[test]
+    fn test_current_honors_backend_setting() {
+        let settings = PackageSettings {
+            backend: Some(PackageSource::Prebuilt),
+            ..Default::default()
+        };
+        assert_eq!(current(&settings).name(), "prebuilt");
+        assert!(!current(&settings).needs_sudo());
+    }
+}
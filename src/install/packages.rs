@@ -1,4 +1,10 @@
-use crate::error::Result;
+use crate::core::cancel;
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::PackageSettings;
+use crate::core::pool::{Pool, Progress};
+use crate::error::{DotfilesError, Result};
+use crate::install::homebrew::{self, PackageDetails, PackageKind};
+use crate::install::package_manager::{self, PackageManager};
 
 /// Essential packages to install for dotfiles management
 pub const ESSENTIAL_PACKAGES: &[&str] = &[
@@ -47,259 +53,864 @@ pub const EDITOR_PACKAGES: &[&str] = &[
     "lazygit", // Git TUI
 ];
 
-/// Installs a single package via Homebrew (idempotent)
-pub fn install_package(package: &str) -> Result<()> {
-    if crate::install::homebrew::is_package_installed(package) {
-        println!("✓ {} is already installed", package);
-        return Ok(());
-    }
+/// Kubernetes tools (opt-in via `[kubernetes] enabled`)
+pub const KUBERNETES_PACKAGES: &[&str] = &[
+    "kubernetes-cli", // kubectl
+    "k9s",            // Cluster TUI
+    "krew",           // kubectl plugin manager
+];
 
-    crate::install::homebrew::install_package(package)
+/// Package groups, in the order the setup picker lists them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageCategory {
+    Essential,
+    Optional,
+    Development,
+    Cloud,
+    Productivity,
+    Editor,
+    Kubernetes,
 }
 
-/// Installs all essential packages
-pub fn install_essential_packages() -> Result<Vec<String>> {
-    let mut installed = Vec::new();
-
-    println!("Installing essential packages...");
+impl PackageCategory {
+    pub const ALL: &'static [PackageCategory] = &[
+        PackageCategory::Essential,
+        PackageCategory::Optional,
+        PackageCategory::Development,
+        PackageCategory::Cloud,
+        PackageCategory::Productivity,
+        PackageCategory::Editor,
+        PackageCategory::Kubernetes,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PackageCategory::Essential => "essential",
+            PackageCategory::Optional => "optional",
+            PackageCategory::Development => "development",
+            PackageCategory::Cloud => "cloud",
+            PackageCategory::Productivity => "productivity",
+            PackageCategory::Editor => "editor",
+            PackageCategory::Kubernetes => "kubernetes",
+        }
+    }
 
-    for package in ESSENTIAL_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
-                // Continue with other packages even if one fails
-            }
+    pub fn packages(&self) -> &'static [&'static str] {
+        match self {
+            PackageCategory::Essential => ESSENTIAL_PACKAGES,
+            PackageCategory::Optional => OPTIONAL_PACKAGES,
+            PackageCategory::Development => DEVELOPMENT_PACKAGES,
+            PackageCategory::Cloud => CLOUD_PACKAGES,
+            PackageCategory::Productivity => PRODUCTIVITY_PACKAGES,
+            PackageCategory::Editor => EDITOR_PACKAGES,
+            PackageCategory::Kubernetes => KUBERNETES_PACKAGES,
         }
     }
 
-    if !installed.is_empty() {
-        println!("✓ Installed {} essential packages", installed.len());
+    /// Returns true if setup lets you choose packages from this group
+    ///
+    /// Essentials are always installed and kubernetes follows `[kubernetes] enabled`.
+    pub fn is_pickable(&self) -> bool {
+        !matches!(
+            self,
+            PackageCategory::Essential | PackageCategory::Kubernetes
+        )
     }
+}
 
-    Ok(installed)
+/// What a built-in package is, for the setup picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub category: PackageCategory,
+    pub kind: PackageKind,
+    /// Free-form keywords, e.g. `git` or `tui`
+    pub tags: &'static [&'static str],
+    /// What has to be in place before it installs
+    pub requires: &'static [Requirement],
+    /// Work to run once it is installed
+    pub post_install: Option<PostInstall>,
 }
 
-/// Installs optional packages
-pub fn install_optional_packages() -> Result<Vec<String>> {
-    let mut installed = Vec::new();
+/// Something that has to be in place before a package installs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Requirement {
+    /// Another package, installed first
+    Package(&'static str),
+    /// A Homebrew tap, added first
+    Tap(&'static str),
+}
 
-    println!("Installing optional packages...");
+/// Work to run right after a package installs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostInstall {
+    /// Generate fzf's key bindings and completion script and source it from .zshrc
+    FzfShellIntegration,
+}
 
-    for package in OPTIONAL_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
-                // Continue with other packages even if one fails
-            }
+impl PostInstall {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostInstall::FzfShellIntegration => "fzf key bindings and completion",
         }
     }
+}
 
-    if !installed.is_empty() {
-        println!("✓ Installed {} optional packages", installed.len());
+/// One step of installing a set of packages, in the order it runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallStep {
+    Tap(String),
+    Install(String),
+    PostInstall {
+        package: String,
+        action: PostInstall,
+    },
+}
+
+/// Descriptions of every package in the built-in groups
+pub const CATALOG: &[PackageInfo] = &[
+    info(
+        "stow",
+        "Symlink farm manager used to link the repo",
+        PackageCategory::Essential,
+        &["links"],
+    ),
+    PackageInfo {
+        post_install: Some(PostInstall::FzfShellIntegration),
+        ..info(
+            "fzf",
+            "Fuzzy finder for files, history, and more",
+            PackageCategory::Essential,
+            &["search"],
+        )
+    },
+    info(
+        "bat",
+        "cat with syntax highlighting",
+        PackageCategory::Essential,
+        &["files"],
+    ),
+    info(
+        "fd",
+        "Fast, friendly alternative to find",
+        PackageCategory::Essential,
+        &["search"],
+    ),
+    info(
+        "tree",
+        "Directory tree viewer",
+        PackageCategory::Essential,
+        &["files"],
+    ),
+    info(
+        "nvim",
+        "Neovim editor",
+        PackageCategory::Essential,
+        &["editor"],
+    ),
+    info(
+        "tmux",
+        "Terminal multiplexer",
+        PackageCategory::Essential,
+        &["terminal"],
+    ),
+    info(
+        "ripgrep",
+        "Fast recursive grep (rg)",
+        PackageCategory::Optional,
+        &["search"],
+    ),
+    info(
+        "git",
+        "Newer git than the system one",
+        PackageCategory::Optional,
+        &["git"],
+    ),
+    info(
+        "curl",
+        "HTTP client",
+        PackageCategory::Optional,
+        &["network"],
+    ),
+    info(
+        "wget",
+        "File downloader",
+        PackageCategory::Optional,
+        &["network"],
+    ),
+    info(
+        "gh",
+        "GitHub CLI for PRs, issues, and releases",
+        PackageCategory::Development,
+        &["git"],
+    ),
+    info(
+        "jq",
+        "JSON processor",
+        PackageCategory::Development,
+        &["data"],
+    ),
+    info(
+        "yq",
+        "YAML processor",
+        PackageCategory::Development,
+        &["data"],
+    ),
+    info(
+        "httpie",
+        "Human-friendly HTTP client",
+        PackageCategory::Development,
+        &["network"],
+    ),
+    info(
+        "just",
+        "Command runner for project recipes",
+        PackageCategory::Development,
+        &["build"],
+    ),
+    info(
+        "awscli",
+        "AWS command line interface",
+        PackageCategory::Cloud,
+        &["aws"],
+    ),
+    info(
+        "opentofu",
+        "Open source infrastructure as code (tofu)",
+        PackageCategory::Cloud,
+        &["iac"],
+    ),
+    info(
+        "terraform",
+        "Infrastructure as code (legacy)",
+        PackageCategory::Cloud,
+        &["iac"],
+    ),
+    PackageInfo {
+        kind: PackageKind::Cask,
+        ..info(
+            "obsidian",
+            "Markdown note-taking app",
+            PackageCategory::Productivity,
+            &["notes", "gui"],
+        )
+    },
+    PackageInfo {
+        requires: &[Requirement::Tap("yakitrak/tap")],
+        ..info(
+            "yakitrak/tap/obsidian-cli",
+            "Open and search Obsidian vaults from the shell",
+            PackageCategory::Productivity,
+            &["notes"],
+        )
+    },
+    info(
+        "helix",
+        "Modal editor with built-in LSP support",
+        PackageCategory::Editor,
+        &["editor"],
+    ),
+    info(
+        "lazygit",
+        "Terminal UI for git",
+        PackageCategory::Editor,
+        &["git", "tui"],
+    ),
+    info(
+        "kubernetes-cli",
+        "kubectl",
+        PackageCategory::Kubernetes,
+        &["k8s"],
+    ),
+    info(
+        "k9s",
+        "Terminal UI for Kubernetes clusters",
+        PackageCategory::Kubernetes,
+        &["k8s", "tui"],
+    ),
+    PackageInfo {
+        requires: &[Requirement::Package("kubernetes-cli")],
+        ..info(
+            "krew",
+            "kubectl plugin manager",
+            PackageCategory::Kubernetes,
+            &["k8s"],
+        )
+    },
+];
+
+const fn info(
+    name: &'static str,
+    description: &'static str,
+    category: PackageCategory,
+    tags: &'static [&'static str],
+) -> PackageInfo {
+    PackageInfo {
+        name,
+        description,
+        category,
+        kind: PackageKind::Formula,
+        tags,
+        requires: &[],
+        post_install: None,
     }
+}
 
-    Ok(installed)
+/// Looks up a built-in package's description
+pub fn package_info(name: &str) -> Option<&'static PackageInfo> {
+    CATALOG.iter().find(|info| info.name == name)
 }
 
-/// Checks if all essential packages are installed
-pub fn check_essential_packages() -> Vec<String> {
-    ESSENTIAL_PACKAGES
+/// Returns the packages setup lets you choose, grouped by category
+pub fn pickable_packages() -> Vec<&'static PackageInfo> {
+    PackageCategory::ALL
         .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .filter(|category| category.is_pickable())
+        .flat_map(|category| {
+            CATALOG
+                .iter()
+                .filter(move |info| info.category == *category)
+        })
         .collect()
 }
 
-/// Installs development packages
-pub fn install_development_packages() -> Result<Vec<String>> {
-    let mut installed = Vec::new();
+/// Returns the taps and packages `package` needs first, by name
+///
+/// Taps come from the catalog and from fully qualified names such as
+/// `user/tap/formula`, so packages outside the catalog get theirs too.
+fn requirements(package: &str) -> (Vec<String>, Vec<String>) {
+    let requires = package_info(package).map_or(&[][..], |info| info.requires);
+    let mut taps: Vec<String> = homebrew::tap_of(package).into_iter().collect();
+    let mut packages = Vec::new();
+    for requirement in requires {
+        match requirement {
+            Requirement::Tap(tap) if !taps.iter().any(|t| t == tap) => taps.push(tap.to_string()),
+            Requirement::Tap(_) => {}
+            Requirement::Package(name) => packages.push(name.to_string()),
+        }
+    }
+    (taps, packages)
+}
 
-    println!("Installing development packages...");
+/// Returns the taps `package` needs added before it installs
+pub fn required_taps(package: &str) -> Vec<String> {
+    requirements(package).0
+}
 
-    for package in DEVELOPMENT_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
-            }
+/// Orders the steps of installing `packages` so requirements come first
+///
+/// Each package's taps are added, and the packages it requires installed
+/// (unless `is_installed` says they already are), before the package itself;
+/// its post-install work runs right after it. List position only matters
+/// between unrelated packages. Fails if requirements form a cycle.
+pub fn install_plan(
+    packages: &[String],
+    is_installed: impl Fn(&str) -> bool,
+) -> Result<Vec<InstallStep>> {
+    let mut steps = Vec::new();
+    let mut visiting = Vec::new();
+    for package in packages {
+        plan_steps(package, &is_installed, &mut visiting, &mut steps)?;
+    }
+    Ok(steps)
+}
+
+fn plan_steps(
+    package: &str,
+    is_installed: &dyn Fn(&str) -> bool,
+    visiting: &mut Vec<String>,
+    steps: &mut Vec<InstallStep>,
+) -> Result<()> {
+    if steps.contains(&InstallStep::Install(package.to_string())) {
+        return Ok(());
+    }
+    if let Some(start) = visiting.iter().position(|p| p == package) {
+        let mut cycle = visiting[start..].to_vec();
+        cycle.push(package.to_string());
+        return Err(DotfilesError::Config(format!(
+            "Package requirements form a cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    visiting.push(package.to_string());
+    let (taps, required) = requirements(package);
+    for tap in taps {
+        let step = InstallStep::Tap(tap);
+        if !steps.contains(&step) {
+            steps.push(step);
         }
     }
+    for required in required.iter().filter(|p| !is_installed(p)) {
+        plan_steps(required, is_installed, visiting, steps)?;
+    }
+    visiting.pop();
+
+    steps.push(InstallStep::Install(package.to_string()));
+    if let Some(action) = package_info(package).and_then(|info| info.post_install) {
+        steps.push(InstallStep::PostInstall {
+            package: package.to_string(),
+            action,
+        });
+    }
+    Ok(())
+}
 
-    if !installed.is_empty() {
-        println!("✓ Installed {} development packages", installed.len());
+/// Runs a package's post-install work
+fn run_post_install(action: PostInstall) -> Result<()> {
+    match action {
+        PostInstall::FzfShellIntegration => {
+            let brew_path = homebrew::get_brew_path()
+                .ok_or_else(|| DotfilesError::DependencyMissing("Homebrew".to_string()))?;
+            let prefix = CommandRunner::new(brew_path)
+                .args(["--prefix", "fzf"])
+                .timeout(command::QUICK_TIMEOUT)
+                .stream(false)
+                .run()?;
+            // Writes ~/.fzf.zsh only; ~/.zshrc is usually linked into the
+            // repo, so the managed block sources it instead (see install::shell)
+            CommandRunner::new(std::path::Path::new(prefix.stdout.trim()).join("install"))
+                .args([
+                    "--key-bindings",
+                    "--completion",
+                    "--no-update-rc",
+                    "--no-bash",
+                    "--no-fish",
+                ])
+                .timeout(command::QUICK_TIMEOUT)
+                .stream(false)
+                .run()?;
+            Ok(())
+        }
     }
+}
 
-    Ok(installed)
+/// Installs a single package with this machine's package manager (idempotent)
+pub fn install_package(package: &str, settings: &PackageSettings) -> Result<()> {
+    let manager = package_manager::current(settings);
+    if manager.is_installed(package) {
+        println!("✓ {} is already installed", package);
+        return Ok(());
+    }
+
+    manager.install(package)
 }
 
-/// Checks development packages
-pub fn check_development_packages() -> Vec<String> {
-    DEVELOPMENT_PACKAGES
+/// Returns the packages from a group that are not skipped in config
+pub fn active_packages<'a>(packages: &[&'a str], settings: &PackageSettings) -> Vec<&'a str> {
+    packages
         .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .copied()
+        .filter(|pkg| !settings.is_skipped(pkg))
         .collect()
 }
 
-/// Installs cloud packages
-pub fn install_cloud_packages() -> Result<Vec<String>> {
+/// Installs every non-skipped package in a group, continuing past failures
+///
+/// With Homebrew, missing packages are downloaded concurrently first; the
+/// installs themselves run one at a time because the package manager locks
+/// while installing.
+fn install_group(
+    label: &str,
+    packages: &[&str],
+    settings: &PackageSettings,
+) -> Result<Vec<String>> {
     let mut installed = Vec::new();
+    let manager = package_manager::current(settings);
 
-    println!("Installing cloud packages...");
+    println!("Installing {} packages...", label);
 
-    for package in CLOUD_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
+    let missing = missing_packages(packages, settings);
+    let steps = install_plan(&missing, |package| manager.is_installed(package))?;
+    let to_install: Vec<String> = steps
+        .iter()
+        .filter_map(|step| match step {
+            InstallStep::Install(package) => Some(package.clone()),
+            _ => None,
+        })
+        .collect();
+    prefetch(manager, &to_install);
+
+    let tapped = manager.tapped();
+    // Taps and packages that failed, so whatever needs them is skipped
+    let mut failed: Vec<String> = Vec::new();
+    for step in &steps {
+        cancel::check(&format!("installing {} packages", label))?;
+        match step {
+            InstallStep::Tap(tap) if !tapped.contains(tap) => {
+                if let Err(e) = manager.tap(tap) {
+                    eprintln!("Warning: Failed to tap {}: {}", tap, e);
+                    failed.push(tap.clone());
+                }
+            }
+            InstallStep::Tap(_) => {}
+            InstallStep::Install(package) => {
+                let (taps, required) = requirements(package);
+                if let Some(blocker) = taps.iter().chain(&required).find(|r| failed.contains(r)) {
+                    eprintln!("Warning: Skipping {}: {} failed", package, blocker);
+                    failed.push(package.clone());
+                    continue;
+                }
+                match manager.install(package) {
+                    Ok(()) => {
+                        installed.push(package.to_string());
+                    }
+                    Err(e) => {
+                        eprintln!("Warning: Failed to install {}: {}", package, e);
+                        // Continue with other packages even if one fails
+                        failed.push(package.clone());
+                    }
+                }
             }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
+            InstallStep::PostInstall { package, action } if installed.contains(package) => {
+                if let Err(e) = run_post_install(*action) {
+                    eprintln!("Warning: Failed to set up {}: {}", action.label(), e);
+                }
             }
+            InstallStep::PostInstall { .. } => {}
         }
     }
 
     if !installed.is_empty() {
-        println!("✓ Installed {} cloud packages", installed.len());
+        println!("✓ Installed {} {} packages", installed.len(), label);
     }
 
     Ok(installed)
 }
 
-/// Checks cloud packages
-pub fn check_cloud_packages() -> Vec<String> {
-    CLOUD_PACKAGES
-        .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
-        .collect()
+/// Downloads packages concurrently so the serial installs that follow don't wait on the network
+fn prefetch(manager: &dyn PackageManager, packages: &[String]) {
+    if packages.len() < 2 || !manager.can_fetch() {
+        return;
+    }
+
+    println!("  Downloading {} packages...", packages.len());
+    Pool::new().run(
+        packages,
+        |package| package.clone(),
+        |package| manager.fetch(package),
+        |event| {
+            // Failed fetches are retried by the install, so only report them
+            if let Progress::Finished {
+                name, ok: false, ..
+            } = event
+            {
+                eprintln!("  Warning: could not pre-download {}", name);
+            }
+        },
+    );
 }
 
-/// Installs productivity packages
-pub fn install_productivity_packages() -> Result<Vec<String>> {
-    let mut installed = Vec::new();
+/// Splits the non-skipped packages of a group by whether they are installed
+fn partition_installed(
+    packages: &[&str],
+    settings: &PackageSettings,
+) -> (Vec<String>, Vec<String>) {
+    let active = active_packages(packages, settings);
+    let manager = package_manager::current(settings);
+    let installed = Pool::new().map(&active, |pkg| manager.is_installed(pkg));
+
+    let (installed, missing): (Vec<_>, Vec<_>) = active
+        .into_iter()
+        .zip(installed)
+        .partition(|(_, installed)| *installed);
+    (
+        installed
+            .into_iter()
+            .map(|(pkg, _)| pkg.to_string())
+            .collect(),
+        missing
+            .into_iter()
+            .map(|(pkg, _)| pkg.to_string())
+            .collect(),
+    )
+}
 
-    println!("Installing productivity packages...");
+/// Returns non-skipped packages from a group that are not installed
+fn missing_packages(packages: &[&str], settings: &PackageSettings) -> Vec<String> {
+    partition_installed(packages, settings).1
+}
 
-    for package in PRODUCTIVITY_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
-            }
-        }
-    }
+/// Returns non-skipped packages from a group that are installed
+fn installed_packages(packages: &[&str], settings: &PackageSettings) -> Vec<String> {
+    partition_installed(packages, settings).0
+}
 
-    if !installed.is_empty() {
-        println!("✓ Installed {} productivity packages", installed.len());
-    }
+/// Returns the installed, non-skipped packages from every built-in group
+pub fn installed_managed_packages(settings: &PackageSettings) -> Vec<String> {
+    [
+        ESSENTIAL_PACKAGES,
+        OPTIONAL_PACKAGES,
+        DEVELOPMENT_PACKAGES,
+        CLOUD_PACKAGES,
+        PRODUCTIVITY_PACKAGES,
+        EDITOR_PACKAGES,
+        KUBERNETES_PACKAGES,
+    ]
+    .into_iter()
+    .flat_map(|group| installed_packages(group, settings))
+    .collect()
+}
 
-    Ok(installed)
+/// Installs essential packages
+pub fn install_essential_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("essential", ESSENTIAL_PACKAGES, settings)
+}
+
+/// Checks essential packages
+pub fn check_essential_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(ESSENTIAL_PACKAGES, settings)
+}
+
+/// Installs the packages chosen in setup's picker (`[packages] selected`)
+pub fn install_selected_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    let selected: Vec<&str> = settings.selected.iter().map(String::as_str).collect();
+    install_group("selected", &selected, settings)
+}
+
+/// Checks the packages chosen in setup's picker
+pub fn check_selected_packages(settings: &PackageSettings) -> Vec<String> {
+    let selected: Vec<&str> = settings.selected.iter().map(String::as_str).collect();
+    missing_packages(&selected, settings)
+}
+
+/// Installs optional packages
+pub fn install_optional_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("optional", OPTIONAL_PACKAGES, settings)
+}
+
+/// Installs development packages
+pub fn install_development_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("development", DEVELOPMENT_PACKAGES, settings)
+}
+
+/// Checks development packages
+pub fn check_development_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(DEVELOPMENT_PACKAGES, settings)
+}
+
+/// Installs cloud packages
+pub fn install_cloud_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("cloud", CLOUD_PACKAGES, settings)
+}
+
+/// Checks cloud packages
+pub fn check_cloud_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(CLOUD_PACKAGES, settings)
+}
+
+/// Installs productivity packages
+pub fn install_productivity_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("productivity", PRODUCTIVITY_PACKAGES, settings)
 }
 
 /// Checks productivity packages
-pub fn check_productivity_packages() -> Vec<String> {
-    PRODUCTIVITY_PACKAGES
-        .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
-        .collect()
+pub fn check_productivity_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(PRODUCTIVITY_PACKAGES, settings)
 }
 
 /// Installs editor packages
-pub fn install_editor_packages() -> Result<Vec<String>> {
-    let mut installed = Vec::new();
+pub fn install_editor_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("editor", EDITOR_PACKAGES, settings)
+}
+
+/// Checks editor packages
+pub fn check_editor_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(EDITOR_PACKAGES, settings)
+}
 
-    println!("Installing editor packages...");
+/// Installs kubernetes packages
+pub fn install_kubernetes_packages(settings: &PackageSettings) -> Result<Vec<String>> {
+    install_group("kubernetes", KUBERNETES_PACKAGES, settings)
+}
 
-    for package in EDITOR_PACKAGES {
-        match install_package(package) {
-            Ok(()) => {
-                installed.push(package.to_string());
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to install {}: {}", package, e);
-            }
-        }
-    }
+/// Checks kubernetes packages
+pub fn check_kubernetes_packages(settings: &PackageSettings) -> Vec<String> {
+    missing_packages(KUBERNETES_PACKAGES, settings)
+}
 
-    if !installed.is_empty() {
-        println!("✓ Installed {} editor packages", installed.len());
+/// Pins installed packages listed as `pinned` so `brew upgrade` leaves them alone
+///
+/// Returns the packages that were newly pinned.
+pub fn ensure_pins(settings: &PackageSettings) -> Result<Vec<String>> {
+    let pins = pins_needed(settings, &[]);
+    for package in &pins {
+        crate::install::homebrew::pin_package(package)?;
     }
 
-    Ok(installed)
+    Ok(pins)
 }
 
-/// Checks editor packages
-pub fn check_editor_packages() -> Vec<String> {
-    EDITOR_PACKAGES
+/// Returns the `pinned` packages `ensure_pins` would pin
+///
+/// `installing` lists packages about to be installed, which get pinned
+/// along with the ones already on the machine.
+pub fn pins_needed(settings: &PackageSettings, installing: &[String]) -> Vec<String> {
+    let already_pinned = crate::install::homebrew::pinned_packages();
+
+    settings
+        .pinned
         .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .filter(|package| {
+            !settings.is_skipped(package)
+                && !already_pinned.contains(package)
+                && (installing.contains(package)
+                    || crate::install::homebrew::is_package_installed(package))
+        })
+        .cloned()
         .collect()
 }
 
-/// Returns a summary of package installation status
-pub fn package_status() -> PackageStatus {
-    let missing_essential: Vec<String> = ESSENTIAL_PACKAGES
-        .iter()
-        .filter(|pkg| !crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
-        .collect();
+/// What setup would do with one package of a group
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedAction {
+    /// Not installed, so setup installs it
+    Install,
+    /// Installed and current
+    Installed { version: Option<String> },
+    /// Installed, but a newer version exists; setup doesn't upgrade
+    Outdated { version: String, latest: String },
+    /// Excluded by `[packages] skip` or a `[packages.when]` filter
+    Skipped,
+}
 
-    let installed_essential: Vec<String> = ESSENTIAL_PACKAGES
-        .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
-        .collect();
+/// A package and what setup would do with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedPackage {
+    pub name: String,
+    /// None if Homebrew couldn't say (not installed yet, or an untapped tap)
+    pub kind: Option<PackageKind>,
+    pub action: PlannedAction,
+}
 
-    let installed_optional: Vec<String> = OPTIONAL_PACKAGES
-        .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
-        .collect();
+/// What setup would do with a package group, for `setup --dry-run`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagePlan {
+    pub label: String,
+    pub packages: Vec<PlannedPackage>,
+    /// Taps that installing the group would add
+    pub taps: Vec<String>,
+    /// The install steps in the order they would run
+    pub steps: Vec<InstallStep>,
+}
 
-    let installed_development: Vec<String> = DEVELOPMENT_PACKAGES
-        .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+impl PackagePlan {
+    /// Returns the names of the packages with actions matching `filter`
+    pub fn names(&self, filter: impl Fn(&PlannedAction) -> bool) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|package| filter(&package.action))
+            .map(|package| package.name.clone())
+            .collect()
+    }
+}
+
+/// Works out what installing a group would do, asking the package manager about each package
+///
+/// Versions and kinds come from Homebrew, when it is installed.
+pub fn plan_group(label: &str, packages: &[&str], settings: &PackageSettings) -> PackagePlan {
+    let manager = package_manager::current(settings);
+    let active: Vec<String> = active_packages(packages, settings)
+        .into_iter()
+        .map(str::to_string)
         .collect();
+    let details = homebrew::package_details(&active);
+    let tapped = manager.tapped();
 
-    let installed_cloud: Vec<String> = CLOUD_PACKAGES
+    let planned: Vec<PlannedPackage> = packages
         .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .map(|package| {
+            let details = details.iter().find(|d| d.matches(package));
+            plan_package(manager, package, details, settings)
+        })
         .collect();
-
-    let installed_productivity: Vec<String> = PRODUCTIVITY_PACKAGES
+    let installing: Vec<String> = planned
         .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .filter(|package| package.action == PlannedAction::Install)
+        .map(|package| package.name.clone())
         .collect();
-
-    let installed_editors: Vec<String> = EDITOR_PACKAGES
+    // A cycle can only come from the catalog; setup reports it when installing
+    let steps = install_plan(&installing, |package| {
+        details
+            .iter()
+            .any(|d| d.matches(package) && d.installed.is_some())
+            || manager.is_installed(package)
+    })
+    .unwrap_or_default();
+    let mut taps: Vec<String> = steps
         .iter()
-        .filter(|pkg| crate::install::homebrew::is_package_installed(pkg))
-        .map(|pkg| pkg.to_string())
+        .filter_map(|step| match step {
+            InstallStep::Tap(tap) if !tapped.contains(tap) => Some(tap.clone()),
+            _ => None,
+        })
         .collect();
+    taps.sort();
 
+    PackagePlan {
+        label: label.to_string(),
+        packages: planned,
+        taps,
+        steps,
+    }
+}
+
+fn plan_package(
+    manager: &dyn PackageManager,
+    package: &str,
+    details: Option<&PackageDetails>,
+    settings: &PackageSettings,
+) -> PlannedPackage {
+    let action = if settings.is_skipped(package) {
+        PlannedAction::Skipped
+    } else {
+        match details {
+            Some(PackageDetails {
+                installed: Some(version),
+                latest: Some(latest),
+                outdated: true,
+                ..
+            }) => PlannedAction::Outdated {
+                version: version.clone(),
+                latest: latest.clone(),
+            },
+            Some(PackageDetails {
+                installed: Some(version),
+                ..
+            }) => PlannedAction::Installed {
+                version: Some(version.clone()),
+            },
+            Some(_) => PlannedAction::Install,
+            // Without details, fall back to asking whether it's installed at all
+            None if manager.is_installed(package) => PlannedAction::Installed { version: None },
+            None => PlannedAction::Install,
+        }
+    };
+
+    PlannedPackage {
+        name: package.to_string(),
+        kind: details.map(|d| d.kind),
+        action,
+    }
+}
+
+/// Returns a summary of package installation status
+pub fn package_status(settings: &PackageSettings) -> PackageStatus {
+    let (installed_essential, missing_essential) =
+        partition_installed(ESSENTIAL_PACKAGES, settings);
+    let (installed_development, missing_development) =
+        partition_installed(DEVELOPMENT_PACKAGES, settings);
+    let (installed_cloud, missing_cloud) = partition_installed(CLOUD_PACKAGES, settings);
     PackageStatus {
         missing_essential,
         installed_essential,
-        installed_optional,
+        installed_optional: installed_packages(OPTIONAL_PACKAGES, settings),
         installed_development,
+        missing_development,
         installed_cloud,
-        installed_productivity,
-        installed_editors,
+        missing_cloud,
+        installed_productivity: installed_packages(PRODUCTIVITY_PACKAGES, settings),
+        installed_editors: installed_packages(EDITOR_PACKAGES, settings),
     }
 }
 
@@ -310,7 +921,9 @@ pub struct PackageStatus {
     pub installed_essential: Vec<String>,
     pub installed_optional: Vec<String>,
     pub installed_development: Vec<String>,
+    pub missing_development: Vec<String>,
     pub installed_cloud: Vec<String>,
+    pub missing_cloud: Vec<String>,
     pub installed_productivity: Vec<String>,
     pub installed_editors: Vec<String>,
 }
@@ -361,7 +974,7 @@ mod tests {
     fn test_check_essential_packages() {
         // This test checks that the function runs without panicking
         // The actual result depends on what's installed on the system
-        let missing = check_essential_packages();
+        let missing = check_essential_packages(&PackageSettings::default());
 
         // Missing packages should all be from the essential list
         for pkg in &missing {
@@ -376,7 +989,7 @@ mod tests {
     #[test]
     fn test_package_status() {
         // Test that package_status runs without panicking
-        let status = package_status();
+        let status = package_status(&PackageSettings::default());
 
         // All missing packages should be essential packages
         for pkg in &status.missing_essential {
@@ -408,7 +1021,7 @@ mod tests {
 
     #[test]
     fn test_package_status_completeness() {
-        let status = package_status();
+        let status = package_status(&PackageSettings::default());
 
         // If no essential packages are missing, is_complete should be true
         if status.missing_essential.is_empty() {
@@ -420,7 +1033,7 @@ mod tests {
 
     #[test]
     fn test_package_status_total() {
-        let status = package_status();
+        let status = package_status(&PackageSettings::default());
         let total = status.total_installed();
 
         assert_eq!(
@@ -433,4 +1046,169 @@ mod tests {
                 + status.installed_editors.len()
         );
     }
+
+    #[test]
+    fn test_active_packages_excludes_skipped() {
+        let settings = PackageSettings {
+            skip: vec!["nvim".to_string()],
+            ..Default::default()
+        };
+
+        let active = active_packages(ESSENTIAL_PACKAGES, &settings);
+        assert!(!active.contains(&"nvim"));
+        assert_eq!(active.len(), ESSENTIAL_PACKAGES.len() - 1);
+    }
+
+    #[test]
+    fn test_catalog_covers_every_group() {
+        for category in PackageCategory::ALL {
+            for package in category.packages() {
+                let info = package_info(package)
+                    .unwrap_or_else(|| panic!("{} has no catalog entry", package));
+                assert_eq!(info.category, *category, "{}", package);
+            }
+        }
+        assert_eq!(
+            CATALOG.len(),
+            PackageCategory::ALL
+                .iter()
+                .map(|c| c.packages().len())
+                .sum::<usize>()
+        );
+        assert_eq!(
+            package_info("obsidian").map(|info| info.kind),
+            Some(PackageKind::Cask)
+        );
+
+        let pickable = pickable_packages();
+        assert_eq!(pickable[0].category, PackageCategory::Optional);
+        assert!(pickable.iter().all(|info| info.category.is_pickable()));
+    }
+
+    #[test]
+    fn test_plan_package() {
+        let brew = &package_manager::Homebrew;
+        let settings = PackageSettings {
+            skip: vec!["tmux".to_string()],
+            ..Default::default()
+        };
+        let details = |installed: Option<&str>, outdated: bool| PackageDetails {
+            names: vec!["neovim".to_string(), "nvim".to_string()],
+            kind: PackageKind::Formula,
+            tap: Some("homebrew/core".to_string()),
+            installed: installed.map(str::to_string),
+            latest: Some("0.10.4".to_string()),
+            outdated,
+            pinned: false,
+        };
+
+        assert_eq!(
+            plan_package(brew, "nvim", Some(&details(None, false)), &settings).action,
+            PlannedAction::Install
+        );
+        assert_eq!(
+            plan_package(
+                brew,
+                "nvim",
+                Some(&details(Some("0.10.4"), false)),
+                &settings
+            )
+            .action,
+            PlannedAction::Installed {
+                version: Some("0.10.4".to_string())
+            }
+        );
+        assert_eq!(
+            plan_package(
+                brew,
+                "nvim",
+                Some(&details(Some("0.10.2"), true)),
+                &settings
+            )
+            .action,
+            PlannedAction::Outdated {
+                version: "0.10.2".to_string(),
+                latest: "0.10.4".to_string()
+            }
+        );
+        let skipped = plan_package(brew, "tmux", None, &settings);
+        assert_eq!(skipped.action, PlannedAction::Skipped);
+        assert_eq!(skipped.kind, None);
+
+        let plan = PackagePlan {
+            label: "essential".to_string(),
+            packages: vec![
+                plan_package(brew, "nvim", Some(&details(None, false)), &settings),
+                skipped,
+            ],
+            taps: Vec::new(),
+            steps: Vec::new(),
+        };
+        assert_eq!(
+            plan.names(|action| *action == PlannedAction::Install),
+            vec!["nvim"]
+        );
+    }
+
+    #[test]
+    fn test_install_plan_orders_requirements() {
+        let names = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        let steps = install_plan(&names(&["krew", "k9s", "kubernetes-cli"]), |_| false).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                InstallStep::Install("kubernetes-cli".to_string()),
+                InstallStep::Install("krew".to_string()),
+                InstallStep::Install("k9s".to_string()),
+            ]
+        );
+        let steps = install_plan(&names(&["krew"]), |p| p == "kubernetes-cli").unwrap();
+        assert_eq!(steps, vec![InstallStep::Install("krew".to_string())]);
+
+        let steps = install_plan(&names(&["yakitrak/tap/obsidian-cli", "fzf"]), |_| false).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                InstallStep::Tap("yakitrak/tap".to_string()),
+                InstallStep::Install("yakitrak/tap/obsidian-cli".to_string()),
+                InstallStep::Install("fzf".to_string()),
+                InstallStep::PostInstall {
+                    package: "fzf".to_string(),
+                    action: PostInstall::FzfShellIntegration
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_catalog_requirements_are_acyclic() {
+        let all: Vec<String> = CATALOG.iter().map(|info| info.name.to_string()).collect();
+        let steps = install_plan(&all, |_| false).unwrap();
+        for info in CATALOG {
+            let position = |name: &str| {
+                steps
+                    .iter()
+                    .position(|step| *step == InstallStep::Install(name.to_string()))
+                    .unwrap()
+            };
+            for requirement in info.requires {
+                if let Requirement::Package(required) = requirement {
+                    assert!(position(required) < position(info.name));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_package_status_ignores_skipped() {
+        let settings = PackageSettings {
+            skip: ESSENTIAL_PACKAGES.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let status = package_status(&settings);
+        assert!(status.is_complete());
+        assert!(status.installed_essential.is_empty());
+    }
 }
@@ -0,0 +1,366 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::github;
+use crate::error::{DotfilesError, Result};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+
+/// How a project names the release archive for each platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Naming {
+    /// Rust target triples (`aarch64-apple-darwin`, `x86_64-unknown-linux-musl`)
+    Triple,
+    /// Go's `GOOS_GOARCH` (`darwin_arm64`, `linux_amd64`)
+    GoOsArch,
+}
+
+/// A tool installed from the archives attached to its GitHub releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binary {
+    /// Homebrew formula, the name packages go by everywhere else
+    pub formula: &'static str,
+    /// Repository publishing the releases
+    pub repo: &'static str,
+    /// Executable taken from the archive
+    pub command: &'static str,
+    naming: Naming,
+}
+
+/// Every tool the prebuilt backend can install
+pub const BINARIES: &[Binary] = &[
+    Binary {
+        formula: "fzf",
+        repo: "junegunn/fzf",
+        command: "fzf",
+        naming: Naming::GoOsArch,
+    },
+    Binary {
+        formula: "fd",
+        repo: "sharkdp/fd",
+        command: "fd",
+        naming: Naming::Triple,
+    },
+    Binary {
+        formula: "bat",
+        repo: "sharkdp/bat",
+        command: "bat",
+        naming: Naming::Triple,
+    },
+    Binary {
+        formula: "ripgrep",
+        repo: "BurntSushi/ripgrep",
+        command: "rg",
+        naming: Naming::Triple,
+    },
+];
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+    /// `sha256:<hex>`, computed by GitHub when the asset was uploaded
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+/// Returns the prebuilt binary for a Homebrew formula, if there is one
+pub fn find(formula: &str) -> Option<&'static Binary> {
+    BINARIES.iter().find(|binary| binary.formula == formula)
+}
+
+/// `~/.local/bin`, where binaries are installed
+pub fn bin_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(".local/bin")
+}
+
+/// Returns true if the binary for `formula` is in `~/.local/bin`
+pub fn is_installed(home_dir: &Path, formula: &str) -> bool {
+    find(formula).is_some_and(|binary| bin_dir(home_dir).join(binary.command).is_file())
+}
+
+/// Installs the latest release of `formula` into `~/.local/bin` and returns the binary's path
+///
+/// The archive must match the SHA-256 digest GitHub publishes for the
+/// asset; a release without one is refused rather than installed unverified.
+pub fn install(home_dir: &Path, formula: &str) -> Result<PathBuf> {
+    let binary = find(formula).ok_or_else(|| {
+        DotfilesError::InstallationFailed(format!(
+            "{} has no prebuilt binary; install it another way, or add it to [packages] skip",
+            formula
+        ))
+    })?;
+
+    let body = github::get(home_dir, &format!("/repos/{}/releases/latest", binary.repo))?;
+    let release: Release = serde_json::from_str(&body)?;
+    let suffixes = platform_suffixes(binary.naming, std::env::consts::OS, std::env::consts::ARCH);
+    let asset = pick_asset(&release.assets, &suffixes).ok_or_else(|| {
+        DotfilesError::InstallationFailed(format!(
+            "{} {} has no archive for {}/{}",
+            binary.repo,
+            release.tag_name,
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let expected = asset
+        .digest
+        .as_deref()
+        .and_then(|digest| digest.strip_prefix("sha256:"))
+        .ok_or_else(|| {
+            DotfilesError::InstallationFailed(format!(
+                "GitHub publishes no checksum for {}; refusing to install it unverified",
+                asset.name
+            ))
+        })?;
+
+    println!(
+        "Installing {} {} ({})...",
+        formula, release.tag_name, asset.name
+    );
+    // A private, randomly named file; it is verified and extracted through the
+    // handle opened when it was created, so what is checked is what gets installed
+    let mut archive = tempfile::Builder::new()
+        .prefix("dotfiles-prebuilt-")
+        .tempfile()?;
+    download(&asset.browser_download_url, archive.path())?;
+    let file = archive.as_file_mut();
+    verify(file, &asset.name, expected)?;
+    file.rewind()?;
+    let destination = bin_dir(home_dir).join(binary.command);
+    extract_binary(file, &asset.name, binary.command, &destination)?;
+    Ok(destination)
+}
+
+/// Archive name endings for a platform, most preferred first (static musl builds before glibc)
+fn platform_suffixes(naming: Naming, os: &str, arch: &str) -> Vec<String> {
+    let targets: Vec<String> = match (naming, os) {
+        (Naming::Triple, "macos") => vec![format!("{}-apple-darwin", arch)],
+        (Naming::Triple, "linux") => vec![
+            format!("{}-unknown-linux-musl", arch),
+            format!("{}-unknown-linux-gnu", arch),
+        ],
+        (Naming::GoOsArch, "macos" | "linux") => {
+            let goos = if os == "macos" { "darwin" } else { "linux" };
+            let goarch = match arch {
+                "x86_64" => "amd64",
+                "aarch64" => "arm64",
+                other => other,
+            };
+            vec![format!("{}_{}", goos, goarch)]
+        }
+        _ => Vec::new(),
+    };
+    targets
+        .into_iter()
+        .map(|target| format!("-{}.tar.gz", target))
+        .collect()
+}
+
+fn pick_asset<'a>(assets: &'a [Asset], suffixes: &[String]) -> Option<&'a Asset> {
+    suffixes
+        .iter()
+        .find_map(|suffix| assets.iter().find(|asset| asset.name.ends_with(suffix)))
+}
+
+fn download(url: &str, path: &Path) -> Result<()> {
+    let output = CommandRunner::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--proto",
+            "=https",
+            "--tlsv1.2",
+            "--retry",
+            "3",
+            "--output",
+        ])
+        .arg(path)
+        .arg(url)
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .output()?;
+    if !output.success() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Could not download {}: {}",
+            url,
+            output.stderr.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Checks the archive `name` read from `archive` against a hex SHA-256 digest
+fn verify(archive: &mut impl Read, name: &str, expected: &str) -> Result<()> {
+    let mut hasher = Sha256::new();
+    io::copy(archive, &mut hasher)?;
+    let actual = format!("{:x}", hasher.finalize());
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            name, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the file named `command` from a `.tar.gz` to `destination`, executable
+///
+/// The binary is written next to `destination` and renamed over it, so a
+/// copy that is running is never overwritten in place.
+fn extract_binary(archive: impl Read, name: &str, command: &str, destination: &Path) -> Result<()> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file()
+            || entry.path()?.file_name() != Some(OsStr::new(command))
+        {
+            continue;
+        }
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = destination.with_extension("partial");
+        io::copy(&mut entry, &mut File::create(&partial)?)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&partial, fs::Permissions::from_mode(0o755))?;
+        }
+        fs::rename(&partial, destination)?;
+        return Ok(());
+    }
+
+    Err(DotfilesError::InstallationFailed(format!(
+        "{} not found in {}",
+        command, name
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::TempDir;
+
+    fn asset(name: &str) -> Asset {
+        Asset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{}", name),
+            digest: None,
+        }
+    }
+
+    #[test]
+    fn test_platform_suffixes() {
+        assert_eq!(
+            platform_suffixes(Naming::Triple, "macos", "aarch64"),
+            vec!["-aarch64-apple-darwin.tar.gz"]
+        );
+        assert_eq!(
+            platform_suffixes(Naming::Triple, "linux", "x86_64"),
+            vec![
+                "-x86_64-unknown-linux-musl.tar.gz",
+                "-x86_64-unknown-linux-gnu.tar.gz"
+            ]
+        );
+        assert_eq!(
+            platform_suffixes(Naming::GoOsArch, "macos", "x86_64"),
+            vec!["-darwin_amd64.tar.gz"]
+        );
+        assert!(platform_suffixes(Naming::Triple, "windows", "x86_64").is_empty());
+    }
+
+    #[test]
+    fn test_pick_asset() {
+        let assets = vec![
+            asset("ripgrep-14.1.1-aarch64-unknown-linux-gnu.tar.gz"),
+            asset("ripgrep-14.1.1-aarch64-unknown-linux-gnu.tar.gz.sha256"),
+            asset("ripgrep-14.1.1-x86_64-apple-darwin.tar.gz"),
+            asset("ripgrep-14.1.1-x86_64-unknown-linux-musl.tar.gz"),
+        ];
+        let pick = |os, arch| {
+            pick_asset(&assets, &platform_suffixes(Naming::Triple, os, arch))
+                .map(|asset| asset.name.as_str())
+        };
+        assert_eq!(
+            pick("linux", "aarch64"),
+            Some("ripgrep-14.1.1-aarch64-unknown-linux-gnu.tar.gz")
+        );
+        assert_eq!(
+            pick("linux", "x86_64"),
+            Some("ripgrep-14.1.1-x86_64-unknown-linux-musl.tar.gz")
+        );
+        assert_eq!(pick("macos", "aarch64"), None);
+    }
+
+    #[test]
+    fn test_release_digest_parse() {
+        let release: Release = serde_json::from_str(
+            r#"{"tag_name": "v10.2.0", "assets": [
+                {"name": "fd-v10.2.0-x86_64-apple-darwin.tar.gz",
+                 "browser_download_url": "https://github.com/sharkdp/fd/releases/download/v10.2.0/fd-v10.2.0-x86_64-apple-darwin.tar.gz",
+                 "digest": "sha256:abc123", "size": 1}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(release.assets[0].digest.as_deref(), Some("sha256:abc123"));
+    }
+
+    #[test]
+    fn test_verify_and_extract_binary() {
+        let temp = TempDir::new().unwrap();
+        let archive = temp.path().join("rg.tar.gz");
+        let mut builder = tar::Builder::new(GzEncoder::new(
+            File::create(&archive).unwrap(),
+            Compression::default(),
+        ));
+        for (path, content) in [
+            ("ripgrep-14.1.1/README.md", "docs"),
+            ("ripgrep-14.1.1/rg", "#!/bin/sh\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update(fs::read(&archive).unwrap());
+        let digest = format!("{:x}", hasher.finalize());
+        let open = || File::open(&archive).unwrap();
+        verify(&mut open(), "rg.tar.gz", &digest).unwrap();
+        assert!(verify(&mut open(), "rg.tar.gz", &"0".repeat(64)).is_err());
+
+        let home = temp.path().join("home");
+        let destination = bin_dir(&home).join("rg");
+        extract_binary(open(), "rg.tar.gz", "rg", &destination).unwrap();
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "#!/bin/sh\n");
+        assert!(is_installed(&home, "ripgrep"));
+        assert!(!is_installed(&home, "fd"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&destination).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+        assert!(extract_binary(open(), "rg.tar.gz", "fd", &bin_dir(&home).join("fd")).is_err());
+    }
+}
@@ -1,7 +1,8 @@
+use crate::core::command::{self, CommandRunner};
 use crate::error::{DotfilesError, Result};
+use chrono::NaiveDateTime;
 use colored::Colorize;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
 /// Repository configuration for cloning
 pub struct RepoConfig {
@@ -37,19 +38,21 @@ pub fn clone_repo(config: &RepoConfig) -> Result<()> {
         std::fs::create_dir_all(parent)?;
     }
 
-    let status = Command::new("git")
-        .arg("clone")
+    let output = CommandRunner::new("git")
+        .args(["clone", "--recurse-submodules"])
         .arg(&config.url)
         .arg(&config.target_path)
-        .status()
+        .timeout(command::NETWORK_TIMEOUT)
+        .output()
         .map_err(|e| {
             DotfilesError::InstallationFailed(format!("Failed to execute git clone: {}", e))
         })?;
 
-    if !status.success() {
+    if !output.success() {
         return Err(DotfilesError::InstallationFailed(format!(
-            "Failed to clone {} repository",
-            config.name
+            "Failed to clone {} repository: {}",
+            config.name,
+            output.stderr.trim()
         )));
     }
 
@@ -73,7 +76,7 @@ pub fn clone_dotfiles_repo(target_dir: &Path, repo_url: &str) -> Result<()> {
 
 /// Clones the claude repository
 pub fn clone_claude_repo(repo_url: &str) -> Result<()> {
-    let home = dirs::home_dir()
+    let home = crate::core::paths::home_dir()
         .ok_or_else(|| DotfilesError::Config("Could not determine home directory".to_string()))?;
 
     let config = RepoConfig {
@@ -85,11 +88,129 @@ pub fn clone_claude_repo(repo_url: &str) -> Result<()> {
     clone_repo(&config)
 }
 
+/// Returns the `origin` remote URL of a git repository, if it has one
+pub fn remote_url(path: &Path) -> Option<String> {
+    let output = CommandRunner::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["remote", "get-url", "origin"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+
+    let url = output.stdout.trim();
+    (output.success() && !url.is_empty()).then(|| url.to_string())
+}
+
+/// Returns when HEAD was committed, in local time, or None outside a git repo
+pub fn last_commit_time(path: &Path) -> Option<NaiveDateTime> {
+    let output = CommandRunner::new("git")
+        .arg("-C")
+        .arg(path)
+        .args([
+            "log",
+            "-1",
+            "--format=%cd",
+            "--date=format-local:%Y-%m-%d %H:%M:%S",
+        ])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+
+    if !output.success() {
+        return None;
+    }
+    NaiveDateTime::parse_from_str(output.stdout.trim(), "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Returns true if the repo at `path` declares submodules
+pub fn has_submodules(path: &Path) -> bool {
+    path.join(".gitmodules").exists()
+}
+
+/// Initializes and updates a repo's submodules to the commits it records
+///
+/// Picks up changed submodule URLs first. Does nothing for a repo without
+/// submodules.
+pub fn update_submodules(path: &Path) -> Result<()> {
+    if !has_submodules(path) {
+        return Ok(());
+    }
+    for args in [
+        &["submodule", "sync", "--recursive"][..],
+        &["submodule", "update", "--init", "--recursive"][..],
+    ] {
+        CommandRunner::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(args)
+            .timeout(command::NETWORK_TIMEOUT)
+            .stream(false)
+            .run()?;
+    }
+    Ok(())
+}
+
 /// Checks if a directory is a git repository
 pub fn is_git_repo(path: &Path) -> bool {
     path.join(".git").exists()
 }
 
+/// How a working copy differs from its upstream branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepoStatus {
+    /// Upstream commits not yet pulled
+    pub behind: usize,
+    /// Local commits not yet pushed
+    pub ahead: usize,
+    /// Modified, staged, or untracked paths
+    pub changed: usize,
+}
+
+/// Fetches from the remote, then compares the working copy with its upstream
+///
+/// A failed fetch (offline, no remote) is ignored; `behind` then reflects
+/// the last successful fetch.
+pub fn status(path: &Path) -> Result<RepoStatus> {
+    let _ = CommandRunner::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["fetch", "--quiet"])
+        .timeout(command::NETWORK_TIMEOUT)
+        .stream(false)
+        .output();
+
+    let output = CommandRunner::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["status", "--porcelain=v2", "--branch"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(parse_status(&output.stdout))
+}
+
+/// Parses `git status --porcelain=v2 --branch` output
+fn parse_status(output: &str) -> RepoStatus {
+    let mut status = RepoStatus::default();
+    for line in output.lines() {
+        if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            for count in counts.split_whitespace() {
+                if let Some(n) = count.strip_prefix('+') {
+                    status.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = count.strip_prefix('-') {
+                    status.behind = n.parse().unwrap_or(0);
+                }
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            status.changed += 1;
+        }
+    }
+    status
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,6 +226,24 @@ mod tests {
         assert!(is_git_repo(temp.path()));
     }
 
+    #[test]
+    fn test_parse_status() {
+        let output = "# branch.oid 1234\n# branch.head main\n# branch.upstream origin/main\n\
+                      # branch.ab +1 -3\n1 .M N... 100644 100644 100644 a b zshrc\n? notes.md\n";
+        assert_eq!(
+            parse_status(output),
+            RepoStatus {
+                behind: 3,
+                ahead: 1,
+                changed: 2
+            }
+        );
+        assert_eq!(
+            parse_status("# branch.oid 1234\n# branch.head main\n"),
+            RepoStatus::default()
+        );
+    }
+
     #[test]
     fn test_is_git_repo_returns_false_for_non_git_repo() {
         let temp = TempDir::new().unwrap();
@@ -1,7 +1,11 @@
+use crate::core::audit::{self, Action};
+use crate::core::config::Config;
 use crate::error::Result;
+use crate::install::kubernetes;
+use crate::install::version_manager::VersionManager;
 use colored::Colorize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Ensures a script is sourced in shell RC file
 pub fn ensure_script_sourced(shell_rc: &Path, script_path: &Path, script_name: &str) -> Result<()> {
@@ -33,16 +37,25 @@ pub fn ensure_script_sourced(shell_rc: &Path, script_path: &Path, script_name: &
 
     let source_line = format!(
         "\n# Source {} (added by dotfiles-tool)\nsource {}\n",
-        script_name, script_str
+        script_name,
+        shell_word(script_str)
     );
 
     let new_content = content + &source_line;
+    let backup = backup_rc(shell_rc)?;
     fs::write(shell_rc, new_content)?;
+    audit::record(Action::FileWritten {
+        path: shell_rc.to_path_buf(),
+        reason: format!("sourced {}", script_name),
+    });
 
     println!(
         "{}",
         format!("  ✓ Added {} to {}", script_name, shell_rc.display()).green()
     );
+    if let Some(backup) = backup {
+        println!("    Previous version saved to {}", backup.display());
+    }
     Ok(())
 }
 
@@ -50,14 +63,353 @@ pub fn ensure_script_sourced(shell_rc: &Path, script_path: &Path, script_name: &
 fn is_script_sourced(content: &str, script_path: &Path) -> bool {
     let script_str = script_path.to_str().unwrap_or("");
 
-    content.contains(&format!("source {}", script_str))
-        || content.contains(&format!(". {}", script_str))
+    [script_str.to_string(), shell_word(script_str)]
+        .iter()
+        .any(|word| {
+            content.contains(&format!("source {}", word))
+                || content.contains(&format!(". {}", word))
+        })
+}
+
+/// How many `.bak.<timestamp>` copies of each shell RC file are kept
+pub const RC_BACKUPS_KEPT: usize = 5;
+
+/// Copies a shell RC file to `<name>.bak.<timestamp>` next to it before it is edited
+///
+/// Returns None if there was nothing to copy. Copies from the same second
+/// get a numeric suffix instead of overwriting each other. Only the oldest
+/// copy, which holds the file as it was before the tool first touched it,
+/// and the newest others up to `RC_BACKUPS_KEPT` in all are kept; other
+/// `<name>.bak.*` files (e.g. a hand-made `.zshrc.bak.orig`) are left alone.
+pub fn backup_rc(shell_rc: &Path) -> Result<Option<PathBuf>> {
+    if !shell_rc.is_file() {
+        return Ok(None);
+    }
+    let (Some(dir), Some(name)) = (shell_rc.parent(), shell_rc.file_name()) else {
+        return Ok(None);
+    };
+    let prefix = format!("{}.bak.", name.to_string_lossy());
+    let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+
+    let mut backup = dir.join(format!("{}{}", prefix, timestamp));
+    let mut n = 2;
+    while backup.exists() {
+        backup = dir.join(format!("{}{}-{}", prefix, timestamp, n));
+        n += 1;
+    }
+    fs::copy(shell_rc, &backup)?;
+    audit::record(Action::FileCopied {
+        from: shell_rc.to_path_buf(),
+        to: backup.clone(),
+    });
+
+    let mut backups: Vec<(String, u32, String)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let (stamp, n) = parse_backup_stamp(name.strip_prefix(&prefix)?)?;
+            Some((stamp.to_string(), n, name))
+        })
+        .collect();
+    // "<timestamp>-<n>" sorts by n numerically, so -10 comes after -2
+    backups.sort();
+    // The oldest copy is never pruned
+    let excess = backups.len().saturating_sub(RC_BACKUPS_KEPT);
+    for (_, _, old) in backups.iter().skip(1).take(excess) {
+        let _ = fs::remove_file(dir.join(old));
+    }
+
+    Ok(Some(backup))
+}
+
+/// Splits a `YYYYMMDD-HHMMSS[-n]` backup suffix into its timestamp and n (1 if absent)
+fn parse_backup_stamp(suffix: &str) -> Option<(&str, u32)> {
+    let stamp = suffix.get(..15)?;
+    let bytes = stamp.as_bytes();
+    let valid = bytes.iter().enumerate().all(|(i, b)| {
+        if i == 8 {
+            *b == b'-'
+        } else {
+            b.is_ascii_digit()
+        }
+    });
+    if !valid {
+        return None;
+    }
+    let n = match &suffix[15..] {
+        "" => 1,
+        rest => {
+            let digits = rest.strip_prefix('-')?;
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            digits.parse().ok()?
+        }
+    };
+    Some((stamp, n))
+}
+
+/// Opening marker of the block of shell config owned by dotfiles-tool
+pub const MANAGED_BLOCK_START: &str = "# >>> dotfiles-tool managed block >>>";
+
+/// Closing marker of the managed block
+pub const MANAGED_BLOCK_END: &str = "# <<< dotfiles-tool managed block <<<";
+
+/// Returns the environment variables the managed block exports, in order
+pub fn managed_env_vars(config: &Config) -> Vec<(String, String)> {
+    let mut vars = vec![(
+        "XDG_CONFIG_HOME".to_string(),
+        config.xdg_config_home.to_string_lossy().into_owned(),
+    )];
+    vars.extend(
+        config
+            .homebrew
+            .env_vars()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string())),
+    );
+
+    if config.kubernetes.enabled {
+        if let Some(home) = crate::core::paths::home_dir() {
+            let paths = kubernetes::kubeconfig_paths(&config.dotfiles_dir, &home);
+            if !paths.is_empty() {
+                let joined: Vec<String> = paths
+                    .iter()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                vars.push(("KUBECONFIG".to_string(), joined.join(":")));
+            }
+        }
+    }
+
+    vars
+}
+
+/// fzf's key bindings and completion, written by its installer after a Homebrew install
+pub const FZF_SCRIPT: &str = ".fzf.zsh";
+
+/// Builds the lines of the managed shell block from configuration
+///
+/// Includes the tool's `dfs`/`dfe` shortcuts when `shell_aliases` is set,
+/// puts `~/.local/bin` on PATH when `[packages] backend` installs there,
+/// sources fzf's shell integration once its installer has written it to
+/// `home_dir`, and
+/// ends with the configured version manager's activation line, so switching
+/// managers replaces the old hook instead of leaving it to shadow the new one.
+pub fn managed_block_lines(config: &Config, home_dir: &Path) -> Vec<String> {
+    let mut lines: Vec<String> = managed_env_vars(config)
+        .iter()
+        .map(|(key, value)| export_line(key, value))
+        .collect();
+    if config.shell_aliases {
+        lines.extend(tool_alias_lines(Shell::Zsh, &config.dotfiles_dir));
+    }
+    if config.packages.backend.is_some() {
+        lines.push(r#"export PATH="$HOME/.local/bin:$PATH""#.to_string());
+    }
+    if home_dir.join(FZF_SCRIPT).is_file() {
+        lines.push(format!(r#"source "$HOME/{}""#, FZF_SCRIPT));
+    }
+    if let Some(vm) = VersionManager::from_language_manager(config.language_manager) {
+        lines.push(vm.activation_line().to_string());
+    }
+    lines
+}
+
+/// Shells the tool's own shortcuts can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+impl Shell {
+    /// Parses a shell name or path, such as "fish" or `$SHELL` (`/bin/zsh`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match Path::new(name).file_name()?.to_str()? {
+            "zsh" => Some(Self::Zsh),
+            "bash" => Some(Self::Bash),
+            "fish" => Some(Self::Fish),
+            _ => None,
+        }
+    }
+
+    /// The login shell from `$SHELL`, defaulting to zsh
+    pub fn current() -> Self {
+        std::env::var("SHELL")
+            .ok()
+            .and_then(|shell| Self::from_name(&shell))
+            .unwrap_or(Self::Zsh)
+    }
+}
+
+/// Returns shortcuts for this tool in `shell`'s syntax
+///
+/// `dfs` runs `dotfiles sync`; `dfe <name>` opens `<name>` in the dotfiles
+/// repo with `$EDITOR`, or the repo itself when no name is given.
+pub fn tool_alias_lines(shell: Shell, dotfiles_dir: &Path) -> Vec<String> {
+    let dir = shell_word(&dotfiles_dir.to_string_lossy());
+    match shell {
+        Shell::Zsh | Shell::Bash => vec![
+            "alias dfs='dotfiles sync'".to_string(),
+            format!("dfe() {{ \"${{EDITOR:-vi}}\" {}/\"$1\"; }}", dir),
+        ],
+        Shell::Fish => vec![
+            "alias dfs 'dotfiles sync'".to_string(),
+            format!(
+                "function dfe; set -q EDITOR; or set -l EDITOR vi; $EDITOR {}/$argv[1]; end",
+                dir
+            ),
+        ],
+    }
+}
+
+/// Returns 1-based line numbers outside the managed block that activate `vm`
+pub fn find_hook_lines(content: &str, vm: VersionManager) -> Vec<usize> {
+    let mut inside = false;
+    let mut found = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        match line.trim() {
+            MANAGED_BLOCK_START => inside = true,
+            MANAGED_BLOCK_END => inside = false,
+            _ if !inside && vm.is_hook_line(line) => found.push(index + 1),
+            _ => {}
+        }
+    }
+
+    found
+}
+
+/// Formats an `export` line, double-quoting the value when needed
+pub fn export_line(key: &str, value: &str) -> String {
+    format!("export {}={}", key, shell_word(value))
+}
+
+/// Returns `value` as a single shell word, double-quoting it when needed
+///
+/// Spaces and non-ASCII characters (e.g. `/Users/Zoë Smith`) are quoted;
+/// `"`, `\`, `$`, and `` ` `` are escaped inside the quotes.
+pub fn shell_word(value: &str) -> String {
+    let is_plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-:~+@".contains(c));
+
+    if is_plain {
+        value.to_string()
+    } else {
+        let escaped: String = value
+            .chars()
+            .flat_map(|c| match c {
+                '"' | '\\' | '$' | '`' => vec!['\\', c],
+                _ => vec![c],
+            })
+            .collect();
+        format!("\"{}\"", escaped)
+    }
+}
+
+/// Renders the managed block, including its markers
+pub fn render_managed_block(lines: &[String]) -> String {
+    let mut block = String::new();
+    block.push_str(MANAGED_BLOCK_START);
+    block.push('\n');
+    for line in lines {
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push_str(MANAGED_BLOCK_END);
+    block.push('\n');
+    block
+}
+
+/// Returns the lines inside the managed block, or None if there is no block
+pub fn read_managed_block(content: &str) -> Option<Vec<String>> {
+    let start = content.find(MANAGED_BLOCK_START)?;
+    let body_start = start + MANAGED_BLOCK_START.len();
+    let end = content[body_start..].find(MANAGED_BLOCK_END)? + body_start;
+
+    Some(
+        content[body_start..end]
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Replaces the managed block in `content`, appending one if none exists
+///
+/// An empty `lines` removes the block entirely. Everything outside the
+/// markers is left untouched.
+pub fn upsert_managed_block(content: &str, lines: &[String]) -> String {
+    let existing = content.find(MANAGED_BLOCK_START).and_then(|start| {
+        let after = start + MANAGED_BLOCK_START.len();
+        content[after..].find(MANAGED_BLOCK_END).map(|end| {
+            let mut end = after + end + MANAGED_BLOCK_END.len();
+            if content[end..].starts_with('\n') {
+                end += 1;
+            }
+            (start, end)
+        })
+    });
+
+    let block = if lines.is_empty() {
+        String::new()
+    } else {
+        render_managed_block(lines)
+    };
+
+    match existing {
+        Some((start, end)) => format!("{}{}{}", &content[..start], block, &content[end..]),
+        None if block.is_empty() => content.to_string(),
+        None => {
+            let mut updated = content.to_string();
+            if !updated.is_empty() && !updated.ends_with('\n') {
+                updated.push('\n');
+            }
+            if !updated.is_empty() {
+                updated.push('\n');
+            }
+            updated.push_str(&block);
+            updated
+        }
+    }
+}
+
+/// Writes the managed block into a shell RC file
+///
+/// Returns true if the file changed.
+pub fn ensure_managed_block(shell_rc: &Path, lines: &[String]) -> Result<bool> {
+    let content = if shell_rc.exists() {
+        fs::read_to_string(shell_rc)?
+    } else {
+        String::new()
+    };
+
+    let updated = upsert_managed_block(&content, lines);
+    if updated == content {
+        return Ok(false);
+    }
+
+    backup_rc(shell_rc)?;
+    fs::write(shell_rc, updated)?;
+    audit::record(Action::FileWritten {
+        path: shell_rc.to_path_buf(),
+        reason: "updated managed block".to_string(),
+    });
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::{HomebrewSettings, LanguageManager};
     use std::fs;
+    use std::path::PathBuf;
     use tempfile::TempDir;
 
     #[test]
@@ -150,4 +502,242 @@ mod tests {
         assert!(content.contains(existing));
         assert!(content.contains("source"));
     }
+
+    #[test]
+    fn test_ensure_script_sourced_quotes_unusual_paths() {
+        let temp = TempDir::new().unwrap();
+        let zshrc = temp.path().join(".zshrc");
+        let script = temp.path().join("My Dotfiles").join("Zoë's script.sh");
+
+        fs::create_dir_all(script.parent().unwrap()).unwrap();
+        fs::write(&script, "#!/bin/bash\necho test").unwrap();
+
+        ensure_script_sourced(&zshrc, &script, "script.sh").unwrap();
+        let content = fs::read_to_string(&zshrc).unwrap();
+        assert!(content.contains(&format!("source \"{}\"", script.display())));
+
+        // Sourcing again recognizes the quoted line
+        ensure_script_sourced(&zshrc, &script, "script.sh").unwrap();
+        assert_eq!(fs::read_to_string(&zshrc).unwrap(), content);
+
+        assert_eq!(shell_word("/usr/local/bin"), "/usr/local/bin");
+        assert_eq!(shell_word("$HOME/my dir"), "\"\\$HOME/my dir\"");
+    }
+
+    #[test]
+    fn test_managed_block_lines_from_homebrew_settings() {
+        let config = Config {
+            homebrew: HomebrewSettings {
+                no_analytics: true,
+                no_auto_update: true,
+                ..HomebrewSettings::default()
+            },
+            language_manager: LanguageManager::None,
+            ..Config::default()
+        };
+
+        assert_eq!(
+            &managed_block_lines(&config, Path::new("/nonexistent"))[1..],
+            &[
+                "export HOMEBREW_NO_ANALYTICS=1",
+                "export HOMEBREW_NO_AUTO_UPDATE=1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_upsert_managed_block_appends() {
+        let lines = vec!["export HOMEBREW_NO_ANALYTICS=1".to_string()];
+        let updated = upsert_managed_block("alias ll='ls -la'", &lines);
+
+        assert!(updated.starts_with("alias ll='ls -la'\n\n"));
+        assert_eq!(read_managed_block(&updated), Some(lines));
+    }
+
+    #[test]
+    fn test_upsert_managed_block_replaces_in_place() {
+        let content = format!(
+            "before\n{}export OLD=1\n{}\nafter\n",
+            format_args!("{}\n", MANAGED_BLOCK_START),
+            MANAGED_BLOCK_END
+        );
+        let lines = vec!["export NEW=1".to_string()];
+        let updated = upsert_managed_block(&content, &lines);
+
+        assert!(updated.starts_with("before\n"));
+        assert!(updated.ends_with("after\n"));
+        assert!(!updated.contains("OLD"));
+        assert_eq!(read_managed_block(&updated), Some(lines));
+    }
+
+    #[test]
+    fn test_upsert_managed_block_removes_when_empty() {
+        let content = format!(
+            "before\n{}",
+            render_managed_block(&["export A=1".to_string()])
+        );
+        assert_eq!(upsert_managed_block(&content, &[]), "before\n");
+    }
+
+    #[test]
+    fn test_backup_rc_before_edit() {
+        let temp = TempDir::new().unwrap();
+        let zshrc = temp.path().join(".zshrc");
+        let lines = vec!["export HOMEBREW_NO_AUTO_UPDATE=1".to_string()];
+
+        assert_eq!(backup_rc(&zshrc).unwrap(), None);
+        fs::write(&zshrc, "alias ll='ls -la'\n").unwrap();
+        assert!(ensure_managed_block(&zshrc, &lines).unwrap());
+
+        let backups = || {
+            let mut names: Vec<String> = fs::read_dir(temp.path())
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+                .filter(|name| name.starts_with(".zshrc.bak."))
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(backups().len(), 1);
+        assert_eq!(
+            fs::read_to_string(temp.path().join(&backups()[0])).unwrap(),
+            "alias ll='ls -la'\n"
+        );
+
+        let original = backups()[0].clone();
+        fs::write(temp.path().join(".zshrc.bak.orig"), "mine\n").unwrap();
+        for _ in 0..RC_BACKUPS_KEPT + 2 {
+            backup_rc(&zshrc).unwrap();
+        }
+        let kept = backups();
+        // RC_BACKUPS_KEPT timestamped copies, plus the hand-made one left alone
+        assert_eq!(kept.len(), RC_BACKUPS_KEPT + 1);
+        assert!(kept.contains(&".zshrc.bak.orig".to_string()));
+        // The very first copy, holding the original content, is never pruned
+        assert!(kept.contains(&original));
+        assert_eq!(
+            fs::read_to_string(temp.path().join(&original)).unwrap(),
+            "alias ll='ls -la'\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_backup_stamp() {
+        assert_eq!(
+            parse_backup_stamp("20261016-120000"),
+            Some(("20261016-120000", 1))
+        );
+        assert_eq!(
+            parse_backup_stamp("20261016-120000-10"),
+            Some(("20261016-120000", 10))
+        );
+        assert_eq!(parse_backup_stamp("orig"), None);
+        assert_eq!(parse_backup_stamp("20261016-120000-"), None);
+        assert_eq!(parse_backup_stamp("20261016-120000.old"), None);
+        assert_eq!(parse_backup_stamp("2026101a-120000"), None);
+    }
+
+    #[test]
+    fn test_ensure_managed_block_idempotent() {
+        let temp = TempDir::new().unwrap();
+        let zshrc = temp.path().join(".zshrc");
+        let lines = vec!["export HOMEBREW_NO_AUTO_UPDATE=1".to_string()];
+
+        assert!(ensure_managed_block(&zshrc, &lines).unwrap());
+        assert!(!ensure_managed_block(&zshrc, &lines).unwrap());
+    }
+
+    #[test]
+    fn test_managed_block_exports_xdg_config_home() {
+        let config = Config {
+            xdg_config_home: PathBuf::from("/Users/me/.config"),
+            language_manager: LanguageManager::None,
+            ..Config::default()
+        };
+
+        assert_eq!(
+            managed_block_lines(&config, Path::new("/nonexistent")),
+            vec!["export XDG_CONFIG_HOME=/Users/me/.config"]
+        );
+    }
+
+    #[test]
+    fn test_managed_block_sources_fzf_once_installed() {
+        let home = TempDir::new().unwrap();
+        let config = Config {
+            xdg_config_home: PathBuf::from("/Users/me/.config"),
+            language_manager: LanguageManager::Mise,
+            ..Config::default()
+        };
+        let fzf = r#"source "$HOME/.fzf.zsh""#.to_string();
+
+        assert!(!managed_block_lines(&config, home.path()).contains(&fzf));
+
+        fs::write(home.path().join(FZF_SCRIPT), "").unwrap();
+        let lines = managed_block_lines(&config, home.path());
+        assert_eq!(lines[lines.len() - 2], fzf);
+    }
+
+    #[test]
+    fn test_managed_block_ends_with_activation() {
+        let config = Config {
+            language_manager: LanguageManager::Mise,
+            ..Config::default()
+        };
+
+        assert_eq!(
+            managed_block_lines(&config, Path::new("/nonexistent"))
+                .last()
+                .map(String::as_str),
+            Some(r#"eval "$(mise activate zsh)""#)
+        );
+    }
+
+    #[test]
+    fn test_tool_alias_lines_per_shell() {
+        assert_eq!(Shell::from_name("/usr/local/bin/fish"), Some(Shell::Fish));
+        assert_eq!(Shell::from_name("bash"), Some(Shell::Bash));
+        assert_eq!(Shell::from_name("/bin/tcsh"), None);
+
+        let dir = Path::new("/Users/Zoë Smith/dotfiles");
+        assert_eq!(
+            tool_alias_lines(Shell::Zsh, dir),
+            vec![
+                "alias dfs='dotfiles sync'",
+                r#"dfe() { "${EDITOR:-vi}" "/Users/Zoë Smith/dotfiles"/"$1"; }"#,
+            ]
+        );
+        let fish = tool_alias_lines(Shell::Fish, dir);
+        assert_eq!(fish[0], "alias dfs 'dotfiles sync'");
+        assert!(fish[1].contains(r#""/Users/Zoë Smith/dotfiles"/$argv[1]"#));
+
+        let config = Config {
+            language_manager: LanguageManager::Mise,
+            shell_aliases: true,
+            ..Config::default()
+        };
+        let lines = managed_block_lines(&config, Path::new("/nonexistent"));
+        assert!(lines.contains(&"alias dfs='dotfiles sync'".to_string()));
+        assert!(lines.last().unwrap().contains("mise activate"));
+    }
+
+    #[test]
+    fn test_find_hook_lines_skips_managed_block() {
+        let content = format!(
+            "export PATH=/usr/bin\n. ~/.asdf/asdf.sh\n{}",
+            render_managed_block(&[VersionManager::Asdf.activation_line().to_string()])
+        );
+
+        assert_eq!(find_hook_lines(&content, VersionManager::Asdf), vec![2]);
+        assert!(find_hook_lines(&content, VersionManager::Mise).is_empty());
+    }
+
+    #[test]
+    fn test_export_line_quotes_special_values() {
+        assert_eq!(
+            export_line("XDG_CONFIG_HOME", "/Users/me/My Config"),
+            "export XDG_CONFIG_HOME=\"/Users/me/My Config\""
+        );
+        assert_eq!(export_line("A", "$x"), "export A=\"\\$x\"");
+    }
 }
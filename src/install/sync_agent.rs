@@ -0,0 +1,224 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{CommandRunner, QUICK_TIMEOUT};
+use crate::core::notify::UNATTENDED_VAR;
+use crate::error::{DotfilesError, Result};
+use crate::validate::launch_agents::MANAGED_LABEL_PREFIX;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name shared by the launchd label and the systemd unit
+const AGENT_NAME: &str = "sync-watch";
+
+/// Returns the launchd label of the sync watcher
+pub fn label() -> String {
+    format!("{}{}", MANAGED_LABEL_PREFIX, AGENT_NAME)
+}
+
+/// Returns where the agent definition lives for this platform
+pub fn agent_path(home_dir: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home_dir
+            .join("Library/LaunchAgents")
+            .join(format!("{}.plist", label()))
+    } else {
+        home_dir
+            .join(".config/systemd/user")
+            .join(format!("dotfiles-{}.service", AGENT_NAME))
+    }
+}
+
+/// Returns the command line the agent runs
+fn watch_args(exe: &Path, interval_minutes: u64) -> Vec<String> {
+    vec![
+        exe.display().to_string(),
+        "sync".to_string(),
+        "watch".to_string(),
+        "--interval".to_string(),
+        interval_minutes.to_string(),
+    ]
+}
+
+/// Renders a LaunchAgent plist that keeps `dotfiles sync watch` running
+pub fn render_plist(exe: &Path, interval_minutes: u64, log: &Path) -> String {
+    let args: String = watch_args(exe, interval_minutes)
+        .iter()
+        .map(|arg| format!("        <string>{}</string>\n", xml_escape(arg)))
+        .collect();
+    let log = xml_escape(&log.display().to_string());
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{label}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>{unattended}</key>
+        <string>1</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        label = label(),
+        unattended = UNATTENDED_VAR,
+    )
+}
+
+/// Renders a systemd user service that keeps `dotfiles sync watch` running
+pub fn render_systemd_unit(exe: &Path, interval_minutes: u64) -> String {
+    let exec = watch_args(exe, interval_minutes)
+        .iter()
+        .map(|arg| {
+            if arg.contains(char::is_whitespace) {
+                format!("\"{}\"", arg)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "[Unit]\n\
+         Description=Watch dotfiles for upstream changes and local drift\n\
+         \n\
+         [Service]\n\
+         Environment={}=1\n\
+         ExecStart={}\n\
+         Restart=on-failure\n\
+         RestartSec=60\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        UNATTENDED_VAR, exec
+    )
+}
+
+/// Where the macOS agent's output goes, next to the state file
+pub fn log_path(home_dir: &Path) -> PathBuf {
+    crate::core::state::state_path(home_dir).with_file_name("sync-watch.log")
+}
+
+/// Writes the agent definition for this platform and starts it
+pub fn install(home_dir: &Path, interval_minutes: u64) -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let path = agent_path(home_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if cfg!(target_os = "macos") {
+        let log = log_path(home_dir);
+        if let Some(parent) = log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Reloading picks up a changed interval or binary path
+        let _ = launchctl(&["unload".as_ref(), path.as_os_str()]);
+        fs::write(&path, render_plist(&exe, interval_minutes, &log))?;
+        launchctl(&["load".as_ref(), "-w".as_ref(), path.as_os_str()])?;
+    } else {
+        fs::write(&path, render_systemd_unit(&exe, interval_minutes))?;
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", "--now", &unit_name()])?;
+        systemctl(&["restart", &unit_name()])?;
+    }
+
+    audit::record(Action::FileWritten {
+        path: path.clone(),
+        reason: "installed sync watcher".to_string(),
+    });
+    Ok(path)
+}
+
+/// Stops the agent and removes its definition; returns false if it was not installed
+pub fn remove(home_dir: &Path) -> Result<bool> {
+    let path = agent_path(home_dir);
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    if cfg!(target_os = "macos") {
+        let _ = launchctl(&["unload".as_ref(), "-w".as_ref(), path.as_os_str()]);
+        fs::remove_file(&path)?;
+    } else {
+        let _ = systemctl(&["disable", "--now", &unit_name()]);
+        fs::remove_file(&path)?;
+        systemctl(&["daemon-reload"])?;
+    }
+
+    Ok(true)
+}
+
+fn unit_name() -> String {
+    format!("dotfiles-{}.service", AGENT_NAME)
+}
+
+fn launchctl(args: &[&OsStr]) -> Result<()> {
+    service_command("launchctl", args)
+}
+
+fn systemctl(args: &[&str]) -> Result<()> {
+    let mut full = vec!["--user"];
+    full.extend_from_slice(args);
+    service_command("systemctl", &full)
+}
+
+fn service_command<S: AsRef<OsStr>>(program: &str, args: &[S]) -> Result<()> {
+    CommandRunner::new(program)
+        .args(args)
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .run()
+        .map(|_| ())
+        .map_err(|e| DotfilesError::CommandFailed(format!("{} failed: {}", program, e)))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::launch_agents::list_launch_agents;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_plist_is_recognized_as_managed_agent() {
+        let temp = TempDir::new().unwrap();
+        let exe = Path::new("/usr/local/bin/dotfiles");
+        let plist = render_plist(exe, 15, &temp.path().join("sync-watch.log"));
+        assert!(plist.contains("<string>--interval</string>\n        <string>15</string>"));
+        assert!(plist.contains("<key>DOTFILES_UNATTENDED</key>"));
+
+        fs::write(temp.path().join(format!("{}.plist", label())), plist).unwrap();
+        let agents = list_launch_agents(temp.path());
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].label, "com.dotfiles-tool.sync-watch");
+        assert_eq!(agents[0].program.as_deref(), Some(exe));
+    }
+
+    #[test]
+    fn test_systemd_unit_quotes_paths_with_spaces() {
+        let unit = render_systemd_unit(Path::new("/home/me/my bin/dotfiles"), 30);
+        assert!(unit.contains("ExecStart=\"/home/me/my bin/dotfiles\" sync watch --interval 30\n"));
+        assert!(unit.contains("Environment=DOTFILES_UNATTENDED=1\n"));
+        assert!(unit.contains("WantedBy=default.target"));
+    }
+}
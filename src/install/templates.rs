@@ -0,0 +1,311 @@
+use crate::core::audit::{self, Action};
+use crate::core::state::{self, fingerprint, State};
+use crate::core::walk::RepoWalker;
+use crate::error::{DotfilesError, Result};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Directory in the dotfiles repo whose files are rendered into `$HOME`
+///
+/// `templates/home/.gitconfig.work` renders to `~/.gitconfig.work`.
+pub const TEMPLATE_DIR: &str = "templates/home";
+
+/// Machine-local answers to template variables, kept next to the state file
+pub const VARS_FILE: &str = "vars.toml";
+
+/// A repo template and where it renders to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub content: String,
+}
+
+/// What happened to one template during a render pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderOutcome {
+    /// Target written (created or updated)
+    Written,
+    /// Target already matches the rendered output
+    Unchanged,
+    /// Variables without an answer in `vars.toml`
+    MissingVars(Vec<String>),
+    /// Target was edited since it was last rendered; left alone
+    ModifiedLocally,
+}
+
+/// Returns the path of the machine-local `vars.toml` (never inside the repo)
+pub fn vars_path(home_dir: &Path) -> PathBuf {
+    state::state_path(home_dir).with_file_name(VARS_FILE)
+}
+
+/// Loads template variables, returning none if the file does not exist yet
+pub fn load_vars(path: &Path) -> Result<BTreeMap<String, String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(toml::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Saves template variables readable only by the owner (they may be personal)
+pub fn save_vars(path: &Path, vars: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, toml::to_string_pretty(vars)?)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the repo's templates, sorted by target
+pub fn find_templates(dotfiles_dir: &Path, home_dir: &Path) -> Result<Vec<Template>> {
+    let root = dotfiles_dir.join(TEMPLATE_DIR);
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    RepoWalker::new(&root)
+        .files()?
+        .into_iter()
+        .map(|source| {
+            let rel = source.strip_prefix(&root).map_err(|_| {
+                DotfilesError::Config(format!("{} is outside {}", source.display(), TEMPLATE_DIR))
+            })?;
+            Ok(Template {
+                target: home_dir.join(rel),
+                content: fs::read_to_string(&source)?,
+                source,
+            })
+        })
+        .collect()
+}
+
+fn variable_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap())
+}
+
+/// Returns the `{{ name }}` variables a template references
+pub fn variables(content: &str) -> BTreeSet<String> {
+    variable_pattern()
+        .captures_iter(content)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// Returns variables referenced by any template that have no answer yet
+pub fn missing_variables(
+    templates: &[Template],
+    vars: &BTreeMap<String, String>,
+) -> BTreeSet<String> {
+    templates
+        .iter()
+        .flat_map(|template| variables(&template.content))
+        .filter(|name| !vars.contains_key(name))
+        .collect()
+}
+
+/// Substitutes variables, failing with the names that have no value
+pub fn render(
+    content: &str,
+    vars: &BTreeMap<String, String>,
+) -> std::result::Result<String, Vec<String>> {
+    let missing: Vec<String> = variables(content)
+        .into_iter()
+        .filter(|name| !vars.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+
+    Ok(variable_pattern()
+        .replace_all(content, |caps: &regex::Captures| vars[&caps[1]].clone())
+        .into_owned())
+}
+
+/// Renders every template, recording what was written in `state`
+///
+/// A target that differs from what was last rendered (or that existed before
+/// the first render) is treated as a local edit and never overwritten.
+pub fn render_all(
+    templates: &[Template],
+    vars: &BTreeMap<String, String>,
+    state: &mut State,
+) -> Result<Vec<(PathBuf, RenderOutcome)>> {
+    let mut outcomes = Vec::new();
+
+    for template in templates {
+        let outcome = render_one(template, vars, state)?;
+        outcomes.push((template.target.clone(), outcome));
+    }
+
+    Ok(outcomes)
+}
+
+fn render_one(
+    template: &Template,
+    vars: &BTreeMap<String, String>,
+    state: &mut State,
+) -> Result<RenderOutcome> {
+    let rendered = match render(&template.content, vars) {
+        Ok(rendered) => rendered,
+        Err(missing) => return Ok(RenderOutcome::MissingVars(missing)),
+    };
+    let key = template.target.display().to_string();
+
+    if let Ok(current) = fs::read_to_string(&template.target) {
+        if current == rendered {
+            state.rendered.insert(key, fingerprint(&rendered));
+            return Ok(RenderOutcome::Unchanged);
+        }
+        if state.rendered.get(&key) != Some(&fingerprint(&current)) {
+            return Ok(RenderOutcome::ModifiedLocally);
+        }
+    } else if template.target.exists() {
+        // A directory or unreadable file; not ours to replace
+        return Ok(RenderOutcome::ModifiedLocally);
+    }
+
+    write(template, &rendered, state)?;
+    Ok(RenderOutcome::Written)
+}
+
+/// Writes a rendered template to its target, replacing whatever is there
+pub fn write(template: &Template, rendered: &str, state: &mut State) -> Result<()> {
+    if let Some(parent) = template.target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&template.target, rendered)?;
+    audit::record(Action::FileWritten {
+        path: template.target.clone(),
+        reason: "rendered template".to_string(),
+    });
+    state
+        .rendered
+        .insert(template.target.display().to_string(), fingerprint(rendered));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_variables() {
+        let content =
+            "[user]\n  email = {{ work_email }}\n  signingkey = {{signing_key}}\n{{ work_email }}";
+        assert_eq!(
+            variables(content).into_iter().collect::<Vec<_>>(),
+            vec!["signing_key", "work_email"]
+        );
+        assert!(variables("no {{ 1bad }} vars").is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let content = "email = {{ work_email }}";
+        assert_eq!(
+            render(content, &vars(&[("work_email", "me@work.com")])).unwrap(),
+            "email = me@work.com"
+        );
+        assert_eq!(
+            render(content, &BTreeMap::new()).unwrap_err(),
+            vec!["work_email"]
+        );
+    }
+
+    #[test]
+    fn test_find_templates_and_missing_variables() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        fs::create_dir_all(dotfiles.join("templates/home/.ssh")).unwrap();
+        fs::write(
+            dotfiles.join("templates/home/.gitconfig.work"),
+            "{{ work_email }}",
+        )
+        .unwrap();
+        fs::write(
+            dotfiles.join("templates/home/.ssh/config"),
+            "User {{ user }}",
+        )
+        .unwrap();
+
+        let templates = find_templates(&dotfiles, &home).unwrap();
+        let targets: Vec<&PathBuf> = templates.iter().map(|t| &t.target).collect();
+        assert_eq!(
+            targets,
+            vec![&home.join(".gitconfig.work"), &home.join(".ssh/config")]
+        );
+
+        let missing = missing_variables(&templates, &vars(&[("user", "me")]));
+        assert_eq!(missing.into_iter().collect::<Vec<_>>(), vec!["work_email"]);
+    }
+
+    #[test]
+    fn test_render_all_rerenders_and_protects_local_edits() {
+        let temp = TempDir::new().unwrap();
+        let target = temp.path().join(".gitconfig.work");
+        let mut template = Template {
+            source: temp.path().join("src"),
+            target: target.clone(),
+            content: "email = {{ email }}\n".to_string(),
+        };
+        let mut state = State::default();
+
+        let outcomes = render_all(&[template.clone()], &BTreeMap::new(), &mut state).unwrap();
+        assert_eq!(
+            outcomes[0].1,
+            RenderOutcome::MissingVars(vec!["email".to_string()])
+        );
+
+        let answers = vars(&[("email", "a@b.c")]);
+        let outcomes = render_all(&[template.clone()], &answers, &mut state).unwrap();
+        assert_eq!(outcomes[0].1, RenderOutcome::Written);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "email = a@b.c\n");
+
+        let outcomes = render_all(&[template.clone()], &answers, &mut state).unwrap();
+        assert_eq!(outcomes[0].1, RenderOutcome::Unchanged);
+
+        // Template changed upstream: re-rendered
+        template.content = "[user]\nemail = {{ email }}\n".to_string();
+        let outcomes = render_all(&[template.clone()], &answers, &mut state).unwrap();
+        assert_eq!(outcomes[0].1, RenderOutcome::Written);
+
+        // Local edit: left alone
+        fs::write(&target, "edited by hand\n").unwrap();
+        template.content = "email={{ email }}\n".to_string();
+        let outcomes = render_all(&[template], &answers, &mut state).unwrap();
+        assert_eq!(outcomes[0].1, RenderOutcome::ModifiedLocally);
+        assert_eq!(fs::read_to_string(&target).unwrap(), "edited by hand\n");
+    }
+
+    #[test]
+    fn test_vars_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("dotfiles/vars.toml");
+        assert!(load_vars(&path).unwrap().is_empty());
+
+        let answers = vars(&[("work_email", "me@work.com")]);
+        save_vars(&path, &answers).unwrap();
+        assert_eq!(load_vars(&path).unwrap(), answers);
+    }
+}
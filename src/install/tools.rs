@@ -1,7 +1,7 @@
+use crate::core::command::{self, CommandRunner};
 use crate::error::{DotfilesError, Result};
 use colored::Colorize;
 use std::path::Path;
-use std::process::Command;
 
 /// Installs TPM (Tmux Plugin Manager)
 pub fn install_tpm(home_dir: &Path) -> Result<()> {
@@ -23,19 +23,20 @@ pub fn install_tpm(home_dir: &Path) -> Result<()> {
     }
 
     // Clone TPM repository
-    let status = Command::new("git")
-        .arg("clone")
-        .arg("https://github.com/tmux-plugins/tpm")
+    let output = CommandRunner::new("git")
+        .args(["clone", "https://github.com/tmux-plugins/tpm"])
         .arg(&tpm_path)
-        .status()
+        .timeout(command::NETWORK_TIMEOUT)
+        .output()
         .map_err(|e| {
             DotfilesError::InstallationFailed(format!("Failed to execute git clone: {}", e))
         })?;
 
-    if !status.success() {
-        return Err(DotfilesError::InstallationFailed(
-            "TPM installation failed".to_string(),
-        ));
+    if !output.success() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "TPM installation failed: {}",
+            output.stderr.trim()
+        )));
     }
 
     println!("{}", "  ✓ TPM installed successfully".green());
@@ -1,5 +1,10 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::LanguageManager;
 use crate::error::{DotfilesError, Result};
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Supported version managers
@@ -11,6 +16,64 @@ pub enum VersionManager {
 }
 
 impl VersionManager {
+    pub const ALL: [VersionManager; 3] = [
+        VersionManager::Mise,
+        VersionManager::Asdf,
+        VersionManager::Rtx,
+    ];
+
+    /// Parses a version manager name as typed on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|vm| vm.command().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the version manager a config selects, if any
+    pub fn from_language_manager(manager: LanguageManager) -> Option<Self> {
+        match manager {
+            LanguageManager::Asdf => Some(VersionManager::Asdf),
+            LanguageManager::Mise => Some(VersionManager::Mise),
+            LanguageManager::Rtx => Some(VersionManager::Rtx),
+            LanguageManager::None => None,
+        }
+    }
+
+    /// Returns the config value selecting this version manager
+    pub fn language_manager(&self) -> LanguageManager {
+        match self {
+            VersionManager::Asdf => LanguageManager::Asdf,
+            VersionManager::Mise => LanguageManager::Mise,
+            VersionManager::Rtx => LanguageManager::Rtx,
+        }
+    }
+
+    /// Returns the zsh line that activates this version manager
+    pub fn activation_line(&self) -> &str {
+        match self {
+            VersionManager::Asdf => r#"export PATH="${ASDF_DATA_DIR:-$HOME/.asdf}/shims:$PATH""#,
+            VersionManager::Mise => r#"eval "$(mise activate zsh)""#,
+            VersionManager::Rtx => r#"eval "$(rtx activate zsh)""#,
+        }
+    }
+
+    /// Returns true if a shell rc line activates this version manager
+    ///
+    /// Covers the activation line above plus the common hand-written forms
+    /// (`. ~/.asdf/asdf.sh`, `$(brew --prefix asdf)/libexec/asdf.sh`, ...).
+    pub fn is_hook_line(&self, line: &str) -> bool {
+        let line = line.trim();
+        if line.starts_with('#') {
+            return false;
+        }
+        let markers: &[&str] = match self {
+            VersionManager::Asdf => &["asdf.sh", ".asdf/shims", "ASDF_DATA_DIR", "asdf/libexec"],
+            VersionManager::Mise => &["mise activate", "mise/shims"],
+            VersionManager::Rtx => &["rtx activate", "rtx/shims"],
+        };
+        markers.iter().any(|marker| line.contains(marker))
+    }
+
     /// Returns the command name for this version manager
     pub fn command(&self) -> &str {
         match self {
@@ -37,18 +100,26 @@ impl VersionManager {
             VersionManager::Rtx => "rtx",
         }
     }
+
+    /// Returns the directory where this version manager keeps installs and shims
+    pub fn data_dir(&self, home_dir: &Path) -> PathBuf {
+        match self {
+            VersionManager::Asdf => home_dir.join(".asdf"),
+            VersionManager::Mise => home_dir.join(".local/share/mise"),
+            VersionManager::Rtx => home_dir.join(".local/share/rtx"),
+        }
+    }
+
+    /// Returns the directory where this version manager places its shims
+    pub fn shim_dir(&self, home_dir: &Path) -> PathBuf {
+        self.data_dir(home_dir).join("shims")
+    }
 }
 
 /// Detects which version manager is installed
 pub fn detect() -> Option<VersionManager> {
     // Check in order of preference: mise, asdf, rtx
-    [
-        VersionManager::Mise,
-        VersionManager::Asdf,
-        VersionManager::Rtx,
-    ]
-    .into_iter()
-    .find(|&vm| is_installed(vm))
+    VersionManager::ALL.into_iter().find(|&vm| is_installed(vm))
 }
 
 /// Checks if a specific version manager is installed
@@ -100,6 +171,40 @@ pub fn install_preferred() -> Result<VersionManager> {
 
 /// Installs a language runtime using the specified version manager
 pub fn install_language(vm: VersionManager, language: &str, version: &str) -> Result<()> {
+    install_version(vm, language, version)?;
+    set_global(vm, language, version)?;
+
+    println!("{} {} installed and set as global!", language, version);
+    Ok(())
+}
+
+/// Installs several versions of a language runtime, setting one as global
+///
+/// The remaining versions are installed but left inactive, so projects can
+/// select them (e.g. with `mise use`) without a further download.
+pub fn install_language_versions(
+    vm: VersionManager,
+    language: &str,
+    versions: &[String],
+    global: &str,
+) -> Result<()> {
+    for version in versions {
+        install_version(vm, language, version)?;
+    }
+
+    set_global(vm, language, global)?;
+
+    println!(
+        "{} {} installed ({} set as global)",
+        language,
+        versions.join(", "),
+        global
+    );
+    Ok(())
+}
+
+/// Installs a single language version without activating it
+pub fn install_version(vm: VersionManager, language: &str, version: &str) -> Result<()> {
     let vm_path = get_path(vm)
         .ok_or_else(|| DotfilesError::DependencyMissing(vm.display_name().to_string()))?;
 
@@ -112,48 +217,263 @@ pub fn install_language(vm: VersionManager, language: &str, version: &str) -> Re
 
     // Add plugin first (for asdf)
     if vm == VersionManager::Asdf {
-        let _ = Command::new(&vm_path)
-            .arg("plugin")
-            .arg("add")
-            .arg(language)
+        let _ = CommandRunner::new(&vm_path)
+            .args(["plugin", "add", language])
+            .timeout(command::NETWORK_TIMEOUT)
             .output();
     }
 
     // Install the language version
-    let status = Command::new(&vm_path)
-        .arg("install")
-        .arg(language)
-        .arg(version)
-        .status()?;
+    let output = CommandRunner::new(&vm_path)
+        .args(["install", language, version])
+        .timeout(command::INSTALL_TIMEOUT)
+        .output()?;
 
-    if !status.success() {
+    if !output.success() {
         return Err(DotfilesError::InstallationFailed(format!(
-            "Failed to install {} {}",
-            language, version
+            "Failed to install {} {}: {}",
+            language,
+            version,
+            output.stderr.trim()
         )));
     }
 
-    // Set as global version
-    let status = Command::new(&vm_path)
-        .arg("global")
-        .arg(language)
-        .arg(version)
-        .status()?;
+    audit::record(Action::PackageInstalled {
+        package: format!("{}@{}", language, version),
+        manager: vm.display_name().to_string(),
+    });
+    Ok(())
+}
+
+/// Sets the global version of a language runtime
+pub fn set_global(vm: VersionManager, language: &str, version: &str) -> Result<()> {
+    let vm_path = get_path(vm)
+        .ok_or_else(|| DotfilesError::DependencyMissing(vm.display_name().to_string()))?;
+
+    let runner = match vm {
+        VersionManager::Asdf => CommandRunner::new(&vm_path).args(["global", language, version]),
+        VersionManager::Mise | VersionManager::Rtx => CommandRunner::new(&vm_path)
+            .args(["use", "--global"])
+            .arg(format!("{}@{}", language, version)),
+    };
+    let output = runner.timeout(command::QUICK_TIMEOUT).output()?;
 
-    if !status.success() {
+    if !output.success() {
         return Err(DotfilesError::InstallationFailed(format!(
-            "Failed to set {} {} as global",
-            language, version
+            "Failed to set {} {} as global: {}",
+            language,
+            version,
+            output.stderr.trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Lists the installed versions of a language runtime
+pub fn installed_versions(vm: VersionManager, language: &str) -> Vec<String> {
+    let Some(vm_path) = get_path(vm) else {
+        return Vec::new();
+    };
+
+    let runner = match vm {
+        VersionManager::Asdf => CommandRunner::new(&vm_path).args(["list", language]),
+        VersionManager::Mise | VersionManager::Rtx => {
+            CommandRunner::new(&vm_path).args(["ls", "--installed", "--no-header", language])
+        }
+    };
+
+    match runner
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => parse_installed_versions(vm, &output.stdout),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses the output of `asdf list <lang>` or `mise ls --installed <lang>`
+fn parse_installed_versions(vm: VersionManager, output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| match vm {
+            // asdf marks the current version with a leading '*'
+            VersionManager::Asdf => {
+                let version = line.trim().trim_start_matches('*').trim();
+                (!version.is_empty() && !version.starts_with("No versions"))
+                    .then(|| version.to_string())
+            }
+            // mise prints "<tool>  <version>  [source]  [requested]"
+            VersionManager::Mise | VersionManager::Rtx => {
+                line.split_whitespace().nth(1).map(|v| v.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Uninstalls a language runtime version using the specified version manager
+pub fn uninstall_language(vm: VersionManager, language: &str, version: &str) -> Result<()> {
+    let vm_path = get_path(vm)
+        .ok_or_else(|| DotfilesError::DependencyMissing(vm.display_name().to_string()))?;
+
+    println!(
+        "Uninstalling {} {} using {}...",
+        language,
+        version,
+        vm.display_name()
+    );
+
+    let runner = match vm {
+        VersionManager::Asdf => CommandRunner::new(&vm_path).args(["uninstall", language, version]),
+        VersionManager::Mise | VersionManager::Rtx => CommandRunner::new(&vm_path)
+            .arg("uninstall")
+            .arg(format!("{}@{}", language, version)),
+    };
+    let output = runner.timeout(command::QUICK_TIMEOUT).output()?;
+
+    if !output.success() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Failed to uninstall {} {}: {}",
+            language,
+            version,
+            output.stderr.trim()
         )));
     }
 
-    println!("{} {} installed and set as global!", language, version);
     Ok(())
 }
 
+/// Removes a language from a `.tool-versions` file
+///
+/// When `version` is given only that version is dropped from the language's
+/// line; the line is removed entirely once no versions remain. Returns true if
+/// the file was modified.
+pub fn remove_from_tool_versions(
+    tool_versions: &Path,
+    language: &str,
+    version: Option<&str>,
+) -> Result<bool> {
+    if !tool_versions.exists() {
+        return Ok(false);
+    }
+
+    let content = fs::read_to_string(tool_versions)?;
+    let mut changed = false;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        if parts.next() != Some(language) {
+            lines.push(line.to_string());
+            continue;
+        }
+
+        let remaining: Vec<&str> = match version {
+            Some(version) => parts.filter(|v| *v != version).collect(),
+            None => Vec::new(),
+        };
+
+        if remaining.is_empty() {
+            changed = true;
+        } else {
+            let rebuilt = format!("{} {}", language, remaining.join(" "));
+            changed |= rebuilt != line.trim();
+            lines.push(rebuilt);
+        }
+    }
+
+    if changed {
+        let mut new_content = lines.join("\n");
+        if !new_content.is_empty() {
+            new_content.push('\n');
+        }
+        fs::write(tool_versions, new_content)?;
+    }
+
+    Ok(changed)
+}
+
+/// Returns shims for the given binaries that still exist in the shim directory
+pub fn leftover_shims(shim_dir: &Path, binaries: &[&str]) -> Vec<PathBuf> {
+    binaries
+        .iter()
+        .map(|bin| shim_dir.join(bin))
+        .filter(|shim| shim.exists())
+        .collect()
+}
+
+/// Parses `.tool-versions` into languages and their versions, in file order
+pub fn parse_tool_versions(content: &str) -> Vec<(String, Vec<String>)> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let language = parts.next()?.to_string();
+            let versions: Vec<String> = parts.map(str::to_string).collect();
+            (!versions.is_empty()).then_some((language, versions))
+        })
+        .collect()
+}
+
+/// Returns shim directories of managers other than `active` found in `path`
+///
+/// A leftover shim directory early in PATH shadows the active manager's
+/// binaries, so `node` keeps resolving to the old install.
+pub fn stale_shim_dirs(
+    active: VersionManager,
+    home_dir: &Path,
+    path: &OsStr,
+) -> Vec<(VersionManager, PathBuf)> {
+    let entries: Vec<PathBuf> = std::env::split_paths(path).collect();
+    VersionManager::ALL
+        .into_iter()
+        .filter(|vm| *vm != active)
+        .map(|vm| (vm, vm.shim_dir(home_dir)))
+        .filter(|(_, shims)| entries.contains(shims))
+        .collect()
+}
+
+/// Uninstalls a version manager and deletes its installs and shims
+///
+/// The Homebrew package is removed when brew has it; the data directory is
+/// removed either way, since that is where the shims live.
+pub fn uninstall(vm: VersionManager, home_dir: &Path) -> Result<()> {
+    let brew_installed = CommandRunner::new("brew")
+        .args(["list", "--formula", vm.homebrew_package()])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .map(|output| output.success())
+        .unwrap_or(false);
+
+    if brew_installed {
+        CommandRunner::new("brew")
+            .args(["uninstall", vm.homebrew_package()])
+            .timeout(command::INSTALL_TIMEOUT)
+            .run()?;
+    }
+
+    let data_dir = vm.data_dir(home_dir);
+    if data_dir.exists() {
+        fs::remove_dir_all(&data_dir)?;
+    }
+
+    Ok(())
+}
+
+/// Checks whether a directory is on the current PATH
+pub fn is_on_path(dir: &Path) -> bool {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|p| p == dir))
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
 
     #[test]
     fn test_version_manager_command() {
@@ -241,4 +561,137 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_shim_dir() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            VersionManager::Asdf.shim_dir(home),
+            PathBuf::from("/home/user/.asdf/shims")
+        );
+        assert_eq!(
+            VersionManager::Mise.shim_dir(home),
+            PathBuf::from("/home/user/.local/share/mise/shims")
+        );
+    }
+
+    #[test]
+    fn test_parse_installed_versions_asdf() {
+        let output = "  3.11.7\n *3.12.1\n";
+        assert_eq!(
+            parse_installed_versions(VersionManager::Asdf, output),
+            vec!["3.11.7", "3.12.1"]
+        );
+    }
+
+    #[test]
+    fn test_parse_installed_versions_mise() {
+        let output = "python  3.11.7\npython  3.12.1  ~/.tool-versions  3.12.1\n";
+        assert_eq!(
+            parse_installed_versions(VersionManager::Mise, output),
+            vec!["3.11.7", "3.12.1"]
+        );
+    }
+
+    #[test]
+    fn test_remove_from_tool_versions_whole_language() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".tool-versions");
+        fs::write(&path, "python 3.12.1\nnodejs 22.12.0\n").unwrap();
+
+        assert!(remove_from_tool_versions(&path, "python", None).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nodejs 22.12.0\n");
+    }
+
+    #[test]
+    fn test_remove_from_tool_versions_single_version() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".tool-versions");
+        fs::write(&path, "python 3.12.1 3.11.7\n").unwrap();
+
+        assert!(remove_from_tool_versions(&path, "python", Some("3.11.7")).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "python 3.12.1\n");
+
+        // Removing the last version drops the line
+        assert!(remove_from_tool_versions(&path, "python", Some("3.12.1")).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    }
+
+    #[test]
+    fn test_remove_from_tool_versions_untouched() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".tool-versions");
+        fs::write(&path, "nodejs 22.12.0\n").unwrap();
+
+        assert!(!remove_from_tool_versions(&path, "python", None).unwrap());
+        assert!(!remove_from_tool_versions(&temp.path().join("missing"), "python", None).unwrap());
+    }
+
+    #[test]
+    fn test_leftover_shims() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("python"), "#!/bin/sh").unwrap();
+
+        let shims = leftover_shims(temp.path(), &["python", "pip"]);
+        assert_eq!(shims, vec![temp.path().join("python")]);
+    }
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            VersionManager::from_name("mise"),
+            Some(VersionManager::Mise)
+        );
+        assert_eq!(
+            VersionManager::from_name("ASDF"),
+            Some(VersionManager::Asdf)
+        );
+        assert_eq!(VersionManager::from_name("nvm"), None);
+    }
+
+    #[test]
+    fn test_is_hook_line() {
+        let asdf = VersionManager::Asdf;
+        assert!(asdf.is_hook_line(". $(brew --prefix asdf)/libexec/asdf.sh"));
+        assert!(asdf.is_hook_line("  source ~/.asdf/asdf.sh"));
+        assert!(asdf.is_hook_line(asdf.activation_line()));
+        assert!(!asdf.is_hook_line("# . ~/.asdf/asdf.sh"));
+        assert!(!asdf.is_hook_line(VersionManager::Mise.activation_line()));
+        assert!(VersionManager::Mise.is_hook_line(r#"eval "$(~/.local/bin/mise activate zsh)""#));
+    }
+
+    #[test]
+    fn test_parse_tool_versions() {
+        let content = "nodejs 22.12.0 20.18.0\n# comment\npython 3.12.1 # pinned\nruby\n";
+        assert_eq!(
+            parse_tool_versions(content),
+            vec![
+                (
+                    "nodejs".to_string(),
+                    vec!["22.12.0".to_string(), "20.18.0".to_string()]
+                ),
+                ("python".to_string(), vec!["3.12.1".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_shim_dirs() {
+        let home = Path::new("/home/user");
+        let path = std::env::join_paths([
+            "/home/user/.asdf/shims",
+            "/home/user/.local/share/mise/shims",
+            "/usr/bin",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            stale_shim_dirs(VersionManager::Mise, home, &path),
+            vec![(
+                VersionManager::Asdf,
+                PathBuf::from("/home/user/.asdf/shims")
+            )]
+        );
+        assert!(stale_shim_dirs(VersionManager::Mise, home, OsStr::new("/usr/bin")).is_empty());
+    }
 }
@@ -0,0 +1,332 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::config::LanguageVersions;
+use crate::error::{DotfilesError, Result};
+use crate::install::version_manager::{self, VersionManager};
+use crate::symlink::{manual::ManualSymlinker, SymlinkReport, Symlinker};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Returns the version manager config files kept in the dotfiles repo
+///
+/// Each entry maps a repo-relative source path to the directory (relative to
+/// home, or XDG config home when `xdg` is true) where it should be linked.
+pub fn config_files(vm: VersionManager) -> &'static [(&'static str, &'static str, bool)] {
+    match vm {
+        VersionManager::Asdf => &[(".asdfrc", "", false), (".tool-versions", "", false)],
+        VersionManager::Mise => &[("mise/config.toml", "mise", true)],
+        VersionManager::Rtx => &[("rtx/config.toml", "rtx", true)],
+    }
+}
+
+/// Links the version manager's own config files from the dotfiles repo
+pub fn link_config(
+    vm: VersionManager,
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    xdg_config_home: &Path,
+) -> Result<SymlinkReport> {
+    let symlinker = ManualSymlinker::new();
+    let mut report = SymlinkReport::new();
+
+    for (source, target_dir, xdg) in config_files(vm) {
+        let source_path = dotfiles_dir.join(source);
+        if !source_path.exists() {
+            continue;
+        }
+
+        let base = if *xdg { xdg_config_home } else { home_dir };
+        let target_path = base.join(target_dir);
+        std::fs::create_dir_all(&target_path)?;
+
+        report.merge(symlinker.symlink(&source_path, &target_path)?);
+    }
+
+    Ok(report)
+}
+
+/// Generates `.tool-versions` content from configured languages
+///
+/// asdf treats the first listed version as active, so the global version is
+/// written first followed by the other installed versions.
+pub fn generate_tool_versions(languages: &BTreeMap<String, LanguageVersions>) -> String {
+    let mut content = String::new();
+
+    for (language, versions) in languages {
+        let Some(global) = versions.global_version() else {
+            continue;
+        };
+
+        let mut ordered = vec![global];
+        ordered.extend(
+            versions
+                .versions
+                .iter()
+                .map(|v| v.as_str())
+                .filter(|v| *v != global),
+        );
+        content.push_str(&format!("{} {}\n", language, ordered.join(" ")));
+    }
+
+    content
+}
+
+/// Writes `~/.tool-versions` from config if the repo does not provide one
+///
+/// Returns the path written, or None when the file already exists.
+pub fn ensure_tool_versions(
+    home_dir: &Path,
+    languages: &BTreeMap<String, LanguageVersions>,
+) -> Result<Option<PathBuf>> {
+    let path = home_dir.join(".tool-versions");
+    if path.exists() || path.is_symlink() || languages.is_empty() {
+        return Ok(None);
+    }
+
+    std::fs::write(&path, generate_tool_versions(languages))?;
+    Ok(Some(path))
+}
+
+/// Lists plugins with their source URLs as reported by the version manager
+pub fn installed_plugins(vm: VersionManager) -> BTreeMap<String, String> {
+    let Some(vm_path) = version_manager::get_path(vm) else {
+        return BTreeMap::new();
+    };
+
+    let runner = match vm {
+        VersionManager::Asdf => CommandRunner::new(&vm_path).args(["plugin", "list", "--urls"]),
+        VersionManager::Mise | VersionManager::Rtx => {
+            CommandRunner::new(&vm_path).args(["plugins", "ls", "--urls"])
+        }
+    };
+
+    match runner
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    {
+        Ok(output) if output.success() => parse_plugin_urls(&output.stdout),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Parses `<name> <url>` lines from plugin listings
+pub fn parse_plugin_urls(output: &str) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let name = parts.next()?;
+            let url = parts.next().unwrap_or_default();
+            Some((name.to_string(), url.to_string()))
+        })
+        .collect()
+}
+
+/// Compares two plugin URLs, ignoring a trailing `.git` or slash
+pub fn urls_match(a: &str, b: &str) -> bool {
+    fn normalize(url: &str) -> &str {
+        let url = url.trim_end_matches('/');
+        url.strip_suffix(".git").unwrap_or(url)
+    }
+    normalize(a) == normalize(b)
+}
+
+/// Adds a plugin from the given source URL
+pub fn add_plugin(vm: VersionManager, name: &str, url: &str) -> Result<()> {
+    let vm_path = version_manager::get_path(vm)
+        .ok_or_else(|| DotfilesError::DependencyMissing(vm.display_name().to_string()))?;
+
+    let runner = match vm {
+        VersionManager::Asdf => CommandRunner::new(&vm_path).args(["plugin", "add", name, url]),
+        VersionManager::Mise | VersionManager::Rtx => {
+            CommandRunner::new(&vm_path).args(["plugins", "install", name, url])
+        }
+    };
+    let output = runner.timeout(command::NETWORK_TIMEOUT).output()?;
+
+    if !output.success() {
+        return Err(DotfilesError::InstallationFailed(format!(
+            "Failed to add {} plugin {}: {}",
+            vm.display_name(),
+            name,
+            output.stderr.trim()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Adds any configured plugins that are not yet installed
+///
+/// Returns the names of plugins that were added. A plugin installed from a
+/// different URL is an error rather than being replaced, since removing an
+/// asdf plugin also deletes every version installed through it.
+pub fn ensure_plugins(
+    vm: VersionManager,
+    expected: &BTreeMap<String, String>,
+) -> Result<Vec<String>> {
+    let installed = installed_plugins(vm);
+    let mut added = Vec::new();
+
+    for (name, url) in expected {
+        if !installed.contains_key(name) {
+            add_plugin(vm, name, url)?;
+            added.push(name.clone());
+        }
+    }
+
+    let mismatched = mismatched_plugins(&installed, expected);
+    if !mismatched.is_empty() {
+        return Err(DotfilesError::InstallationFailed(
+            mismatched
+                .iter()
+                .map(|(name, actual, url)| {
+                    format!(
+                        "{} plugin {} is installed from {}, expected {} (remove and re-add it)",
+                        vm.display_name(),
+                        name,
+                        actual,
+                        url
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+        ));
+    }
+
+    Ok(added)
+}
+
+/// Returns `(name, installed url, expected url)` for plugins installed from another source
+///
+/// Plugins listed without a URL are left alone.
+fn mismatched_plugins<'a>(
+    installed: &'a BTreeMap<String, String>,
+    expected: &'a BTreeMap<String, String>,
+) -> Vec<(&'a str, &'a str, &'a str)> {
+    expected
+        .iter()
+        .filter_map(|(name, url)| {
+            let actual = installed.get(name)?;
+            (!actual.is_empty() && !urls_match(actual, url)).then_some((
+                name.as_str(),
+                actual.as_str(),
+                url.as_str(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_plugin_urls() {
+        let output = "nodejs   https://github.com/asdf-vm/asdf-nodejs.git\npython   https://github.com/asdf-community/asdf-python.git\n";
+        let plugins = parse_plugin_urls(output);
+
+        assert_eq!(plugins.len(), 2);
+        assert_eq!(
+            plugins["nodejs"],
+            "https://github.com/asdf-vm/asdf-nodejs.git"
+        );
+    }
+
+    #[test]
+    fn test_urls_match() {
+        assert!(urls_match(
+            "https://github.com/asdf-vm/asdf-nodejs.git",
+            "https://github.com/asdf-vm/asdf-nodejs"
+        ));
+        assert!(urls_match(
+            "https://github.com/asdf-vm/asdf-nodejs/",
+            "https://github.com/asdf-vm/asdf-nodejs"
+        ));
+        assert!(!urls_match(
+            "https://github.com/asdf-vm/asdf-nodejs",
+            "https://github.com/someone/asdf-nodejs"
+        ));
+    }
+
+    #[test]
+    fn test_mismatched_plugins() {
+        let installed = parse_plugin_urls(
+            "nodejs   https://github.com/asdf-vm/asdf-nodejs.git\npython   https://github.com/someone/asdf-python.git\nruby\n",
+        );
+        let mut expected = BTreeMap::new();
+        for (name, url) in [
+            ("nodejs", "https://github.com/asdf-vm/asdf-nodejs"),
+            (
+                "python",
+                "https://github.com/asdf-community/asdf-python.git",
+            ),
+            ("ruby", "https://github.com/asdf-vm/asdf-ruby.git"),
+            (
+                "golang",
+                "https://github.com/asdf-community/asdf-golang.git",
+            ),
+        ] {
+            expected.insert(name.to_string(), url.to_string());
+        }
+
+        assert_eq!(
+            mismatched_plugins(&installed, &expected),
+            vec![(
+                "python",
+                "https://github.com/someone/asdf-python.git",
+                "https://github.com/asdf-community/asdf-python.git"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_generate_tool_versions_global_first() {
+        let mut languages = BTreeMap::new();
+        languages.insert(
+            "java".to_string(),
+            LanguageVersions {
+                versions: vec!["openjdk-21".to_string(), "openjdk-17".to_string()],
+                global: Some("openjdk-17".to_string()),
+                when: None,
+            },
+        );
+        languages.insert("python".to_string(), LanguageVersions::single("3.12.1"));
+
+        let content = generate_tool_versions(&languages);
+        assert_eq!(content, "java openjdk-17 openjdk-21\npython 3.12.1\n");
+    }
+
+    #[test]
+    fn test_ensure_tool_versions_does_not_overwrite() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".tool-versions");
+        fs::write(&path, "nodejs 22.12.0\n").unwrap();
+
+        let mut languages = BTreeMap::new();
+        languages.insert("python".to_string(), LanguageVersions::single("3.12.1"));
+
+        assert!(ensure_tool_versions(temp.path(), &languages)
+            .unwrap()
+            .is_none());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nodejs 22.12.0\n");
+    }
+
+    #[test]
+    fn test_link_config_mise() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        let xdg = home.join(".config");
+
+        fs::create_dir_all(dotfiles.join("mise")).unwrap();
+        fs::write(dotfiles.join("mise/config.toml"), "[settings]\n").unwrap();
+
+        let report = link_config(VersionManager::Mise, &dotfiles, &home, &xdg).unwrap();
+
+        assert_eq!(report.created.len(), 1);
+        assert!(xdg.join("mise/config.toml").is_symlink());
+    }
+}
@@ -0,0 +1,82 @@
+use crate::core::cancel;
+use crate::core::command::{self, CommandRunner};
+use crate::error::Result;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long setup waits for the Command Line Tools installer to finish
+pub const INSTALL_WAIT: Duration = Duration::from_secs(30 * 60);
+
+/// How often setup checks whether the installer has finished
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Returns where `xcode-select` says the developer tools are, if it says anything
+pub fn selected_path() -> Option<PathBuf> {
+    CommandRunner::new("xcode-select")
+        .arg("-p")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()
+        .filter(|output| output.success())
+        .map(|output| PathBuf::from(output.stdout.trim()))
+        .filter(|path| !path.as_os_str().is_empty())
+}
+
+/// Returns true if the Command Line Tools (or a full Xcode) are installed and selected
+pub fn is_installed() -> bool {
+    selected_path().is_some_and(|path| path.exists())
+}
+
+/// Opens the macOS dialog that downloads and installs the Command Line Tools
+///
+/// Returns as soon as the dialog is up; the install itself runs in the
+/// background (see [`wait_until`]).
+pub fn request_install() -> Result<()> {
+    CommandRunner::new("xcode-select")
+        .arg("--install")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Polls `done` every `interval` until it returns true, `timeout` passes, or Ctrl-C
+///
+/// Returns whether `done` returned true.
+pub fn wait_until(done: impl Fn() -> bool, timeout: Duration, interval: Duration) -> bool {
+    let started = Instant::now();
+    loop {
+        if done() {
+            return true;
+        }
+        if cancel::is_cancelled() || started.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_wait_until() {
+        let polls = Cell::new(0);
+        let done = || {
+            polls.set(polls.get() + 1);
+            polls.get() == 3
+        };
+        assert!(wait_until(done, Duration::from_secs(5), Duration::ZERO));
+        assert_eq!(polls.get(), 3);
+
+        assert!(!wait_until(
+            || false,
+            Duration::from_millis(20),
+            Duration::from_millis(5)
+        ));
+    }
+}
@@ -15,6 +15,10 @@ impl LanguageInstaller for GoInstaller {
         "Go"
     }
 
+    fn binaries(&self) -> &[&str] {
+        &["go", "gofmt"]
+    }
+
     fn fallback_instructions(&self) -> String {
         format!(
             "Install {} manually:\n  \
@@ -15,6 +15,10 @@ impl LanguageInstaller for JavaInstaller {
         "Java"
     }
 
+    fn binaries(&self) -> &[&str] {
+        &["java", "javac", "jar"]
+    }
+
     fn fallback_instructions(&self) -> String {
         format!(
             "Install {} manually:\n  \
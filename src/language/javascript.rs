@@ -15,6 +15,10 @@ impl LanguageInstaller for JavaScriptInstaller {
         "Node.js"
     }
 
+    fn binaries(&self) -> &[&str] {
+        &["node", "npm", "npx"]
+    }
+
     fn fallback_instructions(&self) -> String {
         format!(
             "Install {} manually:\n  \
@@ -4,6 +4,7 @@ pub mod javascript;
 pub mod python;
 pub mod rust;
 
+use crate::core::config::LanguageVersions;
 use crate::error::Result;
 use crate::install::version_manager::VersionManager;
 
@@ -24,6 +25,50 @@ pub trait LanguageInstaller {
         crate::install::version_manager::install_language(vm, self.language_name(), version)
     }
 
+    /// Installs every configured version, setting the configured global one
+    fn install_versions(&self, vm: VersionManager, versions: &LanguageVersions) -> Result<()> {
+        if versions.versions.is_empty() {
+            return self.install(vm, None);
+        }
+
+        crate::install::version_manager::install_language_versions(
+            vm,
+            self.language_name(),
+            &versions.versions,
+            self.global_version(versions),
+        )
+    }
+
+    /// Installs every configured version (or the default one) without changing the global
+    fn install_inactive(&self, vm: VersionManager, versions: &LanguageVersions) -> Result<()> {
+        if versions.versions.is_empty() {
+            return crate::install::version_manager::install_version(
+                vm,
+                self.language_name(),
+                self.default_version(),
+            );
+        }
+        for version in &versions.versions {
+            crate::install::version_manager::install_version(vm, self.language_name(), version)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the version `install_versions` sets as global
+    fn global_version<'a>(&'a self, versions: &'a LanguageVersions) -> &'a str {
+        versions
+            .global_version()
+            .unwrap_or_else(|| self.default_version())
+    }
+
+    /// Removes an installed version using the specified version manager
+    fn remove(&self, vm: VersionManager, version: &str) -> Result<()> {
+        crate::install::version_manager::uninstall_language(vm, self.language_name(), version)
+    }
+
+    /// Returns the executables provided by this runtime (used to spot leftover shims)
+    fn binaries(&self) -> &[&str];
+
     /// Provides fallback installation instructions if no version manager is available
     fn fallback_instructions(&self) -> String;
 }
@@ -15,6 +15,10 @@ impl LanguageInstaller for PythonInstaller {
         "Python"
     }
 
+    fn binaries(&self) -> &[&str] {
+        &["python", "python3", "pip", "pip3"]
+    }
+
     fn fallback_instructions(&self) -> String {
         format!(
             "Install {} manually:\n  \
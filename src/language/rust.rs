@@ -15,6 +15,10 @@ impl LanguageInstaller for RustInstaller {
         "Rust"
     }
 
+    fn binaries(&self) -> &[&str] {
+        &["cargo", "rustc", "rustup"]
+    }
+
     fn fallback_instructions(&self) -> String {
         format!(
             "Install {} manually:\n  \
@@ -8,6 +8,12 @@ pub mod language;
 pub mod symlink;
 pub mod validate;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 // Re-export commonly used types
-pub use core::config::{Config, LanguageManager, SymlinkMethod};
+pub use core::config::{
+    Config, CriticalSymlink, GitSettings, HomebrewSettings, KubernetesSettings, LanguageManager,
+    LanguageVersions, LinkSeverity, ObsidianSettings, PackageSettings, SymlinkMethod,
+};
 pub use error::{DotfilesError, Result};
@@ -1,12 +1,45 @@
 use clap::{Parser, Subcommand};
 use dotfiles::commands;
+use dotfiles::core::config::SecretStoreKind;
+use dotfiles::core::context::AppContext;
+use dotfiles::core::exit::ExitStatus;
+use dotfiles::core::{audit, notify, paths, quiet};
 use dotfiles::Result;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(name = "dotfiles")]
 #[command(about = "Interactive dotfiles setup and management")]
 #[command(version)]
 struct Cli {
+    /// Stream output from external commands (brew, git, stow, ...)
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// Print nothing but a final `status=<status> code=<n>` line (pair with --yes)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Run hooks and checks defined in the repo without asking for approval (for CI)
+    #[arg(long, global = true)]
+    trust_all: bool,
+
+    /// Treat this directory as the home directory, to rehearse a run in a sandbox
+    /// (or set DOTFILES_HOME_OVERRIDE); XDG_*_HOME point inside it too
+    #[arg(long, global = true, value_name = "DIR")]
+    home: Option<PathBuf>,
+
+    /// Create links in this directory instead of $HOME, e.g. a prefix on a shared server
+    /// (or set DOTFILES_TARGET_DIR); setup remembers it as `link_root`
+    #[arg(long, global = true, value_name = "DIR")]
+    target_dir: Option<PathBuf>,
+
+    /// Skip everything that needs sudo or changes the system: Homebrew, packages,
+    /// machine identity (or set DOTFILES_USERSPACE_ONLY=1); setup remembers it
+    #[arg(long, global = true)]
+    userspace_only: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -14,34 +47,658 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Bootstrap dotfiles on a fresh system
-    Init,
+    Init {
+        /// Dotfiles repository to clone
+        #[arg(long)]
+        url: Option<String>,
+        /// Continue into setup after cloning without asking
+        #[arg(long)]
+        run_setup: bool,
+        /// Non-interactive: accept defaults and run setup (requires --url)
+        #[arg(short, long, requires = "url")]
+        yes: bool,
+    },
+    /// Scaffold a fresh dotfiles repository with starter configs and a Brewfile
+    NewRepo {
+        /// Where to create it (default: ~/Development/dotfiles)
+        path: Option<PathBuf>,
+        /// Also create the repo on GitHub with gh and push it
+        #[arg(long)]
+        github: bool,
+        /// Make the GitHub repo public instead of private
+        #[arg(long, requires = "github")]
+        public: bool,
+    },
     /// Run interactive setup
     Setup {
         #[arg(long)]
         dry_run: bool,
+        /// Non-interactive: use values from config instead of prompting
+        #[arg(short, long)]
+        yes: bool,
+        /// Use this `[profiles.<name>]` from now on instead of matching by hostname
+        #[arg(long)]
+        profile: Option<String>,
     },
     /// Validate all configurations
-    Doctor,
-    /// Migrate existing configs
+    Doctor {
+        /// Print the report (with per-step timings) as JSON; same as --format json
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+        /// Print the report in a machine-readable format: json or yaml
+        #[arg(long, value_parser = ["json", "yaml"])]
+        format: Option<String>,
+        /// Install missing nvim providers first, then apply safe fixes (missing or
+        /// dangling links, essential packages, source lines) and check again
+        #[arg(long)]
+        fix: bool,
+        /// Rescan every file instead of reusing results for unchanged files
+        #[arg(long)]
+        no_cache: bool,
+        #[command(subcommand)]
+        command: Option<DoctorCommand>,
+    },
+    /// Bundle doctor output, redacted config, and machine info for a bug report
+    ReportIssue {
+        /// Write the markdown report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Move a config file into the dotfiles repo and symlink it back
+    Adopt {
+        /// File or directory to adopt (e.g. ~/.psqlrc)
+        path: PathBuf,
+    },
+    /// Convert a bare-repo dotfiles setup (the `config` alias style) into the dotfiles repo
+    ImportBare {
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Remove the symlinks setup made from $HOME into the dotfiles repo
+    #[command(alias = "uninstall")]
+    Unlink {
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show which dotfiles are linked, which packages are missing, and whether the config is current
+    Status,
+    /// Check the dotfiles repo for collisions, stray files, and layout problems
+    Lint,
+    /// Show what happens to every repo file, and which managed files in $HOME lost their source
+    Coverage,
+    /// List copied and templated files that differ from the repo, and diff, overwrite, or adopt them
+    Drift {
+        /// Only list them, without prompting
+        #[arg(long)]
+        list: bool,
+    },
+    /// Pull the dotfiles repo and re-render changed templates
+    Sync {
+        #[command(subcommand)]
+        command: Option<SyncCommand>,
+    },
+    /// List past runs that changed files or packages, or show one run's changes
+    History {
+        /// Run ID (or a unique prefix) to show in detail
+        run_id: Option<String>,
+    },
+    /// Export or import app settings (Raycast, Alfred, Rectangle, iTerm2) kept in the repo
+    Appconfig {
+        #[command(subcommand)]
+        command: Option<AppconfigCommand>,
+    },
+    /// Export the package set to a Brewfile in the dotfiles repo, or install from it
+    Brewfile {
+        #[command(subcommand)]
+        command: BrewfileCommand,
+    },
+    /// Show the manual steps left after setup
+    Checklist {
+        #[command(subcommand)]
+        command: Option<ChecklistCommand>,
+    },
+    /// Copy an existing dotfiles directory into the dotfiles repo, commit it, and link from there
+    Migrate {
+        /// The existing dotfiles directory (e.g. ~/.dotfiles)
+        source: PathBuf,
+        /// Repo to migrate into (default: the configured dotfiles_dir)
+        #[arg(long)]
+        target: Option<PathBuf>,
+        /// How the existing directory is organized: flat or packages (Stow); detected if omitted
+        #[arg(long, value_parser = ["flat", "packages"])]
+        layout: Option<String>,
+        /// Copy files without scanning them for secrets
+        #[arg(long)]
+        no_secret_scan: bool,
+        /// Replace detected secrets in shell files with ${VAR} references: in the repo's
+        /// copies only, or in the existing files too (in-place)
+        #[arg(long, value_parser = ["repo", "in-place"], conflicts_with = "no_secret_scan")]
+        redact: Option<String>,
+        /// Where to keep extracted secrets: env (a git-ignored .env in the repo), keychain,
+        /// or 1password ([secrets] vault); defaults to [secrets] store
+        #[arg(long, value_parser = ["env", "keychain", "1password"], conflicts_with = "no_secret_scan")]
+        secret_store: Option<String>,
+        /// Don't back up the existing directory first
+        #[arg(long)]
+        no_backup: bool,
+        /// Show what would be copied and linked without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Back up the dotfiles repo, or manage existing backups
+    #[command(args_conflicts_with_subcommands = true)]
+    Backup {
+        /// Write an age-encrypted .tar.gz.age archive to [backups] age_recipient
+        #[arg(long)]
+        encrypt: bool,
+        /// Upload the new backup to [backups] remote (needs --encrypt or --allow-unencrypted)
+        #[arg(long)]
+        push: bool,
+        /// Allow --push to upload a backup that isn't encrypted
+        #[arg(long, requires = "push")]
+        allow_unencrypted: bool,
+        #[command(subcommand)]
+        command: Option<BackupCommand>,
+    },
+    /// Restore a whole backup, backing up the current contents first
+    Restore {
+        /// Backup to restore, by timestamp (YYYYMMDD-HHMMSS) or directory name; asks if omitted
+        #[arg(long)]
+        timestamp: Option<String>,
+        /// Directory to restore into (default: where the backup was taken from)
+        #[arg(long)]
+        target: Option<PathBuf>,
+        /// Fetch the backups pushed to [backups] remote and restore one of those
+        #[arg(long)]
+        from_remote: bool,
+    },
+    /// Inspect the dotfiles configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Generate files to commit to your dotfiles repo
+    Generate {
+        #[command(subcommand)]
+        command: GenerateCommand,
+    },
+    /// Manage language runtimes
+    Languages {
+        #[command(subcommand)]
+        command: LanguagesCommand,
+    },
+    /// Manage the language version manager
+    Vm {
+        #[command(subcommand)]
+        command: VmCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print ~/.dotfiles.conf and any problems with its values
+    Show,
+}
+
+#[derive(Subcommand)]
+enum DoctorCommand {
+    /// Diff a `doctor --json` export from another machine against this one
+    Compare {
+        /// JSON file written by `dotfiles doctor --json` on the other machine
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum SyncCommand {
+    /// Periodically check the dotfiles repo and ~/.claude, notifying when a sync or commit is needed
+    Watch {
+        /// Minutes between checks
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// Check once and exit
+        #[arg(long)]
+        once: bool,
+    },
+    /// Install a launchd/systemd user agent that runs `sync watch` in the background
+    Agent {
+        /// Minutes between checks
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// Stop and remove the agent instead
+        #[arg(long)]
+        remove: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommand {
+    /// Browse backups, diff files against current state, and restore entries
+    Browse,
+    /// Move old ~/.dotfiles-backup-* directories into the configured backup directory
     Migrate,
-    /// Create backup
-    Backup,
+    /// Delete the oldest backups beyond [backups] max_count / max_size_mb
+    Prune {
+        /// Number of newest backups to keep instead of the configured limits (at least 1)
+        #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        keep: Option<usize>,
+        /// Show what would be deleted without deleting it
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete without asking (required when stdin isn't a terminal)
+        #[arg(short, long)]
+        yes: bool,
+    },
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum BrewfileCommand {
+    /// Write the package groups, taps, and installed casks to <dotfiles>/Brewfile
+    Export {
+        /// Write here instead of the dotfiles repo
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Print the Brewfile instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Add the taps and install the packages <dotfiles>/Brewfile lists that are missing
+    Install {
+        /// Show what would be installed without installing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AppconfigCommand {
+    /// Copy an app's current settings into the repo's appconfig/ directory
+    Export {
+        /// App id (e.g. raycast, rectangle)
+        app: String,
+        /// Export file to use instead of the newest one in ~/Downloads (Raycast)
+        #[arg(long)]
+        from: Option<PathBuf>,
+    },
+    /// Apply an app's settings from the repo to this machine
+    Import {
+        /// App id (e.g. raycast, rectangle)
+        app: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChecklistCommand {
+    /// Mark an item done
+    Done {
+        /// Item id (e.g. gh-auth)
+        item: String,
+    },
+    /// Mark an item not done again
+    Undo {
+        /// Item id (e.g. gh-auth)
+        item: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum GenerateCommand {
+    /// Print the `dfs`/`dfe` shortcuts for this tool, for shells setup doesn't manage
+    Aliases {
+        /// Shell syntax to use: zsh, bash, or fish (default: from $SHELL)
+        #[arg(long)]
+        shell: Option<String>,
+    },
+    /// POSIX sh script that installs this tool and runs `dotfiles init --yes`
+    BootstrapScript {
+        /// Dotfiles repository to clone (defaults to the configured repo's origin)
+        #[arg(long)]
+        url: Option<String>,
+        /// GitHub repository to download release binaries from
+        #[arg(long, default_value = commands::generate::DEFAULT_RELEASE_REPO)]
+        release_repo: String,
+        /// Write to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
 
-    match cli.command {
-        Commands::Init => commands::init(),
-        Commands::Setup { dry_run } => commands::setup(dry_run),
-        Commands::Doctor => commands::doctor(),
-        Commands::Migrate => {
-            println!("Migrate command (not yet implemented)");
-            Ok(())
+#[derive(Subcommand)]
+enum LanguagesCommand {
+    /// Uninstall a language runtime and clear it from .tool-versions
+    Remove {
+        /// Language name (e.g. python, nodejs)
+        language: String,
+        /// Specific version to remove (defaults to all installed versions)
+        version: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum VmCommand {
+    /// Install another version manager, move runtimes to it, and swap the shell hook
+    Switch {
+        /// Version manager to switch to (mise, asdf, or rtx)
+        manager: String,
+        /// Uninstall the previous manager and delete its installs and shims
+        #[arg(long)]
+        remove_old: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            // --help and --version are not errors
+            return if e.use_stderr() {
+                ExitCode::from(ExitStatus::Usage.code())
+            } else {
+                ExitCode::SUCCESS
+            };
         }
-        Commands::Backup => {
-            println!("Backup command (not yet implemented)");
-            Ok(())
+    };
+    dotfiles::core::command::set_verbose(cli.verbose);
+    dotfiles::core::trust::set_trust_all(cli.trust_all);
+    dotfiles::core::userspace::set_userspace_only(cli.userspace_only);
+    // --quiet sends prompts to /dev/null, so nothing may wait on one
+    if cli.quiet {
+        if let Some(reason) = prompt_needed(&cli.command) {
+            eprintln!("Error: --quiet hides prompts, but {}", reason);
+            return ExitCode::from(ExitStatus::Usage.code());
         }
     }
+    dotfiles::core::sudo::set_non_interactive(cli.quiet);
+    dotfiles::core::cancel::install_handler();
+
+    let home_override = cli.home.clone().or_else(|| {
+        std::env::var_os(paths::HOME_OVERRIDE_VAR)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    });
+    if let Some(dir) = home_override {
+        match paths::expand(&dir).and_then(|dir| paths::resolve_home_override(&dir)) {
+            Ok(home) => {
+                // Child processes (git, brew, stow, ...) look at $HOME and XDG themselves
+                std::env::set_var("HOME", &home);
+                for (var, dir) in paths::xdg_dirs(&home) {
+                    std::env::set_var(var, dir);
+                }
+                paths::set_home_override(Some(home));
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(ExitStatus::Usage.code());
+            }
+        }
+    }
+    // Expanded after --home, so `~` in the link root follows it
+    let link_root = cli.target_dir.clone().or_else(|| {
+        std::env::var_os(paths::LINK_ROOT_VAR)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    });
+    if let Some(dir) = link_root {
+        match paths::expand(&dir).and_then(|dir| paths::resolve_link_root(&dir)) {
+            Ok(root) => paths::set_link_root_override(Some(root)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(ExitStatus::Usage.code());
+            }
+        }
+    }
+
+    // Built once overrides are in place, so every command sees the same home
+    let context = match AppContext::from_env() {
+        Ok(context) => context,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(ExitStatus::Errors.code());
+        }
+    };
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    audit::start(&context.home, &args.join(" "));
+
+    let silenced = if cli.quiet { quiet::silence() } else { None };
+    let result = run(&context, cli.command);
+    drop(silenced);
+
+    let status = match &result {
+        Ok(status) => *status,
+        Err(e) => ExitStatus::from(e),
+    };
+    audit::finish(status.label());
+    // Warnings alone don't notify, or a scheduled doctor would notify every run
+    if matches!(status, ExitStatus::Errors | ExitStatus::Conflicts) {
+        let detail = match &result {
+            Err(e) => e.to_string(),
+            Ok(_) => format!("Exited with status {}", status.label()),
+        };
+        notify::notify_unattended(&format!("dotfiles {} failed", args.join(" ")), &detail);
+    }
+    if cli.quiet {
+        println!("{}", status.status_line(result.as_ref().err()));
+    } else if let Err(e) = &result {
+        eprintln!("Error: {}", e);
+    }
+
+    ExitCode::from(status.code())
+}
+
+/// Returns why `command` cannot run without prompting, if it can't
+fn prompt_needed(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Setup { yes: false, .. } => {
+            Some("setup asks for its settings unless --yes is given")
+        }
+        Commands::Init { yes: false, .. } => {
+            Some("init asks for the repository unless --yes is given")
+        }
+        Commands::Backup {
+            command: Some(BackupCommand::Browse),
+            ..
+        } => Some("backup browse is interactive"),
+        _ => None,
+    }
+}
+
+fn run(context: &AppContext, command: Commands) -> Result<ExitStatus> {
+    match command {
+        Commands::Init {
+            url,
+            run_setup,
+            yes,
+        } => commands::init(
+            context,
+            commands::init::InitOptions {
+                url,
+                run_setup,
+                assume_yes: yes,
+            },
+        ),
+        Commands::NewRepo {
+            path,
+            github,
+            public,
+        } => commands::new_repo::run(
+            context,
+            commands::new_repo::NewRepoOptions {
+                path: expand(path)?,
+                github,
+                public,
+            },
+        )
+        .map(ExitStatus::from),
+        Commands::Setup {
+            dry_run,
+            yes,
+            profile,
+        } => commands::setup(
+            context,
+            commands::setup::SetupOptions {
+                dry_run,
+                assume_yes: yes,
+                dotfiles_dir: None,
+                profile,
+            },
+        ),
+        Commands::Doctor {
+            json,
+            format,
+            fix,
+            no_cache,
+            command,
+        } => match command {
+            Some(DoctorCommand::Compare { file }) => {
+                commands::compare::run(context, &paths::expand(&file)?).map(ExitStatus::from)
+            }
+            None => {
+                let format = match format.as_deref() {
+                    Some(name) => commands::doctor::OutputFormat::from_name(name),
+                    None => json.then_some(commands::doctor::OutputFormat::Json),
+                };
+                commands::doctor(context, format, fix, no_cache)
+            }
+        },
+        Commands::ReportIssue { output } => {
+            commands::report_issue::run(context, expand(output)?.as_deref()).map(ExitStatus::from)
+        }
+        Commands::Adopt { path } => {
+            commands::adopt::run(context, &paths::expand(&path)?).map(ExitStatus::from)
+        }
+        Commands::ImportBare { dry_run } => {
+            commands::import_bare::run(context, dry_run).map(ExitStatus::from)
+        }
+        Commands::Unlink { dry_run } => {
+            commands::unlink::run(context, dry_run).map(ExitStatus::from)
+        }
+        Commands::Status => commands::status::run(context),
+        Commands::Lint => commands::lint::run(context),
+        Commands::Coverage => commands::coverage::run(context),
+        Commands::Drift { list } => commands::drift::run(context, list),
+        Commands::Sync { command } => match command {
+            None => commands::sync::run(context),
+            Some(SyncCommand::Watch { interval, once }) => {
+                commands::sync::watch(context, interval, once)
+            }
+            Some(SyncCommand::Agent { interval, remove }) => {
+                commands::sync::agent(context, interval, remove)
+            }
+        }
+        .map(ExitStatus::from),
+        Commands::History { run_id } => {
+            commands::history::run(context, run_id.as_deref()).map(ExitStatus::from)
+        }
+        Commands::Appconfig { command } => match command {
+            None => commands::appconfig::list(context),
+            Some(AppconfigCommand::Export { app, from }) => {
+                commands::appconfig::export(context, &app, expand(from)?.as_deref())
+            }
+            Some(AppconfigCommand::Import { app }) => commands::appconfig::import(context, &app),
+        }
+        .map(ExitStatus::from),
+        Commands::Brewfile { command } => match command {
+            BrewfileCommand::Export { output, dry_run } => {
+                commands::brewfile::export(context, expand(output)?.as_deref(), dry_run)
+            }
+            BrewfileCommand::Install { dry_run } => commands::brewfile::install(context, dry_run),
+        }
+        .map(ExitStatus::from),
+        Commands::Checklist { command } => match command {
+            None => commands::checklist::show(context),
+            Some(ChecklistCommand::Done { item }) => commands::checklist::done(context, &item),
+            Some(ChecklistCommand::Undo { item }) => commands::checklist::undo(context, &item),
+        }
+        .map(ExitStatus::from),
+        Commands::Migrate {
+            source,
+            target,
+            layout,
+            no_secret_scan,
+            redact,
+            secret_store,
+            no_backup,
+            dry_run,
+        } => commands::migrate::run(
+            context,
+            commands::migrate::MigrateOptions {
+                source: paths::expand(&source)?,
+                target: expand(target)?,
+                layout: layout.as_deref().and_then(commands::migrate::parse_layout),
+                extract_secrets: !no_secret_scan,
+                redact_secrets: redact
+                    .as_deref()
+                    .and_then(commands::migrate::parse_redaction),
+                secret_store: secret_store.as_deref().and_then(SecretStoreKind::from_name),
+                create_backup: !no_backup,
+                dry_run,
+            },
+        ),
+        Commands::Backup {
+            encrypt,
+            push,
+            allow_unencrypted,
+            command,
+        } => match command {
+            Some(BackupCommand::Browse) => commands::backup::browse(context).map(ExitStatus::from),
+            Some(BackupCommand::Migrate) => {
+                commands::backup::migrate(context).map(ExitStatus::from)
+            }
+            Some(BackupCommand::Prune { keep, dry_run, yes }) => {
+                commands::backup::prune(context, keep, dry_run, yes).map(ExitStatus::from)
+            }
+            None => commands::backup::create(context, encrypt, push, allow_unencrypted)
+                .map(ExitStatus::from),
+        },
+        Commands::Restore {
+            timestamp,
+            target,
+            from_remote,
+        } => commands::restore::run(
+            context,
+            timestamp.as_deref(),
+            expand(target)?.as_deref(),
+            from_remote,
+        )
+        .map(ExitStatus::from),
+        Commands::Config { command } => match command {
+            ConfigCommand::Show => commands::config::show(context).map(ExitStatus::from),
+        },
+        Commands::Generate { command } => match command {
+            GenerateCommand::Aliases { shell } => {
+                commands::generate::aliases(context, shell.as_deref()).map(ExitStatus::from)
+            }
+            GenerateCommand::BootstrapScript {
+                url,
+                release_repo,
+                output,
+            } => commands::generate::bootstrap_script(
+                context,
+                url.as_deref(),
+                &release_repo,
+                expand(output)?.as_deref(),
+            )
+            .map(ExitStatus::from),
+        },
+        Commands::Languages { command } => match command {
+            LanguagesCommand::Remove { language, version } => {
+                commands::languages::remove(context, &language, version.as_deref())
+                    .map(ExitStatus::from)
+            }
+        },
+        Commands::Vm { command } => match command {
+            VmCommand::Switch {
+                manager,
+                remove_old,
+            } => commands::vm::switch(context, &manager, remove_old),
+        },
+    }
+}
+
+/// Expands `~` and `$VAR` in an optional path flag, after `--home` has been applied
+fn expand(path: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    path.map(|path| paths::expand(&path)).transpose()
 }
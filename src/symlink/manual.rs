@@ -1,6 +1,10 @@
+use crate::core::audit::{self, Action};
+use crate::core::timing::{self, Timing};
+use crate::core::walk::RepoWalker;
 use crate::error::{DotfilesError, Result};
 use crate::symlink::{SymlinkReport, SymlinkStatus, Symlinker};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// Manual symlink creator (fallback when GNU Stow is not available)
 pub struct ManualSymlinker {
@@ -8,6 +12,10 @@ pub struct ManualSymlinker {
     pub dry_run: bool,
     /// Whether to force overwrite existing symlinks
     pub force: bool,
+    /// Top-level entries to leave unlinked (e.g. not tagged for this machine)
+    pub skip: Vec<String>,
+    /// Where the repo's `.config` is linked, if not into the target directory
+    pub config_home: Option<PathBuf>,
 }
 
 impl ManualSymlinker {
@@ -16,6 +24,8 @@ impl ManualSymlinker {
         Self {
             dry_run: false,
             force: false,
+            skip: Vec::new(),
+            config_home: None,
         }
     }
 
@@ -24,17 +34,37 @@ impl ManualSymlinker {
         Self {
             dry_run: true,
             force: false,
+            skip: Vec::new(),
+            config_home: None,
         }
     }
 
+    /// Leaves these top-level entries unlinked
+    pub fn skipping(mut self, names: Vec<String>) -> Self {
+        self.skip = names;
+        self
+    }
+
+    /// Links the repo's `.config` to `dir` (the configured `xdg_config_home`)
+    pub fn with_config_home(mut self, dir: PathBuf) -> Self {
+        self.config_home = Some(dir);
+        self
+    }
+
     /// Creates a symlink from source to target
-    fn create_symlink(&self, source: &Path, target: &Path) -> Result<SymlinkStatus> {
+    pub(crate) fn create_symlink(&self, source: &Path, target: &Path) -> Result<SymlinkStatus> {
         // Check if target already exists
         if target.exists() {
             if target.is_symlink() {
                 // Check if it points to the right place
                 if let Ok(link_target) = std::fs::read_link(target) {
-                    if link_target == source {
+                    // Junctions read back as `\\?\C:\...`, so Windows compares resolved paths
+                    let same_target = cfg!(windows)
+                        && matches!(
+                            (target.canonicalize(), source.canonicalize()),
+                            (Ok(a), Ok(b)) if a == b
+                        );
+                    if link_target == source || same_target {
                         return Ok(SymlinkStatus::AlreadyExists {
                             target: target.to_path_buf(),
                         });
@@ -44,7 +74,10 @@ impl ManualSymlinker {
                 // Symlink exists but points elsewhere
                 if self.force {
                     if !self.dry_run {
-                        std::fs::remove_file(target)?;
+                        remove_link(target)?;
+                        audit::record(Action::LinkRemoved {
+                            link: target.to_path_buf(),
+                        });
                     }
                 } else {
                     return Ok(SymlinkStatus::Conflict {
@@ -77,13 +110,11 @@ impl ManualSymlinker {
 
         // Create the symlink
         if !self.dry_run {
-            #[cfg(unix)]
-            std::os::unix::fs::symlink(source, target)?;
-
-            #[cfg(not(unix))]
-            return Err(DotfilesError::SymlinkFailed(
-                "Manual symlinks only supported on Unix systems".to_string(),
-            ));
+            link(source, target)?;
+            audit::record(Action::LinkCreated {
+                link: target.to_path_buf(),
+                target: source.to_path_buf(),
+            });
         }
 
         Ok(SymlinkStatus::Created {
@@ -92,24 +123,46 @@ impl ManualSymlinker {
         })
     }
 
-    /// Removes a symlink if it exists
-    fn remove_symlink(&self, target: &Path) -> Result<SymlinkStatus> {
-        if !target.exists() {
-            return Ok(SymlinkStatus::Skipped {
-                target: target.to_path_buf(),
-                reason: "Symlink does not exist".to_string(),
+    /// Removes the symlink at `target` if it points to `source`
+    ///
+    /// Links pointing anywhere else are left alone, as are regular files.
+    pub(crate) fn remove_symlink(&self, source: &Path, target: &Path) -> Result<SymlinkStatus> {
+        if !target.is_symlink() {
+            return Ok(if target.exists() {
+                SymlinkStatus::Conflict {
+                    target: target.to_path_buf(),
+                    reason: "Not a symlink, will not remove".to_string(),
+                }
+            } else {
+                SymlinkStatus::Skipped {
+                    target: target.to_path_buf(),
+                    reason: "Symlink does not exist".to_string(),
+                }
             });
         }
 
-        if !target.is_symlink() {
-            return Ok(SymlinkStatus::Conflict {
+        let link = std::fs::read_link(target)?;
+        let resolved = match target.parent() {
+            Some(parent) if link.is_relative() => parent.join(&link),
+            _ => link,
+        };
+        let points_to_source = resolved == source
+            || matches!(
+                (resolved.canonicalize(), source.canonicalize()),
+                (Ok(a), Ok(b)) if a == b
+            );
+        if !points_to_source {
+            return Ok(SymlinkStatus::Skipped {
                 target: target.to_path_buf(),
-                reason: "Not a symlink, will not remove".to_string(),
+                reason: format!("Links to {}, will not remove", resolved.display()),
             });
         }
 
         if !self.dry_run {
-            std::fs::remove_file(target)?;
+            remove_link(target)?;
+            audit::record(Action::LinkRemoved {
+                link: target.to_path_buf(),
+            });
         }
 
         Ok(SymlinkStatus::Created {
@@ -119,6 +172,88 @@ impl ManualSymlinker {
     }
 }
 
+/// Creates a symlink at `target` pointing to `source`
+#[cfg(unix)]
+fn link(source: &Path, target: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(source, target)?;
+    Ok(())
+}
+
+/// Creates a symlink at `target` pointing to `source`
+///
+/// Windows needs Developer Mode or an elevated shell for symlinks. Without
+/// either, directories are linked with a junction instead, which needs no
+/// privilege; files have no such fallback.
+#[cfg(windows)]
+fn link(source: &Path, target: &Path) -> Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    if !source.is_dir() {
+        return symlink_file(source, target).map_err(|e| {
+            if is_privilege_error(&e) {
+                DotfilesError::SymlinkFailed(format!(
+                    "Cannot link {}: turn on Developer Mode or run from an elevated shell",
+                    target.display()
+                ))
+            } else {
+                e.into()
+            }
+        });
+    }
+    match symlink_dir(source, target) {
+        Err(e) if is_privilege_error(&e) => create_junction(source, target),
+        result => Ok(result?),
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn link(_source: &Path, target: &Path) -> Result<()> {
+    Err(DotfilesError::SymlinkFailed(format!(
+        "Cannot link {}: manual symlinks are only supported on Unix and Windows",
+        target.display()
+    )))
+}
+
+/// Whether creating a symlink failed for lack of the symlink privilege
+#[cfg(windows)]
+fn is_privilege_error(error: &std::io::Error) -> bool {
+    // ERROR_PRIVILEGE_NOT_HELD
+    error.raw_os_error() == Some(1314)
+}
+
+/// Links a directory with a junction, which unprivileged users can create
+#[cfg(windows)]
+fn create_junction(source: &Path, target: &Path) -> Result<()> {
+    use crate::core::command::{self, CommandRunner};
+
+    // Junctions must point at an absolute path
+    let source = std::path::absolute(source)?;
+    CommandRunner::new("cmd")
+        .args(["/C", "mklink", "/J"])
+        .arg(target)
+        .arg(&source)
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Removes a link created by [`link`]
+///
+/// On Windows, directory symlinks and junctions are removed as directories;
+/// this never touches what they point to.
+fn remove_link(target: &Path) -> Result<()> {
+    #[cfg(windows)]
+    if std::os::windows::fs::FileTypeExt::is_symlink_dir(
+        &std::fs::symlink_metadata(target)?.file_type(),
+    ) {
+        std::fs::remove_dir(target)?;
+        return Ok(());
+    }
+    std::fs::remove_file(target)?;
+    Ok(())
+}
+
 impl Default for ManualSymlinker {
     fn default() -> Self {
         Self::new()
@@ -127,6 +262,7 @@ impl Default for ManualSymlinker {
 
 impl Symlinker for ManualSymlinker {
     fn symlink(&self, source: &Path, target: &Path) -> Result<SymlinkReport> {
+        let started = Instant::now();
         let mut report = SymlinkReport::new();
 
         if !source.exists() {
@@ -136,29 +272,38 @@ impl Symlinker for ManualSymlinker {
             )));
         }
 
+        // Case collisions and overlong paths block the whole run, before any change
+        let problems = crate::symlink::preflight(source, target, &self.skip);
+        if !problems.is_empty() {
+            for (target, reason) in problems {
+                report.add(SymlinkStatus::Conflict { target, reason });
+            }
+            report.wall_time = started.elapsed();
+            return Ok(report);
+        }
+
         // Walk through source directory
         if source.is_dir() {
-            let entries = std::fs::read_dir(source)?;
-
-            for entry in entries {
-                let entry = entry?;
-                let source_path = entry.path();
+            // Excluded and ignored entries never come back from the walker
+            for source_path in RepoWalker::new(source)
+                .max_depth(1)
+                .skip(&self.skip)
+                .entries()?
+            {
                 let file_name = source_path
                     .file_name()
                     .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid filename".to_string()))?;
 
-                // Skip excluded files
-                let file_name_str = file_name.to_str().ok_or_else(|| {
-                    DotfilesError::SymlinkFailed("Invalid UTF-8 in filename".to_string())
-                })?;
-                if crate::symlink::EXCLUSIONS.contains(&file_name_str) {
-                    continue;
-                }
-
-                let target_path = target.join(file_name);
+                let target_path =
+                    crate::symlink::entry_target(target, file_name, self.config_home.as_deref());
 
-                let status = self.create_symlink(&source_path, &target_path)?;
-                report.add(status);
+                let (status, elapsed) =
+                    timing::measure(|| self.create_symlink(&source_path, &target_path));
+                report.add(status?);
+                report.timings.push(
+                    Timing::new(file_name.to_string_lossy(), elapsed)
+                        .with_size(timing::disk_size(&source_path)),
+                );
             }
         } else {
             // Source is a file, create a single symlink
@@ -167,16 +312,21 @@ impl Symlinker for ManualSymlinker {
                 .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid filename".to_string()))?;
             let target_path = target.join(file_name);
 
-            let status = self.create_symlink(source, &target_path)?;
-            report.add(status);
+            let (status, elapsed) = timing::measure(|| self.create_symlink(source, &target_path));
+            report.add(status?);
+            report.timings.push(
+                Timing::new(file_name.to_string_lossy(), elapsed)
+                    .with_size(timing::disk_size(source)),
+            );
         }
 
+        report.wall_time = started.elapsed();
         Ok(report)
     }
 
     fn is_available(&self) -> bool {
-        // Manual symlinking is always available on Unix systems
-        cfg!(unix)
+        // Always available on Unix; Windows falls back to junctions for directories
+        cfg!(any(unix, windows))
     }
 
     fn name(&self) -> &str {
@@ -195,26 +345,18 @@ impl Symlinker for ManualSymlinker {
 
         // Walk through source directory and remove corresponding symlinks
         if source.is_dir() {
-            let entries = std::fs::read_dir(source)?;
-
-            for entry in entries {
-                let entry = entry?;
-                let source_path = entry.path();
+            for source_path in RepoWalker::new(source)
+                .max_depth(1)
+                .skip(&self.skip)
+                .entries()?
+            {
                 let file_name = source_path
                     .file_name()
                     .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid filename".to_string()))?;
+                let target_path =
+                    crate::symlink::entry_target(target, file_name, self.config_home.as_deref());
 
-                // Skip excluded files
-                let file_name_str = file_name.to_str().ok_or_else(|| {
-                    DotfilesError::SymlinkFailed("Invalid UTF-8 in filename".to_string())
-                })?;
-                if crate::symlink::EXCLUSIONS.contains(&file_name_str) {
-                    continue;
-                }
-
-                let target_path = target.join(file_name);
-
-                let status = self.remove_symlink(&target_path)?;
+                let status = self.remove_symlink(&source_path, &target_path)?;
                 report.add(status);
             }
         } else {
@@ -223,7 +365,7 @@ impl Symlinker for ManualSymlinker {
                 .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid filename".to_string()))?;
             let target_path = target.join(file_name);
 
-            let status = self.remove_symlink(&target_path)?;
+            let status = self.remove_symlink(source, &target_path)?;
             report.add(status);
         }
 
@@ -260,11 +402,11 @@ mod tests {
     #[test]
     fn test_manual_symlinker_is_available() {
         let manual = ManualSymlinker::new();
-        // On Unix systems, manual symlinking should always be available
-        #[cfg(unix)]
+        // On Unix and Windows, manual symlinking should always be available
+        #[cfg(any(unix, windows))]
         assert!(manual.is_available());
 
-        #[cfg(not(unix))]
+        #[cfg(not(any(unix, windows)))]
         assert!(!manual.is_available());
     }
 
@@ -294,6 +436,53 @@ mod tests {
         assert_eq!(link_target, source_file);
     }
 
+    #[test]
+    #[cfg(windows)]
+    fn test_create_symlink_windows_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let target_dir = temp_dir.path().join("target");
+        fs::create_dir(&source_dir).unwrap();
+        fs::write(source_dir.join("init.lua"), "-- config").unwrap();
+
+        // A symlink with Developer Mode, a junction without it
+        let manual = ManualSymlinker::new();
+        let status = manual.create_symlink(&source_dir, &target_dir).unwrap();
+        assert!(matches!(status, SymlinkStatus::Created { .. }));
+        assert!(target_dir.is_symlink());
+        assert_eq!(
+            fs::read_to_string(target_dir.join("init.lua")).unwrap(),
+            "-- config"
+        );
+
+        let status = manual.create_symlink(&source_dir, &target_dir).unwrap();
+        assert!(matches!(status, SymlinkStatus::AlreadyExists { .. }));
+
+        let status = manual.remove_symlink(&source_dir, &target_dir).unwrap();
+        assert!(matches!(status, SymlinkStatus::Created { .. }));
+        assert!(!target_dir.exists());
+        assert!(source_dir.join("init.lua").exists());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn test_create_symlink_windows_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_file = temp_dir.path().join("source.txt");
+        let target_file = temp_dir.path().join("target.txt");
+        fs::write(&source_file, "test content").unwrap();
+
+        // File symlinks need Developer Mode or elevation, with no fallback
+        let manual = ManualSymlinker::new();
+        match manual.create_symlink(&source_file, &target_file) {
+            Ok(status) => {
+                assert!(matches!(status, SymlinkStatus::Created { .. }));
+                assert_eq!(fs::read_link(&target_file).unwrap(), source_file);
+            }
+            Err(e) => assert!(e.to_string().contains("Developer Mode")),
+        }
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_create_symlink_already_exists() {
@@ -382,11 +571,23 @@ mod tests {
         fs::write(&source_file, "test content").unwrap();
         std::os::unix::fs::symlink(&source_file, &target_file).unwrap();
 
+        // A link to something else is not ours to remove
         let manual = ManualSymlinker::new();
-        let status = manual.remove_symlink(&target_file).unwrap();
+        let other_file = temp_dir.path().join("other.txt");
+        let status = manual.remove_symlink(&other_file, &target_file).unwrap();
+        assert!(matches!(status, SymlinkStatus::Skipped { .. }));
+        assert!(target_file.is_symlink());
+
+        let status = manual.remove_symlink(&source_file, &target_file).unwrap();
 
         assert!(matches!(status, SymlinkStatus::Created { .. }));
         assert!(!target_file.exists());
+
+        // A dangling link into the repo is removed too
+        std::os::unix::fs::symlink(&other_file, &target_file).unwrap();
+        let status = manual.remove_symlink(&other_file, &target_file).unwrap();
+        assert!(matches!(status, SymlinkStatus::Created { .. }));
+        assert!(!target_file.is_symlink());
     }
 
     #[test]
@@ -466,4 +667,67 @@ mod tests {
         assert!(!target_dir.join("file1.txt").exists());
         assert!(target_dir.join(".git").exists());
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_config_into_config_home() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("source");
+        let target_dir = temp_dir.path().join("target");
+        let config_home = temp_dir.path().join("xdg");
+        fs::create_dir_all(source_dir.join(".config/nvim")).unwrap();
+        fs::write(source_dir.join(".zshrc"), "content").unwrap();
+        fs::create_dir(&target_dir).unwrap();
+
+        let manual = ManualSymlinker::new().with_config_home(config_home.clone());
+        let report = manual.symlink(&source_dir, &target_dir).unwrap();
+        assert_eq!(report.total(), 2);
+        assert_eq!(
+            fs::read_link(&config_home).unwrap(),
+            source_dir.join(".config")
+        );
+        assert!(!target_dir.join(".config").exists());
+        assert!(crate::symlink::validate_symlinks(
+            &source_dir,
+            &target_dir,
+            Some(&config_home),
+            &[]
+        )
+        .unwrap()
+        .is_empty());
+
+        manual.remove(&source_dir, &target_dir).unwrap();
+        assert!(!config_home.is_symlink());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_unusual_names() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let source_dir = temp_dir.path().join("My Dotfiles").join("Zoë");
+        let target_dir = temp_dir.path().join("home dir").join("Ünïcode");
+        let latin1 = std::ffi::OsStr::from_bytes(b"caf\xe9");
+
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::create_dir_all(&target_dir).unwrap();
+        fs::write(source_dir.join("notes for work.md"), "content").unwrap();
+        fs::write(source_dir.join(latin1), "content").unwrap();
+
+        let report = ManualSymlinker::new()
+            .symlink(&source_dir, &target_dir)
+            .unwrap();
+
+        assert_eq!(report.total(), 2);
+        assert!(report.is_success());
+        assert_eq!(
+            fs::read_link(target_dir.join("notes for work.md")).unwrap(),
+            source_dir.join("notes for work.md")
+        );
+        assert_eq!(
+            fs::read_link(target_dir.join(latin1)).unwrap(),
+            source_dir.join(latin1)
+        );
+    }
 }
@@ -1,14 +1,49 @@
 pub mod manual;
 pub mod stow;
 
+use crate::core::command::{self, CommandRunner};
+use crate::core::timing::{self, Timing};
+use crate::core::walk::RepoWalker;
 use crate::error::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Files and directories to exclude from symlinking
 ///
 /// These are commonly non-portable or repository-specific files that
 /// should not be symlinked to the home directory.
-pub const EXCLUSIONS: &[&str] = &[".git", ".DS_Store", ".claude", "README.md", "LICENSE"];
+pub const EXCLUSIONS: &[&str] = &[
+    ".git",
+    ".gitmodules",
+    ".DS_Store",
+    ".claude",
+    "README.md",
+    "LICENSE",
+    // App settings exported with `dotfiles appconfig` (see install::appconfig)
+    "appconfig",
+    // Package list for `brew bundle` (see install::brewfile)
+    "Brewfile",
+    // Per-repo ignore rules (see core::walk)
+    ".dotfilesignore",
+    ".stow-local-ignore",
+    // Run by setup and migration, never linked (see core::hooks)
+    "hooks",
+    // Linked to ~/.config/karabiner instead (see install::karabiner)
+    "karabiner",
+    // Linked into ~/.kube/configs instead (see install::kubernetes)
+    "kube",
+    // Copied, not linked, by scaffolding steps (see install::aws)
+    "templates",
+];
+
+/// Longest linked path allowed, in bytes (`PATH_MAX` on macOS)
+pub const MAX_PATH_LEN: usize = 1024;
+
+/// Longest single path component allowed, in bytes (`NAME_MAX` on most filesystems)
+pub const MAX_NAME_LEN: usize = 255;
 
 /// Special directories that need individual file symlinks instead of directory symlinks
 ///
@@ -54,13 +89,57 @@ impl SymlinkStatus {
     }
 }
 
+/// Problem `validate_symlinks` found with one entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkIssue {
+    /// The repo directory itself is missing
+    SourceMissing,
+    /// Nothing exists at the link location
+    Missing,
+    /// A link exists but its target is gone
+    Dangling,
+    /// A regular file or directory is in the way
+    NotASymlink,
+    /// The link points somewhere other than the repo entry
+    PointsElsewhere { actual: PathBuf, expected: PathBuf },
+    /// The link couldn't be read
+    Unreadable,
+}
+
+impl LinkIssue {
+    /// Returns true when no link is in place yet, dangling ones included
+    pub fn is_unlinked(&self) -> bool {
+        matches!(self, LinkIssue::Missing | LinkIssue::Dangling)
+    }
+}
+
+impl std::fmt::Display for LinkIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkIssue::SourceMissing => write!(f, "Source directory does not exist"),
+            LinkIssue::Missing => write!(f, "Symlink does not exist"),
+            LinkIssue::Dangling => write!(f, "Symlink is broken"),
+            LinkIssue::NotASymlink => write!(f, "Not a symlink"),
+            LinkIssue::PointsElsewhere { actual, expected } => {
+                write!(f, "Points to {:?} instead of {:?}", actual, expected)
+            }
+            LinkIssue::Unreadable => write!(f, "Failed to read symlink"),
+        }
+    }
+}
+
 /// Report summarizing symlink operations
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SymlinkReport {
     pub created: Vec<PathBuf>,
     pub already_exists: Vec<PathBuf>,
     pub conflicts: Vec<(PathBuf, String)>,
     pub skipped: Vec<(PathBuf, String)>,
+    /// Time spent (and bytes covered) per linked item
+    pub timings: Vec<Timing>,
+    /// Total time spent producing this report
+    #[serde(rename = "wall_time_ms", serialize_with = "timing::serialize_millis")]
+    pub wall_time: Duration,
 }
 
 impl SymlinkReport {
@@ -97,16 +176,37 @@ impl SymlinkReport {
         self.created.len() + self.already_exists.len() + self.conflicts.len() + self.skipped.len()
     }
 
+    /// Appends another report's results, timings, and wall time
+    pub fn merge(&mut self, other: SymlinkReport) {
+        self.created.extend(other.created);
+        self.already_exists.extend(other.already_exists);
+        self.conflicts.extend(other.conflicts);
+        self.skipped.extend(other.skipped);
+        self.timings.extend(other.timings);
+        self.wall_time += other.wall_time;
+    }
+
+    /// Returns the `n` slowest linked items, slowest first
+    pub fn slowest(&self, n: usize) -> Vec<&Timing> {
+        timing::slowest(&self.timings, n)
+    }
+
     /// Returns a summary string
     pub fn summary(&self) -> String {
         format!(
-            "Created: {}, Already exists: {}, Conflicts: {}, Skipped: {}",
+            "Created: {}, Already exists: {}, Conflicts: {}, Skipped: {} (in {})",
             self.created.len(),
             self.already_exists.len(),
             self.conflicts.len(),
-            self.skipped.len()
+            self.skipped.len(),
+            timing::format_duration(self.wall_time)
         )
     }
+
+    /// Serializes the report as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
 }
 
 /// Common interface for symlink creation methods
@@ -142,9 +242,8 @@ pub fn detect_conflicts(source: &Path, target: &Path) -> Vec<(PathBuf, String)>
     }
 
     // Walk through source directory and check for conflicts
-    if let Ok(entries) = std::fs::read_dir(source) {
-        for entry in entries.flatten() {
-            let source_path = entry.path();
+    if let Ok(entries) = RepoWalker::new(source).max_depth(1).entries() {
+        for source_path in entries {
             let file_name = source_path.file_name().unwrap();
             let target_path = target.join(file_name);
 
@@ -173,36 +272,198 @@ pub fn detect_conflicts(source: &Path, target: &Path) -> Vec<(PathBuf, String)>
     conflicts
 }
 
+/// Finds problems that would otherwise surface halfway through linking
+///
+/// Reports repo entries whose paths differ only by case (`Readme.md` and
+/// `README.md`) when `target` is on a case-insensitive filesystem, such as
+/// default APFS, and entries whose path under `target` would be longer than
+/// [`MAX_PATH_LEN`] or have a component longer than [`MAX_NAME_LEN`].
+/// Tracked files are included, since a checkout on a case-insensitive volume
+/// only keeps one of two colliding names. Symlinkers report these as
+/// conflicts and link nothing.
+pub fn preflight(source: &Path, target: &Path, skip: &[String]) -> Vec<(PathBuf, String)> {
+    if !source.is_dir() {
+        let name = source.file_name().map(PathBuf::from).unwrap_or_default();
+        return check_paths(&[name], target, false);
+    }
+
+    let mut rels: Vec<PathBuf> = RepoWalker::new(source)
+        .skip(skip)
+        .entries()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|entry| Some(entry.strip_prefix(source).ok()?.to_path_buf()))
+        .collect();
+    rels.extend(tracked_files(source).into_iter().filter(|rel| {
+        rel.components().next().is_some_and(|top| {
+            let top = top.as_os_str().to_string_lossy();
+            !EXCLUSIONS.contains(&top.as_ref()) && !skip.iter().any(|name| *name == top)
+        })
+    }));
+    rels.sort();
+    rels.dedup();
+
+    check_paths(&rels, target, is_case_insensitive(target))
+}
+
+/// Paths tracked by git under `dir`, if it is the root of a repo
+///
+/// Files inside submodules are listed like any other file.
+fn tracked_files(dir: &Path) -> Vec<PathBuf> {
+    if !dir.join(".git").exists() {
+        return Vec::new();
+    }
+    CommandRunner::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["ls-files", "-z", "--recurse-submodules"])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()
+        .filter(|output| output.success())
+        .map(|output| {
+            output
+                .stdout
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Checks repo-relative paths against case folding and length limits under `target`
+fn check_paths(rels: &[PathBuf], target: &Path, case_insensitive: bool) -> Vec<(PathBuf, String)> {
+    let mut problems = Vec::new();
+    let mut seen: BTreeMap<String, &PathBuf> = BTreeMap::new();
+
+    for rel in rels {
+        let linked = target.join(rel);
+
+        if case_insensitive {
+            let folded = rel.to_string_lossy().to_lowercase();
+            match seen.get(&folded) {
+                Some(first) => problems.push((
+                    linked.clone(),
+                    format!(
+                        "{} and {} differ only by case and collide on this filesystem",
+                        first.display(),
+                        rel.display()
+                    ),
+                )),
+                None => {
+                    seen.insert(folded, rel);
+                }
+            }
+        }
+
+        let path_len = linked.as_os_str().len();
+        if path_len > MAX_PATH_LEN {
+            problems.push((
+                linked,
+                format!(
+                    "Path is {} bytes long (limit {}); some tools fail on it",
+                    path_len, MAX_PATH_LEN
+                ),
+            ));
+        } else if let Some(name) = rel
+            .components()
+            .map(|c| c.as_os_str())
+            .find(|name| name.len() > MAX_NAME_LEN)
+        {
+            problems.push((
+                linked,
+                format!(
+                    "Name {}... is {} bytes long (limit {})",
+                    name.to_string_lossy().chars().take(32).collect::<String>(),
+                    name.len(),
+                    MAX_NAME_LEN
+                ),
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Returns true if `dir` is on a case-insensitive filesystem
+///
+/// Checks whether an existing entry can be found under its name with the
+/// case flipped. With nothing to compare, assumes the platform default
+/// (case-insensitive on macOS).
+pub fn is_case_insensitive(dir: &Path) -> bool {
+    let flip = |c: char| {
+        if c.is_ascii_lowercase() {
+            c.to_ascii_uppercase()
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let probe = std::fs::read_dir(dir).ok().and_then(|entries| {
+        entries.flatten().find_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            name.chars()
+                .any(|c| c.is_ascii_alphabetic())
+                .then(|| name.chars().map(flip).collect::<String>())
+        })
+    });
+    match probe {
+        Some(flipped) => dir.join(flipped).symlink_metadata().is_ok(),
+        None => cfg!(target_os = "macos"),
+    }
+}
+
+/// Where a top-level repo entry is linked
+///
+/// The repo's `.config` goes to `config_home` (the configured
+/// `xdg_config_home`) when one is given; every other entry goes into `target`.
+pub fn entry_target(target: &Path, name: &OsStr, config_home: Option<&Path>) -> PathBuf {
+    match config_home {
+        Some(dir) if name == ".config" => dir.to_path_buf(),
+        _ => target.join(name),
+    }
+}
+
 /// Validates that symlinks point to the correct locations
-pub fn validate_symlinks(source: &Path, target: &Path) -> Result<Vec<(PathBuf, String)>> {
+pub fn validate_symlinks(
+    source: &Path,
+    target: &Path,
+    config_home: Option<&Path>,
+    skipped: &[String],
+) -> Result<Vec<(PathBuf, LinkIssue)>> {
     let mut issues = Vec::new();
 
     if !source.exists() {
-        return Ok(vec![(
-            source.to_path_buf(),
-            "Source directory does not exist".to_string(),
-        )]);
+        return Ok(vec![(source.to_path_buf(), LinkIssue::SourceMissing)]);
     }
 
-    if let Ok(entries) = std::fs::read_dir(source) {
-        for entry in entries.flatten() {
-            let source_path = entry.path();
+    if let Ok(entries) = RepoWalker::new(source).max_depth(1).skip(skipped).entries() {
+        for source_path in entries {
             let file_name = source_path.file_name().unwrap();
-            let target_path = target.join(file_name);
+            let target_path = entry_target(target, file_name, config_home);
 
             if !target_path.exists() {
-                issues.push((target_path.clone(), "Symlink does not exist".to_string()));
+                let issue = if target_path.is_symlink() {
+                    LinkIssue::Dangling
+                } else {
+                    LinkIssue::Missing
+                };
+                issues.push((target_path.clone(), issue));
             } else if !target_path.is_symlink() {
-                issues.push((target_path.clone(), "Not a symlink".to_string()));
+                issues.push((target_path.clone(), LinkIssue::NotASymlink));
             } else if let Ok(link_target) = std::fs::read_link(&target_path) {
                 if link_target != source_path {
                     issues.push((
                         target_path.clone(),
-                        format!("Points to {:?} instead of {:?}", link_target, source_path),
+                        LinkIssue::PointsElsewhere {
+                            actual: link_target,
+                            expected: source_path,
+                        },
                     ));
                 }
             } else {
-                issues.push((target_path.clone(), "Failed to read symlink".to_string()));
+                issues.push((target_path.clone(), LinkIssue::Unreadable));
             }
         }
     }
@@ -238,21 +499,93 @@ pub fn symlink_individual_files(
         // Symlink individual files from the special directory
         let special_report = symlinker.symlink(&source_special, &target_special)?;
 
-        // Merge reports
-        for path in special_report.created {
-            report.created.push(path);
-        }
-        for path in special_report.already_exists {
-            report.already_exists.push(path);
-        }
-        for (path, reason) in special_report.conflicts {
-            report.conflicts.push((path, reason));
+        report.merge(special_report);
+    }
+
+    Ok(report)
+}
+
+/// Removes the links `symlink_individual_files` made
+pub fn remove_individual_files(
+    symlinker: &dyn Symlinker,
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+) -> Result<SymlinkReport> {
+    let mut report = SymlinkReport::new();
+
+    for special_dir in INDIVIDUAL_FILE_SYMLINK_DIRS {
+        let source_special = dotfiles_dir.join(special_dir);
+        let target_special = home_dir.join(special_dir);
+        if source_special.exists() && target_special.is_dir() {
+            report.merge(symlinker.remove(&source_special, &target_special)?);
         }
-        for (path, reason) in special_report.skipped {
-            report.skipped.push((path, reason));
+    }
+
+    Ok(report)
+}
+
+/// Resolves a `[targets]` destination, expanding a leading `~` to `home_dir`
+pub fn expand_target(target: &str, home_dir: &Path) -> PathBuf {
+    match target.strip_prefix('~') {
+        Some("") => home_dir.to_path_buf(),
+        Some(rest) if rest.starts_with('/') => home_dir.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(target),
+    }
+}
+
+/// Links repo files to the explicit destinations in the `[targets]` config table
+///
+/// Keys are paths relative to `dotfiles_dir`. Always uses manual symlinks,
+/// since stow can only link into a single target tree.
+pub fn link_targets(
+    symlinker: &manual::ManualSymlinker,
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    targets: &BTreeMap<String, String>,
+) -> Result<SymlinkReport> {
+    let started = Instant::now();
+    let mut report = SymlinkReport::new();
+
+    for (source, target) in targets {
+        let source_path = dotfiles_dir.join(source);
+        let target_path = expand_target(target, home_dir);
+
+        if !source_path.exists() {
+            report.add(SymlinkStatus::Skipped {
+                target: target_path,
+                reason: format!("{} not found in dotfiles", source),
+            });
+            continue;
         }
+
+        let (status, elapsed) =
+            timing::measure(|| symlinker.create_symlink(&source_path, &target_path));
+        report.add(status?);
+        report
+            .timings
+            .push(Timing::new(source, elapsed).with_size(timing::disk_size(&source_path)));
     }
 
+    report.wall_time = started.elapsed();
+    Ok(report)
+}
+
+/// Removes the links `link_targets` made, leaving destinations that point elsewhere
+pub fn unlink_targets(
+    symlinker: &manual::ManualSymlinker,
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    targets: &BTreeMap<String, String>,
+) -> Result<SymlinkReport> {
+    let started = Instant::now();
+    let mut report = SymlinkReport::new();
+
+    for (source, target) in targets {
+        let target_path = expand_target(target, home_dir);
+        report.add(symlinker.remove_symlink(&dotfiles_dir.join(source), &target_path)?);
+    }
+
+    report.wall_time = started.elapsed();
     Ok(report)
 }
 
@@ -356,9 +689,109 @@ mod tests {
 
     #[test]
     fn test_validate_symlinks_nonexistent_source() {
-        let issues =
-            validate_symlinks(Path::new("/nonexistent/source"), Path::new("/target")).unwrap();
-        assert_eq!(issues.len(), 1);
-        assert!(issues[0].1.contains("does not exist"));
+        let issues = validate_symlinks(
+            Path::new("/nonexistent/source"),
+            Path::new("/target"),
+            None,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            issues,
+            vec![(
+                PathBuf::from("/nonexistent/source"),
+                LinkIssue::SourceMissing
+            )]
+        );
+    }
+
+    #[test]
+    fn test_check_paths() {
+        let rels: Vec<PathBuf> = [
+            ".ZSHRC",
+            ".config/Nvim/init.lua",
+            ".config/nvim/init.lua",
+            ".zshrc",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let target = Path::new("/home/me");
+
+        assert!(check_paths(&rels, target, false).is_empty());
+
+        let problems = check_paths(&rels, target, true);
+        let reasons: Vec<&str> = problems.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ".config/Nvim/init.lua and .config/nvim/init.lua differ only by case and collide on this filesystem",
+                ".ZSHRC and .zshrc differ only by case and collide on this filesystem",
+            ]
+        );
+        assert_eq!(problems[1].0, target.join(".zshrc"));
+
+        let long = vec![
+            PathBuf::from("a".repeat(300)),
+            PathBuf::from("b/".repeat(600)),
+        ];
+        let problems = check_paths(&long, target, false);
+        assert!(problems[0].1.contains("is 300 bytes long (limit 255)"));
+        assert!(problems[1].1.contains("bytes long (limit 1024)"));
+    }
+
+    #[test]
+    fn test_expand_target() {
+        let home = Path::new("/home/me");
+        assert_eq!(expand_target("~", home), PathBuf::from("/home/me"));
+        assert_eq!(
+            expand_target("~/Library/Application Support/Code", home),
+            PathBuf::from("/home/me/Library/Application Support/Code")
+        );
+        assert_eq!(
+            expand_target("/etc/hosts", home),
+            PathBuf::from("/etc/hosts")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_link_targets() {
+        use tempfile::TempDir;
+
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        std::fs::create_dir_all(dotfiles.join("vscode")).unwrap();
+        std::fs::write(dotfiles.join("vscode/settings.json"), "{}").unwrap();
+
+        let targets = BTreeMap::from([
+            (
+                "vscode/settings.json".to_string(),
+                "~/Library/Application Support/Code/User/settings.json".to_string(),
+            ),
+            (
+                "karabiner.json".to_string(),
+                "~/.config/karabiner/karabiner.json".to_string(),
+            ),
+        ]);
+        let symlinker = manual::ManualSymlinker::new();
+        let report = link_targets(&symlinker, &dotfiles, &home, &targets).unwrap();
+
+        let linked = home.join("Library/Application Support/Code/User/settings.json");
+        assert_eq!(report.created, vec![linked.clone()]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(
+            std::fs::read_link(&linked).unwrap(),
+            dotfiles.join("vscode/settings.json")
+        );
+
+        // Second run finds the link in place
+        let report = link_targets(&symlinker, &dotfiles, &home, &targets).unwrap();
+        assert_eq!(report.already_exists.len(), 1);
+
+        let report = unlink_targets(&symlinker, &dotfiles, &home, &targets).unwrap();
+        assert_eq!(report.created, vec![linked.clone()]);
+        assert!(!linked.is_symlink());
     }
 }
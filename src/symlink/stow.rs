@@ -1,7 +1,11 @@
+use crate::core::audit::{self, Action};
+use crate::core::command::{self, CommandOutput, CommandRunner};
+use crate::core::timing::{self, Timing};
+use crate::core::walk::{RepoWalker, ALWAYS_SKIPPED};
 use crate::error::{DotfilesError, Result};
 use crate::symlink::{SymlinkReport, SymlinkStatus, Symlinker};
-use std::path::Path;
-use std::process::Command;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 /// GNU Stow symlink manager
 pub struct StowSymlinker {
@@ -9,6 +13,10 @@ pub struct StowSymlinker {
     pub dry_run: bool,
     /// Whether to show verbose output
     pub verbose: bool,
+    /// Top-level entries to leave unlinked (e.g. not tagged for this machine)
+    pub skip: Vec<String>,
+    /// Where the repo's `.config` is stowed, if not into the target directory
+    pub config_home: Option<PathBuf>,
 }
 
 impl StowSymlinker {
@@ -17,6 +25,8 @@ impl StowSymlinker {
         Self {
             dry_run: false,
             verbose: false,
+            skip: Vec::new(),
+            config_home: None,
         }
     }
 
@@ -25,23 +35,99 @@ impl StowSymlinker {
         Self {
             dry_run: true,
             verbose: false,
+            skip: Vec::new(),
+            config_home: None,
         }
     }
 
+    /// Leaves these top-level entries unlinked
+    pub fn skipping(mut self, names: Vec<String>) -> Self {
+        self.skip = names;
+        self
+    }
+
+    /// Stows the repo's `.config` into `dir` (the configured `xdg_config_home`)
+    pub fn with_config_home(mut self, dir: PathBuf) -> Self {
+        self.config_home = Some(dir);
+        self
+    }
+
+    /// `config_home`, when the repo has a `.config` that belongs somewhere other than `target`
+    fn separate_config_home(&self, source: &Path, target: &Path) -> Option<&Path> {
+        self.config_home.as_deref().filter(|dir| {
+            *dir != target.join(".config")
+                && source.join(".config").is_dir()
+                && !self.skip.iter().any(|name| name == ".config")
+        })
+    }
+
+    /// Stows (or with `delete`, unstows) the repo's `.config` as its own package into `dir`
+    fn stow_config_home(&self, source: &Path, dir: &Path, delete: bool) -> Result<SymlinkReport> {
+        if !delete && !self.dry_run {
+            std::fs::create_dir_all(dir)?;
+        }
+        let package = OsStr::new(".config");
+        let ignored: Vec<String> = ALWAYS_SKIPPED
+            .iter()
+            .map(|name| format!("(^|/){}$", regex::escape(name)))
+            .collect();
+        let args = self.stow_args(source, dir, package, &ignored, delete);
+        let output = self.run_stow(&args)?;
+        Ok(self.parse_stow_output(&source.join(package), dir, &output))
+    }
+
     /// Gets the path to the stow executable
     fn stow_path(&self) -> Option<std::path::PathBuf> {
         crate::detect::tools::get_tool_path("stow").map(std::path::PathBuf::from)
     }
 
+    /// Builds stow arguments; paths go through untouched, whatever their encoding
+    fn stow_args<'a>(
+        &self,
+        stow_dir: &'a Path,
+        target: &'a Path,
+        package: &'a OsStr,
+        ignored: &'a [String],
+        delete: bool,
+    ) -> Vec<&'a OsStr> {
+        let mut args: Vec<&OsStr> = vec![
+            "-d".as_ref(),
+            stow_dir.as_os_str(),
+            "-t".as_ref(),
+            target.as_os_str(),
+        ];
+
+        if delete {
+            args.push("-D".as_ref()); // Delete/unstow
+        } else {
+            for pattern in ignored {
+                args.push("--ignore".as_ref());
+                args.push(pattern.as_ref());
+            }
+        }
+
+        if self.dry_run {
+            args.push("-n".as_ref()); // no-op/dry-run
+        }
+
+        if self.verbose {
+            args.push("-v".as_ref()); // verbose
+        }
+
+        args.push(package);
+        args
+    }
+
     /// Runs a stow command with the given arguments
-    fn run_stow(&self, args: &[&str]) -> Result<std::process::Output> {
+    fn run_stow(&self, args: &[&OsStr]) -> Result<CommandOutput> {
         let stow = self
             .stow_path()
             .ok_or_else(|| DotfilesError::DependencyMissing("GNU Stow".to_string()))?;
 
-        let output = Command::new(stow).args(args).output()?;
-
-        Ok(output)
+        CommandRunner::new(stow)
+            .args(args)
+            .timeout(command::QUICK_TIMEOUT)
+            .output()
     }
 
     /// Parses stow output to determine what happened
@@ -49,12 +135,12 @@ impl StowSymlinker {
         &self,
         source: &Path,
         target: &Path,
-        output: &std::process::Output,
+        output: &CommandOutput,
     ) -> SymlinkReport {
         let mut report = SymlinkReport::new();
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.success() {
+            let stderr = &output.stderr;
 
             // Stow reports conflicts in stderr
             if stderr.contains("existing target") || stderr.contains("conflict") {
@@ -89,6 +175,24 @@ impl StowSymlinker {
     }
 }
 
+/// Stow `--ignore` patterns for the built-in [`EXCLUSIONS`](crate::symlink::EXCLUSIONS)
+///
+/// Stow matches each pattern against the end of the path below the package,
+/// so a bare name would also hide e.g. `.config/git/hooks`. Exclusions are
+/// anchored to the package root; only [`ALWAYS_SKIPPED`] names match at any depth.
+fn exclusion_patterns() -> Vec<String> {
+    crate::symlink::EXCLUSIONS
+        .iter()
+        .map(|name| {
+            if ALWAYS_SKIPPED.contains(name) {
+                format!("(^|/){}$", regex::escape(name))
+            } else {
+                format!("^{}$", regex::escape(name))
+            }
+        })
+        .collect()
+}
+
 impl Default for StowSymlinker {
     fn default() -> Self {
         Self::new()
@@ -104,47 +208,63 @@ impl Symlinker for StowSymlinker {
             )));
         }
 
+        // Case collisions and overlong paths block the whole run, before stow starts
+        let problems = crate::symlink::preflight(source, target, &self.skip);
+        if !problems.is_empty() {
+            let mut report = SymlinkReport::new();
+            for (target, reason) in problems {
+                report.add(SymlinkStatus::Conflict { target, reason });
+            }
+            return Ok(report);
+        }
+
         // Get the package name (last component of source path)
         let package = source
             .file_name()
-            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid source path".to_string()))?
-            .to_str()
-            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid UTF-8 in path".to_string()))?;
+            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid source path".to_string()))?;
 
         // Get the stow directory (parent of source)
         let stow_dir = source.parent().ok_or_else(|| {
             DotfilesError::SymlinkFailed("Source has no parent directory".to_string())
         })?;
 
-        // Build stow command arguments
-        let mut args = vec![
-            "-d",
-            stow_dir.to_str().unwrap(),
-            "-t",
-            target.to_str().unwrap(),
-        ];
-
-        // Add exclusion patterns
-        for pattern in crate::symlink::EXCLUSIONS {
-            args.push("--ignore");
-            args.push(pattern);
-        }
-
-        if self.dry_run {
-            args.push("-n"); // no-op/dry-run
+        // Entries skipped by .gitignore/.dotfilesignore or tags, escaped for stow's regexes
+        let mut ignored = exclusion_patterns();
+        ignored.extend(
+            RepoWalker::new(source)
+                .skip(&self.skip)
+                .ignored_children()?
+                .iter()
+                .filter(|name| !crate::symlink::EXCLUSIONS.contains(&name.as_str()))
+                .map(|name| format!("^{}$", regex::escape(name))),
+        );
+
+        let config_home = self.separate_config_home(source, target);
+        if config_home.is_some() {
+            ignored.push(format!("^{}$", regex::escape(".config")));
         }
 
-        if self.verbose {
-            args.push("-v"); // verbose
-        }
-
-        args.push(package);
+        let args = self.stow_args(stow_dir, target, package, &ignored, false);
 
         // Run stow command
-        let output = self.run_stow(&args)?;
+        let (output, elapsed) = timing::measure(|| self.run_stow(&args));
 
         // Parse output and return report
-        Ok(self.parse_stow_output(source, target, &output))
+        let mut report = self.parse_stow_output(source, target, &output?);
+        if let Some(dir) = config_home {
+            report.merge(self.stow_config_home(source, dir, false)?);
+        }
+        if !self.dry_run && report.is_success() {
+            audit::record(Action::Stowed {
+                package: source.to_path_buf(),
+                target: target.to_path_buf(),
+            });
+        }
+        report.timings.push(
+            Timing::new(package.to_string_lossy(), elapsed).with_size(timing::disk_size(source)),
+        );
+        report.wall_time = elapsed;
+        Ok(report)
     }
 
     fn is_available(&self) -> bool {
@@ -165,35 +285,19 @@ impl Symlinker for StowSymlinker {
 
         let package = source
             .file_name()
-            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid source path".to_string()))?
-            .to_str()
-            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid UTF-8 in path".to_string()))?;
+            .ok_or_else(|| DotfilesError::SymlinkFailed("Invalid source path".to_string()))?;
 
         let stow_dir = source.parent().ok_or_else(|| {
             DotfilesError::SymlinkFailed("Source has no parent directory".to_string())
         })?;
 
-        // Build stow command with -D (delete/unstow)
-        let mut args = vec![
-            "-d",
-            stow_dir.to_str().unwrap(),
-            "-t",
-            target.to_str().unwrap(),
-            "-D", // Delete/unstow
-        ];
-
-        if self.dry_run {
-            args.push("-n");
-        }
-
-        if self.verbose {
-            args.push("-v");
-        }
-
-        args.push(package);
-
+        let args = self.stow_args(stow_dir, target, package, &[], true);
         let output = self.run_stow(&args)?;
-        Ok(self.parse_stow_output(source, target, &output))
+        let mut report = self.parse_stow_output(source, target, &output);
+        if let Some(dir) = self.separate_config_home(source, target) {
+            report.merge(self.stow_config_home(source, dir, true)?);
+        }
+        Ok(report)
     }
 }
 
@@ -250,10 +354,65 @@ mod tests {
     fn test_stow_exclusions_constant() {
         use crate::symlink::EXCLUSIONS;
         assert!(EXCLUSIONS.contains(&".git"));
+        assert!(EXCLUSIONS.contains(&".gitmodules"));
         assert!(EXCLUSIONS.contains(&".DS_Store"));
         assert!(EXCLUSIONS.contains(&".claude"));
         assert!(EXCLUSIONS.contains(&"README.md"));
         assert!(EXCLUSIONS.contains(&"LICENSE"));
-        assert_eq!(EXCLUSIONS.len(), 5);
+        assert!(EXCLUSIONS.contains(&"templates"));
+        assert!(EXCLUSIONS.contains(&"kube"));
+        assert!(EXCLUSIONS.contains(&"karabiner"));
+        assert!(EXCLUSIONS.contains(&"appconfig"));
+        assert!(EXCLUSIONS.contains(&".dotfilesignore"));
+        assert!(EXCLUSIONS.contains(&"Brewfile"));
+        assert!(EXCLUSIONS.contains(&".stow-local-ignore"));
+        assert!(EXCLUSIONS.contains(&"hooks"));
+        assert_eq!(EXCLUSIONS.len(), 14);
+    }
+
+    #[test]
+    fn test_exclusion_patterns_anchor_to_package_root() {
+        let patterns = exclusion_patterns();
+        assert_eq!(patterns.len(), crate::symlink::EXCLUSIONS.len());
+        assert!(patterns.contains(&"^hooks$".to_string()));
+        assert!(patterns.contains(&"^README\\.md$".to_string()));
+        assert!(patterns.contains(&"(^|/)\\.DS_Store$".to_string()));
+
+        let hooks = regex::Regex::new("^hooks$").unwrap();
+        assert!(hooks.is_match("hooks"));
+        assert!(!hooks.is_match(".config/git/hooks"));
+        let ds_store = regex::Regex::new("(^|/)\\.DS_Store$").unwrap();
+        assert!(ds_store.is_match(".config/nvim/.DS_Store"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_stow_args_keep_paths_intact() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let stow_dir = Path::new("/Users/zoë/My Dotfiles");
+        let target = Path::new("/Users/zoë");
+        let package = OsStr::from_bytes(b"caf\xe9 config");
+        let ignored = vec!["^notes$".to_string()];
+
+        let stow = StowSymlinker::dry_run();
+        let args = stow.stow_args(stow_dir, target, package, &ignored, false);
+        assert_eq!(
+            &args[..4],
+            [
+                "-d".as_ref(),
+                stow_dir.as_os_str(),
+                "-t".as_ref(),
+                target.as_os_str()
+            ]
+        );
+        assert!(args.contains(&OsStr::new("^notes$")));
+        assert!(args.contains(&OsStr::new("-n")));
+        assert_eq!(args.last(), Some(&package));
+
+        let args = stow.stow_args(stow_dir, target, package, &ignored, true);
+        assert!(args.contains(&OsStr::new("-D")));
+        assert!(!args.contains(&OsStr::new("--ignore")));
+        assert_eq!(args.last(), Some(&package));
     }
 }
@@ -0,0 +1,9 @@
+//! Helpers shared by unit tests
+
+/// Removes terminal color codes, so assertions hold whether or not colors are on
+pub fn strip_ansi(text: &str) -> String {
+    regex::Regex::new("\x1b\\[[0-9;]*m")
+        .unwrap()
+        .replace_all(text, "")
+        .into_owned()
+}
@@ -0,0 +1,275 @@
+use crate::install::aws;
+use crate::validate::{CheckReport, CheckResult};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A `[section]` of an AWS config file with its top-level keys and values
+#[derive(Debug, Default)]
+struct Section {
+    header: String,
+    values: BTreeMap<String, String>,
+}
+
+/// Validates `~/.aws/config` profiles and `~/.aws/credentials` permissions
+pub fn validate_aws(home_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let aws_dir = home_dir.join(".aws");
+
+    let config_path = aws_dir.join("config");
+    if let Ok(content) = std::fs::read_to_string(&config_path) {
+        for check in check_config(&content) {
+            report.add(check);
+        }
+    }
+
+    let credentials_path = aws_dir.join("credentials");
+    if credentials_path.exists() {
+        report.add(check_credentials_permissions(&credentials_path));
+    }
+
+    report
+}
+
+fn check_config(content: &str) -> Vec<CheckResult> {
+    let (sections, errors) = parse_sections(content);
+
+    if !errors.is_empty() {
+        return vec![CheckResult::error(
+            "AWS:config",
+            format!("~/.aws/config is malformed: {}", errors.join("; ")),
+            Some("Fix the listed lines; see `aws configure sso` for the expected format"),
+        )];
+    }
+
+    let mut checks = Vec::new();
+    let profiles: Vec<&Section> = sections
+        .iter()
+        .filter(|s| s.header == "default" || s.header.starts_with("profile "))
+        .collect();
+    checks.push(CheckResult::pass(
+        "AWS:config",
+        format!("{} profiles", profiles.len()),
+    ));
+
+    let secrets = aws::find_secret_keys(content);
+    if !secrets.is_empty() {
+        checks.push(CheckResult::warn(
+            "AWS:config secrets",
+            format!("~/.aws/config contains {}", secrets.join(", ")),
+            Some("Use SSO profiles and `aws sso login` instead of static keys"),
+        ));
+    }
+
+    for profile in profiles {
+        if let Some(problem) = sso_problem(profile, &sections) {
+            checks.push(CheckResult::warn(
+                format!("AWS:{}", profile.header),
+                problem,
+                Some("Re-run: aws configure sso --profile <name>"),
+            ));
+        }
+    }
+
+    checks
+}
+
+/// Returns what is missing from an SSO profile, if anything
+fn sso_problem(profile: &Section, sections: &[Section]) -> Option<String> {
+    let values = &profile.values;
+    if !values.contains_key("sso_session") && !values.contains_key("sso_start_url") {
+        return None;
+    }
+
+    if let Some(session) = values.get("sso_session") {
+        let header = format!("sso-session {}", session);
+        if !sections.iter().any(|s| s.header == header) {
+            return Some(format!("references missing [{}]", header));
+        }
+    }
+
+    let missing: Vec<&str> = ["sso_account_id", "sso_role_name"]
+        .into_iter()
+        .filter(|key| !values.contains_key(*key))
+        .collect();
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("SSO profile is missing {}", missing.join(", ")))
+    }
+}
+
+/// Parses an AWS INI file into sections, collecting per-line syntax errors
+fn parse_sections(content: &str) -> (Vec<Section>, Vec<String>) {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let line_no = index + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(inner) = line.strip_prefix('[') {
+            let Some(header) = inner.strip_suffix(']').map(str::trim) else {
+                errors.push(format!("line {}: unterminated section header", line_no));
+                continue;
+            };
+            if !is_known_section(header) {
+                errors.push(format!("line {}: unknown section [{}]", line_no, header));
+            } else if sections.iter().any(|s| s.header == header) {
+                errors.push(format!("line {}: duplicate section [{}]", line_no, header));
+            }
+            sections.push(Section {
+                header: header.to_string(),
+                ..Section::default()
+            });
+            continue;
+        }
+
+        // Indented lines continue a nested value (e.g. `s3 =` sub-settings)
+        if raw.starts_with(char::is_whitespace) && !sections.is_empty() {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            errors.push(format!("line {}: expected `key = value`", line_no));
+            continue;
+        };
+        let Some(section) = sections.last_mut() else {
+            errors.push(format!("line {}: setting outside of a section", line_no));
+            continue;
+        };
+        section
+            .values
+            .insert(key.trim().to_lowercase(), value.trim().to_string());
+    }
+
+    (sections, errors)
+}
+
+fn is_known_section(header: &str) -> bool {
+    header == "default"
+        || ["profile ", "sso-session ", "services "]
+            .iter()
+            .any(|prefix| {
+                header
+                    .strip_prefix(prefix)
+                    .is_some_and(|name| !name.trim().is_empty())
+            })
+}
+
+#[cfg(unix)]
+fn check_credentials_permissions(path: &Path) -> CheckResult {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = match std::fs::metadata(path) {
+        Ok(metadata) => metadata.permissions().mode() & 0o777,
+        Err(e) => {
+            return CheckResult::error(
+                "AWS:credentials",
+                format!("Cannot read {}: {}", path.display(), e),
+                None::<String>,
+            )
+        }
+    };
+
+    if mode & 0o077 == 0 {
+        CheckResult::pass("AWS:credentials", format!("permissions {:o}", mode))
+    } else {
+        CheckResult::error(
+            "AWS:credentials",
+            format!("{} is readable by other users ({:o})", path.display(), mode),
+            Some(format!("Run: chmod 600 {}", path.display())),
+        )
+    }
+}
+
+#[cfg(not(unix))]
+fn check_credentials_permissions(path: &Path) -> CheckResult {
+    CheckResult::pass("AWS:credentials", format!("{} exists", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const VALID: &str = "\
+[default]
+region = us-east-1
+
+[profile dev]
+sso_session = work
+sso_account_id = 123456789012
+sso_role_name = Developer
+s3 =
+  max_concurrent_requests = 20
+
+[sso-session work]
+sso_start_url = https://example.awsapps.com/start
+sso_region = us-east-1
+";
+
+    #[test]
+    fn test_valid_config() {
+        let checks = check_config(VALID);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].is_pass());
+        assert!(checks[0].message().contains("2 profiles"));
+    }
+
+    #[test]
+    fn test_malformed_config() {
+        let checks = check_config("region = us-east-1\n[profile]\n[default]\nnot a setting\n");
+
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].is_error());
+        let message = checks[0].message();
+        assert!(message.contains("line 1: setting outside of a section"));
+        assert!(message.contains("line 2: unknown section [profile]"));
+        assert!(message.contains("line 4: expected `key = value`"));
+    }
+
+    #[test]
+    fn test_incomplete_sso_profile() {
+        let checks = check_config("[profile dev]\nsso_session = missing\n");
+        assert!(checks[1].is_warn());
+        assert!(checks[1].message().contains("[sso-session missing]"));
+
+        let checks =
+            check_config("[profile dev]\nsso_start_url = https://x\nsso_role_name = Dev\n");
+        assert!(checks[1].message().contains("sso_account_id"));
+    }
+
+    #[test]
+    fn test_secrets_in_config_warn() {
+        let checks = check_config("[default]\naws_secret_access_key = abc\n");
+        assert!(checks
+            .iter()
+            .any(|c| c.is_warn() && c.name() == "AWS:config secrets"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_credentials_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("credentials");
+        fs::write(&path, "[default]\n").unwrap();
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(check_credentials_permissions(&path).is_error());
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(check_credentials_permissions(&path).is_pass());
+    }
+
+    #[test]
+    fn test_validate_aws_without_aws_dir() {
+        let temp = TempDir::new().unwrap();
+        assert!(validate_aws(temp.path()).checks.is_empty());
+    }
+}
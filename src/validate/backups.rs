@@ -0,0 +1,101 @@
+use crate::backup::{self, BackupLayout, BackupLimits};
+use crate::core::timing::{disk_sizes, format_size};
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Validates that backups haven't piled up past `[backups] max_count` or `max_size_mb`
+///
+/// Counts the configured backup directory and any legacy `.dotfiles-backup-*`
+/// directories left in `$HOME`. Reports nothing if there are no backups.
+pub fn validate_backup_usage(
+    home_dir: &Path,
+    layout: &BackupLayout,
+    limits: BackupLimits,
+) -> CheckReport {
+    let mut report = CheckReport::new();
+    let Ok(backups) = backup::all_backups(home_dir, layout) else {
+        return report;
+    };
+    if backups.is_empty() {
+        return report;
+    }
+
+    let sizes = disk_sizes(backups.iter().map(|backup| backup.path.as_path()));
+    report.add(usage_result(&sizes, limits));
+    report
+}
+
+fn usage_result(sizes: &[u64], limits: BackupLimits) -> CheckResult {
+    let total: u64 = sizes.iter().sum();
+    let summary = format!("{} backup(s) using {}", sizes.len(), format_size(total));
+    if sizes.len() <= limits.max_count && total <= limits.max_bytes {
+        return CheckResult::pass("Backups:disk", summary);
+    }
+
+    let keep = limits.keep(sizes);
+    let savings: u64 = sizes[keep..].iter().sum();
+    CheckResult::warn(
+        "Backups:disk",
+        format!(
+            "{} (limits: {} backups, {})",
+            summary,
+            limits.max_count,
+            format_size(limits.max_bytes)
+        ),
+        Some(format!(
+            "Run: dotfiles backup prune (keeps the newest {}, frees {})",
+            keep,
+            format_size(savings)
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::BackupSettings;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_usage_result() {
+        let limits = BackupLimits {
+            max_count: 3,
+            max_bytes: 100 * MB,
+        };
+        assert!(usage_result(&[10 * MB, 10 * MB], limits).is_pass());
+
+        let result = usage_result(&[10 * MB; 5], limits);
+        assert!(!result.is_pass());
+        assert!(result.suggestion().unwrap().contains("frees 20.0 MB"));
+
+        let result = usage_result(&[80 * MB, 40 * MB], limits);
+        assert!(result.suggestion().unwrap().contains("keeps the newest 1"));
+    }
+
+    #[test]
+    fn test_validate_backup_usage() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        let layout = BackupLayout::from_settings(&BackupSettings::default(), home);
+        let limits = BackupLimits {
+            max_count: 1,
+            max_bytes: MB,
+        };
+        assert_eq!(validate_backup_usage(home, &layout, limits).total(), 0);
+
+        for name in [
+            ".dotfiles-backup-20260101-090000",
+            ".dotfiles-backup-20260201-090000",
+        ] {
+            fs::create_dir_all(home.join(name)).unwrap();
+            fs::write(home.join(name).join(".zshrc"), "export EDITOR=vim\n").unwrap();
+        }
+        let report = validate_backup_usage(home, &layout, limits);
+        assert_eq!(report.total(), 1);
+        assert!(!report.checks[0].is_pass());
+        assert!(report.checks[0].message().starts_with("2 backup(s)"));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::install::brewfile::{self, Drift, Installed};
+use crate::install::homebrew::{self, PackageKind};
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Validates that what Homebrew has installed matches the repo's Brewfile
+///
+/// Reports nothing if the repo has no Brewfile or Homebrew isn't installed.
+pub fn validate_brewfile(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let Ok(Some(brewfile)) = brewfile::read(dotfiles_dir) else {
+        return report;
+    };
+    if !homebrew::is_installed() {
+        return report;
+    }
+
+    for result in check_drift(&brewfile.drift(&Installed::query())) {
+        report.add(result);
+    }
+    report
+}
+
+fn check_drift(drift: &Drift) -> Vec<CheckResult> {
+    let names = |packages: &[(PackageKind, String)]| {
+        packages
+            .iter()
+            .map(|(kind, name)| match kind {
+                PackageKind::Formula => name.clone(),
+                PackageKind::Cask => format!("{} (cask)", name),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut missing: Vec<String> = drift.missing_taps.clone();
+    if !drift.missing.is_empty() {
+        missing.push(names(&drift.missing));
+    }
+    let missing = if missing.is_empty() {
+        CheckResult::pass("Brewfile:installed", "Everything listed is installed")
+    } else {
+        CheckResult::warn(
+            "Brewfile:installed",
+            format!("Listed but not installed: {}", missing.join(", ")),
+            Some("Run: dotfiles brewfile install"),
+        )
+    };
+
+    let extra = if drift.extra.is_empty() {
+        CheckResult::pass("Brewfile:unlisted", "Every installed package is listed")
+    } else {
+        CheckResult::warn(
+            "Brewfile:unlisted",
+            format!("Installed but not in the Brewfile: {}", names(&drift.extra)),
+            Some("Add them to the Brewfile, or regenerate it with: dotfiles brewfile export"),
+        )
+    };
+
+    vec![missing, extra]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_drift() {
+        let results = check_drift(&Drift::default());
+        assert!(results.iter().all(|result| result.is_pass()));
+
+        let results = check_drift(&Drift {
+            missing_taps: vec!["yakitrak/tap".to_string()],
+            missing: vec![(PackageKind::Cask, "obsidian".to_string())],
+            extra: vec![(PackageKind::Formula, "htop".to_string())],
+        });
+        assert_eq!(
+            results[0].message(),
+            "Listed but not installed: yakitrak/tap, obsidian (cask)"
+        );
+        assert_eq!(
+            results[1].message(),
+            "Installed but not in the Brewfile: htop"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::validate::CheckResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+/// A per-file check result, valid while the files it looked at are unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedCheck {
+    /// `<mtime ns>:<size>` of each file the check read, in order
+    pub fingerprint: String,
+    pub result: CheckResult,
+}
+
+/// Reuses per-file check results between doctor runs
+///
+/// Entries are keyed by check kind and path. A result is reused only while
+/// the mtime and size of every file it depends on are unchanged. A disabled
+/// cache (`doctor --no-cache`) still records fresh results for the next run.
+#[derive(Debug, Default)]
+pub struct CheckCache {
+    reuse: bool,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: BTreeMap<String, CachedCheck>,
+    seen: BTreeSet<String>,
+    hits: usize,
+}
+
+impl CheckCache {
+    /// Creates a cache from stored entries; `reuse` false forces every check to run
+    pub fn new(entries: BTreeMap<String, CachedCheck>, reuse: bool) -> Self {
+        Self {
+            reuse,
+            inner: Mutex::new(Inner {
+                entries,
+                ..Inner::default()
+            }),
+        }
+    }
+
+    /// A cache that never reuses results (for tests and one-off checks)
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached result for `kind` on `path`, or runs `check` and caches it
+    ///
+    /// `path` and every path in `depends_on` are fingerprinted; if any of them
+    /// is missing the check always runs and nothing is cached.
+    pub fn check(
+        &self,
+        kind: &str,
+        path: &Path,
+        depends_on: &[&Path],
+        check: impl FnOnce() -> CheckResult,
+    ) -> CheckResult {
+        let key = format!("{}:{}", kind, path.display());
+        let fingerprint = std::iter::once(path)
+            .chain(depends_on.iter().copied())
+            .map(fingerprint)
+            .collect::<Option<Vec<_>>>()
+            .map(|parts| parts.join(" "));
+
+        let Some(fingerprint) = fingerprint else {
+            return check();
+        };
+
+        {
+            let mut inner = self.lock();
+            inner.seen.insert(key.clone());
+            if let Some(cached) = inner.entries.get(&key) {
+                if self.reuse && cached.fingerprint == fingerprint {
+                    let result = cached.result.clone();
+                    inner.hits += 1;
+                    return result;
+                }
+            }
+        }
+
+        let result = check();
+        self.lock().entries.insert(
+            key,
+            CachedCheck {
+                fingerprint,
+                result: result.clone(),
+            },
+        );
+        result
+    }
+
+    /// Returns how many results were reused this run
+    pub fn hits(&self) -> usize {
+        self.lock().hits
+    }
+
+    /// Returns the entries to store, dropping files no check looked at this run
+    pub fn into_entries(self) -> BTreeMap<String, CachedCheck> {
+        let inner = self.inner.into_inner().unwrap_or_else(|e| e.into_inner());
+        inner
+            .entries
+            .into_iter()
+            .filter(|(key, _)| inner.seen.contains(key))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Returns `<mtime ns>:<size>` for a path without following a final symlink
+///
+/// Symlinks are fingerprinted as the link plus what it points to, so
+/// retargeting the link or changing the target both invalidate the entry.
+fn fingerprint(path: &Path) -> Option<String> {
+    let link = std::fs::symlink_metadata(path).ok()?;
+    let stamp = |meta: &std::fs::Metadata| {
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        format!("{}:{}", mtime, meta.len())
+    };
+
+    if link.file_type().is_symlink() {
+        let target = std::fs::read_link(path).ok()?;
+        let resolved = std::fs::metadata(path)
+            .map(|meta| stamp(&meta))
+            .unwrap_or_else(|_| "missing".to_string());
+        Some(format!(
+            "{}->{}@{}",
+            stamp(&link),
+            target.display(),
+            resolved
+        ))
+    } else {
+        Some(stamp(&link))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_reuses_until_file_changes() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("config.toml");
+        fs::write(&file, "a = 1").unwrap();
+        let runs = Cell::new(0);
+        let run = || {
+            runs.set(runs.get() + 1);
+            CheckResult::pass("Config:config.toml", "Valid TOML syntax")
+        };
+
+        let cache = CheckCache::new(BTreeMap::new(), true);
+        cache.check("toml", &file, &[], run);
+        let cache = CheckCache::new(cache.into_entries(), true);
+        let result = cache.check("toml", &file, &[], run);
+        assert_eq!(runs.get(), 1);
+        assert_eq!(cache.hits(), 1);
+        assert!(result.is_pass());
+
+        fs::write(&file, "a = 12").unwrap();
+        cache.check("toml", &file, &[], run);
+        assert_eq!(runs.get(), 2);
+    }
+
+    #[test]
+    fn test_no_reuse_still_records() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join(".zshrc");
+        fs::write(&file, "").unwrap();
+        let missing = temp.path().join("missing");
+
+        let cache = CheckCache::new(BTreeMap::new(), false);
+        cache.check("paths", &file, &[], || CheckResult::pass("Paths", "ok"));
+        cache.check("paths", &file, &[], || CheckResult::pass("Paths", "ok"));
+        cache.check("paths", &missing, &[], || CheckResult::pass("Paths", "ok"));
+        assert_eq!(cache.hits(), 0);
+
+        let entries = cache.into_entries();
+        assert_eq!(entries.len(), 1);
+        assert!(entries.contains_key(&format!("paths:{}", file.display())));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_fingerprint_follows_retargeted_link() {
+        let temp = TempDir::new().unwrap();
+        let (a, b, link) = (
+            temp.path().join("a"),
+            temp.path().join("b"),
+            temp.path().join("link"),
+        );
+        fs::write(&a, "").unwrap();
+        fs::write(&b, "").unwrap();
+        std::os::unix::fs::symlink(&a, &link).unwrap();
+        let before = fingerprint(&link).unwrap();
+
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(&b, &link).unwrap();
+        assert_ne!(fingerprint(&link).unwrap(), before);
+    }
+}
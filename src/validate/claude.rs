@@ -1,5 +1,7 @@
 use crate::validate::{CheckReport, CheckResult};
-use std::path::Path;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Validates .claude directory setup
@@ -29,6 +31,18 @@ pub fn validate_claude_directory(home_dir: &Path, dotfiles_dir: &Path) -> CheckR
                 &dotfiles_claude,
             ));
         }
+
+        // Check CLAUDE.md resolves into the dotfiles repo
+        if let Some(result) = check_claude_md_link(&claude_dir, dotfiles_dir) {
+            report.add(result);
+        }
+
+        // Check settings.json structure
+        if let Ok(content) = fs::read_to_string(claude_dir.join("settings.json")) {
+            for result in check_settings(&content, home_dir) {
+                report.add(result);
+            }
+        }
     }
 
     report
@@ -134,6 +148,371 @@ fn check_claude_individual_symlinks(claude_dir: &Path, dotfiles_claude_dir: &Pat
     }
 }
 
+fn check_claude_md_link(claude_dir: &Path, dotfiles_dir: &Path) -> Option<CheckResult> {
+    let link = claude_dir.join("CLAUDE.md");
+    let target = fs::read_link(&link).ok()?;
+
+    let dotfiles_dir = dotfiles_dir
+        .canonicalize()
+        .unwrap_or_else(|_| dotfiles_dir.to_path_buf());
+    Some(match link.canonicalize() {
+        Err(_) => CheckResult::error(
+            "Claude CLAUDE.md",
+            format!(
+                "~/.claude/CLAUDE.md is a broken link to {}",
+                target.display()
+            ),
+            Some("Run: dotfiles setup to relink it"),
+        ),
+        Ok(resolved) if resolved.starts_with(&dotfiles_dir) => CheckResult::pass(
+            "Claude CLAUDE.md",
+            format!("~/.claude/CLAUDE.md -> {}", resolved.display()),
+        ),
+        Ok(resolved) => CheckResult::warn(
+            "Claude CLAUDE.md",
+            format!(
+                "~/.claude/CLAUDE.md points outside the dotfiles repo, to {}",
+                resolved.display()
+            ),
+            Some("Move it into <dotfiles>/.claude and run: dotfiles setup"),
+        ),
+    })
+}
+
+/// Top-level keys of `~/.claude/settings.json` and the type each holds
+const SETTINGS_KEYS: &[(&str, Kind)] = &[
+    ("$schema", Kind::String),
+    ("alwaysThinkingEnabled", Kind::Bool),
+    ("apiKeyHelper", Kind::String),
+    ("awsAuthRefresh", Kind::String),
+    ("awsCredentialExport", Kind::String),
+    ("cleanupPeriodDays", Kind::Number),
+    ("companyAnnouncements", Kind::StringList),
+    ("disableAllHooks", Kind::Bool),
+    ("disabledMcpjsonServers", Kind::StringList),
+    ("enableAllProjectMcpServers", Kind::Bool),
+    ("enabledMcpjsonServers", Kind::StringList),
+    ("enabledPlugins", Kind::Object),
+    ("env", Kind::StringMap),
+    ("extraKnownMarketplaces", Kind::Object),
+    ("forceLoginMethod", Kind::String),
+    ("forceLoginOrgUUID", Kind::String),
+    ("hooks", Kind::Object),
+    ("includeCoAuthoredBy", Kind::Bool),
+    ("model", Kind::String),
+    ("otelHeadersHelper", Kind::String),
+    ("outputStyle", Kind::String),
+    ("permissions", Kind::Object),
+    ("sandbox", Kind::Object),
+    ("spinnerTipsEnabled", Kind::Bool),
+    ("statusLine", Kind::Object),
+];
+
+const PERMISSION_KEYS: &[(&str, Kind)] = &[
+    ("additionalDirectories", Kind::StringList),
+    ("allow", Kind::StringList),
+    ("ask", Kind::StringList),
+    ("defaultMode", Kind::String),
+    ("deny", Kind::StringList),
+    ("disableBypassPermissionsMode", Kind::String),
+];
+
+const PERMISSION_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+const HOOK_EVENTS: &[&str] = &[
+    "Notification",
+    "PostToolUse",
+    "PreCompact",
+    "PreToolUse",
+    "SessionEnd",
+    "SessionStart",
+    "Stop",
+    "SubagentStop",
+    "UserPromptSubmit",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Bool,
+    Number,
+    String,
+    StringList,
+    StringMap,
+    Object,
+}
+
+impl Kind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Kind::Bool => value.is_boolean(),
+            Kind::Number => value.is_number(),
+            Kind::String => value.is_string(),
+            Kind::StringList => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+            Kind::StringMap => value
+                .as_object()
+                .is_some_and(|map| map.values().all(Value::is_string)),
+            Kind::Object => value.is_object(),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Kind::Bool => "true or false",
+            Kind::Number => "a number",
+            Kind::String => "a string",
+            Kind::StringList => "a list of strings",
+            Kind::StringMap => "an object of strings",
+            Kind::Object => "an object",
+        }
+    }
+}
+
+/// What is wrong with a settings.json, by field
+#[derive(Debug, Default)]
+struct SettingsProblems {
+    /// Wrong types or values, and commands or directories that don't exist
+    errors: Vec<String>,
+    /// Keys Claude doesn't know (yet), which are usually typos
+    unknown: Vec<String>,
+    /// Values this check doesn't know, which a newer Claude may accept
+    unknown_values: Vec<String>,
+}
+
+impl SettingsProblems {
+    /// Checks the keys of `object` at `path` against `schema`, returning the ones that are well-typed
+    fn check_keys<'a>(
+        &mut self,
+        object: &'a Map<String, Value>,
+        path: &str,
+        schema: &[(&str, Kind)],
+    ) -> Vec<(&'a str, &'a Value)> {
+        let mut valid = Vec::new();
+        for (key, value) in object {
+            let field = join_field(path, key);
+            match schema.iter().find(|(name, _)| name == key) {
+                None => self.unknown.push(field),
+                Some((_, kind)) if !kind.matches(value) => {
+                    self.errors
+                        .push(format!("{} must be {}", field, kind.describe()))
+                }
+                Some(_) => valid.push((key.as_str(), value)),
+            }
+        }
+        valid
+    }
+}
+
+fn check_settings(content: &str, home_dir: &Path) -> Vec<CheckResult> {
+    let problems = settings_problems(content, home_dir);
+    let mut results = Vec::new();
+
+    if problems.errors.is_empty() {
+        results.push(CheckResult::pass(
+            "Claude Settings",
+            "~/.claude/settings.json is valid",
+        ));
+    } else {
+        results.push(CheckResult::error(
+            "Claude Settings",
+            format!(
+                "~/.claude/settings.json has problems: {}",
+                problems.errors.join("; ")
+            ),
+            Some("Fix the listed fields (schema: https://json.schemastore.org/claude-code-settings.json)"),
+        ));
+    }
+    if !problems.unknown.is_empty() {
+        results.push(CheckResult::warn(
+            "Claude Settings Keys",
+            format!(
+                "Unknown keys in ~/.claude/settings.json: {}",
+                problems.unknown.join(", ")
+            ),
+            Some("Check them for typos; Claude ignores keys it doesn't know"),
+        ));
+    }
+    if !problems.unknown_values.is_empty() {
+        results.push(CheckResult::warn(
+            "Claude Settings Values",
+            format!(
+                "Unrecognized values in ~/.claude/settings.json: {}",
+                problems.unknown_values.join("; ")
+            ),
+            Some("Check them for typos; they may also be newer than this check"),
+        ));
+    }
+    results
+}
+
+fn settings_problems(content: &str, home_dir: &Path) -> SettingsProblems {
+    let mut problems = SettingsProblems::default();
+    let root: Value = match serde_json::from_str(content) {
+        Ok(root) => root,
+        Err(e) => {
+            problems.errors.push(format!("invalid JSON: {}", e));
+            return problems;
+        }
+    };
+    let Some(root) = root.as_object() else {
+        problems
+            .errors
+            .push("top level is not an object".to_string());
+        return problems;
+    };
+
+    for (key, value) in problems.check_keys(root, "", SETTINGS_KEYS) {
+        match key {
+            "apiKeyHelper" => check_command(&mut problems, key, value, home_dir),
+            "permissions" => check_permissions(&mut problems, value, home_dir),
+            "hooks" => check_hooks(&mut problems, value, home_dir),
+            "statusLine" => check_status_line(&mut problems, value, home_dir),
+            _ => {}
+        }
+    }
+    problems
+}
+
+fn check_permissions(problems: &mut SettingsProblems, permissions: &Value, home_dir: &Path) {
+    let Some(permissions) = permissions.as_object() else {
+        return;
+    };
+    for (key, value) in problems.check_keys(permissions, "permissions", PERMISSION_KEYS) {
+        match key {
+            "defaultMode" => {
+                let mode = value.as_str().unwrap_or_default();
+                if !PERMISSION_MODES.contains(&mode) {
+                    problems.unknown_values.push(format!(
+                        "permissions.defaultMode \"{}\" (known: {})",
+                        mode,
+                        PERMISSION_MODES.join(", ")
+                    ));
+                }
+            }
+            "additionalDirectories" => {
+                for (i, dir) in value.as_array().into_iter().flatten().enumerate() {
+                    let dir = dir.as_str().unwrap_or_default();
+                    if !expand_home(dir, home_dir).is_dir() {
+                        problems.errors.push(format!(
+                            "permissions.additionalDirectories[{}]: {} does not exist",
+                            i, dir
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_hooks(problems: &mut SettingsProblems, hooks: &Value, home_dir: &Path) {
+    let Some(hooks) = hooks.as_object() else {
+        return;
+    };
+    for (event, matchers) in hooks {
+        let field = format!("hooks.{}", event);
+        if !HOOK_EVENTS.contains(&event.as_str()) {
+            problems.unknown.push(field);
+            continue;
+        }
+        let Some(matchers) = matchers.as_array() else {
+            problems.errors.push(format!("{} must be a list", field));
+            continue;
+        };
+        for (i, matcher) in matchers.iter().enumerate() {
+            let field = format!("{}[{}]", field, i);
+            if matcher.get("matcher").is_some_and(|m| !m.is_string()) {
+                problems
+                    .errors
+                    .push(format!("{}.matcher must be a string", field));
+            }
+            let Some(commands) = matcher.get("hooks").and_then(Value::as_array) else {
+                problems
+                    .errors
+                    .push(format!("{}.hooks must be a list of hooks", field));
+                continue;
+            };
+            for (j, hook) in commands.iter().enumerate() {
+                let field = format!("{}.hooks[{}]", field, j);
+                check_command_object(problems, &field, hook, home_dir);
+                if hook.get("timeout").is_some_and(|t| !t.is_number()) {
+                    problems
+                        .errors
+                        .push(format!("{}.timeout must be a number", field));
+                }
+            }
+        }
+    }
+}
+
+fn check_status_line(problems: &mut SettingsProblems, status_line: &Value, home_dir: &Path) {
+    check_command_object(problems, "statusLine", status_line, home_dir);
+    if status_line.get("padding").is_some_and(|p| !p.is_number()) {
+        problems
+            .errors
+            .push("statusLine.padding must be a number".to_string());
+    }
+}
+
+/// Checks a `{ "type": "command", "command": "..." }` object
+fn check_command_object(
+    problems: &mut SettingsProblems,
+    field: &str,
+    object: &Value,
+    home_dir: &Path,
+) {
+    match object.get("type").and_then(Value::as_str) {
+        Some("command") => {}
+        Some(other) => problems.errors.push(format!(
+            "{}.type must be \"command\" (found \"{}\")",
+            field, other
+        )),
+        None => problems.errors.push(format!("{}.type is missing", field)),
+    }
+    match object.get("command") {
+        Some(command) => check_command(problems, &format!("{}.command", field), command, home_dir),
+        None => problems
+            .errors
+            .push(format!("{}.command is missing", field)),
+    }
+}
+
+/// Checks that a command run by path (e.g. `~/.claude/hooks/lint.sh`) exists
+fn check_command(problems: &mut SettingsProblems, field: &str, command: &Value, home_dir: &Path) {
+    let Some(command) = command.as_str() else {
+        problems.errors.push(format!("{} must be a string", field));
+        return;
+    };
+    let program = command.split_whitespace().next().unwrap_or_default();
+    let program = program.trim_matches(|c| c == '"' || c == '\'');
+    let by_path = ["/", "~/", "$HOME/", "${HOME}/"]
+        .iter()
+        .any(|prefix| program.starts_with(prefix));
+    if by_path && !expand_home(program, home_dir).exists() {
+        problems
+            .errors
+            .push(format!("{}: {} does not exist", field, program));
+    }
+}
+
+/// Expands a leading `~`, `$HOME`, or `${HOME}` to `home_dir`
+fn expand_home(path: &str, home_dir: &Path) -> PathBuf {
+    ["~/", "$HOME/", "${HOME}/"]
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+        .map(|rest| home_dir.join(rest))
+        .unwrap_or_else(|| PathBuf::from(path))
+}
+
+fn join_field(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,4 +587,98 @@ mod tests {
         assert!(result.is_warn());
         assert!(result.message().contains("CLAUDE.md"));
     }
+
+    #[test]
+    fn test_settings_problems() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        fs::create_dir_all(home.join(".claude/hooks")).unwrap();
+        fs::write(home.join(".claude/hooks/format.sh"), "#!/bin/sh\n").unwrap();
+
+        let valid = r#"{
+            "model": "opus",
+            "env": { "EDITOR": "nvim" },
+            "permissions": { "allow": ["Bash(git status)"], "defaultMode": "acceptEdits" },
+            "hooks": {
+                "PostToolUse": [{
+                    "matcher": "Edit",
+                    "hooks": [{ "type": "command", "command": "~/.claude/hooks/format.sh --fix", "timeout": 30 }]
+                }]
+            },
+            "statusLine": { "type": "command", "command": "echo hi" }
+        }"#;
+        let problems = settings_problems(valid, home);
+        assert!(problems.errors.is_empty(), "{:?}", problems.errors);
+        assert!(problems.unknown.is_empty());
+
+        let invalid = r#"{
+            "includeCoAuthoredBy": "yes",
+            "env": { "RETRIES": 3 },
+            "permisions": {},
+            "permissions": { "defaultMode": "auto", "additionalDirectories": ["~/missing"] },
+            "hooks": {
+                "PostToolUse": [{ "hooks": [{ "type": "command", "command": "$HOME/.claude/hooks/gone.sh" }] }],
+                "OnSave": []
+            },
+            "statusLine": { "type": "script" }
+        }"#;
+        let problems = settings_problems(invalid, home);
+        assert_eq!(
+            problems.errors,
+            vec![
+                "env must be an object of strings",
+                "includeCoAuthoredBy must be true or false",
+                "hooks.PostToolUse[0].hooks[0].command: $HOME/.claude/hooks/gone.sh does not exist",
+                "permissions.additionalDirectories[0]: ~/missing does not exist",
+                "statusLine.type must be \"command\" (found \"script\")",
+                "statusLine.command is missing",
+            ]
+        );
+        assert_eq!(problems.unknown, vec!["permisions", "hooks.OnSave"]);
+        assert_eq!(
+            problems.unknown_values,
+            vec!["permissions.defaultMode \"auto\" (known: default, acceptEdits, plan, bypassPermissions)"]
+        );
+        let results = check_settings(r#"{ "permissions": { "defaultMode": "auto" } }"#, home);
+        assert!(results[0].is_pass());
+        assert!(results[1].is_warn());
+
+        let results = check_settings("[]", home);
+        assert!(results[0].is_error());
+        assert!(results[0].message().contains("top level is not an object"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_check_claude_md_link() {
+        let temp = TempDir::new().unwrap();
+        let claude_dir = temp.path().join(".claude");
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(&claude_dir).unwrap();
+        fs::create_dir_all(dotfiles.join(".claude")).unwrap();
+        fs::write(dotfiles.join(".claude/CLAUDE.md"), "# Rules\n").unwrap();
+        fs::write(temp.path().join("elsewhere.md"), "# Rules\n").unwrap();
+        let link = claude_dir.join("CLAUDE.md");
+
+        fs::write(&link, "# Rules\n").unwrap();
+        assert!(check_claude_md_link(&claude_dir, &dotfiles).is_none());
+
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".claude/CLAUDE.md"), &link).unwrap();
+        assert!(check_claude_md_link(&claude_dir, &dotfiles)
+            .unwrap()
+            .is_pass());
+
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(temp.path().join("elsewhere.md"), &link).unwrap();
+        assert!(check_claude_md_link(&claude_dir, &dotfiles)
+            .unwrap()
+            .is_warn());
+
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".claude/gone.md"), &link).unwrap();
+        assert!(check_claude_md_link(&claude_dir, &dotfiles)
+            .unwrap()
+            .is_error());
+    }
 }
@@ -1,7 +1,55 @@
+use crate::core::config::Config;
+use crate::core::walk::RepoWalker;
+use crate::validate::cache::CheckCache;
 use crate::validate::{CheckReport, CheckResult};
 use std::fs;
 use std::path::Path;
 
+/// Validates ~/.dotfiles.conf: it parses and its values make sense
+pub fn validate_dotfiles_config(config_path: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    if !config_path.exists() {
+        report.add(CheckResult::warn(
+            "Dotfiles Config",
+            format!("{} not found", config_path.display()),
+            Some("Run: dotfiles setup"),
+        ));
+        return report;
+    }
+
+    let config = match Config::load(&config_path.to_path_buf()) {
+        Ok(config) => config,
+        Err(e) => {
+            report.add(CheckResult::error(
+                "Dotfiles Config",
+                e.to_string(),
+                Some("Fix the file or re-run: dotfiles setup"),
+            ));
+            return report;
+        }
+    };
+
+    let problems = config.validate();
+    if problems.is_empty() {
+        report.add(CheckResult::pass(
+            "Dotfiles Config",
+            "Configuration is valid",
+        ));
+    }
+
+    for problem in problems {
+        let name = format!("Dotfiles Config:{}", problem.field);
+        report.add(if problem.is_error() {
+            CheckResult::error(name, problem.message, Some(problem.suggestion))
+        } else {
+            CheckResult::warn(name, problem.message, Some(problem.suggestion))
+        });
+    }
+
+    report
+}
+
 /// Validates TOML syntax
 pub fn validate_toml(file_path: &Path) -> CheckResult {
     match fs::read_to_string(file_path) {
@@ -119,7 +167,10 @@ pub fn validate_config(file_path: &Path) -> CheckResult {
 }
 
 /// Scans a directory for config files and validates them
-pub fn scan_directory(dir_path: &Path) -> CheckReport {
+///
+/// Covers the TOML, JSON, and YAML files at the top level. A parse that
+/// passed or failed is remembered in `cache` until the file is edited.
+pub fn scan_directory(dir_path: &Path, cache: &CheckCache) -> CheckReport {
     let mut report = CheckReport::new();
 
     if !dir_path.exists() {
@@ -131,17 +182,13 @@ pub fn scan_directory(dir_path: &Path) -> CheckReport {
         return report;
     }
 
-    match fs::read_dir(dir_path) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        let ext_str = ext.to_str().unwrap_or("");
-                        if matches!(ext_str, "toml" | "json" | "yaml" | "yml") {
-                            report.add(validate_config(&path));
-                        }
+    match RepoWalker::new(dir_path).max_depth(1).files() {
+        Ok(files) => {
+            for path in files {
+                if let Some(ext) = path.extension() {
+                    let ext_str = ext.to_str().unwrap_or("");
+                    if matches!(ext_str, "toml" | "json" | "yaml" | "yml") {
+                        report.add(cache.check("config", &path, &[], || validate_config(&path)));
                     }
                 }
             }
@@ -278,7 +325,7 @@ mod tests {
         fs::write(temp_dir.path().join("valid.json"), "{}").unwrap();
         fs::write(temp_dir.path().join("invalid.toml"), "[section").unwrap();
 
-        let report = scan_directory(temp_dir.path());
+        let report = scan_directory(temp_dir.path(), &CheckCache::disabled());
 
         // Should have 3 results
         assert_eq!(report.total(), 3);
@@ -288,7 +335,7 @@ mod tests {
     #[test]
     fn test_scan_directory_empty() {
         let temp_dir = TempDir::new().unwrap();
-        let report = scan_directory(temp_dir.path());
+        let report = scan_directory(temp_dir.path(), &CheckCache::disabled());
 
         // Empty directory should have one pass result
         assert_eq!(report.total(), 1);
@@ -297,7 +344,7 @@ mod tests {
 
     #[test]
     fn test_scan_directory_nonexistent() {
-        let report = scan_directory(Path::new("/nonexistent/directory"));
+        let report = scan_directory(Path::new("/nonexistent/directory"), &CheckCache::disabled());
 
         assert!(report.has_errors());
         assert!(report
@@ -305,4 +352,27 @@ mod tests {
             .iter()
             .any(|c| c.message().contains("does not exist")));
     }
+
+    #[test]
+    fn test_validate_dotfiles_config_missing() {
+        let temp = TempDir::new().unwrap();
+        let report = validate_dotfiles_config(&temp.path().join(".dotfiles.conf"));
+        assert_eq!(report.warn_count(), 1);
+    }
+
+    #[test]
+    fn test_validate_dotfiles_config_reports_problems() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(".dotfiles.conf");
+        let config = Config {
+            dotfiles_dir: temp.path().join("missing"),
+            xdg_config_home: temp.path().to_path_buf(),
+            ..Config::default()
+        };
+        config.save(&path).unwrap();
+
+        let report = validate_dotfiles_config(&path);
+        assert_eq!(report.error_count(), 1);
+        assert_eq!(report.checks[0].name(), "Dotfiles Config:dotfiles_dir");
+    }
 }
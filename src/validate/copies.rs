@@ -0,0 +1,71 @@
+use crate::core::state::State;
+use crate::install::copies::{self, CopiedFile, CopyStatus};
+use crate::validate::{CheckReport, CheckResult};
+
+/// Validates that each copy-only file still matches its repo version
+pub fn validate_copies(files: &[CopiedFile], state: Option<&State>) -> CheckReport {
+    let mut report = CheckReport::new();
+    let empty = State::default();
+    let state = state.unwrap_or(&empty);
+
+    for file in files {
+        report.add(check_copy(file, state));
+    }
+    report
+}
+
+fn check_copy(file: &CopiedFile, state: &State) -> CheckResult {
+    let name = format!("Copies:{}", file.name);
+    match copies::status(file, state) {
+        Ok(CopyStatus::InSync) => CheckResult::pass(name, "Matches the repo"),
+        Ok(status @ (CopyStatus::Missing | CopyStatus::RepoChanged | CopyStatus::Symlinked)) => {
+            CheckResult::warn(
+                name,
+                format!("{} is {}", file.target.display(), status.describe()),
+                Some("Run: dotfiles sync"),
+            )
+        }
+        Ok(status) => CheckResult::warn(
+            name,
+            format!("{} {}", file.target.display(), status.describe()),
+            Some("Push the change into the repo or pull the repo's version with: dotfiles sync"),
+        ),
+        Err(e) => CheckResult::error(
+            name,
+            format!("Could not read {}: {}", file.source.display(), e),
+            Some("Add the file to the dotfiles repo, or remove it from [files]"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_copy() {
+        let temp = TempDir::new().unwrap();
+        let file = CopiedFile {
+            name: "settings.json".to_string(),
+            source: temp.path().join("settings.json"),
+            target: temp.path().join("live.json"),
+        };
+        fs::write(&file.source, "{}").unwrap();
+
+        let mut state = State::default();
+        assert!(check_copy(&file, &state).is_warn());
+
+        copies::pull(&file, &mut state).unwrap();
+        assert!(check_copy(&file, &state).is_pass());
+
+        fs::write(&file.target, "{\"saved\": true}").unwrap();
+        assert!(check_copy(&file, &state)
+            .message()
+            .ends_with("changed on this machine"));
+
+        fs::remove_file(&file.source).unwrap();
+        assert!(check_copy(&file, &state).is_error());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::core::config::PackageSettings;
+use crate::detect::backend;
+use crate::install::homebrew::FormulaInfo;
 use crate::validate::{CheckReport, CheckResult};
 
 /// Validates that Homebrew is installed (macOS only)
@@ -15,7 +18,9 @@ pub fn check_homebrew() -> CheckResult {
         CheckResult::error(
             "Homebrew",
             "Not installed",
-            Some("Install with: /bin/bash -c \"$(curl -fsSL https://raw.githubusercontent.com/Homebrew/install/HEAD/install.sh)\""),
+            Some(
+                "Run: dotfiles setup (downloads and verifies the install script before running it)",
+            ),
         )
     }
 }
@@ -34,57 +39,146 @@ pub fn check_version_manager() -> CheckResult {
         CheckResult::warn(
             "Version Manager",
             "No version manager detected (ASDF, mise, or rtx)",
-            Some("Install mise with: brew install mise"),
+            Some(format!(
+                "Install mise with: {}",
+                backend::install_command(&["mise"])
+            )),
         )
     }
 }
 
 /// Validates that a specific tool is installed
+///
+/// Looks for the command the package installs, e.g. `batcat` for bat on Debian.
 pub fn check_tool(tool: &str) -> CheckResult {
-    if crate::detect::tools::is_installed(tool) {
+    let command = backend::PackageBackend::detect().command_name(tool);
+    if crate::detect::tools::is_installed(command) {
         let path =
-            crate::detect::tools::get_tool_path(tool).unwrap_or_else(|| "unknown".to_string());
+            crate::detect::tools::get_tool_path(command).unwrap_or_else(|| "unknown".to_string());
         CheckResult::pass(tool, format!("Installed at {}", path))
     } else {
-        let suggestion = match tool {
-            "stow" => "brew install stow",
-            "git" => "brew install git",
-            "fzf" => "brew install fzf",
-            "bat" => "brew install bat",
-            "fd" => "brew install fd",
-            "tree" => "brew install tree",
-            "nvim" => "brew install nvim",
-            "tmux" => "brew install tmux",
-            "ripgrep" => "brew install ripgrep",
-            _ => "brew install <package>",
-        };
-
         CheckResult::error(
             tool,
             "Not installed",
-            Some(format!("Install with: {}", suggestion)),
+            Some(format!(
+                "Install with: {}",
+                backend::install_command(&[tool])
+            )),
         )
     }
 }
 
-/// Validates all dependencies
-pub fn validate_all() -> CheckReport {
+/// Validates all dependencies, ignoring packages skipped in config
+pub fn validate_all(settings: &PackageSettings, userspace_only: bool) -> CheckReport {
     let mut report = CheckReport::new();
 
-    // Check Homebrew
-    report.add(check_homebrew());
+    // Check Homebrew, which userspace-only mode never installs
+    if !userspace_only {
+        report.add(check_homebrew());
+    }
 
     // Check version manager
     report.add(check_version_manager());
 
     // Check essential tools
-    for tool in crate::install::packages::ESSENTIAL_PACKAGES {
+    for tool in crate::install::packages::active_packages(
+        crate::install::packages::ESSENTIAL_PACKAGES,
+        settings,
+    ) {
         report.add(check_tool(tool));
     }
 
     report
 }
 
+/// Validates that packages listed as `pinned` are pinned in Homebrew
+pub fn validate_pins(settings: &PackageSettings) -> CheckReport {
+    let mut report = CheckReport::new();
+    if settings.pinned.is_empty() || !crate::install::homebrew::is_installed() {
+        return report;
+    }
+
+    let pinned = crate::install::homebrew::pinned_packages();
+    for package in &settings.pinned {
+        if settings.is_skipped(package) {
+            continue;
+        }
+        report.add(check_pin(package, &pinned));
+    }
+
+    report
+}
+
+fn check_pin(package: &str, pinned: &[String]) -> CheckResult {
+    let name = format!("Pin:{}", package);
+    if pinned.iter().any(|p| p == package) {
+        CheckResult::pass(name, "Pinned in Homebrew")
+    } else {
+        CheckResult::warn(
+            name,
+            "Not pinned; brew upgrade may replace it",
+            Some(format!("Run: brew pin {}", package)),
+        )
+    }
+}
+
+/// Validates that managed formulae are linked and have all their dependencies
+pub fn validate_brew_health(settings: &PackageSettings) -> CheckReport {
+    let mut report = CheckReport::new();
+    if !crate::install::homebrew::is_installed() {
+        return report;
+    }
+
+    let installed = crate::install::packages::installed_managed_packages(settings);
+    let formulae = crate::install::homebrew::formula_info(&installed);
+    let names: Vec<String> = formulae.iter().map(|f| f.name.clone()).collect();
+    let missing = crate::install::homebrew::missing_dependencies(&names);
+
+    for check in check_brew_health(&formulae, &missing) {
+        report.add(check);
+    }
+
+    report
+}
+
+fn check_brew_health(
+    formulae: &[FormulaInfo],
+    missing: &[(String, Vec<String>)],
+) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+
+    for formula in formulae.iter().filter(|f| !f.linked && !f.keg_only) {
+        checks.push(CheckResult::warn(
+            format!("Brew Link:{}", formula.name),
+            "Installed but not linked into the Homebrew prefix",
+            Some(format!(
+                "Run: brew link {} (add --overwrite if it reports conflicts)",
+                formula.name
+            )),
+        ));
+    }
+
+    for (formula, deps) in missing {
+        checks.push(CheckResult::warn(
+            format!("Brew Deps:{}", formula),
+            format!("Missing dependencies: {}", deps.join(", ")),
+            Some(format!("Run: brew install {}", deps.join(" "))),
+        ));
+    }
+
+    if checks.is_empty() && !formulae.is_empty() {
+        checks.push(CheckResult::pass(
+            "Brew Health",
+            format!(
+                "{} managed formulae linked with all dependencies",
+                formulae.len()
+            ),
+        ));
+    }
+
+    checks
+}
+
 /// Validates only critical dependencies (Homebrew and stow)
 pub fn validate_critical() -> CheckReport {
     let mut report = CheckReport::new();
@@ -146,7 +240,10 @@ mod tests {
 
         if result.is_error() {
             assert!(result.suggestion().is_some());
-            assert!(result.suggestion().unwrap().contains("brew install"));
+            assert!(result
+                .suggestion()
+                .unwrap()
+                .contains(&backend::install_command(&["stow"])));
         }
     }
 
@@ -174,7 +271,7 @@ mod tests {
 
     #[test]
     fn test_validate_all() {
-        let report = validate_all();
+        let report = validate_all(&PackageSettings::default(), false);
 
         // Should have Homebrew + Version Manager + all essential packages
         // That's 2 + ESSENTIAL_PACKAGES.len()
@@ -189,6 +286,11 @@ mod tests {
 
         // Check that stow is included
         assert!(report.checks.iter().any(|c| c.name() == "stow"));
+
+        // Userspace-only mode never installs Homebrew, so doesn't check for it
+        let report = validate_all(&PackageSettings::default(), true);
+        assert_eq!(report.total(), expected - 1);
+        assert!(!report.checks.iter().any(|c| c.name() == "Homebrew"));
     }
 
     #[test]
@@ -201,9 +303,53 @@ mod tests {
             if result.is_error() {
                 assert!(result.suggestion().is_some());
                 let suggestion = result.suggestion().unwrap();
-                assert!(suggestion.contains("brew install"));
-                assert!(suggestion.contains(tool));
+                assert!(suggestion.contains(&backend::install_command(&[tool])));
             }
         }
     }
+
+    #[test]
+    fn test_validate_all_ignores_skipped() {
+        let settings = PackageSettings {
+            skip: vec!["nvim".to_string()],
+            ..Default::default()
+        };
+        let report = validate_all(&settings, false);
+
+        assert!(!report.checks.iter().any(|c| c.name() == "nvim"));
+    }
+
+    #[test]
+    fn test_check_pin() {
+        let pinned = vec!["tmux".to_string()];
+        assert!(check_pin("tmux", &pinned).is_pass());
+        assert!(check_pin("nvim", &pinned).is_warn());
+    }
+
+    #[test]
+    fn test_check_brew_health() {
+        let formula = |name: &str, linked: bool, keg_only: bool| FormulaInfo {
+            name: name.to_string(),
+            linked,
+            keg_only,
+        };
+
+        let healthy = vec![
+            formula("fzf", true, false),
+            formula("openssl@3", false, true),
+        ];
+        let checks = check_brew_health(&healthy, &[]);
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].is_pass());
+
+        let checks = check_brew_health(
+            &[formula("bat", false, false)],
+            &[("nvim".to_string(), vec!["luajit".to_string()])],
+        );
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].name(), "Brew Link:bat");
+        assert!(checks[0].is_warn());
+        assert_eq!(checks[1].name(), "Brew Deps:nvim");
+        assert!(checks[1].message().contains("luajit"));
+    }
 }
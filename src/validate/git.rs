@@ -0,0 +1,127 @@
+use crate::core::config::GitSettings;
+use crate::detect::{backend, tools};
+use crate::install::git;
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Validates the git pager, difftool, and declared aliases
+pub fn validate_git(home_dir: &Path, settings: &GitSettings) -> CheckReport {
+    let mut report = CheckReport::new();
+    let local = git::local_config_path(home_dir);
+
+    if !git::desired_entries(settings).is_empty() {
+        report.add(check_local_config_included(git::is_local_config_included(
+            home_dir,
+        )));
+    }
+
+    if let Some(pager) = git::get(None, "core.pager") {
+        report.add(check_binary("Git:pager", &pager, pager_binary(&pager)));
+    } else if settings.delta {
+        report.add(CheckResult::warn(
+            "Git:pager",
+            "core.pager is not set; delta is not in use",
+            Some("Run: dotfiles setup"),
+        ));
+    }
+
+    if let Some(tool) = git::get(None, "diff.tool") {
+        report.add(check_binary(
+            "Git:difftool",
+            &tool,
+            git::difftool_binary(&tool),
+        ));
+    }
+
+    for (name, command) in &settings.aliases {
+        let actual = git::get(Some(&local), &format!("alias.{}", name));
+        report.add(check_alias(name, command, actual.as_deref()));
+    }
+
+    report
+}
+
+/// Returns the executable of a pager command line (e.g. `delta --dark` -> `delta`)
+fn pager_binary(pager: &str) -> &str {
+    pager.split_whitespace().next().unwrap_or(pager)
+}
+
+fn check_local_config_included(included: bool) -> CheckResult {
+    if included {
+        CheckResult::pass(
+            "Git:local config",
+            format!("~/{} included", git::LOCAL_CONFIG),
+        )
+    } else {
+        CheckResult::warn(
+            "Git:local config",
+            format!("~/{} is not included from ~/.gitconfig", git::LOCAL_CONFIG),
+            Some(format!(
+                "Run: git config --global --add include.path ~/{}",
+                git::LOCAL_CONFIG
+            )),
+        )
+    }
+}
+
+fn check_binary(name: &str, setting: &str, binary: &str) -> CheckResult {
+    if tools::is_installed(binary) {
+        CheckResult::pass(name, format!("{} ({} found)", setting, binary))
+    } else {
+        let fix = if binary == "delta" {
+            format!("Run: {}", backend::install_command(&[git::DELTA_PACKAGE]))
+        } else {
+            format!("Install {} or change the git setting", binary)
+        };
+        CheckResult::error(
+            name,
+            format!("{} is configured but {} is not installed", setting, binary),
+            Some(fix),
+        )
+    }
+}
+
+fn check_alias(name: &str, expected: &str, actual: Option<&str>) -> CheckResult {
+    let check_name = format!("Git:alias {}", name);
+    match actual {
+        Some(actual) if actual == expected => CheckResult::pass(check_name, expected),
+        Some(actual) => CheckResult::warn(
+            check_name,
+            format!("is `{}` but config declares `{}`", actual, expected),
+            Some("Run: dotfiles setup"),
+        ),
+        None => CheckResult::warn(check_name, "not defined", Some("Run: dotfiles setup")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_binary() {
+        assert_eq!(pager_binary("delta --dark"), "delta");
+        assert_eq!(pager_binary("less"), "less");
+    }
+
+    #[test]
+    fn test_check_binary() {
+        assert!(check_binary("Git:pager", "ls", "ls").is_pass());
+
+        let result = check_binary("Git:pager", "delta", "nonexistent-pager-xyz");
+        assert!(result.is_error());
+    }
+
+    #[test]
+    fn test_check_alias() {
+        assert!(check_alias("co", "checkout", Some("checkout")).is_pass());
+        assert!(check_alias("co", "checkout", Some("switch")).is_warn());
+        assert!(check_alias("co", "checkout", None).is_warn());
+    }
+
+    #[test]
+    fn test_check_local_config_included() {
+        assert!(check_local_config_included(true).is_pass());
+        assert!(check_local_config_included(false).is_warn());
+    }
+}
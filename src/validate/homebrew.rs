@@ -0,0 +1,236 @@
+use crate::detect::os::OS;
+use crate::install::{homebrew, xcode};
+use crate::validate::{CheckReport, CheckResult};
+use std::path::{Path, PathBuf};
+
+/// Directories under the prefix that `brew install` writes to
+const PREFIX_DIRS: &[&str] = &[
+    "bin",
+    "etc",
+    "include",
+    "lib",
+    "sbin",
+    "share",
+    "var",
+    "opt",
+    "Cellar",
+    "Caskroom",
+    "Frameworks",
+];
+
+/// A Homebrew directory the current user can't install into
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Unwritable {
+    path: PathBuf,
+    owned: bool,
+}
+
+/// Validates that the Homebrew prefix belongs to this user and that the
+/// Xcode Command Line Tools are installed (macOS)
+///
+/// Migration Assistant copies `/opt/homebrew` over with the old user's
+/// ownership, after which every install fails with permission errors.
+pub fn validate_homebrew_permissions(os: OS) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    if let Some(prefix) = homebrew::prefix() {
+        let dirs = brew_dirs(&prefix);
+        report.add(check_prefix(&prefix, &unwritable(&dirs), &current_user()));
+    }
+    if os == OS::MacOS {
+        report.add(check_command_line_tools(
+            xcode::selected_path().as_deref(),
+            Path::exists,
+        ));
+    }
+
+    report
+}
+
+/// The prefix directories Homebrew writes to, plus its repository
+///
+/// `/usr/local` itself belongs to root on Intel Macs; only what Homebrew
+/// created inside it has to belong to the user.
+fn brew_dirs(prefix: &Path) -> Vec<PathBuf> {
+    let repository = prefix.join("Homebrew");
+    let mut dirs = vec![if repository.is_dir() {
+        repository
+    } else {
+        prefix.to_path_buf()
+    }];
+    dirs.extend(
+        PREFIX_DIRS
+            .iter()
+            .map(|dir| prefix.join(dir))
+            .filter(|dir| dir.is_dir()),
+    );
+    dirs
+}
+
+#[cfg(unix)]
+fn unwritable(dirs: &[PathBuf]) -> Vec<Unwritable> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+
+    let uid = crate::core::sudo::effective_uid();
+    dirs.iter()
+        .filter_map(|dir| {
+            let owned = std::fs::metadata(dir).ok()?.uid() == uid;
+            let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+            // SAFETY: `path` is a valid NUL-terminated string for the duration of the call
+            let writable = unsafe { libc::access(path.as_ptr(), libc::W_OK) } == 0;
+            (!owned || !writable).then(|| Unwritable {
+                path: dir.clone(),
+                owned,
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn unwritable(_dirs: &[PathBuf]) -> Vec<Unwritable> {
+    Vec::new()
+}
+
+fn current_user() -> String {
+    std::env::var("USER").unwrap_or_else(|_| "$(whoami)".to_string())
+}
+
+fn check_prefix(prefix: &Path, unwritable: &[Unwritable], user: &str) -> CheckResult {
+    const NAME: &str = "Brew Prefix";
+    if unwritable.is_empty() {
+        return CheckResult::pass(
+            NAME,
+            format!("{} is owned and writable by {}", prefix.display(), user),
+        );
+    }
+
+    let list = |owned: bool| {
+        unwritable
+            .iter()
+            .filter(|dir| dir.owned == owned)
+            .map(|dir| dir.path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut fixes = Vec::new();
+    let not_owned = list(false);
+    if !not_owned.is_empty() {
+        fixes.push(format!("sudo chown -R {} {}", user, not_owned));
+    }
+    let read_only = list(true);
+    if !read_only.is_empty() {
+        fixes.push(format!("chmod -R u+w {}", read_only));
+    }
+
+    let names: Vec<String> = unwritable
+        .iter()
+        .map(|dir| dir.path.display().to_string())
+        .collect();
+    CheckResult::error(
+        NAME,
+        format!(
+            "{} can't write to {}; brew install and upgrade will fail",
+            user,
+            names.join(", ")
+        ),
+        Some(format!("Run: {}", fixes.join(" && "))),
+    )
+}
+
+fn check_command_line_tools(path: Option<&Path>, exists: impl Fn(&Path) -> bool) -> CheckResult {
+    const NAME: &str = "Command Line Tools";
+    match path {
+        Some(path) if exists(path) => {
+            CheckResult::pass(NAME, format!("Installed at {}", path.display()))
+        }
+        // Selected path left behind by a migration or a deleted Xcode.app
+        Some(path) => CheckResult::error(
+            NAME,
+            format!(
+                "xcode-select points at {}, which is missing",
+                path.display()
+            ),
+            Some("Run: sudo xcode-select --reset, or if that fails: xcode-select --install"),
+        ),
+        None => CheckResult::error(
+            NAME,
+            "Not installed; Homebrew needs them to build and install formulae",
+            Some("Run: xcode-select --install"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_brew_dirs() {
+        let temp = TempDir::new().unwrap();
+        let prefix = temp.path();
+        std::fs::create_dir_all(prefix.join("bin")).unwrap();
+        std::fs::create_dir_all(prefix.join("Cellar")).unwrap();
+        assert_eq!(
+            brew_dirs(prefix),
+            vec![
+                prefix.to_path_buf(),
+                prefix.join("bin"),
+                prefix.join("Cellar")
+            ]
+        );
+
+        // Intel layout: the repository lives in a subdirectory
+        std::fs::create_dir_all(prefix.join("Homebrew")).unwrap();
+        assert_eq!(brew_dirs(prefix)[0], prefix.join("Homebrew"));
+
+        #[cfg(unix)]
+        assert!(unwritable(&brew_dirs(prefix)).is_empty());
+    }
+
+    #[test]
+    fn test_check_prefix() {
+        let prefix = Path::new("/opt/homebrew");
+        assert!(check_prefix(prefix, &[], "jo").is_pass());
+
+        let result = check_prefix(
+            prefix,
+            &[
+                Unwritable {
+                    path: prefix.join("Cellar"),
+                    owned: false,
+                },
+                Unwritable {
+                    path: prefix.join("bin"),
+                    owned: false,
+                },
+                Unwritable {
+                    path: prefix.join("var"),
+                    owned: true,
+                },
+            ],
+            "jo",
+        );
+        assert!(result.is_error());
+        assert_eq!(
+            result.suggestion(),
+            Some(
+                "Run: sudo chown -R jo /opt/homebrew/Cellar /opt/homebrew/bin && chmod -R u+w /opt/homebrew/var"
+            )
+        );
+    }
+
+    #[test]
+    fn test_check_command_line_tools() {
+        let clt = Path::new("/Library/Developer/CommandLineTools");
+        assert!(check_command_line_tools(Some(clt), |_| true).is_pass());
+
+        let moved = check_command_line_tools(Some(clt), |_| false);
+        assert!(moved.message().contains("missing"));
+        assert!(moved.suggestion().unwrap().contains("xcode-select --reset"));
+
+        let missing = check_command_line_tools(None, |_| true);
+        assert_eq!(missing.suggestion(), Some("Run: xcode-select --install"));
+    }
+}
@@ -20,6 +20,7 @@ fn check_iterm_plist_in_dotfiles(dotfiles_dir: &Path) -> CheckResult {
         dotfiles_dir.join(".config/iterm2/com.googlecode.iterm2.plist"),
         dotfiles_dir.join("iterm/com.googlecode.iterm2.plist"),
         dotfiles_dir.join("iterm2/com.googlecode.iterm2.plist"),
+        dotfiles_dir.join("appconfig/iterm2/com.googlecode.iterm2.plist"),
     ];
 
     for path in possible_locations {
@@ -34,7 +35,7 @@ fn check_iterm_plist_in_dotfiles(dotfiles_dir: &Path) -> CheckResult {
     CheckResult::warn(
         "iTerm Plist",
         "iTerm configuration not found in dotfiles",
-        Some("Export iTerm preferences to dotfiles directory"),
+        Some("Run: dotfiles appconfig export iterm2"),
     )
 }
 
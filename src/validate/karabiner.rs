@@ -0,0 +1,115 @@
+use crate::install::karabiner;
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Validates that Karabiner's config is linked from dotfiles and loadable (macOS only)
+pub fn validate_karabiner(home_dir: &Path, dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let config_dir = karabiner::config_dir(home_dir);
+
+    if karabiner::repo_config(dotfiles_dir).is_file() {
+        report.add(check_linked(&config_dir, dotfiles_dir));
+    }
+
+    if let Ok(content) = std::fs::read_to_string(config_dir.join("karabiner.json")) {
+        report.add(check_structure(&content));
+    }
+
+    if !karabiner::cli_installed() {
+        report.add(CheckResult::warn(
+            "Karabiner:cli",
+            "Karabiner-Elements is not installed",
+            Some("Run: brew install --cask karabiner-elements"),
+        ));
+    }
+
+    report
+}
+
+fn check_linked(config_dir: &Path, dotfiles_dir: &Path) -> CheckResult {
+    let expected = dotfiles_dir.join(karabiner::REPO_CONFIG_DIR);
+    match std::fs::read_link(config_dir) {
+        Ok(target) if target == expected => CheckResult::pass(
+            "Karabiner:link",
+            format!("~/.config/karabiner -> {}", expected.display()),
+        ),
+        Ok(target) => CheckResult::warn(
+            "Karabiner:link",
+            format!("~/.config/karabiner points to {}", target.display()),
+            Some("Remove the link and run: dotfiles setup"),
+        ),
+        Err(_) if config_dir.exists() => CheckResult::warn(
+            "Karabiner:link",
+            "~/.config/karabiner is a plain directory, not linked from dotfiles",
+            Some(
+                "Move it aside (mv ~/.config/karabiner ~/.config/karabiner.bak) and run: dotfiles setup",
+            ),
+        ),
+        Err(_) => CheckResult::warn(
+            "Karabiner:link",
+            "~/.config/karabiner is missing",
+            Some("Run: dotfiles setup"),
+        ),
+    }
+}
+
+fn check_structure(content: &str) -> CheckResult {
+    let problems = karabiner::structure_problems(content);
+    if problems.is_empty() {
+        CheckResult::pass("Karabiner:config", "karabiner.json is valid")
+    } else {
+        CheckResult::error(
+            "Karabiner:config",
+            format!(
+                "karabiner.json will not load, so no remappings are active: {}",
+                problems.join("; ")
+            ),
+            Some("Fix the listed problems, or restore it with: git -C <dotfiles> checkout karabiner/karabiner.json"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_validate_karabiner() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        let dotfiles = home.join("dotfiles");
+        fs::create_dir_all(dotfiles.join("karabiner")).unwrap();
+        fs::write(
+            karabiner::repo_config(&dotfiles),
+            r#"{ "profiles": [{ "name": "Default", "selected": true }] }"#,
+        )
+        .unwrap();
+        fs::create_dir_all(home.join(".config")).unwrap();
+
+        let report = validate_karabiner(home, &dotfiles);
+        let link = report.checks.iter().find(|c| c.name() == "Karabiner:link");
+        assert!(!link.unwrap().is_pass());
+
+        std::os::unix::fs::symlink(dotfiles.join("karabiner"), home.join(".config/karabiner"))
+            .unwrap();
+        let report = validate_karabiner(home, &dotfiles);
+        for name in ["Karabiner:link", "Karabiner:config"] {
+            assert!(report
+                .checks
+                .iter()
+                .find(|c| c.name() == name)
+                .unwrap()
+                .is_pass());
+        }
+
+        fs::write(karabiner::repo_config(&dotfiles), "{ \"profiles\": [] }").unwrap();
+        let report = validate_karabiner(home, &dotfiles);
+        assert!(report
+            .checks
+            .iter()
+            .any(|c| c.name() == "Karabiner:config" && c.is_error()));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::install::kubernetes;
+use crate::validate::{CheckReport, CheckResult};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Validates kubeconfig contexts and declared krew plugins
+pub fn validate_kubernetes(
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    krew_plugins: &[String],
+) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    let mut files = kubernetes::kubeconfig_paths(dotfiles_dir, home_dir);
+    if files.is_empty() {
+        files.push(home_dir.join(".kube/config"));
+    }
+    let contexts = contexts_by_file(&files);
+
+    report.add(check_duplicate_contexts(&contexts));
+
+    let mut names: Vec<&str> = contexts.iter().map(|(name, _)| name.as_str()).collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        report.add(check_context(name, kubernetes::is_context_reachable(name)));
+    }
+
+    if !krew_plugins.is_empty() {
+        report.add(check_krew_plugins(
+            krew_plugins,
+            &kubernetes::installed_krew_plugins(),
+        ));
+    }
+
+    report
+}
+
+/// Reads every context name along with the kubeconfig file defining it
+fn contexts_by_file(files: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    files
+        .iter()
+        .filter_map(|path| Some((path, std::fs::read_to_string(path).ok()?)))
+        .flat_map(|(path, content)| {
+            kubernetes::context_names(&content)
+                .into_iter()
+                .map(move |name| (name, path.clone()))
+        })
+        .collect()
+}
+
+fn check_duplicate_contexts(contexts: &[(String, PathBuf)]) -> CheckResult {
+    let mut files_by_name: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (name, path) in contexts {
+        files_by_name
+            .entry(name)
+            .or_default()
+            .push(path.display().to_string());
+    }
+
+    let duplicates: Vec<String> = files_by_name
+        .iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(name, files)| format!("{} ({})", name, files.join(", ")))
+        .collect();
+
+    if duplicates.is_empty() {
+        CheckResult::pass(
+            "Kubernetes:contexts",
+            format!("{} contexts, no duplicates", files_by_name.len()),
+        )
+    } else {
+        CheckResult::warn(
+            "Kubernetes:contexts",
+            format!(
+                "Duplicate contexts, only the first is used: {}",
+                duplicates.join("; ")
+            ),
+            Some("Rename the context in one of the kubeconfig files"),
+        )
+    }
+}
+
+fn check_context(name: &str, reachable: bool) -> CheckResult {
+    let check_name = format!("Kubernetes:{}", name);
+    if reachable {
+        CheckResult::pass(check_name, "cluster reachable")
+    } else {
+        CheckResult::warn(
+            check_name,
+            "cluster unreachable",
+            Some(format!(
+                "Check VPN/credentials, or remove the context: kubectl config delete-context {}",
+                name
+            )),
+        )
+    }
+}
+
+fn check_krew_plugins(declared: &[String], installed: &[String]) -> CheckResult {
+    let missing: Vec<&str> = declared
+        .iter()
+        .filter(|plugin| !installed.contains(plugin))
+        .map(|plugin| plugin.as_str())
+        .collect();
+
+    if missing.is_empty() {
+        CheckResult::pass(
+            "Kubernetes:krew",
+            format!("{} plugins installed", declared.len()),
+        )
+    } else {
+        CheckResult::warn(
+            "Kubernetes:krew",
+            format!("Missing krew plugins: {}", missing.join(", ")),
+            Some(format!("Run: kubectl krew install {}", missing.join(" "))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn kubeconfig(contexts: &[&str]) -> String {
+        let mut content = String::from("apiVersion: v1\ncontexts:\n");
+        for name in contexts {
+            content.push_str(&format!("  - name: {}\n    context: {{}}\n", name));
+        }
+        content
+    }
+
+    #[test]
+    fn test_duplicate_contexts() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.yaml");
+        let b = temp.path().join("b.yaml");
+        fs::write(&a, kubeconfig(&["prod", "staging"])).unwrap();
+        fs::write(&b, kubeconfig(&["prod"])).unwrap();
+
+        let contexts = contexts_by_file(&[a.clone(), b.clone()]);
+        let result = check_duplicate_contexts(&contexts);
+        assert!(result.is_warn());
+        assert!(result.message().contains("prod"));
+        assert!(!result.message().contains("staging"));
+
+        let contexts = contexts_by_file(&[a, temp.path().join("missing.yaml")]);
+        assert!(check_duplicate_contexts(&contexts).is_pass());
+    }
+
+    #[test]
+    fn test_check_context() {
+        assert!(check_context("prod", true).is_pass());
+        assert!(check_context("prod", false).is_warn());
+    }
+
+    #[test]
+    fn test_check_krew_plugins() {
+        let declared = vec!["ctx".to_string(), "ns".to_string()];
+        assert!(check_krew_plugins(&declared, &declared).is_pass());
+
+        let result = check_krew_plugins(&declared, &["ctx".to_string()]);
+        assert!(result.is_warn());
+        assert!(result.message().contains("ns"));
+    }
+}
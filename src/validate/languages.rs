@@ -0,0 +1,225 @@
+use crate::core::command::{self, CommandRunner};
+use crate::install::shell;
+use crate::install::version_manager::{self, VersionManager};
+use crate::language::LanguageInstaller;
+use crate::validate::{CheckReport, CheckResult};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Validates that installed languages resolve through the version manager in a login shell
+pub fn validate_language_shims(
+    vm: Option<VersionManager>,
+    home_dir: &Path,
+    languages: &[Box<dyn LanguageInstaller>],
+) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    let Some(vm) = vm else {
+        return report;
+    };
+
+    let data_dir = vm.data_dir(home_dir);
+    for language in languages {
+        let Some(binary) = language.binaries().first() else {
+            continue;
+        };
+        let resolved = resolve_in_login_shell(binary);
+        report.add(check_resolution(vm, binary, resolved.as_deref(), &data_dir));
+    }
+
+    report
+}
+
+/// Resolves a command the way a fresh login shell would (`command -v`)
+///
+/// Gives up after a timeout, since an rc file that prompts or stalls would
+/// otherwise hang doctor.
+pub fn resolve_in_login_shell(binary: &str) -> Option<PathBuf> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
+
+    let output = CommandRunner::new(shell)
+        .args(["-l", "-i", "-c"])
+        .arg(format!("command -v {}", binary))
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+
+    if !output.success() {
+        return None;
+    }
+
+    // rc files may print banners; the resolved path is the last line
+    output
+        .stdout
+        .lines()
+        .rev()
+        .map(|line| line.trim())
+        .find(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+}
+
+/// Checks that a resolved binary lives under the version manager's shims or installs
+fn check_resolution(
+    vm: VersionManager,
+    binary: &str,
+    resolved: Option<&Path>,
+    data_dir: &Path,
+) -> CheckResult {
+    match resolved {
+        None => CheckResult::error(
+            format!("Shim:{}", binary),
+            "Not found in a login shell",
+            Some(format!(
+                "Ensure {} is activated in your shell rc, then restart the shell",
+                vm.display_name()
+            )),
+        ),
+        Some(path) if path.starts_with(data_dir) => CheckResult::pass(
+            format!("Shim:{}", binary),
+            format!("Resolves to {}", path.display()),
+        ),
+        Some(path) => CheckResult::warn(
+            format!("Shim:{}", binary),
+            format!(
+                "Resolves to {} instead of {} shims",
+                path.display(),
+                vm.display_name()
+            ),
+            Some(format!(
+                "Put {} on PATH before system directories and restart your shell",
+                data_dir.join("shims").display()
+            )),
+        ),
+    }
+}
+
+/// Flags other version managers' shim dirs on PATH and their hooks in `~/.zshrc`
+pub fn validate_stale_managers(active: VersionManager, home_dir: &Path) -> CheckReport {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let zshrc = std::fs::read_to_string(home_dir.join(".zshrc")).unwrap_or_default();
+    check_stale_managers(active, home_dir, &path, &zshrc)
+}
+
+fn check_stale_managers(
+    active: VersionManager,
+    home_dir: &Path,
+    path: &OsStr,
+    zshrc: &str,
+) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for (vm, shims) in version_manager::stale_shim_dirs(active, home_dir, path) {
+        report.add(CheckResult::warn(
+            format!("Version Manager:{} shims", vm.command()),
+            format!(
+                "{} is on PATH and can shadow {}",
+                shims.display(),
+                active.display_name()
+            ),
+            Some(format!(
+                "Remove the {} hook from your shell rc and restart the shell",
+                vm.command()
+            )),
+        ));
+    }
+
+    for vm in VersionManager::ALL.into_iter().filter(|vm| *vm != active) {
+        let lines = shell::find_hook_lines(zshrc, vm);
+        if lines.is_empty() {
+            continue;
+        }
+        let numbers: Vec<String> = lines.iter().map(|n| n.to_string()).collect();
+        report.add(CheckResult::warn(
+            format!("Version Manager:{} hook", vm.command()),
+            format!(
+                "~/.zshrc still activates {} (line {})",
+                vm.command(),
+                numbers.join(", ")
+            ),
+            Some(format!(
+                "Delete those lines; {} is activated from the managed block",
+                active.command()
+            )),
+        ));
+    }
+
+    if report.checks.is_empty() {
+        report.add(CheckResult::pass(
+            "Version Manager:leftovers",
+            format!("No other version manager shadows {}", active.display_name()),
+        ));
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_resolution_shim() {
+        let data_dir = Path::new("/home/user/.local/share/mise");
+        let resolved = data_dir.join("shims/python");
+        let result = check_resolution(VersionManager::Mise, "python", Some(&resolved), data_dir);
+        assert!(result.is_pass());
+    }
+
+    #[test]
+    fn test_check_resolution_install_dir() {
+        // `mise activate` puts install dirs on PATH instead of shims
+        let data_dir = Path::new("/home/user/.local/share/mise");
+        let resolved = data_dir.join("installs/python/3.12.1/bin/python");
+        let result = check_resolution(VersionManager::Mise, "python", Some(&resolved), data_dir);
+        assert!(result.is_pass());
+    }
+
+    #[test]
+    fn test_check_resolution_system_binary() {
+        let data_dir = Path::new("/home/user/.asdf");
+        let result = check_resolution(
+            VersionManager::Asdf,
+            "python",
+            Some(Path::new("/usr/bin/python")),
+            data_dir,
+        );
+        assert!(result.is_warn());
+        assert!(result.message().contains("/usr/bin/python"));
+    }
+
+    #[test]
+    fn test_check_resolution_missing() {
+        let result = check_resolution(
+            VersionManager::Asdf,
+            "node",
+            None,
+            Path::new("/home/user/.asdf"),
+        );
+        assert!(result.is_error());
+    }
+
+    #[test]
+    fn test_validate_language_shims_no_vm() {
+        let report = validate_language_shims(None, Path::new("/home/user"), &[]);
+        assert_eq!(report.total(), 0);
+    }
+
+    #[test]
+    fn test_check_stale_managers() {
+        let home = Path::new("/home/user");
+        let path = std::env::join_paths(["/home/user/.asdf/shims", "/usr/bin"]).unwrap();
+        let zshrc = "alias ll='ls -la'\n. /opt/homebrew/opt/asdf/libexec/asdf.sh\n";
+
+        let report = check_stale_managers(VersionManager::Mise, home, &path, zshrc);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            vec!["Version Manager:asdf shims", "Version Manager:asdf hook"]
+        );
+        assert!(report.checks[1].message().contains("line 2"));
+
+        let clean = check_stale_managers(VersionManager::Mise, home, OsStr::new("/usr/bin"), "");
+        assert!(clean.is_clean());
+    }
+}
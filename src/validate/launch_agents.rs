@@ -0,0 +1,295 @@
+use crate::core::command::{CommandRunner, QUICK_TIMEOUT};
+use crate::validate::{CheckReport, CheckResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Label prefix for LaunchAgents installed by this tool
+pub const MANAGED_LABEL_PREFIX: &str = "com.dotfiles-tool.";
+
+/// A user LaunchAgent found in ~/Library/LaunchAgents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchAgent {
+    pub label: String,
+    /// Location of the plist file
+    pub path: PathBuf,
+    /// Binary the agent runs (`Program`, or the first `ProgramArguments` entry)
+    pub program: Option<PathBuf>,
+}
+
+/// Validates tool-managed LaunchAgents and lists other agents and login items (macOS only)
+///
+/// An agent counts as tool-managed when it is declared in `launch_agents` in
+/// the config or its label starts with `MANAGED_LABEL_PREFIX`.
+pub fn validate_launch_agents(home_dir: &Path, declared: &[String]) -> CheckReport {
+    let mut report = CheckReport::new();
+    let agents = list_launch_agents(&home_dir.join("Library/LaunchAgents"));
+
+    let mut managed: Vec<&str> = declared.iter().map(|label| label.as_str()).collect();
+    for agent in &agents {
+        if agent.label.starts_with(MANAGED_LABEL_PREFIX) && !managed.contains(&agent.label.as_str())
+        {
+            managed.push(&agent.label);
+        }
+    }
+
+    for label in &managed {
+        let agent = agents.iter().find(|a| a.label == *label);
+        let loaded = agent.is_some() && is_loaded(label);
+        report.add(check_agent(label, agent, loaded));
+    }
+
+    let others: Vec<&str> = agents
+        .iter()
+        .map(|a| a.label.as_str())
+        .filter(|label| !managed.contains(label))
+        .collect();
+    report.add(CheckResult::pass(
+        "Launch Agents",
+        describe_list("other user agents", &others),
+    ));
+
+    if let Some(items) = login_items() {
+        let items: Vec<&str> = items.iter().map(|i| i.as_str()).collect();
+        report.add(CheckResult::pass(
+            "Login Items",
+            describe_list("login items", &items),
+        ));
+    }
+
+    report
+}
+
+/// Reads every parseable plist in a LaunchAgents directory, sorted by label
+pub fn list_launch_agents(dir: &Path) -> Vec<LaunchAgent> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut agents: Vec<LaunchAgent> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "plist"))
+        .filter_map(|path| {
+            let content = read_plist(&path)?;
+            let label = plist_string(&content, "Label")?;
+            let program = plist_string(&content, "Program")
+                .or_else(|| plist_string(&content, "ProgramArguments"))
+                .map(PathBuf::from);
+            Some(LaunchAgent {
+                label,
+                path,
+                program,
+            })
+        })
+        .collect();
+
+    agents.sort_by(|a, b| a.label.cmp(&b.label));
+    agents
+}
+
+fn check_agent(label: &str, agent: Option<&LaunchAgent>, loaded: bool) -> CheckResult {
+    let name = format!("Launch Agent:{}", label);
+
+    let Some(agent) = agent else {
+        return CheckResult::warn(
+            name,
+            "declared but not installed in ~/Library/LaunchAgents",
+            Some("Re-run `dotfiles setup`, or remove it from launch_agents in ~/.dotfiles.conf"),
+        );
+    };
+
+    if let Some(program) = &agent.program {
+        if !program.exists() {
+            return CheckResult::warn(
+                name,
+                format!("points at missing binary {}", program.display()),
+                Some(format!(
+                    "Update the path in {} and reload it with `launchctl unload` / `launchctl load -w`",
+                    agent.path.display()
+                )),
+            );
+        }
+    }
+
+    if !loaded {
+        return CheckResult::warn(
+            name,
+            "installed but not loaded",
+            Some(format!("Run: launchctl load -w {}", agent.path.display())),
+        );
+    }
+
+    CheckResult::pass(name, format!("loaded from {}", agent.path.display()))
+}
+
+fn describe_list(what: &str, items: &[&str]) -> String {
+    if items.is_empty() {
+        format!("No {}", what)
+    } else {
+        format!("{} {}: {}", items.len(), what, items.join(", "))
+    }
+}
+
+/// Reads a plist as XML, converting binary plists with `plutil`
+fn read_plist(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    if !bytes.starts_with(b"bplist") {
+        return String::from_utf8(bytes).ok();
+    }
+
+    CommandRunner::new("plutil")
+        .args(["-convert", "xml1", "-o", "-"])
+        .arg(path)
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .run()
+        .ok()
+        .map(|output| output.stdout)
+}
+
+/// Returns the first `<string>` value following `<key>key</key>` in an XML plist
+///
+/// For array values (e.g. `ProgramArguments`) this is the first element.
+fn plist_string(content: &str, key: &str) -> Option<String> {
+    let marker = format!("<key>{}</key>", key);
+    let rest = content[content.find(&marker)? + marker.len()..].trim_start();
+    let rest = rest.strip_prefix("<array>").unwrap_or(rest).trim_start();
+    let rest = rest.strip_prefix("<string>")?;
+    let value = &rest[..rest.find("</string>")?];
+
+    Some(
+        value
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+fn is_loaded(label: &str) -> bool {
+    CommandRunner::new("launchctl")
+        .args(["list", label])
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .is_ok_and(|output| output.success())
+}
+
+fn login_items() -> Option<Vec<String>> {
+    let output = CommandRunner::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to get the name of every login item",
+        ])
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .run()
+        .ok()?;
+
+    Some(
+        output
+            .stdout
+            .trim()
+            .split(", ")
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plist(label: &str, program: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>backup</string>
+    </array>
+</dict>
+</plist>"#,
+            label, program
+        )
+    }
+
+    #[test]
+    fn test_plist_string() {
+        let content = plist("com.dotfiles-tool.backup", "/usr/local/bin/dotfiles");
+        assert_eq!(
+            plist_string(&content, "Label").as_deref(),
+            Some("com.dotfiles-tool.backup")
+        );
+        assert_eq!(
+            plist_string(&content, "ProgramArguments").as_deref(),
+            Some("/usr/local/bin/dotfiles")
+        );
+        assert_eq!(plist_string(&content, "Program"), None);
+    }
+
+    #[test]
+    fn test_list_launch_agents() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("b.plist"),
+            plist("com.dotfiles-tool.sync", "/bin/sh"),
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("a.plist"),
+            plist("com.example.agent", "/bin/ls"),
+        )
+        .unwrap();
+        fs::write(temp.path().join("notes.txt"), "not a plist").unwrap();
+
+        let agents = list_launch_agents(temp.path());
+        assert_eq!(agents.len(), 2);
+        assert_eq!(agents[0].label, "com.dotfiles-tool.sync");
+        assert_eq!(agents[1].label, "com.example.agent");
+        assert_eq!(agents[1].program, Some(PathBuf::from("/bin/ls")));
+    }
+
+    #[test]
+    fn test_list_launch_agents_missing_dir() {
+        assert!(list_launch_agents(Path::new("/nonexistent/LaunchAgents")).is_empty());
+    }
+
+    #[test]
+    fn test_check_agent() {
+        let temp = TempDir::new().unwrap();
+        let binary = temp.path().join("dotfiles");
+        fs::write(&binary, "").unwrap();
+
+        let agent = LaunchAgent {
+            label: "com.dotfiles-tool.backup".to_string(),
+            path: temp.path().join("com.dotfiles-tool.backup.plist"),
+            program: Some(binary),
+        };
+        assert!(check_agent(&agent.label, Some(&agent), true).is_pass());
+        assert!(check_agent(&agent.label, Some(&agent), false).is_warn());
+        assert!(check_agent(&agent.label, None, false).is_warn());
+
+        let stale = LaunchAgent {
+            program: Some(temp.path().join("old/dotfiles")),
+            ..agent
+        };
+        let result = check_agent(&stale.label, Some(&stale), true);
+        assert!(result.is_warn());
+        assert!(result.message().contains("missing binary"));
+    }
+
+    #[test]
+    fn test_validate_launch_agents_declared_but_missing() {
+        let temp = TempDir::new().unwrap();
+        let report = validate_launch_agents(temp.path(), &["com.dotfiles-tool.backup".to_string()]);
+
+        assert!(report.checks[0].is_warn());
+        assert!(report.checks[0].message().contains("not installed"));
+    }
+}
@@ -0,0 +1,515 @@
+use crate::core::command::{self, CommandRunner};
+use crate::core::walk::{RepoWalker, ALWAYS_SKIPPED};
+use crate::install::{repos, templates};
+use crate::symlink;
+use crate::validate::{CheckReport, CheckResult};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// File names that usually hold keys or credentials
+const SENSITIVE_NAMES: &[&str] = &[
+    "id_rsa",
+    "id_dsa",
+    "id_ecdsa",
+    "id_ed25519",
+    ".netrc",
+    ".pgpass",
+    ".env",
+    "credentials",
+    "hosts.yml",
+];
+
+/// Extensions that usually mark private keys or key stores
+const SENSITIVE_EXTENSIONS: &[&str] = &["pem", "key", "p12", "pfx", "kdbx"];
+
+/// One place something in the repo will be linked or rendered to
+#[derive(Debug)]
+struct Link {
+    /// Repo-relative source, for messages
+    source: String,
+    target: PathBuf,
+    /// Top-level directory linked as a whole; anything placed below it lands in the repo
+    whole_dir: bool,
+}
+
+/// Lints the dotfiles repo itself, independent of what is linked on this machine
+///
+/// `skipped` top-level entries (see `Config::skipped_links`) are never linked,
+/// so they are left out of the collision and layout checks.
+pub fn lint_repo(
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    targets: &BTreeMap<String, String>,
+    skipped: &[String],
+) -> CheckReport {
+    let mut report = CheckReport::new();
+    report.merge(check_collisions(dotfiles_dir, home_dir, targets, skipped));
+    report.merge(check_tracked_junk(dotfiles_dir));
+    report.merge(check_sensitive_permissions(dotfiles_dir));
+    report.merge(check_broken_links(dotfiles_dir));
+    report.merge(check_layout(dotfiles_dir, skipped));
+    report
+}
+
+/// Flags repo entries that would be linked or rendered onto the same path
+///
+/// Targets are compared case-insensitively, since macOS volumes usually are.
+/// A `[targets]` source that the top-level pass also links (because it is not
+/// in `.dotfilesignore`) counts as a collision too.
+pub fn check_collisions(
+    dotfiles_dir: &Path,
+    home_dir: &Path,
+    targets: &BTreeMap<String, String>,
+    skipped: &[String],
+) -> CheckReport {
+    let mut report = CheckReport::new();
+    let top_level = RepoWalker::new(dotfiles_dir)
+        .max_depth(1)
+        .skip(skipped)
+        .entries()
+        .unwrap_or_default();
+
+    let mut links: Vec<Link> = top_level
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?;
+            Some(Link {
+                source: name.to_string_lossy().into_owned(),
+                target: home_dir.join(name),
+                whole_dir: path.is_dir() && !path.is_symlink(),
+            })
+        })
+        .collect();
+
+    for (source, target) in targets {
+        links.push(Link {
+            source: source.clone(),
+            target: symlink::expand_target(target, home_dir),
+            whole_dir: false,
+        });
+
+        if let Some(first) = Path::new(source).components().next() {
+            let top = dotfiles_dir.join(first);
+            if top_level.contains(&top) {
+                report.add(CheckResult::error(
+                    format!("Lint:{}", source),
+                    format!(
+                        "Linked twice: via [targets] and via ~/{}",
+                        first.as_os_str().to_string_lossy()
+                    ),
+                    Some(format!(
+                        "Add {} to .dotfilesignore",
+                        first.as_os_str().to_string_lossy()
+                    )),
+                ));
+            }
+        }
+    }
+
+    for template in templates::find_templates(dotfiles_dir, home_dir).unwrap_or_default() {
+        links.push(Link {
+            source: template
+                .source
+                .strip_prefix(dotfiles_dir)
+                .unwrap_or(&template.source)
+                .display()
+                .to_string(),
+            target: template.target,
+            whole_dir: false,
+        });
+    }
+
+    for (i, a) in links.iter().enumerate() {
+        for b in &links[i + 1..] {
+            if let Some(message) = collision(a, b) {
+                report.add(CheckResult::error(
+                    format!("Lint:{}", b.source),
+                    message,
+                    Some("Rename one of them or move it to a [targets] entry"),
+                ));
+            }
+        }
+    }
+
+    if report.checks.is_empty() {
+        report.add(CheckResult::pass(
+            "Lint:collisions",
+            format!("{} link targets, none overlapping", links.len()),
+        ));
+    }
+
+    report
+}
+
+fn collision(a: &Link, b: &Link) -> Option<String> {
+    let lower = |path: &Path| path.to_string_lossy().to_lowercase();
+    let (a_target, b_target) = (lower(&a.target), lower(&b.target));
+
+    if a_target == b_target {
+        return Some(format!(
+            "{} and {} both link to {}",
+            a.source,
+            b.source,
+            b.target.display()
+        ));
+    }
+
+    let (outer, inner) = if b_target.starts_with(&format!("{}/", a_target)) {
+        (a, b)
+    } else if a_target.starts_with(&format!("{}/", b_target)) {
+        (b, a)
+    } else {
+        return None;
+    };
+
+    outer.whole_dir.then(|| {
+        format!(
+            "{} links to {}, inside {} which is linked as a whole (it would write into the repo)",
+            inner.source,
+            inner.target.display(),
+            outer.source
+        )
+    })
+}
+
+/// Flags tracked files the tool always skips (e.g. `.DS_Store`)
+pub fn check_tracked_junk(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    if !repos::is_git_repo(dotfiles_dir) {
+        return report;
+    }
+
+    let Ok(output) = CommandRunner::new("git")
+        .arg("-C")
+        .arg(dotfiles_dir)
+        .arg("ls-files")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    else {
+        return report;
+    };
+
+    let junk: Vec<&str> = output
+        .stdout
+        .lines()
+        .filter(|path| {
+            Path::new(path).components().any(|component| {
+                ALWAYS_SKIPPED.contains(&component.as_os_str().to_string_lossy().as_ref())
+            })
+        })
+        .collect();
+
+    for path in &junk {
+        report.add(CheckResult::warn(
+            format!("Lint:{}", path),
+            "Tracked, but never linked",
+            Some(format!(
+                "Run: git rm --cached '{}' and add it to .gitignore",
+                path
+            )),
+        ));
+    }
+
+    if junk.is_empty() {
+        report.add(CheckResult::pass(
+            "Lint:tracked files",
+            "No tracked files that are always skipped",
+        ));
+    }
+
+    report
+}
+
+/// Returns true if a file name looks like it holds keys or credentials
+pub fn looks_sensitive(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    SENSITIVE_NAMES.contains(&name.as_str())
+        || name.starts_with(".env.")
+        || SENSITIVE_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// Flags sensitive-looking files that other users can read
+///
+/// Ignored files are included: a linked directory exposes them all the same.
+pub fn check_sensitive_permissions(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for path in repo_entries(dotfiles_dir) {
+        if !path.is_file() || path.is_symlink() || !looks_sensitive(&path) {
+            continue;
+        }
+        if is_world_readable(&path) {
+            let rel = relative(&path, dotfiles_dir);
+            report.add(CheckResult::warn(
+                format!("Lint:{}", rel),
+                "Looks sensitive and is world-readable",
+                Some(format!(
+                    "Run: chmod 600 {}, and consider keeping it out of the repo",
+                    path.display()
+                )),
+            ));
+        }
+    }
+
+    if report.checks.is_empty() {
+        report.add(CheckResult::pass(
+            "Lint:permissions",
+            "No world-readable files that look sensitive",
+        ));
+    }
+
+    report
+}
+
+#[cfg(unix)]
+fn is_world_readable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.permissions().mode() & 0o004 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_world_readable(_path: &Path) -> bool {
+    false
+}
+
+/// Flags symlinks inside the repo whose target does not exist
+pub fn check_broken_links(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for path in repo_entries(dotfiles_dir) {
+        if path.is_symlink() && !path.exists() {
+            let points_to = std::fs::read_link(&path)
+                .map(|dest| dest.display().to_string())
+                .unwrap_or_default();
+            report.add(CheckResult::error(
+                format!("Lint:{}", relative(&path, dotfiles_dir)),
+                format!("Broken symlink (points to {})", points_to),
+                Some(format!("Fix or remove: rm {}", path.display())),
+            ));
+        }
+    }
+
+    if report.checks.is_empty() {
+        report.add(CheckResult::pass(
+            "Lint:broken links",
+            "No broken symlinks in the repo",
+        ));
+    }
+
+    report
+}
+
+/// Flags stow-style packages (`zsh/.zshrc`), which this tool links as `~/zsh`
+///
+/// Top-level entries are linked into `$HOME` as-is, so a package directory
+/// next to flat dotfiles is almost always a half-finished migration.
+pub fn check_layout(dotfiles_dir: &Path, skipped: &[String]) -> CheckReport {
+    let mut report = CheckReport::new();
+    let top_level = RepoWalker::new(dotfiles_dir)
+        .max_depth(1)
+        .skip(skipped)
+        .entries()
+        .unwrap_or_default();
+
+    let is_dotted = |path: &Path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+    };
+    let flat: Vec<&PathBuf> = top_level.iter().filter(|path| is_dotted(path)).collect();
+    let packages: Vec<String> = top_level
+        .iter()
+        .filter(|path| path.is_dir() && !is_dotted(path))
+        .filter(|path| {
+            std::fs::read_dir(path)
+                .map(|entries| entries.flatten().any(|entry| is_dotted(&entry.path())))
+                .unwrap_or(false)
+        })
+        .map(|path| relative(path, dotfiles_dir))
+        .collect();
+
+    if packages.is_empty() {
+        report.add(CheckResult::pass(
+            "Lint:layout",
+            "Flat layout (entries link straight into $HOME)",
+        ));
+        return report;
+    }
+
+    let example = &packages[0];
+    let message = if flat.is_empty() {
+        format!(
+            "Stow package layout ({}); each package would be linked as ~/<package>",
+            packages.join(", ")
+        )
+    } else {
+        format!(
+            "Mixed layout: packages ({}) next to flat dotfiles",
+            packages.join(", ")
+        )
+    };
+    report.add(CheckResult::warn(
+        "Lint:layout",
+        message,
+        Some(format!(
+            "Move package contents to the top level (e.g. {}/.zshrc -> .zshrc), or list them in [targets]",
+            example
+        )),
+    ));
+
+    report
+}
+
+/// Every file and directory in the repo, ignored or not, except `.git` internals
+fn repo_entries(dotfiles_dir: &Path) -> Vec<PathBuf> {
+    RepoWalker::new(dotfiles_dir)
+        .respect_ignores(false)
+        .entries()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|path| {
+            !path
+                .strip_prefix(dotfiles_dir)
+                .unwrap_or(path)
+                .components()
+                .any(|component| component == Component::Normal(".git".as_ref()))
+        })
+        .collect()
+}
+
+fn relative(path: &Path, dotfiles_dir: &Path) -> String {
+    path.strip_prefix(dotfiles_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn names(report: &CheckReport) -> Vec<&str> {
+        report.checks.iter().map(|c| c.name()).collect()
+    }
+
+    #[test]
+    fn test_check_collisions_case_and_nested() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let home = temp.path().join("home");
+        fs::create_dir_all(dotfiles.join(".config/karabiner")).unwrap();
+        fs::write(dotfiles.join(".zshrc"), "").unwrap();
+        fs::write(dotfiles.join(".dotfilesignore"), "mac\n").unwrap();
+
+        let targets = BTreeMap::from([
+            ("mac/.zshrc".to_string(), "~/.ZshRC".to_string()),
+            (
+                "mac/karabiner.json".to_string(),
+                "~/.config/karabiner/karabiner.json".to_string(),
+            ),
+        ]);
+        let report = check_collisions(&dotfiles, &home, &targets, &[]);
+
+        assert_eq!(report.error_count(), 2);
+        assert_eq!(
+            names(&report),
+            vec!["Lint:mac/karabiner.json", "Lint:mac/.zshrc"]
+        );
+        assert!(report.checks[0].message().contains("linked as a whole"));
+        assert!(report.checks[1].message().contains("both link to"));
+    }
+
+    #[test]
+    fn test_check_collisions_linked_twice() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(dotfiles.join("mac")).unwrap();
+        fs::write(dotfiles.join("mac/karabiner.json"), "").unwrap();
+
+        let targets = BTreeMap::from([(
+            "mac/karabiner.json".to_string(),
+            "~/karabiner.json".to_string(),
+        )]);
+        let report = check_collisions(&dotfiles, temp.path(), &targets, &[]);
+        assert!(report.has_errors());
+        assert!(report.checks[0].message().starts_with("Linked twice"));
+        assert!(
+            check_collisions(&dotfiles, temp.path(), &targets, &["mac".to_string()]).is_clean()
+        );
+
+        fs::write(dotfiles.join(".dotfilesignore"), "mac\n").unwrap();
+        assert!(check_collisions(&dotfiles, temp.path(), &targets, &[]).is_clean());
+    }
+
+    #[test]
+    fn test_looks_sensitive() {
+        assert!(looks_sensitive(Path::new(".ssh/id_ed25519")));
+        assert!(looks_sensitive(Path::new("certs/server.PEM")));
+        assert!(looks_sensitive(Path::new(".env.local")));
+        assert!(!looks_sensitive(Path::new(".ssh/id_ed25519.pub")));
+        assert!(!looks_sensitive(Path::new(".zshrc")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_sensitive_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let key = temp.path().join(".ssh/id_rsa");
+        fs::create_dir_all(key.parent().unwrap()).unwrap();
+        fs::write(&key, "key").unwrap();
+        fs::set_permissions(&key, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let report = check_sensitive_permissions(temp.path());
+        assert_eq!(names(&report), vec!["Lint:.ssh/id_rsa"]);
+        assert!(report.checks[0].is_warn());
+
+        fs::set_permissions(&key, fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(check_sensitive_permissions(temp.path()).is_clean());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_broken_links() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".config")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent/x", temp.path().join(".config/dead")).unwrap();
+        std::os::unix::fs::symlink(".config", temp.path().join("alive")).unwrap();
+
+        let report = check_broken_links(temp.path());
+        assert_eq!(names(&report), vec!["Lint:.config/dead"]);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_check_layout() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join(".zshrc"), "").unwrap();
+        fs::create_dir_all(temp.path().join("scripts")).unwrap();
+        fs::write(temp.path().join("scripts/install.sh"), "").unwrap();
+        assert!(check_layout(temp.path(), &[]).is_clean());
+
+        fs::create_dir_all(temp.path().join("tmux")).unwrap();
+        fs::write(temp.path().join("tmux/.tmux.conf"), "").unwrap();
+        let report = check_layout(temp.path(), &[]);
+        assert!(report.checks[0].is_warn());
+        assert!(report.checks[0].message().starts_with("Mixed layout"));
+        assert!(report.checks[0].message().contains("tmux"));
+
+        // Skipped entries are never linked, so they aren't packages either
+        assert!(check_layout(temp.path(), &["tmux".to_string()]).is_clean());
+    }
+}
@@ -0,0 +1,73 @@
+use crate::core::config::MachineSettings;
+use crate::detect::os::OS;
+use crate::install::machine::{self, Drift};
+use crate::validate::{CheckReport, CheckResult};
+
+/// Validates that the hostname, timezone, and locale match `[machine]` in the config
+pub fn validate_machine(settings: &MachineSettings, os: OS) -> CheckReport {
+    let drift = machine::drift(settings, os);
+    let mut report = CheckReport::new();
+
+    for (setting, desired) in [
+        (machine::Setting::Hostname, &settings.hostname),
+        (machine::Setting::Timezone, &settings.timezone),
+        (machine::Setting::Locale, &settings.locale),
+    ] {
+        let Some(desired) = desired else {
+            continue;
+        };
+        let name = format!("Machine:{}", setting.name());
+        match drift.iter().find(|d| d.setting == setting) {
+            Some(d) => report.add(drift_result(&name, d, os)),
+            None => report.add(CheckResult::pass(name, desired.clone())),
+        }
+    }
+
+    report
+}
+
+fn drift_result(name: &str, drift: &Drift, os: OS) -> CheckResult {
+    let current = drift.current.as_deref().unwrap_or("unknown");
+    CheckResult::warn(
+        name,
+        format!(
+            "{} is {}, config says {}",
+            drift.setting.name(),
+            current,
+            drift.desired
+        ),
+        Some(format!(
+            "Run: {} (or dotfiles setup)",
+            machine::display_commands(&machine::commands(drift.setting, &drift.desired, os))
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_result() {
+        let drift = Drift {
+            setting: machine::Setting::Timezone,
+            current: Some("UTC".to_string()),
+            desired: "Europe/Berlin".to_string(),
+        };
+        let result = drift_result("Machine:timezone", &drift, OS::Linux);
+        assert!(!result.is_pass());
+        assert_eq!(
+            result.message(),
+            "timezone is UTC, config says Europe/Berlin"
+        );
+        assert_eq!(
+            result.suggestion(),
+            Some("Run: sudo timedatectl set-timezone Europe/Berlin (or dotfiles setup)")
+        );
+
+        assert_eq!(
+            validate_machine(&MachineSettings::default(), OS::Linux).total(),
+            0
+        );
+    }
+}
@@ -1,15 +1,91 @@
+pub mod aws;
+pub mod backups;
+pub mod brewfile;
+pub mod cache;
 pub mod claude;
 pub mod configs;
+pub mod copies;
 pub mod dependencies;
+pub mod git;
+pub mod homebrew;
 pub mod iterm;
+pub mod karabiner;
+pub mod kubernetes;
+pub mod languages;
+pub mod launch_agents;
+pub mod layout;
+pub mod machine;
+pub mod nvim;
+pub mod obsidian;
 pub mod paths;
+pub mod plugins;
+pub mod repo;
+pub mod score;
+pub mod secrets;
 pub mod shell;
+pub mod sources;
 pub mod symlinks;
+pub mod tmux;
+pub mod unmanaged;
+pub mod updates;
 
+use crate::core::timing::{self, Timing};
+use crate::error::Result;
 use colored::Colorize;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Number of slowest steps listed in the report summary
+const SLOWEST_SHOWN: usize = 3;
+
+/// A change `doctor --fix` can make to resolve a failed check
+///
+/// Only changes that can't lose data: links are created where nothing, or
+/// only a dangling link, is in the way, packages are only added, and
+/// source lines are appended to the RC file. Untracking a `.env` leaves the
+/// file itself in place.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FixAction {
+    /// Create a missing link at `target`, replacing a dangling one
+    Relink { source: PathBuf, target: PathBuf },
+    /// Install missing packages with the package backend
+    InstallPackages { packages: Vec<String> },
+    /// Add a line sourcing `script` to `shell_rc`
+    SourceScript {
+        shell_rc: PathBuf,
+        script: PathBuf,
+        name: String,
+    },
+    /// Stop tracking `path` (relative to `repo`) and list it in its directory's `.gitignore`
+    UntrackEnv { repo: PathBuf, path: String },
+}
+
+impl FixAction {
+    /// Describes the fix for progress output
+    pub fn describe(&self) -> String {
+        match self {
+            FixAction::Relink { source, target } => {
+                format!("Link {} -> {}", target.display(), source.display())
+            }
+            FixAction::InstallPackages { packages } => {
+                format!("Install {}", packages.join(", "))
+            }
+            FixAction::SourceScript { shell_rc, name, .. } => {
+                format!("Source {} in {}", name, shell_rc.display())
+            }
+            FixAction::UntrackEnv { path, .. } => {
+                format!("Untrack {} and add it to .gitignore", path)
+            }
+        }
+    }
+}
 
 /// Result of a validation check
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
 pub enum CheckResult {
     /// Check passed successfully
     Pass { name: String, message: String },
@@ -18,12 +94,16 @@ pub enum CheckResult {
         name: String,
         message: String,
         suggestion: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fix: Option<FixAction>,
     },
     /// Check failed with errors
     Error {
         name: String,
         message: String,
         suggestion: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fix: Option<FixAction>,
     },
 }
 
@@ -46,6 +126,7 @@ impl CheckResult {
             name: name.into(),
             message: message.into(),
             suggestion: suggestion.map(|s| s.into()),
+            fix: None,
         }
     }
 
@@ -59,7 +140,17 @@ impl CheckResult {
             name: name.into(),
             message: message.into(),
             suggestion: suggestion.map(|s| s.into()),
+            fix: None,
+        }
+    }
+
+    /// Attaches a fix `doctor --fix` can apply; passing checks are left as they are
+    pub fn with_fix(mut self, action: FixAction) -> Self {
+        match &mut self {
+            CheckResult::Pass { .. } => {}
+            CheckResult::Warn { fix, .. } | CheckResult::Error { fix, .. } => *fix = Some(action),
         }
+        self
     }
 
     /// Returns true if this is a passing check
@@ -86,6 +177,20 @@ impl CheckResult {
         }
     }
 
+    /// Returns the category, the part of the name before any `:`
+    pub fn category(&self) -> &str {
+        self.name().split(':').next().unwrap_or("General")
+    }
+
+    /// Returns `ok`, `warning`, or `error`
+    pub fn severity(&self) -> &'static str {
+        match self {
+            CheckResult::Pass { .. } => "ok",
+            CheckResult::Warn { .. } => "warning",
+            CheckResult::Error { .. } => "error",
+        }
+    }
+
     /// Returns the message
     pub fn message(&self) -> &str {
         match self {
@@ -104,6 +209,15 @@ impl CheckResult {
         }
     }
 
+    /// Returns the fix `doctor --fix` can apply, if any
+    pub fn fix(&self) -> Option<&FixAction> {
+        match self {
+            CheckResult::Pass { .. } => None,
+            CheckResult::Warn { fix, .. } => fix.as_ref(),
+            CheckResult::Error { fix, .. } => fix.as_ref(),
+        }
+    }
+
     /// Formats this check result with colors
     pub fn format_colored(&self) -> String {
         match self {
@@ -114,6 +228,7 @@ impl CheckResult {
                 name,
                 message,
                 suggestion,
+                ..
             } => {
                 let mut output = format!("  {} {} - {}", "⚠".yellow(), name.bold(), message);
                 if let Some(fix) = suggestion {
@@ -125,6 +240,7 @@ impl CheckResult {
                 name,
                 message,
                 suggestion,
+                ..
             } => {
                 let mut output = format!("  {} {} - {}", "✗".red(), name.bold(), message);
                 if let Some(fix) = suggestion {
@@ -137,9 +253,57 @@ impl CheckResult {
 }
 
 /// Report containing multiple check results
+///
+/// Serializes with a `summary` of the counts and overall status, and each
+/// check with its `category` and `severity` alongside its own fields.
 #[derive(Debug, Clone, Default)]
 pub struct CheckReport {
     pub checks: Vec<CheckResult>,
+    /// Time spent per validation step
+    pub timings: Vec<Timing>,
+    /// Total time spent producing this report
+    pub wall_time: Duration,
+}
+
+/// Check counts and the overall status of a report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportSummary {
+    /// `ok`, `warnings`, or `errors`
+    pub status: String,
+    pub passed: usize,
+    pub warnings: usize,
+    pub errors: usize,
+    pub total: usize,
+}
+
+/// A check as serialized in a report
+#[derive(Serialize)]
+struct CheckEntry<'a> {
+    category: &'a str,
+    severity: &'a str,
+    #[serde(flatten)]
+    result: &'a CheckResult,
+}
+
+impl Serialize for CheckReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let checks: Vec<CheckEntry> = self
+            .checks
+            .iter()
+            .map(|result| CheckEntry {
+                category: result.category(),
+                severity: result.severity(),
+                result,
+            })
+            .collect();
+
+        let mut report = serializer.serialize_struct("CheckReport", 4)?;
+        report.serialize_field("summary", &self.counts())?;
+        report.serialize_field("checks", &checks)?;
+        report.serialize_field("timings", &self.timings)?;
+        report.serialize_field("wall_time_ms", &(self.wall_time.as_millis() as u64))?;
+        report.end()
+    }
 }
 
 impl CheckReport {
@@ -153,6 +317,26 @@ impl CheckReport {
         self.checks.push(result);
     }
 
+    /// Runs a validation step and records how long it took under `name`
+    pub fn timed(name: impl Into<String>, check: impl FnOnce() -> CheckReport) -> CheckReport {
+        let (mut report, elapsed) = timing::measure(check);
+        report.timings.push(Timing::new(name, elapsed));
+        report.wall_time = elapsed;
+        report
+    }
+
+    /// Appends another report's checks, timings, and wall time
+    pub fn merge(&mut self, other: CheckReport) {
+        self.checks.extend(other.checks);
+        self.timings.extend(other.timings);
+        self.wall_time += other.wall_time;
+    }
+
+    /// Returns the `n` slowest steps, slowest first
+    pub fn slowest(&self, n: usize) -> Vec<&Timing> {
+        timing::slowest(&self.timings, n)
+    }
+
     /// Returns the number of passing checks
     pub fn pass_count(&self) -> usize {
         self.checks.iter().filter(|c| c.is_pass()).count()
@@ -168,6 +352,19 @@ impl CheckReport {
         self.checks.iter().filter(|c| c.is_error()).count()
     }
 
+    /// Returns the check counts and overall status
+    pub fn counts(&self) -> ReportSummary {
+        ReportSummary {
+            status: crate::core::exit::ExitStatus::from_report(self)
+                .label()
+                .to_string(),
+            passed: self.pass_count(),
+            warnings: self.warn_count(),
+            errors: self.error_count(),
+            total: self.total(),
+        }
+    }
+
     /// Returns true if all checks passed (no warnings or errors)
     pub fn is_clean(&self) -> bool {
         self.warn_count() == 0 && self.error_count() == 0
@@ -192,13 +389,10 @@ impl CheckReport {
             std::collections::HashMap::new();
 
         for check in &self.checks {
-            let category = check
-                .name()
-                .split(':')
-                .next()
-                .unwrap_or("General")
-                .to_string();
-            categories.entry(category).or_default().push(check);
+            categories
+                .entry(check.category().to_string())
+                .or_default()
+                .push(check);
         }
 
         // Sort categories for consistent output
@@ -232,9 +426,31 @@ impl CheckReport {
 
         output.push_str(&format!("  Total: {} checks\n", self.total()));
 
+        if !self.wall_time.is_zero() {
+            output.push_str(&format!(
+                "  Completed in {}\n",
+                timing::format_duration(self.wall_time)
+            ));
+        }
+
+        let slowest = self.slowest(SLOWEST_SHOWN);
+        if !slowest.is_empty() {
+            let entries: Vec<String> = slowest.iter().map(|t| t.display()).collect();
+            output.push_str(
+                &format!("  Slowest: {}\n", entries.join(", "))
+                    .dimmed()
+                    .to_string(),
+            );
+        }
+
         output
     }
 
+    /// Serializes the report as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
     /// Returns a simple summary string
     pub fn summary(&self) -> String {
         format!(
@@ -364,4 +580,79 @@ mod tests {
         assert!(formatted.contains("Warning"));
         assert!(formatted.contains("Fix"));
     }
+
+    #[test]
+    fn test_check_report_timed_and_merge() {
+        let mut overall = CheckReport::new();
+        overall.merge(CheckReport::timed("Dependencies", || {
+            let mut report = CheckReport::new();
+            report.add(CheckResult::pass("stow", "Installed"));
+            report
+        }));
+        overall.merge(CheckReport::timed("Symlinks", CheckReport::new));
+
+        assert_eq!(overall.total(), 1);
+        assert_eq!(overall.timings.len(), 2);
+        assert_eq!(overall.timings[0].name, "Dependencies");
+        assert!(overall.format_colored().contains("Slowest"));
+    }
+
+    #[test]
+    fn test_check_report_to_json() {
+        let mut report = CheckReport::new();
+        report.add(CheckResult::warn(
+            "Pin:tmux",
+            "Not pinned",
+            Some("brew pin tmux"),
+        ));
+        report
+            .timings
+            .push(Timing::new("Brew packages", Duration::from_millis(1200)));
+        report.wall_time = Duration::from_millis(1500);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["checks"][0]["status"], "warn");
+        assert_eq!(json["checks"][0]["suggestion"], "brew pin tmux");
+        assert_eq!(json["timings"][0]["duration_ms"], 1200);
+        assert_eq!(json["wall_time_ms"], 1500);
+        assert_eq!(json["checks"][0]["category"], "Pin");
+        assert_eq!(json["checks"][0]["severity"], "warning");
+        assert_eq!(json["summary"]["status"], "warnings");
+        assert_eq!(json["summary"]["warnings"], 1);
+        assert_eq!(json["summary"]["total"], 1);
+
+        // Exports stay readable as plain check results
+        let checks: Vec<CheckResult> = serde_json::from_value(json["checks"].clone()).unwrap();
+        assert_eq!(checks, report.checks);
+
+        let yaml = serde_yaml::to_string(&report).unwrap();
+        assert!(yaml.contains("severity: warning"));
+        assert!(yaml.contains("status: warnings"));
+    }
+
+    #[test]
+    fn test_check_result_with_fix() {
+        let fix = FixAction::InstallPackages {
+            packages: vec!["tmux".to_string()],
+        };
+        assert!(CheckResult::pass("A", "ok")
+            .with_fix(fix.clone())
+            .fix()
+            .is_none());
+
+        let result = CheckResult::error("Essential Package", "Missing", None::<String>)
+            .with_fix(fix.clone());
+        assert_eq!(result.fix(), Some(&fix));
+        assert_eq!(fix.describe(), "Install tmux");
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains(r#""kind":"install_packages""#));
+        let parsed: CheckResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, result);
+
+        let old: CheckResult =
+            serde_json::from_str(r#"{"status":"warn","name":"A","message":"m","suggestion":null}"#)
+                .unwrap();
+        assert!(old.fix().is_none());
+    }
 }
@@ -0,0 +1,113 @@
+use crate::detect::backend;
+use crate::install::nvim::{self, HealthArea, HealthItem, HealthLevel};
+use crate::validate::{CheckReport, CheckResult};
+
+/// Validates nvim providers, clipboard, and tree-sitter via `:checkhealth`
+pub fn validate_nvim() -> CheckReport {
+    match nvim::checkhealth() {
+        Some(output) => check_health_items(&nvim::parse_checkhealth(&output)),
+        None => {
+            let mut report = CheckReport::new();
+            report.add(CheckResult::warn(
+                "Nvim:checkhealth",
+                "Could not run :checkhealth headlessly",
+                Some("Run inside nvim: :checkhealth"),
+            ));
+            report
+        }
+    }
+}
+
+/// Maps parsed health items to one check per area that appeared in the output
+pub fn check_health_items(items: &[HealthItem]) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for area in HealthArea::ALL {
+        let area_items: Vec<&HealthItem> = items.iter().filter(|item| item.area == area).collect();
+        if area_items.is_empty() {
+            continue;
+        }
+
+        let name = format!("Nvim:{}", area.label());
+        let worst = area_items.iter().max_by_key(|item| item.level).unwrap();
+        let problems = area_items
+            .iter()
+            .filter(|item| item.level != HealthLevel::Ok)
+            .count();
+        let message = if problems > 1 {
+            format!("{} (+{} more)", worst.message, problems - 1)
+        } else {
+            worst.message.clone()
+        };
+
+        report.add(match worst.level {
+            HealthLevel::Ok => CheckResult::pass(name, "healthy"),
+            HealthLevel::Warning => CheckResult::warn(name, message, Some(suggestion(area))),
+            HealthLevel::Error => CheckResult::error(name, message, Some(suggestion(area))),
+        });
+    }
+
+    report
+}
+
+fn suggestion(area: HealthArea) -> String {
+    match area {
+        HealthArea::Python3 => "Run: dotfiles doctor --fix (installs pynvim)".to_string(),
+        HealthArea::Node => {
+            "Run: dotfiles doctor --fix (installs the neovim npm package)".to_string()
+        }
+        HealthArea::Clipboard if cfg!(target_os = "macos") => {
+            "pbcopy should be present; check your PATH".to_string()
+        }
+        HealthArea::Clipboard => "Install wl-clipboard, xclip, or xsel".to_string(),
+        HealthArea::TreeSitter => {
+            format!(
+                "Run: {}, then :TSUpdate in nvim",
+                backend::install_command(&["tree-sitter"])
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(area: HealthArea, level: HealthLevel, message: &str) -> HealthItem {
+        HealthItem {
+            area,
+            level,
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_health_items() {
+        let items = vec![
+            item(HealthArea::Clipboard, HealthLevel::Ok, "pbcopy"),
+            item(HealthArea::Python3, HealthLevel::Warning, "no pynvim"),
+            item(HealthArea::Python3, HealthLevel::Error, "provider error"),
+            item(HealthArea::Node, HealthLevel::Warning, "missing neovim"),
+        ];
+
+        let report = check_health_items(&items);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Nvim:python3 provider",
+                "Nvim:node provider",
+                "Nvim:clipboard"
+            ]
+        );
+        assert!(report.checks[0].is_error());
+        assert_eq!(report.checks[0].message(), "provider error (+1 more)");
+        assert!(report.checks[1].is_warn());
+        assert!(report.checks[2].is_pass());
+    }
+
+    #[test]
+    fn test_check_health_items_empty() {
+        assert!(check_health_items(&[]).checks.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::detect::tools;
+use crate::install::obsidian;
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Validates the configured Obsidian vault and obsidian-cli default
+pub fn validate_obsidian(vault: &Path, dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    report.add(check_vault_exists(vault));
+    if !vault.is_dir() {
+        return report;
+    }
+
+    if dotfiles_dir.join(obsidian::REPO_CONFIG_DIR).is_dir() {
+        report.add(check_vault_config_linked(vault));
+    }
+
+    if tools::is_installed("obsidian-cli") {
+        report.add(check_default_vault(
+            vault,
+            obsidian::default_vault().as_deref(),
+        ));
+    }
+
+    report
+}
+
+fn check_vault_exists(vault: &Path) -> CheckResult {
+    if vault.is_dir() {
+        CheckResult::pass("Obsidian:vault", format!("{}", vault.display()))
+    } else {
+        CheckResult::error(
+            "Obsidian:vault",
+            format!("{} does not exist", vault.display()),
+            Some("Create or sync the vault, or fix [obsidian] vault in ~/.dotfiles.conf"),
+        )
+    }
+}
+
+fn check_vault_config_linked(vault: &Path) -> CheckResult {
+    let config_dir = vault.join(".obsidian");
+    let linked = std::fs::read_dir(&config_dir)
+        .map(|entries| entries.flatten().any(|e| e.path().is_symlink()))
+        .unwrap_or(false);
+
+    if linked {
+        CheckResult::pass(
+            "Obsidian:config",
+            format!("{} linked from dotfiles", config_dir.display()),
+        )
+    } else {
+        CheckResult::warn(
+            "Obsidian:config",
+            format!(
+                "{} has no config linked from dotfiles",
+                config_dir.display()
+            ),
+            Some("Run: dotfiles setup"),
+        )
+    }
+}
+
+fn check_default_vault(vault: &Path, default: Option<&str>) -> CheckResult {
+    let expected = obsidian::vault_name(vault).unwrap_or_default();
+
+    match default {
+        Some(name) if name == expected => CheckResult::pass(
+            "Obsidian:default vault",
+            format!("obsidian-cli uses {}", name),
+        ),
+        Some(name) => CheckResult::warn(
+            "Obsidian:default vault",
+            format!("obsidian-cli uses {} instead of {}", name, expected),
+            Some(format!("Run: obsidian-cli set-default {}", expected)),
+        ),
+        None => CheckResult::warn(
+            "Obsidian:default vault",
+            "obsidian-cli has no default vault",
+            Some(format!("Run: obsidian-cli set-default {}", expected)),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_vault_is_error() {
+        let temp = TempDir::new().unwrap();
+        let report = validate_obsidian(&temp.path().join("Notes"), temp.path());
+
+        assert_eq!(report.checks.len(), 1);
+        assert!(report.checks[0].is_error());
+    }
+
+    #[test]
+    fn test_vault_config_linked() {
+        let temp = TempDir::new().unwrap();
+        let dotfiles = temp.path().join("dotfiles");
+        let vault = temp.path().join("Notes");
+        fs::create_dir_all(dotfiles.join("obsidian/snippets")).unwrap();
+        fs::create_dir_all(&vault).unwrap();
+
+        assert!(check_vault_config_linked(&vault).is_warn());
+
+        obsidian::link_vault_config(&dotfiles, &vault).unwrap();
+        assert!(check_vault_config_linked(&vault).is_pass());
+    }
+
+    #[test]
+    fn test_check_default_vault() {
+        let vault = Path::new("/Users/me/Notes");
+        assert!(check_default_vault(vault, Some("Notes")).is_pass());
+        assert!(check_default_vault(vault, Some("Work")).is_warn());
+        assert!(check_default_vault(vault, None).is_warn());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::core::walk::RepoWalker;
+use crate::validate::cache::CheckCache;
 use crate::validate::{CheckReport, CheckResult};
 use regex::Regex;
 use std::fs;
@@ -75,7 +77,10 @@ pub fn scan_file(file_path: &Path) -> CheckResult {
 }
 
 /// Scans a directory for hardcoded paths in config files
-pub fn scan_directory(dir_path: &Path) -> CheckReport {
+///
+/// Only the top level is scanned. A file's findings are kept in `cache`, so
+/// it is read again only after its size or modification time changes.
+pub fn scan_directory(dir_path: &Path, cache: &CheckCache) -> CheckReport {
     let mut report = CheckReport::new();
 
     if !dir_path.exists() {
@@ -92,22 +97,18 @@ pub fn scan_directory(dir_path: &Path) -> CheckReport {
         "sh", "bash", "zsh", "fish", "rc", "conf", "config", "toml", "yaml", "yml",
     ];
 
-    match fs::read_dir(dir_path) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                if path.is_file() {
-                    if let Some(ext) = path.extension() {
-                        if config_extensions.contains(&ext.to_str().unwrap_or("")) {
-                            report.add(scan_file(&path));
-                        }
-                    } else if path.file_name().is_some() {
-                        // Check for dotfiles without extension
-                        let name = path.file_name().unwrap().to_str().unwrap_or("");
-                        if name.starts_with('.') {
-                            report.add(scan_file(&path));
-                        }
+    match RepoWalker::new(dir_path).max_depth(1).files() {
+        Ok(files) => {
+            for path in files {
+                if let Some(ext) = path.extension() {
+                    if config_extensions.contains(&ext.to_str().unwrap_or("")) {
+                        report.add(cache.check("paths", &path, &[], || scan_file(&path)));
+                    }
+                } else if path.file_name().is_some() {
+                    // Check for dotfiles without extension
+                    let name = path.file_name().unwrap().to_str().unwrap_or("");
+                    if name.starts_with('.') {
+                        report.add(cache.check("paths", &path, &[], || scan_file(&path)));
                     }
                 }
             }
@@ -203,7 +204,7 @@ mod tests {
     #[test]
     fn test_scan_directory_empty() {
         let temp_dir = TempDir::new().unwrap();
-        let report = scan_directory(temp_dir.path());
+        let report = scan_directory(temp_dir.path(), &CheckCache::disabled());
 
         // Empty directory should have one pass result
         assert_eq!(report.total(), 1);
@@ -219,7 +220,7 @@ mod tests {
         fs::write(&file1, "echo $HOME\n").unwrap();
         fs::write(&file2, "export PATH=/Users/john/bin:$PATH\n").unwrap();
 
-        let report = scan_directory(temp_dir.path());
+        let report = scan_directory(temp_dir.path(), &CheckCache::disabled());
 
         // Should have 2 results (one for each file)
         assert_eq!(report.total(), 2);
@@ -228,7 +229,7 @@ mod tests {
 
     #[test]
     fn test_scan_directory_nonexistent() {
-        let report = scan_directory(Path::new("/nonexistent/directory"));
+        let report = scan_directory(Path::new("/nonexistent/directory"), &CheckCache::disabled());
 
         assert!(report.has_errors());
         assert!(report
@@ -0,0 +1,133 @@
+use crate::detect::backend;
+use crate::install::version_manager::VersionManager;
+use crate::install::vm_config;
+use crate::validate::{CheckReport, CheckResult};
+use std::collections::BTreeMap;
+
+/// Validates that configured version manager plugins are added from the expected sources
+pub fn validate_plugins(
+    vm: Option<VersionManager>,
+    expected: &BTreeMap<String, String>,
+) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    if expected.is_empty() {
+        return report;
+    }
+
+    let Some(vm) = vm else {
+        report.add(CheckResult::warn(
+            "Plugins",
+            "Plugins are configured but no version manager is installed",
+            Some(format!(
+                "Install mise with: {}",
+                backend::install_command(&["mise"])
+            )),
+        ));
+        return report;
+    };
+
+    let installed = vm_config::installed_plugins(vm);
+    for (name, url) in expected {
+        report.add(check_plugin(vm, name, url, &installed));
+    }
+
+    report
+}
+
+/// Checks a single plugin against the installed plugin list
+fn check_plugin(
+    vm: VersionManager,
+    name: &str,
+    expected_url: &str,
+    installed: &BTreeMap<String, String>,
+) -> CheckResult {
+    let add_command = match vm {
+        VersionManager::Asdf => format!("asdf plugin add {} {}", name, expected_url),
+        VersionManager::Mise | VersionManager::Rtx => {
+            format!("{} plugins install {} {}", vm.command(), name, expected_url)
+        }
+    };
+
+    match installed.get(name) {
+        None => CheckResult::error(
+            format!("Plugin:{}", name),
+            "Plugin not added",
+            Some(format!("Run: {}", add_command)),
+        ),
+        Some(actual) if vm_config::urls_match(actual, expected_url) => {
+            CheckResult::pass(format!("Plugin:{}", name), format!("Added from {}", actual))
+        }
+        Some(actual) => CheckResult::warn(
+            format!("Plugin:{}", name),
+            format!("Added from {} instead of {}", actual, expected_url),
+            Some(format!(
+                "Re-add: {} plugin remove {} && {}",
+                vm.command(),
+                name,
+                add_command
+            )),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed() -> BTreeMap<String, String> {
+        let mut plugins = BTreeMap::new();
+        plugins.insert(
+            "nodejs".to_string(),
+            "https://github.com/asdf-vm/asdf-nodejs.git".to_string(),
+        );
+        plugins
+    }
+
+    #[test]
+    fn test_check_plugin_matching_url() {
+        let result = check_plugin(
+            VersionManager::Asdf,
+            "nodejs",
+            "https://github.com/asdf-vm/asdf-nodejs",
+            &installed(),
+        );
+        assert!(result.is_pass());
+    }
+
+    #[test]
+    fn test_check_plugin_wrong_url() {
+        let result = check_plugin(
+            VersionManager::Asdf,
+            "nodejs",
+            "https://github.com/fork/asdf-nodejs",
+            &installed(),
+        );
+        assert!(result.is_warn());
+        assert!(result
+            .suggestion()
+            .unwrap()
+            .contains("plugin remove nodejs"));
+    }
+
+    #[test]
+    fn test_check_plugin_missing() {
+        let result = check_plugin(
+            VersionManager::Mise,
+            "python",
+            "https://github.com/asdf-community/asdf-python",
+            &installed(),
+        );
+        assert!(result.is_error());
+        assert!(result
+            .suggestion()
+            .unwrap()
+            .contains("mise plugins install"));
+    }
+
+    #[test]
+    fn test_validate_plugins_empty() {
+        let report = validate_plugins(None, &BTreeMap::new());
+        assert_eq!(report.total(), 0);
+    }
+}
@@ -0,0 +1,474 @@
+use crate::core::command::{CommandRunner, QUICK_TIMEOUT};
+use crate::validate::{CheckReport, CheckResult};
+use std::path::{Path, PathBuf};
+
+/// Number of changed paths listed in the working tree warning
+const CHANGES_SHOWN: usize = 5;
+
+/// Validates the dotfiles repo itself
+///
+/// Checks that it is a git repo with a reachable `origin`, is not a shallow
+/// clone when it has submodules, has its submodules initialized, in sync, and
+/// clean, has no merge or rebase in progress, has a clean working tree, and
+/// is on the expected branch. `expected_branch`
+/// defaults to the remote's default branch. Changes to paths in `generated`
+/// (written by this tool, e.g. `dotfiles adopt`) don't count as dirty.
+pub fn validate_repo(
+    dotfiles_dir: &Path,
+    expected_branch: Option<&str>,
+    generated: &[PathBuf],
+) -> CheckReport {
+    let mut report = CheckReport::new();
+    let dir = dotfiles_dir.display().to_string();
+
+    let Some(git_dir) = git(dotfiles_dir, &["rev-parse", "--absolute-git-dir"]) else {
+        report.add(CheckResult::error(
+            "Repo:git",
+            format!("{} is not a git repository", dir),
+            Some(format!(
+                "Clone it with `dotfiles init --url <repo>`, or run: git -C {} init && git -C {} remote add origin <repo>",
+                dir, dir
+            )),
+        ));
+        return report;
+    };
+    report.add(CheckResult::pass("Repo:git", "Git repository"));
+
+    report.add(check_remote(dotfiles_dir, &dir));
+    if dotfiles_dir.join(".gitmodules").exists() {
+        report.add(check_shallow(
+            git(dotfiles_dir, &["rev-parse", "--is-shallow-repository"]).as_deref() == Some("true"),
+            &dir,
+        ));
+        if let Some(status) = git(dotfiles_dir, &["submodule", "status", "--recursive"]) {
+            let submodules = parse_submodules(&status);
+            let dirty: Vec<&str> = submodules
+                .iter()
+                .filter(|(state, _)| *state == ' ' || *state == '+')
+                .map(|(_, path)| path.as_str())
+                .filter(|path| {
+                    git(&dotfiles_dir.join(path), &["status", "--porcelain"])
+                        .is_some_and(|status| !status.is_empty())
+                })
+                .collect();
+            report.add(check_submodules(&submodules, &dirty, &dir));
+        }
+    }
+    report.add(check_in_progress(Path::new(&git_dir), &dir));
+
+    if let Some(status) = git(dotfiles_dir, &["status", "--porcelain"]) {
+        report.add(check_worktree(&status, dotfiles_dir, generated, &dir));
+    }
+
+    let branch = git(
+        dotfiles_dir,
+        &["symbolic-ref", "--quiet", "--short", "HEAD"],
+    );
+    let expected = expected_branch.map(String::from).or_else(|| {
+        git(
+            dotfiles_dir,
+            &[
+                "symbolic-ref",
+                "--quiet",
+                "--short",
+                "refs/remotes/origin/HEAD",
+            ],
+        )
+        .map(|head| head.trim_start_matches("origin/").to_string())
+    });
+    report.add(check_branch(branch.as_deref(), expected.as_deref(), &dir));
+
+    report
+}
+
+fn check_remote(dotfiles_dir: &Path, dir: &str) -> CheckResult {
+    let Some(url) = git(dotfiles_dir, &["remote", "get-url", "origin"]) else {
+        return CheckResult::warn(
+            "Repo:remote",
+            "No origin remote; changes can't be pushed or synced",
+            Some(format!("Run: git -C {} remote add origin <repo>", dir)),
+        );
+    };
+
+    let output = CommandRunner::new("git")
+        .args(["-C", dir, "ls-remote", "--exit-code", "origin", "HEAD"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_SSH_COMMAND", "ssh -o BatchMode=yes")
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+
+    match output {
+        Ok(output) if output.success() => {
+            CheckResult::pass("Repo:remote", format!("{} reachable", url))
+        }
+        result => {
+            let reason = result
+                .map(|output| {
+                    output
+                        .stderr
+                        .lines()
+                        .next()
+                        .unwrap_or("")
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_else(|e| e.to_string());
+            let fix = if url.starts_with("git@") || url.starts_with("ssh://") {
+                "Check your SSH key is loaded and authorized: ssh -T git@github.com".to_string()
+            } else {
+                format!(
+                    "Check the URL and your credentials: git -C {} remote -v",
+                    dir
+                )
+            };
+            CheckResult::warn(
+                "Repo:remote",
+                format!("{} unreachable: {}", url, reason),
+                Some(fix),
+            )
+        }
+    }
+}
+
+fn check_shallow(shallow: bool, dir: &str) -> CheckResult {
+    if shallow {
+        CheckResult::warn(
+            "Repo:shallow",
+            "Shallow clone with submodules; submodule commits may be missing",
+            Some(format!(
+                "Run: git -C {} fetch --unshallow && git -C {} submodule update --init --recursive",
+                dir, dir
+            )),
+        )
+    } else {
+        CheckResult::pass("Repo:shallow", "Full clone")
+    }
+}
+
+/// Parses `git submodule status` into (state, path) pairs
+///
+/// The state is `-` for uninitialized, `+` for a checkout that differs from
+/// the recorded commit, `U` for merge conflicts, and a space otherwise.
+fn parse_submodules(status: &str) -> Vec<(char, String)> {
+    status
+        .lines()
+        .filter_map(|line| {
+            let state = line.chars().next()?;
+            let path = line.get(1..)?.split_whitespace().nth(1)?;
+            Some((state, path.to_string()))
+        })
+        .collect()
+}
+
+fn check_submodules(submodules: &[(char, String)], dirty: &[&str], dir: &str) -> CheckResult {
+    let with_state = |wanted: char| -> Vec<&str> {
+        submodules
+            .iter()
+            .filter(|(state, _)| *state == wanted)
+            .map(|(_, path)| path.as_str())
+            .collect()
+    };
+    let uninitialized = with_state('-');
+    let conflicted = with_state('U');
+    let moved = with_state('+');
+
+    if !uninitialized.is_empty() {
+        return CheckResult::warn(
+            "Repo:submodules",
+            format!("Not initialized: {}", uninitialized.join(", ")),
+            Some(format!(
+                "Run: git -C {} submodule update --init --recursive",
+                dir
+            )),
+        );
+    }
+    if !conflicted.is_empty() {
+        return CheckResult::error(
+            "Repo:submodules",
+            format!("Merge conflicts: {}", conflicted.join(", ")),
+            Some(format!(
+                "Pick a commit in each, then run: git -C {} add {}",
+                dir,
+                conflicted.join(" ")
+            )),
+        );
+    }
+    if !dirty.is_empty() {
+        return CheckResult::warn(
+            "Repo:submodules",
+            format!("Uncommitted changes in: {}", dirty.join(", ")),
+            Some("Commit or stash them inside each submodule, then commit the new submodule commit here"),
+        );
+    }
+    if !moved.is_empty() {
+        return CheckResult::warn(
+            "Repo:submodules",
+            format!(
+                "Checked out at a different commit than recorded: {}",
+                moved.join(", ")
+            ),
+            Some(format!(
+                "Commit the new commits with `git -C {} add {}`, or reset with: dotfiles sync",
+                dir,
+                moved.join(" ")
+            )),
+        );
+    }
+    CheckResult::pass(
+        "Repo:submodules",
+        format!("{} submodule(s) up to date", submodules.len()),
+    )
+}
+
+/// Fails when a merge, rebase, or cherry-pick was left unfinished
+fn check_in_progress(git_dir: &Path, dir: &str) -> CheckResult {
+    let in_progress = [
+        ("MERGE_HEAD", "merge", "merge"),
+        ("rebase-merge", "rebase", "rebase"),
+        ("rebase-apply", "rebase", "rebase"),
+        ("CHERRY_PICK_HEAD", "cherry-pick", "cherry-pick"),
+    ]
+    .into_iter()
+    .find(|(marker, _, _)| git_dir.join(marker).exists());
+
+    match in_progress {
+        Some((_, what, command)) => CheckResult::error(
+            "Repo:in progress",
+            format!("A {} is in progress", what),
+            Some(format!(
+                "Resolve conflicts and run: git -C {} {} --continue (or {} --abort)",
+                dir, command, command
+            )),
+        ),
+        None => CheckResult::pass("Repo:in progress", "No merge or rebase in progress"),
+    }
+}
+
+fn check_worktree(
+    status: &str,
+    dotfiles_dir: &Path,
+    generated: &[PathBuf],
+    dir: &str,
+) -> CheckResult {
+    let changed = changed_paths(status);
+    let (ours, others): (Vec<&String>, Vec<&String>) = changed
+        .iter()
+        .partition(|path| generated.iter().any(|g| *g == dotfiles_dir.join(path)));
+
+    if !others.is_empty() {
+        let mut listed: Vec<&str> = others
+            .iter()
+            .take(CHANGES_SHOWN)
+            .map(|p| p.as_str())
+            .collect();
+        if others.len() > CHANGES_SHOWN {
+            listed.push("...");
+        }
+        return CheckResult::warn(
+            "Repo:working tree",
+            format!(
+                "{} uncommitted changes: {}",
+                others.len(),
+                listed.join(", ")
+            ),
+            Some(format!(
+                "Review with `git -C {} status`, then commit or stash them",
+                dir
+            )),
+        );
+    }
+
+    if ours.is_empty() {
+        CheckResult::pass("Repo:working tree", "Clean")
+    } else {
+        CheckResult::pass(
+            "Repo:working tree",
+            format!(
+                "Only changes made by dotfiles ({}); commit them when ready",
+                ours.iter()
+                    .map(|p| p.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    }
+}
+
+/// Returns the paths in `git status --porcelain` output (new names for renames)
+fn changed_paths(status: &str) -> Vec<String> {
+    status
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .map(|path| {
+            let path = path.rsplit(" -> ").next().unwrap_or(path);
+            path.trim_matches('"').trim_end_matches('/').to_string()
+        })
+        .collect()
+}
+
+fn check_branch(branch: Option<&str>, expected: Option<&str>, dir: &str) -> CheckResult {
+    match (branch, expected) {
+        (None, Some(expected)) => CheckResult::warn(
+            "Repo:branch",
+            "Detached HEAD",
+            Some(format!("Run: git -C {} switch {}", dir, expected)),
+        ),
+        (None, None) => CheckResult::warn(
+            "Repo:branch",
+            "Detached HEAD",
+            Some(format!("Run: git -C {} switch <branch>", dir)),
+        ),
+        (Some(branch), Some(expected)) if branch != expected => CheckResult::warn(
+            "Repo:branch",
+            format!("On {}, expected {}", branch, expected),
+            Some(format!(
+                "Run: git -C {} switch {} (or set dotfiles_branch in ~/.dotfiles.conf)",
+                dir, expected
+            )),
+        ),
+        (Some(branch), _) => CheckResult::pass("Repo:branch", format!("On {}", branch)),
+    }
+}
+
+/// Runs a quick git command in `dir`, returning trimmed stdout on success
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = CommandRunner::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .timeout(QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+        .ok()?;
+    output
+        .success()
+        .then(|| output.stdout.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn init_repo(dir: &Path) {
+        for args in [
+            vec!["init", "-q", "-b", "main"],
+            vec!["config", "user.email", "me@example.com"],
+            vec!["config", "user.name", "Me"],
+        ] {
+            git(dir, &args).unwrap();
+        }
+        fs::write(dir.join(".zshrc"), "").unwrap();
+        git(dir, &["add", "."]).unwrap();
+        git(dir, &["commit", "-q", "-m", "init"]).unwrap();
+    }
+
+    fn find<'a>(report: &'a CheckReport, name: &str) -> &'a CheckResult {
+        report.checks.iter().find(|c| c.name() == name).unwrap()
+    }
+
+    #[test]
+    fn test_not_a_repo() {
+        let temp = TempDir::new().unwrap();
+        let report = validate_repo(temp.path(), None, &[]);
+        assert_eq!(report.total(), 1);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_repo_health() {
+        let temp = TempDir::new().unwrap();
+        init_repo(temp.path());
+        fs::write(temp.path().join(".psqlrc"), "").unwrap();
+
+        let report = validate_repo(temp.path(), Some("main"), &[temp.path().join(".psqlrc")]);
+        assert!(find(&report, "Repo:git").is_pass());
+        assert!(!find(&report, "Repo:remote").is_pass());
+        assert!(find(&report, "Repo:in progress").is_pass());
+        assert!(find(&report, "Repo:working tree").is_pass());
+        assert!(find(&report, "Repo:branch").is_pass());
+
+        fs::write(temp.path().join(".zshrc"), "edited").unwrap();
+        fs::write(temp.path().join(".git/MERGE_HEAD"), "").unwrap();
+        let report = validate_repo(temp.path(), Some("work"), &[]);
+        assert!(find(&report, "Repo:in progress").is_error());
+        let tree = find(&report, "Repo:working tree");
+        assert!(!tree.is_pass());
+        assert!(tree.message().contains("2 uncommitted changes"));
+        assert!(find(&report, "Repo:branch")
+            .suggestion()
+            .unwrap()
+            .contains("switch work"));
+    }
+
+    #[test]
+    fn test_check_submodules() {
+        let status = "-3f1a2b4c nvim\n+9e8d7c6b tmux/plugins/tpm (v3.1.0-2-g9e8d7c6)\n 1a2b3c4d zsh/pure (heads/main)\n";
+        let submodules = parse_submodules(status);
+        assert_eq!(
+            submodules,
+            vec![
+                ('-', "nvim".to_string()),
+                ('+', "tmux/plugins/tpm".to_string()),
+                (' ', "zsh/pure".to_string()),
+            ]
+        );
+
+        let result = check_submodules(&submodules, &[], "~/dotfiles");
+        assert!(result.message().contains("Not initialized: nvim"));
+        let result = check_submodules(&submodules[1..], &["zsh/pure"], "~/dotfiles");
+        assert!(result.message().contains("zsh/pure"));
+        let result = check_submodules(&submodules[1..], &[], "~/dotfiles");
+        assert!(result.message().contains("tmux/plugins/tpm"));
+        assert!(check_submodules(&submodules[2..], &[], "~/dotfiles").is_pass());
+        assert!(check_submodules(&[('U', "nvim".to_string())], &[], "~/dotfiles").is_error());
+    }
+
+    #[test]
+    fn test_submodule_repo() {
+        let temp = TempDir::new().unwrap();
+        let nvim = temp.path().join("nvim");
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(&nvim).unwrap();
+        fs::create_dir_all(&dotfiles).unwrap();
+        init_repo(&nvim);
+        init_repo(&dotfiles);
+        git(
+            &dotfiles,
+            &[
+                "-c",
+                "protocol.file.allow=always",
+                "submodule",
+                "add",
+                "-q",
+                nvim.to_str().unwrap(),
+                "nvim",
+            ],
+        )
+        .unwrap();
+        git(&dotfiles, &["commit", "-q", "-m", "add nvim"]).unwrap();
+
+        let report = validate_repo(&dotfiles, Some("main"), &[]);
+        assert!(find(&report, "Repo:submodules").is_pass());
+
+        fs::write(dotfiles.join("nvim/init.lua"), "vim.o.number = true").unwrap();
+        let report = validate_repo(&dotfiles, Some("main"), &[]);
+        assert!(find(&report, "Repo:submodules")
+            .message()
+            .contains("Uncommitted changes in: nvim"));
+
+        git(&dotfiles, &["submodule", "deinit", "-q", "-f", "nvim"]).unwrap();
+        let report = validate_repo(&dotfiles, Some("main"), &[]);
+        assert!(find(&report, "Repo:submodules")
+            .message()
+            .contains("Not initialized: nvim"));
+    }
+
+    #[test]
+    fn test_changed_paths() {
+        let status = " M .zshrc\n?? nvim/\nR  old.lua -> new.lua\n";
+        assert_eq!(changed_paths(status), vec![".zshrc", "nvim", "new.lua"]);
+    }
+}
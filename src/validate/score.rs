@@ -0,0 +1,148 @@
+use crate::validate::{CheckReport, CheckResult};
+use serde::{Deserialize, Serialize};
+
+/// How many past scores are kept in the state file
+pub const HISTORY_KEPT: usize = 30;
+
+/// Check groups a working machine can't do without
+const CORE_GROUPS: &[&str] = &[
+    "Dotfiles Config",
+    "Essential Package",
+    "Homebrew",
+    "Repo",
+    "Shell Env",
+    "Shell RC",
+    "Shim",
+    "Sources",
+    "Symlink",
+    "Symlinks",
+    "Version Manager",
+];
+
+/// Check groups for nice-to-have tools and suggestions
+const OPTIONAL_GROUPS: &[&str] = &[
+    "Cloud Tools",
+    "Development Tools",
+    "Editor Tools",
+    "Kubernetes Tools",
+    "Login Items",
+    "Paths",
+    "Productivity Tools",
+    "Unmanaged",
+];
+
+/// A doctor run's completeness score, as recorded in the state file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    /// Local time of the doctor run
+    pub at: String,
+    pub score: u8,
+}
+
+/// Rates how completely the machine is set up, from 0 to 100
+///
+/// Checks are grouped by the part of their name before `:` (so fifty
+/// per-file checks count as one group), each group scores the average of
+/// its checks (pass 1, warning ½, error 0), and groups are weighted: core
+/// setup 3, optional tools 1, everything else 2. No checks at all scores 0.
+pub fn score(report: &CheckReport) -> u8 {
+    let mut groups: Vec<(&str, u32, u32)> = Vec::new();
+    for check in &report.checks {
+        let group = check.name().split(':').next().unwrap_or_default();
+        let credit = match check {
+            CheckResult::Pass { .. } => 2,
+            CheckResult::Warn { .. } => 1,
+            CheckResult::Error { .. } => 0,
+        };
+        match groups.iter_mut().find(|(name, _, _)| *name == group) {
+            Some((_, earned, total)) => {
+                *earned += credit;
+                *total += 2;
+            }
+            None => groups.push((group, credit, 2)),
+        }
+    }
+
+    let (earned, possible) =
+        groups
+            .iter()
+            .fold((0.0, 0.0), |(earned, possible), (group, got, total)| {
+                let weight = f64::from(weight(group));
+                (
+                    earned + weight * f64::from(*got) / f64::from(*total),
+                    possible + weight,
+                )
+            });
+    if possible == 0.0 {
+        return 0;
+    }
+    (100.0 * earned / possible).round() as u8
+}
+
+fn weight(group: &str) -> u32 {
+    if CORE_GROUPS.contains(&group) {
+        3
+    } else if OPTIONAL_GROUPS.contains(&group) {
+        1
+    } else {
+        2
+    }
+}
+
+/// Adds a score to the history, dropping the oldest entries past `HISTORY_KEPT`
+pub fn record(history: &mut Vec<ScoreEntry>, at: String, score: u8) {
+    history.push(ScoreEntry { at, score });
+    let excess = history.len().saturating_sub(HISTORY_KEPT);
+    history.drain(..excess);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_weights() {
+        let mut report = CheckReport::new();
+        assert_eq!(score(&report), 0);
+
+        report.add(CheckResult::pass("Essential Package", "ok"));
+        report.add(CheckResult::pass("Cloud Tools", "ok"));
+        assert_eq!(score(&report), 100);
+
+        // A missing essential costs more than missing cloud tools
+        let mut essential_missing = CheckReport::new();
+        essential_missing.add(CheckResult::error(
+            "Essential Package",
+            "missing",
+            None::<String>,
+        ));
+        essential_missing.add(CheckResult::pass("Cloud Tools", "ok"));
+        let mut cloud_missing = CheckReport::new();
+        cloud_missing.add(CheckResult::pass("Essential Package", "ok"));
+        cloud_missing.add(CheckResult::warn("Cloud Tools", "missing", None::<String>));
+        assert_eq!(score(&essential_missing), 25);
+        assert_eq!(score(&cloud_missing), 88);
+
+        // Per-file checks share one group
+        let mut lint = CheckReport::new();
+        lint.add(CheckResult::pass("Repo:git", "ok"));
+        for i in 0..10 {
+            lint.add(CheckResult::error(
+                format!("Lint:{}", i),
+                "bad",
+                None::<String>,
+            ));
+        }
+        assert_eq!(score(&lint), 60);
+    }
+
+    #[test]
+    fn test_record_keeps_recent() {
+        let mut history = Vec::new();
+        for i in 0..HISTORY_KEPT + 5 {
+            record(&mut history, i.to_string(), 50);
+        }
+        assert_eq!(history.len(), HISTORY_KEPT);
+        assert_eq!(history[0].at, "5");
+    }
+}
@@ -0,0 +1,496 @@
+use crate::backup::secret_store::OnePassword;
+use crate::backup::secrets;
+use crate::core::command::{self, CommandRunner};
+use crate::core::walk::RepoWalker;
+use crate::detect::tools;
+use crate::install::repos;
+use crate::validate::{CheckReport, CheckResult, FixAction};
+use regex::Regex;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Shell startup files searched for a line that loads the `.env`
+const RC_FILES: &[&str] = &[
+    ".zshenv",
+    ".zprofile",
+    ".zshrc",
+    ".bash_profile",
+    ".bashrc",
+    ".profile",
+];
+
+/// Files larger than this are not searched for `$VAR` references
+const MAX_SCANNED_SIZE: u64 = 1024 * 1024;
+
+/// Validates that secrets extracted to the repo's `.env` are loaded and used
+///
+/// Checks that a shell startup file or the repo's `.envrc` loads the file,
+/// that `$VAR` references to its keys in the repo resolve once it's loaded,
+/// and that, when nothing loads it, each key is at least referenced
+/// somewhere. Reports nothing if there is no `.env`.
+pub fn validate_env_consumption(home_dir: &Path, dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let env_path = dotfiles_dir.join(".env");
+    let Ok(content) = fs::read_to_string(&env_path) else {
+        return report;
+    };
+    let keys = secrets::env_keys(&content);
+    if keys.is_empty() {
+        return report;
+    }
+
+    let loader = find_loader(home_dir, dotfiles_dir, &env_path);
+    report.add(match &loader {
+        Some(loader) => {
+            CheckResult::pass("Secrets:loaded", format!(".env is loaded by {}", loader))
+        }
+        None => CheckResult::warn(
+            "Secrets:loaded",
+            format!(
+                "{} secret(s) in {} but no shell startup file or .envrc loads it",
+                keys.len(),
+                env_path.display()
+            ),
+            Some(format!(
+                "Add to ~/.zshrc: set -a; source {}; set +a",
+                env_path.display()
+            )),
+        ),
+    });
+
+    let referenced = referenced_names(dotfiles_dir, &keys);
+    if !referenced.is_empty() {
+        let loaded = loader.is_some().then_some(env_path.as_path());
+        report.add(check_resolved(home_dir, loaded, &referenced));
+    }
+
+    if loader.is_none() {
+        let unused: Vec<&str> = keys
+            .iter()
+            .filter(|key| !referenced.contains(*key))
+            .map(String::as_str)
+            .collect();
+        if !unused.is_empty() {
+            report.add(CheckResult::warn(
+                "Secrets:unused",
+                format!(
+                    "extracted but neither loaded nor referenced: {}",
+                    unused.join(", ")
+                ),
+                Some("Load the .env from your shell, or reference them as ${NAME} in the configs they came from"),
+            ));
+        }
+    }
+
+    report
+}
+
+/// Describes what loads the `.env`, or None if nothing does
+fn find_loader(home_dir: &Path, dotfiles_dir: &Path, env_path: &Path) -> Option<String> {
+    for name in RC_FILES {
+        let Ok(content) = fs::read_to_string(home_dir.join(name)) else {
+            continue;
+        };
+        if sources_env(&content, env_path, home_dir) {
+            return Some(format!("~/{}", name));
+        }
+    }
+
+    // direnv's `dotenv` loads ./.env when entering the repo
+    let envrc = fs::read_to_string(dotfiles_dir.join(".envrc")).unwrap_or_default();
+    envrc
+        .lines()
+        .map(str::trim)
+        .any(|line| line.starts_with("dotenv"))
+        .then(|| "direnv (.envrc)".to_string())
+}
+
+/// Returns true if a non-comment line sources `env_path`, however it spells the path
+fn sources_env(content: &str, env_path: &Path, home_dir: &Path) -> bool {
+    let mut spellings = vec![env_path.display().to_string()];
+    if let Ok(rel) = env_path.strip_prefix(home_dir) {
+        for prefix in ["~", "$HOME", "${HOME}"] {
+            spellings.push(format!("{}/{}", prefix, rel.display()));
+        }
+    }
+
+    content.lines().map(str::trim).any(|line| {
+        !line.starts_with('#')
+            && (line.contains("source ") || line.starts_with(". ") || line.contains("; . "))
+            && spellings.iter().any(|path| line.contains(path.as_str()))
+    })
+}
+
+/// Returns the `.env` keys referenced as `$NAME` or `${NAME}` in repo files
+fn referenced_names(dotfiles_dir: &Path, keys: &[String]) -> BTreeSet<String> {
+    let pattern = Regex::new(r"\$\{?([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    let mut referenced = BTreeSet::new();
+
+    for path in RepoWalker::new(dotfiles_dir).files().unwrap_or_default() {
+        if path.file_name().is_some_and(|name| name == ".env") {
+            continue;
+        }
+        if fs::metadata(&path).map_or(true, |meta| meta.len() > MAX_SCANNED_SIZE) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for captures in pattern.captures_iter(&content) {
+            let name = &captures[1];
+            if keys.iter().any(|key| key == name) {
+                referenced.insert(name.to_string());
+            }
+        }
+    }
+
+    referenced
+}
+
+/// Checks that referenced keys are set once the `.env` is loaded as the shell loads it
+///
+/// Runs a non-interactive `sh` (found on PATH) that exports the file's
+/// assignments, when something loads it, and prints the environment; no
+/// startup files are read, so nothing else in them runs.
+fn check_resolved(
+    home_dir: &Path,
+    env_path: Option<&Path>,
+    referenced: &BTreeSet<String>,
+) -> CheckResult {
+    let script = if env_path.is_some() {
+        r#"set -a; . "$DOTFILES_ENV_FILE"; set +a; exec env"#
+    } else {
+        "exec env"
+    };
+    let mut runner = CommandRunner::new("sh")
+        .args(["-c", script])
+        .env("HOME", home_dir);
+    if let Some(env_path) = env_path {
+        // Passed through the environment, so the path needs no quoting
+        runner = runner.env("DOTFILES_ENV_FILE", env_path);
+    }
+    let output = runner
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output();
+    let Ok(output) = output else {
+        return CheckResult::warn(
+            "Secrets:resolved",
+            "Could not run sh to load the .env",
+            None::<String>,
+        );
+    };
+
+    let set: BTreeSet<&str> = output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+        .collect();
+    unresolved_result(referenced, &set)
+}
+
+fn unresolved_result(referenced: &BTreeSet<String>, set: &BTreeSet<&str>) -> CheckResult {
+    let missing: Vec<&str> = referenced
+        .iter()
+        .map(String::as_str)
+        .filter(|key| !set.contains(key))
+        .collect();
+    if missing.is_empty() {
+        CheckResult::pass(
+            "Secrets:resolved",
+            format!(
+                "{} referenced secret(s) are set once the .env is loaded",
+                referenced.len()
+            ),
+        )
+    } else {
+        CheckResult::warn(
+            "Secrets:resolved",
+            format!(
+                "referenced in the repo but unset once the .env is loaded: {}",
+                missing.join(", ")
+            ),
+            Some("Load the repo's .env from your shell startup files"),
+        )
+    }
+}
+
+/// Validates that `op://` references in the repo resolve through the 1Password CLI
+///
+/// Reports nothing if the repo references no 1Password items.
+pub fn validate_op_references(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let references = op_references(dotfiles_dir);
+    if references.is_empty() {
+        return report;
+    }
+
+    if !tools::is_installed("op") {
+        report.add(CheckResult::error(
+            "Secrets:1Password",
+            format!(
+                "{} op:// reference(s) in the repo but op is not installed",
+                references.len()
+            ),
+            Some("Run: brew install 1password-cli"),
+        ));
+        return report;
+    }
+    if !OnePassword::is_signed_in() {
+        report.add(CheckResult::error(
+            "Secrets:1Password",
+            "op is not signed in",
+            Some("Run: eval $(op signin), or turn on the 1Password app's CLI integration"),
+        ));
+        return report;
+    }
+
+    let unresolved: Vec<&str> = references
+        .iter()
+        .map(String::as_str)
+        .filter(|reference| !OnePassword::resolves(reference))
+        .collect();
+    report.add(if unresolved.is_empty() {
+        CheckResult::pass(
+            "Secrets:1Password",
+            format!("{} op:// reference(s) resolve", references.len()),
+        )
+    } else {
+        CheckResult::error(
+            "Secrets:1Password",
+            format!("unresolved: {}", unresolved.join(", ")),
+            Some("Create the missing items, or fix the vault, item, and field names"),
+        )
+    });
+    report
+}
+
+/// Errors on `.env` files committed to the repo's git history
+///
+/// A tracked `.env` is pushed with everything else, secrets included, even
+/// after it is added to `.gitignore`. Reports nothing outside a git repo.
+pub fn validate_tracked_env(dotfiles_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    if !repos::is_git_repo(dotfiles_dir) {
+        return report;
+    }
+    let Ok(output) = CommandRunner::new("git")
+        .arg("-C")
+        .arg(dotfiles_dir)
+        .arg("ls-files")
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .output()
+    else {
+        return report;
+    };
+
+    let tracked: Vec<&str> = output
+        .stdout
+        .lines()
+        .filter(|path| {
+            Path::new(path)
+                .file_name()
+                .is_some_and(|name| name == ".env")
+        })
+        .collect();
+    for path in &tracked {
+        report.add(
+            CheckResult::error(
+                format!("Secrets:{}", path),
+                "Tracked by git, so its secrets are committed",
+                Some(format!(
+                    "Run: git rm --cached '{}', add it to .gitignore, and rotate the secrets it holds",
+                    path
+                )),
+            )
+            .with_fix(FixAction::UntrackEnv {
+                repo: dotfiles_dir.to_path_buf(),
+                path: path.to_string(),
+            }),
+        );
+    }
+    if tracked.is_empty() {
+        report.add(CheckResult::pass(
+            "Secrets:tracked .env",
+            "No .env files are tracked by git",
+        ));
+    }
+    report
+}
+
+/// Removes a tracked `.env` from the index and lists it in its directory's `.gitignore`
+///
+/// The file stays on disk; the secrets already committed stay in history.
+pub fn untrack_env(repo: &Path, path: &str) -> crate::error::Result<()> {
+    let file = repo.join(path);
+    if let (Some(dir), Some(name)) = (file.parent(), file.file_name().and_then(|n| n.to_str())) {
+        secrets::ensure_gitignored(dir, &[name])?;
+    }
+    CommandRunner::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rm", "--cached", "--quiet", "--", path])
+        .timeout(command::QUICK_TIMEOUT)
+        .stream(false)
+        .run()?;
+    Ok(())
+}
+
+/// Returns the `op://vault/item/field` references in repo files
+fn op_references(dotfiles_dir: &Path) -> BTreeSet<String> {
+    let pattern = Regex::new(r#"op://[^\s'"`)]+"#).unwrap();
+    let mut references = BTreeSet::new();
+
+    for path in RepoWalker::new(dotfiles_dir).files().unwrap_or_default() {
+        if fs::metadata(&path).map_or(true, |meta| meta.len() > MAX_SCANNED_SIZE) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        references.extend(
+            pattern
+                .find_iter(&content)
+                .map(|found| found.as_str().to_string()),
+        );
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sources_env() {
+        let home = Path::new("/Users/me");
+        let env = Path::new("/Users/me/dotfiles/.env");
+        assert!(sources_env(
+            "set -a; source ~/dotfiles/.env; set +a\n",
+            env,
+            home
+        ));
+        assert!(sources_env(". \"$HOME/dotfiles/.env\"\n", env, home));
+        assert!(!sources_env("# source ~/dotfiles/.env\n", env, home));
+        assert!(!sources_env("source ~/other/.env\n", env, home));
+    }
+
+    #[test]
+    fn test_validate_env_consumption() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path();
+        let dotfiles = home.join("dotfiles");
+        fs::create_dir_all(&dotfiles).unwrap();
+        assert_eq!(validate_env_consumption(home, &dotfiles).total(), 0);
+
+        fs::write(dotfiles.join(".env"), "NPM_TOKEN=abc\nOLD_API_KEY=xyz\n").unwrap();
+        fs::write(
+            dotfiles.join(".npmrc"),
+            "//registry/:_authToken=${NPM_TOKEN}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            referenced_names(&dotfiles, &["NPM_TOKEN".into(), "OLD_API_KEY".into()]),
+            BTreeSet::from(["NPM_TOKEN".to_string()])
+        );
+
+        let report = validate_env_consumption(home, &dotfiles);
+        let unused = report
+            .checks
+            .iter()
+            .find(|result| result.name() == "Secrets:unused")
+            .unwrap();
+        assert!(unused.message().contains("OLD_API_KEY"));
+        assert!(!unused.message().contains("NPM_TOKEN"));
+
+        fs::write(dotfiles.join(".envrc"), "dotenv\n").unwrap();
+        let report = validate_env_consumption(home, &dotfiles);
+        assert!(report.checks.iter().any(|result| result.is_pass()));
+        assert!(!report
+            .checks
+            .iter()
+            .any(|result| result.name() == "Secrets:unused"));
+    }
+
+    #[test]
+    fn test_unresolved_result() {
+        let referenced = BTreeSet::from(["NPM_TOKEN".to_string()]);
+        assert!(unresolved_result(&referenced, &BTreeSet::from(["NPM_TOKEN"])).is_pass());
+        assert!(!unresolved_result(&referenced, &BTreeSet::from(["PATH"])).is_pass());
+
+        let temp = TempDir::new().unwrap();
+        let unusual = BTreeSet::from(["DOTFILES_TEST_SECRET".to_string()]);
+        let env_path = temp.path().join("my dotfiles.env");
+        fs::write(&env_path, "DOTFILES_TEST_SECRET='abc def'\n").unwrap();
+        assert!(check_resolved(temp.path(), Some(&env_path), &unusual).is_pass());
+        assert!(!check_resolved(temp.path(), None, &unusual).is_pass());
+    }
+
+    #[test]
+    fn test_op_references() {
+        let temp = TempDir::new().unwrap();
+        assert!(validate_op_references(temp.path()).checks.is_empty());
+
+        fs::write(
+            temp.path().join("secrets.sh"),
+            "export API_TOKEN=\"$(op read 'op://Private/API_TOKEN/password' 2>/dev/null)\"\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join(".env.tpl"),
+            "GITHUB_TOKEN=op://Work/GitHub/token\n",
+        )
+        .unwrap();
+        assert_eq!(
+            op_references(temp.path()),
+            BTreeSet::from([
+                "op://Private/API_TOKEN/password".to_string(),
+                "op://Work/GitHub/token".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_tracked_env() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        assert_eq!(validate_tracked_env(dir).total(), 0);
+
+        let git = |args: &[&str]| {
+            CommandRunner::new("git")
+                .arg("-C")
+                .arg(dir)
+                .args(args)
+                .stream(false)
+                .run()
+                .unwrap();
+        };
+        git(&["init", "-q"]);
+        fs::write(dir.join(".zshrc"), "").unwrap();
+        git(&["add", ".zshrc"]);
+        let report = validate_tracked_env(dir);
+        assert_eq!(report.total(), 1);
+        assert!(!report.has_errors());
+
+        fs::create_dir(dir.join("zsh")).unwrap();
+        fs::write(dir.join("zsh/.env"), "TOKEN=x\n").unwrap();
+        git(&["add", "zsh/.env"]);
+        let report = validate_tracked_env(dir);
+        assert!(report.has_errors());
+        assert_eq!(report.checks[0].name(), "Secrets:zsh/.env");
+
+        let Some(FixAction::UntrackEnv { repo, path }) = report.checks[0].fix() else {
+            panic!("expected an untrack fix");
+        };
+        untrack_env(repo, path).unwrap();
+        assert!(dir.join("zsh/.env").exists());
+        assert!(fs::read_to_string(dir.join("zsh/.gitignore"))
+            .unwrap()
+            .contains(".env"));
+        assert!(!validate_tracked_env(dir).has_errors());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::validate::{CheckReport, CheckResult};
+use crate::install::shell;
+use crate::validate::{CheckReport, CheckResult, FixAction};
 use std::fs;
 use std::path::Path;
 
@@ -26,6 +27,100 @@ pub fn validate_shell_integration(home_dir: &Path, dotfiles_dir: &Path) -> Check
     report
 }
 
+/// Validates that the managed block in .zshrc exports the expected variables
+pub fn validate_managed_exports(home_dir: &Path, expected: &[(String, String)]) -> CheckReport {
+    let mut report = CheckReport::new();
+    if expected.is_empty() {
+        return report;
+    }
+
+    let zshrc = home_dir.join(".zshrc");
+    let lines = fs::read_to_string(&zshrc)
+        .ok()
+        .and_then(|content| shell::read_managed_block(&content))
+        .unwrap_or_default();
+
+    for (key, value) in expected {
+        report.add(check_export(&lines, key, value));
+    }
+
+    report
+}
+
+fn check_export(lines: &[String], key: &str, value: &str) -> CheckResult {
+    let name = format!("Shell Env:{}", key);
+    let prefix = format!("export {}=", key);
+
+    match lines.iter().find_map(|line| line.strip_prefix(&prefix)) {
+        Some(actual) if unquote(actual) == value => {
+            CheckResult::pass(name, format!("exported as {}", value))
+        }
+        Some(actual) => CheckResult::warn(
+            name,
+            format!("exported as {} (expected {})", actual, value),
+            Some("Run: dotfiles setup to refresh the managed shell block"),
+        ),
+        None => CheckResult::error(
+            name,
+            "not exported in the managed block of .zshrc",
+            Some("Run: dotfiles setup to refresh the managed shell block"),
+        ),
+    }
+}
+
+/// Reverses the quoting applied by `shell::export_line`
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => {
+            let mut unescaped = String::new();
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                if c == '\\' {
+                    if let Some(next) = chars.next() {
+                        unescaped.push(next);
+                    }
+                } else {
+                    unescaped.push(c);
+                }
+            }
+            unescaped
+        }
+        None => value.to_string(),
+    }
+}
+
+/// Validates that the current shell environment matches the managed exports
+pub fn validate_live_env(expected: &[(String, String)]) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for (key, value) in expected {
+        let actual = std::env::var(key).ok();
+        report.add(check_live_env(key, value, actual.as_deref()));
+    }
+
+    report
+}
+
+fn check_live_env(key: &str, expected: &str, actual: Option<&str>) -> CheckResult {
+    let name = format!("Live Env:{}", key);
+
+    match actual {
+        Some(actual) if actual.trim_end_matches('/') == expected.trim_end_matches('/') => {
+            CheckResult::pass(name, format!("set to {}", actual))
+        }
+        Some(actual) => CheckResult::error(
+            name,
+            format!("set to {} but config says {}", actual, expected),
+            Some("Check for a conflicting export in your shell config, then restart your shell"),
+        ),
+        None => CheckResult::warn(
+            name,
+            "not set in the current shell",
+            Some("Restart your shell or run: source ~/.zshrc"),
+        ),
+    }
+}
+
 fn check_script_sourced(shell_rc: &Path, script_path: &Path, script_name: &str) -> CheckResult {
     if !script_path.exists() {
         return CheckResult::warn(
@@ -52,6 +147,11 @@ fn check_script_sourced(shell_rc: &Path, script_path: &Path, script_name: &str)
                     format!("{} not sourced in .zshrc", script_name),
                     Some("Run: dotfiles setup to add source line"),
                 )
+                .with_fix(FixAction::SourceScript {
+                    shell_rc: shell_rc.to_path_buf(),
+                    script: script_path.to_path_buf(),
+                    name: script_name.to_string(),
+                })
             }
         }
         Err(e) => CheckResult::error(
@@ -175,4 +275,65 @@ mod tests {
         assert!(report.checks[0].is_warn());
         assert!(report.checks[0].message().contains(".zshrc not found"));
     }
+
+    #[test]
+    fn test_validate_managed_exports() {
+        let temp = TempDir::new().unwrap();
+        let lines = vec!["export HOMEBREW_NO_ANALYTICS=1".to_string()];
+        fs::write(
+            temp.path().join(".zshrc"),
+            shell::render_managed_block(&lines),
+        )
+        .unwrap();
+
+        let report = validate_managed_exports(
+            temp.path(),
+            &[
+                ("HOMEBREW_NO_ANALYTICS".to_string(), "1".to_string()),
+                ("HOMEBREW_NO_AUTO_UPDATE".to_string(), "1".to_string()),
+            ],
+        );
+
+        assert_eq!(report.checks.len(), 2);
+        assert!(matches!(report.checks[0], CheckResult::Pass { .. }));
+        assert!(matches!(report.checks[1], CheckResult::Error { .. }));
+    }
+
+    #[test]
+    fn test_validate_managed_exports_nothing_expected() {
+        let temp = TempDir::new().unwrap();
+        assert!(validate_managed_exports(temp.path(), &[]).checks.is_empty());
+    }
+
+    #[test]
+    fn test_validate_managed_exports_quoted_path() {
+        let temp = TempDir::new().unwrap();
+        let lines = vec![shell::export_line("XDG_CONFIG_HOME", "/Users/me/My Config")];
+        fs::write(
+            temp.path().join(".zshrc"),
+            shell::render_managed_block(&lines),
+        )
+        .unwrap();
+
+        let report = validate_managed_exports(
+            temp.path(),
+            &[(
+                "XDG_CONFIG_HOME".to_string(),
+                "/Users/me/My Config".to_string(),
+            )],
+        );
+        assert!(report.checks[0].is_pass());
+    }
+
+    #[test]
+    fn test_check_live_env() {
+        assert!(check_live_env(
+            "XDG_CONFIG_HOME",
+            "/home/me/.config",
+            Some("/home/me/.config/")
+        )
+        .is_pass());
+        assert!(check_live_env("XDG_CONFIG_HOME", "/home/me/.config", Some("/tmp/cfg")).is_error());
+        assert!(check_live_env("XDG_CONFIG_HOME", "/home/me/.config", None).is_warn());
+    }
 }
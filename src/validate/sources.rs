@@ -0,0 +1,253 @@
+use crate::backup::bare;
+use crate::core::paths::display_path;
+use crate::validate::{CheckReport, CheckResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Places guides commonly tell people to clone their dotfiles, relative to `$HOME`
+const CANDIDATE_DIRS: &[&str] = &[
+    "dotfiles",
+    ".dotfiles",
+    "Development/dotfiles",
+    "dev/dotfiles",
+    "code/dotfiles",
+    "src/dotfiles",
+    "Projects/dotfiles",
+];
+
+/// A dotfiles checkout and how many links resolve into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    pub path: PathBuf,
+    pub links: usize,
+}
+
+/// Warns when more than one dotfiles checkout exists and links are split between them
+///
+/// Looks for repos in the usual clone locations (plus the configured one),
+/// then follows the symlinks directly in `$HOME` and up to two levels into
+/// `$XDG_CONFIG_HOME` to see which checkout each one actually uses.
+pub fn validate_sources(home_dir: &Path, xdg_config_home: &Path, configured: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let sources = find_sources(home_dir, xdg_config_home, configured);
+    let configured = configured
+        .canonicalize()
+        .unwrap_or_else(|_| configured.to_path_buf());
+
+    if sources.len() < 2 {
+        report.add(CheckResult::pass(
+            "Sources:dotfiles",
+            format!("Only one dotfiles checkout ({})", configured.display()),
+        ));
+        return report;
+    }
+
+    report.add(split_result(&sources, &configured, home_dir));
+    report
+}
+
+/// Reports a bare git repo managing `$HOME` directly (the `config` alias style)
+///
+/// Suggests `dotfiles import-bare` while the configured checkout doesn't
+/// exist yet, and removing the bare repo once it does.
+pub fn validate_bare_repo(home_dir: &Path, configured: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let Some(repo) = bare::detect(home_dir) else {
+        return report;
+    };
+
+    let tracked = repo.tracked().map(|files| files.len()).unwrap_or(0);
+    let git_dir = display_path(&repo.git_dir, home_dir);
+    report.add(if configured.join(".git").exists() {
+        CheckResult::warn(
+            "Sources:bare",
+            format!(
+                "Bare repo {} still tracks {} files in $HOME next to {}",
+                git_dir,
+                tracked,
+                configured.display()
+            ),
+            Some(format!(
+                "Once everything is in {}, remove {} and the `config` alias",
+                configured.display(),
+                git_dir
+            )),
+        )
+    } else {
+        CheckResult::warn(
+            "Sources:bare",
+            format!(
+                "Dotfiles are managed by a bare repo ({}, {} tracked files)",
+                git_dir, tracked
+            ),
+            Some("Run: dotfiles import-bare (clones it and links the files instead)"),
+        )
+    });
+    report
+}
+
+/// Returns every dotfiles checkout found, with the number of links into each
+fn find_sources(home_dir: &Path, xdg_config_home: &Path, configured: &Path) -> Vec<Source> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for dir in CANDIDATE_DIRS
+        .iter()
+        .map(|dir| home_dir.join(dir))
+        .chain(std::iter::once(configured.to_path_buf()))
+    {
+        if !dir.join(".git").exists() {
+            continue;
+        }
+        if let Ok(dir) = dir.canonicalize() {
+            if !paths.contains(&dir) {
+                paths.push(dir);
+            }
+        }
+    }
+
+    let targets = link_targets(home_dir, 1)
+        .into_iter()
+        .chain(link_targets(xdg_config_home, 2))
+        .collect::<Vec<_>>();
+    count_links(&paths, &targets)
+}
+
+/// Pairs each checkout with the number of `targets` inside it
+fn count_links(sources: &[PathBuf], targets: &[PathBuf]) -> Vec<Source> {
+    sources
+        .iter()
+        .map(|path| Source {
+            path: path.clone(),
+            links: targets.iter().filter(|t| t.starts_with(path)).count(),
+        })
+        .collect()
+}
+
+/// Resolves the symlinks under `dir`, descending at most `depth` levels
+fn link_targets(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut targets = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_symlink() {
+            if let Ok(target) = path.canonicalize() {
+                targets.push(target);
+            }
+        } else if depth > 1 && path.is_dir() {
+            targets.extend(link_targets(&path, depth - 1));
+        }
+    }
+    targets
+}
+
+fn split_result(sources: &[Source], configured: &Path, home_dir: &Path) -> CheckResult {
+    let tilde = |path: &Path| display_path(path, home_dir);
+    let listing = sources
+        .iter()
+        .map(|s| format!("{} ({} links)", tilde(&s.path), s.links))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let stale: Vec<&Source> = sources
+        .iter()
+        .filter(|s| s.path != configured && s.links > 0)
+        .collect();
+
+    if stale.is_empty() {
+        let unused: Vec<String> = sources
+            .iter()
+            .filter(|s| s.path != configured)
+            .map(|s| tilde(&s.path))
+            .collect();
+        return CheckResult::warn(
+            "Sources:dotfiles",
+            format!(
+                "{} dotfiles checkouts: {}; only {} is used",
+                sources.len(),
+                listing,
+                tilde(configured)
+            ),
+            Some(format!(
+                "Move anything worth keeping into {}, then delete {}",
+                tilde(configured),
+                unused.join(" and ")
+            )),
+        );
+    }
+
+    let stale_list: Vec<String> = stale.iter().map(|s| tilde(&s.path)).collect();
+    CheckResult::warn(
+        "Sources:dotfiles",
+        format!(
+            "Links are split between {} dotfiles checkouts: {}; the config uses {}",
+            sources.len(),
+            listing,
+            tilde(configured)
+        ),
+        Some(format!(
+            "Compare them (diff -r {} {}), commit anything worth keeping in {}, \
+             remove {}, then run: dotfiles setup",
+            stale_list[0],
+            tilde(configured),
+            tilde(configured),
+            stale_list.join(" and ")
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_sources() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().canonicalize().unwrap();
+        let config_dir = home.join(".config");
+        let current = home.join("Development/dotfiles");
+        let stale = home.join("dotfiles");
+        for repo in [&current, &stale] {
+            fs::create_dir_all(repo.join(".git")).unwrap();
+            fs::create_dir_all(repo.join("nvim")).unwrap();
+            fs::write(repo.join(".zshrc"), "").unwrap();
+        }
+        fs::create_dir_all(&config_dir).unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::symlink;
+            symlink(current.join(".zshrc"), home.join(".zshrc")).unwrap();
+
+            let report = validate_sources(&home, &config_dir, &current);
+            assert_eq!(report.total(), 1);
+            assert!(!report.checks[0].is_pass());
+            assert!(report.checks[0]
+                .message()
+                .contains("only ~/Development/dotfiles is used"));
+
+            symlink(stale.join("nvim"), config_dir.join("nvim")).unwrap();
+            let sources = find_sources(&home, &config_dir, &current);
+            assert_eq!(
+                sources,
+                vec![
+                    Source {
+                        path: stale.clone(),
+                        links: 1
+                    },
+                    Source {
+                        path: current.clone(),
+                        links: 1
+                    },
+                ]
+            );
+            let report = validate_sources(&home, &config_dir, &current);
+            assert!(report.checks[0]
+                .message()
+                .starts_with("Links are split between 2 dotfiles checkouts"));
+        }
+
+        fs::remove_dir_all(&stale).unwrap();
+        assert!(validate_sources(&home, &config_dir, &current).checks[0].is_pass());
+    }
+}
@@ -1,12 +1,21 @@
-use crate::validate::{CheckReport, CheckResult};
-use std::path::Path;
-
-/// Validates symlinks in a directory
-pub fn validate_symlinks(source: &Path, target: &Path) -> CheckReport {
+use crate::core::config::{CriticalSymlink, LinkSeverity};
+use crate::symlink;
+use crate::validate::cache::CheckCache;
+use crate::validate::{CheckReport, CheckResult, FixAction};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Validates symlinks in a directory, expecting `.config` at `config_home` when given
+pub fn validate_symlinks(
+    source: &Path,
+    target: &Path,
+    config_home: Option<&Path>,
+    skipped: &[String],
+) -> CheckReport {
     let mut report = CheckReport::new();
 
     // Use the existing validation function from symlink module
-    match crate::symlink::validate_symlinks(source, target) {
+    match crate::symlink::validate_symlinks(source, target, config_home, skipped) {
         Ok(issues) => {
             if issues.is_empty() {
                 report.add(CheckResult::pass(
@@ -15,11 +24,20 @@ pub fn validate_symlinks(source: &Path, target: &Path) -> CheckReport {
                 ));
             } else {
                 for (path, issue) in issues {
-                    report.add(CheckResult::error(
-                        format!("Symlink:{}", path.file_name().unwrap().to_string_lossy()),
-                        issue.clone(),
+                    let file_name = path.file_name().unwrap();
+                    let mut result = CheckResult::error(
+                        format!("Symlink:{}", file_name.to_string_lossy()),
+                        issue.to_string(),
                         Some(format!("Fix symlink at {:?}", path)),
-                    ));
+                    );
+                    // Anything else in the way is left for the user to move
+                    if issue.is_unlinked() {
+                        result = result.with_fix(FixAction::Relink {
+                            source: source.join(file_name),
+                            target: path,
+                        });
+                    }
+                    report.add(result);
                 }
             }
         }
@@ -35,33 +53,97 @@ pub fn validate_symlinks(source: &Path, target: &Path) -> CheckReport {
     report
 }
 
-/// Validates specific critical symlinks
-pub fn validate_critical_symlinks(home_dir: &Path, dotfiles_dir: &Path) -> CheckReport {
+/// Validates the critical symlinks listed in config
+///
+/// Entries under `.config` are expected below `xdg_config_home`, which is not
+/// necessarily `~/.config`. Links whose source is missing from the repo are
+/// skipped; broken links marked `warning` are reported as warnings.
+pub fn validate_critical_symlinks(
+    home_dir: &Path,
+    dotfiles_dir: &Path,
+    xdg_config_home: &Path,
+    links: &[CriticalSymlink],
+    cache: &CheckCache,
+) -> CheckReport {
     let mut report = CheckReport::new();
 
-    let symlinks = vec![
-        (".config", ".config"),
-        (".zshrc", ".zshrc"),
-        (".gitconfig", ".gitconfig"),
-        (".tmux.conf", ".tmux.conf"),
-        (".tmux", ".tmux"),
-        (".zsh", ".zsh"),
-    ];
-
-    for (link_name, source_name) in symlinks {
-        let link = home_dir.join(link_name);
-        let expected_source = dotfiles_dir.join(source_name);
+    for link in links {
+        let expected_source = dotfiles_dir.join(&link.path);
 
         // Only check if source exists in dotfiles
-        if expected_source.exists() {
-            let result = check_symlink(&link, &expected_source);
-            report.add(result);
+        if !expected_source.exists() {
+            continue;
+        }
+
+        let target = link_location(home_dir, xdg_config_home, &link.path);
+        let result = cache.check("symlink", &target, &[&expected_source], || {
+            check_symlink(&target, &expected_source)
+        });
+        report.add(match link.severity {
+            LinkSeverity::Error => result,
+            LinkSeverity::Warning => as_warning(result),
+        });
+    }
+
+    report
+}
+
+/// Validates the links declared in the `[targets]` config table
+pub fn validate_targets(
+    home_dir: &Path,
+    dotfiles_dir: &Path,
+    targets: &BTreeMap<String, String>,
+    cache: &CheckCache,
+) -> CheckReport {
+    let mut report = CheckReport::new();
+
+    for (source, target) in targets {
+        let expected_source = dotfiles_dir.join(source);
+        if !expected_source.exists() {
+            report.add(CheckResult::warn(
+                format!("Target:{}", source),
+                "Listed in [targets] but not found in dotfiles",
+                Some("Remove the entry or add the file to the repo"),
+            ));
+            continue;
         }
+
+        let target = symlink::expand_target(target, home_dir);
+        report.add(cache.check("symlink", &target, &[&expected_source], || {
+            check_symlink(&target, &expected_source)
+        }));
     }
 
     report
 }
 
+/// Resolves where a critical link lives, mapping `.config` onto `xdg_config_home`
+fn link_location(home_dir: &Path, xdg_config_home: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    match path.strip_prefix(".config") {
+        Ok(rest) if rest.as_os_str().is_empty() => xdg_config_home.to_path_buf(),
+        Ok(rest) => xdg_config_home.join(rest),
+        Err(_) => home_dir.join(path),
+    }
+}
+
+fn as_warning(result: CheckResult) -> CheckResult {
+    match result {
+        CheckResult::Error {
+            name,
+            message,
+            suggestion,
+            fix,
+        } => CheckResult::Warn {
+            name,
+            message,
+            suggestion,
+            fix,
+        },
+        other => other,
+    }
+}
+
 /// Checks if a specific symlink points to the correct location
 pub fn check_symlink(target: &Path, expected_source: &Path) -> CheckResult {
     if !target.exists() {
@@ -75,6 +157,18 @@ pub fn check_symlink(target: &Path, expected_source: &Path) -> CheckResult {
                 "Create symlink: ln -s {:?} {:?}",
                 expected_source, target
             )),
+        )
+        .with_fix(relink(target, expected_source));
+    }
+
+    // A link inside a symlinked directory (e.g. ~/.config/nvim) is fine
+    if !target.is_symlink() && resolves_to(target, expected_source) {
+        return CheckResult::pass(
+            format!(
+                "Symlink:{}",
+                target.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            format!("Linked via parent directory to {:?}", expected_source),
         );
     }
 
@@ -130,13 +224,33 @@ pub fn check_symlink(target: &Path, expected_source: &Path) -> CheckResult {
     }
 }
 
+fn relink(target: &Path, expected_source: &Path) -> FixAction {
+    FixAction::Relink {
+        source: expected_source.to_path_buf(),
+        target: target.to_path_buf(),
+    }
+}
+
+fn resolves_to(target: &Path, expected_source: &Path) -> bool {
+    match (target.canonicalize(), expected_source.canonicalize()) {
+        (Ok(target), Ok(source)) => target == source,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::config::default_critical_symlinks;
 
     #[test]
     fn test_validate_symlinks_nonexistent_source() {
-        let report = validate_symlinks(Path::new("/nonexistent/source"), Path::new("/target"));
+        let report = validate_symlinks(
+            Path::new("/nonexistent/source"),
+            Path::new("/target"),
+            None,
+            &[],
+        );
 
         // Should have one error for nonexistent source
         assert!(report.has_errors());
@@ -146,6 +260,39 @@ mod tests {
             .any(|c| c.message().contains("does not exist")));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_validate_symlinks_fixes_only_unlinked_entries() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("dotfiles");
+        let target = temp.path().join("home");
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        for name in [".zshrc", ".vimrc", ".tmux.conf", ".gitconfig"] {
+            fs::write(source.join(name), name).unwrap();
+        }
+        std::os::unix::fs::symlink(temp.path().join("gone"), target.join(".vimrc")).unwrap();
+        std::os::unix::fs::symlink(source.join(".zshrc"), target.join(".tmux.conf")).unwrap();
+        fs::write(target.join(".gitconfig"), "local").unwrap();
+
+        let report = validate_symlinks(&source, &target, None, &[]);
+        let mut fixed: Vec<_> = report
+            .checks
+            .iter()
+            .filter_map(CheckResult::fix)
+            .map(|fix| match fix {
+                FixAction::Relink { target, .. } => target.clone(),
+                other => panic!("unexpected fix {:?}", other),
+            })
+            .collect();
+        fixed.sort();
+        assert_eq!(fixed, vec![target.join(".vimrc"), target.join(".zshrc")]);
+        assert_eq!(report.checks.len(), 4);
+    }
+
     #[test]
     fn test_check_symlink_nonexistent() {
         let result = check_symlink(Path::new("/nonexistent/target"), Path::new("/some/source"));
@@ -153,6 +300,7 @@ mod tests {
         assert!(result.is_error());
         assert!(result.message().contains("does not exist"));
         assert!(result.suggestion().is_some());
+        assert!(matches!(result.fix(), Some(FixAction::Relink { .. })));
     }
 
     #[test]
@@ -192,6 +340,8 @@ mod tests {
         let result = check_symlink(&target, &source2);
         assert!(result.is_error());
         assert!(result.message().contains("instead of"));
+        // The other link may be deliberate, so it isn't replaced automatically
+        assert!(result.fix().is_none());
     }
 
     #[test]
@@ -211,6 +361,7 @@ mod tests {
         let result = check_symlink(&target, &source);
         assert!(result.is_error());
         assert!(result.message().contains("not a symlink"));
+        assert!(result.fix().is_none());
     }
 
     #[test]
@@ -231,10 +382,16 @@ mod tests {
         fs::write(dotfiles.join(".gitconfig"), "test").unwrap();
 
         // Create symlinks
-        std::os::unix::fs::symlink(&dotfiles.join(".zshrc"), &home.join(".zshrc")).unwrap();
-        std::os::unix::fs::symlink(&dotfiles.join(".gitconfig"), &home.join(".gitconfig")).unwrap();
-
-        let report = validate_critical_symlinks(&home, &dotfiles);
+        std::os::unix::fs::symlink(dotfiles.join(".zshrc"), home.join(".zshrc")).unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".gitconfig"), home.join(".gitconfig")).unwrap();
+
+        let report = validate_critical_symlinks(
+            &home,
+            &dotfiles,
+            &home.join(".config"),
+            &default_critical_symlinks(),
+            &CheckCache::disabled(),
+        );
 
         // Should have checks for the symlinks that exist in dotfiles
         assert!(report.checks.len() >= 2);
@@ -261,9 +418,111 @@ mod tests {
         fs::create_dir(&dotfiles).unwrap();
 
         // Don't create any sources in dotfiles
-        let report = validate_critical_symlinks(&home, &dotfiles);
+        let report = validate_critical_symlinks(
+            &home,
+            &dotfiles,
+            &home.join(".config"),
+            &default_critical_symlinks(),
+            &CheckCache::disabled(),
+        );
 
         // Should have no checks if sources don't exist
         assert_eq!(report.checks.len(), 0);
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_validate_critical_symlinks_custom_xdg() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let dotfiles = temp_dir.path().join("dotfiles");
+        let xdg = home.join(".xdg");
+
+        fs::create_dir(&home).unwrap();
+        fs::create_dir_all(dotfiles.join(".config")).unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".config"), &xdg).unwrap();
+
+        let report = validate_critical_symlinks(
+            &home,
+            &dotfiles,
+            &xdg,
+            &default_critical_symlinks(),
+            &CheckCache::disabled(),
+        );
+
+        assert_eq!(report.checks.len(), 1);
+        assert!(report.checks[0].is_pass());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_validate_critical_symlinks_from_config() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let dotfiles = temp_dir.path().join("dotfiles");
+        fs::create_dir(&home).unwrap();
+        fs::create_dir_all(dotfiles.join(".config/nvim")).unwrap();
+        fs::write(dotfiles.join(".zshrc"), "test").unwrap();
+        fs::write(dotfiles.join(".tmux.conf"), "test").unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".config"), home.join(".config")).unwrap();
+
+        let links = vec![
+            CriticalSymlink::error(".config/nvim"),
+            CriticalSymlink::error(".zshrc"),
+            CriticalSymlink {
+                path: ".tmux.conf".to_string(),
+                severity: LinkSeverity::Warning,
+            },
+        ];
+        let report = validate_critical_symlinks(
+            &home,
+            &dotfiles,
+            &home.join(".config"),
+            &links,
+            &CheckCache::disabled(),
+        );
+
+        assert_eq!(report.checks.len(), 3);
+        // nvim is reachable through the symlinked .config directory
+        assert!(report.checks[0].is_pass());
+        assert!(report.checks[1].is_error());
+        assert!(report.checks[2].is_warn());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_validate_targets() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let home = temp_dir.path().join("home");
+        let dotfiles = temp_dir.path().join("dotfiles");
+        fs::create_dir_all(home.join("Library")).unwrap();
+        fs::create_dir(&dotfiles).unwrap();
+        fs::write(dotfiles.join("karabiner.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(
+            dotfiles.join("karabiner.json"),
+            home.join("Library/karabiner.json"),
+        )
+        .unwrap();
+
+        let targets = BTreeMap::from([
+            (
+                "karabiner.json".to_string(),
+                "~/Library/karabiner.json".to_string(),
+            ),
+            ("missing.json".to_string(), "~/missing.json".to_string()),
+        ]);
+        let report = validate_targets(&home, &dotfiles, &targets, &CheckCache::disabled());
+
+        assert!(report.checks[0].is_pass());
+        assert!(report.checks[1].is_warn());
+    }
 }
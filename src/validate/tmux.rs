@@ -0,0 +1,448 @@
+use crate::core::command::{CommandRunner, QUICK_TIMEOUT};
+use crate::core::paths::display_path;
+use crate::validate::{CheckReport, CheckResult};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Settings listed in a check message before the rest are summarized
+const SHOWN_SETTINGS: usize = 3;
+
+/// What the running tmux server reports about itself
+struct TmuxServer {
+    started: SystemTime,
+    /// Global session, window, and server options
+    options: BTreeMap<String, String>,
+    /// Where TPM installs plugins, if TPM has run
+    plugin_path: Option<PathBuf>,
+}
+
+/// Validates that a running tmux server has the linked config loaded and its plugins installed
+///
+/// Settings from the config are compared with `tmux show-options`; plugins
+/// declared with `set -g @plugin` must be present where TPM installs them.
+/// Reports nothing if there is no tmux config.
+pub fn validate_tmux(home_dir: &Path, config_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    let Some(path) = config_path(home_dir, config_dir) else {
+        return report;
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return report;
+    };
+    let display = display_path(&path, home_dir);
+    let settings = parse_settings(&content);
+    let server = running_server();
+
+    if let Some(server) = &server {
+        let changed = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        report.add(check_loaded(&display, &settings, server, changed));
+    }
+
+    let plugins: Vec<&str> = settings
+        .iter()
+        .filter(|(name, _)| name == "@plugin")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    if !plugins.is_empty() {
+        let plugin_path = server
+            .and_then(|server| server.plugin_path)
+            .unwrap_or_else(|| default_plugin_path(home_dir, config_dir, &path));
+        report.add(check_plugins(&plugins, &plugin_path));
+    }
+
+    report
+}
+
+/// The config tmux reads: `~/.tmux.conf`, or the XDG location
+fn config_path(home_dir: &Path, config_dir: &Path) -> Option<PathBuf> {
+    [
+        home_dir.join(".tmux.conf"),
+        config_dir.join("tmux/tmux.conf"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Where TPM installs plugins when `TMUX_PLUGIN_MANAGER_PATH` isn't set
+fn default_plugin_path(home_dir: &Path, config_dir: &Path, config: &Path) -> PathBuf {
+    if config.starts_with(config_dir) {
+        config_dir.join("tmux/plugins")
+    } else {
+        home_dir.join(".tmux/plugins")
+    }
+}
+
+fn running_server() -> Option<TmuxServer> {
+    let tmux = |args: &[&str]| {
+        CommandRunner::new("tmux")
+            .args(args)
+            .timeout(QUICK_TIMEOUT)
+            .stream(false)
+            .output()
+            .ok()
+            .filter(|output| output.success())
+            .map(|output| output.stdout)
+    };
+
+    // Fails when no server is running
+    let started: u64 = tmux(&["list-sessions", "-F", "#{start_time}"])?
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    let mut options = BTreeMap::new();
+    for scope in ["-g", "-gw", "-s"] {
+        if let Some(stdout) = tmux(&["show-options", scope]) {
+            options.extend(parse_show_options(&stdout));
+        }
+    }
+
+    let plugin_path =
+        tmux(&["show-environment", "-g", "TMUX_PLUGIN_MANAGER_PATH"]).and_then(|stdout| {
+            let (_, value) = stdout.trim().split_once('=')?;
+            Some(PathBuf::from(value))
+        });
+
+    Some(TmuxServer {
+        started: UNIX_EPOCH + Duration::from_secs(started),
+        options,
+        plugin_path,
+    })
+}
+
+/// Global options set in a tmux config, in order, as (name, value)
+///
+/// Covers `set`/`set-option`/`setw`/`set-window-option` with `-g` or `-s`.
+/// Appends, unsets, format expansion, explicit targets, and values using
+/// formats, variables, or `~` are skipped, since what tmux ends up with
+/// can't be read off the line.
+fn parse_settings(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let words = split_words(line);
+            let (command, rest) = words.split_first()?;
+            if !matches!(
+                command.as_str(),
+                "set" | "set-option" | "setw" | "set-window-option"
+            ) {
+                return None;
+            }
+
+            let flags: String = rest
+                .iter()
+                .take_while(|word| word.starts_with('-'))
+                .flat_map(|word| word.chars().skip(1))
+                .collect();
+            if !flags.contains(['g', 's']) || flags.contains(['a', 'u', 'F', 't']) {
+                return None;
+            }
+
+            let mut args = rest.iter().skip_while(|word| word.starts_with('-'));
+            let name = args.next()?.clone();
+            let value = args.cloned().collect::<Vec<_>>().join(" ");
+            // A flag set without a value is toggled, which depends on what came before
+            if value.is_empty()
+                || value.contains("#{")
+                || value.contains('$')
+                || value.starts_with('~')
+            {
+                return None;
+            }
+            Some((name, value))
+        })
+        .collect()
+}
+
+/// Splits a tmux config line into words, honouring quotes and dropping comments
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, '#') if !in_word => break,
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Parses `tmux show-options` output into option values
+fn parse_show_options(stdout: &str) -> BTreeMap<String, String> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once(' ')?;
+            // Array options (e.g. `command-alias[0]`) aren't compared
+            if name.contains('[') {
+                return None;
+            }
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn check_loaded(
+    display: &str,
+    settings: &[(String, String)],
+    server: &TmuxServer,
+    changed: Option<SystemTime>,
+) -> CheckResult {
+    // Later lines override earlier ones, as they do in tmux
+    let expected: BTreeMap<&str, &str> = settings
+        .iter()
+        .filter(|(name, _)| !name.starts_with('@'))
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let differing: Vec<&str> = expected
+        .iter()
+        .filter(|(name, value)| {
+            server
+                .options
+                .get(**name)
+                .is_some_and(|actual| !same_value(value, actual))
+        })
+        .map(|(name, _)| *name)
+        .collect();
+    let stale = changed.is_some_and(|changed| changed > server.started);
+    let suggestion = Some(format!("Run: tmux source-file {}", display));
+
+    if differing.is_empty() {
+        let compared = expected
+            .keys()
+            .filter(|name| server.options.contains_key(**name))
+            .count();
+        return if compared == 0 && stale {
+            CheckResult::warn(
+                "Tmux:config",
+                format!("Running server started before {} last changed", display),
+                suggestion,
+            )
+        } else {
+            CheckResult::pass(
+                "Tmux:config",
+                format!("Running server has {} loaded", display),
+            )
+        };
+    }
+
+    let mut names = differing[..differing.len().min(SHOWN_SETTINGS)].join(", ");
+    if differing.len() > SHOWN_SETTINGS {
+        names.push_str(&format!(" (+{} more)", differing.len() - SHOWN_SETTINGS));
+    }
+    let message = if stale {
+        format!(
+            "Running server started before {} last changed; not in effect: {}",
+            display, names
+        )
+    } else {
+        format!("Running server differs from {}: {}", display, names)
+    };
+    CheckResult::warn("Tmux:config", message, suggestion)
+}
+
+/// Compares a configured value with what tmux reports, as tmux reads it
+///
+/// Flag options report `on`/`off`, but tmux accepts `yes`/`no` and `1`/`0`
+/// for them too, in any case.
+fn same_value(configured: &str, actual: &str) -> bool {
+    let flag = |value: &str| match value.to_ascii_lowercase().as_str() {
+        "on" | "yes" | "1" => Some(true),
+        "off" | "no" | "0" => Some(false),
+        _ => None,
+    };
+    if matches!(actual, "on" | "off") {
+        flag(configured) == flag(actual)
+    } else {
+        configured == actual
+    }
+}
+
+/// The directory TPM installs a plugin like `user/repo` or `user/repo#branch` into
+fn plugin_dir_name(plugin: &str) -> &str {
+    let plugin = plugin.split('#').next().unwrap_or(plugin);
+    let name = plugin.rsplit('/').next().unwrap_or(plugin);
+    name.strip_suffix(".git").unwrap_or(name)
+}
+
+fn check_plugins(plugins: &[&str], plugin_path: &Path) -> CheckResult {
+    let missing: Vec<&str> = plugins
+        .iter()
+        .copied()
+        .filter(|plugin| !plugin_path.join(plugin_dir_name(plugin)).is_dir())
+        .collect();
+    if missing.is_empty() {
+        return CheckResult::pass(
+            "Tmux:plugins",
+            format!("{} plugin(s) installed", plugins.len()),
+        );
+    }
+
+    let tpm = plugin_path.join("tpm");
+    let suggestion = if tpm.is_dir() {
+        format!(
+            "Press prefix + I in tmux, or run: {}",
+            tpm.join("bin/install_plugins").display()
+        )
+    } else {
+        format!(
+            "Install TPM: git clone https://github.com/tmux-plugins/tpm {}, then press prefix + I in tmux",
+            tpm.display()
+        )
+    };
+    CheckResult::warn(
+        "Tmux:plugins",
+        format!("Plugins not installed: {}", missing.join(", ")),
+        Some(suggestion),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn server(options: &[(&str, &str)]) -> TmuxServer {
+        TmuxServer {
+            started: UNIX_EPOCH + Duration::from_secs(1_000),
+            options: options
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            plugin_path: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let content = r##"
+# Options
+set -g mouse on
+set-option -sg escape-time 10  # snappy escape
+setw -g mode-keys vi
+set -g status-right "#H %H:%M"
+set -g @plugin 'tmux-plugins/tpm'
+set -ag terminal-overrides ",xterm*:Tc"
+set -g status-left "#{session_name}"
+set mouse off
+bind r source-file ~/.tmux.conf
+"##;
+        assert_eq!(
+            parse_settings(content),
+            [
+                ("mouse", "on"),
+                ("escape-time", "10"),
+                ("mode-keys", "vi"),
+                ("status-right", "#H %H:%M"),
+                ("@plugin", "tmux-plugins/tpm"),
+            ]
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_show_options() {
+        let options = parse_show_options(
+            "mouse on\nstatus-right \"#H %H:%M\"\ncommand-alias[0] split-pane=split-window\n",
+        );
+        assert_eq!(options["mouse"], "on");
+        assert_eq!(options["status-right"], "#H %H:%M");
+        assert!(!options.contains_key("command-alias[0]"));
+    }
+
+    #[test]
+    fn test_check_loaded() {
+        let settings = parse_settings("set -g mouse on\nset -g base-index 1\n");
+        let before = Some(UNIX_EPOCH + Duration::from_secs(500));
+        let after = Some(UNIX_EPOCH + Duration::from_secs(2_000));
+
+        let loaded = server(&[("mouse", "on"), ("base-index", "1")]);
+        assert!(check_loaded("~/.tmux.conf", &settings, &loaded, after).is_pass());
+
+        let defaults = server(&[("mouse", "off"), ("base-index", "0")]);
+        let result = check_loaded("~/.tmux.conf", &settings, &defaults, after);
+        assert!(result.is_warn());
+        assert!(result.message().contains("started before ~/.tmux.conf"));
+        assert!(result.message().contains("base-index, mouse"));
+        assert_eq!(
+            result.suggestion(),
+            Some("Run: tmux source-file ~/.tmux.conf")
+        );
+
+        let result = check_loaded("~/.tmux.conf", &settings, &defaults, before);
+        assert!(result.message().starts_with("Running server differs"));
+
+        // Nothing comparable, so only the timing is left to go on
+        let empty = server(&[]);
+        assert!(check_loaded("~/.tmux.conf", &settings, &empty, before).is_pass());
+        assert!(check_loaded("~/.tmux.conf", &settings, &empty, after).is_warn());
+        // tmux's other spellings of on and off
+        let synonyms =
+            parse_settings("set -g mouse yes\nsetw -g monitor-activity 0\nset -g status\n");
+        assert_eq!(synonyms.len(), 2);
+        let server = server(&[("mouse", "on"), ("monitor-activity", "off")]);
+        assert!(check_loaded("~/.tmux.conf", &synonyms, &server, after).is_pass());
+        assert!(!same_value("1", "0"));
+    }
+
+    #[test]
+    fn test_check_plugins() {
+        assert_eq!(plugin_dir_name("tmux-plugins/tpm"), "tpm");
+        assert_eq!(
+            plugin_dir_name("https://github.com/user/plugin.git#v1"),
+            "plugin"
+        );
+
+        let temp = TempDir::new().unwrap();
+        let plugins = ["tmux-plugins/tpm", "tmux-plugins/tmux-sensible"];
+        let result = check_plugins(&plugins, temp.path());
+        assert!(result.is_warn());
+        assert!(result.suggestion().unwrap().contains("git clone"));
+
+        fs::create_dir_all(temp.path().join("tpm")).unwrap();
+        let result = check_plugins(&plugins, temp.path());
+        assert_eq!(
+            result.message(),
+            "Plugins not installed: tmux-plugins/tmux-sensible"
+        );
+        assert!(result.suggestion().unwrap().contains("install_plugins"));
+
+        fs::create_dir_all(temp.path().join("tmux-sensible")).unwrap();
+        assert!(check_plugins(&plugins, temp.path()).is_pass());
+    }
+
+    #[test]
+    fn test_validate_tmux_without_config() {
+        let temp = TempDir::new().unwrap();
+        let report = validate_tmux(temp.path(), &temp.path().join(".config"));
+        assert_eq!(report.total(), 0);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::core::paths::display_path;
+use crate::validate::{CheckReport, CheckResult};
+use std::path::Path;
+
+/// Well-known configs that live directly in `$HOME`
+const HOME_CONFIGS: &[&str] = &[
+    ".ripgreprc",
+    ".psqlrc",
+    ".inputrc",
+    ".editorconfig",
+    ".curlrc",
+    ".wgetrc",
+    ".vimrc",
+    ".sqliterc",
+];
+
+/// Well-known configs that live under `$XDG_CONFIG_HOME`
+///
+/// Only files without credentials (e.g. `gh/config.yml`, never `gh/hosts.yml`).
+const XDG_CONFIGS: &[&str] = &[
+    "starship.toml",
+    "gh/config.yml",
+    "alacritty/alacritty.toml",
+    "kitty/kitty.conf",
+    "ghostty/config",
+    "wezterm/wezterm.lua",
+    "bat/config",
+    "lazygit/config.yml",
+    "atuin/config.toml",
+];
+
+/// Lists well-known config files that exist but are not managed by the repo
+///
+/// A file counts as managed when it resolves into `dotfiles_dir` (directly or
+/// through a symlinked parent such as `~/.config`). Symlinks pointing elsewhere
+/// belong to another tool and are left alone.
+pub fn validate_unmanaged(
+    home_dir: &Path,
+    xdg_config_home: &Path,
+    dotfiles_dir: &Path,
+) -> CheckReport {
+    let mut report = CheckReport::new();
+    let dotfiles = dotfiles_dir
+        .canonicalize()
+        .unwrap_or_else(|_| dotfiles_dir.to_path_buf());
+
+    let candidates = HOME_CONFIGS
+        .iter()
+        .map(|name| home_dir.join(name))
+        .chain(XDG_CONFIGS.iter().map(|name| xdg_config_home.join(name)));
+
+    for path in candidates {
+        if is_unmanaged(&path, &dotfiles) {
+            report.add(unmanaged_result(&path, home_dir));
+        }
+    }
+
+    if report.checks.is_empty() {
+        report.add(CheckResult::pass(
+            "Unmanaged",
+            "No well-known configs found outside the dotfiles repo",
+        ));
+    }
+
+    report
+}
+
+fn is_unmanaged(path: &Path, dotfiles: &Path) -> bool {
+    if !path.exists() || path.is_symlink() {
+        return false;
+    }
+
+    path.canonicalize()
+        .map(|resolved| !resolved.starts_with(dotfiles))
+        .unwrap_or(false)
+}
+
+fn unmanaged_result(path: &Path, home_dir: &Path) -> CheckResult {
+    let display = display_path(path, home_dir);
+
+    CheckResult::warn(
+        format!("Unmanaged:{}", display),
+        "Exists but is not tracked in the dotfiles repo",
+        Some(format!("Run: dotfiles adopt {}", display)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_unmanaged() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let dotfiles = temp.path().join("dotfiles");
+        let xdg = home.join(".config");
+        fs::create_dir_all(xdg.join("gh")).unwrap();
+        fs::create_dir_all(&dotfiles).unwrap();
+
+        fs::write(home.join(".psqlrc"), "").unwrap();
+        fs::write(xdg.join("gh/config.yml"), "").unwrap();
+        fs::write(dotfiles.join(".ripgreprc"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dotfiles.join(".ripgreprc"), home.join(".ripgreprc")).unwrap();
+
+        let report = validate_unmanaged(&home, &xdg, &dotfiles);
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name()).collect();
+
+        assert_eq!(
+            names,
+            vec!["Unmanaged:~/.psqlrc", "Unmanaged:~/.config/gh/config.yml"]
+        );
+        assert!(report.checks.iter().all(|c| c.is_warn()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_files_inside_linked_config_dir_are_managed() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let dotfiles = temp.path().join("dotfiles");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(dotfiles.join(".config")).unwrap();
+        fs::write(dotfiles.join(".config/starship.toml"), "").unwrap();
+        std::os::unix::fs::symlink(dotfiles.join(".config"), home.join(".config")).unwrap();
+
+        let report = validate_unmanaged(&home, &home.join(".config"), &dotfiles);
+
+        assert_eq!(report.checks.len(), 1);
+        assert!(report.checks[0].is_pass());
+    }
+}
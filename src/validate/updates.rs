@@ -0,0 +1,68 @@
+use crate::commands::generate::DEFAULT_RELEASE_REPO;
+use crate::core::github;
+use crate::error::DotfilesError;
+use crate::validate::{CheckReport, CheckResult};
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// Validates that this binary is at least as new as the latest published release
+///
+/// Reports nothing when GitHub can't be reached (offline machines shouldn't
+/// warn on every run); running into the API rate limit is a warning that
+/// says when it resets and how to raise it.
+pub fn validate_tool_version(home_dir: &Path) -> CheckReport {
+    let mut report = CheckReport::new();
+    match github::latest_release(home_dir, DEFAULT_RELEASE_REPO) {
+        Ok(latest) => report.add(version_result(env!("CARGO_PKG_VERSION"), &latest)),
+        Err(DotfilesError::RateLimited(message)) => report.add(CheckResult::warn(
+            "Tool version",
+            "Could not check for a newer release",
+            Some(message),
+        )),
+        Err(_) => {}
+    }
+    report
+}
+
+fn version_result(current: &str, latest: &str) -> CheckResult {
+    match compare_versions(current, latest) {
+        Ordering::Less => CheckResult::warn(
+            "Tool version",
+            format!("{} is installed; {} is available", current, latest),
+            Some(format!(
+                "Download it from https://github.com/{}/releases/latest",
+                DEFAULT_RELEASE_REPO
+            )),
+        ),
+        _ => CheckResult::pass("Tool version", format!("{} is up to date", current)),
+    }
+}
+
+/// Compares dotted versions numerically, ignoring a leading `v`
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_result() {
+        assert_eq!(compare_versions("0.9.0", "v0.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("v1.2.0", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.3.0", "v1.2.9"), Ordering::Greater);
+
+        let outdated = version_result("0.9.0", "v0.10.0");
+        assert!(outdated.is_warn());
+        assert!(outdated.message().contains("v0.10.0 is available"));
+        assert!(version_result("1.0.0", "v1.0.0").is_pass());
+    }
+}
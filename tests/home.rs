@@ -0,0 +1,82 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+/// `dotfiles --home <sandbox>` with every XDG base directory pointing at `outside`
+fn sandboxed(sandbox: &TempDir, outside: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("dotfiles").unwrap();
+    cmd.arg("--home")
+        .arg(sandbox.path())
+        .env_remove("DOTFILES_HOME_OVERRIDE");
+    for var in [
+        "XDG_CONFIG_HOME",
+        "XDG_DATA_HOME",
+        "XDG_STATE_HOME",
+        "XDG_CACHE_HOME",
+    ] {
+        cmd.env(var, outside.path().join(var));
+    }
+    cmd
+}
+
+#[test]
+fn home_override_keeps_state_in_the_sandbox() {
+    let sandbox = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+
+    sandboxed(&sandbox, &outside)
+        .args(["checklist", "done", "gh-auth"])
+        .assert()
+        .success();
+
+    let state = sandbox.path().join(".local/state/dotfiles/state.json");
+    assert!(fs::read_to_string(state).unwrap().contains("gh-auth"));
+    assert_eq!(fs::read_dir(outside.path()).unwrap().count(), 0);
+}
+
+#[test]
+#[cfg(unix)]
+fn home_override_exports_xdg_dirs_to_child_processes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let home = sandbox.path().canonicalize().unwrap();
+    fs::create_dir_all(home.join("old")).unwrap();
+    fs::write(home.join("old/.zshrc"), "export EDITOR=nvim\n").unwrap();
+    // A repo hook records the environment it was run with
+    let hook = home.join("dotfiles/hooks/pre-migrate.d/10-env");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(
+        &hook,
+        "#!/bin/sh\nenv | grep '^XDG_' | sort > \"$HOME/hook-env\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+    sandboxed(&sandbox, &outside)
+        .args(["--trust-all", "migrate", "--no-backup", "--target"])
+        .arg(home.join("dotfiles"))
+        .arg(home.join("old"))
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .assert()
+        .success();
+
+    let env = fs::read_to_string(home.join("hook-env")).unwrap();
+    for (var, dir) in [
+        ("XDG_CACHE_HOME", ".cache"),
+        ("XDG_CONFIG_HOME", ".config"),
+        ("XDG_DATA_HOME", ".local/share"),
+        ("XDG_STATE_HOME", ".local/state"),
+    ] {
+        assert!(
+            env.contains(&format!("{}={}\n", var, home.join(dir).display())),
+            "{} not moved into the sandbox:\n{}",
+            var,
+            env
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use assert_cmd::Command;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// `dotfiles --home <home>`, with a git identity so commits work in the sandbox
+fn dotfiles(home: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("dotfiles").unwrap();
+    cmd.arg("--home")
+        .arg(home)
+        .env_remove("DOTFILES_HOME_OVERRIDE")
+        .env_remove("DOTFILES_TARGET_DIR")
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com");
+    cmd
+}
+
+fn git_log(repo: &Path) -> String {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["log", "--oneline"])
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+#[cfg(unix)]
+fn migrate_copies_commits_and_links() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path().join("home");
+    let source = home.join(".dotfiles");
+    fs::create_dir_all(source.join("zsh")).unwrap();
+    fs::write(source.join("zsh/.zshrc"), "export EDITOR=nvim\n").unwrap();
+    std::os::unix::fs::symlink(source.join("zsh/.zshrc"), home.join(".zshrc")).unwrap();
+
+    dotfiles(&home)
+        .args(["migrate", "~/.dotfiles", "--target", "~/dotfiles"])
+        .assert()
+        .success();
+
+    let home = home.canonicalize().unwrap();
+    let target = home.join("dotfiles");
+    assert_eq!(
+        fs::read_to_string(target.join(".zshrc")).unwrap(),
+        "export EDITOR=nvim\n"
+    );
+    assert_eq!(
+        fs::read_link(home.join(".zshrc")).unwrap(),
+        target.join(".zshrc")
+    );
+    assert!(git_log(&target).contains("Migrate dotfiles from"));
+    // The old setup was backed up under the sandboxed home
+    assert!(home.join(".local/state/dotfiles/backups").is_dir());
+}
+
+#[test]
+fn migrate_dry_run_changes_nothing() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let source = home.join("old");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join(".tmux.conf"), "set -g mouse on\n").unwrap();
+
+    dotfiles(home)
+        .args(["migrate", "--dry-run", "--target"])
+        .arg(home.join("dotfiles"))
+        .arg(&source)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Would copy 1 file(s)"));
+
+    assert!(!home.join("dotfiles").exists());
+    assert!(!home.join(".tmux.conf").exists());
+}
+
+#[test]
+fn migrate_missing_source_fails() {
+    let temp = TempDir::new().unwrap();
+    dotfiles(temp.path())
+        .args(["migrate", "--no-backup", "~/nowhere"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("Source directory does not exist"));
+}
+
+#[test]
+#[cfg(unix)]
+fn migrate_redacts_only_when_asked() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    let source = home.join("old");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join(".zshrc"), "export API_TOKEN=secret123\n").unwrap();
+
+    // Without --redact the copy still holds the secret, so nothing is committed
+    dotfiles(home)
+        .args(["migrate", "--no-backup", "--target", "~/dotfiles", "~/old"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("Not committing"));
+    fs::remove_dir_all(home.join("dotfiles")).unwrap();
+
+    dotfiles(home)
+        .args(["migrate", "--no-backup", "--redact", "repo"])
+        .args(["--target", "~/dotfiles", "~/old"])
+        .assert()
+        .success();
+    let target = home.join("dotfiles");
+    assert_eq!(
+        fs::read_to_string(target.join(".zshrc")).unwrap(),
+        "export API_TOKEN=${API_TOKEN}\n"
+    );
+    assert!(fs::read_to_string(target.join(".env"))
+        .unwrap()
+        .contains("API_TOKEN=secret123"));
+    assert!(fs::read_to_string(target.join(".gitignore"))
+        .unwrap()
+        .lines()
+        .any(|line| line == ".env"));
+    assert!(!git_log(&target).is_empty());
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn migrate_secret_store_keychain_needs_macos() {
+    let temp = TempDir::new().unwrap();
+    let home = temp.path();
+    fs::create_dir_all(home.join("old")).unwrap();
+    fs::write(home.join("old/.zshrc"), "export API_TOKEN=secret123\n").unwrap();
+
+    dotfiles(home)
+        .args(["migrate", "--no-backup", "--secret-store", "keychain"])
+        .args(["--target", "~/dotfiles", "~/old"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("Keychain backend needs macOS"));
+    assert!(!home.join("dotfiles/.env").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn migrate_secret_store_from_config_uses_1password_vault() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().unwrap();
+    let home = temp.path().join("home");
+    fs::create_dir_all(home.join("old")).unwrap();
+    fs::write(home.join("old/.zshrc"), "export API_TOKEN=secret123\n").unwrap();
+    fs::write(
+        home.join(".dotfiles.conf"),
+        format!(
+            "dotfiles_dir = \"{}\"\nxdg_config_home = \"{}\"\nlanguage_manager = \"Mise\"\nsymlink_method = \"Manual\"\ninstall_oh_my_zsh = false\n[secrets]\nstore = \"1password\"\nvault = \"Dev\"\n",
+            home.join("dotfiles").display(),
+            home.join(".config").display()
+        ),
+    )
+    .unwrap();
+
+    // A stand-in `op` that has no items yet and records what it was asked to do
+    let bin = temp.path().join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let log = temp.path().join("op.log");
+    fs::write(
+        bin.join("op"),
+        format!(
+            "#!/bin/sh\necho \"$@\" >> '{}'\n[ \"$2\" = get ] && exit 1\nexit 0\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(bin.join("op"), fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    dotfiles(&home)
+        .env("PATH", path)
+        .args(["migrate", "--no-backup", "--redact", "repo", "~/old"])
+        .assert()
+        .success();
+
+    let target = home.join("dotfiles");
+    assert!(!target.join(".env").exists());
+    assert!(fs::read_to_string(target.join("secrets.sh"))
+        .unwrap()
+        .contains("op read 'op://Dev/API_TOKEN/password'"));
+    assert!(fs::read_to_string(&log)
+        .unwrap()
+        .contains("item create --vault Dev"));
+}
+
+#[test]
+#[cfg(unix)]
+fn migrate_skips_hooks_filtered_out_for_this_machine() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = TempDir::new().unwrap();
+    let home = temp.path().join("home");
+    fs::create_dir_all(home.join("old")).unwrap();
+    fs::write(home.join("old/.zshrc"), "export EDITOR=nvim\n").unwrap();
+    fs::write(
+        home.join(".dotfiles.conf"),
+        format!(
+            "dotfiles_dir = \"{}\"\nxdg_config_home = \"{}\"\nlanguage_manager = \"Mise\"\nsymlink_method = \"Manual\"\ninstall_oh_my_zsh = false\n[hooks.when]\n\"pre-migrate.d/10-server\" = \"server\"\n",
+            home.join("dotfiles").display(),
+            home.join(".config").display()
+        ),
+    )
+    .unwrap();
+    let hook = home.join("dotfiles/hooks/pre-migrate.d/10-server");
+    fs::create_dir_all(hook.parent().unwrap()).unwrap();
+    fs::write(&hook, "#!/bin/sh\nexit 1\n").unwrap();
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+
+    dotfiles(&home)
+        .args(["migrate", "--no-backup", "~/old"])
+        .assert()
+        .success();
+    assert!(home.join("dotfiles/.zshrc").exists());
+}